@@ -1,7 +1,7 @@
 local flow = Flow.new("pdf_merge_example")
 
 flow:step("merge", nodes.pdf_merge({
-    files = {
+    sources = {
         "examples/08-extraction/sample1.pdf",
         "examples/08-extraction/sample2.pdf"
     },
@@ -0,0 +1,30 @@
+-- A small flow under test: price an order and route high-value orders
+-- to a VIP discount step.
+local flow = Flow.new("order_pricing")
+
+flow:step("total", function(ctx)
+    local total = 0
+    for _, item in ipairs(ctx.items) do
+        total = total + item.price * item.qty
+    end
+    return { total = total }
+end)
+
+flow:step("check_total", nodes.if_node({
+    condition = "ctx.total > 100",
+    true_route = "vip",
+    false_route = "standard"
+})):depends_on("total")
+
+flow:step("vip_discount", function(ctx)
+    return { discount = ctx.total * 0.1, tier = "vip" }
+end):depends_on("check_total"):route("vip")
+
+flow:step("standard_pricing", function(ctx)
+    return { discount = 0, tier = "standard" }
+end):depends_on("check_total"):route("standard")
+
+return flow
+
+-- Test with:
+--   ironflow test examples/21-testing
@@ -0,0 +1,11 @@
+return {
+    name = "small orders use standard pricing",
+    flow = "order_pricing.lua",
+    context = {
+        items = { { sku = "B-7", price = 5, qty = 3 } }
+    },
+    expect = {
+        ctx = { total = 15, discount = 0, tier = "standard" },
+        tasks = { vip_discount = "skipped" }
+    }
+}
@@ -0,0 +1,17 @@
+-- Fixture: a fixture returns the flow to run, its input context and what
+-- the run should look like afterwards.
+return {
+    name = "orders over 100 get a VIP discount",
+    flow = "order_pricing.lua",
+    context = {
+        items = {
+            { sku = "A-1", price = 60, qty = 2 },
+            { sku = "B-7", price = 5, qty = 1 }
+        }
+    },
+    expect = {
+        status = "success",
+        ctx = { total = 125, discount = 12.5, tier = "vip" },
+        tasks = { vip_discount = "success", standard_pricing = "skipped" }
+    }
+}
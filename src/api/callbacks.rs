@@ -0,0 +1,156 @@
+//! Result callbacks for `POST /flows/run`.
+//!
+//! When a run request carries a `callback_url`, the server POSTs the run's
+//! final outputs (or its error) there once the run finishes, so callers can
+//! fire and forget instead of polling `/runs/{id}`. Deliveries are retried
+//! with exponential backoff and, when `IRONFLOW_CALLBACK_SECRET` is set,
+//! signed with HMAC-SHA256 over the raw body:
+//!
+//! ```text
+//! X-IronFlow-Signature: sha256=<hex digest>
+//! ```
+
+use std::time::Duration;
+
+use hmac::{Hmac, KeyInit, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use tracing::{info, warn};
+
+use crate::engine::types::{Context, RunInfo};
+
+/// Header carrying the body signature.
+pub const SIGNATURE_HEADER: &str = "X-IronFlow-Signature";
+
+const MAX_ATTEMPTS: u32 = 4;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Body POSTed to the callback URL.
+#[derive(Debug, Serialize)]
+pub struct CallbackPayload {
+    pub run_id: String,
+    pub flow_name: String,
+    pub status: String,
+    /// Final context without internal (`_`-prefixed) keys.
+    pub outputs: Context,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl CallbackPayload {
+    /// Project a finished run into a callback body. The error lists the
+    /// failed steps, in name order.
+    pub fn from_run(run: &RunInfo) -> Self {
+        let outputs = run
+            .ctx
+            .iter()
+            .filter(|(k, _)| !k.starts_with('_'))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+
+        let mut failed: Vec<_> = run
+            .tasks
+            .values()
+            .filter(|t| t.status.is_failed())
+            .collect();
+        failed.sort_by(|a, b| a.name.cmp(&b.name));
+        let error = (!failed.is_empty()).then(|| {
+            failed
+                .iter()
+                .map(|t| {
+                    format!(
+                        "Step '{}' failed: {}",
+                        t.name,
+                        t.error.as_deref().unwrap_or("unknown error")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("; ")
+        });
+
+        Self {
+            run_id: run.id.clone(),
+            flow_name: run.flow_name.clone(),
+            status: run.status.to_string(),
+            outputs,
+            error,
+        }
+    }
+
+    /// A run that could not be executed or read back at all.
+    pub fn from_error(run_id: &str, flow_name: &str, error: String) -> Self {
+        Self {
+            run_id: run_id.to_string(),
+            flow_name: flow_name.to_string(),
+            status: "failed".to_string(),
+            outputs: Context::new(),
+            error: Some(error),
+        }
+    }
+}
+
+/// `sha256=<hex>` HMAC of `body` under `secret`.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Signing secret from `IRONFLOW_CALLBACK_SECRET`, if set.
+pub fn callback_secret() -> Option<String> {
+    std::env::var("IRONFLOW_CALLBACK_SECRET")
+        .ok()
+        .filter(|s| !s.is_empty())
+}
+
+/// POST `payload` to `url`, retrying network errors and 5xx/429 responses.
+/// Returns whether the receiver accepted it.
+pub async fn deliver(url: &str, payload: &CallbackPayload, secret: Option<&str>) -> bool {
+    let body = match serde_json::to_vec(payload) {
+        Ok(body) => body,
+        Err(err) => {
+            warn!(run_id = %payload.run_id, error = %err, "Failed to encode run callback");
+            return false;
+        }
+    };
+    let signature = secret.map(|secret| sign(secret, &body));
+    let client = reqwest::Client::new();
+    let mut backoff = INITIAL_BACKOFF;
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let mut request = client
+            .post(url)
+            .timeout(REQUEST_TIMEOUT)
+            .header("content-type", "application/json")
+            .header("X-IronFlow-Run-Id", &payload.run_id)
+            .body(body.clone());
+        if let Some(signature) = &signature {
+            request = request.header(SIGNATURE_HEADER, signature);
+        }
+
+        let retryable = match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                info!(run_id = %payload.run_id, url, attempt, "Delivered run callback");
+                return true;
+            }
+            Ok(response) => {
+                let status = response.status();
+                warn!(run_id = %payload.run_id, url, attempt, %status, "Run callback rejected");
+                status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            Err(err) => {
+                warn!(run_id = %payload.run_id, url, attempt, error = %err, "Run callback failed");
+                true
+            }
+        };
+
+        if !retryable || attempt == MAX_ATTEMPTS {
+            break;
+        }
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+    }
+    false
+}
@@ -15,7 +15,9 @@ pub struct ErrorResponse {
 pub enum AppError {
     BadRequest(String),
     NotFound(String),
+    Unauthorized(String),
     Forbidden(String),
+    UnsupportedMediaType(String),
     Internal(anyhow::Error),
 }
 
@@ -24,7 +26,9 @@ impl IntoResponse for AppError {
         let (status, error, details) = match self {
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg, None),
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg, None),
+            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg, None),
             AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg, None),
+            AppError::UnsupportedMediaType(msg) => (StatusCode::UNSUPPORTED_MEDIA_TYPE, msg, None),
             AppError::Internal(err) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Internal server error".to_string(),
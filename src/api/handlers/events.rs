@@ -1,15 +1,83 @@
+use std::collections::VecDeque;
 use std::sync::Arc;
 use std::time::Duration;
 
 use axum::extract::{Path, Query, State};
 use axum::response::sse::{Event, KeepAlive, Sse};
 use futures_util::Stream;
+use tokio::sync::broadcast;
+
+use crate::engine::{RunEvent, RunEventType};
 
 use super::super::AppState;
 use super::super::errors::AppError;
 use super::types::RunEventsQuery;
 
+const BATCH_LIMIT: usize = 100;
+/// Fallback poll interval for event stores without a live feed.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Where an SSE stream is in a run's event log.
+struct EventCursor {
+    state: Arc<AppState>,
+    run_id: String,
+    after: Option<String>,
+    pending: VecDeque<RunEvent>,
+    live: Option<broadcast::Receiver<RunEvent>>,
+    /// Set once the run has ended; the stream closes when `pending` drains.
+    finished: bool,
+}
+
+impl EventCursor {
+    async fn fetch(&mut self) {
+        let events = self
+            .state
+            .event_store
+            .list_since(&self.run_id, self.after.as_deref(), BATCH_LIMIT)
+            .await
+            .unwrap_or_default();
+        if let Some(last) = events.last() {
+            self.after = Some(last.id.clone());
+        }
+        self.pending.extend(events);
+    }
+
+    /// Sleep until this run publishes something or the poll interval passes.
+    async fn wait(&mut self) {
+        let Some(live) = self.live.as_mut() else {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            return;
+        };
+        let run_id = self.run_id.as_str();
+        let woken = async {
+            loop {
+                match live.recv().await {
+                    Ok(event) if event.run_id == run_id => return true,
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(_)) => return true,
+                    Err(broadcast::error::RecvError::Closed) => return false,
+                }
+            }
+        };
+        tokio::select! {
+            open = woken => if !open { self.live = None },
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+        }
+    }
+
+    async fn run_is_terminal(&self) -> bool {
+        self.state
+            .store
+            .get_run_info(&self.run_id)
+            .await
+            .is_ok_and(|info| info.status.is_terminal())
+    }
+}
+
 /// GET /runs/:id/events
+///
+/// Streams the run's lifecycle events as they are published and closes after
+/// `run_finished` (or once the run is terminal and no events remain).
 pub async fn run_events(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
@@ -21,29 +89,44 @@ pub async fn run_events(
         .await
         .map_err(|_| AppError::NotFound(format!("Run '{}' not found", id)))?;
 
-    const BATCH_LIMIT: usize = 100;
-    let stream_state = (state.event_store.clone(), id, params.after);
-    let stream =
-        futures_util::stream::unfold(stream_state, |(event_store, run_id, after)| async move {
-            let mut tick = tokio::time::interval(Duration::from_secs(1));
-            loop {
-                tick.tick().await;
-                let events = event_store
-                    .list_since(&run_id, after.as_deref(), BATCH_LIMIT)
-                    .await
-                    .unwrap_or_default();
-
-                if let Some(event) = events.into_iter().next() {
-                    let next_after = Some(event.id.clone());
-                    let sse_event = Event::default()
-                        .id(event.id.clone())
-                        .event(event.event_type.as_sse_name())
-                        .json_data(event)
-                        .unwrap_or_else(|_| Event::default().event("event_serialization_error"));
-                    return Some((Ok(sse_event), (event_store, run_id, next_after)));
+    let cursor = EventCursor {
+        live: state.event_store.subscribe(),
+        state,
+        run_id: id,
+        after: params.after,
+        pending: VecDeque::new(),
+        finished: false,
+    };
+    let stream = futures_util::stream::unfold(cursor, |mut cursor| async move {
+        loop {
+            if let Some(event) = cursor.pending.pop_front() {
+                if event.event_type == RunEventType::RunFinished {
+                    cursor.finished = true;
                 }
+                let sse_event = Event::default()
+                    .id(event.id.clone())
+                    .event(event.event_type.as_sse_name())
+                    .json_data(event)
+                    .unwrap_or_else(|_| Event::default().event("event_serialization_error"));
+                return Some((Ok(sse_event), cursor));
+            }
+            if cursor.finished {
+                return None;
+            }
+
+            cursor.fetch().await;
+            if !cursor.pending.is_empty() {
+                continue;
+            }
+            // Nothing new. A terminal run gets one last look, since its
+            // status is stored just before `run_finished` is published.
+            if cursor.run_is_terminal().await {
+                cursor.finished = true;
             }
-        });
+            cursor.wait().await;
+            cursor.fetch().await;
+        }
+    });
 
     Ok(Sse::new(stream).keep_alive(
         KeepAlive::new()
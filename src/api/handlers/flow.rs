@@ -2,20 +2,26 @@ use std::sync::Arc;
 
 use axum::Json;
 use axum::extract::State;
+use axum::http::StatusCode;
 
 use crate::engine::WorkflowEngine;
+use crate::engine::types::RunStatus;
 use crate::lua::LuaRuntime;
 
 use super::super::AppState;
+use super::super::callbacks::{self, CallbackPayload};
 use super::super::errors::AppError;
 use super::helpers::{decode_base64_source, resolve_flow_path};
 use super::types::{RunFlowRequest, RunFlowResponse, ValidateFlowRequest, ValidateResponse};
 
 /// POST /flows/run
+///
+/// Answers with the finished run, or with 202 and the new run ID straight
+/// away when a `callback_url` is given.
 pub async fn run_flow(
     State(state): State<Arc<AppState>>,
     Json(req): Json<RunFlowRequest>,
-) -> Result<Json<RunFlowResponse>, AppError> {
+) -> Result<(StatusCode, Json<RunFlowResponse>), AppError> {
     let source_count = [
         req.source.is_some(),
         req.source_base64.is_some(),
@@ -36,7 +42,23 @@ pub async fn run_flow(
         ));
     }
 
-    let flow = if let Some(source) = &req.source {
+    if req.timeout_s.is_some_and(|t| !t.is_finite() || t <= 0.0) {
+        return Err(AppError::BadRequest(
+            "'timeout_s' must be a positive number of seconds".to_string(),
+        ));
+    }
+
+    if let Some(url) = &req.callback_url {
+        let parsed = reqwest::Url::parse(url)
+            .map_err(|e| AppError::BadRequest(format!("Invalid 'callback_url': {}", e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(AppError::BadRequest(
+                "'callback_url' must be an http or https URL".to_string(),
+            ));
+        }
+    }
+
+    let mut flow = if let Some(source) = &req.source {
         LuaRuntime::load_flow_from_string(source, &state.registry)
             .map_err(|e| AppError::BadRequest(format!("Failed to parse flow: {:#}", e)))?
     } else if let Some(b64) = &req.source_base64 {
@@ -50,6 +72,10 @@ pub async fn run_flow(
             .map_err(|e| AppError::BadRequest(format!("Failed to load flow: {:#}", e)))?
     };
 
+    if req.timeout_s.is_some() {
+        flow.timeout_s = req.timeout_s;
+    }
+
     let mut initial_ctx = req.context.unwrap_or_default();
     let flow_name = flow.name.clone();
 
@@ -75,16 +101,77 @@ pub async fn run_flow(
         state.store.clone(),
         state.event_store.clone(),
         state.max_concurrent_tasks,
-    );
-    let run_id = engine.execute(&flow, initial_ctx).await?;
+    )
+    .with_concurrency_limits(state.concurrency_limits.clone());
+    let run_id = uuid::Uuid::new_v4().to_string();
+
+    // With a callback the caller is not kept waiting: the run goes to the
+    // background and the result arrives at `callback_url` when it ends. The
+    // dedupe key is claimed first, so a duplicate request gets the earlier
+    // run's current state instead of an ID that never runs, and no callback.
+    if let Some(url) = req.callback_url {
+        let owner = engine.claim_run_id(&flow, &initial_ctx, &run_id).await?;
+        if owner != run_id {
+            let run_info = state.store.get_run_info(&owner).await?;
+            return Ok((
+                StatusCode::OK,
+                Json(RunFlowResponse {
+                    run_id: run_info.id,
+                    flow_name,
+                    status: run_info.status.to_string(),
+                }),
+            ));
+        }
 
+        let accepted = RunFlowResponse {
+            run_id: run_id.clone(),
+            flow_name: flow_name.clone(),
+            status: RunStatus::Pending.to_string(),
+        };
+        // Tracked until the callback is delivered, so a graceful shutdown
+        // waits for both.
+        let active = state.active_runs.track(&run_id);
+        tokio::spawn(async move {
+            let executed = engine
+                .execute_with_id_and_cancel(
+                    &flow,
+                    initial_ctx,
+                    run_id.clone(),
+                    active.cancel_token(),
+                )
+                .await;
+            let payload = match executed {
+                Ok(run_id) => match state.store.get_run_info(&run_id).await {
+                    Ok(info) => CallbackPayload::from_run(&info),
+                    Err(err) => {
+                        CallbackPayload::from_error(&run_id, &flow_name, format!("{:#}", err))
+                    }
+                },
+                Err(err) => CallbackPayload::from_error(&run_id, &flow_name, format!("{:#}", err)),
+            };
+            callbacks::deliver(&url, &payload, callbacks::callback_secret().as_deref()).await;
+            drop(active);
+        });
+        return Ok((StatusCode::ACCEPTED, Json(accepted)));
+    }
+
+    // A deduplicated flow may hand back an earlier run's ID.
+    let run_id = {
+        let active = state.active_runs.track(&run_id);
+        engine
+            .execute_with_id_and_cancel(&flow, initial_ctx, run_id.clone(), active.cancel_token())
+            .await?
+    };
     let run_info = state.store.get_run_info(&run_id).await?;
 
-    Ok(Json(RunFlowResponse {
-        run_id,
-        flow_name,
-        status: run_info.status.to_string(),
-    }))
+    Ok((
+        StatusCode::OK,
+        Json(RunFlowResponse {
+            run_id: run_info.id,
+            flow_name,
+            status: run_info.status.to_string(),
+        }),
+    ))
 }
 
 /// POST /flows/validate
@@ -140,11 +227,18 @@ pub async fn validate_flow(
             // Validate DAG (dependencies + cycle detection)
             errors.extend(flow.validate_dag());
 
+            let warnings = if errors.is_empty() {
+                flow.context_key_warnings(&state.registry)
+            } else {
+                Vec::new()
+            };
+
             Ok(Json(ValidateResponse {
                 valid: errors.is_empty(),
                 flow_name: Some(flow.name),
                 steps: Some(flow.steps.len()),
                 errors,
+                warnings,
             }))
         }
         Err(e) => Ok(Json(ValidateResponse {
@@ -152,6 +246,7 @@ pub async fn validate_flow(
             flow_name: None,
             steps: None,
             errors: vec![format!("{:#}", e)],
+            warnings: Vec::new(),
         })),
     }
 }
@@ -3,6 +3,7 @@ mod flow;
 mod helpers;
 mod nodes;
 mod runs;
+mod stats;
 mod types;
 mod webhooks;
 
@@ -10,12 +11,14 @@ mod webhooks;
 pub use events::run_events;
 pub use flow::{run_flow, validate_flow};
 pub use helpers::resolve_flow_path;
-pub use nodes::{health, list_nodes};
-pub use runs::{delete_run, get_run, list_runs};
+pub use nodes::{health, list_nodes, livez};
+pub use runs::{cancel_run, delete_run, get_run, list_runs};
+pub use stats::flow_stats;
 pub use webhooks::run_webhook;
 
 // Re-export shared request/response types.
 pub use types::{
-    DEFAULT_LIST_RUNS_LIMIT, HealthResponse, ListRunsQuery, MAX_LIST_RUNS_LIMIT, NodeInfo,
-    RunEventsQuery, RunFlowRequest, RunFlowResponse, ValidateFlowRequest, ValidateResponse,
+    DEFAULT_LIST_RUNS_LIMIT, DEFAULT_STATS_WINDOW, FlowStatsQuery, HealthResponse, ListRunsQuery,
+    MAX_LIST_RUNS_LIMIT, MAX_STATS_WINDOW_DAYS, NodeInfo, RunEventsQuery, RunFlowRequest,
+    RunFlowResponse, RunSort, ValidateFlowRequest, ValidateResponse,
 };
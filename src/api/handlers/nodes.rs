@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use axum::Json;
 use axum::extract::State;
+use axum::http::StatusCode;
 
 use super::super::AppState;
 use super::types::{HealthResponse, NodeInfo};
@@ -12,9 +13,10 @@ pub async fn list_nodes(State(state): State<Arc<AppState>>) -> Json<serde_json::
         .registry
         .list()
         .iter()
-        .map(|(name, desc)| NodeInfo {
+        .map(|(name, desc, pure)| NodeInfo {
             node_type: name.to_string(),
             description: desc.to_string(),
+            pure: *pure,
         })
         .collect();
 
@@ -26,9 +28,36 @@ pub async fn list_nodes(State(state): State<Arc<AppState>>) -> Json<serde_json::
 }
 
 /// GET /health
-pub async fn health() -> Json<HealthResponse> {
-    Json(HealthResponse {
-        status: "ok".to_string(),
-        version: env!("CARGO_PKG_VERSION").to_string(),
-    })
+///
+/// Readiness probe: returns 503 when the state store fails its ping.
+pub async fn health(State(state): State<Arc<AppState>>) -> (StatusCode, Json<HealthResponse>) {
+    let store_result = state.store.ping().await;
+    let (code, status, store, store_error) = match store_result {
+        Ok(()) => (StatusCode::OK, "ok", "ok", None),
+        Err(e) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "unavailable",
+            "unavailable",
+            Some(format!("{:#}", e)),
+        ),
+    };
+
+    (
+        code,
+        Json(HealthResponse {
+            status: status.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            uptime_s: state.started_at.elapsed().as_secs(),
+            active_runs: state.active_runs.len(),
+            store: store.to_string(),
+            store_error,
+        }),
+    )
+}
+
+/// GET /livez
+///
+/// Liveness probe: succeeds whenever the process can serve requests.
+pub async fn livez() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok" }))
 }
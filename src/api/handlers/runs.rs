@@ -2,11 +2,14 @@ use std::sync::Arc;
 
 use axum::Json;
 use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+
+use crate::engine::types::RunStatus;
 
 use super::super::AppState;
 use super::super::errors::AppError;
 use super::helpers::parse_status;
-use super::types::{DEFAULT_LIST_RUNS_LIMIT, ListRunsQuery, MAX_LIST_RUNS_LIMIT};
+use super::types::{DEFAULT_LIST_RUNS_LIMIT, ListRunsQuery, MAX_LIST_RUNS_LIMIT, RunSort};
 
 /// GET /runs
 pub async fn list_runs(
@@ -30,7 +33,12 @@ pub async fn list_runs(
     // Default impl still loads full runs under the hood; concrete stores
     // (JSON, Redis) can override `list_run_summaries` for a real win.
     let mut summaries_all = state.store.list_run_summaries(status_filter).await?;
-    summaries_all.sort_by_key(|summary| std::cmp::Reverse(summary.started));
+    match params.sort {
+        RunSort::StartedAsc => summaries_all.sort_by_key(|summary| summary.started),
+        RunSort::StartedDesc => {
+            summaries_all.sort_by_key(|summary| std::cmp::Reverse(summary.started))
+        }
+    }
 
     let total_matching = summaries_all.len();
     let page: Vec<&crate::engine::types::RunSummary> =
@@ -55,6 +63,7 @@ pub async fn list_runs(
         "total": total_matching,
         "limit": limit,
         "offset": offset,
+        "sort": params.sort,
         "returned": summaries.len(),
     })))
 }
@@ -91,3 +100,32 @@ pub async fn delete_run(
         "deleted": id,
     })))
 }
+
+/// POST /runs/:id/cancel
+///
+/// Signals the run's cancel token and answers 202 with the status the run
+/// had at that moment; poll `GET /runs/:id` for the final `cancelled`.
+pub async fn cancel_run(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    if !state.active_runs.cancel(&id) {
+        return Err(AppError::NotFound(format!("Run '{}' is not active", id)));
+    }
+
+    let status = state
+        .store
+        .get_run_info(&id)
+        .await
+        .map(|info| info.status.to_string())
+        .unwrap_or_else(|_| RunStatus::Pending.to_string());
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({
+            "run_id": id,
+            "status": status,
+            "cancel_requested": true,
+        })),
+    ))
+}
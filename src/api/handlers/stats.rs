@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use chrono::{Duration, Utc};
+
+use super::super::AppState;
+use super::super::errors::AppError;
+use super::types::{DEFAULT_STATS_WINDOW, FlowStatsQuery, MAX_STATS_WINDOW_DAYS};
+
+/// GET /flows/{name}/stats
+pub async fn flow_stats(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Query(params): Query<FlowStatsQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let window = params
+        .window
+        .unwrap_or_else(|| DEFAULT_STATS_WINDOW.to_string());
+    let span = parse_window(&window).map_err(AppError::BadRequest)?;
+    let since = Utc::now() - span;
+
+    let days = state.store.flow_daily_stats(&name, since).await?;
+
+    let runs: usize = days.iter().map(|d| d.runs).sum();
+    let success: usize = days.iter().map(|d| d.success).sum();
+    let failed: usize = days.iter().map(|d| d.failed).sum();
+    let success_rate = (success + failed > 0).then(|| success as f64 / (success + failed) as f64);
+
+    Ok(Json(serde_json::json!({
+        "flow_name": name,
+        "window": window,
+        "since": since,
+        "days": days,
+        "totals": {
+            "runs": runs,
+            "success": success,
+            "failed": failed,
+            "success_rate": success_rate,
+        },
+    })))
+}
+
+/// Parse `<n>d` or `<n>h` into a duration, capped at `MAX_STATS_WINDOW_DAYS`.
+fn parse_window(raw: &str) -> Result<Duration, String> {
+    let invalid = || format!("Invalid window '{raw}': expected e.g. '7d' or '12h'");
+    let trimmed = raw.trim();
+    if trimmed.len() < 2 {
+        return Err(invalid());
+    }
+    let (amount, unit) = trimmed.split_at(trimmed.len() - 1);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    if amount <= 0 {
+        return Err(invalid());
+    }
+    // Compare in hours before building the Duration, which panics on overflow.
+    let hours = match unit {
+        "d" => amount.saturating_mul(24),
+        "h" => amount,
+        _ => return Err(invalid()),
+    };
+    if hours > MAX_STATS_WINDOW_DAYS * 24 {
+        return Err(format!(
+            "Window '{raw}' exceeds the maximum of {MAX_STATS_WINDOW_DAYS}d"
+        ));
+    }
+    Ok(Duration::hours(hours))
+}
@@ -18,6 +18,12 @@ pub struct RunFlowRequest {
     /// Initial context for the workflow.
     #[serde(default)]
     pub context: Option<Context>,
+    /// URL to POST the final outputs (or error) to when the run finishes.
+    #[serde(default)]
+    pub callback_url: Option<String>,
+    /// Run timeout in seconds, overriding the flow's `flow:timeout`.
+    #[serde(default)]
+    pub timeout_s: Option<f64>,
 }
 
 #[derive(Serialize)]
@@ -47,6 +53,9 @@ pub struct ValidateResponse {
     pub steps: Option<usize>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub errors: Vec<String>,
+    /// Context keys a step reads that no upstream step produces.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
 }
 
 #[derive(Deserialize)]
@@ -54,6 +63,17 @@ pub struct ListRunsQuery {
     pub status: Option<String>,
     pub limit: Option<usize>,
     pub offset: Option<usize>,
+    #[serde(default)]
+    pub sort: RunSort,
+}
+
+/// Order of `GET /runs` results.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunSort {
+    StartedAsc,
+    #[default]
+    StartedDesc,
 }
 
 #[derive(Deserialize)]
@@ -61,6 +81,17 @@ pub struct RunEventsQuery {
     pub after: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct FlowStatsQuery {
+    /// Lookback window such as `7d` or `12h`.
+    pub window: Option<String>,
+}
+
+/// Lookback used by `GET /flows/{name}/stats` when `?window` is not supplied.
+pub const DEFAULT_STATS_WINDOW: &str = "7d";
+/// Longest accepted `?window`, in days.
+pub const MAX_STATS_WINDOW_DAYS: i64 = 366;
+
 /// Default page size when `?limit` is not supplied.
 pub const DEFAULT_LIST_RUNS_LIMIT: usize = 50;
 /// Hard cap on `?limit` to prevent one request from loading the whole catalog.
@@ -70,10 +101,18 @@ pub const MAX_LIST_RUNS_LIMIT: usize = 500;
 pub struct NodeInfo {
     pub node_type: String,
     pub description: String,
+    /// True when the node has no side effects (safe to preview or cache).
+    pub pure: bool,
 }
 
 #[derive(Serialize)]
 pub struct HealthResponse {
     pub status: String,
     pub version: String,
+    pub uptime_s: u64,
+    pub active_runs: usize,
+    /// `"ok"` when the state store answered its readiness check.
+    pub store: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store_error: Option<String>,
 }
@@ -1,7 +1,10 @@
 use std::sync::Arc;
 
 use axum::Json;
+use axum::body::Bytes;
 use axum::extract::{Path, State};
+use axum::http::HeaderMap;
+use axum::http::header::CONTENT_TYPE;
 
 use crate::engine::WorkflowEngine;
 use crate::engine::types::Context;
@@ -9,6 +12,7 @@ use crate::lua::LuaRuntime;
 
 use super::super::AppState;
 use super::super::errors::AppError;
+use super::super::webhooks::SignatureError;
 use super::helpers::resolve_flow_path;
 use super::types::RunFlowResponse;
 
@@ -16,20 +20,37 @@ use super::types::RunFlowResponse;
 pub async fn run_webhook(
     State(state): State<Arc<AppState>>,
     Path(name): Path<String>,
-    headers: axum::http::HeaderMap,
-    body: Option<Json<Context>>,
+    headers: HeaderMap,
+    body: Bytes,
 ) -> Result<Json<RunFlowResponse>, AppError> {
-    let flow_file = state
+    let target = state
         .webhooks
         .get(&name)
         .ok_or_else(|| AppError::NotFound(format!("Webhook '{}' not found", name)))?;
 
+    // Verify against the exact bytes received, before parsing anything.
+    if let Some(signature) = target.signature() {
+        signature.verify(&headers, &body).map_err(|err| match err {
+            SignatureError::Rejected(msg) => {
+                AppError::Unauthorized(format!("Webhook '{}': {}", name, msg))
+            }
+            SignatureError::Misconfigured(msg) => {
+                AppError::Internal(anyhow::anyhow!("Webhook '{}': {}", name, msg))
+            }
+        })?;
+    }
+
+    let mut initial_ctx = json_body(&headers, &body)?;
+
+    let resolved = target
+        .resolve(&headers, &initial_ctx)
+        .map_err(|e| AppError::BadRequest(format!("Webhook '{}': {}", name, e)))?;
+    let flow_file = resolved.flow_file;
+
     let path = resolve_flow_path(flow_file, &state)?;
     let flow = LuaRuntime::load_flow(&path, &state.registry)
         .map_err(|e| AppError::BadRequest(format!("Failed to load flow: {:#}", e)))?;
 
-    let mut initial_ctx = body.map(|Json(ctx)| ctx).unwrap_or_default();
-
     // Inject request headers as _headers (lowercase keys)
     let headers_map: serde_json::Map<String, serde_json::Value> = headers
         .iter()
@@ -49,6 +70,12 @@ pub async fn run_webhook(
 
     // Inject webhook name
     initial_ctx.insert("_webhook".to_string(), serde_json::Value::String(name));
+    if let Some(route) = resolved.route {
+        initial_ctx.insert(
+            "_webhook_route".to_string(),
+            serde_json::Value::String(route),
+        );
+    }
     let flow_name = flow.name.clone();
 
     // Inject _flow_dir for subworkflow path resolution
@@ -66,8 +93,16 @@ pub async fn run_webhook(
         state.store.clone(),
         state.event_store.clone(),
         state.max_concurrent_tasks,
-    );
-    let run_id = engine.execute(&flow, initial_ctx).await?;
+    )
+    .with_concurrency_limits(state.concurrency_limits.clone());
+    let run_id = uuid::Uuid::new_v4().to_string();
+    // A deduplicated flow may hand back an earlier run's ID.
+    let run_id = {
+        let active = state.active_runs.track(&run_id);
+        engine
+            .execute_with_id_and_cancel(&flow, initial_ctx, run_id.clone(), active.cancel_token())
+            .await?
+    };
 
     let run_info = state.store.get_run_info(&run_id).await?;
 
@@ -77,3 +112,36 @@ pub async fn run_webhook(
         status: run_info.status.to_string(),
     }))
 }
+
+/// The JSON object body, or an empty context when the request has no body
+/// or no content type. A body sent as anything but JSON is rejected with
+/// 415, and malformed JSON with 400.
+fn json_body(headers: &HeaderMap, body: &[u8]) -> Result<Context, AppError> {
+    let Some(content_type) = headers.get(CONTENT_TYPE) else {
+        return Ok(Context::new());
+    };
+    if body.is_empty() {
+        return Ok(Context::new());
+    }
+    let is_json = content_type
+        .to_str()
+        .ok()
+        .map(|v| {
+            v.split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase()
+        })
+        .is_some_and(|mime| {
+            mime.starts_with("application/")
+                && (mime == "application/json" || mime.ends_with("+json"))
+        });
+    if !is_json {
+        return Err(AppError::UnsupportedMediaType(
+            "Expected request with `Content-Type: application/json`".to_string(),
+        ));
+    }
+    serde_json::from_slice(body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON body: {}", e)))
+}
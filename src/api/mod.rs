@@ -1,10 +1,14 @@
+pub mod callbacks;
 pub mod errors;
 pub mod handlers;
+pub mod shutdown;
+pub mod webhooks;
 
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use axum::Router;
@@ -19,9 +23,12 @@ use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing::{info, warn};
 
+use crate::engine::executor::ConcurrencyLimits;
 use crate::nodes::NodeRegistry;
 use crate::storage::StateStore;
 use crate::storage::event_store::EventStore;
+use shutdown::ActiveRuns;
+use webhooks::WebhookTarget;
 
 /// Shared application state accessible by all handlers.
 pub struct AppState {
@@ -30,8 +37,14 @@ pub struct AppState {
     pub event_store: Arc<dyn EventStore>,
     pub flows_dir: Option<PathBuf>,
     pub max_concurrent_tasks: Option<usize>,
-    /// Webhook name → flow file path mappings from config.
-    pub webhooks: HashMap<String, String>,
+    /// Node type caps shared by every run the server starts.
+    pub concurrency_limits: ConcurrencyLimits,
+    /// Webhook name → flow file (or routing table) from config.
+    pub webhooks: HashMap<String, WebhookTarget>,
+    /// Server start time, reported as uptime by `/health`.
+    pub started_at: Instant,
+    /// Flow runs currently executing through the API.
+    pub active_runs: ActiveRuns,
 }
 
 /// Configuration for the REST API server.
@@ -41,10 +54,13 @@ pub struct ServeOptions {
     pub flows_dir: Option<PathBuf>,
     pub max_body: usize,
     pub max_concurrent_tasks: Option<usize>,
-    pub webhooks: HashMap<String, String>,
+    pub concurrency_limits: HashMap<String, usize>,
+    pub webhooks: HashMap<String, WebhookTarget>,
     pub cors_origins: Option<Vec<String>>,
     pub api_key: Option<String>,
     pub allow_unauthenticated_api: bool,
+    /// How long to wait for in-flight runs after SIGTERM/Ctrl+C.
+    pub shutdown_timeout: Duration,
 }
 
 #[derive(Clone)]
@@ -67,14 +83,28 @@ pub async fn serve(
     options: ServeOptions,
 ) -> Result<()> {
     let registry = Arc::new(NodeRegistry::with_builtins());
+    serve_with_registry(registry, store, event_store, options).await
+}
 
+/// Start the REST API server with a caller-supplied node registry, e.g. one
+/// built with [`NodeRegistry::with_builtins_and`] to include custom nodes.
+pub async fn serve_with_registry(
+    registry: Arc<NodeRegistry>,
+    store: Arc<dyn StateStore>,
+    event_store: Arc<dyn EventStore>,
+    options: ServeOptions,
+) -> Result<()> {
+    let shutdown_timeout = options.shutdown_timeout;
     let state = Arc::new(AppState {
         registry,
         store,
         event_store,
         flows_dir: options.flows_dir,
         max_concurrent_tasks: options.max_concurrent_tasks,
+        concurrency_limits: ConcurrencyLimits::new(&options.concurrency_limits),
         webhooks: options.webhooks,
+        started_at: Instant::now(),
+        active_runs: ActiveRuns::new(),
     });
 
     let auth = build_api_auth(
@@ -83,13 +113,40 @@ pub async fn serve(
         &options.host,
     )?;
 
+    let app = router(state.clone(), auth)
+        .layer(DefaultBodyLimit::max(options.max_body))
+        .layer(TraceLayer::new_for_http())
+        .layer(cors_layer(options.cors_origins)?);
+
+    let addr: SocketAddr = format!("{}:{}", options.host, options.port).parse()?;
+    info!("IronFlow API server listening on {}", addr);
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    shutdown::serve_until(
+        listener,
+        app,
+        state,
+        shutdown_timeout,
+        shutdown::shutdown_signal(),
+    )
+    .await
+}
+
+/// Build the API routes over `state`. With `auth`, every route except the
+/// `/health` and `/livez` probes requires the API key.
+pub fn router(state: Arc<AppState>, auth: Option<ApiAuth>) -> Router {
     let protected_routes = Router::new()
         .route("/flows/run", post(handlers::run_flow))
         .route("/flows/validate", post(handlers::validate_flow))
+        .route("/flows/{name}/stats", get(handlers::flow_stats))
         .route("/runs", get(handlers::list_runs))
         .route("/runs/{id}", get(handlers::get_run))
         .route("/runs/{id}/events", get(handlers::run_events))
         .route("/runs/{id}", delete(handlers::delete_run))
+        .route(
+            "/runs/{id}/cancel",
+            post(handlers::cancel_run).delete(handlers::cancel_run),
+        )
         .route("/nodes", get(handlers::list_nodes))
         .route("/webhooks/{name}", post(handlers::run_webhook));
 
@@ -99,21 +156,11 @@ pub async fn serve(
         protected_routes
     };
 
-    let app = Router::new()
+    Router::new()
         .route("/health", get(handlers::health))
+        .route("/livez", get(handlers::livez))
         .merge(protected_routes)
-        .layer(DefaultBodyLimit::max(options.max_body))
-        .layer(TraceLayer::new_for_http())
-        .layer(cors_layer(options.cors_origins)?)
-        .with_state(state);
-
-    let addr: SocketAddr = format!("{}:{}", options.host, options.port).parse()?;
-    info!("IronFlow API server listening on {}", addr);
-
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
-
-    Ok(())
+        .with_state(state)
 }
 
 /// Build the CORS policy for the API server.
@@ -208,12 +255,23 @@ fn request_has_api_key(headers: &HeaderMap, expected: &str) -> bool {
         .get(axum::http::header::AUTHORIZATION)
         .and_then(|value| value.to_str().ok())
         .and_then(|value| value.strip_prefix("Bearer "))
-        .is_some_and(|token| token == expected);
+        .is_some_and(|token| keys_match(token, expected));
 
     let api_key = headers
         .get("x-api-key")
         .and_then(|value| value.to_str().ok())
-        .is_some_and(|token| token == expected);
+        .is_some_and(|token| keys_match(token, expected));
 
     bearer || api_key
 }
+
+/// Compare keys without short-circuiting on the first differing byte.
+fn keys_match(provided: &str, expected: &str) -> bool {
+    let (provided, expected) = (provided.as_bytes(), expected.as_bytes());
+    provided.len() == expected.len()
+        && provided
+            .iter()
+            .zip(expected)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
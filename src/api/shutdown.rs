@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use axum::Router;
+use tokio::net::TcpListener;
+use tokio::sync::{Notify, oneshot};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::engine::types::RunStatus;
+
+use super::AppState;
+
+/// Default time `serve` waits for in-flight runs after a shutdown signal.
+pub const DEFAULT_SHUTDOWN_TIMEOUT_S: u64 = 30;
+
+/// Run IDs currently executing through the API server, with the token that
+/// cancels each one. Clones share the same set.
+#[derive(Default, Clone)]
+pub struct ActiveRuns {
+    inner: Arc<ActiveRunsInner>,
+}
+
+#[derive(Default)]
+struct ActiveRunsInner {
+    ids: Mutex<HashMap<String, CancellationToken>>,
+    /// Woken whenever a run is removed, for [`ActiveRuns::wait_idle`].
+    removed: Notify,
+}
+
+impl ActiveRuns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.ids.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Snapshot of the run IDs still in flight.
+    pub fn ids(&self) -> Vec<String> {
+        self.inner.ids.lock().unwrap().keys().cloned().collect()
+    }
+
+    /// Register `run_id` as active until the returned guard is dropped. The
+    /// guard owns its handle, so it can move into a spawned task.
+    pub fn track(&self, run_id: &str) -> ActiveRunGuard {
+        let cancel = CancellationToken::new();
+        self.inner
+            .ids
+            .lock()
+            .unwrap()
+            .insert(run_id.to_string(), cancel.clone());
+        ActiveRunGuard {
+            runs: self.clone(),
+            run_id: run_id.to_string(),
+            cancel,
+        }
+    }
+
+    /// Trigger the cancel token of an active run. Returns `false` when no
+    /// run with that ID is executing here.
+    pub fn cancel(&self, run_id: &str) -> bool {
+        match self.inner.ids.lock().unwrap().get(run_id) {
+            Some(cancel) => {
+                cancel.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Resolve once no run is active.
+    pub async fn wait_idle(&self) {
+        loop {
+            let removed = self.inner.removed.notified();
+            tokio::pin!(removed);
+            // Register for the wake-up before checking, so a removal between
+            // the check and the await is not missed.
+            removed.as_mut().enable();
+            if self.is_empty() {
+                return;
+            }
+            removed.await;
+        }
+    }
+}
+
+/// Removes a run from `ActiveRuns` when the work executing it ends.
+pub struct ActiveRunGuard {
+    runs: ActiveRuns,
+    run_id: String,
+    cancel: CancellationToken,
+}
+
+impl ActiveRunGuard {
+    /// Token fired by [`ActiveRuns::cancel`] for this run.
+    pub fn cancel_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+}
+
+impl Drop for ActiveRunGuard {
+    fn drop(&mut self) {
+        self.runs.inner.ids.lock().unwrap().remove(&self.run_id);
+        self.runs.inner.removed.notify_waiters();
+    }
+}
+
+/// Resolve on Ctrl+C or, on Unix, SIGTERM.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!(error = %e, "Failed to listen for Ctrl+C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sig) => {
+                sig.recv().await;
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// Serve `app` until `signal` resolves, then stop accepting connections and
+/// give in-flight runs up to `shutdown_timeout` to finish.
+///
+/// Runs still executing when the timeout expires are marked `stalled` in the
+/// state store so they are not left looking `running` forever.
+pub async fn serve_until<F>(
+    listener: TcpListener,
+    app: Router,
+    state: Arc<AppState>,
+    shutdown_timeout: Duration,
+    signal: F,
+) -> Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let (started_tx, started_rx) = oneshot::channel::<()>();
+    let server = axum::serve(listener, app).with_graceful_shutdown(async move {
+        signal.await;
+        let _ = started_tx.send(());
+    });
+    let server = server.into_future();
+    tokio::pin!(server);
+
+    tokio::select! {
+        result = &mut server => return Ok(result?),
+        _ = started_rx => {}
+    }
+
+    info!(
+        active_runs = state.active_runs.len(),
+        timeout_s = shutdown_timeout.as_secs_f64(),
+        "Shutdown requested; waiting for in-flight runs"
+    );
+
+    // Graceful shutdown resolves once every open connection — and therefore
+    // every synchronous run request — has completed. Runs started with a
+    // `callback_url` answered long ago, so wait for them separately.
+    let drained = tokio::time::timeout(shutdown_timeout, async {
+        (&mut server).await?;
+        state.active_runs.wait_idle().await;
+        anyhow::Ok(())
+    })
+    .await;
+
+    match drained {
+        Ok(result) => {
+            result?;
+            info!("All in-flight runs finished; server stopped");
+        }
+        Err(_) => {
+            let stalled = mark_active_runs_stalled(&state).await;
+            warn!(
+                stalled,
+                "Shutdown timeout reached; marked unfinished runs as stalled"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Persist `stalled` for every run still registered in `ActiveRuns`.
+/// Returns the number of runs updated.
+pub async fn mark_active_runs_stalled(state: &AppState) -> usize {
+    let mut updated = 0;
+    for run_id in state.active_runs.ids() {
+        match state
+            .store
+            .set_run_status(&run_id, RunStatus::Stalled)
+            .await
+        {
+            Ok(()) => updated += 1,
+            Err(e) => warn!(run_id = %run_id, error = %e, "Failed to mark run stalled"),
+        }
+    }
+    updated
+}
@@ -0,0 +1,281 @@
+//! Webhook → flow mappings from `ironflow.yaml`.
+
+use std::collections::HashMap;
+
+use axum::http::HeaderMap;
+use base64::Engine;
+use hmac::{Hmac, KeyInit, Mac};
+use serde::Deserialize;
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+
+use crate::engine::types::Context;
+
+/// What a webhook name runs: a single flow file, or a routing table that
+/// picks the flow from the request.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum WebhookTarget {
+    /// `hello: hello_world.lua`
+    Flow(String),
+    /// `github: { route_by: { header: X-GitHub-Event }, routes: {...}, default: ... }`
+    Routed(WebhookRouting),
+    /// `stripe: { flow: stripe.lua, signature: { secret_env: STRIPE_SECRET } }`
+    Signed(SignedFlow),
+}
+
+/// A single flow file behind signature verification.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignedFlow {
+    pub flow: String,
+    pub signature: WebhookSignature,
+}
+
+/// HMAC verification of the raw request body against a signature header,
+/// as sent by GitHub (`X-Hub-Signature-256: sha256=<hex>`) and similar.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookSignature {
+    /// Header carrying the signature, matched case-insensitively.
+    #[serde(default = "default_signature_header")]
+    pub header: String,
+    /// Shared secret, inline.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Environment variable holding the shared secret.
+    #[serde(default)]
+    pub secret_env: Option<String>,
+    /// `sha256` (default), `sha1` or `sha512`.
+    #[serde(default = "default_signature_algorithm")]
+    pub algorithm: String,
+    /// How the digest is written: `hex` (default) or `base64`.
+    #[serde(default = "default_signature_encoding")]
+    pub encoding: String,
+}
+
+fn default_signature_header() -> String {
+    "X-Hub-Signature-256".to_string()
+}
+
+fn default_signature_algorithm() -> String {
+    "sha256".to_string()
+}
+
+fn default_signature_encoding() -> String {
+    "hex".to_string()
+}
+
+/// Why a request failed signature verification.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignatureError {
+    /// The request's signature is missing or wrong.
+    Rejected(String),
+    /// The webhook's own configuration is unusable.
+    Misconfigured(String),
+}
+
+impl WebhookSignature {
+    /// Check the signature header against an HMAC of `body`. An optional
+    /// `<algorithm>=` prefix on the header value is ignored; the digest is
+    /// compared in constant time.
+    pub fn verify(&self, headers: &HeaderMap, body: &[u8]) -> Result<(), SignatureError> {
+        let secret = match (&self.secret, &self.secret_env) {
+            (Some(secret), _) => secret.clone(),
+            (None, Some(var)) => std::env::var(var).map_err(|_| {
+                SignatureError::Misconfigured(format!("signing secret ${} is not set", var))
+            })?,
+            (None, None) => {
+                return Err(SignatureError::Misconfigured(
+                    "signature needs 'secret' or 'secret_env'".to_string(),
+                ));
+            }
+        };
+
+        let provided = headers
+            .get(self.header.as_str())
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .ok_or_else(|| SignatureError::Rejected(format!("missing {} header", self.header)))?;
+        let provided = provided
+            .split_once('=')
+            .filter(|(prefix, _)| prefix.eq_ignore_ascii_case(&self.algorithm))
+            .map_or(provided, |(_, digest)| digest);
+        let digest = match self.encoding.as_str() {
+            "hex" => hex::decode(provided).ok(),
+            "base64" => base64::engine::general_purpose::STANDARD
+                .decode(provided)
+                .ok(),
+            other => {
+                return Err(SignatureError::Misconfigured(format!(
+                    "unknown signature encoding '{}'",
+                    other
+                )));
+            }
+        }
+        .ok_or_else(|| SignatureError::Rejected("malformed signature".to_string()))?;
+
+        let key = secret.as_bytes();
+        let valid = match self.algorithm.to_ascii_lowercase().as_str() {
+            "sha256" => verify_hmac::<Hmac<Sha256>>(key, body, &digest),
+            "sha1" => verify_hmac::<Hmac<Sha1>>(key, body, &digest),
+            "sha512" => verify_hmac::<Hmac<Sha512>>(key, body, &digest),
+            other => {
+                return Err(SignatureError::Misconfigured(format!(
+                    "unknown signature algorithm '{}'",
+                    other
+                )));
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(SignatureError::Rejected("signature mismatch".to_string()))
+        }
+    }
+}
+
+fn verify_hmac<M: Mac + KeyInit>(key: &[u8], body: &[u8], digest: &[u8]) -> bool {
+    let mut mac = <M as KeyInit>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(body);
+    mac.verify_slice(digest).is_ok()
+}
+
+/// Payload-based dispatch for one webhook endpoint.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookRouting {
+    /// Where to read the routing value from.
+    pub route_by: RouteSelector,
+    /// Selector value → flow file.
+    pub routes: HashMap<String, String>,
+    /// Flow file used when the value is missing or has no route.
+    #[serde(default)]
+    pub default: Option<String>,
+    #[serde(default)]
+    pub signature: Option<WebhookSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteSelector {
+    /// Request header name, matched case-insensitively.
+    Header(String),
+    /// Dotted path into the JSON body, e.g. `action` or `$.repository.name`.
+    JsonPath(String),
+}
+
+/// The flow a request resolved to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedWebhook<'a> {
+    pub flow_file: &'a str,
+    /// Routing value that selected the flow; `None` for plain webhooks and
+    /// for requests that fell through to `default`.
+    pub route: Option<String>,
+}
+
+impl WebhookTarget {
+    /// Signature check requests must pass before the flow runs, if any.
+    pub fn signature(&self) -> Option<&WebhookSignature> {
+        match self {
+            WebhookTarget::Flow(_) => None,
+            WebhookTarget::Routed(routing) => routing.signature.as_ref(),
+            WebhookTarget::Signed(signed) => Some(&signed.signature),
+        }
+    }
+
+    /// Pick the flow file for a request. Errors when the webhook is routed,
+    /// nothing matches, and no `default` is configured.
+    pub fn resolve(
+        &self,
+        headers: &HeaderMap,
+        body: &Context,
+    ) -> Result<ResolvedWebhook<'_>, String> {
+        let routing = match self {
+            WebhookTarget::Flow(flow_file)
+            | WebhookTarget::Signed(SignedFlow {
+                flow: flow_file, ..
+            }) => {
+                return Ok(ResolvedWebhook {
+                    flow_file,
+                    route: None,
+                });
+            }
+            WebhookTarget::Routed(routing) => routing,
+        };
+
+        let value = routing.route_by.select(headers, body);
+        if let Some(ref value) = value
+            && let Some(flow_file) = routing.routes.get(value)
+        {
+            return Ok(ResolvedWebhook {
+                flow_file,
+                route: Some(value.clone()),
+            });
+        }
+
+        match routing.default {
+            Some(ref flow_file) => Ok(ResolvedWebhook {
+                flow_file,
+                route: None,
+            }),
+            None => Err(match value {
+                Some(value) => format!("No route for {} = '{}'", routing.route_by, value),
+                None => format!("Request has no {}", routing.route_by),
+            }),
+        }
+    }
+}
+
+impl RouteSelector {
+    fn select(&self, headers: &HeaderMap, body: &Context) -> Option<String> {
+        match self {
+            RouteSelector::Header(name) => headers
+                .get(name.as_str())
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.trim().to_string()),
+            RouteSelector::JsonPath(path) => {
+                let path = path.strip_prefix("$.").unwrap_or(path);
+                let mut segments = path.split('.');
+                let mut current = body.get(segments.next()?)?;
+                for segment in segments {
+                    current = match current {
+                        serde_json::Value::Object(map) => map.get(segment)?,
+                        serde_json::Value::Array(items) => {
+                            items.get(segment.parse::<usize>().ok()?)?
+                        }
+                        _ => return None,
+                    };
+                }
+                match current {
+                    serde_json::Value::String(s) => Some(s.clone()),
+                    serde_json::Value::Number(_) | serde_json::Value::Bool(_) => {
+                        Some(current.to_string())
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for RouteSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RouteSelector::Header(name) => write!(f, "header '{}'", name),
+            RouteSelector::JsonPath(path) => write!(f, "body field '{}'", path),
+        }
+    }
+}
+
+impl From<String> for WebhookTarget {
+    fn from(flow_file: String) -> Self {
+        WebhookTarget::Flow(flow_file)
+    }
+}
+
+impl From<&str> for WebhookTarget {
+    fn from(flow_file: &str) -> Self {
+        WebhookTarget::Flow(flow_file.to_string())
+    }
+}
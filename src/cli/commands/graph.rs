@@ -0,0 +1,23 @@
+use std::path::PathBuf;
+
+use anyhow::{Context as _, Result};
+
+use crate::lua::LuaRuntime;
+use crate::nodes::NodeRegistry;
+
+pub(crate) fn cmd_graph(registry: &NodeRegistry, flow_path: PathBuf, format: &str) -> Result<()> {
+    let flow_str = flow_path
+        .to_str()
+        .ok_or_else(|| anyhow::anyhow!("Invalid flow path"))?;
+
+    let flow = LuaRuntime::load_flow(flow_str, registry)
+        .with_context(|| format!("Failed to load flow: {}", flow_path.display()))?;
+
+    let graph = match format {
+        "dot" => flow.to_dot(),
+        "mermaid" => flow.to_mermaid(),
+        other => anyhow::bail!("Invalid graph format '{}'. Use: dot, mermaid", other),
+    };
+    print!("{}", graph);
+    Ok(())
+}
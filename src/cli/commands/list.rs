@@ -17,6 +17,7 @@ pub(crate) async fn cmd_list(
             "success" => Ok(crate::engine::types::RunStatus::Success),
             "failed" => Ok(crate::engine::types::RunStatus::Failed),
             "stalled" => Ok(crate::engine::types::RunStatus::Stalled),
+            "cancelled" => Ok(crate::engine::types::RunStatus::Cancelled),
             _ => Err(anyhow::anyhow!("Invalid status filter: {}", s)),
         })
         .transpose()?;
@@ -1,13 +1,19 @@
+mod graph;
 mod inspect;
 mod list;
 mod nodes;
+mod retry;
 mod run;
 mod serve;
+mod test;
 mod validate;
 
+pub(crate) use graph::cmd_graph;
 pub(crate) use inspect::cmd_inspect;
 pub(crate) use list::cmd_list;
 pub(crate) use nodes::cmd_nodes;
+pub(crate) use retry::cmd_retry;
 pub(crate) use run::cmd_run;
 pub(crate) use serve::{apply_config_path, cmd_serve};
+pub(crate) use test::cmd_test;
 pub(crate) use validate::cmd_validate;
@@ -2,15 +2,15 @@ use anyhow::Result;
 
 use crate::nodes::NodeRegistry;
 
-pub(crate) fn cmd_nodes() -> Result<()> {
-    let registry = NodeRegistry::with_builtins();
+pub(crate) fn cmd_nodes(registry: &NodeRegistry) -> Result<()> {
     let nodes = registry.list();
 
-    println!("{:<20} DESCRIPTION", "NODE TYPE");
-    println!("{}", "-".repeat(60));
+    println!("{:<20} {:<5} DESCRIPTION", "NODE TYPE", "PURE");
+    println!("{}", "-".repeat(66));
 
-    for (name, desc) in &nodes {
-        println!("{:<20} {}", name, desc);
+    for (name, desc, pure) in &nodes {
+        let pure = if *pure { "yes" } else { "" };
+        println!("{:<20} {:<5} {}", name, pure, desc);
     }
 
     println!("\nTotal: {} node(s)", nodes.len());
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+
+use crate::engine::WorkflowEngine;
+use crate::lua::LuaRuntime;
+use crate::nodes::NodeRegistry;
+use crate::storage::StateStore;
+
+use super::run::print_run_result;
+
+pub(crate) async fn cmd_retry(
+    registry: Arc<NodeRegistry>,
+    run_id: String,
+    flow_path: Option<PathBuf>,
+    verbose: bool,
+    store: Arc<dyn StateStore>,
+    max_concurrent_tasks: Option<usize>,
+    concurrency_limits: HashMap<String, usize>,
+) -> Result<()> {
+    let original = store
+        .get_run_info(&run_id)
+        .await
+        .with_context(|| format!("Run '{}' not found", run_id))?;
+
+    // Runs started by `ironflow run` record their flow file
+    let flow_path = match flow_path {
+        Some(path) => path,
+        None => original
+            .ctx
+            .get("_flow_file")
+            .and_then(|v| v.as_str())
+            .map(PathBuf::from)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Run '{}' does not record its flow file; pass it with --flow",
+                    run_id
+                )
+            })?,
+    };
+    let flow_str = flow_path
+        .to_str()
+        .ok_or_else(|| anyhow::anyhow!("Invalid flow path"))?;
+    let flow = LuaRuntime::load_flow(flow_str, &registry)
+        .with_context(|| format!("Failed to load flow: {}", flow_path.display()))?;
+
+    println!("Flow: {} ({} steps)", flow.name, flow.steps.len());
+    println!("Retrying run: {}", run_id);
+
+    let engine = WorkflowEngine::new(registry, store.clone(), max_concurrent_tasks)
+        .with_concurrency_limits(concurrency_limits);
+    let new_run_id = engine.retry(&flow, &original).await?;
+
+    print_run_result(store.as_ref(), &new_run_id, verbose).await
+}
@@ -1,33 +1,56 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::{Context as _, Result};
 
 use crate::engine::WorkflowEngine;
-use crate::engine::types::Context;
+use crate::engine::determinism::{self, Determinism};
+use crate::engine::executor::DEFAULT_MEMO_ENTRIES;
+use crate::engine::types::{Context, FlowDefinition};
 use crate::lua::LuaRuntime;
 use crate::nodes::NodeRegistry;
 use crate::storage::StateStore;
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn cmd_run(
+    registry: Arc<NodeRegistry>,
     flow_path: PathBuf,
     context_json: Option<String>,
+    env: Option<String>,
     verbose: bool,
     store: Arc<dyn StateStore>,
     max_concurrent_tasks: Option<usize>,
+    concurrency_limits: HashMap<String, usize>,
+    seed: Option<u64>,
+    profile: bool,
+    timeout: Option<f64>,
+    dry_run: bool,
+    memoize: bool,
 ) -> Result<()> {
-    let registry = NodeRegistry::with_builtins();
-
     let flow_str = flow_path
         .to_str()
         .ok_or_else(|| anyhow::anyhow!("Invalid flow path"))?;
 
-    let flow = LuaRuntime::load_flow(flow_str, &registry)
-        .with_context(|| format!("Failed to load flow: {}", flow_path.display()))?;
+    let load = || LuaRuntime::load_flow(flow_str, &registry);
+    let mut flow = match seed {
+        Some(seed) => determinism::sync_scope(Arc::new(Determinism::new(seed).child("load")), load),
+        None => load(),
+    }
+    .with_context(|| format!("Failed to load flow: {}", flow_path.display()))?;
+    if let Some(timeout) = timeout {
+        if !timeout.is_finite() || timeout <= 0.0 {
+            anyhow::bail!("--timeout must be a positive number of seconds");
+        }
+        flow.timeout_s = Some(timeout);
+    }
 
     println!("Flow: {} ({} steps)", flow.name, flow.steps.len());
 
     if verbose {
+        if let Some(t) = flow.timeout_s {
+            println!("Run timeout: {}s", t);
+        }
         println!("\nSteps:");
         for step in &flow.steps {
             let deps = if step.dependencies.is_empty() {
@@ -37,21 +60,38 @@ pub(crate) async fn cmd_run(
             };
             println!("  {} [{}] deps: {}", step.name, step.node_type, deps);
             if step.retry.max_retries > 0 {
+                let cap = step
+                    .retry
+                    .max_backoff_s
+                    .map(|max| format!(", max {}s", max))
+                    .unwrap_or_default();
                 println!(
-                    "    retries: {}, backoff: {}s",
-                    step.retry.max_retries, step.retry.backoff_s
+                    "    retries: {}, backoff: {}s {}{}",
+                    step.retry.max_retries,
+                    step.retry.backoff_s,
+                    step.retry.backoff.name(),
+                    cap
                 );
+                if !step.retry.retry_on.is_empty() {
+                    println!("    retry on: {}", step.retry.retry_on.join(", "));
+                }
             }
             if let Some(t) = step.timeout_s {
                 println!("    timeout: {}s", t);
             }
             if let Some(ref r) = step.route {
-                println!("    route: {}", r);
+                match step.route_from {
+                    Some(ref from) => println!("    route: {} (from {})", r, from),
+                    None => println!("    route: {}", r),
+                }
             }
         }
     }
 
-    // Parse initial context
+    // The engine layers the flow's environment overlays under --context
+    if let Some(ref env) = env {
+        println!("Environment: {}", env);
+    }
     let mut initial_ctx: Context = match context_json {
         Some(json) => {
             serde_json::from_str(&json).with_context(|| "Failed to parse --context JSON")?
@@ -59,20 +99,80 @@ pub(crate) async fn cmd_run(
         None => Context::new(),
     };
 
-    // Inject _flow_dir so subworkflow nodes can resolve relative paths
-    if let Some(flow_dir) = flow_path.canonicalize()?.parent() {
+    // Inject _flow_dir so subworkflow nodes can resolve relative paths, and
+    // _flow_file so `ironflow retry` can reload the flow later
+    let flow_file = flow_path.canonicalize()?;
+    if let Some(flow_dir) = flow_file.parent() {
         initial_ctx.insert(
             "_flow_dir".to_string(),
             serde_json::Value::String(flow_dir.to_string_lossy().to_string()),
         );
     }
+    initial_ctx.insert(
+        "_flow_file".to_string(),
+        serde_json::Value::String(flow_file.to_string_lossy().to_string()),
+    );
+
+    let mut engine = WorkflowEngine::new(registry, store.clone(), max_concurrent_tasks)
+        .with_concurrency_limits(concurrency_limits)
+        .with_profiling(profile)
+        .with_environment(env);
+    if let Some(seed) = seed {
+        engine = engine.with_seed(seed);
+    }
+    if memoize {
+        engine = engine.with_memoization(DEFAULT_MEMO_ENTRIES);
+    }
 
-    let engine = WorkflowEngine::new(Arc::new(registry), store.clone(), max_concurrent_tasks);
+    if dry_run {
+        return print_plan(&engine, &flow, &initial_ctx);
+    }
 
     let run_id = engine.execute(&flow, initial_ctx).await?;
+    print_run_result(store.as_ref(), &run_id, verbose).await
+}
+
+/// Print what a run would do, without executing any step.
+fn print_plan(engine: &WorkflowEngine, flow: &FlowDefinition, initial_ctx: &Context) -> Result<()> {
+    let plan = engine.plan(flow, initial_ctx)?;
+    println!("\nDry run: no steps were executed.");
 
-    // Print results
-    let run_info = store.get_run_info(&run_id).await?;
+    let mut phase = 0;
+    for step in &plan {
+        if step.phase != phase {
+            phase = step.phase;
+            println!("\nPhase {}:", phase);
+        }
+        let effect = if step.pure { "pure" } else { "side-effecting" };
+        println!("  {} [{}] {}", step.name, step.node_type, effect);
+        if !step.dependencies.is_empty() {
+            println!("    deps: {}", step.dependencies.join(", "));
+        }
+        if let Some(ref route) = step.route {
+            println!("    route: {}", route);
+        }
+        println!(
+            "    config: {}",
+            serde_json::to_string(&step.resolved_config)?
+        );
+    }
+
+    let side_effecting = plan.iter().filter(|s| !s.pure).count();
+    println!(
+        "\n{} step(s), {} side-effecting",
+        plan.len(),
+        side_effecting
+    );
+    Ok(())
+}
+
+/// Print a finished run's status, tasks, profile and user context.
+pub(super) async fn print_run_result(
+    store: &dyn StateStore,
+    run_id: &str,
+    verbose: bool,
+) -> Result<()> {
+    let run_info = store.get_run_info(run_id).await?;
     println!("\nRun ID: {}", run_id);
     println!("Status: {}", run_info.status);
 
@@ -82,6 +182,7 @@ pub(crate) async fn cmd_run(
             crate::engine::types::TaskStatus::Success => "✓",
             crate::engine::types::TaskStatus::Failed => "✗",
             crate::engine::types::TaskStatus::Skipped => "⊘",
+            crate::engine::types::TaskStatus::TimedOut => "⏱",
             crate::engine::types::TaskStatus::Running => "⟳",
             crate::engine::types::TaskStatus::Pending => "○",
         };
@@ -101,6 +202,19 @@ pub(crate) async fn cmd_run(
         }
     }
 
+    if let Some(ref run_profile) = run_info.profile {
+        println!("\nProfile (slowest first):");
+        for step in &run_profile.steps {
+            println!(
+                "  {:>8}ms  {} [{}]",
+                step.duration_ms, step.name, step.node_type
+            );
+            for (phase, ms) in &step.timings {
+                println!("              {}: {:.1}ms", phase, ms);
+            }
+        }
+    }
+
     if !run_info.ctx.is_empty() {
         // Only print non-internal context keys
         let user_ctx: Context = run_info
@@ -3,15 +3,20 @@ use std::sync::Arc;
 
 use anyhow::Result;
 
+use crate::nodes::NodeRegistry;
 use crate::storage::StateStore;
 use crate::storage::event_store::EventStore;
 
 /// Execute the `serve` subcommand.
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn cmd_serve(
+    registry: Arc<NodeRegistry>,
     host: String,
     port: u16,
     flows_dir: Option<PathBuf>,
     max_body: usize,
+    shutdown_timeout: u64,
+    api_key: Option<String>,
     store: Arc<dyn StateStore>,
     event_store: Arc<dyn EventStore>,
     cfg: &crate::cli::IronFlowConfig,
@@ -32,12 +37,18 @@ pub(crate) async fn cmd_serve(
     } else {
         max_body
     };
-    let api_key = resolve_api_key(cfg.api_key.clone());
+    let shutdown_timeout = if shutdown_timeout == crate::api::shutdown::DEFAULT_SHUTDOWN_TIMEOUT_S {
+        cfg.shutdown_timeout.unwrap_or(shutdown_timeout)
+    } else {
+        shutdown_timeout
+    };
+    let api_key = api_key.or_else(|| cfg.api_key.clone());
     let allow_unauthenticated_api =
         resolve_allow_unauthenticated_api(cfg.allow_unauthenticated_api.unwrap_or(false));
     let cors_origins = resolve_cors_origins(cfg.cors_origins.clone());
     let webhooks = cfg.webhooks.clone().unwrap_or_default();
-    crate::api::serve(
+    crate::api::serve_with_registry(
+        registry,
         store,
         event_store,
         crate::api::ServeOptions {
@@ -46,10 +57,12 @@ pub(crate) async fn cmd_serve(
             flows_dir,
             max_body,
             max_concurrent_tasks: cfg.max_concurrent_tasks,
+            concurrency_limits: cfg.concurrency_limits.clone().unwrap_or_default(),
             webhooks,
             cors_origins,
             api_key,
             allow_unauthenticated_api,
+            shutdown_timeout: std::time::Duration::from_secs(shutdown_timeout),
         },
     )
     .await
@@ -82,10 +95,6 @@ fn resolve_cors_origins(config_value: Option<Vec<String>>) -> Option<Vec<String>
         .or(config_value)
 }
 
-fn resolve_api_key(config_value: Option<String>) -> Option<String> {
-    std::env::var("IRONFLOW_API_KEY").ok().or(config_value)
-}
-
 fn resolve_allow_unauthenticated_api(config_value: bool) -> bool {
     std::env::var("IRONFLOW_ALLOW_UNAUTHENTICATED_API")
         .ok()
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{Context as _, Result};
+use serde_json::Value;
+
+use crate::engine::WorkflowEngine;
+use crate::engine::determinism::{self, Determinism};
+use crate::engine::types::{Context, RunInfo};
+use crate::lua::LuaRuntime;
+use crate::nodes::NodeRegistry;
+use crate::storage::StateStore;
+use crate::storage::null_store::NullStateStore;
+
+/// File suffix that marks a flow test fixture.
+const FIXTURE_SUFFIX: &str = ".test.lua";
+
+/// Seed used when a fixture does not set one, so every test run is
+/// reproducible.
+const DEFAULT_SEED: u64 = 0;
+
+pub(crate) async fn cmd_test(
+    registry: Arc<NodeRegistry>,
+    path: PathBuf,
+    max_concurrent_tasks: Option<usize>,
+    concurrency_limits: HashMap<String, usize>,
+) -> Result<()> {
+    let fixtures = discover_fixtures(&path)?;
+    if fixtures.is_empty() {
+        anyhow::bail!(
+            "No *{} fixtures found in {}",
+            FIXTURE_SUFFIX,
+            path.display()
+        );
+    }
+
+    let mut failed = 0;
+    for fixture in &fixtures {
+        let started = Instant::now();
+        let (name, problems) = match run_fixture(
+            registry.clone(),
+            fixture,
+            max_concurrent_tasks,
+            concurrency_limits.clone(),
+        )
+        .await
+        {
+            Ok(result) => result,
+            Err(e) => (fixture.display().to_string(), vec![format!("{:#}", e)]),
+        };
+        let elapsed = started.elapsed().as_millis();
+
+        if problems.is_empty() {
+            println!("  ✓ {} ({}ms)", name, elapsed);
+        } else {
+            failed += 1;
+            println!("  ✗ {} ({}ms)", name, elapsed);
+            for problem in &problems {
+                println!("      {}", problem);
+            }
+        }
+    }
+
+    println!("\n{} passed, {} failed", fixtures.len() - failed, failed);
+    if failed > 0 {
+        anyhow::bail!("{} of {} flow tests failed", failed, fixtures.len());
+    }
+    Ok(())
+}
+
+/// Collect fixture files under `path` (or `path` itself), sorted so output
+/// order is stable.
+fn discover_fixtures(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut fixtures = Vec::new();
+    let mut dirs = vec![path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = std::fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+        for entry in entries {
+            let entry_path = entry?.path();
+            if entry_path.is_dir() {
+                dirs.push(entry_path);
+            } else if entry_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.ends_with(FIXTURE_SUFFIX))
+            {
+                fixtures.push(entry_path);
+            }
+        }
+    }
+    fixtures.sort();
+    Ok(fixtures)
+}
+
+/// Run one fixture. Returns its display name and the list of mismatches
+/// (empty when it passed).
+async fn run_fixture(
+    registry: Arc<NodeRegistry>,
+    fixture_path: &Path,
+    max_concurrent_tasks: Option<usize>,
+    concurrency_limits: HashMap<String, usize>,
+) -> Result<(String, Vec<String>)> {
+    let fixture_str = fixture_path
+        .to_str()
+        .ok_or_else(|| anyhow::anyhow!("Invalid fixture path"))?;
+    let fixture = LuaRuntime::load_data(fixture_str)?;
+    if !fixture.is_object() {
+        anyhow::bail!("fixture must return a table");
+    }
+
+    let name = fixture
+        .get("name")
+        .and_then(|v| v.as_str())
+        .map(String::from)
+        .unwrap_or_else(|| fixture_path.display().to_string());
+
+    let flow_rel = fixture
+        .get("flow")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("fixture requires a 'flow' path"))?;
+    let flow_path = fixture_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(flow_rel);
+    let flow_str = flow_path
+        .to_str()
+        .ok_or_else(|| anyhow::anyhow!("Invalid flow path"))?;
+
+    let seed = fixture
+        .get("seed")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_SEED);
+    let flow = determinism::sync_scope(Arc::new(Determinism::new(seed).child("load")), || {
+        LuaRuntime::load_flow(flow_str, &registry)
+    })
+    .with_context(|| format!("Failed to load flow: {}", flow_path.display()))?;
+
+    let env = fixture.get("env").and_then(|v| v.as_str());
+    let mut initial_ctx = match fixture.get("context") {
+        Some(Value::Object(map)) => map.clone().into_iter().collect::<Context>(),
+        Some(Value::Null) | None => Context::new(),
+        Some(_) => anyhow::bail!("fixture 'context' must be a table"),
+    };
+    if let Some(flow_dir) = flow_path.canonicalize()?.parent() {
+        initial_ctx.insert(
+            "_flow_dir".to_string(),
+            Value::String(flow_dir.to_string_lossy().to_string()),
+        );
+    }
+
+    // Runs are kept in memory only, so fixtures never touch the real store.
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(registry, store.clone(), max_concurrent_tasks)
+        .with_concurrency_limits(concurrency_limits)
+        .with_environment(env.map(str::to_string))
+        .with_seed(seed);
+    let run_id = engine.execute(&flow, initial_ctx).await?;
+    let run_info = store.get_run_info(&run_id).await?;
+
+    let expect = fixture.get("expect").cloned().unwrap_or(Value::Null);
+    Ok((name, check_expectations(&expect, &run_info)))
+}
+
+/// Compare a run against a fixture's `expect` table.
+///
+/// - `status` (default `"success"`): the run status.
+/// - `ctx`: a subset of the final context; tables match recursively and
+///   extra keys in the run are ignored.
+/// - `tasks`: step name to expected task status.
+/// - `error`: text that some task's error must contain.
+fn check_expectations(expect: &Value, run: &RunInfo) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    let status = expect
+        .get("status")
+        .and_then(|v| v.as_str())
+        .unwrap_or("success");
+    if run.status.to_string() != status {
+        problems.push(format!("status: expected {}, got {}", status, run.status));
+        for (step, task) in &run.tasks {
+            if let Some(ref err) = task.error {
+                problems.push(format!("  {}: {}", step, err));
+            }
+        }
+    }
+
+    if let Some(Value::Object(expected_ctx)) = expect.get("ctx") {
+        for (key, expected) in expected_ctx {
+            match_subset(
+                &format!("ctx.{}", key),
+                expected,
+                run.ctx.get(key),
+                &mut problems,
+            );
+        }
+    }
+
+    if let Some(Value::Object(expected_tasks)) = expect.get("tasks") {
+        for (step, expected) in expected_tasks {
+            let expected = expected.as_str().unwrap_or_default();
+            match run.tasks.get(step) {
+                Some(task) if task.status.to_string() == expected => {}
+                Some(task) => problems.push(format!(
+                    "tasks.{}: expected {}, got {}",
+                    step, expected, task.status
+                )),
+                None => problems.push(format!(
+                    "tasks.{}: expected {}, but the step did not run",
+                    step, expected
+                )),
+            }
+        }
+    }
+
+    if let Some(needle) = expect.get("error").and_then(|v| v.as_str())
+        && !run
+            .tasks
+            .values()
+            .any(|t| t.error.as_deref().is_some_and(|e| e.contains(needle)))
+    {
+        problems.push(format!("error: no task error contains {:?}", needle));
+    }
+
+    problems
+}
+
+/// Check that `actual` contains everything in `expected`. Objects are
+/// compared key by key, arrays element by element (same length), and
+/// numbers by value so `1` matches `1.0`.
+fn match_subset(path: &str, expected: &Value, actual: Option<&Value>, problems: &mut Vec<String>) {
+    let Some(actual) = actual else {
+        problems.push(format!("{}: expected {}, got nothing", path, expected));
+        return;
+    };
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, value) in expected {
+                match_subset(
+                    &format!("{}.{}", path, key),
+                    value,
+                    actual.get(key),
+                    problems,
+                );
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => {
+            for (idx, (e, a)) in expected.iter().zip(actual).enumerate() {
+                match_subset(&format!("{}[{}]", path, idx + 1), e, Some(a), problems);
+            }
+        }
+        (Value::Number(e), Value::Number(a)) if e.as_f64() == a.as_f64() => {}
+        _ if expected == actual => {}
+        _ => problems.push(format!("{}: expected {}, got {}", path, expected, actual)),
+    }
+}
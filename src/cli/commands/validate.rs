@@ -5,14 +5,12 @@ use anyhow::{Context as _, Result};
 use crate::lua::LuaRuntime;
 use crate::nodes::NodeRegistry;
 
-pub(crate) fn cmd_validate(flow_path: PathBuf) -> Result<()> {
-    let registry = NodeRegistry::with_builtins();
-
+pub(crate) fn cmd_validate(registry: &NodeRegistry, flow_path: PathBuf) -> Result<()> {
     let flow_str = flow_path
         .to_str()
         .ok_or_else(|| anyhow::anyhow!("Invalid flow path"))?;
 
-    let flow = LuaRuntime::load_flow(flow_str, &registry)
+    let flow = LuaRuntime::load_flow(flow_str, registry)
         .with_context(|| format!("Failed to load flow: {}", flow_path.display()))?;
 
     println!("Flow: {}", flow.name);
@@ -35,6 +33,14 @@ pub(crate) fn cmd_validate(flow_path: PathBuf) -> Result<()> {
     if errors.is_empty() {
         println!("Validation: OK");
 
+        let warnings = flow.context_key_warnings(registry);
+        if !warnings.is_empty() {
+            println!("\nWarnings:");
+            for warning in &warnings {
+                println!("  - {}", warning);
+            }
+        }
+
         println!("\nExecution order:");
         for step in &flow.steps {
             let deps = if step.dependencies.is_empty() {
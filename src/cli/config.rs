@@ -4,6 +4,8 @@ use std::path::Path;
 use anyhow::{Context as _, Result};
 use serde::Deserialize;
 
+use crate::api::webhooks::WebhookTarget;
+
 /// Configuration loaded from `ironflow.yaml`.
 /// All fields are optional — missing fields fall back to CLI/env/defaults.
 #[derive(Debug, Deserialize, Default)]
@@ -15,6 +17,15 @@ pub struct IronFlowConfig {
     pub flows_dir: Option<String>,
     pub max_body: Option<usize>,
     pub max_concurrent_tasks: Option<usize>,
+    /// Directory of plugin shared libraries loaded at startup, in addition
+    /// to any `--plugin` flags.
+    pub plugins_dir: Option<String>,
+    /// Node type → max steps of that type running at once, e.g. `llm: 2`.
+    /// Shared across runs (every API request under `serve`), on top of
+    /// `max_concurrent_tasks`.
+    pub concurrency_limits: Option<HashMap<String, usize>>,
+    /// Seconds `serve` waits for in-flight runs after SIGTERM/Ctrl+C.
+    pub shutdown_timeout: Option<u64>,
     /// API key required for HTTP API access.
     /// Prefer IRONFLOW_API_KEY or a secret manager in production.
     pub api_key: Option<String>,
@@ -41,7 +52,9 @@ pub struct IronFlowConfig {
     pub redis_ttl: Option<u64>,
     /// Webhook name → flow file path mappings.
     /// e.g. `hello: hello_world.lua` → POST /webhooks/hello executes hello_world.lua
-    pub webhooks: Option<HashMap<String, String>>,
+    /// A webhook may instead map to a routing table that picks the flow from
+    /// a request header or body field.
+    pub webhooks: Option<HashMap<String, WebhookTarget>>,
 }
 
 impl IronFlowConfig {
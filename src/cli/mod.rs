@@ -5,12 +5,15 @@ mod store_factory;
 pub use config::IronFlowConfig;
 pub use store_factory::{create_event_store, create_store};
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use tracing::info;
 
+use crate::nodes::{NodeRegistry, plugin};
+
 #[derive(Parser)]
 #[command(name = "ironflow", version, about = "Lightweight workflow engine")]
 pub struct Cli {
@@ -22,6 +25,10 @@ pub struct Cli {
     #[arg(short = 'C', long, global = true)]
     config: Option<PathBuf>,
 
+    /// Shared library with custom nodes to load (repeatable)
+    #[arg(long = "plugin", global = true)]
+    plugins: Vec<PathBuf>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -37,6 +44,10 @@ pub enum Commands {
         #[arg(short, long)]
         context: Option<String>,
 
+        /// Environment whose `flow:env(...)` values seed the context (default values always apply)
+        #[arg(short, long, env = "IRONFLOW_ENV")]
+        env: Option<String>,
+
         /// Enable verbose output
         #[arg(short, long)]
         verbose: bool,
@@ -44,6 +55,26 @@ pub enum Commands {
         /// State store directory
         #[arg(long, default_value = "data/runs")]
         store_dir: PathBuf,
+
+        /// Run deterministically: derive run IDs and Lua uuid/time/random helpers from this seed
+        #[arg(long, env = "IRONFLOW_SEED")]
+        seed: Option<u64>,
+
+        /// Record per-step timings and print a breakdown, slowest steps first
+        #[arg(long)]
+        profile: bool,
+
+        /// Stop the run after this many seconds (overrides the flow's `flow:timeout`)
+        #[arg(long)]
+        timeout: Option<f64>,
+
+        /// Print the execution plan with resolved configs instead of running any step
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Reuse outputs of pure steps repeated with identical config and inputs
+        #[arg(long)]
+        memoize: bool,
     },
 
     /// Validate a flow file without executing
@@ -52,9 +83,26 @@ pub enum Commands {
         flow: PathBuf,
     },
 
+    /// Print a flow's step graph as GraphViz DOT or a Mermaid flowchart
+    Graph {
+        /// Path to the .lua flow file
+        flow: PathBuf,
+
+        /// Output format (dot, mermaid)
+        #[arg(long, default_value = "dot")]
+        format: String,
+    },
+
+    /// Run flow test fixtures (*.test.lua) and report pass/fail
+    Test {
+        /// Fixture file, or directory to search recursively for *.test.lua files
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+
     /// List past workflow runs
     List {
-        /// Filter by status (pending, running, success, failed, stalled)
+        /// Filter by status (pending, running, success, failed, stalled, cancelled)
         #[arg(short, long)]
         status: Option<String>,
 
@@ -67,6 +115,24 @@ pub enum Commands {
         format: String,
     },
 
+    /// Re-run only the failed (and skipped downstream) steps of a past run
+    Retry {
+        /// ID of the failed run
+        run_id: String,
+
+        /// Flow file to load (default: the file recorded by `ironflow run`)
+        #[arg(short, long)]
+        flow: Option<PathBuf>,
+
+        /// Enable verbose output
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// State store directory
+        #[arg(long, default_value = "data/runs")]
+        store_dir: PathBuf,
+    },
+
     /// Inspect a specific run
     Inspect {
         /// Run ID
@@ -101,10 +167,26 @@ pub enum Commands {
         /// Maximum request body size in bytes (default: 1048576 = 1 MB)
         #[arg(long, default_value = "1048576", env = "MAX_BODY")]
         max_body: usize,
+
+        /// Seconds to wait for in-flight runs on shutdown before marking them stalled
+        #[arg(long, default_value = "30", env = "IRONFLOW_SHUTDOWN_TIMEOUT")]
+        shutdown_timeout: u64,
+
+        /// API key clients must send as a Bearer token or X-API-Key header
+        #[arg(long, env = "IRONFLOW_API_KEY", hide_env_values = true)]
+        api_key: Option<String>,
     },
 }
 
 pub async fn run_cli() -> Result<()> {
+    run_cli_with(|_| {}).await
+}
+
+/// Run the CLI with extra nodes registered by `customize`.
+///
+/// Embedders use this from their own `main` to ship a binary that behaves
+/// exactly like `ironflow` but also understands their custom node types.
+pub async fn run_cli_with(customize: impl FnOnce(&mut NodeRegistry)) -> Result<()> {
     let cli = Cli::parse();
 
     // Load .env file
@@ -113,19 +195,60 @@ pub async fn run_cli() -> Result<()> {
     // Load config file (ironflow.yaml)
     let cfg = IronFlowConfig::load(cli.config.as_deref())?;
 
+    // Plugins register inside the customize hook so subworkflows see them too.
+    let plugin_paths =
+        plugin::plugin_paths(&cli.plugins, cfg.plugins_dir.as_deref().map(Path::new))?;
+    let mut plugin_result = Ok(());
+    let registry = Arc::new(NodeRegistry::with_builtins_and(|registry| {
+        customize(registry);
+        plugin_result = plugin::load_plugins(registry, &plugin_paths);
+    }));
+    plugin_result?;
+
     match cli.command {
         Commands::Run {
             flow,
             context,
+            env,
             verbose,
             store_dir,
+            seed,
+            profile,
+            timeout,
+            dry_run,
+            memoize,
         } => {
             let store_dir =
                 commands::apply_config_path(store_dir, "data/runs", cfg.store_dir.as_deref());
             let store = create_store(&cfg, &store_dir).await?;
-            commands::cmd_run(flow, context, verbose, store, cfg.max_concurrent_tasks).await
+            commands::cmd_run(
+                registry,
+                flow,
+                context,
+                env,
+                verbose,
+                store,
+                cfg.max_concurrent_tasks,
+                cfg.concurrency_limits.clone().unwrap_or_default(),
+                seed,
+                profile,
+                timeout,
+                dry_run,
+                memoize,
+            )
+            .await
+        }
+        Commands::Validate { flow } => commands::cmd_validate(&registry, flow),
+        Commands::Graph { flow, format } => commands::cmd_graph(&registry, flow, &format),
+        Commands::Test { path } => {
+            commands::cmd_test(
+                registry,
+                path,
+                cfg.max_concurrent_tasks,
+                cfg.concurrency_limits.clone().unwrap_or_default(),
+            )
+            .await
         }
-        Commands::Validate { flow } => commands::cmd_validate(flow),
         Commands::List {
             status,
             store_dir,
@@ -136,25 +259,59 @@ pub async fn run_cli() -> Result<()> {
             let store = create_store(&cfg, &store_dir).await?;
             commands::cmd_list(status, store, format).await
         }
+        Commands::Retry {
+            run_id,
+            flow,
+            verbose,
+            store_dir,
+        } => {
+            let store_dir =
+                commands::apply_config_path(store_dir, "data/runs", cfg.store_dir.as_deref());
+            let store = create_store(&cfg, &store_dir).await?;
+            commands::cmd_retry(
+                registry,
+                run_id,
+                flow,
+                verbose,
+                store,
+                cfg.max_concurrent_tasks,
+                cfg.concurrency_limits.clone().unwrap_or_default(),
+            )
+            .await
+        }
         Commands::Inspect { run_id, store_dir } => {
             let store_dir =
                 commands::apply_config_path(store_dir, "data/runs", cfg.store_dir.as_deref());
             let store = create_store(&cfg, &store_dir).await?;
             commands::cmd_inspect(run_id, store).await
         }
-        Commands::Nodes => commands::cmd_nodes(),
+        Commands::Nodes => commands::cmd_nodes(&registry),
         Commands::Serve {
             host,
             port,
             store_dir,
             flows_dir,
             max_body,
+            shutdown_timeout,
+            api_key,
         } => {
             let store_dir =
                 commands::apply_config_path(store_dir, "data/runs", cfg.store_dir.as_deref());
             let store = create_store(&cfg, &store_dir).await?;
             let event_store = create_event_store(&cfg, &store_dir).await?;
-            commands::cmd_serve(host, port, flows_dir, max_body, store, event_store, &cfg).await
+            commands::cmd_serve(
+                registry,
+                host,
+                port,
+                flows_dir,
+                max_body,
+                shutdown_timeout,
+                api_key,
+                store,
+                event_store,
+                &cfg,
+            )
+            .await
         }
     }
 }
@@ -0,0 +1,118 @@
+//! Seeded deterministic mode for reproducible runs.
+//!
+//! When a [`WorkflowEngine`](crate::engine::WorkflowEngine) is given a seed,
+//! run IDs and the Lua `uuid4()` / `now_rfc3339()` / `now_unix_ms()` /
+//! `math.random` helpers derive from that seed and a frozen clock instead of
+//! the OS RNG and wall clock. The active seed travels with the executing task
+//! as a Tokio task-local, so nothing changes for unseeded (production) runs.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::{DateTime, TimeZone, Utc};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+tokio::task_local! {
+    static CURRENT: Arc<Determinism>;
+}
+
+/// A seeded source of IDs plus a frozen clock.
+#[derive(Debug)]
+pub struct Determinism {
+    seed: u64,
+    counter: AtomicU64,
+}
+
+impl Determinism {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Derive an independent child whose sequence depends only on this seed
+    /// and `label` — not on how many values the parent has handed out.
+    pub fn child(&self, label: &str) -> Self {
+        let digest = Sha256::new()
+            .chain_update(self.seed.to_le_bytes())
+            .chain_update(label.as_bytes())
+            .finalize();
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        Self::new(u64::from_le_bytes(bytes))
+    }
+
+    /// Next value in this instance's sequence.
+    pub fn next_u64(&self) -> u64 {
+        let n = self.counter.fetch_add(1, Ordering::Relaxed);
+        let digest = Sha256::new()
+            .chain_update(self.seed.to_le_bytes())
+            .chain_update(n.to_le_bytes())
+            .finalize();
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// Next UUID in this instance's sequence (formatted as a v4 UUID).
+    pub fn next_uuid(&self) -> Uuid {
+        let mut bytes = [0u8; 16];
+        bytes[..8].copy_from_slice(&self.next_u64().to_le_bytes());
+        bytes[8..].copy_from_slice(&self.next_u64().to_le_bytes());
+        uuid::Builder::from_random_bytes(bytes).into_uuid()
+    }
+
+    /// The frozen clock: always 2000-01-01T00:00:00Z.
+    pub fn now(&self) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap()
+    }
+}
+
+/// The deterministic state of the current task, if it runs seeded.
+pub fn current() -> Option<Arc<Determinism>> {
+    CURRENT.try_with(|d| d.clone()).ok()
+}
+
+/// Run `fut` with `determinism` as the task's active seed.
+pub async fn scope<F: Future>(determinism: Arc<Determinism>, fut: F) -> F::Output {
+    CURRENT.scope(determinism, fut).await
+}
+
+/// Run `fut` under `determinism` when given, otherwise unchanged.
+pub async fn maybe_scope<F: Future>(determinism: Option<Arc<Determinism>>, fut: F) -> F::Output {
+    match determinism {
+        Some(d) => scope(d, fut).await,
+        None => fut.await,
+    }
+}
+
+/// Run `f` synchronously with `determinism` as the active seed.
+pub fn sync_scope<R>(determinism: Arc<Determinism>, f: impl FnOnce() -> R) -> R {
+    CURRENT.sync_scope(determinism, f)
+}
+
+/// Run `fut` under a child of the active seed labelled `label`. Unseeded
+/// tasks run `fut` unchanged.
+pub async fn scope_child<F: Future>(label: &str, fut: F) -> F::Output {
+    match current() {
+        Some(parent) => scope(Arc::new(parent.child(label)), fut).await,
+        None => fut.await,
+    }
+}
+
+/// A UUID from the active seed, or a random v4 UUID when unseeded.
+pub fn uuid4() -> Uuid {
+    current().map_or_else(Uuid::new_v4, |d| d.next_uuid())
+}
+
+/// The frozen clock when seeded, otherwise the wall clock.
+pub fn now() -> DateTime<Utc> {
+    current().map_or_else(Utc::now, |d| d.now())
+}
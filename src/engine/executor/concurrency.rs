@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+
+/// Caps on how many steps of a node type run at once, e.g. `{"llm": 2}` to
+/// stay under a provider's rate limit. Clones share the same slots, so every
+/// run of an engine, and every engine built from one value (as the API
+/// server does per request), draws from a single pool per node type.
+#[derive(Debug, Clone, Default)]
+pub struct ConcurrencyLimits {
+    semaphores: Arc<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl ConcurrencyLimits {
+    /// Build the limits from node type → maximum. A limit of `0` is treated
+    /// as `1`.
+    pub fn new(limits: &HashMap<String, usize>) -> Self {
+        Self {
+            semaphores: Arc::new(
+                limits
+                    .iter()
+                    .map(|(node_type, limit)| {
+                        (node_type.clone(), Arc::new(Semaphore::new((*limit).max(1))))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// The semaphore limiting `node_type`, if it has a limit.
+    pub(super) fn semaphore(&self, node_type: &str) -> Option<Arc<Semaphore>> {
+        self.semaphores.get(node_type).cloned()
+    }
+}
+
+impl From<HashMap<String, usize>> for ConcurrencyLimits {
+    fn from(limits: HashMap<String, usize>) -> Self {
+        Self::new(&limits)
+    }
+}
@@ -0,0 +1,225 @@
+use std::io::Write;
+
+use anyhow::Result;
+use tracing::warn;
+
+use crate::engine::types::{Context, NodeError, NodeErrorKind, NodeOutput};
+
+/// What to do when a step's output breaks a [`ContextLimits`] bound.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ContextLimitAction {
+    /// Fail the step (and with it the run, unless `on_error` handles it).
+    #[default]
+    Fail,
+    /// Replace the offending output values with a truncation marker and
+    /// log a warning.
+    Truncate,
+}
+
+/// Size bounds on the workflow context, checked against the live context
+/// when a step's output is merged, so outputs of steps that ran in parallel
+/// are counted. Sizes are measured as compact serialized JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContextLimits {
+    /// Cap on the whole context.
+    pub max_context_bytes: Option<usize>,
+    /// Cap on any single value a step writes.
+    pub max_value_bytes: Option<usize>,
+    pub action: ContextLimitAction,
+}
+
+impl ContextLimits {
+    /// Read `IRONFLOW_MAX_CONTEXT_BYTES`, `IRONFLOW_MAX_CONTEXT_VALUE_BYTES`
+    /// and `IRONFLOW_CONTEXT_LIMIT_ACTION` (`fail` or `truncate`). Both caps
+    /// are off when unset or `0`.
+    pub fn from_env() -> Self {
+        let action = match std::env::var("IRONFLOW_CONTEXT_LIMIT_ACTION")
+            .ok()
+            .as_deref()
+            .map(str::trim)
+        {
+            Some(v) if v.eq_ignore_ascii_case("truncate") => ContextLimitAction::Truncate,
+            _ => ContextLimitAction::Fail,
+        };
+        Self {
+            max_context_bytes: crate::util::limits::max_context_bytes().map(|n| n as usize),
+            max_value_bytes: crate::util::limits::max_context_value_bytes().map(|n| n as usize),
+            action,
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.max_context_bytes.is_some() || self.max_value_bytes.is_some()
+    }
+
+    /// Check `output` of `step_name` against the limits, given the context
+    /// it is about to be merged into. With `namespaced`, the copy under
+    /// `ctx.steps.<step_name>` is counted too. Returns the output to merge,
+    /// with values replaced by markers in truncate mode.
+    pub(super) fn enforce(
+        &self,
+        step_name: &str,
+        ctx: &Context,
+        mut output: NodeOutput,
+        namespaced: bool,
+    ) -> Result<NodeOutput> {
+        if !self.is_enabled() {
+            return Ok(output);
+        }
+
+        let mut sizes: Vec<(String, usize)> = output
+            .iter()
+            .map(|(k, v)| (k.clone(), json_len(v)))
+            .collect();
+
+        if let Some(max) = self.max_value_bytes {
+            for (key, size) in sizes.iter_mut().filter(|(_, size)| *size > max) {
+                match self.action {
+                    ContextLimitAction::Fail => {
+                        return Err(NodeError::new(
+                            NodeErrorKind::ContextLimit,
+                            format!(
+                                "Step '{step_name}' wrote {size} bytes to ctx.{key}, over the \
+                             per-value limit of {max} bytes (IRONFLOW_MAX_CONTEXT_VALUE_BYTES)"
+                            ),
+                        )
+                        .into());
+                    }
+                    ContextLimitAction::Truncate => {
+                        warn!(task = %step_name, key = %key, bytes = *size, limit = max, "Truncating oversized context value");
+                        let marker = truncation_marker(*size, max);
+                        *size = json_len(&marker);
+                        output.insert(key.clone(), marker);
+                    }
+                }
+            }
+        }
+
+        let Some(max) = self.max_context_bytes else {
+            return Ok(output);
+        };
+
+        // Size of the merged object: keys the step overwrites are counted
+        // from the new output only.
+        let kept: usize = ctx
+            .iter()
+            .filter(|(k, _)| !output.contains_key(*k))
+            .map(|(k, v)| entry_len(k, json_len(v)))
+            .sum();
+        let entries = ctx.keys().filter(|k| !output.contains_key(*k)).count() + sizes.len();
+        let mut total = kept
+            + sizes
+                .iter()
+                .map(|(k, size)| entry_len(k, *size))
+                .sum::<usize>()
+            + 2
+            + entries.saturating_sub(1);
+        // Every value then lands in the context twice.
+        let copies = if namespaced {
+            total += namespace_growth(step_name, ctx, &output, &sizes);
+            2
+        } else {
+            1
+        };
+        if total <= max {
+            return Ok(output);
+        }
+
+        match self.action {
+            ContextLimitAction::Fail => Err(NodeError::new(
+                NodeErrorKind::ContextLimit,
+                format!(
+                    "Context grew to {total} bytes after step '{step_name}', over the limit of \
+                     {max} bytes (IRONFLOW_MAX_CONTEXT_BYTES)"
+                ),
+            )
+            .into()),
+            ContextLimitAction::Truncate => {
+                // Shrink this step's largest values first; context written by
+                // earlier steps is left alone.
+                sizes.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+                for (key, size) in sizes {
+                    if total <= max {
+                        break;
+                    }
+                    let marker = truncation_marker(size, max);
+                    let marker_len = json_len(&marker);
+                    if marker_len >= size {
+                        continue;
+                    }
+                    warn!(task = %step_name, key = %key, bytes = size, "Truncating context value to stay under IRONFLOW_MAX_CONTEXT_BYTES");
+                    total = total.saturating_sub((size - marker_len) * copies);
+                    output.insert(key, marker);
+                }
+                if total > max {
+                    warn!(task = %step_name, bytes = total, limit = max, "Context still exceeds IRONFLOW_MAX_CONTEXT_BYTES after truncating step output");
+                }
+                Ok(output)
+            }
+        }
+    }
+}
+
+/// Length of a `"key":value` object entry.
+fn entry_len(key: &str, value_len: usize) -> usize {
+    json_len(key) + 1 + value_len
+}
+
+/// Bytes the context grows by when the output, with values of the given
+/// sizes, is also stored under `ctx.steps.<step_name>`.
+fn namespace_growth(
+    step_name: &str,
+    ctx: &Context,
+    output: &NodeOutput,
+    sizes: &[(String, usize)],
+) -> usize {
+    let step_len = 2
+        + sizes
+            .iter()
+            .map(|(k, size)| entry_len(k, *size))
+            .sum::<usize>()
+        + sizes.len().saturating_sub(1);
+    let entry = entry_len(step_name, step_len);
+    match output
+        .get("steps")
+        .or_else(|| ctx.get("steps"))
+        .and_then(|v| v.as_object())
+    {
+        Some(steps) => match steps.get(step_name) {
+            Some(old) => entry.saturating_sub(entry_len(step_name, json_len(old))),
+            None => entry + usize::from(!steps.is_empty()),
+        },
+        // A fresh `steps` object, plus the separator before it.
+        None => entry_len("steps", 2 + entry) + 1,
+    }
+}
+
+fn truncation_marker(original_bytes: usize, limit_bytes: usize) -> serde_json::Value {
+    serde_json::json!({
+        "_truncated": true,
+        "_original_bytes": original_bytes,
+        "_limit_bytes": limit_bytes,
+        "_note": "Value exceeded the workflow context size limit.",
+    })
+}
+
+/// Serialized JSON length without building the string.
+fn json_len<T: serde::Serialize + ?Sized>(value: &T) -> usize {
+    let mut counter = ByteCounter(0);
+    // Writing to a counter cannot fail.
+    let _ = serde_json::to_writer(&mut counter, value);
+    counter.0
+}
+
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
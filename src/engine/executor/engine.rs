@@ -3,21 +3,56 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use tokio::sync::{RwLock, Semaphore};
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn};
 use uuid::Uuid;
 
+use crate::engine::determinism::{self, Determinism};
 use crate::engine::events::{RunEvent, RunEventType};
+use crate::engine::run_meta::{self, RunMetadata};
 use crate::engine::types::*;
 use crate::nodes::NodeRegistry;
+use crate::nodes::database::RunTransactions;
+use crate::secrets::SecretResolver;
 use crate::storage::StateStore;
 use crate::storage::event_store::EventStore;
 
+use super::concurrency::ConcurrencyLimits;
+use super::context_limits::ContextLimits;
+use super::memo::MemoCache;
+
+/// How long `finally` steps may run once a run has been cancelled or has
+/// hit its timeout.
+const STOPPED_FINALLY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
 /// The core workflow execution engine.
 pub struct WorkflowEngine {
     pub(super) registry: Arc<NodeRegistry>,
     pub(super) store: Arc<dyn StateStore>,
     pub(super) events: Option<Arc<dyn EventStore>>,
     pub(super) max_concurrent_tasks: usize,
+    /// Per node type caps on concurrently running steps, on top of
+    /// `max_concurrent_tasks`, shared by every run of this engine.
+    pub(super) concurrency_limits: ConcurrencyLimits,
+    pub(super) determinism: Option<Arc<Determinism>>,
+    /// Resolves `${secret:...}` placeholders in step configs.
+    pub(super) secrets: Arc<SecretResolver>,
+    pub(super) task_options: TaskOptions,
+    /// Bound on memoized pure step outputs per run; `0` disables it.
+    pub(super) memo_entries: usize,
+    /// `flow:env` overlay to seed runs with, on top of `default`.
+    pub(super) environment: Option<String>,
+}
+
+/// Per-task behaviour switches, copied into every spawned task.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct TaskOptions {
+    /// Size bounds checked before a step's output is merged into the context.
+    pub(super) context_limits: ContextLimits,
+    /// Also store each output under `ctx.steps.<step_name>`.
+    pub(super) namespace_step_outputs: bool,
+    /// Collect node sub-timings into `TaskState::timings`.
+    pub(super) profile: bool,
 }
 
 impl WorkflowEngine {
@@ -39,9 +74,30 @@ impl WorkflowEngine {
             store,
             events: None,
             max_concurrent_tasks,
+            concurrency_limits: ConcurrencyLimits::default(),
+            determinism: None,
+            secrets: SecretResolver::shared(),
+            task_options: TaskOptions {
+                context_limits: ContextLimits::from_env(),
+                namespace_step_outputs: namespace_step_outputs_from_env(),
+                profile: false,
+            },
+            memo_entries: 0,
+            environment: None,
         }
     }
 
+    /// An engine that runs at most `limit` steps of a run at once, whatever
+    /// `IRONFLOW_MAX_CONCURRENT_TASKS` says. A permit is taken before each
+    /// step executes and released when it finishes, fails or times out.
+    pub fn with_concurrency(
+        registry: Arc<NodeRegistry>,
+        store: Arc<dyn StateStore>,
+        limit: usize,
+    ) -> Self {
+        Self::new(registry, store, Some(limit.max(1)))
+    }
+
     pub fn new_with_events(
         registry: Arc<NodeRegistry>,
         store: Arc<dyn StateStore>,
@@ -61,17 +117,304 @@ impl WorkflowEngine {
             store,
             events: Some(events),
             max_concurrent_tasks,
+            concurrency_limits: ConcurrencyLimits::default(),
+            determinism: None,
+            secrets: SecretResolver::shared(),
+            task_options: TaskOptions {
+                context_limits: ContextLimits::from_env(),
+                namespace_step_outputs: namespace_step_outputs_from_env(),
+                profile: false,
+            },
+            memo_entries: 0,
+            environment: None,
         }
     }
 
+    /// Run in deterministic mode: run IDs and the Lua `uuid4()`, `now_*` and
+    /// `math.random` helpers derive from `seed` and a frozen clock, so the
+    /// same flow produces the same output on every run. Meant for tests.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.determinism = Some(Arc::new(Determinism::new(seed)));
+        self
+    }
+
+    /// Also expose each step's output under `ctx.steps.<step_name>`, so two
+    /// steps emitting the same key can be told apart. The flat merge into the
+    /// top-level context still happens. Defaults to
+    /// `IRONFLOW_NAMESPACE_STEP_OUTPUTS` (off when unset).
+    pub fn with_namespaced_step_outputs(mut self, enabled: bool) -> Self {
+        self.task_options.namespace_step_outputs = enabled;
+        self
+    }
+
+    /// Record per-step sub-timings (HTTP time to first byte, query time, ...)
+    /// so `RunInfo::profile` can show where a run spent its time.
+    pub fn with_profiling(mut self, enabled: bool) -> Self {
+        self.task_options.profile = enabled;
+        self
+    }
+
+    /// Bound the size of the workflow context so a runaway step (say, a
+    /// huge HTTP body fanned out by `foreach`) cannot bloat the run record.
+    /// Defaults to `ContextLimits::from_env()`.
+    pub fn with_context_limits(mut self, limits: ContextLimits) -> Self {
+        self.task_options.context_limits = limits;
+        self
+    }
+
+    /// Use `secrets` for `${secret:<provider>:<path>}` placeholders, e.g. one
+    /// with extra providers registered. Defaults to the process-wide
+    /// [`SecretResolver::shared`] with the built-in providers.
+    pub fn with_secrets(mut self, secrets: Arc<SecretResolver>) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    /// Cap how many steps of a given node type run at once, e.g.
+    /// `{"llm": 2}` to stay under a provider's rate limit. The slots are
+    /// shared by every run of this engine, and by other engines given a clone
+    /// of the same [`ConcurrencyLimits`]. The per-run `max_concurrent_tasks`
+    /// cap still applies; a step needs a slot from both. A limit of `0` is
+    /// treated as `1`.
+    pub fn with_concurrency_limits(mut self, limits: impl Into<ConcurrencyLimits>) -> Self {
+        self.concurrency_limits = limits.into();
+        self
+    }
+
+    /// Reuse the output of a pure node (see
+    /// [`Node::is_pure`](crate::nodes::Node::is_pure)) when a later step of
+    /// the same run calls it with the same config and context, instead of
+    /// executing it again. At most `max_entries` outputs are kept per run;
+    /// `0` (the default) turns memoization off.
+    pub fn with_memoization(mut self, max_entries: usize) -> Self {
+        self.memo_entries = max_entries;
+        self
+    }
+
+    /// Seed runs with the `flow:env(name, ...)` overlay, layered over the
+    /// `default` one. Without it, a run started by a step of another run
+    /// (e.g. a subworkflow) uses that run's environment, and any other run
+    /// uses `IRONFLOW_ENV`. Naming an environment a flow does not declare
+    /// fails its runs, unless the flow declares none.
+    pub fn with_environment(mut self, environment: Option<String>) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    pub(super) fn active_environment(&self) -> Option<String> {
+        self.environment
+            .clone()
+            .or_else(run_meta::current_environment)
+            .or_else(|| std::env::var("IRONFLOW_ENV").ok().filter(|e| !e.is_empty()))
+    }
+
+    /// The engine's own seed, or the one inherited from the calling task
+    /// (e.g. a subworkflow started by a seeded parent run).
+    fn active_determinism(&self) -> Option<Arc<Determinism>> {
+        self.determinism.clone().or_else(determinism::current)
+    }
+
     /// Execute a flow definition and return the run ID.
     pub async fn execute(&self, flow: &FlowDefinition, initial_ctx: Context) -> Result<String> {
-        let run_id = Uuid::new_v4().to_string();
-        let flow_name = flow.name.clone();
+        let run_id = match self.active_determinism() {
+            Some(d) => d.next_uuid(),
+            None => Uuid::new_v4(),
+        };
+        self.execute_with_id(flow, initial_ctx, run_id.to_string())
+            .await
+    }
+
+    /// Execute a flow that stops early once `cancel` is triggered: no further
+    /// steps are scheduled, in-flight steps are aborted and marked failed,
+    /// and the run ends as `cancelled`.
+    pub async fn execute_with_cancel(
+        &self,
+        flow: &FlowDefinition,
+        initial_ctx: Context,
+        cancel: CancellationToken,
+    ) -> Result<String> {
+        let run_id = match self.active_determinism() {
+            Some(d) => d.next_uuid(),
+            None => Uuid::new_v4(),
+        };
+        self.execute_with_id_and_cancel(flow, initial_ctx, run_id.to_string(), cancel)
+            .await
+    }
+
+    /// Execute a flow under a caller-chosen run ID, so the caller can track
+    /// the run before it finishes (e.g. the API server during shutdown).
+    pub async fn execute_with_id(
+        &self,
+        flow: &FlowDefinition,
+        initial_ctx: Context,
+        run_id: String,
+    ) -> Result<String> {
+        self.execute_with_id_and_cancel(flow, initial_ctx, run_id, CancellationToken::new())
+            .await
+    }
 
+    /// [`execute_with_id`](Self::execute_with_id) with a cancel token, as in
+    /// [`execute_with_cancel`](Self::execute_with_cancel).
+    pub async fn execute_with_id_and_cancel(
+        &self,
+        flow: &FlowDefinition,
+        initial_ctx: Context,
+        run_id: String,
+        cancel: CancellationToken,
+    ) -> Result<String> {
         // Validate the DAG
+        self.topological_sort(flow)?;
+
+        // Flow vars and environment overlays fill in whatever the caller's
+        // context leaves out.
+        let initial_ctx = flow.seed_context(self.active_environment().as_deref(), initial_ctx)?;
+
+        // A duplicate delivery returns the run that already claimed its hash.
+        // A key already pointing at `run_id` was claimed for this run by
+        // `claim_run_id`.
+        if let Some(existing) = self.claim_dedupe_key(flow, &initial_ctx, &run_id).await?
+            && existing != run_id
+        {
+            info!(run_id = %existing, flow = %flow.name, "Skipping duplicate run");
+            return Ok(existing);
+        }
+
+        self.run_flow(flow, initial_ctx, run_id, HashMap::new(), cancel)
+            .await
+    }
+
+    /// Claim `flow`'s dedupe key for `run_id` before executing it, so a
+    /// caller that answers before the run starts can report the run that
+    /// will actually handle the request. Returns `run_id`, or the ID of an
+    /// earlier run that owns the key; in that case executing `run_id` would
+    /// return that ID without running. Flows without `dedupe` always get
+    /// `run_id` back.
+    pub async fn claim_run_id(
+        &self,
+        flow: &FlowDefinition,
+        initial_ctx: &Context,
+        run_id: &str,
+    ) -> Result<String> {
+        let seeded =
+            flow.seed_context(self.active_environment().as_deref(), initial_ctx.clone())?;
+        Ok(self
+            .claim_dedupe_key(flow, &seeded, run_id)
+            .await?
+            .unwrap_or_else(|| run_id.to_string()))
+    }
+
+    /// Point `flow`'s dedupe hash of the seeded context at `run_id`, unless
+    /// an unexpired entry exists; see [`StateStore::claim_dedupe_key`].
+    async fn claim_dedupe_key(
+        &self,
+        flow: &FlowDefinition,
+        seeded_ctx: &Context,
+        run_id: &str,
+    ) -> Result<Option<String>> {
+        let Some(ref dedupe) = flow.dedupe else {
+            return Ok(None);
+        };
+        let key = dedupe.hash(&flow.name, seeded_ctx);
+        let ttl = std::time::Duration::from_secs_f64(dedupe.ttl_s);
+        self.store.claim_dedupe_key(&key, run_id, ttl).await
+    }
+
+    /// Start a new run of `flow` that picks up where `original` failed:
+    /// steps that succeeded keep their recorded output and are not run
+    /// again, everything else (the failed steps and whatever they skipped)
+    /// runs with fresh attempts. The new run starts from `original`'s final
+    /// context, minus its `_error*` keys, with `_retried_from` set to the
+    /// original run ID.
+    pub async fn retry(&self, flow: &FlowDefinition, original: &RunInfo) -> Result<String> {
+        if original.flow_name != flow.name {
+            anyhow::bail!(
+                "Run '{}' belongs to flow '{}', not '{}'",
+                original.id,
+                original.flow_name,
+                flow.name
+            );
+        }
+        match original.status {
+            RunStatus::Failed | RunStatus::Stalled | RunStatus::Cancelled => {}
+            RunStatus::Success => anyhow::bail!("Run '{}' has no failed steps", original.id),
+            RunStatus::Pending | RunStatus::Running => {
+                anyhow::bail!("Run '{}' has not finished yet", original.id)
+            }
+        }
+
+        let reused: HashMap<String, TaskState> = flow
+            .steps
+            .iter()
+            .filter_map(|step| original.tasks.get(&step.name))
+            .filter(|task| task.status == TaskStatus::Success)
+            .map(|task| (task.name.clone(), task.clone()))
+            .collect();
+
+        let mut initial_ctx = original.ctx.clone();
+        initial_ctx.retain(|key, _| !key.starts_with("_error"));
+        initial_ctx.insert(
+            "_retried_from".to_string(),
+            serde_json::Value::String(original.id.clone()),
+        );
+
+        let run_id = match self.active_determinism() {
+            Some(d) => d.next_uuid(),
+            None => Uuid::new_v4(),
+        };
+        info!(run_id = %run_id, retried_from = %original.id, reused = reused.len(), "Retrying run");
+        self.run_flow(
+            flow,
+            initial_ctx,
+            run_id.to_string(),
+            reused,
+            CancellationToken::new(),
+        )
+        .await
+    }
+
+    /// Run `flow` under `run_id`. Steps in `completed_tasks` are recorded
+    /// with the given state and treated as already done.
+    async fn run_flow(
+        &self,
+        flow: &FlowDefinition,
+        initial_ctx: Context,
+        run_id: String,
+        completed_tasks: HashMap<String, TaskState>,
+        cancel: CancellationToken,
+    ) -> Result<String> {
+        let flow_name = flow.name.clone();
+        let run_determinism = self
+            .active_determinism()
+            .map(|d| Arc::new(d.child(&run_id)));
+
+        let run_meta = Arc::new(RunMetadata::new(
+            &run_id,
+            &flow_name,
+            self.active_environment(),
+        ));
+        // Rolls back any `db_transaction` the run leaves open, however it ends.
+        let _transactions = RunTransactions::new(&run_id);
+        let memo = (self.memo_entries > 0).then(|| Arc::new(MemoCache::new(self.memo_entries)));
+
         let execution_order = self.topological_sort(flow)?;
 
+        // The flow's own timeout stops the run through the same path as a
+        // cancel, on a child token so the caller's token is left alone.
+        let cancel = cancel.child_token();
+        let timed_out = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let _timeout_guard = flow.timeout_s.map(|timeout_s| {
+            let cancel = cancel.clone();
+            let timed_out = timed_out.clone();
+            AbortOnDrop(
+                tokio::spawn(async move {
+                    tokio::time::sleep(std::time::Duration::from_secs_f64(timeout_s)).await;
+                    timed_out.store(true, std::sync::atomic::Ordering::SeqCst);
+                    cancel.cancel();
+                })
+                .abort_handle(),
+            )
+        });
+
         // Initialize run in state store
         self.store
             .init_run(&run_id, &flow_name, &initial_ctx)
@@ -89,8 +432,13 @@ impl WorkflowEngine {
 
         // Initialize all task states
         for step in &flow.steps {
-            let task_state = TaskState::new(&step.name, &step.node_type);
-            self.store.upsert_task(&run_id, &task_state).await?;
+            match completed_tasks.get(&step.name) {
+                Some(task_state) => self.store.upsert_task(&run_id, task_state).await?,
+                None => {
+                    let task_state = TaskState::new(&step.name, &step.node_type);
+                    self.store.upsert_task(&run_id, &task_state).await?;
+                }
+            }
         }
 
         info!(run_id = %run_id, flow = %flow_name, "Starting workflow execution");
@@ -120,28 +468,72 @@ impl WorkflowEngine {
         // per write, not one per read.
         let ctx: Arc<RwLock<Arc<Context>>> = Arc::new(RwLock::new(Arc::new(initial_ctx)));
         let semaphore = Arc::new(Semaphore::new(self.max_concurrent_tasks));
-        let completed: Arc<RwLock<HashSet<String>>> = Arc::new(RwLock::new(HashSet::new()));
+        let completed: Arc<RwLock<HashSet<String>>> =
+            Arc::new(RwLock::new(completed_tasks.keys().cloned().collect()));
         let failed: Arc<RwLock<HashSet<String>>> = Arc::new(RwLock::new(HashSet::new()));
         // Steps already executed as on_error handlers (skip in normal scheduling)
         let error_handled: Arc<RwLock<HashSet<String>>> = Arc::new(RwLock::new(HashSet::new()));
+        // Steps skipped because their branch was not taken, directly or via
+        // every one of their dependencies.
+        let mut route_skipped: HashSet<String> = HashSet::new();
+
+        // Everything before the `finally` steps runs in here, so a store
+        // error or a panicked task still reaches them.
+        let outcome: Result<Option<bool>> = async {
+            // Execute in phases from topological order
+            for phase in &execution_order {
+                if cancel.is_cancelled() {
+                    break;
+                }
+                let mut handles = Vec::new();
 
-        // Execute in phases from topological order
-        for phase in &execution_order {
-            let mut handles = Vec::new();
+                for step_name in phase {
+                    if cancel.is_cancelled() {
+                        break;
+                    }
+                    if completed_tasks.contains_key(step_name) {
+                        continue;
+                    }
 
-            for step_name in phase {
-                let step = step_map[step_name].clone();
-                // `step` is now Arc<StepDefinition> — .clone() is a ref-count bump.
+                    let step = step_map[step_name].clone();
+                    // `step` is now Arc<StepDefinition> — .clone() is a ref-count bump.
 
-                // Skip steps that are on_error targets — they only run
-                // when triggered by an error, never in normal scheduling
-                if error_only_steps.contains(step_name) {
-                    let handled = error_handled.read().await;
-                    if handled.contains(step_name) {
-                        // Already ran as error handler — mark completed
-                        completed.write().await.insert(step_name.clone());
-                    } else {
-                        // Never triggered — mark as skipped so it doesn't stay Pending
+                    // Skip steps that are on_error targets — they only run
+                    // when triggered by an error, never in normal scheduling
+                    if error_only_steps.contains(step_name) {
+                        let handled = error_handled.read().await;
+                        if handled.contains(step_name) {
+                            // Already ran as error handler — mark completed
+                            completed.write().await.insert(step_name.clone());
+                        } else {
+                            // Never triggered — mark as skipped so it doesn't stay Pending
+                            let mut task_state = TaskState::new(&step.name, &step.node_type);
+                            task_state.status = TaskStatus::Skipped;
+                            self.store.upsert_task(&run_id, &task_state).await?;
+                            self.publish_event(
+                                RunEvent::task(
+                                    &run_id,
+                                    &step.name,
+                                    &step.node_type,
+                                    RunEventType::TaskSkipped,
+                                    TaskStatus::Skipped,
+                                    None,
+                                )
+                                .with_reason("error handler was not triggered"),
+                            )
+                            .await;
+                        }
+                        // Either way, skip normal scheduling
+                        continue;
+                    }
+
+                    // Check if any dependency failed
+                    let dep_failed = {
+                        let failed_set = failed.read().await;
+                        step.dependencies.iter().any(|d| failed_set.contains(d))
+                    };
+                    if dep_failed {
+                        warn!(task = %step_name, "Skipping task — dependency failed");
                         let mut task_state = TaskState::new(&step.name, &step.node_type);
                         task_state.status = TaskStatus::Skipped;
                         self.store.upsert_task(&run_id, &task_state).await?;
@@ -154,47 +546,19 @@ impl WorkflowEngine {
                                 TaskStatus::Skipped,
                                 None,
                             )
-                            .with_reason("error handler was not triggered"),
+                            .with_reason("dependency failed"),
                         )
                         .await;
+                        failed.write().await.insert(step_name.clone());
+                        continue;
                     }
-                    // Either way, skip normal scheduling
-                    continue;
-                }
-
-                // Check if any dependency failed
-                let dep_failed = {
-                    let failed_set = failed.read().await;
-                    step.dependencies.iter().any(|d| failed_set.contains(d))
-                };
-                if dep_failed {
-                    warn!(task = %step_name, "Skipping task — dependency failed");
-                    let mut task_state = TaskState::new(&step.name, &step.node_type);
-                    task_state.status = TaskStatus::Skipped;
-                    self.store.upsert_task(&run_id, &task_state).await?;
-                    self.publish_event(
-                        RunEvent::task(
-                            &run_id,
-                            &step.name,
-                            &step.node_type,
-                            RunEventType::TaskSkipped,
-                            TaskStatus::Skipped,
-                            None,
-                        )
-                        .with_reason("dependency failed"),
-                    )
-                    .await;
-                    failed.write().await.insert(step_name.clone());
-                    continue;
-                }
 
-                // Check route condition
-                if let Some(ref route) = step.route {
-                    let ctx_read = ctx.read().await;
-                    let should_skip = !self.check_route(&step, route, ctx_read.as_ref());
-                    drop(ctx_read);
-                    if should_skip {
-                        info!(task = %step_name, route = %route, "Skipping task — route not matched");
+                    // A step fed only by skipped branches is skipped as well; one
+                    // satisfied dependency is enough for it to run.
+                    if !step.dependencies.is_empty()
+                        && step.dependencies.iter().all(|d| route_skipped.contains(d))
+                    {
+                        info!(task = %step_name, "Skipping task — all dependencies were skipped");
                         let mut task_state = TaskState::new(&step.name, &step.node_type);
                         task_state.status = TaskStatus::Skipped;
                         self.store.upsert_task(&run_id, &task_state).await?;
@@ -207,65 +571,212 @@ impl WorkflowEngine {
                                 TaskStatus::Skipped,
                                 None,
                             )
-                            .with_reason("route condition was not matched"),
+                            .with_reason("all dependencies were skipped"),
                         )
                         .await;
                         completed.write().await.insert(step_name.clone());
+                        route_skipped.insert(step_name.clone());
                         continue;
                     }
-                }
 
-                let registry = self.registry.clone();
-                let store = self.store.clone();
-                let events = self.events.clone();
-                let ctx = ctx.clone();
-                let semaphore = semaphore.clone();
-                let completed = completed.clone();
-                let failed = failed.clone();
-                let error_handled = error_handled.clone();
-                let run_id = run_id.clone();
-                let step_map = step_map.clone();
-
-                let handle = tokio::spawn(async move {
-                    let _permit = semaphore.acquire().await.unwrap();
-                    let result =
-                        Self::run_task(&registry, &store, events.as_ref(), &run_id, &step, &ctx)
+                    // Check route condition
+                    if let Some(ref route) = step.route {
+                        let ctx_read = ctx.read().await;
+                        let should_skip = !self.check_route(&step, route, ctx_read.as_ref());
+                        drop(ctx_read);
+                        if should_skip {
+                            info!(task = %step_name, route = %route, "Skipping task — route not matched");
+                            let mut task_state = TaskState::new(&step.name, &step.node_type);
+                            task_state.status = TaskStatus::Skipped;
+                            self.store.upsert_task(&run_id, &task_state).await?;
+                            self.publish_event(
+                                RunEvent::task(
+                                    &run_id,
+                                    &step.name,
+                                    &step.node_type,
+                                    RunEventType::TaskSkipped,
+                                    TaskStatus::Skipped,
+                                    None,
+                                )
+                                .with_reason("route condition was not matched"),
+                            )
                             .await;
-
-                    match result {
-                        Ok(()) => {
-                            completed.write().await.insert(step.name.clone());
+                            completed.write().await.insert(step_name.clone());
+                            route_skipped.insert(step_name.clone());
+                            continue;
                         }
-                        Err(e) => {
-                            Self::handle_step_error(
+                    }
+
+                    let registry = self.registry.clone();
+                    let store = self.store.clone();
+                    let events = self.events.clone();
+                    let ctx = ctx.clone();
+                    let semaphore = semaphore.clone();
+                    let type_semaphore = self.concurrency_limits.semaphore(&step.node_type);
+                    let completed = completed.clone();
+                    let failed = failed.clone();
+                    let error_handled = error_handled.clone();
+                    let run_id = run_id.clone();
+                    let step_map = step_map.clone();
+                    let run_determinism = run_determinism.clone();
+                    let run_meta = run_meta.clone();
+                    let secrets = self.secrets.clone();
+                    let task_options = self.task_options;
+                    let memo = memo.clone();
+                    let cancel = cancel.clone();
+
+                    let handle = tokio::spawn(determinism::maybe_scope(run_determinism, async move {
+                        let work = async {
+                            // Wait for the node-type slot first so a throttled step
+                            // does not sit on a global slot other steps could use.
+                            let _type_permit = match type_semaphore {
+                                Some(ref type_semaphore) => {
+                                    Some(type_semaphore.acquire().await.unwrap())
+                                }
+                                None => None,
+                            };
+                            let _permit = semaphore.acquire().await.unwrap();
+                            let result = Self::run_task(
                                 &registry,
                                 &store,
                                 events.as_ref(),
                                 &run_id,
                                 &step,
-                                &step_map,
                                 &ctx,
-                                &completed,
-                                &failed,
-                                &error_handled,
-                                e,
+                                &run_meta,
+                                &secrets,
+                                task_options,
+                                memo.as_deref(),
                             )
                             .await;
+
+                            match result {
+                                Ok(()) => {
+                                    completed.write().await.insert(step.name.clone());
+                                }
+                                Err(e) => {
+                                    Self::handle_step_error(
+                                        &registry,
+                                        &store,
+                                        events.as_ref(),
+                                        &run_id,
+                                        &step,
+                                        &step_map,
+                                        &ctx,
+                                        &completed,
+                                        &failed,
+                                        &error_handled,
+                                        &run_meta,
+                                        &secrets,
+                                        task_options,
+                                        memo.as_deref(),
+                                        e,
+                                    )
+                                    .await;
+                                }
+                            }
+                        };
+                        // Dropping `work` aborts the node mid-flight; the task
+                        // is marked failed once the phase has wound down.
+                        tokio::select! {
+                            _ = cancel.cancelled() => {}
+                            _ = work => {}
                         }
-                    }
-                });
-                handles.push(handle);
+                    }));
+                    handles.push(handle);
+                }
+
+                // Wait for all tasks in this phase to complete
+                for handle in handles {
+                    handle.await?;
+                }
+            }
+
+
+            if cancel.is_cancelled() {
+                return Ok(None);
             }
 
-            // Wait for all tasks in this phase to complete
-            for handle in handles {
-                handle.await?;
+            // Flow-level hooks run once the DAG is done. A failing on_error hook
+            // leaves the original failure in place; a failing on_success or
+            // finally hook fails an otherwise successful run.
+            let dag_failed = !failed.read().await.is_empty();
+            let hooks = if dag_failed {
+                &flow.hooks.on_error
+            } else {
+                &flow.hooks.on_success
+            };
+            if dag_failed && !hooks.is_empty() {
+                let failed_set = failed.read().await;
+                self.inject_run_failure(&run_id, &failed_set, &ctx).await?;
             }
+            let hooks_succeeded = self
+                .run_hooks(
+                    &run_id,
+                    hooks,
+                    &ctx,
+                    &run_meta,
+                    run_determinism.clone(),
+                    memo.as_deref(),
+                )
+                .await;
+            Ok(Some(!dag_failed && hooks_succeeded))
         }
+        .await;
+
+        // `finally` steps run on every outcome, after the other hooks. The
+        // stop of a cancelled or timed-out run does not cut them short, but
+        // they get at most `STOPPED_FINALLY_TIMEOUT` to wind down.
+        let finally = self.run_hooks(
+            &run_id,
+            &flow.hooks.finally,
+            &ctx,
+            &run_meta,
+            run_determinism.clone(),
+            memo.as_deref(),
+        );
+        let finally_succeeded = if cancel.is_cancelled() {
+            match tokio::time::timeout(STOPPED_FINALLY_TIMEOUT, finally).await {
+                Ok(succeeded) => succeeded,
+                Err(_) => {
+                    warn!(
+                        run_id = %run_id,
+                        limit_s = STOPPED_FINALLY_TIMEOUT.as_secs(),
+                        "finally steps of a stopped run did not finish in time"
+                    );
+                    false
+                }
+            }
+        } else {
+            finally.await
+        };
+
+        let dag_and_hooks_succeeded = match outcome {
+            Ok(Some(succeeded)) => succeeded,
+            Ok(None) => {
+                let stop = if timed_out.load(std::sync::atomic::Ordering::SeqCst) {
+                    RunStop::TimedOut(flow.timeout_s.unwrap_or_default())
+                } else {
+                    RunStop::Cancelled
+                };
+                return self.finish_stopped(&run_id, &flow_name, &ctx, stop).await;
+            }
+            Err(e) => {
+                // Best effort: the store may be what failed.
+                let final_ctx = ctx.read().await.clone();
+                let recorded = async {
+                    self.store.update_ctx(&run_id, final_ctx.as_ref()).await?;
+                    self.store.set_run_status(&run_id, RunStatus::Failed).await
+                };
+                if let Err(store_err) = recorded.await {
+                    warn!(run_id = %run_id, error = %store_err, "Failed to record the failed run");
+                }
+                return Err(e);
+            }
+        };
 
         // Determine final status
-        let failed_set = failed.read().await;
-        let final_status = if failed_set.is_empty() {
+        let final_status = if dag_and_hooks_succeeded && finally_succeeded {
             RunStatus::Success
         } else {
             RunStatus::Failed
@@ -297,6 +808,86 @@ impl WorkflowEngine {
         Ok(run_id)
     }
 
+    /// Close out a run stopped early: steps that were mid-flight fail with
+    /// the stop reason, steps that never started are skipped, and the run
+    /// is stored as `cancelled` (or `stalled` when its timeout elapsed).
+    async fn finish_stopped(
+        &self,
+        run_id: &str,
+        flow_name: &str,
+        ctx: &RwLock<Arc<Context>>,
+        stop: RunStop,
+    ) -> Result<String> {
+        let (run_status, task_error, skip_reason) = match stop {
+            RunStop::Cancelled => (
+                RunStatus::Cancelled,
+                "cancelled by request".to_string(),
+                "run cancelled",
+            ),
+            RunStop::TimedOut(timeout_s) => (
+                RunStatus::Stalled,
+                format!("run timed out after {}s", timeout_s),
+                "run timed out",
+            ),
+        };
+        let info = self.store.get_run_info(run_id).await?;
+        let mut tasks: Vec<TaskState> = info.tasks.into_values().collect();
+        tasks.sort_by(|a, b| a.name.cmp(&b.name));
+        for mut task in tasks {
+            let (event_type, reason) = match task.status {
+                TaskStatus::Running => {
+                    task.status = TaskStatus::Failed;
+                    task.error = Some(task_error.clone());
+                    task.finished = Some(chrono::Utc::now());
+                    (RunEventType::TaskFailed, None)
+                }
+                TaskStatus::Pending => {
+                    task.status = TaskStatus::Skipped;
+                    (RunEventType::TaskSkipped, Some(skip_reason))
+                }
+                _ => continue,
+            };
+            self.store.upsert_task(run_id, &task).await?;
+            let mut event = RunEvent::task(
+                run_id,
+                &task.name,
+                &task.node_type,
+                event_type,
+                task.status.clone(),
+                None,
+            );
+            if let Some(error) = &task.error {
+                event = event.with_error(error.clone());
+            }
+            if let Some(reason) = reason {
+                event = event.with_reason(reason);
+            }
+            self.publish_event(event).await;
+        }
+
+        let mut final_ctx = ctx.read().await.as_ref().clone();
+        if run_status == RunStatus::Stalled {
+            final_ctx.insert(
+                "_error_message".to_string(),
+                serde_json::Value::String(task_error.clone()),
+            );
+        }
+        self.store.update_ctx(run_id, &final_ctx).await?;
+        self.store
+            .set_run_status(run_id, run_status.clone())
+            .await?;
+        self.publish_event(RunEvent::run(
+            run_id,
+            flow_name,
+            RunEventType::RunFinished,
+            run_status,
+        ))
+        .await;
+
+        warn!(run_id = %run_id, reason = %task_error, "Workflow execution stopped early");
+        Ok(run_id.to_string())
+    }
+
     pub(super) async fn publish_event(&self, event: RunEvent) {
         Self::publish_event_ref(self.events.as_ref(), event).await;
     }
@@ -309,3 +900,26 @@ impl WorkflowEngine {
         }
     }
 }
+
+/// Why a run ended before all its steps were scheduled.
+enum RunStop {
+    Cancelled,
+    /// The flow's `timeout_s` elapsed.
+    TimedOut(f64),
+}
+
+/// Aborts the wrapped task when dropped.
+struct AbortOnDrop(tokio::task::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+fn namespace_step_outputs_from_env() -> bool {
+    std::env::var("IRONFLOW_NAMESPACE_STEP_OUTPUTS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(false)
+}
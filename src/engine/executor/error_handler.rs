@@ -4,16 +4,20 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::error;
 
-use crate::engine::types::{Context, StepDefinition};
+use crate::engine::run_meta::RunMetadata;
+use crate::engine::types::{Context, NodeError, StepDefinition};
 use crate::nodes::NodeRegistry;
+use crate::secrets::SecretResolver;
 use crate::storage::StateStore;
 use crate::storage::event_store::EventStore;
 
-use super::engine::WorkflowEngine;
+use super::engine::{TaskOptions, WorkflowEngine};
+use super::memo::MemoCache;
 
 impl WorkflowEngine {
     /// Handle an error for a step that has an `on_error` handler configured.
-    /// Injects `_error_message`, `_error_step`, `_error_node_type` into context,
+    /// Injects `_error_message`, `_error_step`, `_error_node_type`, `_error_kind`
+    /// and `_error_detail` (the classified [`NodeError`]) into context,
     /// runs the handler step, and updates `completed`/`failed`/`error_handled` sets.
     #[allow(clippy::too_many_arguments)]
     pub(super) async fn handle_step_error(
@@ -27,12 +31,16 @@ impl WorkflowEngine {
         completed: &Arc<RwLock<HashSet<String>>>,
         failed: &Arc<RwLock<HashSet<String>>>,
         error_handled: &Arc<RwLock<HashSet<String>>>,
+        run_meta: &Arc<RunMetadata>,
+        secrets: &SecretResolver,
+        task_options: TaskOptions,
+        memo: Option<&MemoCache>,
         e: anyhow::Error,
     ) {
         let error_step_name = match &step.on_error {
             Some(name) => name.clone(),
             None => {
-                error!(task = %step.name, error = %e, "Task failed");
+                error!(task = %step.name, error = %format!("{:#}", e), "Task failed");
                 failed.write().await.insert(step.name.clone());
                 return;
             }
@@ -60,11 +68,29 @@ impl WorkflowEngine {
                 "_error_node_type".to_string(),
                 serde_json::Value::String(step.node_type.clone()),
             );
+            let detail = serde_json::to_value(NodeError::classify(&e)).unwrap_or_default();
+            inner.insert(
+                "_error_kind".to_string(),
+                detail.get("kind").cloned().unwrap_or_default(),
+            );
+            inner.insert("_error_detail".to_string(), detail);
         }
 
         // Run the error handler step
         if let Some(error_step) = step_map.get(&error_step_name) {
-            let err_result = Self::run_task(registry, store, events, run_id, error_step, ctx).await;
+            let err_result = Self::run_task(
+                registry,
+                store,
+                events,
+                run_id,
+                error_step,
+                ctx,
+                run_meta,
+                secrets,
+                task_options,
+                memo,
+            )
+            .await;
 
             match err_result {
                 Ok(()) => {
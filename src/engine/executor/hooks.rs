@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::Result;
+use tokio::sync::RwLock;
+use tracing::{error, info};
+
+use crate::engine::determinism::{self, Determinism};
+use crate::engine::run_meta::RunMetadata;
+use crate::engine::types::{Context, StepDefinition, TaskState};
+
+use super::engine::WorkflowEngine;
+use super::memo::MemoCache;
+
+impl WorkflowEngine {
+    /// Run flow-level hook steps one after another. A failing hook is
+    /// recorded on its task and logged, and the remaining hooks still run.
+    /// Returns whether every hook succeeded.
+    pub(super) async fn run_hooks(
+        &self,
+        run_id: &str,
+        hooks: &[StepDefinition],
+        ctx: &Arc<RwLock<Arc<Context>>>,
+        run_meta: &Arc<RunMetadata>,
+        run_determinism: Option<Arc<Determinism>>,
+        memo: Option<&MemoCache>,
+    ) -> bool {
+        let mut all_succeeded = true;
+        for hook in hooks {
+            info!(run_id = %run_id, hook = %hook.name, "Running flow hook");
+            let result = determinism::maybe_scope(
+                run_determinism.clone(),
+                Self::run_task(
+                    &self.registry,
+                    &self.store,
+                    self.events.as_ref(),
+                    run_id,
+                    hook,
+                    ctx,
+                    run_meta,
+                    &self.secrets,
+                    self.task_options,
+                    memo,
+                ),
+            )
+            .await;
+            if let Err(e) = result {
+                error!(hook = %hook.name, error = %format!("{:#}", e), "Flow hook failed");
+                all_succeeded = false;
+            }
+        }
+        all_succeeded
+    }
+
+    /// Describe the run's failure for `on_error` hooks: the first step to
+    /// fail goes into `_error_step`, `_error_message`, `_error_node_type`,
+    /// `_error_kind` and `_error_detail`, and every failed step into
+    /// `_failed_steps`.
+    pub(super) async fn inject_run_failure(
+        &self,
+        run_id: &str,
+        failed: &HashSet<String>,
+        ctx: &RwLock<Arc<Context>>,
+    ) -> Result<()> {
+        let info = self.store.get_run_info(run_id).await?;
+        let mut failed_tasks: Vec<&TaskState> = info
+            .tasks
+            .values()
+            .filter(|task| task.status.is_failed() && failed.contains(&task.name))
+            .collect();
+        failed_tasks.sort_by(|a, b| a.finished.cmp(&b.finished).then(a.name.cmp(&b.name)));
+
+        let mut ctx_write = ctx.write().await;
+        let inner = Arc::make_mut(&mut *ctx_write);
+        inner.insert(
+            "_failed_steps".to_string(),
+            failed_tasks
+                .iter()
+                .map(|task| serde_json::Value::String(task.name.clone()))
+                .collect(),
+        );
+        if let Some(first) = failed_tasks.first() {
+            inner.insert(
+                "_error_step".to_string(),
+                serde_json::Value::String(first.name.clone()),
+            );
+            inner.insert(
+                "_error_message".to_string(),
+                serde_json::Value::String(first.error.clone().unwrap_or_default()),
+            );
+            inner.insert(
+                "_error_node_type".to_string(),
+                serde_json::Value::String(first.node_type.clone()),
+            );
+            let detail = serde_json::to_value(&first.error_detail).unwrap_or_default();
+            inner.insert(
+                "_error_kind".to_string(),
+                detail.get("kind").cloned().unwrap_or_default(),
+            );
+            inner.insert("_error_detail".to_string(), detail);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+//! Per-run memoization of pure node outputs.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Mutex;
+
+use sha2::{Digest, Sha256};
+
+use crate::engine::types::{Context, NodeOutput};
+use crate::lua::interpolate::ctx_refs;
+
+/// Default bound on cached outputs per run when memoization is enabled.
+pub const DEFAULT_MEMO_ENTRIES: usize = 256;
+
+/// Outputs of pure steps that already ran in this run, keyed by
+/// [`memo_key`]. Holds at most `max_entries`; the oldest entry is evicted
+/// first.
+pub(super) struct MemoCache {
+    max_entries: usize,
+    inner: Mutex<MemoEntries>,
+}
+
+#[derive(Default)]
+struct MemoEntries {
+    outputs: HashMap<String, NodeOutput>,
+    order: VecDeque<String>,
+}
+
+impl MemoCache {
+    pub(super) fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries: max_entries.max(1),
+            inner: Mutex::new(MemoEntries::default()),
+        }
+    }
+
+    pub(super) fn get(&self, key: &str) -> Option<NodeOutput> {
+        self.inner.lock().unwrap().outputs.get(key).cloned()
+    }
+
+    pub(super) fn insert(&self, key: String, output: NodeOutput) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.outputs.contains_key(&key) {
+            return;
+        }
+        while inner.order.len() >= self.max_entries {
+            let Some(oldest) = inner.order.pop_front() else {
+                break;
+            };
+            inner.outputs.remove(&oldest);
+        }
+        inner.order.push_back(key.clone());
+        inner.outputs.insert(key, output);
+    }
+}
+
+/// Hash of everything a pure node's output can depend on: its type, its
+/// config, and the context minus the keys the step writes itself
+/// (`produced` and their `<key>_*` companions), so a repeat of the same
+/// step config is not told apart by its predecessor's output. A produced
+/// key the step also reads (`consumed`, or a `${ctx.<key>}` in the config)
+/// stays in the hash: re-encoding `data` into `data` must see the new
+/// `data`.
+///
+/// The `_step_name` the flow loader adds to every config is ignored, so two
+/// steps can share an output. When the node cannot say what it produces
+/// (e.g. `if_node` or a route-mode `assert` writes `_route_<step>`), the
+/// step name and the whole context are part of the key.
+pub(super) fn memo_key(
+    node_type: &str,
+    config: &serde_json::Value,
+    ctx: &Context,
+    produced: Option<&[String]>,
+    consumed: &[String],
+) -> String {
+    let read = ctx_refs(config);
+    let own_output = |key: &str| {
+        if consumed.iter().chain(&read).any(|r| r == key) {
+            return false;
+        }
+        produced.is_some_and(|keys| {
+            keys.iter().any(|p| {
+                key == p
+                    || key
+                        .strip_prefix(p.as_str())
+                        .is_some_and(|rest| rest.starts_with('_'))
+            })
+        })
+    };
+    // `Context` is a HashMap; sort it so equal contexts hash equally.
+    let inputs: BTreeMap<&str, &serde_json::Value> = ctx
+        .iter()
+        .filter(|(k, _)| !own_output(k))
+        .map(|(k, v)| (k.as_str(), v))
+        .collect();
+
+    let config = match (produced, config) {
+        (Some(_), serde_json::Value::Object(map)) if map.contains_key("_step_name") => {
+            let mut map = map.clone();
+            map.remove("_step_name");
+            serde_json::Value::Object(map)
+        }
+        _ => config.clone(),
+    };
+
+    let mut hasher = Sha256::new();
+    hasher.update(node_type.as_bytes());
+    hasher.update([0]);
+    hasher.update(serde_json::to_vec(&config).unwrap_or_default());
+    hasher.update([0]);
+    hasher.update(serde_json::to_vec(&inputs).unwrap_or_default());
+    hex::encode(hasher.finalize())
+}
@@ -1,7 +1,16 @@
+mod concurrency;
 mod context;
+mod context_limits;
 mod engine;
 mod error_handler;
+mod hooks;
+mod memo;
+mod plan;
 mod scheduler;
 mod task_runner;
 
+pub use concurrency::ConcurrencyLimits;
+pub use context_limits::{ContextLimitAction, ContextLimits};
 pub use engine::WorkflowEngine;
+pub use memo::DEFAULT_MEMO_ENTRIES;
+pub use plan::PlannedStep;
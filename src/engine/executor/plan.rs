@@ -0,0 +1,61 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::engine::types::{Context, FlowDefinition};
+use crate::lua::interpolate::interpolate_value_keeping_env;
+use crate::util::redact::redact_secrets;
+
+use super::engine::WorkflowEngine;
+
+/// One step of a dry-run plan, see [`WorkflowEngine::plan`].
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedStep {
+    /// 1-based scheduling phase; steps in the same phase may run concurrently.
+    pub phase: usize,
+    pub name: String,
+    pub node_type: String,
+    pub dependencies: Vec<String>,
+    pub route: Option<String>,
+    /// The step config with placeholders resolved against the initial
+    /// context and secret-looking values masked. Values that upstream steps
+    /// would produce are not available yet.
+    pub resolved_config: serde_json::Value,
+    /// `false` for side-effecting nodes and unknown node types.
+    pub pure: bool,
+}
+
+impl WorkflowEngine {
+    /// Work out what a run of `flow` would do without running it: the steps
+    /// in scheduling order with their resolved configs. No node is executed
+    /// and nothing is written to the state store.
+    pub fn plan(&self, flow: &FlowDefinition, initial_ctx: &Context) -> Result<Vec<PlannedStep>> {
+        let phases = self.topological_sort(flow)?;
+        let initial_ctx =
+            &flow.seed_context(self.active_environment().as_deref(), initial_ctx.clone())?;
+        let mut planned = Vec::with_capacity(flow.steps.len());
+
+        for (phase_idx, phase) in phases.iter().enumerate() {
+            // Keep declaration order within a phase so the plan is stable.
+            for step in flow.steps.iter().filter(|s| phase.contains(&s.name)) {
+                let mut resolved_config = interpolate_value_keeping_env(&step.config, initial_ctx);
+                if let Some(map) = resolved_config.as_object_mut() {
+                    map.retain(|key, _| !key.starts_with('_'));
+                }
+                planned.push(PlannedStep {
+                    phase: phase_idx + 1,
+                    name: step.name.clone(),
+                    node_type: step.node_type.clone(),
+                    dependencies: step.dependencies.clone(),
+                    route: step.route.clone(),
+                    resolved_config: redact_secrets(&resolved_config),
+                    pure: self
+                        .registry
+                        .get(&step.node_type)
+                        .is_some_and(|node| node.is_pure_with(&step.config)),
+                });
+            }
+        }
+
+        Ok(planned)
+    }
+}
@@ -87,8 +87,13 @@ impl WorkflowEngine {
         route: &str,
         ctx: &Context,
     ) -> bool {
-        // Look for _route_{dependency_name} keys in context
-        for dep in &step.dependencies {
+        // Look for _route_{dependency_name} keys in context, restricted to
+        // the declared route source when there is one
+        let sources = match step.route_from {
+            Some(ref from) => std::slice::from_ref(from),
+            None => step.dependencies.as_slice(),
+        };
+        for dep in sources {
             let route_key = format!("_route_{}", dep);
             if let Some(serde_json::Value::String(r)) = ctx.get(&route_key)
                 && r == route
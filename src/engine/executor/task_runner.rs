@@ -1,21 +1,31 @@
 use std::sync::Arc;
 
-use anyhow::{Context as _, Result, bail};
+use anyhow::{Context as _, Result};
 use chrono::Utc;
 use tokio::sync::RwLock;
 use tracing::{info, warn};
 
+use crate::engine::determinism;
 use crate::engine::events::{RunEvent, RunEventType};
-use crate::engine::types::{Context, StepDefinition, TaskState, TaskStatus};
+use crate::engine::profile;
+use crate::engine::run_meta::{self, RunMetadata};
+use crate::engine::types::{
+    Context, NodeError, NodeErrorKind, NodeOutput, StepDefinition, TaskState, TaskStatus,
+};
+use crate::lua::interpolate::interpolate_value_keeping_env;
 use crate::nodes::NodeRegistry;
+use crate::secrets::SecretResolver;
 use crate::storage::StateStore;
 use crate::storage::event_store::EventStore;
+use crate::util::redact::redact_secrets;
 
 use super::context::task_duration_ms;
-use super::engine::WorkflowEngine;
+use super::engine::{TaskOptions, WorkflowEngine};
+use super::memo::{MemoCache, memo_key};
 
 impl WorkflowEngine {
     /// Run a single task with retry logic.
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn run_task(
         registry: &NodeRegistry,
         store: &Arc<dyn StateStore>,
@@ -23,20 +33,37 @@ impl WorkflowEngine {
         run_id: &str,
         step: &StepDefinition,
         ctx: &Arc<RwLock<Arc<Context>>>,
+        run_meta: &Arc<RunMetadata>,
+        secrets: &SecretResolver,
+        options: TaskOptions,
+        memo: Option<&MemoCache>,
     ) -> Result<()> {
         let node = registry
             .get(&step.node_type)
             .with_context(|| format!("Unknown node type: {}", step.node_type))?;
 
+        let memo = memo.filter(|_| node.is_pure_with(&step.config));
+
         let max_attempts = step.retry.max_retries + 1;
+        let mut attempts_made = 0;
         let mut last_error = None;
 
         for attempt in 1..=max_attempts {
+            // Cheap snapshot — `Arc::clone` of the context pointer. The node
+            // borrows from the pointed-to `Context`; writers make-mut to a
+            // fresh Arc so this snapshot stays stable for the call.
+            let current_ctx: Arc<Context> = ctx.read().await.clone();
+
             // Update task state to running
             let mut task_state = TaskState::new(&step.name, &step.node_type);
             task_state.status = TaskStatus::Running;
             task_state.attempt = attempt;
+            attempts_made = attempt;
             task_state.started = Some(Utc::now());
+            task_state.resolved_config = Some(cap_for_history(
+                redact_secrets(&interpolate_value_keeping_env(&step.config, &current_ctx)),
+                "Resolved config exceeded IRONFLOW_MAX_TASK_OUTPUT_BYTES.",
+            ));
             store.upsert_task(run_id, &task_state).await?;
             Self::publish_event_ref(
                 events,
@@ -53,36 +80,89 @@ impl WorkflowEngine {
 
             info!(task = %step.name, attempt = attempt, max = max_attempts, "Running task");
 
-            // Cheap snapshot — `Arc::clone` of the context pointer. The node
-            // borrows from the pointed-to `Context`; writers make-mut to a
-            // fresh Arc so this snapshot stays stable for the call.
-            let current_ctx: Arc<Context> = ctx.read().await.clone();
+            let memo_key = memo.map(|_| {
+                let produced = node.produced_keys(&step.config);
+                memo_key(
+                    &step.node_type,
+                    &step.config,
+                    &current_ctx,
+                    produced.as_deref(),
+                    &node.consumed_keys(&step.config),
+                )
+            });
+            let cached = memo.zip(memo_key.as_deref()).and_then(|(m, k)| m.get(k));
 
-            let result = if let Some(timeout_s) = step.timeout_s {
-                let duration = std::time::Duration::from_secs_f64(timeout_s);
-                match tokio::time::timeout(duration, node.execute(&step.config, &current_ctx)).await
-                {
-                    Ok(r) => r,
-                    Err(_) => Err(anyhow::anyhow!("Task timed out after {}s", timeout_s)),
+            // Seeded runs give each step its own sequence so parallel steps
+            // stay reproducible regardless of scheduling order.
+            let execution = determinism::scope_child(
+                &step.name,
+                run_meta::scope(run_meta.clone(), &step.name, async {
+                    // Secrets are fetched per attempt and never stored in
+                    // `resolved_config`, which keeps the placeholders.
+                    let config = secrets.resolve_config(&step.config).await?;
+                    node.execute(&config, &current_ctx).await
+                }),
+            );
+            let mut timed_out = false;
+            let (result, timings) = profile::collect(options.profile, async {
+                if let Some(output) = cached {
+                    info!(task = %step.name, "Reusing memoized output");
+                    Ok(output)
+                } else if let Some(timeout_s) = step.timeout_s {
+                    let duration = std::time::Duration::from_secs_f64(timeout_s);
+                    match tokio::time::timeout(duration, execution).await {
+                        Ok(r) => r,
+                        Err(_) => {
+                            timed_out = true;
+                            Err(NodeError::new(
+                                NodeErrorKind::Timeout,
+                                format!("node timed out after {}s", timeout_s),
+                            )
+                            .into())
+                        }
+                    }
+                } else {
+                    execution.await
+                }
+            })
+            .await;
+            task_state.timings = timings;
+            if let (Some(memo), Some(key), Ok(output)) = (memo, memo_key, &result) {
+                memo.insert(key, output.clone());
+            }
+
+            // Merge output into context. Limits are checked under the write
+            // lock against the live context, which already holds whatever
+            // parallel steps merged while this one ran. `Arc::make_mut`
+            // clones the inner HashMap only when it's shared with a live
+            // reader; once cloned, future writes go in-place until the next
+            // reader snapshot.
+            let result = match result {
+                Ok(output) => {
+                    let mut ctx_write = ctx.write().await;
+                    options
+                        .context_limits
+                        .enforce(
+                            &step.name,
+                            &ctx_write,
+                            output,
+                            options.namespace_step_outputs,
+                        )
+                        .inspect(|output| {
+                            let inner = Arc::make_mut(&mut *ctx_write);
+                            for (k, v) in output {
+                                inner.insert(k.clone(), v.clone());
+                            }
+                            if options.namespace_step_outputs {
+                                insert_step_namespace(inner, &step.name, output);
+                            }
+                        })
                 }
-            } else {
-                node.execute(&step.config, &current_ctx).await
+                Err(e) => Err(e),
             };
 
             match result {
                 Ok(output) => {
-                    // Merge output into context. `Arc::make_mut` clones the
-                    // inner HashMap only when it's shared with a live reader;
-                    // once cloned, future writes go in-place until the next
-                    // reader snapshot.
-                    {
-                        let mut ctx_write = ctx.write().await;
-                        let inner = Arc::make_mut(&mut *ctx_write);
-                        for (k, v) in &output {
-                            inner.insert(k.clone(), v.clone());
-                        }
-                    }
-
                     // Update task state to success. `output` is a
                     // HashMap<String, Value> — convert it to a JSON object
                     // directly instead of going through `serde_json::to_value`,
@@ -95,18 +175,12 @@ impl WorkflowEngine {
                     // Cap what we persist in task history — huge outputs
                     // already landed in `ctx` via the merge above; there's
                     // no need to duplicate them in the run record.
-                    let max_task_bytes = crate::util::limits::max_task_output_bytes() as usize;
-                    let serialized_size = output_value.to_string().len();
-                    task_state.output = if serialized_size > max_task_bytes {
-                        Some(serde_json::json!({
-                            "_truncated": true,
-                            "_original_bytes": serialized_size,
-                            "_limit_bytes": max_task_bytes,
-                            "_note": "Output exceeded IRONFLOW_MAX_TASK_OUTPUT_BYTES; full value is in workflow context.",
-                        }))
-                    } else {
-                        Some(output_value)
-                    };
+                    let output_value = cap_for_history(
+                        output_value,
+                        "Output exceeded IRONFLOW_MAX_TASK_OUTPUT_BYTES; full value is in workflow context.",
+                    );
+                    run_meta.record_output(&step.name, output_value.clone());
+                    task_state.output = Some(output_value);
                     task_state.finished = Some(Utc::now());
                     let duration_ms = task_duration_ms(task_state.started, task_state.finished);
                     store.upsert_task(run_id, &task_state).await?;
@@ -128,11 +202,17 @@ impl WorkflowEngine {
                     return Ok(());
                 }
                 Err(e) => {
-                    let err_msg = format!("{:#}", e);
+                    let detail = NodeError::classify(&e);
+                    let err_msg = detail.message.clone();
                     warn!(task = %step.name, attempt = attempt, error = %err_msg, "Task attempt failed");
 
-                    task_state.status = TaskStatus::Failed;
+                    task_state.status = if timed_out {
+                        TaskStatus::TimedOut
+                    } else {
+                        TaskStatus::Failed
+                    };
                     task_state.error = Some(err_msg.clone());
+                    task_state.error_detail = Some(detail.clone());
                     task_state.finished = Some(Utc::now());
                     let duration_ms = task_duration_ms(task_state.started, task_state.finished);
                     store.upsert_task(run_id, &task_state).await?;
@@ -143,7 +223,7 @@ impl WorkflowEngine {
                             &step.name,
                             &step.node_type,
                             RunEventType::TaskFailed,
-                            TaskStatus::Failed,
+                            task_state.status.clone(),
                             Some(attempt),
                         )
                         .with_duration_ms(duration_ms)
@@ -151,11 +231,21 @@ impl WorkflowEngine {
                     )
                     .await;
 
-                    last_error = Some(err_msg);
+                    // Errors outside `retry_on` fail the step right away.
+                    let retry = step.retry.should_retry(&detail);
+                    last_error = Some(detail);
+                    if !retry {
+                        if attempt < max_attempts {
+                            info!(task = %step.name, "Error does not match retry_on; not retrying");
+                        }
+                        break;
+                    }
 
                     // Apply backoff before retry (unless this was the last attempt)
-                    if attempt < max_attempts {
-                        let delay = step.retry.backoff_s * 2.0_f64.powi((attempt - 1) as i32);
+                    if attempt < max_attempts
+                        && let Some(ref detail) = last_error
+                    {
+                        let delay = step.retry.delay_after_s(attempt, detail, jitter_sample);
                         info!(task = %step.name, delay_s = delay, "Retrying after backoff");
                         Self::publish_event_ref(
                             events,
@@ -175,11 +265,57 @@ impl WorkflowEngine {
             }
         }
 
-        bail!(
-            "Task '{}' failed after {} attempts: {}",
-            step.name,
-            max_attempts,
-            last_error.unwrap_or_default()
-        )
+        // Keep the classified error in the chain so `on_error` handlers can
+        // see its kind; `{:#}` still renders "...attempts: <message>".
+        let last_error = last_error
+            .unwrap_or_else(|| NodeError::new(NodeErrorKind::Other, "no attempts were made"));
+        Err(anyhow::Error::new(last_error).context(format!(
+            "Task '{}' failed after {} attempts",
+            step.name, attempts_made
+        )))
+    }
+}
+
+/// Replace values larger than `IRONFLOW_MAX_TASK_OUTPUT_BYTES` with a
+/// truncation marker before they are persisted in task history.
+fn cap_for_history(value: serde_json::Value, note: &str) -> serde_json::Value {
+    let max_task_bytes = crate::util::limits::max_task_output_bytes() as usize;
+    let serialized_size = value.to_string().len();
+    if serialized_size > max_task_bytes {
+        serde_json::json!({
+            "_truncated": true,
+            "_original_bytes": serialized_size,
+            "_limit_bytes": max_task_bytes,
+            "_note": note,
+        })
+    } else {
+        value
+    }
+}
+
+/// Record `output` under `ctx.steps.<step_name>`. A non-object `steps` value
+/// already in the context is replaced.
+fn insert_step_namespace(ctx: &mut Context, step_name: &str, output: &NodeOutput) {
+    let steps = ctx
+        .entry("steps".to_string())
+        .or_insert_with(|| serde_json::Value::Object(Default::default()));
+    if !steps.is_object() {
+        warn!(task = %step_name, "Replacing non-object ctx.steps with the step output namespace");
+        *steps = serde_json::Value::Object(Default::default());
+    }
+    if let Some(map) = steps.as_object_mut() {
+        map.insert(
+            step_name.to_string(),
+            serde_json::Value::Object(output.iter().map(|(k, v)| (k.clone(), v.clone())).collect()),
+        );
+    }
+}
+
+/// Uniform sample in `[0, 1)` for retry jitter, drawn from the run's seed in
+/// deterministic mode so seeded runs wait the same way every time.
+fn jitter_sample() -> f64 {
+    match determinism::current() {
+        Some(d) => (d.next_u64() >> 11) as f64 / (1u64 << 53) as f64,
+        None => rand::random::<f64>(),
     }
 }
@@ -0,0 +1,143 @@
+//! Render a flow's DAG as GraphViz DOT or a Mermaid flowchart.
+
+use std::fmt::Write;
+
+use super::types::{FlowDefinition, StepDefinition};
+
+/// One edge of the rendered graph.
+struct Edge<'a> {
+    from: &'a str,
+    to: &'a str,
+    label: Option<&'a str>,
+    /// `on_error` handler edges are drawn dashed.
+    on_error: bool,
+}
+
+impl FlowDefinition {
+    /// GraphViz DOT for the flow: one box per step labelled with its name
+    /// and node type, an edge per dependency, the route on edges that
+    /// enable a routed step, and dashed `on_error` edges to handlers.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph \"{}\" {{", dot_escape(&self.name));
+        let _ = writeln!(out, "  rankdir=TB;");
+        let _ = writeln!(out, "  node [shape=box];");
+        for step in &self.steps {
+            let _ = writeln!(
+                out,
+                "  \"{}\" [label=\"{}\\n[{}]\"];",
+                dot_escape(&step.name),
+                dot_escape(&step.name),
+                dot_escape(&step.node_type)
+            );
+        }
+        for edge in self.edges() {
+            let mut attrs = Vec::new();
+            if let Some(label) = edge.label {
+                attrs.push(format!("label=\"{}\"", dot_escape(label)));
+            }
+            if edge.on_error {
+                attrs.push("style=dashed".to_string());
+            }
+            let attrs = if attrs.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", attrs.join(", "))
+            };
+            let _ = writeln!(
+                out,
+                "  \"{}\" -> \"{}\"{};",
+                dot_escape(edge.from),
+                dot_escape(edge.to),
+                attrs
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// The same graph as [`to_dot`](Self::to_dot), as a Mermaid
+    /// `flowchart TD` block for embedding in Markdown.
+    pub fn to_mermaid(&self) -> String {
+        let ids: std::collections::HashMap<&str, String> = self
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| (step.name.as_str(), format!("s{}", i)))
+            .collect();
+
+        let mut out = String::from("flowchart TD\n");
+        for step in &self.steps {
+            let _ = writeln!(
+                out,
+                "  {}[\"{}<br/>[{}]\"]",
+                ids[step.name.as_str()],
+                mermaid_escape(&step.name),
+                mermaid_escape(&step.node_type)
+            );
+        }
+        for edge in self.edges() {
+            let (Some(from), Some(to)) = (ids.get(edge.from), ids.get(edge.to)) else {
+                continue;
+            };
+            let arrow = if edge.on_error { "-.->" } else { "-->" };
+            match edge.label {
+                Some(label) => {
+                    let _ = writeln!(
+                        out,
+                        "  {} {}|\"{}\"| {}",
+                        from,
+                        arrow,
+                        mermaid_escape(label),
+                        to
+                    );
+                }
+                None => {
+                    let _ = writeln!(out, "  {} {} {}", from, arrow, to);
+                }
+            }
+        }
+        out
+    }
+
+    fn edges(&self) -> Vec<Edge<'_>> {
+        let mut edges = Vec::new();
+        for step in &self.steps {
+            for dep in &step.dependencies {
+                edges.push(Edge {
+                    from: dep,
+                    to: &step.name,
+                    label: route_label(step, dep),
+                    on_error: false,
+                });
+            }
+            if let Some(ref handler) = step.on_error {
+                edges.push(Edge {
+                    from: &step.name,
+                    to: handler,
+                    label: Some("on_error"),
+                    on_error: true,
+                });
+            }
+        }
+        edges
+    }
+}
+
+/// The route on the edge `dep -> step`, if that dependency decides whether
+/// `step` runs.
+fn route_label<'a>(step: &'a StepDefinition, dep: &str) -> Option<&'a str> {
+    let route = step.route.as_deref()?;
+    match step.route_from {
+        Some(ref from) if from != dep => None,
+        _ => Some(route),
+    }
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn mermaid_escape(s: &str) -> String {
+    s.replace('"', "#quot;")
+}
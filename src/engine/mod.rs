@@ -1,5 +1,9 @@
+pub mod determinism;
 pub mod events;
 pub mod executor;
+pub mod graph;
+pub mod profile;
+pub mod run_meta;
 pub mod types;
 
 pub use events::*;
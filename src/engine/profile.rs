@@ -0,0 +1,93 @@
+//! Per-step execution profiling.
+//!
+//! When a [`WorkflowEngine`](crate::engine::WorkflowEngine) runs with
+//! profiling on, each node executes inside a Tokio task-local collector.
+//! Nodes that talk to the network or a database call [`record`] to report
+//! sub-timings (e.g. time to first byte); the engine stores them on the
+//! step's [`TaskState`] and [`RunProfile`] summarizes them per run.
+
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::types::TaskState;
+
+/// Sub-timings reported by a node, in milliseconds, keyed by phase name.
+pub type Timings = BTreeMap<String, f64>;
+
+tokio::task_local! {
+    static CURRENT: Arc<Mutex<Timings>>;
+}
+
+/// Record how long `phase` took in the current node. A no-op when the run
+/// is not being profiled.
+pub fn record(phase: &str, elapsed: Duration) {
+    let _ = CURRENT.try_with(|timings| {
+        timings
+            .lock()
+            .unwrap()
+            .insert(phase.to_string(), elapsed.as_secs_f64() * 1000.0);
+    });
+}
+
+/// Run `fut`, collecting the sub-timings it records. Returns `None` for the
+/// timings when `enabled` is false.
+pub async fn collect<F: Future>(enabled: bool, fut: F) -> (F::Output, Option<Timings>) {
+    if !enabled {
+        return (fut.await, None);
+    }
+    let timings = Arc::new(Mutex::new(Timings::new()));
+    let output = CURRENT.scope(timings.clone(), fut).await;
+    let timings = std::mem::take(&mut *timings.lock().unwrap());
+    (output, Some(timings))
+}
+
+/// Wall time and sub-timings of a single step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepProfile {
+    pub name: String,
+    pub node_type: String,
+    pub attempt: u32,
+    pub duration_ms: i64,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub timings: Timings,
+}
+
+/// Where a run spent its time, slowest steps first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunProfile {
+    pub steps: Vec<StepProfile>,
+}
+
+impl RunProfile {
+    /// Build a profile from the run's tasks. Returns `None` unless the run
+    /// was profiled, i.e. at least one task carries timings.
+    pub fn from_tasks(tasks: &HashMap<String, TaskState>) -> Option<Self> {
+        if tasks.values().all(|task| task.timings.is_none()) {
+            return None;
+        }
+
+        let mut steps: Vec<StepProfile> = tasks
+            .values()
+            .filter_map(|task| {
+                let duration = task.finished?.signed_duration_since(task.started?);
+                Some(StepProfile {
+                    name: task.name.clone(),
+                    node_type: task.node_type.clone(),
+                    attempt: task.attempt,
+                    duration_ms: duration.num_milliseconds(),
+                    timings: task.timings.clone().unwrap_or_default(),
+                })
+            })
+            .collect();
+        steps.sort_by(|a, b| {
+            b.duration_ms
+                .cmp(&a.duration_ms)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Some(Self { steps })
+    }
+}
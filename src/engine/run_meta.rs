@@ -0,0 +1,76 @@
+//! Run metadata visible to the node that is currently executing.
+//!
+//! The engine wraps every node call in a [`StepScope`] carrying the run ID,
+//! flow name, step name and the outputs of the steps that have finished so
+//! far. Like [`determinism`](crate::engine::determinism), it travels as a
+//! Tokio task-local, so nodes that do not care about it see no change.
+
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+
+use serde_json::{Map, Value};
+
+tokio::task_local! {
+    static CURRENT: Arc<StepScope>;
+}
+
+/// Metadata shared by every task of one run.
+#[derive(Debug)]
+pub struct RunMetadata {
+    pub run_id: String,
+    pub flow_name: String,
+    /// The `flow:env` overlay the run was seeded with, if one was named.
+    pub environment: Option<String>,
+    /// Step name to its output, as recorded in task history. The inner
+    /// `Arc` lets readers snapshot the map without copying it.
+    step_outputs: RwLock<Arc<Map<String, Value>>>,
+}
+
+impl RunMetadata {
+    pub fn new(run_id: &str, flow_name: &str, environment: Option<String>) -> Self {
+        Self {
+            run_id: run_id.to_string(),
+            flow_name: flow_name.to_string(),
+            environment,
+            step_outputs: RwLock::new(Arc::new(Map::new())),
+        }
+    }
+
+    /// Record the output of a step that completed successfully.
+    pub fn record_output(&self, step_name: &str, output: Value) {
+        let mut outputs = self.step_outputs.write().unwrap();
+        Arc::make_mut(&mut outputs).insert(step_name.to_string(), output);
+    }
+
+    /// Snapshot of the outputs recorded so far.
+    pub fn step_outputs(&self) -> Arc<Map<String, Value>> {
+        self.step_outputs.read().unwrap().clone()
+    }
+}
+
+/// The run and step a node is executing for.
+#[derive(Debug)]
+pub struct StepScope {
+    pub run: Arc<RunMetadata>,
+    pub step_name: String,
+}
+
+/// The scope of the current task, if it runs inside the engine.
+pub fn current() -> Option<Arc<StepScope>> {
+    CURRENT.try_with(|s| s.clone()).ok()
+}
+
+/// The environment of the run the current task belongs to, so a child
+/// flow started by a step (e.g. `subworkflow`) runs in the same one.
+pub fn current_environment() -> Option<String> {
+    current().and_then(|s| s.run.environment.clone())
+}
+
+/// Run `fut` as step `step_name` of `run`.
+pub async fn scope<F: Future>(run: Arc<RunMetadata>, step_name: &str, fut: F) -> F::Output {
+    let step = Arc::new(StepScope {
+        run,
+        step_name: step_name.to_string(),
+    });
+    CURRENT.scope(step, fut).await
+}
@@ -1,7 +1,9 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::engine::profile::{RunProfile, Timings};
+
 /// Shared context passed between tasks — a JSON-compatible key-value store.
 pub type Context = HashMap<String, serde_json::Value>;
 
@@ -17,6 +19,8 @@ pub enum RunStatus {
     Success,
     Failed,
     Stalled,
+    /// Stopped on request before it finished.
+    Cancelled,
 }
 
 impl RunStatus {
@@ -25,7 +29,7 @@ impl RunStatus {
     pub fn is_terminal(&self) -> bool {
         matches!(
             self,
-            RunStatus::Success | RunStatus::Failed | RunStatus::Stalled
+            RunStatus::Success | RunStatus::Failed | RunStatus::Stalled | RunStatus::Cancelled
         )
     }
 }
@@ -38,6 +42,7 @@ impl std::fmt::Display for RunStatus {
             RunStatus::Success => write!(f, "success"),
             RunStatus::Failed => write!(f, "failed"),
             RunStatus::Stalled => write!(f, "stalled"),
+            RunStatus::Cancelled => write!(f, "cancelled"),
         }
     }
 }
@@ -51,6 +56,16 @@ pub enum TaskStatus {
     Success,
     Failed,
     Skipped,
+    /// Failed because the step's `timeout_s` elapsed.
+    #[serde(rename = "timed_out")]
+    TimedOut,
+}
+
+impl TaskStatus {
+    /// Whether the task ended in failure, by error or timeout.
+    pub fn is_failed(&self) -> bool {
+        matches!(self, TaskStatus::Failed | TaskStatus::TimedOut)
+    }
 }
 
 impl std::fmt::Display for TaskStatus {
@@ -61,6 +76,40 @@ impl std::fmt::Display for TaskStatus {
             TaskStatus::Success => write!(f, "success"),
             TaskStatus::Failed => write!(f, "failed"),
             TaskStatus::Skipped => write!(f, "skipped"),
+            TaskStatus::TimedOut => write!(f, "timed_out"),
+        }
+    }
+}
+
+/// How the delay between retry attempts grows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackoffStrategy {
+    /// Wait `backoff_s` before every retry.
+    Fixed,
+    /// Double the delay after each attempt.
+    #[default]
+    Exponential,
+    /// Exponential, then pick uniformly between zero and that delay so
+    /// clients retrying together spread out.
+    ExponentialJitter,
+}
+
+impl BackoffStrategy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "fixed" => Some(Self::Fixed),
+            "exponential" => Some(Self::Exponential),
+            "exponential_jitter" => Some(Self::ExponentialJitter),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Fixed => "fixed",
+            Self::Exponential => "exponential",
+            Self::ExponentialJitter => "exponential_jitter",
         }
     }
 }
@@ -70,8 +119,18 @@ impl std::fmt::Display for TaskStatus {
 pub struct RetryConfig {
     /// Maximum number of retry attempts.
     pub max_retries: u32,
-    /// Initial backoff duration in seconds (doubles each attempt).
+    /// Base backoff duration in seconds.
     pub backoff_s: f64,
+    #[serde(default)]
+    pub backoff: BackoffStrategy,
+    /// Upper bound on any single delay.
+    #[serde(default)]
+    pub max_backoff_s: Option<f64>,
+    /// Error classes worth retrying (see [`NodeError::matches_class`]).
+    /// Empty retries every failure; otherwise anything else fails the step
+    /// on its first attempt.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retry_on: Vec<String>,
 }
 
 impl Default for RetryConfig {
@@ -79,10 +138,66 @@ impl Default for RetryConfig {
         Self {
             max_retries: 0,
             backoff_s: 1.0,
+            backoff: BackoffStrategy::default(),
+            max_backoff_s: None,
+            retry_on: Vec::new(),
         }
     }
 }
 
+/// Ceiling on an upstream-requested retry delay (`Retry-After`) for steps
+/// without `max_backoff_s`, so a server cannot park a step for days.
+pub const DEFAULT_MAX_RETRY_AFTER_S: f64 = 300.0;
+
+impl RetryConfig {
+    /// Seconds to wait after failed `attempt` (1-based) before the next one.
+    /// `jitter` yields a sample in `[0, 1)`; only `exponential_jitter` calls it.
+    pub fn delay_s(&self, attempt: u32, jitter: impl FnOnce() -> f64) -> f64 {
+        let delay = match self.backoff {
+            BackoffStrategy::Fixed => self.backoff_s,
+            BackoffStrategy::Exponential | BackoffStrategy::ExponentialJitter => {
+                self.backoff_s * 2.0_f64.powi(attempt.saturating_sub(1).min(62) as i32)
+            }
+        };
+        let delay = match self.max_backoff_s {
+            Some(max) => delay.min(max),
+            None => delay,
+        };
+        match self.backoff {
+            BackoffStrategy::ExponentialJitter => delay * jitter().clamp(0.0, 1.0),
+            _ => delay,
+        }
+    }
+
+    /// Seconds to wait after failed `attempt` with `err`: the upstream's
+    /// requested delay when it gave one (capped by `max_backoff_s`, or by
+    /// [`DEFAULT_MAX_RETRY_AFTER_S`] when that is unset), otherwise
+    /// [`delay_s`](Self::delay_s).
+    pub fn delay_after_s(
+        &self,
+        attempt: u32,
+        err: &NodeError,
+        jitter: impl FnOnce() -> f64,
+    ) -> f64 {
+        match err.retry_after_ms {
+            Some(ms) => {
+                let delay = ms as f64 / 1000.0;
+                delay.min(self.max_backoff_s.unwrap_or(DEFAULT_MAX_RETRY_AFTER_S))
+            }
+            None => self.delay_s(attempt, jitter),
+        }
+    }
+
+    /// Whether a failed attempt with `err` may be retried. Context limit
+    /// violations never are.
+    pub fn should_retry(&self, err: &NodeError) -> bool {
+        if err.kind == NodeErrorKind::ContextLimit {
+            return false;
+        }
+        self.retry_on.is_empty() || self.retry_on.iter().any(|c| err.matches_class(c))
+    }
+}
+
 /// State of an individual task within a workflow run.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskState {
@@ -96,10 +211,178 @@ pub struct TaskState {
     pub output: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Machine-readable classification of `error`, for branching on the cause.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_detail: Option<NodeError>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub started: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub finished: Option<DateTime<Utc>>,
+    /// Node-reported sub-timings in milliseconds; set only on profiled runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<Timings>,
+    /// The step config with `${ctx.*}` placeholders resolved against the
+    /// context the attempt ran with, secrets redacted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_config: Option<serde_json::Value>,
+}
+
+/// What kind of failure a node hit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NodeErrorKind {
+    /// The node (or the step's `timeout`) ran out of time.
+    Timeout,
+    /// An HTTP call returned a failing status.
+    Http { status: u16 },
+    /// Input or output failed validation.
+    Validation,
+    /// Filesystem, network or process I/O failed.
+    Io,
+    /// An upstream provider (LLM, embeddings, ...) rejected the request.
+    Provider,
+    /// The step's output would push the context past its size limits.
+    /// Running the step again would only produce the same output, so it is
+    /// never retried.
+    ContextLimit,
+    /// Anything not classified above.
+    Other,
+}
+
+impl NodeErrorKind {
+    /// Whether retrying the same call can reasonably succeed.
+    pub fn default_retriable(&self) -> bool {
+        match self {
+            NodeErrorKind::Timeout | NodeErrorKind::Io | NodeErrorKind::Provider => true,
+            NodeErrorKind::Http { status } => *status == 408 || *status == 429 || *status >= 500,
+            NodeErrorKind::Validation | NodeErrorKind::ContextLimit | NodeErrorKind::Other => false,
+        }
+    }
+}
+
+/// A classified node failure. Nodes may return one (wrapped in
+/// `anyhow::Error`) to state the cause explicitly; otherwise the engine
+/// derives one from the error chain via [`NodeError::classify`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+#[error("{message}")]
+pub struct NodeError {
+    #[serde(flatten)]
+    pub kind: NodeErrorKind,
+    /// Human-readable description, same text as `TaskState.error`.
+    pub message: String,
+    pub retriable: bool,
+    /// How long the upstream asked us to wait before trying again (e.g. an
+    /// HTTP `Retry-After` header). Step retries wait this long instead of
+    /// their configured backoff.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
+}
+
+impl NodeError {
+    pub fn new(kind: NodeErrorKind, message: impl Into<String>) -> Self {
+        let retriable = kind.default_retriable();
+        Self {
+            kind,
+            message: message.into(),
+            retriable,
+            retry_after_ms: None,
+        }
+    }
+
+    /// Attach the delay the upstream asked for before the next attempt.
+    pub fn with_retry_after(mut self, delay: std::time::Duration) -> Self {
+        self.retry_after_ms = Some(u64::try_from(delay.as_millis()).unwrap_or(u64::MAX));
+        self
+    }
+
+    pub fn http(status: u16, message: impl Into<String>) -> Self {
+        Self::new(NodeErrorKind::Http { status }, message)
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(NodeErrorKind::Validation, message)
+    }
+
+    /// An upstream provider error; retriable only for 429 and 5xx statuses.
+    pub fn provider(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            retriable: status == 429 || status >= 500,
+            ..Self::new(NodeErrorKind::Provider, message)
+        }
+    }
+
+    /// Whether this error falls in `class`, compared case-insensitively:
+    ///
+    /// - a kind: `timeout`, `http`, `validation`, `io`, `provider`,
+    ///   `context_limit`, `other`
+    /// - `connection`, an alias for `io`
+    /// - an HTTP status class (`4xx`, `5xx`) or code (`404`)
+    /// - `retriable`, the error's own retriable flag
+    ///
+    /// Anything else matches when it occurs in the message.
+    pub fn matches_class(&self, class: &str) -> bool {
+        let class = class.trim().to_ascii_lowercase();
+        let status = match self.kind {
+            NodeErrorKind::Http { status } => Some(status),
+            _ => None,
+        };
+        match class.as_str() {
+            "timeout" => self.kind == NodeErrorKind::Timeout,
+            "http" => status.is_some(),
+            "validation" => self.kind == NodeErrorKind::Validation,
+            "io" | "connection" => self.kind == NodeErrorKind::Io,
+            "provider" => self.kind == NodeErrorKind::Provider,
+            "context_limit" => self.kind == NodeErrorKind::ContextLimit,
+            "other" => self.kind == NodeErrorKind::Other,
+            "retriable" => self.retriable,
+            _ => {
+                if let Some(digit) = class.strip_suffix("xx").and_then(|d| d.parse::<u16>().ok()) {
+                    return status.is_some_and(|s| s / 100 == digit);
+                }
+                if let Ok(code) = class.parse::<u16>() {
+                    return status == Some(code);
+                }
+                !class.is_empty() && self.message.to_ascii_lowercase().contains(&class)
+            }
+        }
+    }
+
+    /// Classify an arbitrary node error. An explicit `NodeError` anywhere in
+    /// the chain wins; otherwise well-known source errors are mapped to a
+    /// kind. `message` is always the full `{:#}` rendering of `err`.
+    pub fn classify(err: &anyhow::Error) -> Self {
+        let message = format!("{:#}", err);
+        for cause in err.chain() {
+            if let Some(node_err) = cause.downcast_ref::<NodeError>() {
+                return Self {
+                    message,
+                    ..node_err.clone()
+                };
+            }
+            if let Some(req_err) = cause.downcast_ref::<reqwest::Error>() {
+                let kind = if req_err.is_timeout() {
+                    NodeErrorKind::Timeout
+                } else if let Some(status) = req_err.status() {
+                    NodeErrorKind::Http {
+                        status: status.as_u16(),
+                    }
+                } else {
+                    NodeErrorKind::Io
+                };
+                return Self::new(kind, message);
+            }
+            if cause.is::<tokio::time::error::Elapsed>() {
+                return Self::new(NodeErrorKind::Timeout, message);
+            }
+            if cause.is::<std::io::Error>() {
+                return Self::new(NodeErrorKind::Io, message);
+            }
+            if cause.is::<serde_json::Error>() {
+                return Self::new(NodeErrorKind::Validation, message);
+            }
+        }
+        Self::new(NodeErrorKind::Other, message)
+    }
 }
 
 impl TaskState {
@@ -112,8 +395,11 @@ impl TaskState {
             input: None,
             output: None,
             error: None,
+            error_detail: None,
             started: None,
             finished: None,
+            timings: None,
+            resolved_config: None,
         }
     }
 }
@@ -128,6 +414,9 @@ pub struct RunInfo {
     pub finished: Option<DateTime<Utc>>,
     pub ctx: Context,
     pub tasks: HashMap<String, TaskState>,
+    /// Per-step timing breakdown, present when the run was profiled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<RunProfile>,
 }
 
 /// Compact view of a run, suitable for listing endpoints. Carries only the
@@ -155,6 +444,85 @@ impl From<&RunInfo> for RunSummary {
     }
 }
 
+/// One UTC day of a flow's run history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlowDayStats {
+    pub date: NaiveDate,
+    /// Runs started that day, in any status.
+    pub runs: usize,
+    pub success: usize,
+    /// Failed and stalled runs.
+    pub failed: usize,
+    /// `success / (success + failed)`; `None` when no run has finished.
+    pub success_rate: Option<f64>,
+    /// Median and 95th percentile wall-clock duration of finished runs.
+    pub p50_duration_ms: Option<u64>,
+    pub p95_duration_ms: Option<u64>,
+}
+
+impl FlowDayStats {
+    /// Bucket `runs` by the UTC day they started. Returns one entry per day
+    /// from `since` through `until`, including days without runs, so the
+    /// result can be charted directly.
+    pub fn aggregate(runs: &[RunSummary], since: DateTime<Utc>, until: DateTime<Utc>) -> Vec<Self> {
+        let first = since.date_naive();
+        let last = until.date_naive().max(first);
+        let mut buckets: HashMap<NaiveDate, Vec<&RunSummary>> = HashMap::new();
+        for run in runs {
+            if let Some(started) = run.started
+                && started >= since
+                && started <= until
+            {
+                buckets.entry(started.date_naive()).or_default().push(run);
+            }
+        }
+
+        first
+            .iter_days()
+            .take_while(|day| *day <= last)
+            .map(|date| {
+                let day_runs = buckets.remove(&date).unwrap_or_default();
+                let success = day_runs
+                    .iter()
+                    .filter(|r| r.status == RunStatus::Success)
+                    .count();
+                let failed = day_runs
+                    .iter()
+                    .filter(|r| matches!(r.status, RunStatus::Failed | RunStatus::Stalled))
+                    .count();
+                let mut durations: Vec<u64> = day_runs
+                    .iter()
+                    .filter(|r| r.status.is_terminal())
+                    .filter_map(|r| {
+                        let ms = (r.finished? - r.started?).num_milliseconds();
+                        u64::try_from(ms).ok()
+                    })
+                    .collect();
+                durations.sort_unstable();
+                Self {
+                    date,
+                    runs: day_runs.len(),
+                    success,
+                    failed,
+                    success_rate: (success + failed > 0)
+                        .then(|| success as f64 / (success + failed) as f64),
+                    p50_duration_ms: percentile(&durations, 50.0),
+                    p95_duration_ms: percentile(&durations, 95.0),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Nearest-rank percentile of an ascending slice.
+fn percentile(sorted: &[u64], p: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
 /// Definition of a single step in a flow (parsed from Lua).
 #[derive(Debug, Clone)]
 pub struct StepDefinition {
@@ -165,6 +533,9 @@ pub struct StepDefinition {
     pub retry: RetryConfig,
     pub timeout_s: Option<f64>,
     pub route: Option<String>,
+    /// Dependency whose selected route must equal `route`. When `None`, a
+    /// match from any dependency enables the step.
+    pub route_from: Option<String>,
     pub on_error: Option<String>,
 }
 
@@ -173,9 +544,167 @@ pub struct StepDefinition {
 pub struct FlowDefinition {
     pub name: String,
     pub steps: Vec<StepDefinition>,
+    /// Constants declared with `flow.vars = {...}`. They seed every run's
+    /// context beneath the environment overlays and the caller's context.
+    pub vars: Context,
+    /// Per-environment context values declared with `flow:env(name, vars)`.
+    /// The `"default"` entry applies to every run.
+    pub environments: HashMap<String, Context>,
+    /// Content-hash duplicate guard declared with `flow:dedupe(...)`.
+    pub dedupe: Option<DedupeConfig>,
+    /// Wall-clock limit for the whole run, declared with `flow:timeout(s)`.
+    /// On expiry the run is stopped and stored as `stalled`.
+    pub timeout_s: Option<f64>,
+    /// Steps that run after the DAG, declared with `flow:on_error`,
+    /// `flow:on_success` and `flow:finally`.
+    pub hooks: FlowHooks,
+}
+
+/// Flow-level hook steps. Each list runs in declaration order once the
+/// main DAG has finished; hook steps have no dependencies.
+#[derive(Debug, Clone, Default)]
+pub struct FlowHooks {
+    /// Run when any step failed, with the failure described by the
+    /// `_error_*` context keys.
+    pub on_error: Vec<StepDefinition>,
+    /// Run only when every step succeeded.
+    pub on_success: Vec<StepDefinition>,
+    /// Run last on every run, after the other hooks, including runs that
+    /// were cancelled or timed out.
+    pub finally: Vec<StepDefinition>,
+}
+
+/// Skip starting a run when one with the same initial context started
+/// within `ttl_s`; the earlier run's ID is returned instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DedupeConfig {
+    /// Context paths (`order.id`, `_headers.x-github-delivery`) that identify
+    /// a delivery. Empty hashes the whole initial context except
+    /// `_`-prefixed keys, which the API injects per request.
+    pub keys: Vec<String>,
+    /// How long a hash keeps pointing at its run.
+    pub ttl_s: f64,
+}
+
+impl DedupeConfig {
+    pub const DEFAULT_TTL_S: f64 = 86_400.0;
+
+    /// Hex SHA-256 of the flow name and the selected context values, with
+    /// object keys sorted so field order does not matter.
+    pub fn hash(&self, flow_name: &str, ctx: &Context) -> String {
+        use sha2::{Digest, Sha256};
+
+        let selected: serde_json::Value = if self.keys.is_empty() {
+            ctx.iter()
+                .filter(|(k, _)| !k.starts_with('_'))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect::<serde_json::Map<_, _>>()
+                .into()
+        } else {
+            self.keys
+                .iter()
+                .map(|path| lookup_path(ctx, path).cloned().unwrap_or_default())
+                .collect::<Vec<_>>()
+                .into()
+        };
+
+        let mut canonical = String::new();
+        write_canonical_json(&selected, &mut canonical);
+        let digest = Sha256::new()
+            .chain_update(flow_name.as_bytes())
+            .chain_update([0u8])
+            .chain_update(canonical.as_bytes())
+            .finalize();
+        hex::encode(digest)
+    }
+}
+
+fn lookup_path<'a>(ctx: &'a Context, path: &str) -> Option<&'a serde_json::Value> {
+    let mut segments = path.split('.');
+    let mut current = ctx.get(segments.next()?)?;
+    for segment in segments {
+        current = match current {
+            serde_json::Value::Object(map) => map.get(segment)?,
+            serde_json::Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+fn write_canonical_json(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (k, v)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::String(k.clone()).to_string());
+                out.push(':');
+                write_canonical_json(v, out);
+            }
+            out.push('}');
+        }
+        serde_json::Value::Array(items) => {
+            out.push('[');
+            for (i, v) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical_json(v, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
 }
 
 impl FlowDefinition {
+    /// Build the context seeded from the flow's `vars` and environment
+    /// overlays: `vars`, then `default` values, then those of `env` when
+    /// given.
+    ///
+    /// Naming an environment the flow does not declare is an error, unless
+    /// the flow declares no environments at all.
+    pub fn environment_context(&self, env: Option<&str>) -> anyhow::Result<Context> {
+        let mut ctx = self.vars.clone();
+        if let Some(defaults) = self.environments.get("default") {
+            ctx.extend(defaults.clone());
+        }
+        let Some(env) = env.filter(|e| *e != "default") else {
+            return Ok(ctx);
+        };
+        match self.environments.get(env) {
+            Some(vars) => ctx.extend(vars.clone()),
+            None if self.environments.is_empty() => {}
+            None => {
+                let mut known: Vec<&str> = self.environments.keys().map(String::as_str).collect();
+                known.sort_unstable();
+                anyhow::bail!(
+                    "Flow '{}' has no environment '{}' (declared: {})",
+                    self.name,
+                    env,
+                    known.join(", ")
+                );
+            }
+        }
+        Ok(ctx)
+    }
+
+    /// Fill in the flow's [`environment_context`](Self::environment_context)
+    /// under `ctx`; keys already in `ctx` win.
+    pub fn seed_context(&self, env: Option<&str>, ctx: Context) -> anyhow::Result<Context> {
+        let mut seeded = self.environment_context(env)?;
+        if seeded.is_empty() {
+            return Ok(ctx);
+        }
+        seeded.extend(ctx);
+        Ok(seeded)
+    }
+
     /// Validate the DAG: check for missing dependencies and cycles.
     /// Returns a list of error strings (empty if valid).
     pub fn validate_dag(&self) -> Vec<String> {
@@ -194,6 +723,14 @@ impl FlowDefinition {
                     ));
                 }
             }
+            if let Some(ref from) = step.route_from
+                && !step.dependencies.contains(from)
+            {
+                errors.push(format!(
+                    "Step '{}' is routed on '{}', which is not one of its dependencies",
+                    step.name, from
+                ));
+            }
         }
 
         // Run cycle detection via Kahn's algorithm
@@ -246,4 +783,98 @@ impl FlowDefinition {
 
         errors
     }
+
+    /// Check that every context key a step reads (its `source_key`, per
+    /// [`Node::consumed_keys`](crate::nodes::Node::consumed_keys)) is
+    /// produced by one of its transitive dependencies. Keys starting with
+    /// `_`, keys declared with `flow:env` or `flow.vars`, and steps
+    /// downstream of a node with unknown outputs are skipped. These are
+    /// warnings, not errors: the key may still arrive in the run's initial
+    /// context.
+    pub fn context_key_warnings(&self, registry: &crate::nodes::NodeRegistry) -> Vec<String> {
+        use std::collections::HashSet;
+
+        let steps: HashMap<&str, &StepDefinition> =
+            self.steps.iter().map(|s| (s.name.as_str(), s)).collect();
+        let produced: HashMap<&str, Option<Vec<String>>> = self
+            .steps
+            .iter()
+            .map(|s| {
+                let keys = registry
+                    .get(&s.node_type)
+                    .and_then(|node| node.produced_keys(&s.config));
+                (s.name.as_str(), keys)
+            })
+            .collect();
+        let env_keys: HashSet<&str> = self
+            .environments
+            .values()
+            .chain(std::iter::once(&self.vars))
+            .flat_map(|vars| vars.keys().map(String::as_str))
+            .collect();
+        let covers = |producer: &str, key: &str| {
+            key == producer
+                || key
+                    .strip_prefix(producer)
+                    .is_some_and(|rest| rest.starts_with('_'))
+        };
+        let produced_by = |step: &str, key: &str| {
+            produced
+                .get(step)
+                .and_then(|keys| keys.as_ref())
+                .is_some_and(|keys| keys.iter().any(|p| covers(p, key)))
+        };
+
+        let mut warnings = Vec::new();
+        for step in &self.steps {
+            let Some(node) = registry.get(&step.node_type) else {
+                continue;
+            };
+            let consumed = node.consumed_keys(&step.config);
+            if consumed.is_empty() {
+                continue;
+            }
+
+            let mut ancestors: HashSet<&str> = HashSet::new();
+            let mut stack: Vec<&str> = step.dependencies.iter().map(String::as_str).collect();
+            while let Some(name) = stack.pop() {
+                if ancestors.insert(name)
+                    && let Some(dep) = steps.get(name)
+                {
+                    stack.extend(dep.dependencies.iter().map(String::as_str));
+                }
+            }
+            if ancestors
+                .iter()
+                .any(|name| produced.get(name).is_none_or(|keys| keys.is_none()))
+            {
+                continue;
+            }
+
+            for key in &consumed {
+                let root = key.split('.').next().unwrap_or(key);
+                if root.starts_with('_')
+                    || env_keys.contains(root)
+                    || ancestors.iter().any(|name| produced_by(name, root))
+                {
+                    continue;
+                }
+                let elsewhere = self
+                    .steps
+                    .iter()
+                    .find(|other| other.name != step.name && produced_by(&other.name, root));
+                warnings.push(match elsewhere {
+                    Some(other) => format!(
+                        "Step '{}' reads '{}', which is produced by '{}' but that step is not one of its dependencies",
+                        step.name, key, other.name
+                    ),
+                    None => format!(
+                        "Step '{}' reads '{}', which is not produced by any dependency",
+                        step.name, key
+                    ),
+                });
+            }
+        }
+        warnings
+    }
 }
@@ -3,5 +3,6 @@ pub mod cli;
 pub mod engine;
 pub mod lua;
 pub mod nodes;
+pub mod secrets;
 pub mod storage;
 pub mod util;
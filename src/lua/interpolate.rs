@@ -1,20 +1,54 @@
+use std::collections::HashMap;
+
 use crate::engine::types::Context;
+use crate::nodes::transform::resolve_json_path;
 
-/// Interpolate `${ctx.key}` and `${ctx.nested.key}` patterns in a string.
+/// Interpolate `${ctx.key}`, `${ctx.nested.key}` and `${ctx.items[0].name}`
+/// patterns in a string.
+///
+/// `${steps.<name>.<key>}` is shorthand for `${ctx.steps.<name>.<key>}`, the
+/// per-step namespace the engine fills when step namespacing is enabled.
+///
+/// `${env.NAME}` reads the process environment variable `NAME`, so configs
+/// can reference values such as API keys without routing them through the
+/// context. Lookups are read-only; nothing here sets or alters variables.
+/// Missing keys and unset variables become empty strings, and any other
+/// `${...}` token is left as written.
+///
+/// As in the shell, `${ctx.key:-fallback}` (or `${env.NAME:-fallback}`)
+/// yields `fallback` when the value is missing, null or empty. The fallback
+/// is literal text and cannot contain `}`.
 pub fn interpolate_ctx(template: &str, ctx: &Context) -> String {
+    interpolate(template, ctx, true)
+}
+
+fn interpolate(template: &str, ctx: &Context, resolve_env: bool) -> String {
     let mut result = template.to_string();
     let mut start = 0;
 
-    while let Some(rel) = result[start..].find("${ctx.") {
-        let open = start + rel;
-
+    while let Some((open, path_start, source)) = find_placeholder(&result, start) {
         let close = match result[open..].find('}') {
             Some(pos) => open + pos,
             None => break,
         };
 
-        let path = &result[open + 6..close]; // skip "${ctx."
-        let value = resolve_path(path, ctx);
+        if matches!(source, Source::Env) && !resolve_env {
+            start = close + 1;
+            continue;
+        }
+
+        let (path, default) = match result[path_start..close].split_once(":-") {
+            Some((path, default)) => (path, Some(default)),
+            None => (&result[path_start..close], None),
+        };
+        let value = match source {
+            Source::Ctx => resolve_path(path, ctx),
+            Source::Env => std::env::var(path).unwrap_or_default(),
+        };
+        let value = match default {
+            Some(default) if value.is_empty() => default.to_string(),
+            _ => value,
+        };
 
         result.replace_range(open..=close, &value);
         start = open + value.len();
@@ -23,31 +57,180 @@ pub fn interpolate_ctx(template: &str, ctx: &Context) -> String {
     result
 }
 
-/// Resolve a dotted path (e.g., "user.email") from context.
-fn resolve_path(path: &str, ctx: &Context) -> String {
-    let parts: Vec<&str> = path.split('.').collect();
+/// Apply [`interpolate_ctx`] to every string inside a JSON value.
+pub fn interpolate_value(value: &serde_json::Value, ctx: &Context) -> serde_json::Value {
+    interpolate_json(value, ctx, true)
+}
+
+/// Like [`interpolate_value`], but `${env.NAME}` placeholders are left as
+/// written. Used for configs that are stored or shown, such as a task's
+/// `resolved_config` and the dry-run plan, so environment values (often
+/// API keys) stay out of history, like `${secret:...}` does.
+pub fn interpolate_value_keeping_env(
+    value: &serde_json::Value,
+    ctx: &Context,
+) -> serde_json::Value {
+    interpolate_json(value, ctx, false)
+}
 
-    if parts.is_empty() {
-        return String::new();
+fn interpolate_json(
+    value: &serde_json::Value,
+    ctx: &Context,
+    resolve_env: bool,
+) -> serde_json::Value {
+    match value {
+        serde_json::Value::String(s) => serde_json::Value::String(interpolate(s, ctx, resolve_env)),
+        serde_json::Value::Array(items) => serde_json::Value::Array(
+            items
+                .iter()
+                .map(|v| interpolate_json(v, ctx, resolve_env))
+                .collect(),
+        ),
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), interpolate_json(v, ctx, resolve_env)))
+                .collect(),
+        ),
+        other => other.clone(),
     }
+}
 
-    let first = match ctx.get(parts[0]) {
-        Some(v) => v,
-        None => return String::new(),
-    };
+const SECRET_PREFIX: &str = "${secret:";
 
-    let mut current = first;
-    for part in &parts[1..] {
-        current = match current.get(part) {
-            Some(v) => v,
-            None => return String::new(),
-        };
+/// Collect the distinct `<provider>:<path>` references of every
+/// `${secret:<provider>:<path>}` placeholder inside a JSON value.
+pub fn secret_refs(value: &serde_json::Value) -> Vec<String> {
+    fn walk(value: &serde_json::Value, refs: &mut Vec<String>) {
+        match value {
+            serde_json::Value::String(s) => {
+                let mut rest = s.as_str();
+                while let Some(open) = rest.find(SECRET_PREFIX) {
+                    let after = &rest[open + SECRET_PREFIX.len()..];
+                    let Some(close) = after.find('}') else {
+                        break;
+                    };
+                    let reference = &after[..close];
+                    if !refs.iter().any(|r| r == reference) {
+                        refs.push(reference.to_string());
+                    }
+                    rest = &after[close + 1..];
+                }
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|v| walk(v, refs)),
+            serde_json::Value::Object(map) => map.values().for_each(|v| walk(v, refs)),
+            _ => {}
+        }
+    }
+
+    let mut refs = Vec::new();
+    walk(value, &mut refs);
+    refs
+}
+
+/// Collect the distinct top-level context keys read by `${ctx.<key>...}`
+/// and `${steps....}` placeholders (the latter read `steps`) inside a JSON
+/// value.
+pub fn ctx_refs(value: &serde_json::Value) -> Vec<String> {
+    fn walk(value: &serde_json::Value, refs: &mut Vec<String>) {
+        match value {
+            serde_json::Value::String(s) => {
+                let mut start = 0;
+                while let Some((open, path_start, source)) = find_placeholder(s, start) {
+                    let Some(close) = s[open..].find('}').map(|pos| open + pos) else {
+                        break;
+                    };
+                    if matches!(source, Source::Ctx) {
+                        let path = &s[path_start..close];
+                        let key = path.split(['.', '[', ':']).next().unwrap_or(path);
+                        if !refs.iter().any(|r| r == key) {
+                            refs.push(key.to_string());
+                        }
+                    }
+                    start = close + 1;
+                }
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|v| walk(v, refs)),
+            serde_json::Value::Object(map) => map.values().for_each(|v| walk(v, refs)),
+            _ => {}
+        }
     }
 
-    match current {
-        serde_json::Value::String(s) => s.clone(),
-        serde_json::Value::Null => String::new(),
-        other => other.to_string(),
+    let mut refs = Vec::new();
+    walk(value, &mut refs);
+    refs
+}
+
+/// Replace `${secret:<reference>}` placeholders with the values in
+/// `secrets`, keyed by reference. Unknown references are left untouched.
+pub fn substitute_secrets(
+    value: &serde_json::Value,
+    secrets: &HashMap<String, String>,
+) -> serde_json::Value {
+    match value {
+        serde_json::Value::String(s) if s.contains(SECRET_PREFIX) => {
+            let mut result = s.clone();
+            for (reference, secret) in secrets {
+                result = result.replace(&format!("{SECRET_PREFIX}{reference}}}"), secret);
+            }
+            serde_json::Value::String(result)
+        }
+        serde_json::Value::Array(items) => serde_json::Value::Array(
+            items
+                .iter()
+                .map(|v| substitute_secrets(v, secrets))
+                .collect(),
+        ),
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), substitute_secrets(v, secrets)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Where a placeholder's value comes from.
+#[derive(Clone, Copy)]
+enum Source {
+    Ctx,
+    Env,
+}
+
+/// Find the next `${ctx.`, `${steps.` or `${env.` at or after `start`.
+/// Returns the placeholder offset, where its path begins — for `${steps.`
+/// the `steps.` segment is itself part of the context path — and its source.
+fn find_placeholder(text: &str, start: usize) -> Option<(usize, usize, Source)> {
+    [
+        ("${ctx.", 6, Source::Ctx),
+        ("${steps.", 2, Source::Ctx),
+        ("${env.", 6, Source::Env),
+    ]
+    .into_iter()
+    .filter_map(|(prefix, skip, source)| {
+        text[start..]
+            .find(prefix)
+            .map(|rel| (start + rel, start + rel + skip, source))
+    })
+    .min_by_key(|(open, _, _)| *open)
+}
+
+/// Resolve a path such as `user.email` or `items[0].name` from context. The
+/// first segment names the context key; the rest is walked with
+/// [`resolve_json_path`].
+fn resolve_path(path: &str, ctx: &Context) -> String {
+    let (key, rest) = match path.find(['.', '[']) {
+        Some(split) => (&path[..split], path[split..].trim_start_matches('.')),
+        None => (path, ""),
+    };
+
+    let value = ctx
+        .get(key)
+        .and_then(|value| resolve_json_path(value, rest));
+
+    match value {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        None | Some(serde_json::Value::Null) => String::new(),
+        Some(other) => other.to_string(),
     }
 }
 
@@ -81,15 +264,163 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_array_index_interpolation() {
+        let mut ctx = HashMap::new();
+        ctx.insert(
+            "items".to_string(),
+            serde_json::json!([{"name": "first", "tags": ["a", "b"]}, {"name": "second"}]),
+        );
+        ctx.insert("matrix".to_string(), serde_json::json!([[1, 2], [3, 4]]));
+
+        assert_eq!(
+            interpolate_ctx("${ctx.items[1].name} ${ctx.items[0].tags[1]}", &ctx),
+            "second b"
+        );
+        assert_eq!(interpolate_ctx("${ctx.matrix[1][0]}", &ctx), "3");
+        assert_eq!(
+            interpolate_ctx("${ctx.items[0].tags}", &ctx),
+            r#"["a","b"]"#
+        );
+    }
+
+    #[test]
+    fn test_missing_intermediate_keys() {
+        let mut ctx = HashMap::new();
+        ctx.insert("user".to_string(), serde_json::json!({"profile": null}));
+        ctx.insert("items".to_string(), serde_json::json!([{"name": "only"}]));
+
+        assert_eq!(interpolate_ctx("[${ctx.user.profile.email}]", &ctx), "[]");
+        assert_eq!(interpolate_ctx("[${ctx.user.address.city}]", &ctx), "[]");
+        assert_eq!(interpolate_ctx("[${ctx.items[5].name}]", &ctx), "[]");
+        assert_eq!(interpolate_ctx("${ctx.items[3].name:-none}", &ctx), "none");
+    }
+
     #[test]
     fn test_no_interpolation() {
         let ctx = HashMap::new();
         assert_eq!(interpolate_ctx("plain text", &ctx), "plain text");
     }
 
+    #[test]
+    fn test_steps_namespace_interpolation() {
+        let mut ctx = HashMap::new();
+        ctx.insert(
+            "steps".to_string(),
+            serde_json::json!({"first": {"result": 1}, "second": {"result": 2}}),
+        );
+
+        assert_eq!(
+            interpolate_ctx("${steps.first.result}/${ctx.steps.second.result}", &ctx),
+            "1/2"
+        );
+    }
+
     #[test]
     fn test_missing_key() {
         let ctx = HashMap::new();
         assert_eq!(interpolate_ctx("Hello ${ctx.missing}!", &ctx), "Hello !");
     }
+
+    #[test]
+    fn test_env_interpolation() {
+        // The variable names are unique to this test.
+        unsafe {
+            std::env::set_var("IRONFLOW_TEST_INTERP_TOKEN", "abc123");
+        }
+        let ctx = HashMap::new();
+        assert_eq!(
+            interpolate_ctx("Bearer ${env.IRONFLOW_TEST_INTERP_TOKEN}", &ctx),
+            "Bearer abc123"
+        );
+        assert_eq!(
+            interpolate_ctx("[${env.IRONFLOW_TEST_INTERP_UNSET}]", &ctx),
+            "[]"
+        );
+        assert_eq!(
+            interpolate_ctx("${other.thing} ${name} $${env}", &ctx),
+            "${other.thing} ${name} $${env}"
+        );
+    }
+
+    #[test]
+    fn test_ctx_refs_lists_top_level_keys() {
+        let config = serde_json::json!({
+            "url": "${ctx.base}/items/${ctx.items[0].id}",
+            "nested": ["${ctx.base:-x}", "${steps.fetch.body}", "${env.HOME}"],
+        });
+        assert_eq!(ctx_refs(&config), ["base", "steps", "items"]);
+    }
+
+    #[test]
+    fn test_env_kept_for_history() {
+        unsafe {
+            std::env::set_var("IRONFLOW_TEST_INTERP_KEPT", "s3cret");
+        }
+        let ctx = HashMap::from([("id".to_string(), serde_json::json!(7))]);
+        let config = serde_json::json!({
+            "url": "https://api.example.com/${ctx.id}?key=${env.IRONFLOW_TEST_INTERP_KEPT}",
+            "headers": ["${env.IRONFLOW_TEST_INTERP_KEPT:-none}"]
+        });
+        assert_eq!(
+            interpolate_value_keeping_env(&config, &ctx),
+            serde_json::json!({
+                "url": "https://api.example.com/7?key=${env.IRONFLOW_TEST_INTERP_KEPT}",
+                "headers": ["${env.IRONFLOW_TEST_INTERP_KEPT:-none}"]
+            })
+        );
+        assert_eq!(
+            interpolate_value(&config, &ctx)["url"],
+            "https://api.example.com/7?key=s3cret"
+        );
+    }
+
+    #[test]
+    fn test_default_values() {
+        let mut ctx = HashMap::new();
+        ctx.insert("name".to_string(), serde_json::json!("Alice"));
+        ctx.insert("blank".to_string(), serde_json::json!(""));
+        ctx.insert("nothing".to_string(), serde_json::Value::Null);
+        ctx.insert("user".to_string(), serde_json::json!({"dir": "alice"}));
+
+        assert_eq!(interpolate_ctx("Hi ${ctx.name:-there}", &ctx), "Hi Alice");
+        assert_eq!(
+            interpolate_ctx("Hi ${ctx.missing:-there}", &ctx),
+            "Hi there"
+        );
+        assert_eq!(interpolate_ctx("[${ctx.blank:-empty}]", &ctx), "[empty]");
+        assert_eq!(interpolate_ctx("[${ctx.nothing:-none}]", &ctx), "[none]");
+        assert_eq!(interpolate_ctx("[${ctx.blank:-}]", &ctx), "[]");
+        assert_eq!(
+            interpolate_ctx(
+                "/data/${ctx.user.dir:-shared}/${ctx.user.sub:-in box}/x",
+                &ctx
+            ),
+            "/data/alice/in box/x"
+        );
+        assert_eq!(
+            interpolate_ctx("${env.IRONFLOW_TEST_INTERP_NEVER_SET:-fallback}", &ctx),
+            "fallback"
+        );
+    }
+
+    #[test]
+    fn test_secret_placeholders() {
+        let config = serde_json::json!({
+            "url": "postgres://app:${secret:ssm:/db/password}@db/${ctx.name}",
+            "headers": ["Bearer ${secret:vault:kv/api#token}", "${secret:ssm:/db/password}"]
+        });
+        let mut refs = secret_refs(&config);
+        refs.sort();
+        assert_eq!(refs, vec!["ssm:/db/password", "vault:kv/api#token"]);
+
+        let secrets = HashMap::from([("ssm:/db/password".to_string(), "s3cr3t".to_string())]);
+        assert_eq!(
+            substitute_secrets(&config, &secrets),
+            serde_json::json!({
+                "url": "postgres://app:s3cr3t@db/${ctx.name}",
+                "headers": ["Bearer ${secret:vault:kv/api#token}", "s3cr3t"]
+            })
+        );
+    }
 }
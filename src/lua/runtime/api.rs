@@ -15,27 +15,12 @@ pub(super) fn register_flow_api(lua: &Lua, registry: &NodeRegistry) -> Result<()
         flow.set("_name", name)?;
         flow.set("_steps", lua.create_table()?)?;
         flow.set("_step_count", 0i32)?;
+        flow.set("_envs", lua.create_table()?)?;
 
         // flow:step(name, node_config_or_function) -> step_builder
         let step_fn = lua.create_function(
             |lua, (flow_tbl, step_name, node_arg): (LuaTable, String, LuaValue)| {
-                // Accept either a table (node config) or a function (auto-wrapped as code node)
-                let node_config: LuaTable = match node_arg {
-                    LuaValue::Table(tbl) => tbl,
-                    LuaValue::Function(func) => {
-                        let bytecode = func.dump(false);
-                        let b64 = base64::engine::general_purpose::STANDARD.encode(&bytecode);
-                        let tbl = lua.create_table()?;
-                        tbl.set("_node_type", "code")?;
-                        tbl.set("bytecode_b64", b64)?;
-                        tbl
-                    }
-                    _ => {
-                        return Err(LuaError::RuntimeError(
-                            "step() expects a node config table or a function".into(),
-                        ));
-                    }
-                };
+                let node_config = node_config_from_arg(lua, node_arg, "step")?;
                 let steps: LuaTable = flow_tbl.get("_steps")?;
                 let count: i32 = flow_tbl.get("_step_count")?;
 
@@ -44,9 +29,7 @@ pub(super) fn register_flow_api(lua: &Lua, registry: &NodeRegistry) -> Result<()
                 step.set("node_type", node_config.get::<String>("_node_type")?)?;
                 step.set("config", node_config)?;
                 step.set("dependencies", lua.create_table()?)?;
-                step.set("max_retries", 0)?;
-                step.set("backoff_s", 1.0)?;
-                step.set("timeout_s", LuaValue::Nil)?;
+                // Retry and timeout stay unset so flow.defaults can fill them.
                 step.set("route", LuaValue::Nil)?;
 
                 steps.set(count + 1, step.clone())?;
@@ -83,17 +66,8 @@ pub(super) fn register_flow_api(lua: &Lua, registry: &NodeRegistry) -> Result<()
                 })?;
                 builder.set("depends_on", depends_fn)?;
 
-                // builder:retries(max, backoff)
-                let retries_fn = lua.create_function(
-                    |_lua, (builder, max, backoff): (LuaTable, u32, Option<f64>)| {
-                        let step: LuaTable = builder.get("_step")?;
-                        step.set("max_retries", max)?;
-                        if let Some(b) = backoff {
-                            step.set("backoff_s", b)?;
-                        }
-                        Ok(builder)
-                    },
-                )?;
+                // builder:retries(max, backoff_s, { backoff, max_backoff_s, retry_on })
+                let retries_fn = lua.create_function(set_retries)?;
                 builder.set("retries", retries_fn)?;
 
                 // builder:timeout(seconds)
@@ -114,6 +88,26 @@ pub(super) fn register_flow_api(lua: &Lua, registry: &NodeRegistry) -> Result<()
                     })?;
                 builder.set("route", route_fn)?;
 
+                // builder:on_route(step_name, route_name)
+                // Like route(), but only the named dependency's route counts.
+                // Adds the dependency if it is not declared yet.
+                let on_route_fn = lua.create_function(
+                    |_lua, (builder, dep, route): (LuaTable, String, String)| {
+                        let step: LuaTable = builder.get("_step")?;
+                        let deps: LuaTable = step.get("dependencies")?;
+                        let declared = deps
+                            .sequence_values::<String>()
+                            .any(|d| d.map(|d| d == dep).unwrap_or(false));
+                        if !declared {
+                            deps.set(deps.len()? + 1, dep.clone())?;
+                        }
+                        step.set("route", route)?;
+                        step.set("route_from", dep)?;
+                        Ok(builder)
+                    },
+                )?;
+                builder.set("on_route", on_route_fn)?;
+
                 // builder:on_error(step_name)
                 let on_error_fn =
                     lua.create_function(|_lua, (builder, step_name): (LuaTable, String)| {
@@ -162,22 +156,7 @@ pub(super) fn register_flow_api(lua: &Lua, registry: &NodeRegistry) -> Result<()
                     steps.set(count + 1, guard_step)?;
 
                     // 2. Create the actual step with depends_on + route("true")
-                    let node_config: LuaTable = match node_arg {
-                        LuaValue::Table(tbl) => tbl,
-                        LuaValue::Function(func) => {
-                            let bytecode = func.dump(false);
-                            let b64 = base64::engine::general_purpose::STANDARD.encode(&bytecode);
-                            let tbl = lua.create_table()?;
-                            tbl.set("_node_type", "code")?;
-                            tbl.set("bytecode_b64", b64)?;
-                            tbl
-                        }
-                        _ => {
-                            return Err(LuaError::RuntimeError(
-                                "step_if() expects a node config table or a function".into(),
-                            ));
-                        }
-                    };
+                    let node_config = node_config_from_arg(lua, node_arg, "step_if")?;
 
                     let deps = lua.create_table()?;
                     deps.set(1, guard_name)?;
@@ -187,9 +166,6 @@ pub(super) fn register_flow_api(lua: &Lua, registry: &NodeRegistry) -> Result<()
                     step.set("node_type", node_config.get::<String>("_node_type")?)?;
                     step.set("config", node_config)?;
                     step.set("dependencies", deps)?;
-                    step.set("max_retries", 0)?;
-                    step.set("backoff_s", 1.0)?;
-                    step.set("timeout_s", LuaValue::Nil)?;
                     step.set("route", "true")?;
 
                     steps.set(count + 2, step.clone())?;
@@ -225,16 +201,7 @@ pub(super) fn register_flow_api(lua: &Lua, registry: &NodeRegistry) -> Result<()
                     })?;
                     builder.set("depends_on", depends_fn)?;
 
-                    let retries_fn = lua.create_function(
-                        |_lua, (builder, max, backoff): (LuaTable, u32, Option<f64>)| {
-                            let step: LuaTable = builder.get("_step")?;
-                            step.set("max_retries", max)?;
-                            if let Some(b) = backoff {
-                                step.set("backoff_s", b)?;
-                            }
-                            Ok(builder)
-                        },
-                    )?;
+                    let retries_fn = lua.create_function(set_retries)?;
                     builder.set("retries", retries_fn)?;
 
                     let timeout_fn =
@@ -258,6 +225,57 @@ pub(super) fn register_flow_api(lua: &Lua, registry: &NodeRegistry) -> Result<()
             )?;
         flow.set("step_if", step_if_fn)?;
 
+        // flow:env(name, vars) — context values for one environment
+        // ("default" applies to every run)
+        let env_fn = lua.create_function(
+            |_lua, (flow_tbl, env_name, vars): (LuaTable, String, LuaTable)| {
+                let envs: LuaTable = flow_tbl.get("_envs")?;
+                envs.set(env_name, vars)?;
+                Ok(flow_tbl)
+            },
+        )?;
+        flow.set("env", env_fn)?;
+
+        // flow:dedupe({ keys = {...}, ttl_s = n }) — skip runs whose initial
+        // context matches one started within ttl_s
+        let dedupe_fn =
+            lua.create_function(|lua, (flow_tbl, opts): (LuaTable, Option<LuaTable>)| {
+                let opts = match opts {
+                    Some(opts) => opts,
+                    None => lua.create_table()?,
+                };
+                flow_tbl.set("_dedupe", opts)?;
+                Ok(flow_tbl)
+            })?;
+        flow.set("dedupe", dedupe_fn)?;
+
+        // flow:timeout(seconds) — limit on the whole run's wall-clock time
+        let timeout_fn = lua.create_function(|_lua, (flow_tbl, seconds): (LuaTable, f64)| {
+            flow_tbl.set("_timeout_s", seconds)?;
+            Ok(flow_tbl)
+        })?;
+        flow.set("timeout", timeout_fn)?;
+
+        // flow:on_error(name, node_config_or_function), flow:on_success(...)
+        // and flow:finally(...) — hook steps run after the DAG
+        for (method, list_key) in [
+            ("on_error", "_on_error"),
+            ("on_success", "_on_success"),
+            ("finally", "_finally"),
+        ] {
+            let hook_fn = lua.create_function(
+                move |lua, (flow_tbl, step_name, node_arg): (LuaTable, String, LuaValue)| {
+                    add_hook_step(lua, &flow_tbl, list_key, method, step_name, node_arg)
+                },
+            )?;
+            flow.set(method, hook_fn)?;
+        }
+
+        // flow:import(path) — append the steps of another flow file, which
+        // returns its own Flow. Paths and cycles are handled by `include`.
+        let import_fn = lua.create_function(import_flow)?;
+        flow.set("import", import_fn)?;
+
         Ok(flow)
     })?;
     flow_constructor.set("new", new_fn)?;
@@ -265,7 +283,7 @@ pub(super) fn register_flow_api(lua: &Lua, registry: &NodeRegistry) -> Result<()
 
     // Create the nodes table with factory functions for each registered node
     let nodes_table = lua.create_table()?;
-    for (node_type, _desc) in registry.list() {
+    for (node_type, _desc, _pure) in registry.list() {
         let node_type_owned = node_type.to_string();
         let factory = lua.create_function(move |lua, config: Option<LuaTable>| {
             let tbl = config.unwrap_or(lua.create_table()?);
@@ -299,3 +317,143 @@ pub(super) fn register_flow_api(lua: &Lua, registry: &NodeRegistry) -> Result<()
 
     Ok(())
 }
+
+/// Accept either a node config table or a function, which is wrapped as a
+/// `code` node.
+fn node_config_from_arg(lua: &Lua, node_arg: LuaValue, method: &str) -> LuaResult<LuaTable> {
+    match node_arg {
+        LuaValue::Table(tbl) => Ok(tbl),
+        LuaValue::Function(func) => {
+            let bytecode = func.dump(false);
+            let b64 = base64::engine::general_purpose::STANDARD.encode(&bytecode);
+            let tbl = lua.create_table()?;
+            tbl.set("_node_type", "code")?;
+            tbl.set("bytecode_b64", b64)?;
+            Ok(tbl)
+        }
+        _ => Err(LuaError::RuntimeError(format!(
+            "{}() expects a node config table or a function",
+            method
+        ))),
+    }
+}
+
+/// Append a hook step to the flow's `list_key` list and return a builder
+/// that supports `:retries()` and `:timeout()`.
+fn add_hook_step(
+    lua: &Lua,
+    flow_tbl: &LuaTable,
+    list_key: &str,
+    method: &str,
+    step_name: String,
+    node_arg: LuaValue,
+) -> LuaResult<LuaTable> {
+    let node_config = node_config_from_arg(lua, node_arg, method)?;
+    let hooks = match flow_tbl.get::<Option<LuaTable>>(list_key)? {
+        Some(list) => list,
+        None => {
+            let list = lua.create_table()?;
+            flow_tbl.set(list_key, list.clone())?;
+            list
+        }
+    };
+
+    let step = lua.create_table()?;
+    step.set("name", step_name)?;
+    step.set("node_type", node_config.get::<String>("_node_type")?)?;
+    step.set("config", node_config)?;
+    step.set("dependencies", lua.create_table()?)?;
+    hooks.push(step.clone())?;
+
+    let builder = lua.create_table()?;
+    builder.set("_step", step)?;
+    builder.set("retries", lua.create_function(set_retries)?)?;
+    builder.set(
+        "timeout",
+        lua.create_function(|_lua, (builder, seconds): (LuaTable, f64)| {
+            let step: LuaTable = builder.get("_step")?;
+            step.set("timeout_s", seconds)?;
+            Ok(builder)
+        })?,
+    )?;
+    Ok(builder)
+}
+
+/// `flow:import(path)`: load a step library written as a standalone flow and
+/// append its steps to `flow`. Its `vars` are kept aside and merged beneath
+/// the importing flow's own when the flow is extracted.
+fn import_flow(lua: &Lua, (flow_tbl, path): (LuaTable, String)) -> LuaResult<LuaTable> {
+    let include: LuaFunction = lua.globals().get("include")?;
+    let imported = match include.call::<LuaValue>(path.as_str())? {
+        LuaValue::Table(tbl) if tbl.contains_key("_steps")? => tbl,
+        _ => {
+            return Err(LuaError::RuntimeError(format!(
+                "import('{}'): the file must return a Flow",
+                path
+            )));
+        }
+    };
+
+    let steps: LuaTable = flow_tbl.get("_steps")?;
+    let mut count: i32 = flow_tbl.get("_step_count")?;
+    for step in imported
+        .get::<LuaTable>("_steps")?
+        .sequence_values::<LuaTable>()
+    {
+        count += 1;
+        steps.set(count, step?)?;
+    }
+    flow_tbl.set("_step_count", count)?;
+
+    // The imported flow's own imports sit beneath its vars, so vars from
+    // every level of a nested import survive in the same order.
+    let mut layers = Vec::new();
+    if let Some(nested) = imported.get::<Option<LuaTable>>("_imported_vars")? {
+        for vars in nested.sequence_values::<LuaTable>() {
+            layers.push(vars?);
+        }
+    }
+    if let Some(vars) = imported.get::<Option<LuaTable>>("vars")? {
+        layers.push(vars);
+    }
+    if !layers.is_empty() {
+        let imported_vars = match flow_tbl.get::<Option<LuaTable>>("_imported_vars")? {
+            Some(list) => list,
+            None => {
+                let list = lua.create_table()?;
+                flow_tbl.set("_imported_vars", list.clone())?;
+                list
+            }
+        };
+        for vars in layers {
+            imported_vars.push(vars)?;
+        }
+    }
+    Ok(flow_tbl)
+}
+
+/// `builder:retries(max, backoff_s, { backoff = "exponential_jitter", max_backoff_s = 30,
+/// retry_on = { "timeout", "5xx" } })`. Strategy names are validated when the
+/// flow is extracted.
+fn set_retries(
+    _lua: &Lua,
+    (builder, max, backoff_s, opts): (LuaTable, u32, Option<f64>, Option<LuaTable>),
+) -> LuaResult<LuaTable> {
+    let step: LuaTable = builder.get("_step")?;
+    step.set("max_retries", max)?;
+    if let Some(b) = backoff_s {
+        step.set("backoff_s", b)?;
+    }
+    if let Some(opts) = opts {
+        if let Some(backoff) = opts.get::<Option<String>>("backoff")? {
+            step.set("backoff", backoff)?;
+        }
+        if let Some(max_backoff_s) = opts.get::<Option<f64>>("max_backoff_s")? {
+            step.set("max_backoff_s", max_backoff_s)?;
+        }
+        if let Some(retry_on) = opts.get::<Option<LuaTable>>("retry_on")? {
+            step.set("retry_on", retry_on)?;
+        }
+    }
+    Ok(builder)
+}
@@ -1,7 +1,11 @@
+use std::collections::{HashMap, HashSet};
+
 use anyhow::Result;
 use mlua::prelude::*;
 
-use crate::engine::types::{FlowDefinition, RetryConfig, StepDefinition};
+use crate::engine::types::{
+    BackoffStrategy, Context, DedupeConfig, FlowDefinition, FlowHooks, RetryConfig, StepDefinition,
+};
 
 use super::conversion::lua_table_to_json;
 
@@ -15,67 +19,307 @@ pub(super) fn extract_flow(flow_table: &LuaTable) -> Result<FlowDefinition> {
         .get("_steps")
         .map_err(|e| anyhow::anyhow!("Flow must have steps: {}", e))?;
 
+    let defaults = StepDefaults::from_flow(flow_table)?;
+
     let mut steps = Vec::new();
-    let mut seen_names = std::collections::HashSet::new();
+    let mut seen_names = HashSet::new();
 
     for pair in steps_table.pairs::<i32, LuaTable>() {
         let (_, step_table) = pair?;
+        let step = extract_step(&step_table, &defaults)?;
+        if !seen_names.insert(step.name.clone()) {
+            anyhow::bail!(
+                "Duplicate step name '{}' in flow '{}'. Each step must have a unique name.",
+                step.name,
+                name
+            );
+        }
+        steps.push(step);
+    }
+
+    let hooks = FlowHooks {
+        on_error: extract_hooks(flow_table, "_on_error", &defaults, &mut seen_names, &name)?,
+        on_success: extract_hooks(flow_table, "_on_success", &defaults, &mut seen_names, &name)?,
+        finally: extract_hooks(flow_table, "_finally", &defaults, &mut seen_names, &name)?,
+    };
+
+    let vars = extract_vars(flow_table)?;
+
+    let mut environments = HashMap::new();
+    if let Ok(envs_table) = flow_table.get::<LuaTable>("_envs") {
+        for pair in envs_table.pairs::<String, LuaTable>() {
+            let (env_name, vars) = pair?;
+            let vars = match lua_table_to_json(&vars)? {
+                serde_json::Value::Object(map) => map.into_iter().collect(),
+                // An empty Lua table converts to an empty object, so
+                // anything else is a list.
+                _ => anyhow::bail!(
+                    "flow:env('{}', ...) expects a table of named values",
+                    env_name
+                ),
+            };
+            environments.insert(env_name, vars);
+        }
+    }
+
+    let dedupe = extract_dedupe(flow_table)?;
+
+    let timeout_s = flow_table.get::<Option<f64>>("_timeout_s")?;
+    if timeout_s.is_some_and(|s| !s.is_finite() || s <= 0.0) {
+        anyhow::bail!("flow:timeout expects a positive number of seconds");
+    }
+
+    Ok(FlowDefinition {
+        name,
+        steps,
+        vars,
+        environments,
+        dedupe,
+        timeout_s,
+        hooks,
+    })
+}
+
+/// Turn one step table built by `flow:step` (or a hook method) into a
+/// `StepDefinition`, filling unset retry and timeout settings from
+/// `flow.defaults`.
+fn extract_step(step_table: &LuaTable, defaults: &StepDefaults) -> Result<StepDefinition> {
+    let step_name: String = step_table.get("name")?;
+    let node_type: String = step_table.get("node_type")?;
+    let max_retries = step_table
+        .get::<Option<u32>>("max_retries")?
+        .unwrap_or(defaults.retry.max_retries);
+    let backoff_s = step_table
+        .get::<Option<f64>>("backoff_s")?
+        .unwrap_or(defaults.retry.backoff_s);
+    let backoff = match step_table.get::<Option<String>>("backoff")? {
+        Some(name) => {
+            parse_backoff(&name).map_err(|e| anyhow::anyhow!("Step '{}': {}", step_name, e))?
+        }
+        None => defaults.retry.backoff,
+    };
+    let max_backoff_s = step_table
+        .get::<Option<f64>>("max_backoff_s")?
+        .or(defaults.retry.max_backoff_s);
+    let retry_on = match step_table.get::<Option<LuaTable>>("retry_on")? {
+        Some(list) => {
+            parse_retry_on(&list).map_err(|e| anyhow::anyhow!("Step '{}': {}", step_name, e))?
+        }
+        None => defaults.retry.retry_on.clone(),
+    };
+    let timeout_s = step_table
+        .get::<Option<f64>>("timeout_s")?
+        .or(defaults.timeout_s);
+    check_delays(backoff_s, max_backoff_s, timeout_s)
+        .map_err(|e| anyhow::anyhow!("Step '{}': {}", step_name, e))?;
+    let route: Option<String> = step_table.get("route").ok();
+    let route_from: Option<String> = step_table.get("route_from").ok();
+    let on_error: Option<String> = step_table.get("on_error").ok();
+
+    // Extract dependencies
+    let deps_table: LuaTable = step_table.get("dependencies")?;
+    let mut dependencies = Vec::new();
+    for dep_pair in deps_table.pairs::<i32, String>() {
+        let (_, dep) = dep_pair?;
+        dependencies.push(dep);
+    }
 
-        let step_name: String = step_table.get("name")?;
+    // Extract config (the node config table minus internal keys)
+    let config_table: LuaTable = step_table.get("config")?;
+    let config = lua_table_to_json(&config_table)?;
 
-        if !seen_names.insert(step_name.clone()) {
+    // Inject step name into config for conditional nodes
+    let config = match config {
+        serde_json::Value::Object(mut m) => {
+            m.insert(
+                "_step_name".to_string(),
+                serde_json::Value::String(step_name.clone()),
+            );
+            m.remove("_node_type");
+            serde_json::Value::Object(m)
+        }
+        other => other,
+    };
+
+    Ok(StepDefinition {
+        name: step_name,
+        node_type,
+        config,
+        dependencies,
+        retry: RetryConfig {
+            max_retries,
+            backoff_s,
+            backoff,
+            max_backoff_s,
+            retry_on,
+        },
+        timeout_s,
+        route,
+        route_from,
+        on_error,
+    })
+}
+
+/// Read the hook steps stored under `key` by `flow:on_error`,
+/// `flow:on_success` or `flow:finally`. Hook names share the namespace of regular steps.
+fn extract_hooks(
+    flow_table: &LuaTable,
+    key: &str,
+    defaults: &StepDefaults,
+    seen_names: &mut HashSet<String>,
+    flow_name: &str,
+) -> Result<Vec<StepDefinition>> {
+    let Some(list) = flow_table.get::<Option<LuaTable>>(key)? else {
+        return Ok(Vec::new());
+    };
+    let mut hooks = Vec::new();
+    for step_table in list.sequence_values::<LuaTable>() {
+        let step = extract_step(&step_table?, defaults)?;
+        if !seen_names.insert(step.name.clone()) {
             anyhow::bail!(
                 "Duplicate step name '{}' in flow '{}'. Each step must have a unique name.",
-                step_name,
-                name
+                step.name,
+                flow_name
             );
         }
-        let node_type: String = step_table.get("node_type")?;
-        let max_retries: u32 = step_table.get("max_retries").unwrap_or(0);
-        let backoff_s: f64 = step_table.get("backoff_s").unwrap_or(1.0);
-        let timeout_s: Option<f64> = step_table.get("timeout_s").ok();
-        let route: Option<String> = step_table.get("route").ok();
-        let on_error: Option<String> = step_table.get("on_error").ok();
-
-        // Extract dependencies
-        let deps_table: LuaTable = step_table.get("dependencies")?;
-        let mut dependencies = Vec::new();
-        for dep_pair in deps_table.pairs::<i32, String>() {
-            let (_, dep) = dep_pair?;
-            dependencies.push(dep);
+        hooks.push(step);
+    }
+    Ok(hooks)
+}
+
+/// Read `flow.vars = { key = value, ... }`, layered over the vars of flows
+/// pulled in with `flow:import`. Keys starting with `_` are reserved for
+/// values the runtime injects, such as `_flow_dir`.
+fn extract_vars(flow_table: &LuaTable) -> Result<Context> {
+    let mut tables = Vec::new();
+    if let Some(imported) = flow_table.get::<Option<LuaTable>>("_imported_vars")? {
+        for table in imported.sequence_values::<LuaTable>() {
+            tables.push(table?);
         }
+    }
+    if let Some(own) = flow_table
+        .get::<Option<LuaTable>>("vars")
+        .map_err(|e| anyhow::anyhow!("flow.vars must be a table: {}", e))?
+    {
+        tables.push(own);
+    }
 
-        // Extract config (the node config table minus internal keys)
-        let config_table: LuaTable = step_table.get("config")?;
-        let config = lua_table_to_json(&config_table)?;
-
-        // Inject step name into config for conditional nodes
-        let config = match config {
-            serde_json::Value::Object(mut m) => {
-                m.insert(
-                    "_step_name".to_string(),
-                    serde_json::Value::String(step_name.clone()),
-                );
-                m.remove("_node_type");
-                serde_json::Value::Object(m)
-            }
-            other => other,
+    let mut vars = Context::new();
+    for table in tables {
+        match lua_table_to_json(&table)? {
+            serde_json::Value::Object(map) => vars.extend(map),
+            _ => anyhow::bail!("flow.vars expects a table of named values"),
+        }
+    }
+    if let Some(key) = vars.keys().find(|k| k.starts_with('_')) {
+        anyhow::bail!(
+            "flow.vars cannot set '{}': keys starting with '_' are reserved",
+            key
+        );
+    }
+    Ok(vars)
+}
+
+/// Read the options stored by `flow:dedupe({ keys, ttl_s })`.
+fn extract_dedupe(flow_table: &LuaTable) -> Result<Option<DedupeConfig>> {
+    let Some(table) = flow_table.get::<Option<LuaTable>>("_dedupe")? else {
+        return Ok(None);
+    };
+    let keys = match table.get::<Option<LuaTable>>("keys")? {
+        Some(keys) => keys
+            .sequence_values::<String>()
+            .collect::<LuaResult<Vec<_>>>()
+            .map_err(|e| anyhow::anyhow!("flow:dedupe keys must be a list of strings: {}", e))?,
+        None => Vec::new(),
+    };
+    let ttl_s = table
+        .get::<Option<f64>>("ttl_s")?
+        .unwrap_or(DedupeConfig::DEFAULT_TTL_S);
+    if !ttl_s.is_finite() || ttl_s <= 0.0 {
+        anyhow::bail!("flow:dedupe ttl_s must be a positive number of seconds");
+    }
+    Ok(Some(DedupeConfig { keys, ttl_s }))
+}
+
+/// Retry and timeout settings from `flow.defaults`, applied to every step
+/// that does not set its own.
+#[derive(Default)]
+struct StepDefaults {
+    retry: RetryConfig,
+    timeout_s: Option<f64>,
+}
+
+impl StepDefaults {
+    /// Read `flow.defaults = { retry = { max_retries, backoff_s, backoff,
+    /// max_backoff_s, retry_on }, timeout_s }`.
+    fn from_flow(flow_table: &LuaTable) -> Result<Self> {
+        let mut defaults = Self::default();
+        let Some(table) = flow_table
+            .get::<Option<LuaTable>>("defaults")
+            .map_err(|e| anyhow::anyhow!("flow.defaults must be a table: {}", e))?
+        else {
+            return Ok(defaults);
         };
 
-        steps.push(StepDefinition {
-            name: step_name,
-            node_type,
-            config,
-            dependencies,
-            retry: RetryConfig {
-                max_retries,
-                backoff_s,
-            },
-            timeout_s,
-            route,
-            on_error,
-        });
+        if let Some(retry) = table
+            .get::<Option<LuaTable>>("retry")
+            .map_err(|e| anyhow::anyhow!("flow.defaults.retry must be a table: {}", e))?
+        {
+            if let Some(max_retries) = retry.get::<Option<u32>>("max_retries")? {
+                defaults.retry.max_retries = max_retries;
+            }
+            if let Some(backoff_s) = retry.get::<Option<f64>>("backoff_s")? {
+                defaults.retry.backoff_s = backoff_s;
+            }
+            if let Some(backoff) = retry.get::<Option<String>>("backoff")? {
+                defaults.retry.backoff = parse_backoff(&backoff)
+                    .map_err(|e| anyhow::anyhow!("flow.defaults.retry: {}", e))?;
+            }
+            if let Some(max_backoff_s) = retry.get::<Option<f64>>("max_backoff_s")? {
+                defaults.retry.max_backoff_s = Some(max_backoff_s);
+            }
+            if let Some(retry_on) = retry.get::<Option<LuaTable>>("retry_on")? {
+                defaults.retry.retry_on = parse_retry_on(&retry_on)
+                    .map_err(|e| anyhow::anyhow!("flow.defaults.retry: {}", e))?;
+            }
+        }
+        defaults.timeout_s = table.get("timeout_s")?;
+        check_delays(
+            defaults.retry.backoff_s,
+            defaults.retry.max_backoff_s,
+            defaults.timeout_s,
+        )
+        .map_err(|e| anyhow::anyhow!("flow.defaults: {}", e))?;
+        Ok(defaults)
+    }
+}
+
+/// Retry delays must be finite and non-negative, and a timeout finite and
+/// positive; anything else would panic when turned into a `Duration`.
+fn check_delays(backoff_s: f64, max_backoff_s: Option<f64>, timeout_s: Option<f64>) -> Result<()> {
+    if !backoff_s.is_finite() || backoff_s < 0.0 {
+        anyhow::bail!("backoff_s must be a non-negative number of seconds");
+    }
+    if max_backoff_s.is_some_and(|s| !s.is_finite() || s < 0.0) {
+        anyhow::bail!("max_backoff_s must be a non-negative number of seconds");
+    }
+    if timeout_s.is_some_and(|s| !s.is_finite() || s <= 0.0) {
+        anyhow::bail!("timeout_s must be a positive number of seconds");
     }
+    Ok(())
+}
+
+fn parse_backoff(name: &str) -> Result<BackoffStrategy> {
+    BackoffStrategy::parse(name).ok_or_else(|| {
+        anyhow::anyhow!(
+            "unknown backoff '{}'; expected 'fixed', 'exponential', or 'exponential_jitter'",
+            name
+        )
+    })
+}
 
-    Ok(FlowDefinition { name, steps })
+fn parse_retry_on(list: &LuaTable) -> Result<Vec<String>> {
+    list.sequence_values::<String>()
+        .collect::<LuaResult<Vec<_>>>()
+        .map_err(|e| anyhow::anyhow!("retry_on must be a list of strings: {}", e))
 }
@@ -1,9 +1,11 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
 use anyhow::Result;
-use chrono::Utc;
 use mlua::prelude::*;
 use tracing::{debug, error, info, trace, warn};
-use uuid::Uuid;
 
+use crate::engine::determinism;
 use crate::engine::types::FlowDefinition;
 use crate::nodes::NodeRegistry;
 use crate::nodes::utility::code::json_value_to_lua_table;
@@ -13,6 +15,9 @@ use super::api::register_flow_api;
 use super::conversion::{lua_to_log_string, lua_value_to_json};
 use super::extractor::extract_flow;
 
+/// Deepest `include()` nesting allowed in one flow.
+const MAX_INCLUDE_DEPTH: usize = 32;
+
 /// Lua runtime for loading and parsing flow definitions.
 pub struct LuaRuntime;
 
@@ -32,6 +37,10 @@ impl LuaRuntime {
         // Load and execute the Lua file
         let source = std::fs::read_to_string(path)
             .map_err(|e| anyhow::anyhow!("Failed to read flow file '{}': {}", path, e))?;
+        let root = Path::new(path)
+            .canonicalize()
+            .unwrap_or_else(|_| PathBuf::from(path));
+        Self::register_include(&lua, Some(root))?;
 
         let flow_table: LuaTable = lua
             .load(&source)
@@ -51,6 +60,7 @@ impl LuaRuntime {
         apply_lua_limits(&lua, limits)?;
         Self::setup_sandbox(&lua)?;
         register_flow_api(&lua, registry)?;
+        Self::register_include(&lua, None)?;
 
         let flow_table: LuaTable = lua
             .load(source)
@@ -62,6 +72,90 @@ impl LuaRuntime {
         extract_flow(&flow_table)
     }
 
+    /// Evaluate a sandboxed Lua file that returns a plain table and convert
+    /// it to JSON. Used for data files such as `ironflow test` fixtures,
+    /// which may still call `env()` or `json_parse()`.
+    pub fn load_data(path: &str) -> Result<serde_json::Value> {
+        let lua = Lua::new();
+        let limits = LuaExecutionLimits::from_env();
+        apply_lua_limits(&lua, limits)?;
+        Self::setup_sandbox(&lua)?;
+
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read '{}': {}", path, e))?;
+        let value: LuaValue = lua
+            .load(&source)
+            .set_name(path)
+            .eval()
+            .map_err(|e| anyhow::anyhow!("Failed to evaluate '{}': {}", path, e))?;
+        let json = lua_value_to_json(&value)?;
+        collect_lua_garbage(&lua, limits)?;
+        Ok(json)
+    }
+
+    /// Register `include(path, ...)`: run another Lua file in the same state
+    /// and return its results. `path` is resolved against the directory of
+    /// the including file and extra arguments arrive in the included file as
+    /// `...`, so `include("steps/fetch.lua", flow)` lets a file add steps to
+    /// the caller's flow. `root` is the top-level flow file; inline sources
+    /// (`None`) have no directory to resolve against and cannot include.
+    fn register_include(lua: &Lua, root: Option<PathBuf>) -> Result<()> {
+        let stack: Arc<Mutex<Vec<PathBuf>>> = Arc::new(Mutex::new(root.into_iter().collect()));
+
+        let include_fn =
+            lua.create_function(move |lua, (rel, args): (String, LuaMultiValue)| {
+                let path = {
+                    let stack = stack.lock().unwrap();
+                    let Some(current) = stack.last() else {
+                        return Err(LuaError::RuntimeError(
+                            "include() is only available in flows loaded from a file".into(),
+                        ));
+                    };
+                    let base = current.parent().unwrap_or_else(|| Path::new("."));
+                    let path = base.join(&rel).canonicalize().map_err(|e| {
+                        LuaError::RuntimeError(format!(
+                            "include('{}'): cannot resolve from {}: {}",
+                            rel,
+                            base.display(),
+                            e
+                        ))
+                    })?;
+                    if stack.contains(&path) {
+                        let chain: Vec<String> = stack
+                            .iter()
+                            .chain(std::iter::once(&path))
+                            .map(|p| p.display().to_string())
+                            .collect();
+                        return Err(LuaError::RuntimeError(format!(
+                            "include cycle: {}",
+                            chain.join(" -> ")
+                        )));
+                    }
+                    if stack.len() >= MAX_INCLUDE_DEPTH {
+                        return Err(LuaError::RuntimeError(format!(
+                            "include('{}'): nesting deeper than {} files",
+                            rel, MAX_INCLUDE_DEPTH
+                        )));
+                    }
+                    path
+                };
+
+                let source = std::fs::read_to_string(&path).map_err(|e| {
+                    LuaError::RuntimeError(format!("include('{}'): {}", path.display(), e))
+                })?;
+
+                stack.lock().unwrap().push(path.clone());
+                let result = lua
+                    .load(&source)
+                    .set_name(path.display().to_string())
+                    .call::<LuaMultiValue>(args);
+                stack.lock().unwrap().pop();
+                result
+            })?;
+        lua.globals().set("include", include_fn)?;
+        Ok(())
+    }
+
     fn setup_sandbox(lua: &Lua) -> Result<()> {
         // Remove dangerous globals
         let globals = lua.globals();
@@ -137,17 +231,19 @@ impl LuaRuntime {
         globals.set("log", log_fn)?;
 
         // uuid4() -> random UUID string
-        let uuid_fn = lua.create_function(|_, ()| Ok(Uuid::new_v4().to_string()))?;
+        let uuid_fn = lua.create_function(|_, ()| Ok(determinism::uuid4().to_string()))?;
         globals.set("uuid4", uuid_fn)?;
 
         // now_rfc3339() -> RFC3339 timestamp
-        let now_fn = lua.create_function(|_, ()| Ok(Utc::now().to_rfc3339()))?;
+        let now_fn = lua.create_function(|_, ()| Ok(determinism::now().to_rfc3339()))?;
         globals.set("now_rfc3339", now_fn)?;
 
         // now_unix_ms() -> epoch milliseconds
-        let now_unix_fn = lua.create_function(|_, ()| Ok(Utc::now().timestamp_millis()))?;
+        let now_unix_fn = lua.create_function(|_, ()| Ok(determinism::now().timestamp_millis()))?;
         globals.set("now_unix_ms", now_unix_fn)?;
 
+        crate::lua::sandbox::seed_math_random(lua)?;
+
         Ok(())
     }
 }
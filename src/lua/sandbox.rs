@@ -1,11 +1,11 @@
 use anyhow::Result;
 use base64::Engine;
-use chrono::Utc;
 use mlua::prelude::*;
 use tracing::{debug, error, info, trace, warn};
-use uuid::Uuid;
 
+use crate::engine::determinism;
 use crate::engine::types::Context;
+use crate::nodes::http::{decode_response_text, do_http_request};
 use crate::nodes::utility::code::{json_value_to_lua_table, lua_value_to_json};
 
 /// Set up the sandboxed Lua environment with standard globals.
@@ -108,17 +108,19 @@ pub(crate) fn setup_sandbox(lua: &Lua, ctx: &Context) -> Result<LuaValue> {
     globals.set("log", log_fn)?;
 
     // uuid4() -> random UUID string
-    let uuid_fn = lua.create_function(|_, ()| Ok(Uuid::new_v4().to_string()))?;
+    let uuid_fn = lua.create_function(|_, ()| Ok(determinism::uuid4().to_string()))?;
     globals.set("uuid4", uuid_fn)?;
 
     // now_rfc3339() -> RFC3339 timestamp
-    let now_fn = lua.create_function(|_, ()| Ok(Utc::now().to_rfc3339()))?;
+    let now_fn = lua.create_function(|_, ()| Ok(determinism::now().to_rfc3339()))?;
     globals.set("now_rfc3339", now_fn)?;
 
     // now_unix_ms() -> epoch milliseconds
-    let now_unix_fn = lua.create_function(|_, ()| Ok(Utc::now().timestamp_millis()))?;
+    let now_unix_fn = lua.create_function(|_, ()| Ok(determinism::now().timestamp_millis()))?;
     globals.set("now_unix_ms", now_unix_fn)?;
 
+    seed_math_random(lua)?;
+
     // ctx table
     let ctx_value = json_value_to_lua_table(
         lua,
@@ -129,6 +131,105 @@ pub(crate) fn setup_sandbox(lua: &Lua, ctx: &Context) -> Result<LuaValue> {
     Ok(ctx_value)
 }
 
+/// Expose `http_get(url, headers)` and `http_request(opts)` for steps that
+/// opt in with `allow_http = true`.
+///
+/// Requests go through the HTTP nodes' request path, so `opts` accepts the
+/// same keys as `http_request` (`method`, `url`, `headers`, `body`,
+/// `body_type`, `auth`, `timeout`, ...) with the step's `ctx` used for
+/// interpolation. Both return `{ status, success, body, headers }` where
+/// `body` is the response text; a non-2xx status is returned rather than
+/// raised unless `fail_on_status = true`. The functions are async, so the
+/// chunk must be run with `call_async`/`eval_async`.
+pub(crate) fn setup_http(lua: &Lua, ctx: &Context) -> Result<()> {
+    let globals = lua.globals();
+
+    let get_ctx = ctx.clone();
+    let get_fn =
+        lua.create_async_function(move |lua, (url, headers): (String, Option<LuaTable>)| {
+            let ctx = get_ctx.clone();
+            async move {
+                let mut opts = serde_json::json!({ "url": url });
+                if let Some(headers) = headers {
+                    opts["headers"] =
+                        lua_value_to_json(&LuaValue::Table(headers)).map_err(LuaError::external)?;
+                }
+                lua_http_request(&lua, "GET", opts, &ctx).await
+            }
+        })?;
+    globals.set("http_get", get_fn)?;
+
+    let request_ctx = ctx.clone();
+    let request_fn = lua.create_async_function(move |lua, opts: LuaTable| {
+        let ctx = request_ctx.clone();
+        async move {
+            let opts = lua_value_to_json(&LuaValue::Table(opts)).map_err(LuaError::external)?;
+            let method = opts
+                .get("method")
+                .and_then(|v| v.as_str())
+                .unwrap_or("GET")
+                .to_string();
+            lua_http_request(&lua, &method, opts, &ctx).await
+        }
+    })?;
+    globals.set("http_request", request_fn)?;
+
+    Ok(())
+}
+
+async fn lua_http_request(
+    lua: &Lua,
+    method: &str,
+    opts: serde_json::Value,
+    ctx: &Context,
+) -> LuaResult<LuaValue> {
+    let serde_json::Value::Object(mut config) = opts else {
+        return Err(LuaError::runtime("http_request expects a table of options"));
+    };
+    config.insert("output_key".to_string(), serde_json::json!("response"));
+    config
+        .entry("fail_on_status")
+        .or_insert(serde_json::json!(false));
+    // A string body is sent as-is rather than JSON-encoded.
+    if config.get("body").is_some_and(|body| body.is_string()) {
+        config
+            .entry("body_type")
+            .or_insert(serde_json::json!("text"));
+    }
+    // Fetch the raw bytes so `body` is the exact response text; the HTTP
+    // nodes would otherwise hand back JSON bodies already decoded.
+    let raw_text = !config.contains_key("response_encoding");
+    if raw_text {
+        config.insert("response_encoding".to_string(), serde_json::json!("base64"));
+    }
+
+    let output = do_http_request(method, &serde_json::Value::Object(config), ctx)
+        .await
+        .map_err(|e| LuaError::runtime(format!("http_{}: {:#}", method.to_lowercase(), e)))?;
+
+    let body = match output.get("response_data") {
+        Some(serde_json::Value::String(encoded)) if raw_text => {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .map_err(LuaError::external)?;
+            let content_type = output
+                .get("response_headers")
+                .and_then(|headers| headers.get("content-type"))
+                .and_then(|v| v.as_str());
+            serde_json::Value::String(decode_response_text(&bytes, content_type))
+        }
+        Some(data) => data.clone(),
+        None => serde_json::Value::String(String::new()),
+    };
+    let response = serde_json::json!({
+        "status": output.get("response_status"),
+        "success": output.get("response_success"),
+        "body": body,
+        "headers": output.get("response_headers"),
+    });
+    json_value_to_lua_table(lua, &response).map_err(LuaError::external)
+}
+
 fn stringify_lua_value(value: &LuaValue) -> Result<String> {
     match value {
         LuaValue::String(s) => Ok(s.to_str()?.to_string()),
@@ -145,3 +246,14 @@ fn stringify_lua_value(value: &LuaValue) -> Result<String> {
         }
     }
 }
+
+/// Reseed `math.random` from the active deterministic seed, if any, so seeded
+/// runs see the same pseudo-random sequence every time.
+pub(crate) fn seed_math_random(lua: &Lua) -> Result<()> {
+    if let Some(d) = determinism::current() {
+        let math: LuaTable = lua.globals().get("math")?;
+        let randomseed: LuaFunction = math.get("randomseed")?;
+        randomseed.call::<()>(d.next_u64() as i64)?;
+    }
+    Ok(())
+}
@@ -229,6 +229,10 @@ impl Node for AiChunkNode {
         "Split text into chunks using fixed-size, delimiter, or subtitle cue strategies"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let mode = config
             .get("mode")
@@ -54,6 +54,10 @@ impl Node for AiChunkMergeNode {
         "Merge small text chunks into token-budget groups"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let source_key = config
             .get("source_key")
@@ -1,9 +1,12 @@
+use std::time::Instant;
+
 use anyhow::Result;
 use async_trait::async_trait;
 use futures_util::TryStreamExt;
 use serde_json::Value;
 
-use crate::engine::types::{Context, NodeOutput};
+use crate::engine::profile;
+use crate::engine::types::{Context, NodeError, NodeOutput};
 use crate::nodes::Node;
 use crate::util::limits;
 
@@ -126,6 +129,7 @@ impl Node for LlmNode {
             .timeout(std::time::Duration::from_secs_f64(timeout_s))
             .build()?;
 
+        let started = Instant::now();
         let response = client
             .post(&url)
             .headers(headers)
@@ -133,17 +137,21 @@ impl Node for LlmNode {
             .send()
             .await
             .map_err(|e| anyhow::anyhow!("llm: request failed: {}", e))?;
+        profile::record("llm.first_byte_ms", started.elapsed());
 
         let status = response.status();
         let response_text = read_capped_response_body(response, max_response_bytes).await?;
+        profile::record("llm.total_ms", started.elapsed());
 
         if !status.is_success() {
-            anyhow::bail!(
-                "llm: request to {} returned {}: {}",
-                provider_name,
-                url,
-                response_text
-            );
+            return Err(NodeError::provider(
+                status.as_u16(),
+                format!(
+                    "llm: request to {} returned {}: {}",
+                    provider_name, url, response_text
+                ),
+            )
+            .into());
         }
 
         let parsed: Value =
@@ -4,10 +4,11 @@ use aws_sdk_s3::primitives::ByteStream;
 
 use crate::engine::types::{Context, NodeOutput};
 use crate::nodes::Node;
+use crate::util::compression::Codec;
 
 use super::s3_helpers::{
-    build_s3_client, resolve_optional, resolve_output_key, resolve_payload_bytes, resolve_required,
-    write_payload_to_output,
+    build_s3_client, resolve_bool, resolve_optional, resolve_output_key, resolve_payload_bytes,
+    resolve_required, write_payload_to_output,
 };
 
 pub struct S3PutObjectNode;
@@ -101,6 +102,20 @@ impl Node for S3GetObjectNode {
             .get("encoding")
             .and_then(|value| value.as_str())
             .unwrap_or("text");
+        // `decompress` names a codec to always apply, or `auto` to follow the
+        // object's Content-Encoding (same as `auto_decompress = true`).
+        let forced_codec = match config.get("decompress").and_then(|value| value.as_str()) {
+            None | Some("none" | "auto") => None,
+            Some(name) => Some(Codec::parse(name).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "s3_get_object 'decompress' must be 'auto', 'none', 'gzip', 'zstd' or 'bzip2', got '{}'",
+                    name
+                )
+            })?),
+        };
+        let auto_decompress = config.get("decompress").and_then(|value| value.as_str())
+            == Some("auto")
+            || resolve_bool(config, "auto_decompress", None);
 
         let client = build_s3_client(config, ctx).await?;
         let response = client
@@ -114,8 +129,24 @@ impl Node for S3GetObjectNode {
         let content_length = response.content_length();
         let e_tag = response.e_tag().map(ToString::to_string);
         let last_modified = response.last_modified().map(ToString::to_string);
+        let content_encoding = response.content_encoding().map(ToString::to_string);
 
-        let bytes = response.body.collect().await?.into_bytes().to_vec();
+        let mut bytes = response.body.collect().await?.into_bytes().to_vec();
+        let codec = forced_codec.or_else(|| {
+            auto_decompress
+                .then(|| content_encoding.as_deref().and_then(Codec::parse))
+                .flatten()
+        });
+        if let Some(codec) = codec {
+            bytes = codec.decode(&bytes).map_err(|error| {
+                anyhow::anyhow!(
+                    "s3_get_object failed to decompress '{}' as {}: {}",
+                    key,
+                    codec.name(),
+                    error
+                )
+            })?;
+        }
 
         let mut output = NodeOutput::new();
         output.insert(
@@ -150,6 +181,16 @@ impl Node for S3GetObjectNode {
                 serde_json::Value::String(last_modified.to_string()),
             );
         }
+        if let Some(content_encoding) = content_encoding {
+            output.insert(
+                format!("{}_content_encoding", output_key),
+                serde_json::Value::String(content_encoding),
+            );
+        }
+        output.insert(
+            format!("{}_decompressed", output_key),
+            serde_json::Value::Bool(codec.is_some()),
+        );
 
         write_payload_to_output(&mut output, &output_key, &bytes, output_encoding)?;
         output.insert(
@@ -160,6 +201,123 @@ impl Node for S3GetObjectNode {
     }
 }
 
+pub struct S3HeadObjectNode;
+
+#[async_trait]
+impl Node for S3HeadObjectNode {
+    fn node_type(&self) -> &str {
+        "s3_head_object"
+    }
+
+    fn description(&self) -> &str {
+        "Check whether an S3 object exists and read its metadata"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let bucket =
+            resolve_required(config, "bucket", Some("S3_BUCKET"), ctx).ok_or_else(|| {
+                anyhow::anyhow!("s3_head_object requires 'bucket' or S3_BUCKET env var")
+            })?;
+        let key = resolve_required(config, "key", None, ctx)
+            .ok_or_else(|| anyhow::anyhow!("s3_head_object requires 'key'"))?;
+        let output_key = resolve_output_key(config);
+        let required = resolve_bool(config, "required", None);
+        let version_id = resolve_optional(config, "version_id", None, ctx);
+
+        let client = build_s3_client(config, ctx).await?;
+        let mut request = client.head_object().bucket(bucket.clone()).key(key.clone());
+        if let Some(version_id) = version_id {
+            request = request.version_id(version_id);
+        }
+        let result = request.send().await;
+
+        let mut output = NodeOutput::new();
+        output.insert(
+            format!("{}_bucket", output_key),
+            serde_json::Value::String(bucket.clone()),
+        );
+        output.insert(
+            format!("{}_key", output_key),
+            serde_json::Value::String(key.clone()),
+        );
+
+        let response = match result {
+            Ok(response) => response,
+            Err(error) if error.as_service_error().is_some_and(|e| e.is_not_found()) => {
+                if required {
+                    anyhow::bail!("s3_head_object: '{}/{}' does not exist", bucket, key);
+                }
+                output.insert(
+                    format!("{}_exists", output_key),
+                    serde_json::Value::Bool(false),
+                );
+                output.insert(
+                    format!("{}_success", output_key),
+                    serde_json::Value::Bool(true),
+                );
+                return Ok(output);
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        output.insert(
+            format!("{}_exists", output_key),
+            serde_json::Value::Bool(true),
+        );
+        if let Some(content_type) = response.content_type() {
+            output.insert(
+                format!("{}_content_type", output_key),
+                serde_json::Value::String(content_type.to_string()),
+            );
+        }
+        if let Some(content_length) = response.content_length() {
+            output.insert(
+                format!("{}_content_length", output_key),
+                serde_json::json!(content_length),
+            );
+        }
+        if let Some(content_encoding) = response.content_encoding() {
+            output.insert(
+                format!("{}_content_encoding", output_key),
+                serde_json::Value::String(content_encoding.to_string()),
+            );
+        }
+        if let Some(etag) = response.e_tag() {
+            output.insert(
+                format!("{}_etag", output_key),
+                serde_json::Value::String(etag.to_string()),
+            );
+        }
+        if let Some(last_modified) = response.last_modified() {
+            output.insert(
+                format!("{}_last_modified", output_key),
+                serde_json::Value::String(last_modified.to_string()),
+            );
+        }
+        if let Some(version_id) = response.version_id() {
+            output.insert(
+                format!("{}_version_id", output_key),
+                serde_json::Value::String(version_id.to_string()),
+            );
+        }
+        let metadata: serde_json::Map<String, serde_json::Value> = response
+            .metadata()
+            .into_iter()
+            .flatten()
+            .map(|(name, value)| (name.clone(), serde_json::Value::String(value.clone())))
+            .collect();
+        output.insert(
+            format!("{}_metadata", output_key),
+            serde_json::Value::Object(metadata),
+        );
+        output.insert(
+            format!("{}_success", output_key),
+            serde_json::Value::Bool(true),
+        );
+        Ok(output)
+    }
+}
+
 pub struct S3DeleteObjectNode;
 
 #[async_trait]
@@ -0,0 +1,185 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::engine::types::{Context, NodeError, NodeOutput};
+use crate::nodes::Node;
+
+use super::conditional::{evaluate_condition, resolve_nested};
+
+pub struct AssertNode;
+
+#[async_trait]
+impl Node for AssertNode {
+    fn node_type(&self) -> &str {
+        "assert"
+    }
+
+    fn description(&self) -> &str {
+        "Check a list of conditions against context and fail or route on the first miss"
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    /// In `on_fail = "route"` mode the `_route_<step>` key depends on the
+    /// step name, so the outputs cannot be known from config.
+    fn produced_keys(&self, config: &serde_json::Value) -> Option<Vec<String>> {
+        if config.get("on_fail").and_then(|v| v.as_str()) == Some("route") {
+            return None;
+        }
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .unwrap_or("assert");
+        Some(vec![output_key.to_string()])
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let conditions = config
+            .get("conditions")
+            .and_then(|v| v.as_array())
+            .filter(|c| !c.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("assert requires a non-empty 'conditions' array"))?;
+
+        let route_on_fail = match config
+            .get("on_fail")
+            .and_then(|v| v.as_str())
+            .unwrap_or("error")
+        {
+            "error" => false,
+            "route" => true,
+            other => anyhow::bail!(
+                "assert: invalid on_fail '{}'; expected 'error' or 'route'",
+                other
+            ),
+        };
+
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .unwrap_or("assert");
+
+        let mut failures = Vec::new();
+        for (idx, condition) in conditions.iter().enumerate() {
+            if let Some(reason) = check(condition, ctx)? {
+                let label = condition
+                    .get("message")
+                    .and_then(|v| v.as_str())
+                    .map(|m| format!("{} ({})", m, reason))
+                    .unwrap_or(reason);
+                failures.push(format!("assertion {} failed: {}", idx + 1, label));
+            }
+        }
+
+        if !route_on_fail && let Some(first) = failures.first() {
+            let more = match failures.len() {
+                1 => String::new(),
+                n => format!(" (and {} more)", n - 1),
+            };
+            return Err(NodeError::validation(format!("assert: {}{}", first, more)).into());
+        }
+
+        let passed = failures.is_empty();
+        let mut output = NodeOutput::new();
+        output.insert(format!("{}_passed", output_key), Value::Bool(passed));
+        output.insert(
+            format!("{}_failures", output_key),
+            Value::Array(failures.into_iter().map(Value::String).collect()),
+        );
+
+        if route_on_fail {
+            let step_name = config
+                .get("_step_name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("assert");
+            let route_key = if passed { "pass_route" } else { "fail_route" };
+            let route = config
+                .get(route_key)
+                .and_then(|v| v.as_str())
+                .unwrap_or(if passed { "pass" } else { "fail" });
+            output.insert(
+                format!("_route_{}", step_name),
+                Value::String(route.to_string()),
+            );
+        }
+        Ok(output)
+    }
+}
+
+/// Check one condition. Returns `None` when it holds, or why it did not.
+///
+/// A condition is either an `if_node` expression string, or an object with a
+/// `path` and one or more of `equals`, `matches`, `exists`, `min`, `max`.
+fn check(condition: &Value, ctx: &Context) -> Result<Option<String>> {
+    if let Some(expr) = condition.as_str() {
+        return Ok((!evaluate_condition(expr, ctx)).then(|| format!("`{}` is false", expr)));
+    }
+
+    let path = condition
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            anyhow::anyhow!("assert: each condition must be an expression string or have a 'path'")
+        })?;
+    let actual = resolve_nested(path.strip_prefix("ctx.").unwrap_or(path), ctx);
+    let shown = actual.map_or("nothing".to_string(), |v| v.to_string());
+
+    if let Some(exists) = condition.get("exists").and_then(|v| v.as_bool())
+        && exists != actual.is_some()
+    {
+        return Ok(Some(if exists {
+            format!("{} does not exist", path)
+        } else {
+            format!("{} exists ({})", path, shown)
+        }));
+    }
+
+    if let Some(expected) = condition.get("equals")
+        && !actual.is_some_and(|a| values_equal(a, expected))
+    {
+        return Ok(Some(format!(
+            "{} expected to equal {}, got {}",
+            path, expected, shown
+        )));
+    }
+
+    if let Some(pattern) = condition.get("matches").and_then(|v| v.as_str()) {
+        let re = regex::Regex::new(pattern)
+            .map_err(|e| anyhow::anyhow!("assert: invalid regex '{}': {}", pattern, e))?;
+        let text = match actual {
+            Some(Value::String(s)) => Some(s.clone()),
+            Some(Value::Number(n)) => Some(n.to_string()),
+            _ => None,
+        };
+        if !text.is_some_and(|t| re.is_match(&t)) {
+            return Ok(Some(format!(
+                "{} expected to match /{}/, got {}",
+                path, pattern, shown
+            )));
+        }
+    }
+
+    let number = actual.and_then(|v| v.as_f64());
+    if let Some(min) = condition.get("min").and_then(|v| v.as_f64())
+        && !number.is_some_and(|n| n >= min)
+    {
+        return Ok(Some(format!("{} expected >= {}, got {}", path, min, shown)));
+    }
+    if let Some(max) = condition.get("max").and_then(|v| v.as_f64())
+        && !number.is_some_and(|n| n <= max)
+    {
+        return Ok(Some(format!("{} expected <= {}, got {}", path, max, shown)));
+    }
+
+    Ok(None)
+}
+
+/// JSON equality that treats `1` and `1.0` as equal.
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(x), Some(y)) => x == y,
+        _ => a == b,
+    }
+}
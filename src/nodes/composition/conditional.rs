@@ -16,6 +16,16 @@ impl Node for IfNode {
         "Evaluate a condition and set a route"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    /// The `_route_<step>` key depends on the step name, so the outputs
+    /// cannot be known from config.
+    fn produced_keys(&self, _config: &serde_json::Value) -> Option<Vec<String>> {
+        None
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let condition = config
             .get("condition")
@@ -66,6 +76,16 @@ impl Node for SwitchNode {
         "Multi-case routing based on a value"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    /// The `_route_<step>` key depends on the step name, so the outputs
+    /// cannot be known from config.
+    fn produced_keys(&self, _config: &serde_json::Value) -> Option<Vec<String>> {
+        None
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let value_expr = config
             .get("value")
@@ -122,6 +142,16 @@ impl Node for IfHttpStatusNode {
         "Route execution based on an HTTP status code"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    /// The `_route_<step>` key depends on the step name, so the outputs
+    /// cannot be known from config.
+    fn produced_keys(&self, _config: &serde_json::Value) -> Option<Vec<String>> {
+        None
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let status_key = config
             .get("status_key")
@@ -232,6 +262,16 @@ impl Node for IfBodyContainsNode {
         "Route execution based on whether context content contains a pattern"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    /// The `_route_<step>` key depends on the step name, so the outputs
+    /// cannot be known from config.
+    fn produced_keys(&self, _config: &serde_json::Value) -> Option<Vec<String>> {
+        None
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let source_key = config
             .get("source_key")
@@ -311,7 +351,7 @@ impl Node for IfBodyContainsNode {
 
 /// Evaluate a simple condition expression against context.
 /// Supports: ctx.key > N, ctx.key == "value", ctx.key exists, ctx.key != N
-fn evaluate_condition(condition: &str, ctx: &Context) -> bool {
+pub(crate) fn evaluate_condition(condition: &str, ctx: &Context) -> bool {
     let condition = condition.trim();
 
     // "ctx.key exists"
@@ -380,8 +420,8 @@ fn compare_values(left: Option<&serde_json::Value>, op: &str, right: &str) -> bo
     }
 }
 
-/// Resolve a dotted path like "user.email" from context.
-fn resolve_nested<'a>(path: &str, ctx: &'a Context) -> Option<&'a serde_json::Value> {
+/// Resolve a dotted path like "user.email" or "items.0.sku" from context.
+pub(super) fn resolve_nested<'a>(path: &str, ctx: &'a Context) -> Option<&'a serde_json::Value> {
     let parts: Vec<&str> = path.split('.').collect();
 
     if parts.is_empty() {
@@ -391,7 +431,10 @@ fn resolve_nested<'a>(path: &str, ctx: &'a Context) -> Option<&'a serde_json::Va
     let mut current = ctx.get(parts[0])?;
 
     for part in &parts[1..] {
-        current = current.get(part)?;
+        current = match current {
+            serde_json::Value::Array(items) => items.get(part.parse::<usize>().ok()?)?,
+            other => other.get(part)?,
+        };
     }
 
     Some(current)
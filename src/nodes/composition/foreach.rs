@@ -1,15 +1,53 @@
+use std::sync::{Arc, Mutex};
+
 use anyhow::Result;
 use async_trait::async_trait;
 use base64::Engine;
+use futures_util::StreamExt;
 use mlua::prelude::*;
 
 use crate::engine::types::{Context, NodeOutput};
 use crate::lua::sandbox;
-use crate::nodes::Node;
 use crate::nodes::utility::code::{json_value_to_lua_table, lua_value_to_json};
+use crate::nodes::{Node, NodeRegistry};
 use crate::util::limits::{LuaExecutionLimits, apply_lua_limits, collect_lua_garbage};
 
-pub struct ForEachNode;
+use super::loop_node::{LoopNode, body_parts};
+use super::parallel::ParallelNode;
+use super::parallel_subworkflows::ParallelSubworkflowsNode;
+use super::subworkflow::SubworkflowNode;
+use super::tool_dispatch::ToolDispatchNode;
+
+pub struct ForEachNode {
+    /// Registry containing all non-subworkflow nodes. Composition nodes are
+    /// added back at execution time so the body can use them too.
+    pub base_registry: Arc<NodeRegistry>,
+}
+
+impl ForEachNode {
+    fn child_registry(&self) -> Arc<NodeRegistry> {
+        let mut child = self.base_registry.snapshot();
+        child.register(Arc::new(SubworkflowNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(ParallelSubworkflowsNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(ToolDispatchNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(ParallelNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(LoopNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(ForEachNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        Arc::new(child)
+    }
+}
 
 #[async_trait]
 impl Node for ForEachNode {
@@ -18,7 +56,20 @@ impl Node for ForEachNode {
     }
 
     fn description(&self) -> &str {
-        "Iterate over an array, execute a Lua function per item, and collect results"
+        "Iterate over an array, run a Lua function or a node per item, and collect results"
+    }
+
+    /// A Lua transform can call `uuid4`, `now_rfc3339` and `math.random`,
+    /// so only a body is pure, and only as pure as the node it runs.
+    fn is_pure_with(&self, config: &serde_json::Value) -> bool {
+        let Some(body) = config.get("body") else {
+            return false;
+        };
+        body_parts("foreach", body).is_ok_and(|(node_type, body_config)| {
+            self.child_registry()
+                .get(node_type)
+                .is_some_and(|node| node.is_pure_with(body_config))
+        })
     }
 
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
@@ -32,16 +83,23 @@ impl Node for ForEachNode {
             .and_then(|v| v.as_str())
             .unwrap_or("foreach_results");
 
-        let b64 = config
-            .get("transform_bytecode_b64")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("foreach requires 'transform' to be a function"))?;
-
         let filter_nulls = config
             .get("filter_nulls")
             .and_then(|v| v.as_bool())
             .unwrap_or(true);
 
+        let max_concurrency = config
+            .get("max_concurrency")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize)
+            .filter(|n| *n > 0)
+            .unwrap_or(1);
+
+        let continue_on_error = config
+            .get("continue_on_error")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
         let source = ctx
             .get(source_key)
             .ok_or_else(|| anyhow::anyhow!("Key '{}' not found in context", source_key))?;
@@ -50,35 +108,69 @@ impl Node for ForEachNode {
             .as_array()
             .ok_or_else(|| anyhow::anyhow!("Value at '{}' is not an array", source_key))?;
 
-        let lua = Lua::new();
-        let limits = LuaExecutionLimits::from_env();
-        apply_lua_limits(&lua, limits)?;
-        sandbox::setup_sandbox(&lua, ctx)?;
-
-        // Decode and load the transform function
-        let bytecode = base64::engine::general_purpose::STANDARD
-            .decode(b64)
-            .map_err(|e| anyhow::anyhow!("Failed to decode transform bytecode: {}", e))?;
-        let func: LuaFunction = lua
-            .load(&bytecode)
-            .into_function()
-            .map_err(|e| anyhow::anyhow!("Failed to load transform function: {}", e))?;
-
-        let mut results = Vec::with_capacity(items.len());
+        let outcomes = if let Some(body) = config.get("body") {
+            if config.get("transform_bytecode_b64").is_some() {
+                anyhow::bail!("foreach accepts either 'transform' or 'body', not both");
+            }
+            let (node_type, body_config) = body_parts("foreach", body)?;
+            let node = self
+                .child_registry()
+                .get(node_type)
+                .ok_or_else(|| anyhow::anyhow!("foreach: unknown node type '{}'", node_type))?;
+            run_body(
+                node.as_ref(),
+                body_config,
+                items,
+                ctx,
+                max_concurrency,
+                continue_on_error,
+            )
+            .await?
+        } else {
+            let b64 = config
+                .get("transform_bytecode_b64")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "foreach requires 'transform' to be a function or a 'body' node"
+                    )
+                })?;
 
-        for (i, item_json) in items.iter().enumerate() {
-            let item_lua = json_value_to_lua_table(&lua, item_json)?;
+            // Decode the transform once; every worker VM loads the same bytecode
+            let bytecode = base64::engine::general_purpose::STANDARD
+                .decode(b64)
+                .map_err(|e| anyhow::anyhow!("Failed to decode transform bytecode: {}", e))?;
+            let limits = LuaExecutionLimits::from_env();
 
-            let result: LuaValue = func.call((item_lua, (i + 1) as i64)).map_err(|e| {
-                anyhow::anyhow!(
-                    "foreach transform failed on item {} (index {}): {}",
-                    i,
-                    i + 1,
-                    e
+            if max_concurrency == 1 {
+                run_sequential(items, ctx, &bytecode, limits, continue_on_error)?
+            } else {
+                run_concurrent(
+                    items,
+                    ctx,
+                    bytecode,
+                    limits,
+                    max_concurrency,
+                    continue_on_error,
                 )
-            })?;
+                .await?
+            }
+        };
 
-            let json_val = lua_value_to_json(&result)?;
+        let mut results = Vec::with_capacity(items.len());
+        let mut errors = Vec::new();
+        for (i, outcome) in outcomes.into_iter().enumerate() {
+            let json_val = match outcome {
+                Ok(value) => value,
+                Err(message) => {
+                    errors.push(serde_json::json!({
+                        "index": i + 1,
+                        "item": items[i],
+                        "error": message,
+                    }));
+                    serde_json::Value::Null
+                }
+            };
             if filter_nulls && json_val.is_null() {
                 continue;
             }
@@ -94,7 +186,176 @@ impl Node for ForEachNode {
             format!("{}_count", output_key),
             serde_json::json!(results.len()),
         );
-        collect_lua_garbage(&lua, limits)?;
+        if continue_on_error {
+            output.insert(
+                format!("{}_errors", output_key),
+                serde_json::Value::Array(errors),
+            );
+        }
         Ok(output)
     }
 }
+
+/// One Lua VM with the transform loaded, reused across items.
+struct TransformVm {
+    lua: Lua,
+    func: LuaFunction,
+}
+
+impl TransformVm {
+    fn new(ctx: &Context, bytecode: &[u8], limits: LuaExecutionLimits) -> Result<Self> {
+        let lua = Lua::new();
+        apply_lua_limits(&lua, limits)?;
+        sandbox::setup_sandbox(&lua, ctx)?;
+        let func: LuaFunction = lua
+            .load(bytecode)
+            .into_function()
+            .map_err(|e| anyhow::anyhow!("Failed to load transform function: {}", e))?;
+        Ok(Self { lua, func })
+    }
+
+    /// Run the transform on item `i`. `Err` carries the per-item message.
+    fn call(
+        &self,
+        i: usize,
+        item_json: &serde_json::Value,
+    ) -> Result<Result<serde_json::Value, String>> {
+        let item_lua = json_value_to_lua_table(&self.lua, item_json)?;
+        let result = self
+            .func
+            .call::<LuaValue>((item_lua, (i + 1) as i64))
+            .map_err(|e| {
+                format!(
+                    "foreach transform failed on item {} (index {}): {}",
+                    i,
+                    i + 1,
+                    e
+                )
+            })
+            .and_then(|value| lua_value_to_json(&value).map_err(|e| format!("{:#}", e)));
+        Ok(result)
+    }
+}
+
+/// Per-item outcome, in input order.
+type Outcomes = Vec<Result<serde_json::Value, String>>;
+
+fn run_sequential(
+    items: &[serde_json::Value],
+    ctx: &Context,
+    bytecode: &[u8],
+    limits: LuaExecutionLimits,
+    continue_on_error: bool,
+) -> Result<Outcomes> {
+    let vm = TransformVm::new(ctx, bytecode, limits)?;
+    let mut outcomes = Vec::with_capacity(items.len());
+    for (i, item_json) in items.iter().enumerate() {
+        match vm.call(i, item_json)? {
+            Err(message) if !continue_on_error => anyhow::bail!(message),
+            outcome => outcomes.push(outcome),
+        }
+    }
+    collect_lua_garbage(&vm.lua, limits)?;
+    Ok(outcomes)
+}
+
+/// Run the body node once per item, up to `max_concurrency` at a time. Each
+/// run sees the step's context plus `_foreach_index` (1-based) and
+/// `_foreach_item`; its result is the body's output object.
+async fn run_body(
+    node: &dyn Node,
+    body_config: &serde_json::Value,
+    items: &[serde_json::Value],
+    ctx: &Context,
+    max_concurrency: usize,
+    continue_on_error: bool,
+) -> Result<Outcomes> {
+    let mut running =
+        futures_util::stream::iter(items.iter().cloned().enumerate().map(|(i, item)| {
+            let mut item_ctx = ctx.clone();
+            item_ctx.insert("_foreach_index".to_string(), serde_json::json!(i + 1));
+            item_ctx.insert("_foreach_item".to_string(), item);
+            async move {
+                node.execute(body_config, &item_ctx)
+                    .await
+                    .map(|output| serde_json::Value::Object(output.into_iter().collect()))
+                    .map_err(|e| {
+                        format!(
+                            "foreach body failed on item {} (index {}): {:#}",
+                            i,
+                            i + 1,
+                            e
+                        )
+                    })
+            }
+        }))
+        .buffered(max_concurrency);
+
+    let mut outcomes = Vec::with_capacity(items.len());
+    while let Some(outcome) = running.next().await {
+        match outcome {
+            // Returning drops `running`; items not yet started never run.
+            Err(message) if !continue_on_error => anyhow::bail!(message),
+            outcome => outcomes.push(outcome),
+        }
+    }
+    Ok(outcomes)
+}
+
+/// Run up to `max_concurrency` items at once, each on a blocking thread
+/// with a VM borrowed from a pool, so at most `max_concurrency` VMs exist.
+async fn run_concurrent(
+    items: &[serde_json::Value],
+    ctx: &Context,
+    bytecode: Vec<u8>,
+    limits: LuaExecutionLimits,
+    max_concurrency: usize,
+    continue_on_error: bool,
+) -> Result<Outcomes> {
+    let ctx = Arc::new(ctx.clone());
+    let bytecode: Arc<[u8]> = bytecode.into();
+    let pool: Arc<Mutex<Vec<TransformVm>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let mut running =
+        futures_util::stream::iter(items.iter().cloned().enumerate().map(|(i, item_json)| {
+            let ctx = ctx.clone();
+            let bytecode = bytecode.clone();
+            let pool = pool.clone();
+            async move {
+                let outcome = tokio::task::spawn_blocking(move || {
+                    let pooled = pool.lock().expect("foreach VM pool poisoned").pop();
+                    let vm = match pooled {
+                        Some(vm) => vm,
+                        None => TransformVm::new(&ctx, &bytecode, limits)?,
+                    };
+                    let outcome = vm.call(i, &item_json);
+                    pool.lock().expect("foreach VM pool poisoned").push(vm);
+                    outcome
+                })
+                .await
+                .map_err(|e| anyhow::anyhow!("foreach worker panicked: {}", e))
+                .and_then(|outcome| outcome);
+                (i, outcome)
+            }
+        }))
+        .buffer_unordered(max_concurrency);
+
+    let mut outcomes: Vec<Option<Result<serde_json::Value, String>>> =
+        (0..items.len()).map(|_| None).collect();
+    while let Some((i, outcome)) = running.next().await {
+        match outcome? {
+            // Returning drops `running`; items not yet started never run.
+            Err(message) if !continue_on_error => anyhow::bail!(message),
+            outcome => outcomes[i] = Some(outcome),
+        }
+    }
+    drop(running);
+
+    for vm in pool.lock().expect("foreach VM pool poisoned").iter() {
+        collect_lua_garbage(&vm.lua, limits)?;
+    }
+    Ok(outcomes
+        .into_iter()
+        .map(|outcome| outcome.expect("every item ran to completion"))
+        .collect())
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::engine::types::{Context, NodeOutput};
+use crate::nodes::{Node, NodeRegistry};
+
+use super::conditional::evaluate_condition;
+use super::parallel::ParallelNode;
+use super::parallel_subworkflows::ParallelSubworkflowsNode;
+use super::subworkflow::SubworkflowNode;
+use super::tool_dispatch::ToolDispatchNode;
+
+pub struct LoopNode {
+    /// Registry containing all non-subworkflow nodes. Composition nodes are
+    /// added back at execution time so the loop body can use them too.
+    pub base_registry: Arc<NodeRegistry>,
+}
+
+impl LoopNode {
+    fn child_registry(&self) -> Arc<NodeRegistry> {
+        let mut child = self.base_registry.snapshot();
+        child.register(Arc::new(SubworkflowNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(ParallelSubworkflowsNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(ToolDispatchNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(ParallelNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(LoopNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(super::foreach::ForEachNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        Arc::new(child)
+    }
+}
+
+/// The body's node type and config: a node config (`nodes.code({...})`) or
+/// `{ node = "code", config = {...} }`. `owner` prefixes error messages.
+pub(super) fn body_parts<'a>(owner: &str, body: &'a Value) -> Result<(&'a str, &'a Value)> {
+    if let Some(node_type) = body.get("node").and_then(|v| v.as_str()) {
+        let config = body.get("config").unwrap_or(&Value::Null);
+        if !(config.is_object() || config.is_null()) {
+            anyhow::bail!("{}: body 'config' must be a table", owner);
+        }
+        return Ok((node_type, config));
+    }
+    body.get("_node_type")
+        .or_else(|| body.get("node_type"))
+        .and_then(|v| v.as_str())
+        .map(|node_type| (node_type, body))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "{}: 'body' must be a node config (e.g. nodes.code{{...}}) or {{ node = \"...\", config = {{...}} }}",
+                owner
+            )
+        })
+}
+
+#[async_trait]
+impl Node for LoopNode {
+    fn node_type(&self) -> &str {
+        "loop"
+    }
+
+    fn description(&self) -> &str {
+        "Run a node repeatedly while a condition holds, up to max_iterations"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let condition = config
+            .get("condition")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("loop requires 'condition'"))?;
+
+        let max_iterations = config
+            .get("max_iterations")
+            .and_then(|v| v.as_u64())
+            .filter(|n| *n > 0)
+            .ok_or_else(|| {
+                anyhow::anyhow!("loop requires 'max_iterations' as a positive integer")
+            })?;
+
+        let body = config
+            .get("body")
+            .ok_or_else(|| anyhow::anyhow!("loop requires 'body'"))?;
+        let (node_type, body_config) = body_parts("loop", body)?;
+        let node = self
+            .child_registry()
+            .get(node_type)
+            .ok_or_else(|| anyhow::anyhow!("loop: unknown node type '{}'", node_type))?;
+
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .unwrap_or("loop");
+
+        // Each iteration sees the outputs of the ones before it; only the
+        // keys the body wrote are returned to the flow.
+        let mut loop_ctx = ctx.clone();
+        let mut output = NodeOutput::new();
+        let mut iterations = 0u64;
+
+        while evaluate_condition(condition, &loop_ctx) {
+            if iterations == max_iterations {
+                anyhow::bail!(
+                    "loop: condition '{}' still true after max_iterations ({})",
+                    condition,
+                    max_iterations
+                );
+            }
+            iterations += 1;
+            let iteration_output = node
+                .execute(body_config, &loop_ctx)
+                .await
+                .map_err(|e| e.context(format!("loop: iteration {} failed", iterations)))?;
+            for (key, value) in iteration_output {
+                loop_ctx.insert(key.clone(), value.clone());
+                output.insert(key, value);
+            }
+        }
+
+        output.insert(
+            format!("{}_iterations", output_key),
+            serde_json::json!(iterations),
+        );
+        Ok(output)
+    }
+}
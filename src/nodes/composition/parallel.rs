@@ -0,0 +1,259 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures_util::future::BoxFuture;
+use futures_util::{FutureExt, StreamExt};
+use serde_json::Value;
+
+use crate::engine::types::{Context, NodeOutput};
+use crate::nodes::{Node, NodeRegistry};
+
+use super::parallel_subworkflows::ParallelSubworkflowsNode;
+use super::subworkflow::SubworkflowNode;
+use super::tool_dispatch::ToolDispatchNode;
+
+pub struct ParallelNode {
+    /// Registry containing all non-subworkflow nodes. Composition nodes are
+    /// added back at execution time so branches can use them too.
+    pub base_registry: Arc<NodeRegistry>,
+}
+
+impl ParallelNode {
+    fn child_registry(&self) -> Arc<NodeRegistry> {
+        let mut child = self.base_registry.snapshot();
+        child.register(Arc::new(SubworkflowNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(ParallelSubworkflowsNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(ToolDispatchNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(ParallelNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(super::loop_node::LoopNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(super::foreach::ForEachNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        Arc::new(child)
+    }
+}
+
+/// How many branches must succeed for the node to succeed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Completion {
+    All,
+    Any,
+}
+
+/// What to do when two branches write the same output key.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Conflict {
+    /// The later branch in `branches` order wins.
+    Last,
+    /// The earlier branch in `branches` order wins.
+    First,
+    /// Fail the step.
+    Error,
+}
+
+/// A branch's node type and the config it runs with. Branches are either
+/// node configs (`nodes.http_get({...})`) or `{ node = "http_get", config = {...} }`.
+fn branch_parts(branch: &Value, idx: usize) -> Result<(&str, &Value)> {
+    if let Some(node_type) = branch.get("node").and_then(|v| v.as_str()) {
+        let config = branch.get("config").unwrap_or(&Value::Null);
+        if !(config.is_object() || config.is_null()) {
+            anyhow::bail!("parallel: branch {} 'config' must be a table", idx + 1);
+        }
+        return Ok((node_type, config));
+    }
+    branch
+        .get("_node_type")
+        .or_else(|| branch.get("node_type"))
+        .and_then(|v| v.as_str())
+        .map(|node_type| (node_type, branch))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "parallel: branch {} must be a node config (e.g. nodes.http_get{{...}}) or {{ node = \"...\", config = {{...}} }}",
+                idx + 1
+            )
+        })
+}
+
+#[async_trait]
+impl Node for ParallelNode {
+    fn node_type(&self) -> &str {
+        "parallel"
+    }
+
+    fn description(&self) -> &str {
+        "Run an inline group of node configs concurrently and merge their outputs"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let branches = config
+            .get("branches")
+            .and_then(|v| v.as_array())
+            .filter(|branches| !branches.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("parallel requires a non-empty 'branches' array"))?;
+
+        let completion = match config.get("mode").and_then(|v| v.as_str()).unwrap_or("all") {
+            "all" => Completion::All,
+            "any" => Completion::Any,
+            other => anyhow::bail!(
+                "parallel: invalid mode '{}'; expected 'all' or 'any'",
+                other
+            ),
+        };
+
+        let conflict = match config
+            .get("on_conflict")
+            .and_then(|v| v.as_str())
+            .unwrap_or("last")
+        {
+            "last" => Conflict::Last,
+            "first" => Conflict::First,
+            "error" => Conflict::Error,
+            other => anyhow::bail!(
+                "parallel: invalid on_conflict '{}'; expected 'last', 'first', or 'error'",
+                other
+            ),
+        };
+
+        let fail_fast = config
+            .get("fail_fast")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+
+        let max_concurrency = config
+            .get("max_concurrency")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize)
+            .filter(|n| *n > 0)
+            .unwrap_or(branches.len());
+
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .unwrap_or("parallel");
+
+        // Resolve every branch up front so a typo fails before anything runs.
+        let registry = self.child_registry();
+        let mut resolved = Vec::with_capacity(branches.len());
+        for (idx, branch) in branches.iter().enumerate() {
+            let (node_type, branch_config) = branch_parts(branch, idx)?;
+            let node = registry
+                .get(node_type)
+                .ok_or_else(|| anyhow::anyhow!("parallel: unknown node type '{}'", node_type))?;
+            resolved.push((node_type, node, branch_config));
+        }
+
+        let branch_futures: Vec<BoxFuture<'_, (usize, Result<NodeOutput>)>> = resolved
+            .iter()
+            .enumerate()
+            .map(|(idx, (_, node, branch_config))| {
+                let node = node.clone();
+                async move { (idx, node.execute(branch_config, ctx).await) }.boxed()
+            })
+            .collect();
+        let mut running =
+            futures_util::stream::iter(branch_futures).buffer_unordered(max_concurrency);
+
+        let mut results: Vec<Option<Result<NodeOutput>>> =
+            (0..resolved.len()).map(|_| None).collect();
+        while let Some((idx, result)) = running.next().await {
+            match result {
+                // Returning drops `running`, which cancels the branches still in flight.
+                Err(e) if completion == Completion::All && fail_fast => {
+                    return Err(e.context(format!(
+                        "parallel: branch {} ({}) failed",
+                        idx + 1,
+                        resolved[idx].0
+                    )));
+                }
+                result => results[idx] = Some(result),
+            }
+        }
+        drop(running);
+
+        let mut output = NodeOutput::new();
+        let mut branch_results = Vec::with_capacity(results.len());
+        let mut errors = Vec::new();
+        let mut succeeded = 0usize;
+
+        for (idx, result) in results.into_iter().enumerate() {
+            let node_type = resolved[idx].0;
+            match result.expect("every branch ran to completion") {
+                Ok(branch_output) => {
+                    succeeded += 1;
+                    branch_results.push(serde_json::to_value(&branch_output)?);
+                    for (key, value) in branch_output {
+                        if output.contains_key(&key) {
+                            match conflict {
+                                Conflict::Last => {}
+                                Conflict::First => continue,
+                                Conflict::Error => anyhow::bail!(
+                                    "parallel: branch {} ({}) wrote '{}', which an earlier branch already set",
+                                    idx + 1,
+                                    node_type,
+                                    key
+                                ),
+                            }
+                        }
+                        output.insert(key, value);
+                    }
+                }
+                Err(e) => {
+                    branch_results.push(Value::Null);
+                    errors.push(serde_json::json!({
+                        "branch": idx + 1,
+                        "node_type": node_type,
+                        "error": format!("{:#}", e),
+                    }));
+                }
+            }
+        }
+
+        if completion == Completion::All && !errors.is_empty() {
+            anyhow::bail!(
+                "parallel: {} of {} branches failed: {}",
+                errors.len(),
+                branches.len(),
+                Value::Array(errors)
+            );
+        }
+
+        if succeeded == 0 {
+            anyhow::bail!(
+                "parallel: all {} branches failed: {}",
+                branches.len(),
+                Value::Array(errors)
+            );
+        }
+
+        output.insert(
+            format!("{}_succeeded", output_key),
+            serde_json::json!(succeeded),
+        );
+        output.insert(
+            format!("{}_failed", output_key),
+            serde_json::json!(errors.len()),
+        );
+        output.insert(format!("{}_errors", output_key), Value::Array(errors));
+        output.insert(
+            format!("{}_results", output_key),
+            Value::Array(branch_results),
+        );
+        Ok(output)
+    }
+
+    /// Branch outputs are merged into the context under their own keys.
+    fn produced_keys(&self, _config: &serde_json::Value) -> Option<Vec<String>> {
+        None
+    }
+}
@@ -7,6 +7,7 @@ use serde_json::{Map, Value};
 use tokio::sync::Semaphore;
 
 use crate::engine::executor::WorkflowEngine;
+use crate::engine::run_meta;
 use crate::engine::types::{Context, NodeOutput, RunStatus};
 use crate::lua::runtime::LuaRuntime;
 use crate::nodes::{Node, NodeRegistry};
@@ -37,6 +38,15 @@ impl ParallelSubworkflowsNode {
         child.register(Arc::new(super::tool_dispatch::ToolDispatchNode {
             base_registry: self.base_registry.clone(),
         }));
+        child.register(Arc::new(super::parallel::ParallelNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(super::loop_node::LoopNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(super::foreach::ForEachNode {
+            base_registry: self.base_registry.clone(),
+        }));
         Arc::new(child)
     }
 }
@@ -249,6 +259,7 @@ impl Node for ParallelSubworkflowsNode {
 
             let registry = child_registry.clone();
             let sem = semaphore.clone();
+            let environment = run_meta::current_environment();
 
             let handle = tokio::spawn(async move {
                 // Bound concurrent subflow execution — without this, N flows
@@ -257,7 +268,8 @@ impl Node for ParallelSubworkflowsNode {
                 let flow = LuaRuntime::load_flow(&flow_path_str, &registry)?;
                 let flow_name = flow.name.clone();
                 let store: Arc<dyn crate::storage::StateStore> = Arc::new(NullStateStore::new());
-                let engine = WorkflowEngine::new(registry, store.clone(), None);
+                let engine = WorkflowEngine::new(registry, store.clone(), None)
+                    .with_environment(environment);
                 let run_id = engine.execute(&flow, sub_ctx).await?;
                 let run_info = store.get_run_info(&run_id).await?;
                 Ok::<_, anyhow::Error>((idx, flow_name, run_info))
@@ -6,6 +6,7 @@ use async_trait::async_trait;
 use tokio::sync::Semaphore;
 
 use crate::engine::executor::WorkflowEngine;
+use crate::engine::run_meta;
 use crate::engine::types::{Context, NodeOutput, RunStatus};
 use crate::lua::runtime::LuaRuntime;
 use crate::nodes::{Node, NodeRegistry};
@@ -51,6 +52,15 @@ impl SubworkflowNode {
         child.register(Arc::new(super::tool_dispatch::ToolDispatchNode {
             base_registry: self.base_registry.clone(),
         }));
+        child.register(Arc::new(super::parallel::ParallelNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(super::loop_node::LoopNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(super::foreach::ForEachNode {
+            base_registry: self.base_registry.clone(),
+        }));
         Arc::new(child)
     }
 }
@@ -191,10 +201,12 @@ impl Node for SubworkflowNode {
 
             let flow_name = flow.name.clone();
             let flow_name2 = flow_name.clone();
+            let environment = run_meta::current_environment();
             tokio::spawn(async move {
                 // Permit is dropped when this task exits, releasing one slot.
                 let _permit = permit;
-                let engine = WorkflowEngine::new(child_registry, store, None);
+                let engine =
+                    WorkflowEngine::new(child_registry, store, None).with_environment(environment);
                 if let Err(e) = engine.execute(&flow, sub_ctx).await {
                     tracing::error!(
                         flow = %flow_name,
@@ -216,4 +228,10 @@ impl Node for SubworkflowNode {
             Ok(output)
         }
     }
+
+    /// Without `output_key` the child's whole context is merged in.
+    fn produced_keys(&self, config: &serde_json::Value) -> Option<Vec<String>> {
+        let key = config.get("output_key").and_then(|v| v.as_str())?;
+        Some(vec![key.to_string(), "subworkflow".to_string()])
+    }
 }
@@ -36,6 +36,15 @@ impl ToolDispatchNode {
         child.register(Arc::new(ToolDispatchNode {
             base_registry: self.base_registry.clone(),
         }));
+        child.register(Arc::new(super::parallel::ParallelNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(super::loop_node::LoopNode {
+            base_registry: self.base_registry.clone(),
+        }));
+        child.register(Arc::new(super::foreach::ForEachNode {
+            base_registry: self.base_registry.clone(),
+        }));
         Arc::new(child)
     }
 }
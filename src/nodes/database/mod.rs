@@ -1,8 +1,16 @@
 mod arangodb;
+mod pool;
+#[cfg(feature = "postgres")]
+mod postgres;
 mod sql;
+mod transaction;
 
 pub use arangodb::ArangoDbAqlNode;
+pub use pool::pool_size;
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresQueryNode;
 pub use sql::{DbExecNode, DbQueryNode};
+pub use transaction::{DbCommitNode, DbRollbackNode, DbTransactionNode, RunTransactions};
 
 use crate::nodes::NodeRegistry;
 use std::sync::Arc;
@@ -10,5 +18,10 @@ use std::sync::Arc;
 pub fn register_all(registry: &mut NodeRegistry) {
     registry.register(Arc::new(DbQueryNode));
     registry.register(Arc::new(DbExecNode));
+    registry.register(Arc::new(DbTransactionNode));
+    registry.register(Arc::new(DbCommitNode));
+    registry.register(Arc::new(DbRollbackNode));
     registry.register(Arc::new(ArangoDbAqlNode));
+    #[cfg(feature = "postgres")]
+    registry.register(Arc::new(PostgresQueryNode));
 }
@@ -0,0 +1,72 @@
+//! Process-wide connection pools for the database nodes.
+//!
+//! One pool per connection URL is created on first use and shared by every
+//! later step, so a `foreach` over `db_exec` reuses connections instead of
+//! opening one per item. Pools connect lazily and live for the process.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use anyhow::Result;
+use sqlx::pool::PoolOptions;
+use sqlx::{AnyPool, Database, Pool};
+
+use crate::util::limits;
+
+use super::sql::optional_u64_config;
+
+static ANY_POOLS: LazyLock<Mutex<HashMap<String, AnyPool>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[cfg(feature = "postgres")]
+static PG_POOLS: LazyLock<Mutex<HashMap<String, sqlx::PgPool>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Connection cap from the node's `pool_max`, else `IRONFLOW_DB_POOL_MAX`.
+/// It only applies when the pool for a URL is first created.
+fn pool_max(config: &serde_json::Value) -> u32 {
+    optional_u64_config(config, "pool_max")
+        .filter(|max| *max > 0)
+        .unwrap_or_else(limits::db_pool_max)
+        .min(u64::from(u32::MAX)) as u32
+}
+
+fn shared_pool<DB: Database>(
+    pools: &Mutex<HashMap<String, Pool<DB>>>,
+    url: &str,
+    config: &serde_json::Value,
+) -> Result<Pool<DB>> {
+    let mut pools = pools.lock().unwrap();
+    if let Some(pool) = pools.get(url).filter(|pool| !pool.is_closed()) {
+        return Ok(pool.clone());
+    }
+    let pool = PoolOptions::<DB>::new()
+        .max_connections(pool_max(config))
+        .connect_lazy(url)
+        .map_err(|e| anyhow::anyhow!("Invalid database URL: {}", e))?;
+    pools.insert(url.to_string(), pool.clone());
+    Ok(pool)
+}
+
+/// The shared pool for `url`, created on first use.
+pub(super) fn any_pool(url: &str, config: &serde_json::Value) -> Result<AnyPool> {
+    // Install any drivers that are compiled in
+    sqlx::any::install_default_drivers();
+    shared_pool(&ANY_POOLS, url, config)
+}
+
+/// The shared Postgres pool for `url`, created on first use.
+#[cfg(feature = "postgres")]
+pub(super) fn pg_pool(url: &str, config: &serde_json::Value) -> Result<sqlx::PgPool> {
+    shared_pool(&PG_POOLS, url, config)
+}
+
+/// Number of open connections in the pool for `connection`, or `None` when
+/// no `db_query` / `db_exec` / `db_transaction` step has used it yet.
+pub fn pool_size(connection: &str) -> Option<u32> {
+    ANY_POOLS
+        .lock()
+        .unwrap()
+        .get(connection)
+        .map(|pool| pool.size())
+}
@@ -0,0 +1,165 @@
+use std::time::Instant;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use base64::Engine;
+use futures_util::TryStreamExt;
+use sqlx::postgres::{PgArguments, PgPool, PgRow};
+use sqlx::{Arguments, Column, Row, TypeInfo};
+
+use crate::engine::profile;
+use crate::engine::types::{Context, NodeOutput};
+use crate::lua::interpolate::interpolate_ctx;
+use crate::nodes::Node;
+use crate::util::limits;
+
+use super::pool;
+use super::sql::{optional_u64_config, resolve_params};
+
+/// Bind JSON parameters to `$1`, `$2`, ... with their native Postgres types.
+/// Arrays and objects are bound as `jsonb`.
+fn bind_pg_params(params: &[serde_json::Value]) -> Result<PgArguments> {
+    let mut args = PgArguments::default();
+    for (i, param) in params.iter().enumerate() {
+        let bound = match param {
+            serde_json::Value::String(s) => args.add(s.as_str()),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(int_val) => args.add(int_val),
+                None => args.add(n.as_f64()),
+            },
+            serde_json::Value::Bool(b) => args.add(*b),
+            serde_json::Value::Null => args.add(None::<String>),
+            other => args.add(sqlx::types::Json(other.clone())),
+        };
+        bound.map_err(|e| anyhow::anyhow!("Failed to bind param {}: {}", i + 1, e))?;
+    }
+    Ok(args)
+}
+
+/// Convert a Postgres row to a JSON object by its column types. Timestamps
+/// and dates become RFC 3339 / ISO strings, `bytea` becomes base64, and
+/// anything not decodable as text becomes `null`.
+fn pg_row_to_json(row: &PgRow) -> serde_json::Value {
+    let mut map = serde_json::Map::new();
+
+    for col in row.columns() {
+        let i = col.ordinal();
+        let value = match col.type_info().name() {
+            "BOOL" => row.try_get::<Option<bool>, _>(i).map(|v| v.into()),
+            "INT2" => row.try_get::<Option<i16>, _>(i).map(|v| v.into()),
+            "INT4" => row.try_get::<Option<i32>, _>(i).map(|v| v.into()),
+            "INT8" => row.try_get::<Option<i64>, _>(i).map(|v| v.into()),
+            "FLOAT4" => row.try_get::<Option<f32>, _>(i).map(|v| v.into()),
+            "FLOAT8" => row.try_get::<Option<f64>, _>(i).map(|v| v.into()),
+            "JSON" | "JSONB" => row
+                .try_get::<Option<serde_json::Value>, _>(i)
+                .map(|v| v.unwrap_or(serde_json::Value::Null)),
+            "UUID" => row
+                .try_get::<Option<uuid::Uuid>, _>(i)
+                .map(|v| v.map(|u| u.to_string()).into()),
+            "TIMESTAMPTZ" => row
+                .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>(i)
+                .map(|v| v.map(|t| t.to_rfc3339()).into()),
+            "TIMESTAMP" => row
+                .try_get::<Option<chrono::NaiveDateTime>, _>(i)
+                .map(|v| v.map(|t| t.to_string()).into()),
+            "DATE" => row
+                .try_get::<Option<chrono::NaiveDate>, _>(i)
+                .map(|v| v.map(|d| d.to_string()).into()),
+            "BYTEA" => row.try_get::<Option<Vec<u8>>, _>(i).map(|v| {
+                v.map(|bytes| base64::engine::general_purpose::STANDARD.encode(bytes))
+                    .into()
+            }),
+            _ => row.try_get::<Option<String>, _>(i).map(|v| v.into()),
+        };
+        map.insert(
+            col.name().to_string(),
+            value.unwrap_or(serde_json::Value::Null),
+        );
+    }
+
+    serde_json::Value::Object(map)
+}
+
+/// The shared pool for `connection`, falling back to the `DATABASE_URL` env var.
+fn connect_pg(config: &serde_json::Value, ctx: &Context) -> Result<PgPool> {
+    let url = match config.get("connection").and_then(|v| v.as_str()) {
+        Some(url) => interpolate_ctx(url, ctx),
+        None => std::env::var("DATABASE_URL").map_err(|_| {
+            anyhow::anyhow!("pg_query requires 'connection' or the DATABASE_URL env var")
+        })?,
+    };
+    pool::pg_pool(&url, config)
+}
+
+pub struct PostgresQueryNode;
+
+#[async_trait]
+impl Node for PostgresQueryNode {
+    fn node_type(&self) -> &str {
+        "pg_query"
+    }
+
+    fn description(&self) -> &str {
+        "Execute a parameterized PostgreSQL statement and return rows as JSON"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let query = config
+            .get("query")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("pg_query requires 'query' parameter"))?;
+
+        let query = interpolate_ctx(query, ctx);
+        let params = resolve_params(config, ctx);
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .unwrap_or("rows");
+        let max_rows = optional_u64_config(config, "max_rows")
+            .filter(|limit| *limit > 0)
+            .or_else(limits::max_db_rows);
+
+        let connect_started = Instant::now();
+        let mut conn = connect_pg(config, ctx)?
+            .acquire()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to Postgres: {}", e))?;
+        profile::record("db.connect_ms", connect_started.elapsed());
+        let args = bind_pg_params(&params)?;
+        let query_started = Instant::now();
+
+        // `fetch` works for statements without a result set too (plain
+        // INSERT/UPDATE); they simply yield no rows.
+        let mut stream =
+            sqlx::query_with(sqlx::AssertSqlSafe(query.as_str()), args).fetch(&mut *conn);
+        let mut json_rows = Vec::new();
+        while let Some(row) = stream
+            .try_next()
+            .await
+            .map_err(|e| anyhow::anyhow!("pg_query failed: {}", e))?
+        {
+            if let Some(max_rows) = max_rows
+                && json_rows.len() as u64 >= max_rows
+            {
+                anyhow::bail!(
+                    "pg_query exceeded max_rows limit of {}. Add pagination or raise max_rows / IRONFLOW_DB_MAX_ROWS.",
+                    max_rows
+                );
+            }
+            json_rows.push(pg_row_to_json(&row));
+        }
+
+        profile::record("db.query_ms", query_started.elapsed());
+        let count = json_rows.len();
+
+        let mut output = NodeOutput::new();
+        output.insert(output_key.to_string(), serde_json::Value::Array(json_rows));
+        output.insert(format!("{}_count", output_key), serde_json::json!(count));
+        output.insert(
+            format!("{}_success", output_key),
+            serde_json::Value::Bool(true),
+        );
+        Ok(output)
+    }
+}
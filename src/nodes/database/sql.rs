@@ -1,17 +1,23 @@
+use std::time::Instant;
+
 use anyhow::Result;
 use async_trait::async_trait;
 use futures_util::TryStreamExt;
 use sqlx::any::AnyRow;
-use sqlx::{AnyPool, Arguments, Column, Row, TypeInfo};
+use sqlx::pool::PoolConnection;
+use sqlx::{Any, AnyConnection, AnyPool, Arguments, Column, Row, TypeInfo};
 
+use crate::engine::profile;
 use crate::engine::types::{Context, NodeOutput};
 use crate::lua::interpolate::interpolate_ctx;
 use crate::nodes::Node;
 use crate::util::limits;
 
+use super::{pool, transaction};
+
 /// Resolve query parameters from config with context interpolation,
 /// preserving JSON types (string, number, bool, null) for proper SQL binding.
-fn resolve_params(config: &serde_json::Value, ctx: &Context) -> Vec<serde_json::Value> {
+pub(super) fn resolve_params(config: &serde_json::Value, ctx: &Context) -> Vec<serde_json::Value> {
     config
         .get("params")
         .and_then(|v| v.as_array())
@@ -29,7 +35,7 @@ fn resolve_params(config: &serde_json::Value, ctx: &Context) -> Vec<serde_json::
         .unwrap_or_default()
 }
 
-fn optional_u64_config(config: &serde_json::Value, key: &str) -> Option<u64> {
+pub(super) fn optional_u64_config(config: &serde_json::Value, key: &str) -> Option<u64> {
     config.get(key).and_then(|value| match value {
         serde_json::Value::Number(n) => n.as_u64(),
         serde_json::Value::String(s) => s.parse::<u64>().ok(),
@@ -123,23 +129,43 @@ pub(super) fn row_to_json(row: &AnyRow) -> Result<serde_json::Value> {
     Ok(serde_json::Value::Object(map))
 }
 
-/// Connect to a database using the `connection` config parameter.
-pub(super) async fn connect(config: &serde_json::Value, ctx: &Context) -> Result<AnyPool> {
+/// The shared pool for the `connection` config parameter.
+pub(super) fn connect(config: &serde_json::Value, ctx: &Context) -> Result<AnyPool> {
     let url = config
         .get("connection")
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow::anyhow!("db node requires 'connection' (database URL string)"))?;
 
     let url = interpolate_ctx(url, ctx);
+    pool::any_pool(&url, config)
+}
 
-    // Install any drivers that are compiled in
-    sqlx::any::install_default_drivers();
+/// The connection a `db_query` / `db_exec` statement runs on.
+enum StepConnection {
+    /// The open transaction named by `transaction_key`.
+    Transaction(transaction::TransactionLock),
+    Pooled(PoolConnection<Any>),
+}
 
-    let pool = AnyPool::connect(&url)
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to connect to database '{}': {}", url, e))?;
+impl StepConnection {
+    fn as_connection(&mut self) -> &mut AnyConnection {
+        match self {
+            Self::Transaction(tx) => tx.connection(),
+            Self::Pooled(conn) => conn,
+        }
+    }
+}
 
-    Ok(pool)
+async fn acquire(config: &serde_json::Value, ctx: &Context) -> Result<StepConnection> {
+    if let Some(tx) = transaction::lock(config, ctx).await? {
+        return Ok(StepConnection::Transaction(tx));
+    }
+    let pool = connect(config, ctx)?;
+    let conn = pool
+        .acquire()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to connect to database: {}", e))?;
+    Ok(StepConnection::Pooled(conn))
 }
 
 pub struct DbQueryNode;
@@ -173,10 +199,14 @@ impl Node for DbQueryNode {
             .filter(|limit| *limit > 0)
             .or_else(limits::max_db_result_bytes);
 
-        let pool = connect(config, ctx).await?;
+        let connect_started = Instant::now();
+        let mut conn = acquire(config, ctx).await?;
+        profile::record("db.connect_ms", connect_started.elapsed());
         let args = bind_params(&params)?;
+        let query_started = Instant::now();
 
-        let mut stream = sqlx::query_with(sqlx::AssertSqlSafe(query.as_str()), args).fetch(&pool);
+        let mut stream =
+            sqlx::query_with(sqlx::AssertSqlSafe(query.as_str()), args).fetch(conn.as_connection());
         let mut json_rows = Vec::new();
         let mut serialized_bytes = 2u64; // '[' + ']'
 
@@ -211,6 +241,7 @@ impl Node for DbQueryNode {
             json_rows.push(json_row);
         }
 
+        profile::record("db.query_ms", query_started.elapsed());
         let count = json_rows.len();
 
         let mut output = NodeOutput::new();
@@ -222,6 +253,10 @@ impl Node for DbQueryNode {
         );
         Ok(output)
     }
+
+    fn consumed_keys(&self, config: &serde_json::Value) -> Vec<String> {
+        transaction::transaction_key(config)
+    }
 }
 
 pub struct DbExecNode;
@@ -245,14 +280,18 @@ impl Node for DbExecNode {
         let query = interpolate_ctx(query, ctx);
         let params = resolve_params(config, ctx);
 
-        let pool = connect(config, ctx).await?;
+        let connect_started = Instant::now();
+        let mut conn = acquire(config, ctx).await?;
+        profile::record("db.connect_ms", connect_started.elapsed());
         let args = bind_params(&params)?;
+        let query_started = Instant::now();
 
         let result = sqlx::query_with(sqlx::AssertSqlSafe(query.as_str()), args)
-            .execute(&pool)
+            .execute(conn.as_connection())
             .await
             .map_err(|e| anyhow::anyhow!("db_exec failed: {}", e))?;
 
+        profile::record("db.query_ms", query_started.elapsed());
         let rows_affected = result.rows_affected();
 
         let mut output = NodeOutput::new();
@@ -263,4 +302,8 @@ impl Node for DbExecNode {
         output.insert("db_exec_success".to_string(), serde_json::Value::Bool(true));
         Ok(output)
     }
+
+    fn consumed_keys(&self, config: &serde_json::Value) -> Vec<String> {
+        transaction::transaction_key(config)
+    }
 }
@@ -0,0 +1,230 @@
+//! Database transactions that span several steps.
+//!
+//! `db_transaction` opens a transaction and writes an opaque handle to the
+//! context; `db_exec` and `db_query` steps with a `transaction_key` run on
+//! that transaction's connection until `db_commit` or `db_rollback` ends it.
+//! The transactions themselves live in a process-wide table keyed by handle,
+//! since the context only holds JSON. Any transaction a run leaves open is
+//! rolled back when the run ends (see [`RunTransactions`]).
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::{Any, AnyConnection, Transaction};
+use tokio::sync::OwnedMutexGuard;
+
+use crate::engine::run_meta;
+use crate::engine::types::{Context, NodeOutput};
+use crate::nodes::Node;
+
+use super::sql::connect;
+
+/// `None` once the transaction has been committed or rolled back.
+type SharedTransaction = Arc<tokio::sync::Mutex<Option<Transaction<'static, Any>>>>;
+
+struct OpenTransaction {
+    /// Run that opened the transaction, when opened inside the engine.
+    run_id: Option<String>,
+    tx: SharedTransaction,
+}
+
+static OPEN: LazyLock<Mutex<HashMap<String, OpenTransaction>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Read the handle stored under the config's `transaction_key`.
+fn handle_from_ctx(config: &serde_json::Value, ctx: &Context) -> Result<Option<String>> {
+    let Some(key) = config.get("transaction_key").and_then(|v| v.as_str()) else {
+        return Ok(None);
+    };
+    let handle = ctx.get(key).and_then(|v| v.as_str()).ok_or_else(|| {
+        anyhow::anyhow!(
+            "transaction_key '{}' does not name a transaction handle in context",
+            key
+        )
+    })?;
+    Ok(Some(handle.to_string()))
+}
+
+/// A locked transaction that a `db_exec` / `db_query` step runs on.
+pub(super) struct TransactionLock(OwnedMutexGuard<Option<Transaction<'static, Any>>>);
+
+impl TransactionLock {
+    pub(super) fn connection(&mut self) -> &mut AnyConnection {
+        // `lock` only hands out guards for transactions that are still open.
+        self.0.as_mut().expect("transaction is open")
+    }
+}
+
+/// Lock the transaction named by the config's `transaction_key`, or return
+/// `None` when the step does not use one.
+pub(super) async fn lock(
+    config: &serde_json::Value,
+    ctx: &Context,
+) -> Result<Option<TransactionLock>> {
+    let Some(handle) = handle_from_ctx(config, ctx)? else {
+        return Ok(None);
+    };
+    let tx = OPEN
+        .lock()
+        .unwrap()
+        .get(&handle)
+        .map(|open| open.tx.clone())
+        .ok_or_else(|| anyhow::anyhow!("Transaction '{}' is not open", handle))?;
+    let guard = tx.lock_owned().await;
+    if guard.is_none() {
+        anyhow::bail!("Transaction '{}' is not open", handle);
+    }
+    Ok(Some(TransactionLock(guard)))
+}
+
+/// Remove the transaction named by `transaction_key` and take it out of
+/// its slot, waiting for any step still using it.
+async fn take(
+    node_type: &str,
+    config: &serde_json::Value,
+    ctx: &Context,
+) -> Result<(String, Transaction<'static, Any>)> {
+    let handle = handle_from_ctx(config, ctx)?
+        .ok_or_else(|| anyhow::anyhow!("{} requires 'transaction_key' parameter", node_type))?;
+    let open = OPEN
+        .lock()
+        .unwrap()
+        .remove(&handle)
+        .ok_or_else(|| anyhow::anyhow!("Transaction '{}' is not open", handle))?;
+    let tx = open
+        .tx
+        .lock()
+        .await
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("Transaction '{}' is not open", handle))?;
+    Ok((handle, tx))
+}
+
+/// Drop every transaction opened by `run_id`, rolling back their changes.
+fn release_run(run_id: &str) {
+    OPEN.lock()
+        .unwrap()
+        .retain(|_, open| open.run_id.as_deref() != Some(run_id));
+}
+
+/// Rolls back the transactions a run left open when dropped, so a failed,
+/// cancelled or timed-out run never leaks a connection or a held lock.
+pub struct RunTransactions(String);
+
+impl RunTransactions {
+    pub fn new(run_id: &str) -> Self {
+        Self(run_id.to_string())
+    }
+}
+
+impl Drop for RunTransactions {
+    fn drop(&mut self) {
+        release_run(&self.0);
+    }
+}
+
+pub struct DbTransactionNode;
+
+#[async_trait]
+impl Node for DbTransactionNode {
+    fn node_type(&self) -> &str {
+        "db_transaction"
+    }
+
+    fn description(&self) -> &str {
+        "Begin a database transaction shared by later db_exec/db_query steps"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .unwrap_or("transaction");
+
+        let pool = connect(config, ctx)?;
+        let tx = pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to database: {}", e))?;
+
+        let handle = format!("tx-{}", uuid::Uuid::new_v4());
+        OPEN.lock().unwrap().insert(
+            handle.clone(),
+            OpenTransaction {
+                run_id: run_meta::current().map(|scope| scope.run.run_id.clone()),
+                tx: Arc::new(tokio::sync::Mutex::new(Some(tx))),
+            },
+        );
+
+        let mut output = NodeOutput::new();
+        output.insert(output_key.to_string(), serde_json::Value::String(handle));
+        Ok(output)
+    }
+}
+
+pub struct DbCommitNode;
+
+#[async_trait]
+impl Node for DbCommitNode {
+    fn node_type(&self) -> &str {
+        "db_commit"
+    }
+
+    fn description(&self) -> &str {
+        "Commit a transaction opened by db_transaction"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let (handle, tx) = take("db_commit", config, ctx).await?;
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("db_commit failed for '{}': {}", handle, e))?;
+
+        let mut output = NodeOutput::new();
+        output.insert("db_commit_success".to_string(), serde_json::json!(true));
+        Ok(output)
+    }
+
+    fn consumed_keys(&self, config: &serde_json::Value) -> Vec<String> {
+        transaction_key(config)
+    }
+}
+
+pub struct DbRollbackNode;
+
+#[async_trait]
+impl Node for DbRollbackNode {
+    fn node_type(&self) -> &str {
+        "db_rollback"
+    }
+
+    fn description(&self) -> &str {
+        "Roll back a transaction opened by db_transaction"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let (handle, tx) = take("db_rollback", config, ctx).await?;
+        tx.rollback()
+            .await
+            .map_err(|e| anyhow::anyhow!("db_rollback failed for '{}': {}", handle, e))?;
+
+        let mut output = NodeOutput::new();
+        output.insert("db_rollback_success".to_string(), serde_json::json!(true));
+        Ok(output)
+    }
+
+    fn consumed_keys(&self, config: &serde_json::Value) -> Vec<String> {
+        transaction_key(config)
+    }
+}
+
+/// `transaction_key` as a consumed context key, for `ironflow validate`.
+pub(super) fn transaction_key(config: &serde_json::Value) -> Vec<String> {
+    config
+        .get("transaction_key")
+        .and_then(|v| v.as_str())
+        .map(|key| vec![key.to_string()])
+        .unwrap_or_default()
+}
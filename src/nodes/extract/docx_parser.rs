@@ -49,7 +49,7 @@ pub(super) enum DocxBlock {
 /// "accent1"..."accent6", "hyperlink", "followedHyperlink". Returns an empty map if the theme file
 /// is absent or malformed.
 pub(super) fn parse_theme_colors(
-    archive: &mut zip::ZipArchive<std::fs::File>,
+    archive: &mut zip::ZipArchive<std::io::Cursor<Vec<u8>>>,
 ) -> std::collections::HashMap<String, String> {
     let mut map = std::collections::HashMap::new();
     let xml = match archive.by_name("word/theme/theme1.xml") {
@@ -141,7 +141,7 @@ pub(super) fn parse_theme_colors(
 
 /// Parse numbering.xml to identify which numId values are numbered vs bulleted.
 pub(super) fn parse_numbering_defs(
-    archive: &mut zip::ZipArchive<std::fs::File>,
+    archive: &mut zip::ZipArchive<std::io::Cursor<Vec<u8>>>,
 ) -> std::collections::HashMap<String, bool> {
     // Maps numId -> is_numbered (true = ordered, false = bullet)
     let mut result = std::collections::HashMap::new();
@@ -5,6 +5,8 @@ use async_trait::async_trait;
 
 use crate::engine::types::{Context, NodeOutput};
 use crate::nodes::Node;
+use crate::util::compression::read_file_text;
+use crate::util::text::clean_text;
 
 use super::common::{get_path, validate_format};
 
@@ -29,7 +31,8 @@ impl Node for ExtractHtmlNode {
             .unwrap_or("content");
         let metadata_key = config.get("metadata_key").and_then(|v| v.as_str());
 
-        let html = std::fs::read_to_string(&path)
+        let html = read_file_text(&path, config)
+            .map(|text| clean_text(&text))
             .map_err(|e| anyhow::anyhow!("Failed to read '{}': {}", path, e))?;
 
         let content = match format {
@@ -1,6 +1,8 @@
 mod common;
 mod docx_parser;
 mod html;
+#[cfg(feature = "ocr")]
+mod ocr;
 mod pdf;
 mod pptx;
 mod pptx_format;
@@ -25,4 +27,6 @@ pub fn register_all(registry: &mut NodeRegistry) {
     registry.register(Arc::new(ExtractHtmlNode));
     registry.register(Arc::new(ExtractVttNode));
     registry.register(Arc::new(ExtractSrtNode));
+    #[cfg(feature = "ocr")]
+    registry.register(Arc::new(ocr::ExtractOcrNode));
 }
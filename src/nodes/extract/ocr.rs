@@ -0,0 +1,190 @@
+//! `extract_ocr`: recognize text in images and scanned PDFs with Tesseract.
+//!
+//! Built only with `--features ocr`, which links the system Tesseract and
+//! Leptonica libraries. PDF pages are rendered with pdfium first, the same
+//! way `pdf_to_image` renders them, so PDF input also needs pdfium at
+//! runtime.
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::engine::types::{Context, NodeOutput};
+use crate::nodes::Node;
+use crate::nodes::image::common::{
+    is_pdf_file, load_image_bytes, load_pdfium, parse_pages_spec, read_pdf_bytes_capped,
+    validate_pdf_dpi, validate_pdf_render_page_count,
+};
+use crate::nodes::image::image_sources::{ImageInput, resolve_single_image_source};
+use crate::nodes::image::pdf_render::{PdfRenderRequest, render_pdf_page_image};
+
+pub struct ExtractOcrNode;
+
+/// An image to recognize, with the 1-based page it came from.
+struct OcrImage {
+    page: usize,
+    png: Vec<u8>,
+}
+
+struct OcrPage {
+    page: usize,
+    text: String,
+    confidence: i32,
+}
+
+/// PNG bytes of `image`; Leptonica reads PNG on every platform it supports.
+fn encode_png(image: &image::DynamicImage) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    image.write_to(&mut std::io::Cursor::new(&mut buf), image::ImageFormat::Png)?;
+    Ok(buf)
+}
+
+fn render_pdf_pages(path: &str, pages_spec: &str, dpi: f32) -> Result<Vec<OcrImage>> {
+    let bytes = read_pdf_bytes_capped(path, "extract_ocr")?;
+    let pdfium = pdfium_render::prelude::Pdfium::new(load_pdfium()?);
+    let document = pdfium
+        .load_pdf_from_byte_vec(bytes, None)
+        .map_err(|e| anyhow::anyhow!("Failed to open PDF '{}': {:?}", path, e))?;
+
+    let page_count = document.pages().len() as usize;
+    let page_indices = parse_pages_spec(pages_spec, page_count)?;
+    validate_pdf_render_page_count(page_indices.len(), "extract_ocr")?;
+
+    page_indices
+        .into_iter()
+        .map(|page_idx| {
+            let image = render_pdf_page_image(
+                &document,
+                &PdfRenderRequest {
+                    page_count,
+                    page_idx,
+                    format: image::ImageFormat::Png,
+                    width_hint: None,
+                    height_hint: None,
+                    max_side: None,
+                    dpi,
+                },
+            )?;
+            Ok(OcrImage {
+                page: page_idx + 1,
+                png: encode_png(&image)?,
+            })
+        })
+        .collect()
+}
+
+fn recognize(
+    images: Vec<OcrImage>,
+    data_path: Option<&str>,
+    lang: &str,
+    dpi: i32,
+) -> Result<Vec<OcrPage>> {
+    let mut tess = leptess::LepTess::new(data_path, lang).map_err(|e| {
+        anyhow::anyhow!(
+            "extract_ocr: failed to initialize Tesseract for lang '{}': {} (is the traineddata installed? set 'data_path' or TESSDATA_PREFIX)",
+            lang,
+            e
+        )
+    })?;
+
+    images
+        .into_iter()
+        .map(|image| {
+            tess.set_image_from_mem(&image.png).map_err(|e| {
+                anyhow::anyhow!("extract_ocr: failed to load page {}: {}", image.page, e)
+            })?;
+            tess.set_source_resolution(dpi);
+            let text = tess.get_utf8_text().map_err(|e| {
+                anyhow::anyhow!("extract_ocr: page {} is not valid UTF-8: {}", image.page, e)
+            })?;
+            Ok(OcrPage {
+                page: image.page,
+                text: text.trim_end().to_string(),
+                confidence: tess.mean_text_conf(),
+            })
+        })
+        .collect()
+}
+
+#[async_trait]
+impl Node for ExtractOcrNode {
+    fn node_type(&self) -> &str {
+        "extract_ocr"
+    }
+
+    fn description(&self) -> &str {
+        "Recognize text in an image or scanned PDF with Tesseract OCR"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let input = resolve_single_image_source(config, ctx, "extract_ocr")?;
+        let lang = config
+            .get("lang")
+            .and_then(|v| v.as_str())
+            .unwrap_or("eng")
+            .to_string();
+        let data_path = config
+            .get("data_path")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        let pages_spec = config
+            .get("pages")
+            .and_then(|v| v.as_str())
+            .unwrap_or("all")
+            .to_string();
+        let dpi = config.get("dpi").and_then(|v| v.as_f64()).unwrap_or(300.0) as f32;
+        validate_pdf_dpi(dpi, "extract_ocr")?;
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .unwrap_or("content");
+
+        // pdfium and Tesseract are both blocking, CPU-bound C libraries.
+        let pages = tokio::task::spawn_blocking(move || {
+            let images = match input {
+                ImageInput::Path(path) if is_pdf_file(&path) => {
+                    render_pdf_pages(&path, &pages_spec, dpi)?
+                }
+                input => vec![OcrImage {
+                    page: 1,
+                    png: encode_png(&load_image_bytes(input)?.image)?,
+                }],
+            };
+            recognize(images, data_path.as_deref(), &lang, dpi.round() as i32)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("extract_ocr: worker task failed: {}", e))??;
+
+        let text = pages
+            .iter()
+            .map(|page| page.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let confidence = if pages.is_empty() {
+            0.0
+        } else {
+            pages.iter().map(|page| page.confidence as f64).sum::<f64>() / pages.len() as f64
+        };
+        let page_results: Vec<serde_json::Value> = pages
+            .iter()
+            .map(|page| {
+                serde_json::json!({
+                    "page": page.page,
+                    "text": page.text,
+                    "confidence": page.confidence,
+                })
+            })
+            .collect();
+
+        let mut output = NodeOutput::new();
+        output.insert(output_key.to_string(), serde_json::Value::String(text));
+        output.insert(
+            format!("{}_pages", output_key),
+            serde_json::Value::Array(page_results),
+        );
+        output.insert(
+            format!("{}_confidence", output_key),
+            serde_json::json!(confidence),
+        );
+        Ok(output)
+    }
+}
@@ -5,6 +5,7 @@ use async_trait::async_trait;
 
 use crate::engine::types::{Context, NodeOutput};
 use crate::nodes::Node;
+use crate::util::compression::read_file_bytes;
 
 use super::common::{get_path, validate_format};
 
@@ -29,7 +30,7 @@ impl Node for ExtractPdfNode {
             .unwrap_or("content");
         let metadata_key = config.get("metadata_key").and_then(|v| v.as_str());
 
-        let bytes = std::fs::read(&path)
+        let bytes = read_file_bytes(&path, config)
             .map_err(|e| anyhow::anyhow!("Failed to read '{}': {}", path, e))?;
 
         // Extract text
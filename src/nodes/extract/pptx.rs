@@ -6,6 +6,7 @@ use async_trait::async_trait;
 
 use crate::engine::types::{Context, NodeOutput};
 use crate::nodes::Node;
+use crate::util::compression::read_file_bytes;
 
 use super::common::{get_path, validate_word_format};
 use super::pptx_format::{pptx_slides_to_json, pptx_slides_to_markdown, pptx_slides_to_text};
@@ -40,9 +41,9 @@ impl Node for ExtractPptxNode {
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
 
-        let file = std::fs::File::open(&path)
+        let bytes = read_file_bytes(&path, config)
             .map_err(|e| anyhow::anyhow!("Failed to open '{}': {}", path, e))?;
-        let mut archive = zip::ZipArchive::new(file)
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
             .map_err(|e| anyhow::anyhow!("Failed to read PPTX archive '{}': {}", path, e))?;
 
         let slides = extract_pptx_slides(&mut archive, include_image_bytes);
@@ -80,7 +81,7 @@ impl Node for ExtractPptxNode {
 }
 
 fn extract_pptx_metadata(
-    archive: &mut zip::ZipArchive<std::fs::File>,
+    archive: &mut zip::ZipArchive<std::io::Cursor<Vec<u8>>>,
     slide_count: usize,
 ) -> BTreeMap<String, serde_json::Value> {
     let mut meta = BTreeMap::new();
@@ -143,7 +144,7 @@ fn extract_pptx_metadata(
 }
 
 fn extract_pptx_slides(
-    archive: &mut zip::ZipArchive<std::fs::File>,
+    archive: &mut zip::ZipArchive<std::io::Cursor<Vec<u8>>>,
     include_image_bytes: bool,
 ) -> Vec<PptxSlide> {
     // Collect slide files by name, sorted by their numeric suffix.
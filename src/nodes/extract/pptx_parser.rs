@@ -102,7 +102,7 @@ pub(super) fn normalize_pptx_path(source_dir: &str, target: &str) -> String {
 }
 
 pub(super) fn read_pptx_media(
-    archive: &mut zip::ZipArchive<std::fs::File>,
+    archive: &mut zip::ZipArchive<std::io::Cursor<Vec<u8>>>,
     path: &str,
 ) -> Option<(Vec<u8>, String)> {
     let mut entry = archive.by_name(path).ok()?;
@@ -378,7 +378,7 @@ pub(super) fn parse_pptx_notes(xml: &str) -> String {
 }
 
 pub(super) fn extract_pptx_comments(
-    archive: &mut zip::ZipArchive<std::fs::File>,
+    archive: &mut zip::ZipArchive<std::io::Cursor<Vec<u8>>>,
 ) -> Vec<PptxComment> {
     // Author lookup (legacy: ppt/commentAuthors.xml).
     let mut authors: std::collections::HashMap<String, (Option<String>, Option<String>)> =
@@ -5,6 +5,8 @@ use async_trait::async_trait;
 
 use crate::engine::types::{Context, NodeOutput};
 use crate::nodes::Node;
+use crate::util::compression::read_file_text;
+use crate::util::text::clean_text;
 
 use super::common::{get_path, validate_format};
 
@@ -33,7 +35,8 @@ impl Node for ExtractVttNode {
             .unwrap_or("cues");
         let metadata_key = config.get("metadata_key").and_then(|v| v.as_str());
 
-        let input = std::fs::read_to_string(&path)
+        let input = read_file_text(&path, config)
+            .map(|text| clean_text(&text))
             .map_err(|e| anyhow::anyhow!("Failed to read '{}': {}", path, e))?;
 
         let cues = parse_subtitle_cues(&input, true);
@@ -54,6 +57,10 @@ impl Node for ExtractVttNode {
         }
         Ok(output)
     }
+
+    fn produced_keys(&self, config: &serde_json::Value) -> Option<Vec<String>> {
+        Some(subtitle_produced_keys(config))
+    }
 }
 
 pub struct ExtractSrtNode;
@@ -81,7 +88,8 @@ impl Node for ExtractSrtNode {
             .unwrap_or("cues");
         let metadata_key = config.get("metadata_key").and_then(|v| v.as_str());
 
-        let input = std::fs::read_to_string(&path)
+        let input = read_file_text(&path, config)
+            .map(|text| clean_text(&text))
             .map_err(|e| anyhow::anyhow!("Failed to read '{}': {}", path, e))?;
 
         let cues = parse_subtitle_cues(&input, false);
@@ -102,6 +110,30 @@ impl Node for ExtractSrtNode {
         }
         Ok(output)
     }
+
+    fn produced_keys(&self, config: &serde_json::Value) -> Option<Vec<String>> {
+        Some(subtitle_produced_keys(config))
+    }
+}
+
+/// `transcript` is always written alongside the formatted output.
+fn subtitle_produced_keys(config: &serde_json::Value) -> Vec<String> {
+    let key = |name: &str, default: &str| {
+        config
+            .get(name)
+            .and_then(|v| v.as_str())
+            .unwrap_or(default)
+            .to_string()
+    };
+    let mut keys = vec![
+        key("output_key", "transcript"),
+        key("cues_key", "cues"),
+        "transcript".to_string(),
+    ];
+    if let Some(meta_key) = config.get("metadata_key").and_then(|v| v.as_str()) {
+        keys.push(meta_key.to_string());
+    }
+    keys
 }
 
 #[derive(Clone)]
@@ -6,6 +6,7 @@ use async_trait::async_trait;
 
 use crate::engine::types::{Context, NodeOutput};
 use crate::nodes::Node;
+use crate::util::compression::read_file_bytes;
 
 use super::common::{get_path, validate_word_format};
 use super::docx_parser::{parse_docx_blocks, parse_numbering_defs, parse_theme_colors};
@@ -33,9 +34,9 @@ impl Node for ExtractWordNode {
         let metadata_key = config.get("metadata_key").and_then(|v| v.as_str());
         let comments_key = config.get("comments_key").and_then(|v| v.as_str());
 
-        let file = std::fs::File::open(&path)
+        let bytes = read_file_bytes(&path, config)
             .map_err(|e| anyhow::anyhow!("Failed to open '{}': {}", path, e))?;
-        let mut archive = zip::ZipArchive::new(file)
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
             .map_err(|e| anyhow::anyhow!("Failed to read DOCX archive '{}': {}", path, e))?;
 
         // Extract content from word/document.xml
@@ -60,7 +61,7 @@ impl Node for ExtractWordNode {
 }
 
 pub(super) fn extract_docx_metadata(
-    archive: &mut zip::ZipArchive<std::fs::File>,
+    archive: &mut zip::ZipArchive<std::io::Cursor<Vec<u8>>>,
 ) -> BTreeMap<String, String> {
     let mut meta = BTreeMap::new();
 
@@ -143,7 +144,7 @@ pub(super) fn extract_docx_metadata(
 }
 
 fn extract_docx_content(
-    archive: &mut zip::ZipArchive<std::fs::File>,
+    archive: &mut zip::ZipArchive<std::io::Cursor<Vec<u8>>>,
     format: &str,
 ) -> Result<serde_json::Value> {
     let xml = {
@@ -187,7 +188,9 @@ struct DocxComment {
 
 /// Parse word/comments.xml + walk word/document.xml for anchor ranges. Returns
 /// the merged list of comments. Returns an empty vec if no comments part exists.
-fn extract_docx_comments(archive: &mut zip::ZipArchive<std::fs::File>) -> Vec<DocxComment> {
+fn extract_docx_comments(
+    archive: &mut zip::ZipArchive<std::io::Cursor<Vec<u8>>>,
+) -> Vec<DocxComment> {
     let comments_xml = match archive.by_name("word/comments.xml") {
         Ok(mut entry) => {
             let mut buf = String::new();
@@ -5,6 +5,8 @@ use base64::Engine;
 use crate::engine::types::{Context, NodeOutput};
 use crate::lua::interpolate::interpolate_ctx;
 use crate::nodes::Node;
+use crate::util::compression::{gunzip, wants_gunzip};
+use crate::util::text::{normalize_newlines, strip_bom};
 
 pub struct ReadFileNode;
 
@@ -33,6 +35,10 @@ impl Node for ReadFileNode {
             .get("encoding")
             .and_then(|v| v.as_str())
             .unwrap_or("text");
+        let normalize = config
+            .get("normalize_newlines")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
 
         // Pre-flight size guard: fail before allocating a huge buffer.
         let max_bytes = crate::util::limits::max_file_bytes();
@@ -47,12 +53,26 @@ impl Node for ReadFileNode {
             );
         }
 
+        let mut bytes = tokio::fs::read(&path).await?;
+        if wants_gunzip(&path, config) {
+            bytes = gunzip(&bytes).map_err(|e| {
+                anyhow::anyhow!("read_file: failed to decompress '{}': {}", path, e)
+            })?;
+        }
+
         let content = match encoding {
-            "base64" => {
-                let bytes = tokio::fs::read(&path).await?;
-                base64::engine::general_purpose::STANDARD.encode(&bytes)
+            "base64" => base64::engine::general_purpose::STANDARD.encode(&bytes),
+            "text" => {
+                let text = String::from_utf8(bytes).map_err(|e| {
+                    anyhow::anyhow!("read_file: '{}' is not valid UTF-8: {}", path, e)
+                })?;
+                let text = strip_bom(&text);
+                if normalize {
+                    normalize_newlines(text).into_owned()
+                } else {
+                    text.to_string()
+                }
             }
-            "text" => tokio::fs::read_to_string(&path).await?,
             other => anyhow::bail!(
                 "read_file: unsupported encoding '{}'. Must be 'text' or 'base64'.",
                 other
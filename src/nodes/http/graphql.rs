@@ -0,0 +1,108 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::engine::types::{Context, NodeOutput};
+use crate::nodes::Node;
+
+use super::request::do_http_request;
+
+/// Config keys that shape the GraphQL payload rather than the HTTP request.
+const GRAPHQL_KEYS: &[&str] = &["query", "variables", "operation_name", "ignore_errors"];
+
+pub struct GraphQlNode;
+
+#[async_trait]
+impl Node for GraphQlNode {
+    fn node_type(&self) -> &str {
+        "graphql"
+    }
+
+    fn description(&self) -> &str {
+        "Send a GraphQL query or mutation over HTTP"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let query = config
+            .get("query")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("graphql requires 'query' parameter"))?;
+        let ignore_errors = config
+            .get("ignore_errors")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .unwrap_or("graphql");
+
+        let mut payload = serde_json::Map::new();
+        payload.insert("query".to_string(), serde_json::json!(query));
+        if let Some(variables) = config.get("variables") {
+            payload.insert("variables".to_string(), variables.clone());
+        }
+        if let Some(name) = config.get("operation_name").and_then(|v| v.as_str()) {
+            payload.insert("operationName".to_string(), serde_json::json!(name));
+        }
+
+        // Everything else (url, headers, auth, timeout, retries, ...) is an
+        // ordinary `http_request` option; the body is always a JSON POST.
+        let mut http_config = config
+            .as_object()
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("graphql config must be an object"))?;
+        for key in GRAPHQL_KEYS {
+            http_config.remove(*key);
+        }
+        http_config.insert("body".to_string(), serde_json::Value::Object(payload));
+        http_config.insert("body_type".to_string(), serde_json::json!("json"));
+        http_config.insert("response_encoding".to_string(), serde_json::json!("text"));
+        http_config.insert("output_key".to_string(), serde_json::json!(output_key));
+
+        let mut output =
+            do_http_request("POST", &serde_json::Value::Object(http_config), ctx).await?;
+
+        // Split the GraphQL envelope: `data` and `errors` get their own keys.
+        let body = output
+            .remove(&format!("{}_data", output_key))
+            .unwrap_or(serde_json::Value::Null);
+        let data = body.get("data").cloned().unwrap_or(serde_json::Value::Null);
+        let errors = body
+            .get("errors")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        if !errors.is_empty() && !ignore_errors {
+            let messages: Vec<String> = errors
+                .iter()
+                .map(
+                    |error| match error.get("message").and_then(|v| v.as_str()) {
+                        Some(message) => message.to_string(),
+                        None => error.to_string(),
+                    },
+                )
+                .collect();
+            anyhow::bail!(
+                "graphql: response contained {} error(s): {}",
+                errors.len(),
+                messages.join("; ")
+            );
+        }
+
+        let http_success = output
+            .get(&format!("{}_success", output_key))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        output.insert(
+            format!("{}_success", output_key),
+            serde_json::Value::Bool(http_success && errors.is_empty()),
+        );
+        output.insert(format!("{}_data", output_key), data);
+        output.insert(
+            format!("{}_errors", output_key),
+            serde_json::Value::Array(errors),
+        );
+
+        Ok(output)
+    }
+}
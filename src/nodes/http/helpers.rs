@@ -49,6 +49,25 @@ pub(super) fn body_value_to_text(value: &serde_json::Value) -> String {
     }
 }
 
+/// Decode a response body by the `charset` of its `Content-Type`, falling
+/// back to lossy UTF-8 when there is none or it is unknown.
+pub(crate) fn decode_response_text(bytes: &[u8], content_type: Option<&str>) -> String {
+    let encoding = content_type
+        .and_then(|ct| {
+            ct.split(';').skip(1).find_map(|param| {
+                let (name, value) = param.split_once('=')?;
+                name.trim()
+                    .eq_ignore_ascii_case("charset")
+                    .then(|| value.trim().trim_matches('"'))
+            })
+        })
+        .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes()));
+    match encoding {
+        Some(encoding) => encoding.decode(bytes).0.into_owned(),
+        None => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
 pub(super) fn build_form_body(body: &serde_json::Value) -> Result<String> {
     let object = body
         .as_object()
@@ -64,3 +83,68 @@ pub(super) fn build_form_body(body: &serde_json::Value) -> Result<String> {
     }
     Ok(pairs.join("&"))
 }
+
+/// Build a `multipart/form-data` body from a `body` object. Each field is
+/// sent as text, or as a file part when it is `{ file = "path", filename =
+/// "...", content_type = "..." }`. Returns the boundary and the encoded body;
+/// building it up front keeps the request cloneable for status retries.
+pub(super) fn build_multipart_body(body: &serde_json::Value) -> Result<(String, Vec<u8>)> {
+    let object = body
+        .as_object()
+        .ok_or_else(|| anyhow::anyhow!("body_type='multipart' requires 'body' to be an object"))?;
+
+    let boundary = format!("ironflow-{}", uuid::Uuid::new_v4().simple());
+    let mut out = Vec::new();
+    for (name, value) in object {
+        out.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+        let name = header_quote(name);
+        match value.get("file").and_then(|v| v.as_str()) {
+            Some(path) => {
+                let bytes = std::fs::read(path).map_err(|e| {
+                    anyhow::anyhow!("multipart field '{}': cannot read '{}': {}", name, path, e)
+                })?;
+                let filename = value
+                    .get("filename")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| {
+                        std::path::Path::new(path)
+                            .file_name()
+                            .map(|n| n.to_string_lossy().into_owned())
+                            .unwrap_or_else(|| "file".to_string())
+                    });
+                let content_type = value
+                    .get("content_type")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("application/octet-stream");
+                out.extend_from_slice(
+                    format!(
+                        "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+                        name,
+                        header_quote(&filename),
+                        content_type
+                    )
+                    .as_bytes(),
+                );
+                out.extend_from_slice(&bytes);
+            }
+            None => {
+                out.extend_from_slice(
+                    format!("Content-Disposition: form-data; name=\"{}\"\r\n\r\n", name).as_bytes(),
+                );
+                out.extend_from_slice(body_value_to_text(value).as_bytes());
+            }
+        }
+        out.extend_from_slice(b"\r\n");
+    }
+    out.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+    Ok((boundary, out))
+}
+
+/// Escape a value for a quoted `Content-Disposition` parameter.
+fn header_quote(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(['\r', '\n'], " ")
+}
@@ -1,7 +1,19 @@
+mod graphql;
 mod helpers;
+mod poll;
+mod rate_limit;
 mod request;
+mod websocket;
 
-pub use request::{HttpDeleteNode, HttpGetNode, HttpPostNode, HttpPutNode, HttpRequestNode};
+pub use graphql::GraphQlNode;
+pub(crate) use helpers::decode_response_text;
+pub use poll::HttpPollNode;
+pub(crate) use request::do_http_request;
+pub use request::{
+    HttpDeleteNode, HttpGetNode, HttpHeadNode, HttpPatchNode, HttpPostNode, HttpPutNode,
+    HttpRequestNode,
+};
+pub use websocket::WebSocketNode;
 
 use crate::nodes::NodeRegistry;
 use std::sync::Arc;
@@ -12,4 +24,9 @@ pub fn register_all(registry: &mut NodeRegistry) {
     registry.register(Arc::new(HttpPostNode));
     registry.register(Arc::new(HttpPutNode));
     registry.register(Arc::new(HttpDeleteNode));
+    registry.register(Arc::new(HttpPatchNode));
+    registry.register(Arc::new(HttpHeadNode));
+    registry.register(Arc::new(HttpPollNode));
+    registry.register(Arc::new(GraphQlNode));
+    registry.register(Arc::new(WebSocketNode));
 }
@@ -0,0 +1,170 @@
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::engine::types::{Context, NodeError, NodeErrorKind, NodeOutput};
+use crate::nodes::Node;
+use crate::nodes::composition::evaluate_condition;
+
+use super::request::do_http_request;
+
+/// Keys copied from the node config into the start request and every poll
+/// unless the `start` config overrides them.
+const SHARED_KEYS: &[&str] = &["headers", "auth", "timeout"];
+
+pub struct HttpPollNode;
+
+#[async_trait]
+impl Node for HttpPollNode {
+    fn node_type(&self) -> &str {
+        "http_poll"
+    }
+
+    fn description(&self) -> &str {
+        "Poll a status URL until a success or failure condition matches"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let status_url = config
+            .get("status_url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("http_poll requires 'status_url' parameter"))?;
+        let success_expr = config
+            .get("success_expr")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("http_poll requires 'success_expr' parameter"))?;
+        let failure_expr = config.get("failure_expr").and_then(|v| v.as_str());
+        let interval_s = config
+            .get("interval_s")
+            .and_then(|v| v.as_f64())
+            .filter(|v| *v >= 0.0)
+            .unwrap_or(5.0);
+        let timeout_s = config
+            .get("timeout_s")
+            .and_then(|v| v.as_f64())
+            .filter(|v| *v >= 0.0)
+            .unwrap_or(300.0);
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .unwrap_or("poll");
+
+        let started = Instant::now();
+        let deadline = Duration::from_secs_f64(timeout_s);
+        let mut output = NodeOutput::new();
+
+        // The optional start request (e.g. "create job") runs once; its
+        // outputs are visible to `status_url` as `${ctx.<output_key>_start_*}`.
+        let poll_ctx: Cow<'_, Context> = match config.get("start") {
+            Some(start) => {
+                let mut start_config = with_shared_keys(start, config)?;
+                start_config.insert(
+                    "output_key".to_string(),
+                    serde_json::Value::String(format!("{}_start", output_key)),
+                );
+                let method = start_config
+                    .get("method")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("POST")
+                    .to_string();
+                let start_output =
+                    do_http_request(&method, &serde_json::Value::Object(start_config), ctx).await?;
+                let mut poll_ctx = ctx.clone();
+                for (key, value) in start_output {
+                    poll_ctx.insert(key.clone(), value.clone());
+                    output.insert(key, value);
+                }
+                Cow::Owned(poll_ctx)
+            }
+            None => Cow::Borrowed(ctx),
+        };
+
+        let mut status_config = with_shared_keys(&serde_json::json!({}), config)?;
+        status_config.insert(
+            "url".to_string(),
+            serde_json::Value::String(status_url.to_string()),
+        );
+        status_config.insert(
+            "output_key".to_string(),
+            serde_json::Value::String(output_key.to_string()),
+        );
+        if let Some(fail_on_status) = config.get("fail_on_status") {
+            status_config.insert("fail_on_status".to_string(), fail_on_status.clone());
+        }
+        let status_config = serde_json::Value::Object(status_config);
+
+        let mut polls = 0_u64;
+        loop {
+            let response = do_http_request("GET", &status_config, &poll_ctx).await?;
+            polls += 1;
+
+            // Conditions see the latest response as `status`, `body` and
+            // `headers`, e.g. `body.state == 'done'`.
+            let mut condition_ctx = Context::new();
+            for (field, name) in [
+                ("status", "status"),
+                ("data", "body"),
+                ("headers", "headers"),
+            ] {
+                if let Some(value) = response.get(&format!("{}_{}", output_key, field)) {
+                    condition_ctx.insert(name.to_string(), value.clone());
+                }
+            }
+
+            output.extend(response);
+            output.insert(format!("{}_polls", output_key), serde_json::json!(polls));
+
+            if evaluate_condition(success_expr, &condition_ctx) {
+                return Ok(output);
+            }
+            if let Some(expr) = failure_expr
+                && evaluate_condition(expr, &condition_ctx)
+            {
+                anyhow::bail!(
+                    "http_poll: failure_expr '{}' matched after {} polls; last body: {}",
+                    expr,
+                    polls,
+                    condition_ctx
+                        .get("body")
+                        .cloned()
+                        .unwrap_or(serde_json::Value::Null)
+                );
+            }
+
+            let interval = Duration::from_secs_f64(interval_s);
+            if started.elapsed() + interval > deadline {
+                return Err(NodeError::new(
+                    NodeErrorKind::Timeout,
+                    format!(
+                        "http_poll: success_expr '{}' did not match within {}s ({} polls)",
+                        success_expr, timeout_s, polls
+                    ),
+                )
+                .into());
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+/// Copy `base` as an object and fill in [`SHARED_KEYS`] from `config`
+/// where `base` does not set them.
+fn with_shared_keys(
+    base: &serde_json::Value,
+    config: &serde_json::Value,
+) -> Result<serde_json::Map<String, serde_json::Value>> {
+    let mut merged = base
+        .as_object()
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("http_poll: 'start' must be an object"))?;
+    for key in SHARED_KEYS {
+        if let Some(value) = config.get(*key)
+            && !merged.contains_key(*key)
+        {
+            merged.insert(key.to_string(), value.clone());
+        }
+    }
+    Ok(merged)
+}
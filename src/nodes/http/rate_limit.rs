@@ -0,0 +1,87 @@
+//! Per-host token buckets for the `rate_limit` option of the HTTP nodes.
+//!
+//! Buckets are process-global, so every step (and every run) calling the
+//! same host shares one budget.
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+static BUCKETS: LazyLock<Mutex<HashMap<String, Arc<Mutex<TokenBucket>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// `rate_limit = { requests_per_second = N, burst = M }` from node config.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct RateLimit {
+    requests_per_second: f64,
+    burst: f64,
+}
+
+impl RateLimit {
+    pub(super) fn from_config(config: &serde_json::Value) -> Result<Option<Self>> {
+        let Some(limit) = config.get("rate_limit") else {
+            return Ok(None);
+        };
+        let requests_per_second = limit
+            .get("requests_per_second")
+            .and_then(|v| v.as_f64())
+            .filter(|v| v.is_finite() && *v > 0.0)
+            .ok_or_else(|| {
+                anyhow::anyhow!("rate_limit requires 'requests_per_second' as a positive number")
+            })?;
+        let burst = match limit.get("burst") {
+            None => 1,
+            Some(v) => v
+                .as_u64()
+                .filter(|b| *b > 0)
+                .ok_or_else(|| anyhow::anyhow!("rate_limit 'burst' must be a positive integer"))?,
+        };
+        Ok(Some(Self {
+            requests_per_second,
+            burst: burst as f64,
+        }))
+    }
+}
+
+struct TokenBucket {
+    /// Tokens available now; negative when callers have reserved future ones.
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Wait until `host` may receive another request under `limit`. The first
+/// `burst` requests go out at once; later ones are spaced
+/// `1 / requests_per_second` apart. A caller reserves its slot before
+/// sleeping, so concurrent steps queue up instead of racing.
+pub(super) async fn acquire(host: &str, limit: RateLimit) {
+    let bucket = BUCKETS
+        .lock()
+        .unwrap()
+        .entry(host.to_string())
+        .or_insert_with(|| {
+            Arc::new(Mutex::new(TokenBucket {
+                tokens: limit.burst,
+                refilled_at: Instant::now(),
+            }))
+        })
+        .clone();
+
+    let wait = {
+        let mut bucket = bucket.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * limit.requests_per_second).min(limit.burst);
+        bucket.refilled_at = now;
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / limit.requests_per_second)
+        }
+    };
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+}
@@ -1,13 +1,19 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crate::engine::types::{Context, NodeOutput};
+use crate::engine::profile;
+use crate::engine::types::{Context, NodeError, NodeOutput};
 use crate::lua::interpolate::interpolate_ctx;
 use crate::nodes::Node;
+use crate::nodes::utility::cache;
 
-use super::helpers::{body_value_to_text, build_form_body, interpolate_json_value};
+use super::helpers::{
+    body_value_to_text, build_form_body, build_multipart_body, decode_response_text,
+    interpolate_json_value,
+};
+use super::rate_limit::{self, RateLimit};
 
 struct HttpResponseOutput {
     status: u16,
@@ -16,7 +22,7 @@ struct HttpResponseOutput {
     retry_after_secs: Option<f64>,
 }
 
-pub(super) async fn do_http_request(
+pub(crate) async fn do_http_request(
     method: &str,
     config: &serde_json::Value,
     ctx: &Context,
@@ -62,24 +68,79 @@ pub(super) async fn do_http_request(
         .filter(|v| *v >= 0.0)
         .unwrap_or(60.0);
 
+    let response_encoding = config
+        .get("response_encoding")
+        .and_then(|v| v.as_str())
+        .unwrap_or("text");
+    if !matches!(response_encoding, "text" | "base64" | "bytes") {
+        anyhow::bail!(
+            "Unsupported response_encoding: {}. Expected one of: text, base64, bytes",
+            response_encoding
+        );
+    }
+
+    let rate_limit = RateLimit::from_config(config)?;
+    // Buckets are per host (and port), so other APIs are not slowed down.
+    let rate_limit_host = match rate_limit {
+        Some(_) => {
+            let parsed = reqwest::Url::parse(&url)
+                .map_err(|e| anyhow::anyhow!("Invalid URL '{}': {}", url, e))?;
+            let host = parsed.host_str().unwrap_or_default();
+            match parsed.port_or_known_default() {
+                Some(port) => format!("{}:{}", host, port),
+                None => host.to_string(),
+            }
+        }
+        None => String::new(),
+    };
+
+    // Opt-in response cache, stored through the `cache_set` backends.
+    let cache_ttl = config.get("cache_ttl").and_then(|v| v.as_u64());
+    let cache_backend = config
+        .get("cache_backend")
+        .and_then(|v| v.as_str())
+        .unwrap_or("memory");
+    let cache_key = cache_ttl.map(|_| response_cache_key(method, &url, config, ctx));
+    if let Some(ref key) = cache_key
+        && let Some(serde_json::Value::Object(cached)) =
+            cache::load(cache_backend, config, key).await?
+    {
+        let mut output: NodeOutput = cached.into_iter().collect();
+        output.insert(format!("{}_attempts", output_key), serde_json::json!(0));
+        output.insert(format!("{}_cached", output_key), serde_json::json!(true));
+        return Ok(output);
+    }
+
     let client = reqwest::Client::builder()
         .timeout(Duration::from_secs_f64(timeout_s))
         .build()?;
 
-    let mut request = match method.to_uppercase().as_str() {
-        "GET" => client.get(&url),
-        "POST" => client.post(&url),
-        "PUT" => client.put(&url),
-        "DELETE" => client.delete(&url),
-        "PATCH" => client.patch(&url),
+    let method = match method.to_uppercase().as_str() {
+        "GET" => reqwest::Method::GET,
+        "POST" => reqwest::Method::POST,
+        "PUT" => reqwest::Method::PUT,
+        "DELETE" => reqwest::Method::DELETE,
+        "PATCH" => reqwest::Method::PATCH,
+        "HEAD" => reqwest::Method::HEAD,
         _ => anyhow::bail!("Unsupported HTTP method: {}", method),
     };
+    // HEAD responses carry headers only, so there is no body to send or read.
+    let is_head = method == reqwest::Method::HEAD;
+    if is_head && config.get("body").is_some() {
+        anyhow::bail!("HTTP HEAD requests cannot have a 'body'");
+    }
+    let mut request = client.request(method.clone(), &url);
 
     // Headers
     let mut has_content_type_header = false;
+    // A multipart body needs its own Content-Type carrying the boundary.
+    let is_multipart = config.get("body_type").and_then(|v| v.as_str()) == Some("multipart");
     if let Some(headers) = config.get("headers").and_then(|v| v.as_object()) {
         let mut header_map = HeaderMap::new();
         for (k, v) in headers {
+            if is_multipart && k.eq_ignore_ascii_case("content-type") {
+                continue;
+            }
             if let Some(val) = v.as_str() {
                 let val = interpolate_ctx(val, ctx);
                 header_map.insert(
@@ -153,9 +214,18 @@ pub(super) async fn do_http_request(
                 }
                 request = request.body(text_body);
             }
+            "multipart" => {
+                let (boundary, multipart_body) = build_multipart_body(&interpolated_body)?;
+                request = request
+                    .header(
+                        "Content-Type",
+                        format!("multipart/form-data; boundary={}", boundary),
+                    )
+                    .body(multipart_body);
+            }
             other => {
                 anyhow::bail!(
-                    "Unsupported body_type '{}'. Expected one of: json, form, text",
+                    "Unsupported body_type '{}'. Expected one of: json, form, text, multipart",
                     other
                 );
             }
@@ -168,12 +238,20 @@ pub(super) async fn do_http_request(
 
     let mut attempt = 0_u64;
     loop {
+        // Status retries take a token too, so retrying a 429 cannot
+        // exceed the limit.
+        if let Some(limit) = rate_limit {
+            rate_limit::acquire(&rate_limit_host, limit).await;
+        }
+        let started = Instant::now();
         let response = request_template
             .try_clone()
             .ok_or_else(|| anyhow::anyhow!("HTTP request body is not retryable"))?
             .send()
             .await?;
-        let result = response_to_output(response, output_key).await?;
+        profile::record("http.first_byte_ms", started.elapsed());
+        let result = response_to_output(response, output_key, !is_head, response_encoding).await?;
+        profile::record("http.total_ms", started.elapsed());
         let should_retry =
             attempt < status_retries && retry_statuses.contains(&result.status) && !result.success;
 
@@ -195,19 +273,81 @@ pub(super) async fn do_http_request(
         }
 
         let mut output = result.output;
+        if let Some(key) = cache_key {
+            if result.success && !forbids_store(&output, output_key) {
+                let cached = serde_json::Value::Object(
+                    output.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+                );
+                cache::store(cache_backend, config, &key, cached, cache_ttl).await?;
+            }
+            output.insert(format!("{}_cached", output_key), serde_json::json!(false));
+        }
         output.insert(
             format!("{}_attempts", output_key),
             serde_json::Value::Number((attempt + 1).into()),
         );
 
         if fail_on_status && !result.success {
-            anyhow::bail!("HTTP {} {} returned status {}", method, url, result.status);
+            let message = format!("HTTP {} {} returned status {}", method, url, result.status);
+            // Step retries wait as long as the server asked, up to
+            // `max_retry_after`.
+            let err = match result.retry_after_secs {
+                Some(secs) => NodeError::http(
+                    result.status,
+                    format!("{} (Retry-After: {}s)", message, secs),
+                )
+                .with_retry_after(Duration::from_secs_f64(secs.min(max_retry_after_s))),
+                None => NodeError::http(result.status, message),
+            };
+            return Err(err.into());
         }
 
         return Ok(output);
     }
 }
 
+/// Cache key for a request: everything that shapes the response we would
+/// get back (method, URL, headers, auth and body, after interpolation).
+fn response_cache_key(
+    method: &str,
+    url: &str,
+    config: &serde_json::Value,
+    ctx: &Context,
+) -> String {
+    use sha2::{Digest, Sha256};
+
+    let resolve = |field: &str| {
+        config
+            .get(field)
+            .map(|v| interpolate_json_value(v, ctx))
+            .unwrap_or(serde_json::Value::Null)
+    };
+    let request = serde_json::json!([
+        method.to_uppercase(),
+        url,
+        resolve("headers"),
+        resolve("auth"),
+        resolve("body_type"),
+        resolve("body"),
+        resolve("response_encoding"),
+    ]);
+    let digest = Sha256::digest(request.to_string().as_bytes());
+    format!("http:{}", hex::encode(digest))
+}
+
+/// Whether the response said `Cache-Control: no-store`.
+fn forbids_store(output: &NodeOutput, output_key: &str) -> bool {
+    output
+        .get(&format!("{}_headers", output_key))
+        .and_then(|headers| headers.get("cache-control"))
+        .and_then(|v| v.as_str())
+        .is_some_and(|directives| {
+            directives
+                .split(',')
+                .any(|d| d.trim().eq_ignore_ascii_case("no-store"))
+        })
+}
+
 fn parse_retry_statuses(config: &serde_json::Value) -> Result<Vec<u16>> {
     let Some(values) = config.get("retry_statuses").and_then(|v| v.as_array()) else {
         return Ok(Vec::new());
@@ -228,9 +368,36 @@ fn parse_retry_statuses(config: &serde_json::Value) -> Result<Vec<u16>> {
         .collect()
 }
 
+/// Longest `Retry-After` taken at face value (one day). Anything larger is
+/// treated as this, so a hostile `Retry-After: 1e300` cannot overflow a
+/// `Duration`.
+const MAX_RETRY_AFTER_HEADER_S: f64 = 86_400.0;
+
+/// Seconds to wait from a `Retry-After` value: delay seconds (`120`) or an
+/// HTTP-date (`Wed, 21 Oct 2015 07:28:00 GMT`), which counts as `0` once
+/// past. Capped at [`MAX_RETRY_AFTER_HEADER_S`].
+fn parse_retry_after(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let secs = match value.parse::<f64>() {
+        Ok(secs) => (secs.is_finite() && secs >= 0.0).then_some(secs)?,
+        Err(_) => {
+            let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+            let millis = (at.with_timezone(&chrono::Utc) - chrono::Utc::now()).num_milliseconds();
+            millis.max(0) as f64 / 1000.0
+        }
+    };
+    Some(secs.min(MAX_RETRY_AFTER_HEADER_S))
+}
+
+/// Turn a response into node output. With `read_body` false (HEAD), only
+/// status, headers and success are emitted. `response_encoding` picks how
+/// the body lands in `{output_key}_data`: `text` (decoded by its charset,
+/// parsed as JSON when possible), `base64`, or `bytes` (an array of numbers).
 async fn response_to_output(
     response: reqwest::Response,
     output_key: &str,
+    read_body: bool,
+    response_encoding: &str,
 ) -> Result<HttpResponseOutput> {
     let status = response.status().as_u16();
     let success = response.status().is_success();
@@ -238,8 +405,12 @@ async fn response_to_output(
         .headers()
         .get(reqwest::header::RETRY_AFTER)
         .and_then(|value| value.to_str().ok())
-        .and_then(|value| value.parse::<f64>().ok())
-        .filter(|value| *value >= 0.0);
+        .and_then(parse_retry_after);
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
     let resp_headers: serde_json::Map<String, serde_json::Value> = response
         .headers()
         .iter()
@@ -251,6 +422,28 @@ async fn response_to_output(
         })
         .collect();
 
+    let mut output = NodeOutput::new();
+    output.insert(
+        format!("{}_status", output_key),
+        serde_json::Value::Number(status.into()),
+    );
+    output.insert(
+        format!("{}_headers", output_key),
+        serde_json::Value::Object(resp_headers),
+    );
+    output.insert(
+        format!("{}_success", output_key),
+        serde_json::Value::Bool(success),
+    );
+    if !read_body {
+        return Ok(HttpResponseOutput {
+            status,
+            success,
+            output,
+            retry_after_secs,
+        });
+    }
+
     // Size guard enforced during streaming — we do not buffer the full body
     // in memory before deciding. An honest `Content-Length` fails fast; a
     // liar gets cut off mid-stream the moment accumulated bytes cross the
@@ -277,26 +470,21 @@ async fn response_to_output(
         }
         buf.extend_from_slice(&chunk);
     }
-    let body_text = String::from_utf8_lossy(&buf).into_owned();
 
-    // Try to parse as JSON, fall back to string
-    let data: serde_json::Value =
-        serde_json::from_str(&body_text).unwrap_or(serde_json::Value::String(body_text));
+    let data = match response_encoding {
+        "base64" => {
+            use base64::Engine;
+            serde_json::Value::String(base64::engine::general_purpose::STANDARD.encode(&buf))
+        }
+        "bytes" => serde_json::Value::Array(buf.into_iter().map(Into::into).collect()),
+        _ => {
+            let body_text = decode_response_text(&buf, content_type.as_deref());
+            // Try to parse as JSON, fall back to string
+            serde_json::from_str(&body_text).unwrap_or(serde_json::Value::String(body_text))
+        }
+    };
 
-    let mut output = NodeOutput::new();
-    output.insert(
-        format!("{}_status", output_key),
-        serde_json::Value::Number(status.into()),
-    );
     output.insert(format!("{}_data", output_key), data);
-    output.insert(
-        format!("{}_headers", output_key),
-        serde_json::Value::Object(resp_headers),
-    );
-    output.insert(
-        format!("{}_success", output_key),
-        serde_json::Value::Bool(success),
-    );
 
     Ok(HttpResponseOutput {
         status,
@@ -394,3 +582,37 @@ impl Node for HttpDeleteNode {
         do_http_request("DELETE", config, ctx).await
     }
 }
+
+pub struct HttpPatchNode;
+
+#[async_trait]
+impl Node for HttpPatchNode {
+    fn node_type(&self) -> &str {
+        "http_patch"
+    }
+
+    fn description(&self) -> &str {
+        "HTTP PATCH request"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        do_http_request("PATCH", config, ctx).await
+    }
+}
+
+pub struct HttpHeadNode;
+
+#[async_trait]
+impl Node for HttpHeadNode {
+    fn node_type(&self) -> &str {
+        "http_head"
+    }
+
+    fn description(&self) -> &str {
+        "HTTP HEAD request (status and headers only)"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        do_http_request("HEAD", config, ctx).await
+    }
+}
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use base64::Engine;
+use futures_util::{SinkExt, StreamExt};
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+
+use crate::engine::types::{Context, NodeError, NodeErrorKind, NodeOutput};
+use crate::lua::interpolate::interpolate_ctx;
+use crate::nodes::Node;
+
+use super::helpers::interpolate_json_value;
+
+pub struct WebSocketNode;
+
+#[async_trait]
+impl Node for WebSocketNode {
+    fn node_type(&self) -> &str {
+        "websocket"
+    }
+
+    fn description(&self) -> &str {
+        "Connect to a WebSocket, send a message and collect the replies"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let url = config
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("websocket requires 'url' parameter"))?;
+        let url = interpolate_ctx(url, ctx);
+        let max_messages = config
+            .get("max_messages")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(1) as usize;
+        let timeout_s = config
+            .get("timeout_s")
+            .and_then(|v| v.as_f64())
+            .filter(|v| *v >= 0.0)
+            .unwrap_or(30.0);
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .unwrap_or("websocket");
+
+        let mut request = url.as_str().into_client_request()?;
+        if let Some(headers) = config.get("headers").and_then(|v| v.as_object()) {
+            for (k, v) in headers {
+                if let Some(val) = v.as_str() {
+                    let val = interpolate_ctx(val, ctx);
+                    request.headers_mut().insert(
+                        HeaderName::from_bytes(k.as_bytes())?,
+                        HeaderValue::from_str(&val)?,
+                    );
+                }
+            }
+        }
+
+        // One deadline covers the handshake, the send and every read.
+        let deadline = Instant::now() + Duration::from_secs_f64(timeout_s);
+        let (mut socket, _) =
+            tokio::time::timeout_at(deadline, tokio_tungstenite::connect_async(request))
+                .await
+                .map_err(|_| {
+                    NodeError::new(
+                        NodeErrorKind::Timeout,
+                        format!("websocket: connecting to {} timed out", url),
+                    )
+                })??;
+
+        // Strings are sent as-is; any other JSON value is sent serialized.
+        if let Some(message) = config.get("message") {
+            let text = match interpolate_json_value(message, ctx) {
+                serde_json::Value::String(s) => s,
+                other => other.to_string(),
+            };
+            socket.send(Message::text(text)).await?;
+        }
+
+        // Text frames are kept as strings, binary frames are base64-encoded.
+        // Pings are answered by the socket itself and are not collected.
+        let mut frames = Vec::new();
+        let mut closed = false;
+        let mut timed_out = false;
+        while frames.len() < max_messages {
+            let next = match tokio::time::timeout_at(deadline, socket.next()).await {
+                Ok(next) => next,
+                Err(_) => {
+                    timed_out = true;
+                    break;
+                }
+            };
+            match next.transpose()? {
+                Some(Message::Text(text)) => frames.push(serde_json::json!({
+                    "type": "text",
+                    "data": text.as_str(),
+                })),
+                Some(Message::Binary(bytes)) => frames.push(serde_json::json!({
+                    "type": "binary",
+                    "data": base64::engine::general_purpose::STANDARD.encode(&bytes),
+                })),
+                Some(Message::Close(_)) | None => {
+                    closed = true;
+                    break;
+                }
+                Some(_) => {}
+            }
+        }
+        if !closed {
+            // Best effort: the peer may already be gone.
+            let _ = socket.close(None).await;
+        }
+
+        let mut output = NodeOutput::new();
+        output.insert(output_key.to_string(), serde_json::Value::Array(frames));
+        output.insert(
+            format!("{}_closed", output_key),
+            serde_json::Value::Bool(closed),
+        );
+        output.insert(
+            format!("{}_timed_out", output_key),
+            serde_json::Value::Bool(timed_out),
+        );
+        Ok(output)
+    }
+}
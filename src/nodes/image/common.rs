@@ -296,3 +296,12 @@ pub(crate) fn read_pdf_bytes_capped(path: &str, node_name: &str) -> Result<Vec<u
 
     std::fs::read(path).map_err(|e| anyhow::anyhow!("Failed to read '{}': {}", path, e))
 }
+
+/// Whether the file at `path` starts with the `%PDF-` header.
+pub(crate) fn is_pdf_file(path: &str) -> bool {
+    use std::io::Read;
+    let mut magic = [0u8; 5];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|_| &magic == b"%PDF-")
+}
@@ -0,0 +1,988 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use comrak::nodes::{AstNode, ListType, NodeValue};
+use comrak::{Arena, Options, parse_document};
+use lopdf::{
+    Dictionary, Document, Object, ObjectId, Stream,
+    content::{Content, Operation},
+    dictionary,
+};
+
+use crate::engine::types::{Context, NodeOutput};
+use crate::lua::interpolate::interpolate_ctx;
+use crate::nodes::Node;
+
+/// Render Markdown straight to a PDF using the standard Helvetica/Courier
+/// fonts, so no browser or external renderer is needed.
+pub(crate) struct MarkdownToPdfNode;
+
+#[async_trait]
+impl Node for MarkdownToPdfNode {
+    fn node_type(&self) -> &str {
+        "markdown_to_pdf"
+    }
+
+    fn description(&self) -> &str {
+        "Render Markdown to a styled PDF file"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let markdown = resolve_markdown(config, ctx)?;
+        let output_path = config
+            .get("output_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("markdown_to_pdf requires 'output_path' parameter"))?;
+        let output_path = interpolate_ctx(output_path, ctx);
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .unwrap_or("markdown_to_pdf");
+
+        let (page_width, page_height) = resolve_page_size(config.get("page_size"))?;
+        let margins = resolve_margins(config.get("margin"))?;
+        if margins.left + margins.right >= page_width - 72.0
+            || margins.top + margins.bottom >= page_height - 72.0
+        {
+            anyhow::bail!("markdown_to_pdf: margins leave no room for content");
+        }
+        let mut styles = Styles::default();
+        if let Some(css) = config.get("css").and_then(|v| v.as_str()) {
+            styles.apply_css(css);
+        }
+
+        let mut layout = Layout::new(page_width, page_height, margins);
+        let arena = Arena::new();
+        let mut options = Options::default();
+        options.extension.strikethrough = true;
+        options.extension.table = true;
+        options.extension.autolink = true;
+        options.extension.tasklist = true;
+        let root = parse_document(&arena, &markdown, &options);
+        render_blocks(root, &mut layout, &styles, &BlockCtx::default());
+
+        let pages = layout.finish();
+        let page_count = pages.len();
+        write_pdf(pages, page_width, page_height, &output_path)?;
+
+        let mut output = NodeOutput::new();
+        output.insert(
+            format!("{}_path", output_key),
+            serde_json::Value::String(output_path),
+        );
+        output.insert(
+            format!("{}_page_count", output_key),
+            serde_json::json!(page_count),
+        );
+        output.insert(
+            format!("{}_success", output_key),
+            serde_json::Value::Bool(true),
+        );
+        Ok(output)
+    }
+}
+
+/// Markdown from `content` (interpolated) or the context key `source_key`.
+fn resolve_markdown(config: &serde_json::Value, ctx: &Context) -> Result<String> {
+    let content = config.get("content").and_then(|v| v.as_str());
+    let source_key = config.get("source_key").and_then(|v| v.as_str());
+    match (content, source_key) {
+        (Some(_), Some(_)) => {
+            anyhow::bail!("markdown_to_pdf accepts either 'content' or 'source_key', not both")
+        }
+        (Some(content), None) => Ok(interpolate_ctx(content, ctx)),
+        (None, Some(key)) => match ctx.get(key) {
+            Some(serde_json::Value::String(s)) => Ok(s.clone()),
+            Some(_) => anyhow::bail!("Context key '{}' must be a string", key),
+            None => anyhow::bail!("Key '{}' not found in context", key),
+        },
+        (None, None) => anyhow::bail!("markdown_to_pdf requires either 'content' or 'source_key'"),
+    }
+}
+
+/// Page size in points: a name (`A4`, `A5`, `Letter`, `Legal`) or `[w, h]`.
+fn resolve_page_size(value: Option<&serde_json::Value>) -> Result<(f32, f32)> {
+    match value {
+        None => Ok((595.0, 842.0)),
+        Some(serde_json::Value::String(name)) => match name.to_ascii_lowercase().as_str() {
+            "a4" => Ok((595.0, 842.0)),
+            "a5" => Ok((420.0, 595.0)),
+            "letter" => Ok((612.0, 792.0)),
+            "legal" => Ok((612.0, 1008.0)),
+            other => anyhow::bail!(
+                "markdown_to_pdf: unknown page_size '{}'. Use A4, A5, Letter, Legal or [width, height]",
+                other
+            ),
+        },
+        Some(serde_json::Value::Array(dims)) if dims.len() == 2 => {
+            let width = dims[0].as_f64().unwrap_or(0.0) as f32;
+            let height = dims[1].as_f64().unwrap_or(0.0) as f32;
+            if width <= 0.0 || height <= 0.0 {
+                anyhow::bail!("markdown_to_pdf: page_size dimensions must be > 0");
+            }
+            Ok((width, height))
+        }
+        Some(_) => anyhow::bail!("markdown_to_pdf: 'page_size' must be a name or [width, height]"),
+    }
+}
+
+struct Margins {
+    top: f32,
+    right: f32,
+    bottom: f32,
+    left: f32,
+}
+
+/// Margins in points: one number for all sides, or a table of
+/// `top`/`right`/`bottom`/`left` (missing sides keep the 56pt default).
+fn resolve_margins(value: Option<&serde_json::Value>) -> Result<Margins> {
+    const DEFAULT: f64 = 56.0;
+    let side = |v: Option<&serde_json::Value>| -> Result<f32> {
+        match v {
+            None => Ok(DEFAULT as f32),
+            Some(v) => v
+                .as_f64()
+                .filter(|m| *m >= 0.0)
+                .map(|m| m as f32)
+                .ok_or_else(|| anyhow::anyhow!("markdown_to_pdf: margins must be numbers >= 0")),
+        }
+    };
+    match value {
+        Some(serde_json::Value::Object(sides)) => Ok(Margins {
+            top: side(sides.get("top"))?,
+            right: side(sides.get("right"))?,
+            bottom: side(sides.get("bottom"))?,
+            left: side(sides.get("left"))?,
+        }),
+        other => {
+            let all = side(other)?;
+            Ok(Margins {
+                top: all,
+                right: all,
+                bottom: all,
+                left: all,
+            })
+        }
+    }
+}
+
+// --- Styles ---
+
+#[derive(Clone, Copy, PartialEq)]
+struct Rgb(f32, f32, f32);
+
+impl Rgb {
+    /// Parse `#rgb` or `#rrggbb`.
+    fn parse(value: &str) -> Option<Self> {
+        let hex = value.strip_prefix('#')?;
+        let channel = |s: &str| u8::from_str_radix(s, 16).ok().map(|c| c as f32 / 255.0);
+        match hex.len() {
+            3 => {
+                let expanded: Vec<String> = hex.chars().map(|c| format!("{}{}", c, c)).collect();
+                Some(Rgb(
+                    channel(&expanded[0])?,
+                    channel(&expanded[1])?,
+                    channel(&expanded[2])?,
+                ))
+            }
+            6 => Some(Rgb(
+                channel(&hex[0..2])?,
+                channel(&hex[2..4])?,
+                channel(&hex[4..6])?,
+            )),
+            _ => None,
+        }
+    }
+
+    fn operands(self) -> Vec<Object> {
+        vec![self.0.into(), self.1.into(), self.2.into()]
+    }
+}
+
+#[derive(Clone, Copy)]
+struct TextStyle {
+    font_size: f32,
+    line_height: f32,
+    color: Rgb,
+}
+
+struct Styles {
+    body: TextStyle,
+    headings: [TextStyle; 6],
+    code: TextStyle,
+    code_background: Rgb,
+    link: Rgb,
+    blockquote: Rgb,
+    rule: Rgb,
+}
+
+impl Default for Styles {
+    fn default() -> Self {
+        let text = Rgb(0.13, 0.13, 0.13);
+        let heading = |font_size| TextStyle {
+            font_size,
+            line_height: 1.25,
+            color: Rgb(0.07, 0.07, 0.07),
+        };
+        Self {
+            body: TextStyle {
+                font_size: 11.0,
+                line_height: 1.45,
+                color: text,
+            },
+            headings: [
+                heading(24.0),
+                heading(19.0),
+                heading(15.0),
+                heading(13.0),
+                heading(11.5),
+                heading(11.0),
+            ],
+            code: TextStyle {
+                font_size: 9.5,
+                line_height: 1.35,
+                color: text,
+            },
+            code_background: Rgb(0.95, 0.95, 0.95),
+            link: Rgb(0.02, 0.27, 0.68),
+            blockquote: Rgb(0.4, 0.4, 0.4),
+            rule: Rgb(0.8, 0.8, 0.8),
+        }
+    }
+}
+
+impl Styles {
+    /// Apply a small CSS subset: `font-size` (pt, px or em), `line-height`
+    /// (a multiplier), `color` and `background-color` (hex) on the `body`,
+    /// `h1`-`h6`, `code`/`pre`, `a`, `blockquote` and `hr` selectors.
+    /// Anything else is ignored.
+    fn apply_css(&mut self, css: &str) {
+        for rule in strip_css_comments(css).split('}') {
+            let Some((selectors, declarations)) = rule.split_once('{') else {
+                continue;
+            };
+            for declaration in declarations.split(';') {
+                let Some((property, value)) = declaration.split_once(':') else {
+                    continue;
+                };
+                let property = property.trim().to_ascii_lowercase();
+                let value = value.trim();
+                for selector in selectors.split(',') {
+                    self.apply_declaration(selector.trim(), &property, value);
+                }
+            }
+        }
+    }
+
+    fn apply_declaration(&mut self, selector: &str, property: &str, value: &str) {
+        let body_size = self.body.font_size;
+        let target = match selector {
+            "body" | "p" => Some(&mut self.body),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = selector[1..].parse::<usize>().unwrap_or(1);
+                Some(&mut self.headings[level - 1])
+            }
+            "code" | "pre" => {
+                if property == "background-color" || property == "background" {
+                    if let Some(color) = Rgb::parse(value) {
+                        self.code_background = color;
+                    }
+                    return;
+                }
+                Some(&mut self.code)
+            }
+            _ => None,
+        };
+        if let Some(style) = target {
+            match property {
+                "font-size" => {
+                    if let Some(size) = parse_font_size(value, body_size) {
+                        style.font_size = size;
+                    }
+                }
+                "line-height" => {
+                    if let Ok(multiplier) = value.parse::<f32>()
+                        && multiplier > 0.0
+                    {
+                        style.line_height = multiplier;
+                    }
+                }
+                "color" => {
+                    if let Some(color) = Rgb::parse(value) {
+                        style.color = color;
+                    }
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        let color = match property {
+            "color" | "border-color" => Rgb::parse(value),
+            _ => None,
+        };
+        if let Some(color) = color {
+            match selector {
+                "a" => self.link = color,
+                "blockquote" => self.blockquote = color,
+                "hr" => self.rule = color,
+                _ => {}
+            }
+        }
+    }
+}
+
+fn strip_css_comments(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        rest = rest[start + 2..]
+            .find("*/")
+            .map_or("", |end| &rest[start + 2 + end + 2..]);
+    }
+    out.push_str(rest);
+    out
+}
+
+fn parse_font_size(value: &str, base: f32) -> Option<f32> {
+    let (number, scale) = if let Some(n) = value.strip_suffix("pt") {
+        (n, 1.0)
+    } else if let Some(n) = value.strip_suffix("px") {
+        (n, 0.75)
+    } else if let Some(n) = value.strip_suffix("em") {
+        (n, base)
+    } else {
+        (value, 1.0)
+    };
+    number
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .map(|n| n * scale)
+        .filter(|size| *size > 0.0)
+}
+
+// --- Fonts ---
+
+#[derive(Clone, Copy, PartialEq)]
+enum Font {
+    Regular,
+    Bold,
+    Italic,
+    BoldItalic,
+    Mono,
+}
+
+const FONTS: [Font; 5] = [
+    Font::Regular,
+    Font::Bold,
+    Font::Italic,
+    Font::BoldItalic,
+    Font::Mono,
+];
+
+/// Helvetica advance widths for ASCII 32..=126, in 1/1000 em.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Helvetica-Bold advance widths for ASCII 32..=126, in 1/1000 em.
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+            Font::Italic => "F3",
+            Font::BoldItalic => "F4",
+            Font::Mono => "F5",
+        }
+    }
+
+    fn base_font(self) -> &'static str {
+        match self {
+            Font::Regular => "Helvetica",
+            Font::Bold => "Helvetica-Bold",
+            Font::Italic => "Helvetica-Oblique",
+            Font::BoldItalic => "Helvetica-BoldOblique",
+            Font::Mono => "Courier",
+        }
+    }
+
+    fn bold(self) -> Self {
+        match self {
+            Font::Regular => Font::Bold,
+            Font::Italic => Font::BoldItalic,
+            other => other,
+        }
+    }
+
+    fn italic(self) -> Self {
+        match self {
+            Font::Regular => Font::Italic,
+            Font::Bold => Font::BoldItalic,
+            other => other,
+        }
+    }
+
+    /// Width of `text` at `size` points.
+    fn measure(self, text: &str, size: f32) -> f32 {
+        let units: u32 = text
+            .chars()
+            .map(|c| match self {
+                Font::Mono => 600,
+                _ => {
+                    let table = match self {
+                        Font::Bold | Font::BoldItalic => &HELVETICA_BOLD_WIDTHS,
+                        _ => &HELVETICA_WIDTHS,
+                    };
+                    match c as u32 {
+                        code @ 32..=126 => u32::from(table[(code - 32) as usize]),
+                        _ if c == '•' => 350,
+                        _ => 556,
+                    }
+                }
+            })
+            .sum();
+        units as f32 * size / 1000.0
+    }
+}
+
+/// Encode `text` for a WinAnsiEncoding font; characters it cannot show
+/// become `?`.
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            ' '..='~' => c as u8,
+            '\u{a0}'..='\u{ff}' => c as u32 as u8,
+            '€' => 0x80,
+            '…' => 0x85,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            '™' => 0x99,
+            _ => b'?',
+        })
+        .collect()
+}
+
+// --- Layout ---
+
+#[derive(Clone)]
+struct Span {
+    text: String,
+    font: Font,
+    color: Rgb,
+}
+
+/// Per-block state inherited by nested blocks.
+#[derive(Clone, Default)]
+struct BlockCtx {
+    indent: f32,
+    /// x offsets (from the left margin) of enclosing blockquote bars.
+    quote_bars: Vec<f32>,
+    color: Option<Rgb>,
+    /// Fill behind each line (code blocks).
+    background: Option<Rgb>,
+}
+
+/// Inset of code block text from its background.
+const CODE_PADDING: f32 = 6.0;
+
+struct Layout {
+    width: f32,
+    height: f32,
+    margins: Margins,
+    pages: Vec<Vec<Operation>>,
+    y: f32,
+    /// List marker waiting to be drawn beside the next line.
+    marker: Option<(String, f32)>,
+}
+
+impl Layout {
+    fn new(width: f32, height: f32, margins: Margins) -> Self {
+        let y = height - margins.top;
+        Self {
+            width,
+            height,
+            margins,
+            pages: vec![Vec::new()],
+            y,
+            marker: None,
+        }
+    }
+
+    fn content_width(&self, indent: f32) -> f32 {
+        (self.width - self.margins.left - self.margins.right - indent).max(36.0)
+    }
+
+    fn ops(&mut self) -> &mut Vec<Operation> {
+        self.pages.last_mut().expect("layout always has a page")
+    }
+
+    /// Reserve `height` points, starting a new page when they do not fit.
+    fn reserve(&mut self, height: f32) -> f32 {
+        let top = self.height - self.margins.top;
+        if self.y - height < self.margins.bottom && self.y < top {
+            self.pages.push(Vec::new());
+            self.y = top;
+        }
+        let line_top = self.y;
+        self.y -= height;
+        line_top
+    }
+
+    fn gap(&mut self, points: f32) {
+        if self.y < self.height - self.margins.top {
+            self.y -= points;
+        }
+    }
+
+    fn fill_rect(&mut self, color: Rgb, x: f32, y: f32, w: f32, h: f32) {
+        let ops = self.ops();
+        ops.push(Operation::new("rg", color.operands()));
+        ops.push(Operation::new(
+            "re",
+            vec![x.into(), y.into(), w.into(), h.into()],
+        ));
+        ops.push(Operation::new("f", vec![]));
+    }
+
+    fn text(&mut self, text: &str, font: Font, size: f32, color: Rgb, x: f32, baseline: f32) {
+        let ops = self.ops();
+        ops.push(Operation::new("BT", vec![]));
+        ops.push(Operation::new(
+            "Tf",
+            vec![Object::Name(font.resource().into()), size.into()],
+        ));
+        ops.push(Operation::new("rg", color.operands()));
+        ops.push(Operation::new("Td", vec![x.into(), baseline.into()]));
+        ops.push(Operation::new(
+            "Tj",
+            vec![Object::string_literal(win_ansi(text))],
+        ));
+        ops.push(Operation::new("ET", vec![]));
+    }
+
+    /// Draw one laid-out line with its list marker and quote bars.
+    fn draw_line(&mut self, spans: &[Span], style: TextStyle, block: &BlockCtx, line_height: f32) {
+        let top = self.reserve(line_height);
+        let baseline = top - (line_height - style.font_size) / 2.0 - style.font_size * 0.8;
+        let left = self.margins.left;
+        if let Some(background) = block.background {
+            let x = block.indent - CODE_PADDING;
+            let width = self.content_width(x);
+            self.fill_rect(background, left + x, top - line_height, width, line_height);
+        }
+        for bar in &block.quote_bars {
+            self.fill_rect(
+                Rgb(0.85, 0.85, 0.85),
+                left + bar,
+                top - line_height,
+                2.0,
+                line_height,
+            );
+        }
+        if let Some((marker, x)) = self.marker.take() {
+            self.text(
+                &marker,
+                Font::Regular,
+                style.font_size,
+                style.color,
+                left + x,
+                baseline,
+            );
+        }
+        let mut x = left + block.indent;
+        for span in spans {
+            self.text(
+                &span.text,
+                span.font,
+                style.font_size,
+                span.color,
+                x,
+                baseline,
+            );
+            x += span.font.measure(&span.text, style.font_size);
+        }
+    }
+
+    /// Wrap `spans` to the available width and draw them.
+    fn paragraph(&mut self, spans: Vec<Span>, style: TextStyle, block: &BlockCtx) {
+        let max_width = self.content_width(block.indent);
+        let line_height = style.font_size * style.line_height;
+        for line in wrap(spans, style.font_size, max_width) {
+            self.draw_line(&line, style, block, line_height);
+        }
+    }
+
+    fn rule(&mut self, color: Rgb, indent: f32) {
+        let top = self.reserve(12.0);
+        let y = top - 6.0;
+        let x0 = self.margins.left + indent;
+        let x1 = self.width - self.margins.right;
+        let ops = self.ops();
+        ops.push(Operation::new("RG", color.operands()));
+        ops.push(Operation::new("w", vec![0.75.into()]));
+        ops.push(Operation::new("m", vec![x0.into(), y.into()]));
+        ops.push(Operation::new("l", vec![x1.into(), y.into()]));
+        ops.push(Operation::new("S", vec![]));
+    }
+
+    fn finish(self) -> Vec<Vec<Operation>> {
+        self.pages
+    }
+}
+
+/// Greedy word wrap. Spaces collapse, `\n` forces a break, and words wider
+/// than the line are split by character.
+fn wrap(spans: Vec<Span>, size: f32, max_width: f32) -> Vec<Vec<Span>> {
+    let mut lines = Vec::new();
+    let mut line: Vec<Span> = Vec::new();
+    let mut width = 0.0;
+    let mut pending_space: Option<Span> = None;
+
+    let push = |line: &mut Vec<Span>, piece: Span| match line.last_mut() {
+        Some(last) if last.font == piece.font && last.color == piece.color => {
+            last.text.push_str(&piece.text)
+        }
+        _ => line.push(piece),
+    };
+
+    for span in spans {
+        for (idx, segment) in span.text.split('\n').enumerate() {
+            if idx > 0 {
+                lines.push(std::mem::take(&mut line));
+                width = 0.0;
+                pending_space = None;
+            }
+            let mut rest = segment;
+            while !rest.is_empty() {
+                let trimmed = rest.trim_start();
+                if trimmed.len() != rest.len() {
+                    if !line.is_empty() {
+                        pending_space = Some(Span {
+                            text: " ".to_string(),
+                            ..span.clone()
+                        });
+                    }
+                    rest = trimmed;
+                    continue;
+                }
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                let mut word = &rest[..end];
+                rest = &rest[end..];
+
+                loop {
+                    let space_width = pending_space
+                        .as_ref()
+                        .map_or(0.0, |s| s.font.measure(" ", size));
+                    let word_width = span.font.measure(word, size);
+                    if width + space_width + word_width <= max_width {
+                        if let Some(space) = pending_space.take() {
+                            push(&mut line, space);
+                        }
+                        push(
+                            &mut line,
+                            Span {
+                                text: word.to_string(),
+                                ..span.clone()
+                            },
+                        );
+                        width += space_width + word_width;
+                        break;
+                    }
+                    if !line.is_empty() {
+                        lines.push(std::mem::take(&mut line));
+                        width = 0.0;
+                        pending_space = None;
+                        continue;
+                    }
+                    // The word alone is too wide: place as many characters
+                    // as fit and carry the rest to the next line.
+                    let mut split = 0;
+                    let mut used = 0.0;
+                    for (pos, c) in word.char_indices() {
+                        let w = span.font.measure(c.encode_utf8(&mut [0; 4]), size);
+                        if used + w > max_width && pos > 0 {
+                            break;
+                        }
+                        used += w;
+                        split = pos + c.len_utf8();
+                    }
+                    line.push(Span {
+                        text: word[..split].to_string(),
+                        ..span.clone()
+                    });
+                    lines.push(std::mem::take(&mut line));
+                    width = 0.0;
+                    word = &word[split..];
+                    if word.is_empty() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+// --- Markdown walk ---
+
+fn render_blocks<'a>(
+    node: &'a AstNode<'a>,
+    layout: &mut Layout,
+    styles: &Styles,
+    block: &BlockCtx,
+) {
+    for child in node.children() {
+        render_block(child, layout, styles, block);
+    }
+}
+
+fn render_block<'a>(node: &'a AstNode<'a>, layout: &mut Layout, styles: &Styles, block: &BlockCtx) {
+    let value = node.data().value.clone();
+    match value {
+        NodeValue::Paragraph => {
+            let style = TextStyle {
+                color: block.color.unwrap_or(styles.body.color),
+                ..styles.body
+            };
+            let mut spans = Vec::new();
+            collect_inline(node, Font::Regular, style.color, styles, &mut spans);
+            layout.paragraph(spans, style, block);
+            layout.gap(style.font_size * 0.6);
+        }
+        NodeValue::Heading(heading) => {
+            let level = usize::from(heading.level.clamp(1, 6));
+            let style = styles.headings[level - 1];
+            let mut spans = Vec::new();
+            collect_inline(node, Font::Bold, style.color, styles, &mut spans);
+            layout.gap(style.font_size * 0.5);
+            layout.paragraph(spans, style, block);
+            layout.gap(style.font_size * 0.35);
+        }
+        NodeValue::List(list) => {
+            for (number, item) in (list.start..).zip(node.children()) {
+                let marker = match item.data().value {
+                    NodeValue::TaskItem(ref task) if task.symbol.is_some() => "[x]".to_string(),
+                    NodeValue::TaskItem(_) => "[ ]".to_string(),
+                    _ if list.list_type == ListType::Ordered => format!("{}.", number),
+                    _ => "•".to_string(),
+                };
+                layout.marker = Some((marker, block.indent));
+                let nested = BlockCtx {
+                    indent: block.indent + 20.0,
+                    ..block.clone()
+                };
+                render_blocks(item, layout, styles, &nested);
+                layout.marker = None;
+            }
+            layout.gap(styles.body.font_size * 0.3);
+        }
+        NodeValue::CodeBlock(code) => {
+            let style = styles.code;
+            let line_height = style.font_size * style.line_height;
+            let max_width = layout.content_width(block.indent) - 2.0 * CODE_PADDING;
+            let literal = code.literal.trim_end_matches('\n');
+            let code_block = BlockCtx {
+                indent: block.indent + CODE_PADDING,
+                background: Some(styles.code_background),
+                ..block.clone()
+            };
+            layout.gap(2.0);
+            for source_line in literal.split('\n') {
+                let spans = vec![Span {
+                    text: source_line.replace('\t', "    "),
+                    font: Font::Mono,
+                    color: style.color,
+                }];
+                for line in wrap_preformatted(spans, style.font_size, max_width) {
+                    layout.draw_line(&line, style, &code_block, line_height);
+                }
+            }
+            layout.gap(styles.body.font_size * 0.6);
+        }
+        NodeValue::BlockQuote | NodeValue::MultilineBlockQuote(_) | NodeValue::Alert(_) => {
+            let mut quote_bars = block.quote_bars.clone();
+            quote_bars.push(block.indent);
+            let nested = BlockCtx {
+                indent: block.indent + 14.0,
+                quote_bars,
+                color: Some(styles.blockquote),
+                background: None,
+            };
+            render_blocks(node, layout, styles, &nested);
+        }
+        NodeValue::ThematicBreak => {
+            layout.rule(styles.rule, block.indent);
+            layout.gap(styles.body.font_size * 0.4);
+        }
+        NodeValue::Table(_) => {
+            for row in node.children() {
+                let header = matches!(row.data().value, NodeValue::TableRow(true));
+                let font = if header { Font::Bold } else { Font::Regular };
+                let mut spans = Vec::new();
+                for (idx, cell) in row.children().enumerate() {
+                    if idx > 0 {
+                        spans.push(Span {
+                            text: "  |  ".to_string(),
+                            font: Font::Regular,
+                            color: styles.rule,
+                        });
+                    }
+                    collect_inline(cell, font, styles.body.color, styles, &mut spans);
+                }
+                layout.paragraph(spans, styles.body, block);
+            }
+            layout.gap(styles.body.font_size * 0.6);
+        }
+        NodeValue::HtmlBlock(_) | NodeValue::FrontMatter(_) => {}
+        _ => render_blocks(node, layout, styles, block),
+    }
+}
+
+/// Wrap preformatted text by character, keeping its spacing.
+fn wrap_preformatted(spans: Vec<Span>, size: f32, max_width: f32) -> Vec<Vec<Span>> {
+    let mut lines = Vec::new();
+    for span in spans {
+        let per_line = ((max_width / span.font.measure(" ", size)).floor() as usize).max(1);
+        let chars: Vec<char> = span.text.chars().collect();
+        if chars.is_empty() {
+            lines.push(Vec::new());
+        }
+        for chunk in chars.chunks(per_line) {
+            lines.push(vec![Span {
+                text: chunk.iter().collect(),
+                ..span.clone()
+            }]);
+        }
+    }
+    lines
+}
+
+fn collect_inline<'a>(
+    node: &'a AstNode<'a>,
+    font: Font,
+    color: Rgb,
+    styles: &Styles,
+    spans: &mut Vec<Span>,
+) {
+    for child in node.children() {
+        let value = child.data().value.clone();
+        match value {
+            NodeValue::Text(text) => spans.push(Span {
+                text: text.to_string(),
+                font,
+                color,
+            }),
+            NodeValue::Code(code) => spans.push(Span {
+                text: code.literal,
+                font: Font::Mono,
+                color,
+            }),
+            NodeValue::SoftBreak => spans.push(Span {
+                text: " ".to_string(),
+                font,
+                color,
+            }),
+            NodeValue::LineBreak => spans.push(Span {
+                text: "\n".to_string(),
+                font,
+                color,
+            }),
+            NodeValue::Strong => collect_inline(child, font.bold(), color, styles, spans),
+            NodeValue::Emph => collect_inline(child, font.italic(), color, styles, spans),
+            NodeValue::Link(_) => collect_inline(child, font, styles.link, styles, spans),
+            NodeValue::HtmlInline(_) => {}
+            _ => collect_inline(child, font, color, styles, spans),
+        }
+    }
+}
+
+// --- PDF output ---
+
+fn write_pdf(pages: Vec<Vec<Operation>>, width: f32, height: f32, output_path: &str) -> Result<()> {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+
+    let mut fonts = Dictionary::new();
+    for font in FONTS {
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => font.base_font(),
+            "Encoding" => "WinAnsiEncoding",
+        });
+        fonts.set(font.resource(), font_id);
+    }
+    let resources_id = doc.add_object(dictionary! { "Font" => fonts });
+
+    let mut page_ids: Vec<ObjectId> = Vec::new();
+    for operations in pages {
+        let content = Content { operations };
+        let content_id = doc.add_object(Stream::new(
+            dictionary! {},
+            content.encode().map_err(|e| {
+                anyhow::anyhow!("markdown_to_pdf failed to encode content stream: {:?}", e)
+            })?,
+        ));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), width.into(), height.into()],
+        });
+        page_ids.push(page_id);
+    }
+
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => page_ids.iter().map(|id| Object::Reference(*id)).collect::<Vec<_>>(),
+            "Count" => page_ids.len() as u32,
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.compress();
+
+    if let Some(parent) = std::path::Path::new(output_path).parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            anyhow::anyhow!("markdown_to_pdf: failed to create output directory: {}", e)
+        })?;
+    }
+    doc.save(output_path).map_err(|e| {
+        anyhow::anyhow!(
+            "markdown_to_pdf: failed to save PDF '{}': {:?}",
+            output_path,
+            e
+        )
+    })?;
+    Ok(())
+}
@@ -4,17 +4,21 @@ pub(crate) mod image_basic;
 pub(crate) mod image_conversion;
 pub(crate) mod image_metadata;
 pub(crate) mod image_sources;
+pub(crate) mod markdown_pdf;
 pub(crate) mod pdf_merge_split;
 pub(crate) mod pdf_metadata;
 pub(crate) mod pdf_render;
+pub(crate) mod watermark;
 
 pub(crate) use image_advanced::{ImageConvertNode, ImageGrayscaleNode, ImageWatermarkNode};
 pub(crate) use image_basic::{ImageCropNode, ImageFlipNode, ImageResizeNode, ImageRotateNode};
 pub(crate) use image_conversion::ImageToPdfNode;
 pub(crate) use image_metadata::ImageMetadataNode;
+pub(crate) use markdown_pdf::MarkdownToPdfNode;
 pub(crate) use pdf_merge_split::{PdfMergeNode, PdfSplitNode};
 pub(crate) use pdf_metadata::PdfMetadataNode;
 pub(crate) use pdf_render::{PdfThumbnailNode, PdfToImageNode};
+pub(crate) use watermark::WatermarkNode;
 
 use std::sync::Arc;
 
@@ -31,6 +35,8 @@ pub fn register_all(registry: &mut crate::nodes::NodeRegistry) {
     registry.register(Arc::new(ImageMetadataNode));
     registry.register(Arc::new(ImageConvertNode));
     registry.register(Arc::new(ImageWatermarkNode));
+    registry.register(Arc::new(WatermarkNode));
     registry.register(Arc::new(PdfMergeNode));
     registry.register(Arc::new(PdfSplitNode));
+    registry.register(Arc::new(MarkdownToPdfNode));
 }
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -13,20 +13,25 @@ use super::common::parse_pages_spec;
 pub(crate) struct PdfMergeNode;
 pub(crate) struct PdfSplitNode;
 
-/// Recursively collect all objects referenced by a given object.
+/// Page attributes a page can inherit from its ancestors in the page tree.
+const INHERITABLE_PAGE_KEYS: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+/// Recursively collect all objects referenced by a given object, without
+/// entering the objects in `skip`.
 pub(crate) fn collect_objects_recursive(
     doc: &Document,
     obj_id: lopdf::ObjectId,
+    skip: &BTreeSet<lopdf::ObjectId>,
     collected: &mut BTreeMap<lopdf::ObjectId, Object>,
 ) {
-    if collected.contains_key(&obj_id) {
+    if collected.contains_key(&obj_id) || skip.contains(&obj_id) {
         return;
     }
     if let Ok(obj) = doc.get_object(obj_id) {
         collected.insert(obj_id, obj.clone());
         let refs = extract_references(obj);
         for r in refs {
-            collect_objects_recursive(doc, r, collected);
+            collect_objects_recursive(doc, r, skip, collected);
         }
     }
 }
@@ -57,13 +62,14 @@ pub(crate) fn extract_references(obj: &Object) -> Vec<lopdf::ObjectId> {
 }
 
 /// Remap ObjectId references within an object using the provided mapping.
+/// References missing from the mapping point at objects that were not
+/// copied and become `null`.
 pub(crate) fn remap_references(obj: &mut Object, map: &BTreeMap<lopdf::ObjectId, lopdf::ObjectId>) {
     match obj {
-        Object::Reference(id) => {
-            if let Some(new_id) = map.get(id) {
-                *id = *new_id;
-            }
-        }
+        Object::Reference(id) => match map.get(id) {
+            Some(new_id) => *id = *new_id,
+            None => *obj = Object::Null,
+        },
         Object::Array(arr) => {
             for item in arr.iter_mut() {
                 remap_references(item, map);
@@ -83,6 +89,209 @@ pub(crate) fn remap_references(obj: &mut Object, map: &BTreeMap<lopdf::ObjectId,
     }
 }
 
+fn parent_of(doc: &Document, id: lopdf::ObjectId) -> Option<lopdf::ObjectId> {
+    doc.get_dictionary(id)
+        .ok()?
+        .get(b"Parent")
+        .ok()?
+        .as_reference()
+        .ok()
+}
+
+/// The value of `key` on the nearest ancestor of `page_id` in the page tree,
+/// for page attributes such as `Resources` and `MediaBox` that pages inherit.
+pub(crate) fn inherited_page_attribute<'a>(
+    doc: &'a Document,
+    page_id: lopdf::ObjectId,
+    key: &[u8],
+) -> Option<&'a Object> {
+    let mut ancestor = parent_of(doc, page_id);
+    while let Some(id) = ancestor {
+        if let Ok(value) = doc.get_dictionary(id).and_then(|dict| dict.get(key)) {
+            return Some(value);
+        }
+        ancestor = parent_of(doc, id);
+    }
+    None
+}
+
+/// Ids of every node in `doc`'s page tree: the pages and their ancestors.
+fn page_tree_ids(doc: &Document) -> BTreeSet<lopdf::ObjectId> {
+    let mut ids = BTreeSet::new();
+    for page_id in doc.get_pages().into_values() {
+        let mut current = Some(page_id);
+        while let Some(id) = current {
+            if !ids.insert(id) {
+                break;
+            }
+            current = parent_of(doc, id);
+        }
+    }
+    ids
+}
+
+/// Copy `page_ids` from `source` into `target` as kids of `parent`, returning
+/// the new page ids in order.
+///
+/// The source page tree is not copied: each page gets the attributes it
+/// inherited from its ancestors and a new `Parent`, and references to pages
+/// that are not copied (links, annotations) become `null`. Objects shared by
+/// the pages, such as fonts, are copied once.
+pub(crate) fn copy_pages(
+    target: &mut Document,
+    source: &Document,
+    page_ids: &[lopdf::ObjectId],
+    parent: lopdf::ObjectId,
+) -> Result<Vec<lopdf::ObjectId>> {
+    let tree = page_tree_ids(source);
+    let mut id_remap = BTreeMap::new();
+    let mut pages = Vec::with_capacity(page_ids.len());
+    for &page_id in page_ids {
+        let mut page = source
+            .get_dictionary(page_id)
+            .map_err(|e| anyhow::anyhow!("invalid page object {:?}: {:?}", page_id, e))?
+            .clone();
+        for key in INHERITABLE_PAGE_KEYS {
+            if page.has(key) {
+                continue;
+            }
+            if let Some(value) = inherited_page_attribute(source, page_id, key) {
+                page.set(key, value.clone());
+            }
+        }
+        page.remove(b"Parent");
+        // A page listed twice is copied twice; only the last copy is linked
+        // from other pages' references.
+        id_remap.insert(page_id, target.new_object_id());
+        pages.push((id_remap[&page_id], Object::Dictionary(page)));
+    }
+
+    let mut collected = BTreeMap::new();
+    for (_, page) in &pages {
+        for r in extract_references(page) {
+            collect_objects_recursive(source, r, &tree, &mut collected);
+        }
+    }
+    for &old_id in collected.keys() {
+        id_remap.insert(old_id, target.new_object_id());
+    }
+    for (old_id, mut obj) in collected {
+        remap_references(&mut obj, &id_remap);
+        target.objects.insert(id_remap[&old_id], obj);
+    }
+
+    let mut new_page_ids = Vec::with_capacity(pages.len());
+    for (new_id, mut page) in pages {
+        remap_references(&mut page, &id_remap);
+        if let Object::Dictionary(dict) = &mut page {
+            dict.set("Parent", parent);
+        }
+        target.objects.insert(new_id, page);
+        new_page_ids.push(new_id);
+    }
+    Ok(new_page_ids)
+}
+
+/// Write `kids` as the page tree of `doc` under `pages_id`, add a catalog
+/// for it and save to `path`, creating parent directories as needed.
+fn finish_document(
+    mut doc: Document,
+    pages_id: lopdf::ObjectId,
+    kids: Vec<lopdf::ObjectId>,
+    path: &str,
+) -> Result<()> {
+    let count = kids.len() as u32;
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids.into_iter().map(Object::Reference).collect::<Vec<_>>(),
+            "Count" => count,
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.max_id = doc.objects.keys().map(|id| id.0).max().unwrap_or(0);
+
+    if let Some(parent) = std::path::Path::new(path).parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| anyhow::anyhow!("failed to create output directory: {}", e))?;
+    }
+    doc.save(path)
+        .map_err(|e| anyhow::anyhow!("failed to save '{}': {:?}", path, e))?;
+    Ok(())
+}
+
+/// Copy the document information dictionary (title, author, ...) of
+/// `source`, if it has one, into `target`.
+fn copy_info(target: &mut Document, source: &Document) {
+    let Ok(info) = source.trailer.get(b"Info") else {
+        return;
+    };
+    let mut info = info.clone();
+    let mut collected = BTreeMap::new();
+    for r in extract_references(&info) {
+        collect_objects_recursive(source, r, &BTreeSet::new(), &mut collected);
+    }
+    let id_remap: BTreeMap<_, _> = collected
+        .keys()
+        .map(|&old_id| (old_id, target.new_object_id()))
+        .collect();
+    for (old_id, mut obj) in collected {
+        remap_references(&mut obj, &id_remap);
+        target.objects.insert(id_remap[&old_id], obj);
+    }
+    remap_references(&mut info, &id_remap);
+    target.trailer.set("Info", info);
+}
+
+/// Paths from `sources` (or its older name `files`), interpolated, or the
+/// array under `source_key`.
+fn resolve_merge_sources(config: &serde_json::Value, ctx: &Context) -> Result<Vec<String>> {
+    let listed = config.get("sources").or_else(|| config.get("files"));
+    let source_key = config.get("source_key").and_then(|v| v.as_str());
+    let (entries, from_ctx) = match (listed, source_key) {
+        (Some(_), Some(_)) => {
+            anyhow::bail!("pdf_merge accepts either 'sources' or 'source_key', not both")
+        }
+        (Some(listed), None) => (listed, false),
+        (None, Some(key)) => (
+            ctx.get(key)
+                .ok_or_else(|| anyhow::anyhow!("Key '{}' not found in context", key))?,
+            true,
+        ),
+        (None, None) => anyhow::bail!("pdf_merge requires 'sources' (array) or 'source_key'"),
+    };
+
+    let entries = entries
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("pdf_merge: sources must be an array of PDF paths"))?;
+    if entries.is_empty() {
+        anyhow::bail!("pdf_merge: sources must not be empty");
+    }
+    entries
+        .iter()
+        .map(|entry| {
+            let path = entry
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("pdf_merge: each source must be a string path"))?;
+            Ok(if from_ctx {
+                path.to_string()
+            } else {
+                interpolate_ctx(path, ctx)
+            })
+        })
+        .collect()
+}
+
+/// Page object ids of `doc` in page order.
+fn ordered_page_ids(doc: &Document) -> Vec<lopdf::ObjectId> {
+    doc.get_pages().into_values().collect()
+}
+
 #[async_trait]
 impl Node for PdfMergeNode {
     fn node_type(&self) -> &str {
@@ -94,14 +303,7 @@ impl Node for PdfMergeNode {
     }
 
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
-        let files = config
-            .get("files")
-            .and_then(|v| v.as_array())
-            .ok_or_else(|| anyhow::anyhow!("pdf_merge requires 'files' parameter (array)"))?;
-
-        if files.is_empty() {
-            anyhow::bail!("pdf_merge: 'files' array must not be empty");
-        }
+        let sources = resolve_merge_sources(config, ctx)?;
 
         let output_path = config
             .get("output_path")
@@ -114,82 +316,26 @@ impl Node for PdfMergeNode {
             .and_then(|v| v.as_str())
             .unwrap_or("pdf_merge");
 
-        let mut documents = Vec::new();
-        for file_val in files {
-            let path = file_val.as_str().ok_or_else(|| {
-                anyhow::anyhow!("pdf_merge: each entry in 'files' must be a string")
-            })?;
-            let path = interpolate_ctx(path, ctx);
-            let doc = Document::load(&path)
-                .map_err(|e| anyhow::anyhow!("pdf_merge: failed to load '{}': {:?}", path, e))?;
-            documents.push(doc);
-        }
-
         let mut merged = Document::new();
-        let mut merged_pages_ids = Vec::new();
         let pages_id = merged.new_object_id();
-        let mut total_pages: usize = 0;
-
-        for source_doc in &documents {
-            let source_pages = source_doc.get_pages();
-            let mut sorted_nums: Vec<u32> = source_pages.keys().copied().collect();
-            sorted_nums.sort();
-            for page_num in sorted_nums {
-                let page_obj_id = source_pages[&page_num];
-                let mut object_map = BTreeMap::new();
-                collect_objects_recursive(source_doc, page_obj_id, &mut object_map);
-
-                let mut id_remap = BTreeMap::new();
-                for (&old_id, obj) in &object_map {
-                    let new_id = merged.add_object(obj.clone());
-                    id_remap.insert(old_id, new_id);
-                }
-
-                for new_id in id_remap.values() {
-                    if let Ok(obj) = merged.get_object_mut(*new_id) {
-                        remap_references(obj, &id_remap);
-                    }
-                }
-
-                let new_page_id = id_remap[&page_obj_id];
-                if let Ok(Object::Dictionary(dict)) = merged.get_object_mut(new_page_id) {
-                    dict.set("Parent", pages_id);
-                }
-
-                merged_pages_ids.push(new_page_id);
-                total_pages += 1;
-            }
-        }
+        let mut kids = Vec::new();
 
-        let kids: Vec<Object> = merged_pages_ids
-            .iter()
-            .map(|id| Object::Reference(*id))
-            .collect();
-        merged.objects.insert(
-            pages_id,
-            Object::Dictionary(dictionary! {
-                "Type" => "Pages",
-                "Kids" => kids,
-                "Count" => total_pages as u32,
-            }),
-        );
-
-        let catalog_id = merged.add_object(dictionary! {
-            "Type" => "Catalog",
-            "Pages" => pages_id,
-        });
-        merged.trailer.set("Root", catalog_id);
-        merged.max_id = merged.objects.keys().map(|id| id.0).max().unwrap_or(0);
-
-        if let Some(parent) = std::path::Path::new(&output_path).parent() {
-            std::fs::create_dir_all(parent).map_err(|e| {
-                anyhow::anyhow!("pdf_merge: failed to create output directory: {}", e)
-            })?;
+        for path in &sources {
+            let source_doc = Document::load(path)
+                .map_err(|e| anyhow::anyhow!("pdf_merge: failed to load '{}': {:?}", path, e))?;
+            let page_ids = ordered_page_ids(&source_doc);
+            if page_ids.is_empty() {
+                anyhow::bail!("pdf_merge: '{}' has no pages", path);
+            }
+            kids.extend(
+                copy_pages(&mut merged, &source_doc, &page_ids, pages_id)
+                    .map_err(|e| anyhow::anyhow!("pdf_merge: '{}': {}", path, e))?,
+            );
         }
 
-        merged
-            .save(&output_path)
-            .map_err(|e| anyhow::anyhow!("pdf_merge: failed to save merged PDF: {:?}", e))?;
+        let total_pages = kids.len();
+        finish_document(merged, pages_id, kids, &output_path)
+            .map_err(|e| anyhow::anyhow!("pdf_merge: {}", e))?;
 
         let mut output = NodeOutput::new();
         output.insert(
@@ -220,11 +366,14 @@ impl Node for PdfSplitNode {
 
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let path = super::common::resolve_path(config, ctx, "pdf_split")?;
-        let output_dir = config
-            .get("output_dir")
+        let output_path = config
+            .get("output_path")
             .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("pdf_split requires 'output_dir' parameter"))?;
-        let output_dir = interpolate_ctx(output_dir, ctx);
+            .map(|p| interpolate_ctx(p, ctx));
+        let split_each = config
+            .get("split_each")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(output_path.is_none());
         let output_key = config
             .get("output_key")
             .and_then(|v| v.as_str())
@@ -233,82 +382,75 @@ impl Node for PdfSplitNode {
         let source_doc = Document::load(&path)
             .map_err(|e| anyhow::anyhow!("pdf_split: failed to load '{}': {:?}", path, e))?;
 
-        let source_pages = source_doc.get_pages();
-        let page_count = source_pages.len();
-
+        let page_ids = ordered_page_ids(&source_doc);
         let pages_spec = config
             .get("pages")
             .and_then(|v| v.as_str())
             .unwrap_or("all");
-        let page_indices = parse_pages_spec(pages_spec, page_count)?;
-
-        std::fs::create_dir_all(&output_dir)
-            .map_err(|e| anyhow::anyhow!("pdf_split: failed to create output dir: {}", e))?;
-
-        let stem = std::path::Path::new(&path)
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("page");
+        let page_indices = parse_pages_spec(pages_spec, page_ids.len())?;
 
+        let mut output = NodeOutput::new();
         let mut output_files = Vec::new();
 
-        let mut sorted_page_nums: Vec<u32> = source_pages.keys().copied().collect();
-        sorted_page_nums.sort();
-
-        for &page_idx in &page_indices {
-            let page_num = sorted_page_nums.get(page_idx).ok_or_else(|| {
-                anyhow::anyhow!("pdf_split: page index {} out of range", page_idx)
-            })?;
-            let page_obj_id = source_pages[page_num];
-
-            let mut single = Document::new();
-            let pages_id = single.new_object_id();
-
-            let mut object_map = BTreeMap::new();
-            collect_objects_recursive(&source_doc, page_obj_id, &mut object_map);
-
-            let mut id_remap = BTreeMap::new();
-            for (&old_id, obj) in &object_map {
-                let new_id = single.add_object(obj.clone());
-                id_remap.insert(old_id, new_id);
-            }
-
-            for new_id in id_remap.values() {
-                if let Ok(obj) = single.get_object_mut(*new_id) {
-                    remap_references(obj, &id_remap);
-                }
+        if split_each {
+            let output_dir = config
+                .get("output_dir")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "pdf_split requires 'output_dir' parameter when split_each is true"
+                    )
+                })?;
+            let output_dir = interpolate_ctx(output_dir, ctx);
+            std::fs::create_dir_all(&output_dir)
+                .map_err(|e| anyhow::anyhow!("pdf_split: failed to create output dir: {}", e))?;
+
+            let stem = std::path::Path::new(&path)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("page");
+
+            for &page_idx in &page_indices {
+                let page_obj_id = *page_ids.get(page_idx).ok_or_else(|| {
+                    anyhow::anyhow!("pdf_split: page index {} out of range", page_idx)
+                })?;
+
+                let mut single = Document::new();
+                let pages_id = single.new_object_id();
+                let kids = copy_pages(&mut single, &source_doc, &[page_obj_id], pages_id)
+                    .map_err(|e| anyhow::anyhow!("pdf_split: {}", e))?;
+                copy_info(&mut single, &source_doc);
+
+                let out_path = format!("{}/{}_{}.pdf", output_dir, stem, page_idx + 1);
+                finish_document(single, pages_id, kids, &out_path).map_err(|e| {
+                    anyhow::anyhow!("pdf_split: failed to save page {}: {}", page_idx + 1, e)
+                })?;
+
+                output_files.push(serde_json::Value::String(out_path));
             }
-
-            let new_page_id = id_remap[&page_obj_id];
-            if let Ok(Object::Dictionary(dict)) = single.get_object_mut(new_page_id) {
-                dict.set("Parent", pages_id);
-            }
-
-            single.objects.insert(
-                pages_id,
-                Object::Dictionary(dictionary! {
-                    "Type" => "Pages",
-                    "Kids" => vec![Object::Reference(new_page_id)],
-                    "Count" => 1_u32,
-                }),
-            );
-
-            let catalog_id = single.add_object(dictionary! {
-                "Type" => "Catalog",
-                "Pages" => pages_id,
-            });
-            single.trailer.set("Root", catalog_id);
-            single.max_id = single.objects.keys().map(|id| id.0).max().unwrap_or(0);
-
-            let out_path = format!("{}/{}_{}.pdf", output_dir, stem, page_idx + 1);
-            single.save(&out_path).map_err(|e| {
-                anyhow::anyhow!("pdf_split: failed to save page {}: {:?}", page_idx + 1, e)
+        } else {
+            let out_path = output_path.ok_or_else(|| {
+                anyhow::anyhow!(
+                    "pdf_split requires 'output_path' parameter when split_each is false"
+                )
             })?;
-
+            let selected: Vec<_> = page_indices.iter().map(|&i| page_ids[i]).collect();
+
+            let mut extracted = Document::new();
+            let pages_id = extracted.new_object_id();
+            let kids = copy_pages(&mut extracted, &source_doc, &selected, pages_id)
+                .map_err(|e| anyhow::anyhow!("pdf_split: {}", e))?;
+            copy_info(&mut extracted, &source_doc);
+            finish_document(extracted, pages_id, kids, &out_path)
+                .map_err(|e| anyhow::anyhow!("pdf_split: {}", e))?;
+
+            output.insert(
+                format!("{}_path", output_key),
+                serde_json::Value::String(out_path.clone()),
+            );
             output_files.push(serde_json::Value::String(out_path));
         }
 
-        let mut output = NodeOutput::new();
         output.insert(
             format!("{}_files", output_key),
             serde_json::Value::Array(output_files),
@@ -30,10 +30,11 @@ pub(crate) struct PdfRenderRequest {
     pub(crate) dpi: f32,
 }
 
-pub(crate) fn render_pdf_page(
+/// Render one page to an in-memory image sized by the request's hints.
+pub(crate) fn render_pdf_page_image(
     document: &pdfium_render::prelude::PdfDocument,
-    request: PdfRenderRequest,
-) -> Result<RenderedImage> {
+    request: &PdfRenderRequest,
+) -> Result<image::DynamicImage> {
     if request.page_idx >= request.page_count {
         anyhow::bail!(
             "page {} exceeds document page count ({})",
@@ -98,13 +99,21 @@ pub(crate) fn render_pdf_page(
         .render_with_config(&render_config)
         .map_err(|e| anyhow::anyhow!("Failed to render page {}: {:?}", request.page_idx + 1, e))?;
 
-    let img = bitmap.as_image().map_err(|e| {
+    bitmap.as_image().map_err(|e| {
         anyhow::anyhow!(
             "Failed to convert page {} to image: {:?}",
             request.page_idx + 1,
             e
         )
-    })?;
+    })
+}
+
+pub(crate) fn render_pdf_page(
+    document: &pdfium_render::prelude::PdfDocument,
+    request: PdfRenderRequest,
+) -> Result<RenderedImage> {
+    let img = render_pdf_page_image(document, &request)?;
+    let (target_width, target_height) = (img.width(), img.height());
 
     let mut buf: Vec<u8> = Vec::new();
     let mut cursor = std::io::Cursor::new(&mut buf);
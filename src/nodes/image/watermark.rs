@@ -0,0 +1,611 @@
+//! `watermark`: stamp text or an image onto an image, or onto every page of
+//! a PDF.
+//!
+//! Text is drawn with a built-in 5x7 pixel font, so no font files are
+//! needed. The text or image is first turned into an RGBA "stamp"; images
+//! get the stamp blended in pixel by pixel, PDF pages get it as an image
+//! XObject drawn through an opacity graphics state. A stamp on a PDF is not
+//! text, so it never shows up in extracted text.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use lopdf::{Dictionary, Document, Object, Stream, dictionary};
+
+use crate::engine::types::{Context, NodeOutput};
+use crate::lua::interpolate::interpolate_ctx;
+use crate::nodes::Node;
+
+use super::common::{
+    is_pdf_file, load_image_bytes, read_pdf_bytes_capped, resolve_image_output_format,
+    save_dynamic_image,
+};
+use super::image_sources::{ImageInput, resolve_single_image_source};
+use super::pdf_merge_split::inherited_page_attribute;
+
+pub(crate) struct WatermarkNode;
+
+/// 5x7 glyphs for ASCII 0x20..=0x7E, one byte per column, bit 0 = top row.
+const GLYPHS: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5F, 0x00, 0x00], // !
+    [0x00, 0x07, 0x00, 0x07, 0x00], // "
+    [0x14, 0x7F, 0x14, 0x7F, 0x14], // #
+    [0x24, 0x2A, 0x7F, 0x2A, 0x12], // $
+    [0x23, 0x13, 0x08, 0x64, 0x62], // %
+    [0x36, 0x49, 0x55, 0x22, 0x50], // &
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '
+    [0x00, 0x1C, 0x22, 0x41, 0x00], // (
+    [0x00, 0x41, 0x22, 0x1C, 0x00], // )
+    [0x14, 0x08, 0x3E, 0x08, 0x14], // *
+    [0x08, 0x08, 0x3E, 0x08, 0x08], // +
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ,
+    [0x08, 0x08, 0x08, 0x08, 0x08], // -
+    [0x00, 0x60, 0x60, 0x00, 0x00], // .
+    [0x20, 0x10, 0x08, 0x04, 0x02], // /
+    [0x3E, 0x51, 0x49, 0x45, 0x3E], // 0
+    [0x00, 0x42, 0x7F, 0x40, 0x00], // 1
+    [0x42, 0x61, 0x51, 0x49, 0x46], // 2
+    [0x21, 0x41, 0x45, 0x4B, 0x31], // 3
+    [0x18, 0x14, 0x12, 0x7F, 0x10], // 4
+    [0x27, 0x45, 0x45, 0x45, 0x39], // 5
+    [0x3C, 0x4A, 0x49, 0x49, 0x30], // 6
+    [0x01, 0x71, 0x09, 0x05, 0x03], // 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], // 8
+    [0x06, 0x49, 0x49, 0x29, 0x1E], // 9
+    [0x00, 0x36, 0x36, 0x00, 0x00], // :
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ;
+    [0x08, 0x14, 0x22, 0x41, 0x00], // <
+    [0x14, 0x14, 0x14, 0x14, 0x14], // =
+    [0x00, 0x41, 0x22, 0x14, 0x08], // >
+    [0x02, 0x01, 0x51, 0x09, 0x06], // ?
+    [0x32, 0x49, 0x79, 0x41, 0x3E], // @
+    [0x7E, 0x11, 0x11, 0x11, 0x7E], // A
+    [0x7F, 0x49, 0x49, 0x49, 0x36], // B
+    [0x3E, 0x41, 0x41, 0x41, 0x22], // C
+    [0x7F, 0x41, 0x41, 0x22, 0x1C], // D
+    [0x7F, 0x49, 0x49, 0x49, 0x41], // E
+    [0x7F, 0x09, 0x09, 0x09, 0x01], // F
+    [0x3E, 0x41, 0x49, 0x49, 0x7A], // G
+    [0x7F, 0x08, 0x08, 0x08, 0x7F], // H
+    [0x00, 0x41, 0x7F, 0x41, 0x00], // I
+    [0x20, 0x40, 0x41, 0x3F, 0x01], // J
+    [0x7F, 0x08, 0x14, 0x22, 0x41], // K
+    [0x7F, 0x40, 0x40, 0x40, 0x40], // L
+    [0x7F, 0x02, 0x0C, 0x02, 0x7F], // M
+    [0x7F, 0x04, 0x08, 0x10, 0x7F], // N
+    [0x3E, 0x41, 0x41, 0x41, 0x3E], // O
+    [0x7F, 0x09, 0x09, 0x09, 0x06], // P
+    [0x3E, 0x41, 0x51, 0x21, 0x5E], // Q
+    [0x7F, 0x09, 0x19, 0x29, 0x46], // R
+    [0x46, 0x49, 0x49, 0x49, 0x31], // S
+    [0x01, 0x01, 0x7F, 0x01, 0x01], // T
+    [0x3F, 0x40, 0x40, 0x40, 0x3F], // U
+    [0x1F, 0x20, 0x40, 0x20, 0x1F], // V
+    [0x3F, 0x40, 0x38, 0x40, 0x3F], // W
+    [0x63, 0x14, 0x08, 0x14, 0x63], // X
+    [0x07, 0x08, 0x70, 0x08, 0x07], // Y
+    [0x61, 0x51, 0x49, 0x45, 0x43], // Z
+    [0x00, 0x7F, 0x41, 0x41, 0x00], // [
+    [0x02, 0x04, 0x08, 0x10, 0x20], // backslash
+    [0x00, 0x41, 0x41, 0x7F, 0x00], // ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], // ^
+    [0x40, 0x40, 0x40, 0x40, 0x40], // _
+    [0x00, 0x01, 0x02, 0x04, 0x00], // `
+    [0x20, 0x54, 0x54, 0x54, 0x78], // a
+    [0x7F, 0x48, 0x44, 0x44, 0x38], // b
+    [0x38, 0x44, 0x44, 0x44, 0x20], // c
+    [0x38, 0x44, 0x44, 0x48, 0x7F], // d
+    [0x38, 0x54, 0x54, 0x54, 0x18], // e
+    [0x08, 0x7E, 0x09, 0x01, 0x02], // f
+    [0x0C, 0x52, 0x52, 0x52, 0x3E], // g
+    [0x7F, 0x08, 0x04, 0x04, 0x78], // h
+    [0x00, 0x44, 0x7D, 0x40, 0x00], // i
+    [0x20, 0x40, 0x44, 0x3D, 0x00], // j
+    [0x7F, 0x10, 0x28, 0x44, 0x00], // k
+    [0x00, 0x41, 0x7F, 0x40, 0x00], // l
+    [0x7C, 0x04, 0x18, 0x04, 0x78], // m
+    [0x7C, 0x08, 0x04, 0x04, 0x78], // n
+    [0x38, 0x44, 0x44, 0x44, 0x38], // o
+    [0x7C, 0x14, 0x14, 0x14, 0x08], // p
+    [0x08, 0x14, 0x14, 0x18, 0x7C], // q
+    [0x7C, 0x08, 0x04, 0x04, 0x08], // r
+    [0x48, 0x54, 0x54, 0x54, 0x20], // s
+    [0x04, 0x3F, 0x44, 0x40, 0x20], // t
+    [0x3C, 0x40, 0x40, 0x20, 0x7C], // u
+    [0x1C, 0x20, 0x40, 0x20, 0x1C], // v
+    [0x3C, 0x40, 0x30, 0x40, 0x3C], // w
+    [0x44, 0x28, 0x10, 0x28, 0x44], // x
+    [0x0C, 0x50, 0x50, 0x50, 0x3C], // y
+    [0x44, 0x64, 0x54, 0x4C, 0x44], // z
+    [0x00, 0x08, 0x36, 0x41, 0x00], // {
+    [0x00, 0x00, 0x7F, 0x00, 0x00], // |
+    [0x00, 0x41, 0x36, 0x08, 0x00], // }
+    [0x10, 0x08, 0x08, 0x10, 0x08], // ~
+];
+
+/// A glyph cell is 5x7 font pixels plus one column and one row of spacing.
+const CELL_W: u32 = 6;
+const CELL_H: u32 = 8;
+
+fn glyph(c: char) -> &'static [u8; 5] {
+    let index = if (' '..='~').contains(&c) { c } else { '?' } as usize - 0x20;
+    &GLYPHS[index]
+}
+
+/// Render `text` (one line per `\n`) in `color`, `scale` image pixels per
+/// font pixel, on a transparent background.
+fn render_text(text: &str, color: [u8; 3], scale: u32) -> image::RgbaImage {
+    let lines: Vec<&str> = text.lines().collect();
+    let columns = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as u32;
+    // Drop the trailing spacing column and row.
+    let width = (columns * CELL_W).saturating_sub(1).max(1) * scale;
+    let height = (lines.len() as u32 * CELL_H).saturating_sub(1).max(1) * scale;
+
+    let mut stamp = image::RgbaImage::new(width, height);
+    let ink = image::Rgba([color[0], color[1], color[2], 255]);
+    for (row, line) in lines.iter().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            for (dx, bits) in glyph(c).iter().enumerate() {
+                for dy in 0..7 {
+                    if bits & (1 << dy) == 0 {
+                        continue;
+                    }
+                    let x0 = (col as u32 * CELL_W + dx as u32) * scale;
+                    let y0 = (row as u32 * CELL_H + dy) * scale;
+                    for y in y0..y0 + scale {
+                        for x in x0..x0 + scale {
+                            stamp.put_pixel(x, y, ink);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    stamp
+}
+
+/// Parse `#RRGGBB` (the `#` is optional).
+fn parse_color(value: &str) -> Result<[u8; 3]> {
+    let hex = value.trim_start_matches('#');
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+    };
+    match (hex.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(r), Some(g), Some(b)) => Ok([r, g, b]),
+        _ => anyhow::bail!("watermark: 'color' must be '#RRGGBB', got '{}'", value),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Position {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+impl Position {
+    fn parse(value: &str) -> Result<Self> {
+        Ok(match value {
+            "top-left" => Self::TopLeft,
+            "top-right" => Self::TopRight,
+            "bottom-left" => Self::BottomLeft,
+            "bottom-right" => Self::BottomRight,
+            "center" => Self::Center,
+            other => anyhow::bail!(
+                "watermark: unsupported position '{}'. Use top-left, top-right, bottom-left, bottom-right or center",
+                other
+            ),
+        })
+    }
+
+    /// Center of a `box_w` x `box_h` box placed on a `width` x `height`
+    /// canvas, in top-left-origin coordinates.
+    fn box_center(self, width: f64, height: f64, box_w: f64, box_h: f64) -> (f64, f64) {
+        let margin = width.min(height) * 0.03;
+        let left = margin + box_w / 2.0;
+        let right = width - margin - box_w / 2.0;
+        let top = margin + box_h / 2.0;
+        let bottom = height - margin - box_h / 2.0;
+        match self {
+            Self::TopLeft => (left, top),
+            Self::TopRight => (right, top),
+            Self::BottomLeft => (left, bottom),
+            Self::BottomRight => (right, bottom),
+            Self::Center => (width / 2.0, height / 2.0),
+        }
+    }
+}
+
+/// Size of the bounding box of a `w` x `h` rectangle rotated by `radians`.
+fn rotated_size(w: f64, h: f64, radians: f64) -> (f64, f64) {
+    let (sin, cos) = (radians.sin().abs(), radians.cos().abs());
+    (w * cos + h * sin, w * sin + h * cos)
+}
+
+/// Blend `stamp` onto `target` rotated counter-clockwise by `radians` around
+/// its center, at `opacity`.
+fn composite(
+    target: &mut image::RgbaImage,
+    stamp: &image::RgbaImage,
+    position: Position,
+    radians: f64,
+    opacity: f64,
+) {
+    let (sw, sh) = (stamp.width() as f64, stamp.height() as f64);
+    let (bw, bh) = rotated_size(sw, sh, radians);
+    let (cx, cy) = position.box_center(target.width() as f64, target.height() as f64, bw, bh);
+    let (sin, cos) = radians.sin_cos();
+
+    let x_range = (cx - bw / 2.0).floor().max(0.0) as u32
+        ..((cx + bw / 2.0).ceil().max(0.0) as u32).min(target.width());
+    let y_range = (cy - bh / 2.0).floor().max(0.0) as u32
+        ..((cy + bh / 2.0).ceil().max(0.0) as u32).min(target.height());
+    for y in y_range {
+        for x in x_range.clone() {
+            // Map the target pixel back into the unrotated stamp; y points
+            // down, so a counter-clockwise turn flips the sign of `sin`.
+            let dx = x as f64 + 0.5 - cx;
+            let dy = y as f64 + 0.5 - cy;
+            let sx = dx * cos - dy * sin + sw / 2.0;
+            let sy = dx * sin + dy * cos + sh / 2.0;
+            if sx < 0.0 || sy < 0.0 || sx >= sw || sy >= sh {
+                continue;
+            }
+            let src = stamp.get_pixel(sx as u32, sy as u32);
+            let alpha = src[3] as f64 / 255.0 * opacity;
+            if alpha <= 0.0 {
+                continue;
+            }
+            let dst = target.get_pixel_mut(x, y);
+            for c in 0..3 {
+                dst[c] = (src[c] as f64 * alpha + dst[c] as f64 * (1.0 - alpha)).round() as u8;
+            }
+        }
+    }
+}
+
+/// What gets stamped: text in the built-in font, or an image.
+enum StampSource {
+    Text { text: String, color: [u8; 3] },
+    Image(image::RgbaImage),
+}
+
+impl StampSource {
+    /// The stamp for a canvas whose shorter side is `short_side` units, and
+    /// its drawn size in those units. Text is `font_size` units per line;
+    /// images are `width` units wide, defaulting to their pixel size.
+    fn stamp(
+        &self,
+        font_size: Option<f64>,
+        width: Option<f64>,
+        short_side: f64,
+        pixel_units: bool,
+    ) -> (image::RgbaImage, f64, f64) {
+        match self {
+            Self::Text { text, color } => {
+                let line = font_size.unwrap_or(short_side / 10.0).max(1.0);
+                // On images the glyphs are scaled up in pixels; on PDFs the
+                // stamp stays at one pixel per font pixel and is scaled by
+                // the page, without smoothing.
+                let scale = if pixel_units {
+                    ((line / CELL_H as f64).round() as u32).max(1)
+                } else {
+                    1
+                };
+                let stamp = render_text(text, *color, scale);
+                let lines = text.lines().count().max(1) as f64;
+                let height = line * lines;
+                let width = height * stamp.width() as f64 / stamp.height() as f64;
+                if pixel_units {
+                    let (w, h) = (stamp.width() as f64, stamp.height() as f64);
+                    (stamp, w, h)
+                } else {
+                    (stamp, width, height)
+                }
+            }
+            Self::Image(overlay) => {
+                let (ow, oh) = (overlay.width() as f64, overlay.height() as f64);
+                let (w, h) = match width {
+                    Some(w) => (w, oh * w / ow),
+                    None => (ow, oh),
+                };
+                if pixel_units && (w, h) != (ow, oh) {
+                    let resized = image::imageops::resize(
+                        overlay,
+                        w.round().max(1.0) as u32,
+                        h.round().max(1.0) as u32,
+                        image::imageops::FilterType::Lanczos3,
+                    );
+                    (resized, w, h)
+                } else {
+                    (overlay.clone(), w, h)
+                }
+            }
+        }
+    }
+}
+
+/// Options shared by image and PDF targets.
+struct WatermarkOptions {
+    source: StampSource,
+    position: Position,
+    radians: f64,
+    opacity: f64,
+    font_size: Option<f64>,
+    width: Option<f64>,
+}
+
+/// A page attribute set on the page itself or inherited from the page tree.
+fn page_attribute<'a>(
+    doc: &'a Document,
+    page_id: lopdf::ObjectId,
+    key: &[u8],
+) -> Option<&'a Object> {
+    doc.get_dictionary(page_id)
+        .ok()
+        .and_then(|page| page.get(key).ok())
+        .or_else(|| inherited_page_attribute(doc, page_id, key))
+}
+
+/// A dictionary value, following one level of indirection.
+fn resolve_dict(doc: &Document, value: Option<&Object>) -> Dictionary {
+    match value {
+        Some(Object::Dictionary(dict)) => dict.clone(),
+        Some(Object::Reference(id)) => doc.get_dictionary(*id).cloned().unwrap_or_default(),
+        _ => Dictionary::new(),
+    }
+}
+
+fn watermark_pdf(doc: &mut Document, options: &WatermarkOptions) -> Result<usize> {
+    let pages: Vec<lopdf::ObjectId> = doc.get_pages().into_values().collect();
+    let mut embedded: Option<lopdf::ObjectId> = None;
+    let gs_id = doc.add_object(dictionary! {
+        "Type" => "ExtGState",
+        "ca" => options.opacity as f32,
+        "CA" => options.opacity as f32,
+    });
+
+    for &page_id in &pages {
+        let media_box: Vec<f64> = page_attribute(doc, page_id, b"MediaBox")
+            .and_then(|v| v.as_array().ok())
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|v| v.as_float().ok().map(f64::from))
+                    .collect()
+            })
+            .filter(|values: &Vec<f64>| values.len() == 4)
+            .unwrap_or_else(|| vec![0.0, 0.0, 612.0, 792.0]);
+        let (x0, y0) = (media_box[0], media_box[1]);
+        let (page_w, page_h) = (media_box[2] - x0, media_box[3] - y0);
+
+        let (stamp, draw_w, draw_h) =
+            options
+                .source
+                .stamp(options.font_size, options.width, page_w.min(page_h), false);
+        // The stamp bitmap is the same on every page; only its drawn size
+        // follows the page, so embed it once.
+        let stamp_id = match embedded {
+            Some(id) => id,
+            None => {
+                let interpolate = matches!(options.source, StampSource::Image(_));
+                *embedded.insert(add_stamp_xobject(doc, &stamp, interpolate)?)
+            }
+        };
+
+        let (bw, bh) = rotated_size(draw_w, draw_h, options.radians);
+        let (cx, cy) = options.position.box_center(page_w, page_h, bw, bh);
+        // PDF space has its origin at the bottom-left of the media box.
+        let (cx, cy) = (x0 + cx, y0 + (page_h - cy));
+        let (sin, cos) = options.radians.sin_cos();
+        let e = cx - (draw_w / 2.0 * cos - draw_h / 2.0 * sin);
+        let f = cy - (draw_w / 2.0 * sin + draw_h / 2.0 * cos);
+        let content = format!(
+            "Q q /IronflowWatermarkGS gs {:.4} {:.4} {:.4} {:.4} {:.4} {:.4} cm /IronflowWatermark Do Q",
+            draw_w * cos,
+            draw_w * sin,
+            -draw_h * sin,
+            draw_h * cos,
+            e,
+            f
+        );
+
+        // Wrap the existing content in q/Q so its graphics state cannot
+        // leak into the stamp, then draw the stamp last, on top.
+        let open_id = doc.add_object(Stream::new(dictionary! {}, b"q".to_vec()));
+        let stamp_content_id = doc.add_object(Stream::new(dictionary! {}, content.into_bytes()));
+
+        let mut resources = resolve_dict(doc, page_attribute(doc, page_id, b"Resources"));
+        let mut xobjects = resolve_dict(doc, resources.get(b"XObject").ok());
+        xobjects.set("IronflowWatermark", stamp_id);
+        resources.set("XObject", xobjects);
+        let mut states = resolve_dict(doc, resources.get(b"ExtGState").ok());
+        states.set("IronflowWatermarkGS", gs_id);
+        resources.set("ExtGState", states);
+
+        let page = doc.get_dictionary_mut(page_id)?;
+        let mut contents = vec![Object::Reference(open_id)];
+        match page.get(b"Contents") {
+            Ok(Object::Array(existing)) => contents.extend(existing.iter().cloned()),
+            Ok(existing) => contents.push(existing.clone()),
+            Err(_) => {}
+        }
+        contents.push(Object::Reference(stamp_content_id));
+        page.set("Contents", contents);
+        page.set("Resources", resources);
+    }
+    Ok(pages.len())
+}
+
+/// Add `stamp` as an RGB image XObject with its alpha channel as soft mask.
+fn add_stamp_xobject(
+    doc: &mut Document,
+    stamp: &image::RgbaImage,
+    interpolate: bool,
+) -> Result<lopdf::ObjectId> {
+    let (width, height) = (stamp.width() as i64, stamp.height() as i64);
+    let mut rgb = Vec::with_capacity(stamp.len() / 4 * 3);
+    let mut alpha = Vec::with_capacity(stamp.len() / 4);
+    for pixel in stamp.pixels() {
+        rgb.extend_from_slice(&pixel.0[..3]);
+        alpha.push(pixel[3]);
+    }
+
+    let mut mask = Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => width,
+            "Height" => height,
+            "ColorSpace" => "DeviceGray",
+            "BitsPerComponent" => 8,
+            "Interpolate" => interpolate,
+        },
+        alpha,
+    );
+    mask.compress()?;
+    let mask_id = doc.add_object(mask);
+
+    let mut image = Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => width,
+            "Height" => height,
+            "ColorSpace" => "DeviceRGB",
+            "BitsPerComponent" => 8,
+            "Interpolate" => interpolate,
+            "SMask" => mask_id,
+        },
+        rgb,
+    );
+    image.compress()?;
+    Ok(doc.add_object(image))
+}
+
+#[async_trait]
+impl Node for WatermarkNode {
+    fn node_type(&self) -> &str {
+        "watermark"
+    }
+
+    fn description(&self) -> &str {
+        "Stamp text or an image onto an image or every page of a PDF"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let input = resolve_single_image_source(config, ctx, "watermark")?;
+        let output_path = config
+            .get("output_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("watermark requires 'output_path' parameter"))?;
+        let output_path = interpolate_ctx(output_path, ctx);
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .unwrap_or("watermark");
+
+        let text = config.get("text").and_then(|v| v.as_str());
+        let image_path = config.get("image_path").and_then(|v| v.as_str());
+        let source = match (text, image_path) {
+            (Some(_), Some(_)) => {
+                anyhow::bail!("watermark accepts either 'text' or 'image_path', not both")
+            }
+            (Some(text), None) => StampSource::Text {
+                text: interpolate_ctx(text, ctx),
+                color: parse_color(
+                    config
+                        .get("color")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("#808080"),
+                )?,
+            },
+            (None, Some(path)) => StampSource::Image(
+                load_image_bytes(ImageInput::Path(interpolate_ctx(path, ctx)))?
+                    .image
+                    .to_rgba8(),
+            ),
+            (None, None) => anyhow::bail!("watermark requires 'text' or 'image_path'"),
+        };
+        let options = WatermarkOptions {
+            source,
+            position: Position::parse(
+                config
+                    .get("position")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("center"),
+            )?,
+            radians: config
+                .get("rotation")
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.0)
+                .to_radians(),
+            opacity: config
+                .get("opacity")
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.5)
+                .clamp(0.0, 1.0),
+            font_size: config
+                .get("font_size")
+                .and_then(|v| v.as_f64())
+                .filter(|v| *v > 0.0),
+            width: config
+                .get("width")
+                .and_then(|v| v.as_f64())
+                .filter(|v| *v > 0.0),
+        };
+
+        let mut output = NodeOutput::new();
+        match input {
+            ImageInput::Path(path) if is_pdf_file(&path) => {
+                let bytes = read_pdf_bytes_capped(&path, "watermark")?;
+                let mut doc = Document::load_mem(&bytes).map_err(|e| {
+                    anyhow::anyhow!("watermark: failed to load PDF '{}': {:?}", path, e)
+                })?;
+                let pages = watermark_pdf(&mut doc, &options)?;
+                doc.save(&output_path).map_err(|e| {
+                    anyhow::anyhow!("watermark: failed to save '{}': {:?}", output_path, e)
+                })?;
+                output.insert(format!("{}_pages", output_key), serde_json::json!(pages));
+            }
+            input => {
+                let format = resolve_image_output_format(
+                    config.get("format").and_then(|v| v.as_str()),
+                    &output_path,
+                    "watermark",
+                )?;
+                let mut target = load_image_bytes(input)?.image.to_rgba8();
+                let short_side = target.width().min(target.height()) as f64;
+                let (stamp, _, _) =
+                    options
+                        .source
+                        .stamp(options.font_size, options.width, short_side, true);
+                composite(
+                    &mut target,
+                    &stamp,
+                    options.position,
+                    options.radians,
+                    options.opacity,
+                );
+                save_dynamic_image(
+                    image::DynamicImage::ImageRgba8(target),
+                    &output_path,
+                    format,
+                )?;
+            }
+        }
+
+        output.insert(
+            format!("{}_path", output_key),
+            serde_json::Value::String(output_path),
+        );
+        output.insert(
+            format!("{}_success", output_key),
+            serde_json::Value::Bool(true),
+        );
+        Ok(output)
+    }
+}
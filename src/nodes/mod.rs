@@ -8,6 +8,7 @@ pub mod http;
 pub mod image;
 pub mod mcp;
 pub mod notify;
+pub mod plugin;
 pub mod s3vector;
 pub mod transform;
 pub mod utility;
@@ -21,6 +22,11 @@ use async_trait::async_trait;
 use crate::engine::types::{Context, NodeOutput};
 
 /// Trait that all nodes must implement.
+///
+/// This is the stable extension point for embedders: a downstream crate can
+/// implement `Node` and register it through [`NodeRegistry::with_builtins_and`]
+/// (or [`crate::cli::run_cli_with`] / [`crate::api::serve_with_registry`]) to
+/// make it callable from Lua flows as `nodes.<node_type>(...)`.
 #[async_trait]
 pub trait Node: Send + Sync {
     /// Node type identifier (e.g., "http_get", "shell_command").
@@ -37,6 +43,42 @@ pub trait Node: Send + Sync {
     /// `Arc<Context>` across parallel attempts instead of deep-cloning the
     /// whole map on every attempt.
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput>;
+
+    /// Context keys this step reads, used by `ironflow validate` to spot
+    /// references nothing upstream produces. Defaults to `source_key`.
+    fn consumed_keys(&self, config: &serde_json::Value) -> Vec<String> {
+        config
+            .get("source_key")
+            .and_then(|v| v.as_str())
+            .map(|key| vec![key.to_string()])
+            .unwrap_or_default()
+    }
+
+    /// Whether the node only computes over its config and the context, with
+    /// no effect outside the run (no network, files, processes or external
+    /// state). `ironflow run --dry-run` marks the others as side-effecting.
+    /// Defaults to `false`, so an unclassified node is never reported as safe.
+    fn is_pure(&self) -> bool {
+        false
+    }
+
+    /// Whether a step with this config is pure. Defaults to [`Node::is_pure`];
+    /// nodes that run another node from their config override it to account
+    /// for that node.
+    fn is_pure_with(&self, _config: &serde_json::Value) -> bool {
+        self.is_pure()
+    }
+
+    /// Context keys this step writes. A produced key also covers its
+    /// `<key>_*` companions (`rows_count`, `rows_success`). `None` means the
+    /// outputs cannot be known from config, which disables the check for
+    /// everything downstream. Defaults to `output_key` when it is set.
+    fn produced_keys(&self, config: &serde_json::Value) -> Option<Vec<String>> {
+        config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .map(|key| vec![key.to_string()])
+    }
 }
 
 /// Registry of available node types.
@@ -59,6 +101,13 @@ impl NodeRegistry {
 
     /// Create a registry with all built-in nodes registered.
     pub fn with_builtins() -> Self {
+        Self::with_builtins_and(|_| {})
+    }
+
+    /// Create a registry with all built-in nodes plus any nodes added by
+    /// `customize`. The callback runs before the composition nodes snapshot
+    /// the registry, so custom nodes are also available inside subworkflows.
+    pub fn with_builtins_and(customize: impl FnOnce(&mut NodeRegistry)) -> Self {
         let mut registry = Self::new();
         utility::register_all(&mut registry);
         ai::register_all(&mut registry);
@@ -73,6 +122,7 @@ impl NodeRegistry {
         mcp::register_all(&mut registry);
         s3vector::register_all(&mut registry);
         transform::register_all(&mut registry);
+        customize(&mut registry);
 
         // Snapshot the base registry (all nodes except subworkflow) and give
         // it to SubworkflowNode. It adds itself back at execution time so
@@ -85,6 +135,15 @@ impl NodeRegistry {
             base_registry: base.clone(),
         }));
         registry.register(Arc::new(composition::ToolDispatchNode {
+            base_registry: base.clone(),
+        }));
+        registry.register(Arc::new(composition::ParallelNode {
+            base_registry: base.clone(),
+        }));
+        registry.register(Arc::new(composition::LoopNode {
+            base_registry: base.clone(),
+        }));
+        registry.register(Arc::new(composition::ForEachNode {
             base_registry: base,
         }));
 
@@ -108,14 +167,15 @@ impl NodeRegistry {
         self.nodes.get(node_type).cloned()
     }
 
-    /// List all registered node types with descriptions.
-    pub fn list(&self) -> Vec<(&str, &str)> {
-        let mut entries: Vec<(&str, &str)> = self
+    /// List all registered node types with descriptions and whether each
+    /// is pure (see [`Node::is_pure`]).
+    pub fn list(&self) -> Vec<(&str, &str, bool)> {
+        let mut entries: Vec<(&str, &str, bool)> = self
             .nodes
             .values()
-            .map(|n| (n.node_type(), n.description()))
+            .map(|n| (n.node_type(), n.description(), n.is_pure()))
             .collect();
-        entries.sort_by_key(|(name, _)| *name);
+        entries.sort_by_key(|(name, _, _)| *name);
         entries
     }
 }
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use lettre::message::{Mailbox, MultiPart, SinglePart, header::ContentType};
+use base64::Engine;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart, header::ContentType};
 use lettre::transport::smtp::authentication::Credentials;
 use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
 use std::time::Duration;
@@ -40,6 +41,17 @@ fn resolve_param(
         .or_else(|| std::env::var(env_key).ok())
 }
 
+/// Like [`resolve_param`], also accepting the older `fallback_env_key`.
+fn resolve_param_with_alias(
+    config: &serde_json::Value,
+    key: &str,
+    env_key: &str,
+    fallback_env_key: &str,
+    ctx: &Context,
+) -> Option<String> {
+    resolve_param(config, key, env_key, ctx).or_else(|| std::env::var(fallback_env_key).ok())
+}
+
 fn resolve_output_key(config: &serde_json::Value) -> String {
     config
         .get("output_key")
@@ -101,10 +113,103 @@ struct EmailParams {
     cc: Option<Vec<String>>,
     bcc: Option<Vec<String>>,
     reply_to: Option<String>,
+    attachments: Vec<EmailAttachment>,
     output_key: String,
     timeout: Duration,
 }
 
+/// The body of an SMTP message before attachments are added.
+enum MessageBody {
+    Single(SinglePart),
+    Multi(MultiPart),
+}
+
+impl From<SinglePart> for MessageBody {
+    fn from(part: SinglePart) -> Self {
+        Self::Single(part)
+    }
+}
+
+impl From<MultiPart> for MessageBody {
+    fn from(part: MultiPart) -> Self {
+        Self::Multi(part)
+    }
+}
+
+struct EmailAttachment {
+    filename: String,
+    content_type: String,
+    bytes: Vec<u8>,
+}
+
+/// Resolve `attachments`: each entry is a file path, `{ path = "...",
+/// filename = "...", content_type = "..." }`, or `{ content = "<base64>",
+/// filename = "...", content_type = "..." }`. String fields are interpolated.
+fn resolve_attachments(config: &serde_json::Value, ctx: &Context) -> Result<Vec<EmailAttachment>> {
+    let Some(value) = config.get("attachments") else {
+        return Ok(Vec::new());
+    };
+    let entries = value
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("send_email 'attachments' must be an array"))?;
+
+    let mut attachments = Vec::with_capacity(entries.len());
+    for (i, entry) in entries.iter().enumerate() {
+        let entry = match interpolate_json_value(entry, ctx) {
+            serde_json::Value::String(path) => serde_json::json!({ "path": path }),
+            other => other,
+        };
+        let field = |name: &str| entry.get(name).and_then(|v| v.as_str());
+
+        let (bytes, default_name) = match (field("path"), field("content")) {
+            (Some(path), None) => {
+                let bytes = std::fs::read(path).map_err(|e| {
+                    anyhow::anyhow!("send_email attachment {}: cannot read '{}': {}", i, path, e)
+                })?;
+                let name = std::path::Path::new(path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned());
+                (bytes, name)
+            }
+            (None, Some(content)) => {
+                let bytes = base64::engine::general_purpose::STANDARD
+                    .decode(content)
+                    .map_err(|e| {
+                        anyhow::anyhow!(
+                            "send_email attachment {}: 'content' is not valid base64: {}",
+                            i,
+                            e
+                        )
+                    })?;
+                (bytes, None)
+            }
+            _ => anyhow::bail!(
+                "send_email attachment {} needs exactly one of 'path' or 'content'",
+                i
+            ),
+        };
+        let filename = field("filename")
+            .map(str::to_string)
+            .or(default_name)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "send_email attachment {} with 'content' needs 'filename'",
+                    i
+                )
+            })?;
+        let content_type = field("content_type")
+            .unwrap_or("application/octet-stream")
+            .to_string();
+
+        attachments.push(EmailAttachment {
+            filename,
+            content_type,
+            bytes,
+        });
+    }
+    Ok(attachments)
+}
+
 fn resolve_string_list(value: &serde_json::Value, ctx: &Context) -> Option<Vec<String>> {
     resolve_recipients(value, ctx)
 }
@@ -147,6 +252,7 @@ fn extract_common_params(config: &serde_json::Value, ctx: &Context) -> Result<Em
         .get("reply_to")
         .and_then(|v| v.as_str())
         .map(|v| interpolate_ctx(v, ctx));
+    let attachments = resolve_attachments(config, ctx)?;
 
     Ok(EmailParams {
         to,
@@ -157,6 +263,7 @@ fn extract_common_params(config: &serde_json::Value, ctx: &Context) -> Result<Em
         cc,
         bcc,
         reply_to,
+        attachments,
         output_key,
         timeout,
     })
@@ -196,6 +303,19 @@ impl SendEmailNode {
         if let Some(reply_to) = config.get("reply_to") {
             payload["reply_to"] = interpolate_json_value(reply_to, ctx);
         }
+        if !params.attachments.is_empty() {
+            payload["attachments"] = params
+                .attachments
+                .iter()
+                .map(|a| {
+                    serde_json::json!({
+                        "filename": a.filename,
+                        "content": base64::engine::general_purpose::STANDARD.encode(&a.bytes),
+                        "content_type": a.content_type,
+                    })
+                })
+                .collect();
+        }
 
         let api_url = config
             .get("api_url")
@@ -243,11 +363,12 @@ impl SendEmailNode {
         let params = extract_common_params(config, ctx)?;
 
         let smtp_server =
-            resolve_param(config, "smtp_server", "SMTP_SERVER", ctx).ok_or_else(|| {
-                anyhow::anyhow!(
-                    "send_email smtp provider requires 'smtp_server' or SMTP_SERVER env var"
-                )
-            })?;
+            resolve_param_with_alias(config, "smtp_server", "SMTP_SERVER", "SMTP_HOST", ctx)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "send_email smtp provider requires 'smtp_server' or SMTP_SERVER env var"
+                    )
+                })?;
 
         let smtp_port = config
             .get("smtp_port")
@@ -255,8 +376,10 @@ impl SendEmailNode {
             .map(|v| v as u16)
             .or_else(|| std::env::var("SMTP_PORT").ok().and_then(|v| v.parse().ok()));
 
-        let smtp_username = resolve_param(config, "smtp_username", "SMTP_USERNAME", ctx);
-        let smtp_password = resolve_param(config, "smtp_password", "SMTP_PASSWORD", ctx);
+        let smtp_username =
+            resolve_param_with_alias(config, "smtp_username", "SMTP_USERNAME", "SMTP_USER", ctx);
+        let smtp_password =
+            resolve_param_with_alias(config, "smtp_password", "SMTP_PASSWORD", "SMTP_PASS", ctx);
 
         let tls_mode = config
             .get("smtp_tls")
@@ -312,25 +435,51 @@ impl SendEmailNode {
             builder = builder.reply_to(mailbox);
         }
 
-        let email = match (&params.html, &params.text) {
-            (Some(html), Some(text)) => builder.multipart(
-                MultiPart::alternative()
-                    .singlepart(
-                        SinglePart::builder()
-                            .header(ContentType::TEXT_PLAIN)
-                            .body(text.clone()),
-                    )
-                    .singlepart(
-                        SinglePart::builder()
-                            .header(ContentType::TEXT_HTML)
-                            .body(html.clone()),
-                    ),
-            )?,
-            (Some(html), None) => builder.header(ContentType::TEXT_HTML).body(html.clone())?,
-            (None, Some(text)) => builder.header(ContentType::TEXT_PLAIN).body(text.clone())?,
-            (None, None) => builder
+        let text_part = |text: &String| {
+            SinglePart::builder()
                 .header(ContentType::TEXT_PLAIN)
-                .body(String::new())?,
+                .body(text.clone())
+        };
+        let html_part = |html: &String| {
+            SinglePart::builder()
+                .header(ContentType::TEXT_HTML)
+                .body(html.clone())
+        };
+        let body = match (&params.html, &params.text) {
+            (Some(html), Some(text)) => MultiPart::alternative()
+                .singlepart(text_part(text))
+                .singlepart(html_part(html))
+                .into(),
+            (Some(html), None) => html_part(html).into(),
+            (None, Some(text)) => text_part(text).into(),
+            (None, None) => text_part(&String::new()).into(),
+        };
+
+        let email = if params.attachments.is_empty() {
+            match body {
+                MessageBody::Single(part) => builder.singlepart(part)?,
+                MessageBody::Multi(part) => builder.multipart(part)?,
+            }
+        } else {
+            let mut mixed = match body {
+                MessageBody::Single(part) => MultiPart::mixed().singlepart(part),
+                MessageBody::Multi(part) => MultiPart::mixed().multipart(part),
+            };
+            for attachment in &params.attachments {
+                let content_type = ContentType::parse(&attachment.content_type).map_err(|e| {
+                    anyhow::anyhow!(
+                        "send_email: invalid content_type '{}' for attachment '{}': {}",
+                        attachment.content_type,
+                        attachment.filename,
+                        e
+                    )
+                })?;
+                mixed = mixed.singlepart(
+                    Attachment::new(attachment.filename.clone())
+                        .body(attachment.bytes.clone(), content_type),
+                );
+            }
+            builder.multipart(mixed)?
         };
 
         // Build the SMTP transport
@@ -1,13 +1,17 @@
 mod email;
+mod render_email;
 mod slack;
 
 pub use email::SendEmailNode;
-pub use slack::SlackNotificationNode;
+pub use render_email::RenderEmailNode;
+pub use slack::{SlackNotificationNode, SlackNotifyNode};
 
 use crate::nodes::NodeRegistry;
 use std::sync::Arc;
 
 pub fn register_all(registry: &mut NodeRegistry) {
     registry.register(Arc::new(SendEmailNode));
+    registry.register(Arc::new(RenderEmailNode));
     registry.register(Arc::new(SlackNotificationNode));
+    registry.register(Arc::new(SlackNotifyNode));
 }
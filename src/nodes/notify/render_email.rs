@@ -0,0 +1,116 @@
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+
+use crate::engine::types::{Context, NodeOutput};
+use crate::lua::interpolate::{interpolate_ctx, interpolate_value};
+use crate::nodes::Node;
+
+/// The parts of an email, in render order.
+const PARTS: &[&str] = &["subject", "text", "html"];
+
+pub struct RenderEmailNode;
+
+#[async_trait]
+impl Node for RenderEmailNode {
+    fn node_type(&self) -> &str {
+        "render_email"
+    }
+
+    fn description(&self) -> &str {
+        "Render Liquid subject, text and HTML email templates from one data object"
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    /// Templates read from `template_dir` can change between runs.
+    fn is_pure_with(&self, config: &serde_json::Value) -> bool {
+        config.get("template_dir").is_none()
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .unwrap_or("email");
+
+        let template_dir = config
+            .get("template_dir")
+            .and_then(|v| v.as_str())
+            .map(|dir| interpolate_ctx(dir, ctx));
+
+        // Inline templates win over files, so a flow can override one part
+        // (usually the subject) of a shared template set.
+        let mut sources: Vec<(&str, String)> = Vec::new();
+        for part in PARTS {
+            let source = match config.get(*part).and_then(|v| v.as_str()) {
+                Some(inline) => Some(inline.to_string()),
+                None => match &template_dir {
+                    Some(dir) => read_template(dir, part).await?,
+                    None => None,
+                },
+            };
+            if let Some(source) = source {
+                sources.push((part, source));
+            }
+        }
+
+        if !sources.iter().any(|(part, _)| *part == "subject") {
+            anyhow::bail!(
+                "render_email requires a 'subject' template (inline or subject.liquid in template_dir)"
+            );
+        }
+        if !sources.iter().any(|(part, _)| *part != "subject") {
+            anyhow::bail!("render_email requires a 'text' or 'html' template");
+        }
+
+        let data = match (config.get("data"), config.get("data_key")) {
+            (Some(data), _) => interpolate_value(data, ctx),
+            (None, Some(key)) => {
+                let key = key
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("render_email 'data_key' must be a string"))?;
+                ctx.get(key).cloned().ok_or_else(|| {
+                    anyhow::anyhow!("render_email: data_key '{}' not found in context", key)
+                })?
+            }
+            (None, None) => serde_json::to_value(ctx)?,
+        };
+        let globals = liquid::model::to_object(&data)
+            .map_err(|e| anyhow::anyhow!("render_email 'data' must be an object: {}", e))?;
+
+        let parser = liquid::ParserBuilder::with_stdlib().build()?;
+        let mut output = NodeOutput::new();
+        for (part, source) in sources {
+            let rendered = parser
+                .parse(&source)
+                .and_then(|template| template.render(&globals))
+                .map_err(|e| anyhow::anyhow!("render_email: {} template: {}", part, e))?;
+            let key = match part {
+                "subject" => format!("{}_subject", output_key),
+                other => format!("{}_{}_body", output_key, other),
+            };
+            // A subject is a single header line.
+            let rendered = if part == "subject" {
+                rendered.trim().to_string()
+            } else {
+                rendered
+            };
+            output.insert(key, serde_json::Value::String(rendered));
+        }
+        Ok(output)
+    }
+}
+
+/// Read `<dir>/<part>.liquid`, or `None` when the file does not exist.
+async fn read_template(dir: &str, part: &str) -> Result<Option<String>> {
+    let path = Path::new(dir).join(format!("{}.liquid", part));
+    match tokio::fs::read_to_string(&path).await {
+        Ok(source) => Ok(Some(source)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("render_email: reading {}", path.display())),
+    }
+}
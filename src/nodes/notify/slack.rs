@@ -40,6 +40,87 @@ fn resolve_webhook_url(config: &serde_json::Value, ctx: &Context) -> Option<Stri
         .or_else(|| std::env::var("SLACK_WEBHOOK").ok())
 }
 
+/// Slack's Web API base URL for bot-token posts.
+const SLACK_API_URL: &str = "https://slack.com/api";
+
+/// Format a `mention` entry as Slack markup: `here`, `channel` and
+/// `everyone` (with or without `@`) become broadcasts, anything else is
+/// treated as a user ID. Entries already in `<...>` form pass through.
+fn format_mention(mention: &str) -> String {
+    let mention = mention.trim();
+    if mention.starts_with('<') && mention.ends_with('>') {
+        return mention.to_string();
+    }
+    let name = mention.trim_start_matches('@');
+    match name {
+        "here" | "channel" | "everyone" => format!("<!{}>", name),
+        user_id => format!("<@{}>", user_id),
+    }
+}
+
+/// Render the `mention` config (a string or an array of strings) as a
+/// space-separated prefix for the message text.
+fn resolve_mentions(config: &serde_json::Value, ctx: &Context) -> Option<String> {
+    let mentions: Vec<String> = match config.get("mention")? {
+        serde_json::Value::String(s) => vec![format_mention(&interpolate_ctx(s, ctx))],
+        serde_json::Value::Array(items) => items
+            .iter()
+            .filter_map(|item| item.as_str())
+            .map(|item| format_mention(&interpolate_ctx(item, ctx)))
+            .collect(),
+        _ => Vec::new(),
+    };
+    (!mentions.is_empty()).then(|| mentions.join(" "))
+}
+
+/// Where a `slack_notify` message goes.
+enum SlackTarget {
+    Webhook(String),
+    /// `chat.postMessage` with a bot token.
+    Api {
+        url: String,
+        token: String,
+        channel: String,
+    },
+}
+
+/// An explicit `webhook_url` wins, then a `channel` with a bot token, then
+/// the `SLACK_WEBHOOK` env var.
+fn resolve_target(config: &serde_json::Value, ctx: &Context) -> Result<SlackTarget> {
+    let config_str = |key: &str| {
+        config
+            .get(key)
+            .and_then(|value| value.as_str())
+            .map(|value| interpolate_ctx(value, ctx))
+    };
+
+    if let Some(url) = config_str("webhook_url") {
+        return Ok(SlackTarget::Webhook(url));
+    }
+    if let Some(channel) = config_str("channel") {
+        let token = config_str("token")
+            .or_else(|| std::env::var("SLACK_BOT_TOKEN").ok())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "slack_notify requires 'token' or SLACK_BOT_TOKEN env var to post to a channel"
+                )
+            })?;
+        let api_url = config_str("api_url").unwrap_or_else(|| SLACK_API_URL.to_string());
+        return Ok(SlackTarget::Api {
+            url: format!("{}/chat.postMessage", api_url.trim_end_matches('/')),
+            token,
+            channel,
+        });
+    }
+    std::env::var("SLACK_WEBHOOK")
+        .map(SlackTarget::Webhook)
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "slack_notify requires 'webhook_url' (or SLACK_WEBHOOK env var) or 'channel' with a bot token"
+            )
+        })
+}
+
 pub struct SlackNotificationNode;
 
 #[async_trait]
@@ -119,3 +200,121 @@ impl Node for SlackNotificationNode {
         Ok(output)
     }
 }
+
+pub struct SlackNotifyNode;
+
+#[async_trait]
+impl Node for SlackNotifyNode {
+    fn node_type(&self) -> &str {
+        "slack_notify"
+    }
+
+    fn description(&self) -> &str {
+        "Post a text or Block Kit message to Slack via webhook or bot token"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let target = resolve_target(config, ctx)?;
+        let output_key = resolve_output_key(config);
+        let timeout_s = config
+            .get("timeout")
+            .and_then(|value| value.as_f64())
+            .unwrap_or(30.0);
+        let timeout = Duration::from_secs_f64(timeout_s);
+
+        let text = config
+            .get("text")
+            .and_then(|value| value.as_str())
+            .or_else(|| config.get("message").and_then(|value| value.as_str()))
+            .map(|value| interpolate_ctx(value, ctx));
+        let text = match (resolve_mentions(config, ctx), text) {
+            (Some(mentions), Some(text)) => Some(format!("{} {}", mentions, text)),
+            (mentions, text) => mentions.or(text),
+        };
+        let blocks = config
+            .get("blocks")
+            .map(|value| interpolate_json_value(value, ctx));
+        if blocks.as_ref().is_some_and(|value| !value.is_array()) {
+            anyhow::bail!("slack_notify requires 'blocks' to be an array when provided");
+        }
+
+        // With blocks, Slack shows `text` only in notifications, so it is optional.
+        let mut payload = serde_json::Map::new();
+        match (text, blocks) {
+            (None, None) => anyhow::bail!("slack_notify requires 'text', 'message', or 'blocks'"),
+            (text, blocks) => {
+                if let Some(text) = text {
+                    payload.insert("text".to_string(), serde_json::Value::String(text));
+                }
+                if let Some(blocks) = blocks {
+                    payload.insert("blocks".to_string(), blocks);
+                }
+            }
+        }
+        if let Some(thread_ts) = config.get("thread_ts").and_then(|value| value.as_str()) {
+            payload.insert(
+                "thread_ts".to_string(),
+                serde_json::Value::String(interpolate_ctx(thread_ts, ctx)),
+            );
+        }
+
+        let client = reqwest::Client::builder().timeout(timeout).build()?;
+        let request = match &target {
+            SlackTarget::Webhook(url) => client.post(url),
+            SlackTarget::Api {
+                url,
+                token,
+                channel,
+            } => {
+                payload.insert(
+                    "channel".to_string(),
+                    serde_json::Value::String(channel.clone()),
+                );
+                client.post(url).bearer_auth(token)
+            }
+        };
+        let response = request
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("slack_notify request failed: {}", e))?;
+
+        let status = response.status().as_u16();
+        let body = response.text().await?;
+        let data = serde_json::from_str(&body).unwrap_or(serde_json::Value::String(body.clone()));
+
+        // Webhooks report errors through the status code; the Web API answers
+        // 200 with `{"ok": false, "error": "..."}`.
+        if !(200..300).contains(&status) {
+            anyhow::bail!("slack_notify: Slack returned status {}: {}", status, body);
+        }
+        if let SlackTarget::Api { channel, .. } = &target
+            && data.get("ok").and_then(|value| value.as_bool()) != Some(true)
+        {
+            let error = data
+                .get("error")
+                .and_then(|value| value.as_str())
+                .unwrap_or(body.as_str());
+            anyhow::bail!(
+                "slack_notify: chat.postMessage to '{}' failed: {}",
+                channel,
+                error
+            );
+        }
+
+        let mut output = NodeOutput::new();
+        output.insert(
+            format!("{}_status", output_key),
+            serde_json::Value::Number(status.into()),
+        );
+        if let Some(ts) = data.get("ts").cloned() {
+            output.insert(format!("{}_ts", output_key), ts);
+        }
+        output.insert(format!("{}_data", output_key), data);
+        output.insert(
+            format!("{}_success", output_key),
+            serde_json::Value::Bool(true),
+        );
+        Ok(output)
+    }
+}
@@ -0,0 +1,181 @@
+//! Load custom nodes from shared libraries at startup.
+//!
+//! A plugin is a `cdylib` crate that depends on `ironflow` and declares its
+//! entry point with [`declare_plugin!`](crate::declare_plugin):
+//!
+//! ```ignore
+//! fn register(registry: &mut ironflow::nodes::NodeRegistry) {
+//!     registry.register(std::sync::Arc::new(MyNode));
+//! }
+//!
+//! ironflow::declare_plugin!(register);
+//! ```
+//!
+//! Rust has no stable ABI, so the `Node` trait objects a plugin hands over
+//! are only sound when the plugin was built against the same ironflow
+//! version with the same compiler. The macro exports the ironflow version
+//! and [`PLUGIN_ABI_VERSION`] the plugin was built with; the loader refuses
+//! a library whose values differ, but it cannot detect a different `rustc`.
+//! Libraries stay loaded for the life of the process because registered
+//! nodes point into their code.
+
+use std::ffi::{CStr, c_char};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+
+use super::NodeRegistry;
+
+/// Bumped whenever the plugin entry points change shape.
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// The `ironflow` version a plugin was compiled against must match this.
+pub const IRONFLOW_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[doc(hidden)]
+pub const IRONFLOW_VERSION_CSTR: &CStr =
+    match CStr::from_bytes_with_nul(concat!(env!("CARGO_PKG_VERSION"), "\0").as_bytes()) {
+        Ok(version) => version,
+        Err(_) => panic!("version is not NUL-terminated"),
+    };
+
+/// Export the entry points the plugin loader looks for. `$register` is a
+/// `fn(&mut NodeRegistry)` that registers the plugin's nodes.
+#[macro_export]
+macro_rules! declare_plugin {
+    ($register:path) => {
+        #[unsafe(no_mangle)]
+        pub extern "C" fn ironflow_plugin_abi_version() -> u32 {
+            $crate::nodes::plugin::PLUGIN_ABI_VERSION
+        }
+
+        #[unsafe(no_mangle)]
+        pub extern "C" fn ironflow_plugin_version() -> *const ::std::ffi::c_char {
+            $crate::nodes::plugin::IRONFLOW_VERSION_CSTR.as_ptr()
+        }
+
+        #[unsafe(no_mangle)]
+        pub extern "C" fn register_nodes(registry: &mut $crate::nodes::NodeRegistry) {
+            $register(registry)
+        }
+    };
+}
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type VersionFn = unsafe extern "C" fn() -> *const c_char;
+type RegisterFn = unsafe extern "C" fn(&mut NodeRegistry);
+
+/// Plugin libraries to load: each `files` entry, then every shared library
+/// directly inside `dir` in name order.
+pub fn plugin_paths(files: &[PathBuf], dir: Option<&Path>) -> Result<Vec<PathBuf>> {
+    let mut paths = files.to_vec();
+    if let Some(dir) = dir {
+        let mut found = Vec::new();
+        for entry in std::fs::read_dir(dir)
+            .with_context(|| format!("Failed to read plugins_dir '{}'", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.is_file()
+                && path.extension().and_then(|e| e.to_str())
+                    == Some(std::env::consts::DLL_EXTENSION)
+            {
+                found.push(path);
+            }
+        }
+        found.sort();
+        paths.extend(found);
+    }
+    Ok(paths)
+}
+
+/// Load the plugin at `path` and add its nodes to `registry`. Returns the
+/// node types it registered. A plugin may not replace a node that is
+/// already registered.
+pub fn load_plugin(registry: &mut NodeRegistry, path: &Path) -> Result<Vec<String>> {
+    // SAFETY: loading runs the library's initializers; plugins are trusted
+    // code chosen by whoever starts the process.
+    let library = unsafe { libloading::Library::new(path) }
+        .with_context(|| format!("Failed to load plugin '{}'", path.display()))?;
+
+    let missing = |symbol: &str| {
+        format!(
+            "Plugin '{}' does not export {} (declare it with ironflow::declare_plugin!)",
+            path.display(),
+            symbol
+        )
+    };
+    let (abi_version, version) = unsafe {
+        let abi_version_fn = library
+            .get::<AbiVersionFn>(b"ironflow_plugin_abi_version\0")
+            .with_context(|| missing("ironflow_plugin_abi_version"))?;
+        let version_fn = library
+            .get::<VersionFn>(b"ironflow_plugin_version\0")
+            .with_context(|| missing("ironflow_plugin_version"))?;
+        let version = version_fn();
+        let version = if version.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(version).to_string_lossy().into_owned()
+        };
+        (abi_version_fn(), version)
+    };
+    if abi_version != PLUGIN_ABI_VERSION {
+        anyhow::bail!(
+            "Plugin '{}' uses plugin ABI {}, but this binary expects ABI {}",
+            path.display(),
+            abi_version,
+            PLUGIN_ABI_VERSION
+        );
+    }
+    if version != IRONFLOW_VERSION {
+        anyhow::bail!(
+            "Plugin '{}' was built against ironflow {}, but this binary is ironflow {}; rebuild the plugin",
+            path.display(),
+            version,
+            IRONFLOW_VERSION
+        );
+    }
+
+    let mut plugin_registry = NodeRegistry::new();
+    unsafe {
+        let register = library
+            .get::<RegisterFn>(b"register_nodes\0")
+            .with_context(|| missing("register_nodes"))?;
+        register(&mut plugin_registry);
+    }
+
+    let mut added = Vec::new();
+    for (node_type, _, _) in plugin_registry.list() {
+        if registry.get(node_type).is_some() {
+            anyhow::bail!(
+                "Plugin '{}' registers node '{}', which is already registered",
+                path.display(),
+                node_type
+            );
+        }
+        added.push(node_type.to_string());
+    }
+    for node_type in &added {
+        if let Some(node) = plugin_registry.get(node_type) {
+            registry.register(node);
+        }
+    }
+
+    // The registered nodes' code and vtables live in the library.
+    std::mem::forget(library);
+    Ok(added)
+}
+
+/// Load every plugin in `paths`, in order.
+pub fn load_plugins(registry: &mut NodeRegistry, paths: &[PathBuf]) -> Result<()> {
+    for path in paths {
+        let added = load_plugin(registry, path)?;
+        tracing::info!(
+            "Loaded plugin {} ({} node(s): {})",
+            path.display(),
+            added.len(),
+            added.join(", ")
+        );
+    }
+    Ok(())
+}
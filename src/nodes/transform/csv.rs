@@ -1,11 +1,19 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use async_trait::async_trait;
-use csv::{QuoteStyle, ReaderBuilder, Trim, WriterBuilder};
+use base64::Engine;
+use csv::{QuoteStyle, ReaderBuilder, StringRecord, Terminator, Trim, WriterBuilder};
+use flate2::read::MultiGzDecoder;
 
 use crate::engine::types::{Context, NodeOutput};
+use crate::lua::interpolate::interpolate_ctx;
 use crate::nodes::Node;
+use crate::util::compression::{gunzip, wants_gunzip};
+use crate::util::text::strip_bom;
 
 pub struct CsvParseNode;
 
@@ -19,103 +27,423 @@ impl Node for CsvParseNode {
         "Parse CSV text from context into structured JSON data"
     }
 
-    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
-        let source_key = config
-            .get("source_key")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("csv_parse requires 'source_key'"))?;
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    /// Reading `path` or writing `batch_size` chunks to `output_dir`
+    /// touches files.
+    fn is_pure_with(&self, config: &serde_json::Value) -> bool {
+        ["path", "batch_size", "output_dir"]
+            .iter()
+            .all(|key| config.get(*key).is_none())
+    }
 
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let output_key = config
             .get("output_key")
             .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("csv_parse requires 'output_key'"))?;
-
-        let has_header = config
-            .get("has_header")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(true);
+            .ok_or_else(|| anyhow::anyhow!("csv_parse requires 'output_key'"))?
+            .to_string();
+
+        let options = CsvParseOptions::from_config(config)?;
+
+        // With `batch_size`, rows go to JSON chunk files in `output_dir`
+        // instead of context, so memory stays bounded by one chunk.
+        let chunks = match config.get("batch_size").and_then(|v| v.as_u64()) {
+            Some(0) => anyhow::bail!("csv_parse 'batch_size' must be at least 1"),
+            Some(batch_size) => {
+                let output_dir = config
+                    .get("output_dir")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("csv_parse requires 'output_dir' when 'batch_size' is set")
+                    })?;
+                Some(ChunkWriter::new(
+                    PathBuf::from(interpolate_ctx(output_dir, ctx)),
+                    output_key.clone(),
+                    batch_size as usize,
+                )?)
+            }
+            None => None,
+        };
 
-        let trim_fields = config
-            .get("trim")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
+        match (config.get("path"), config.get("source_key")) {
+            (Some(_), Some(_)) => {
+                anyhow::bail!("csv_parse accepts either 'path' or 'source_key', not both")
+            }
+            (Some(path), None) => {
+                let path = path
+                    .as_str()
+                    .map(|p| interpolate_ctx(p, ctx))
+                    .ok_or_else(|| anyhow::anyhow!("csv_parse 'path' must be a string"))?;
+                let gunzip = wants_gunzip(&path, config);
+                // Stream the file on a blocking thread; only the current row
+                // (or chunk) is held in memory.
+                tokio::task::spawn_blocking(move || {
+                    let file = File::open(&path)
+                        .with_context(|| format!("csv_parse: failed to open '{}'", path))?;
+                    let input: Box<dyn Read> = if gunzip {
+                        Box::new(MultiGzDecoder::new(BufReader::new(file)))
+                    } else {
+                        Box::new(file)
+                    };
+                    let mut input = BufReader::new(input);
+                    if input.fill_buf()?.starts_with(UTF8_BOM) {
+                        input.consume(UTF8_BOM.len());
+                    }
+                    options.collect(input, output_key, chunks)
+                })
+                .await?
+            }
+            (None, Some(source_key)) => {
+                let source_key = source_key
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("csv_parse 'source_key' must be a string"))?;
+                let source = ctx
+                    .get(source_key)
+                    .ok_or_else(|| anyhow::anyhow!("Key '{}' not found in context", source_key))?;
+
+                let csv_text = source
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("Value at '{}' is not a string", source_key))?;
+
+                // `compressed: true` means the source holds base64-encoded gzip data,
+                // e.g. from `read_file` with `encoding = "base64"` or an HTTP body.
+                let decompressed;
+                let csv_text = if config
+                    .get("compressed")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false)
+                {
+                    let bytes = base64::engine::general_purpose::STANDARD
+                        .decode(csv_text.trim())
+                        .map_err(|e| anyhow::anyhow!("csv_parse: invalid base64 source: {}", e))?;
+                    decompressed = String::from_utf8(gunzip(&bytes)?).map_err(|e| {
+                        anyhow::anyhow!("csv_parse: decompressed source is not UTF-8: {}", e)
+                    })?;
+                    decompressed.as_str()
+                } else {
+                    csv_text
+                };
+                let csv_text = strip_bom(csv_text);
+                options.collect(csv_text.as_bytes(), output_key, chunks)
+            }
+            (None, None) => anyhow::bail!("csv_parse requires 'source_key' or 'path'"),
+        }
+    }
+}
 
-        let skip_empty_lines = config
-            .get("skip_empty_lines")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(true);
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Reader settings shared by the in-context and streaming sources.
+struct CsvParseOptions {
+    has_header: bool,
+    trim_fields: bool,
+    skip_empty_lines: bool,
+    infer_types: bool,
+    /// Tokens (e.g. "NA", "") that become JSON null when `infer_types` is on
+    /// or the column has an entry in `column_types`.
+    null_values: Vec<String>,
+    /// Explicit per-column types; these columns skip inference.
+    column_types: Option<HashMap<String, ColumnType>>,
+    max_rows: usize,
+    delimiter: u8,
+    quote: u8,
+}
 
-        let infer_types = config
-            .get("infer_types")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
+impl CsvParseOptions {
+    fn from_config(config: &serde_json::Value) -> Result<Self> {
+        Ok(Self {
+            has_header: config
+                .get("has_header")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true),
+            trim_fields: config
+                .get("trim")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            skip_empty_lines: config
+                .get("skip_empty_lines")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true),
+            infer_types: config
+                .get("infer_types")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            null_values: config
+                .get("null_values")
+                .and_then(|v| v.as_array())
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            max_rows: config
+                .get("max_rows")
+                .and_then(|v| v.as_u64())
+                .map(|n| n as usize)
+                .unwrap_or(usize::MAX),
+            column_types: match config.get("column_types") {
+                None => None,
+                Some(serde_json::Value::Object(types)) => Some(
+                    types
+                        .iter()
+                        .map(|(column, ty)| {
+                            let ty = ty.as_str().ok_or_else(|| {
+                                anyhow::anyhow!(
+                                    "csv_parse: column_types['{}'] must be a string",
+                                    column
+                                )
+                            })?;
+                            Ok((column.clone(), ColumnType::parse(ty)?))
+                        })
+                        .collect::<Result<_>>()?,
+                ),
+                Some(_) => anyhow::bail!("csv_parse 'column_types' must be an object"),
+            },
+            delimiter: parse_csv_single_byte(config, "delimiter", b',')?,
+            quote: parse_csv_single_byte(config, "quote_char", b'"')?,
+        })
+    }
 
-        let delimiter = parse_csv_single_byte(config, "delimiter", b',')?;
-        let quote = parse_csv_single_byte(config, "quote_char", b'"')?;
+    /// Parse `input` and build the node output: the rows under `output_key`,
+    /// or chunk file paths when `chunks` is set.
+    fn collect(
+        &self,
+        input: impl Read,
+        output_key: String,
+        chunks: Option<ChunkWriter>,
+    ) -> Result<NodeOutput> {
+        let mut output = NodeOutput::new();
+        let mut errors = ConversionErrors {
+            chunks: chunks
+                .as_ref()
+                .filter(|_| self.column_types.is_some())
+                .map(|chunks| chunks.sibling(format!("{}_errors", output_key))),
+            ..Default::default()
+        };
+        match chunks {
+            Some(mut chunks) => {
+                let count = self.parse_rows(input, |row| chunks.push(row), &mut errors)?;
+                output.insert(
+                    format!("{}_chunks", output_key),
+                    serde_json::json!(chunks.finish()?),
+                );
+                output.insert(format!("{}_count", output_key), serde_json::json!(count));
+            }
+            None => {
+                let mut rows = Vec::new();
+                self.parse_rows(
+                    input,
+                    |row| {
+                        rows.push(row);
+                        Ok(())
+                    },
+                    &mut errors,
+                )?;
+                output.insert(output_key.clone(), serde_json::Value::Array(rows));
+            }
+        }
+        if self.column_types.is_some() {
+            output.insert(
+                format!("{}_error_count", output_key),
+                serde_json::json!(errors.count),
+            );
+            if let Some(chunks) = errors.chunks {
+                output.insert(
+                    format!("{}_error_chunks", output_key),
+                    serde_json::json!(chunks.finish()?),
+                );
+            }
+            output.insert(
+                format!("{}_errors", output_key),
+                serde_json::Value::Array(errors.reported),
+            );
+        }
+        Ok(output)
+    }
 
-        let source = ctx
-            .get(source_key)
-            .ok_or_else(|| anyhow::anyhow!("Key '{}' not found in context", source_key))?;
+    /// Parse CSV from `input`, passing each row to `emit`. Returns the number
+    /// of rows emitted. Fields that fail their `column_types` conversion are
+    /// kept as strings and reported in `errors`.
+    fn parse_rows(
+        &self,
+        input: impl Read,
+        mut emit: impl FnMut(serde_json::Value) -> Result<()>,
+        errors: &mut ConversionErrors,
+    ) -> Result<usize> {
+        let mut reader = ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .has_headers(self.has_header)
+            .trim(if self.trim_fields {
+                Trim::All
+            } else {
+                Trim::None
+            })
+            .from_reader(input);
 
-        let csv_text = source
-            .as_str()
-            .ok_or_else(|| anyhow::anyhow!("Value at '{}' is not a string", source_key))?;
+        let headers = if self.has_header {
+            Some(dedupe_headers(reader.headers()?.iter()))
+        } else {
+            None
+        };
+
+        let mut count = 0;
+        let mut record = StringRecord::new();
+        while count < self.max_rows && reader.read_record(&mut record)? {
+            if self.skip_empty_lines && record.iter().all(|field| field.is_empty()) {
+                continue;
+            }
+            let mut value = |column: &str, field: &str| match self
+                .column_types
+                .as_ref()
+                .and_then(|types| types.get(column))
+            {
+                Some(ty) => {
+                    if self.null_values.iter().any(|token| token == field.trim()) {
+                        return Ok(serde_json::Value::Null);
+                    }
+                    match ty.convert(field) {
+                        Some(value) => Ok(value),
+                        None => {
+                            errors.push(serde_json::json!({
+                                "row": count + 1,
+                                "column": column,
+                                "value": field,
+                                "type": ty.name(),
+                            }))?;
+                            Ok(serde_json::Value::String(field.to_string()))
+                        }
+                    }
+                }
+                None => Ok(csv_value_from_str(
+                    field,
+                    self.infer_types,
+                    &self.null_values,
+                )),
+            };
+
+            let row = match &headers {
+                Some(headers) => {
+                    let mut row = serde_json::Map::new();
+                    for (idx, field) in record.iter().enumerate() {
+                        let key = headers
+                            .get(idx)
+                            .cloned()
+                            .unwrap_or_else(|| format!("column_{}", idx + 1));
+                        let field = value(&key, field)?;
+                        row.insert(key, field);
+                    }
+                    serde_json::Value::Object(row)
+                }
+                None => serde_json::Value::Array(
+                    record
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, field)| value(&format!("column_{}", idx + 1), field))
+                        .collect::<Result<_>>()?,
+                ),
+            };
+            emit(row)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
 
-        let mut reader = ReaderBuilder::new()
-            .delimiter(delimiter)
-            .quote(quote)
-            .has_headers(has_header)
-            .trim(if trim_fields { Trim::All } else { Trim::None })
-            .from_reader(csv_text.as_bytes());
+/// At most this many conversion errors are kept in `{output_key}_errors`;
+/// `{output_key}_error_count` has the full count.
+const MAX_REPORTED_ERRORS: usize = 1000;
+
+/// Fields that failed their `column_types` conversion. The first
+/// [`MAX_REPORTED_ERRORS`] go to context; when rows are chunked, every error
+/// is also written to error chunk files.
+#[derive(Default)]
+struct ConversionErrors {
+    reported: Vec<serde_json::Value>,
+    count: usize,
+    chunks: Option<ChunkWriter>,
+}
 
-        let mut rows = Vec::new();
+impl ConversionErrors {
+    fn push(&mut self, error: serde_json::Value) -> Result<()> {
+        self.count += 1;
+        if self.reported.len() < MAX_REPORTED_ERRORS {
+            self.reported.push(error.clone());
+        }
+        match &mut self.chunks {
+            Some(chunks) => chunks.push(error),
+            None => Ok(()),
+        }
+    }
+}
 
-        if has_header {
-            let headers: Vec<String> = reader
-                .headers()
-                .map(|headers| headers.iter().map(|h| h.to_string()).collect())?;
+/// Writes rows to `{dir}/{prefix}_00001.json`, `..._00002.json`, ... with at
+/// most `batch_size` rows per file, each a JSON array.
+struct ChunkWriter {
+    dir: PathBuf,
+    prefix: String,
+    batch_size: usize,
+    pending: Vec<serde_json::Value>,
+    paths: Vec<String>,
+}
 
-            for record in reader.records() {
-                let record = record?;
-                if skip_empty_lines && record.iter().all(|field| field.is_empty()) {
-                    continue;
-                }
+impl ChunkWriter {
+    fn new(dir: PathBuf, prefix: String, batch_size: usize) -> Result<Self> {
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("csv_parse: failed to create '{}'", dir.display()))?;
+        Ok(Self {
+            dir,
+            prefix,
+            batch_size,
+            pending: Vec::with_capacity(batch_size.min(10_000)),
+            paths: Vec::new(),
+        })
+    }
 
-                let mut row = serde_json::Map::new();
-                for (idx, value) in record.iter().enumerate() {
-                    let key = headers
-                        .get(idx)
-                        .cloned()
-                        .unwrap_or_else(|| format!("column_{}", idx + 1));
-                    row.insert(key, csv_value_from_str(value, infer_types));
-                }
-                for idx in headers.len()..record.len() {
-                    let key = format!("column_{}", idx + 1);
-                    row.insert(
-                        key,
-                        csv_value_from_str(record.get(idx).unwrap_or_default(), infer_types),
-                    );
-                }
-                rows.push(serde_json::Value::Object(row));
-            }
-        } else {
-            for record in reader.records() {
-                let record = record?;
-                if skip_empty_lines && record.iter().all(|field| field.is_empty()) {
-                    continue;
-                }
+    /// A writer for `{dir}/{prefix}_00001.json`, ... in the same directory
+    /// with the same batch size.
+    fn sibling(&self, prefix: String) -> Self {
+        Self {
+            dir: self.dir.clone(),
+            prefix,
+            batch_size: self.batch_size,
+            pending: Vec::new(),
+            paths: Vec::new(),
+        }
+    }
 
-                let row: Vec<serde_json::Value> = record
-                    .iter()
-                    .map(|value| csv_value_from_str(value, infer_types))
-                    .collect();
-                rows.push(serde_json::Value::Array(row));
-            }
+    fn push(&mut self, row: serde_json::Value) -> Result<()> {
+        self.pending.push(row);
+        if self.pending.len() >= self.batch_size {
+            self.flush()?;
         }
+        Ok(())
+    }
 
-        let mut output = NodeOutput::new();
-        output.insert(output_key.to_string(), serde_json::Value::Array(rows));
-        Ok(output)
+    fn flush(&mut self) -> Result<()> {
+        let path = self
+            .dir
+            .join(format!("{}_{:05}.json", self.prefix, self.paths.len() + 1));
+        let file = File::create(&path)
+            .with_context(|| format!("csv_parse: failed to create '{}'", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, &self.pending)?;
+        writer.flush()?;
+        self.pending.clear();
+        self.paths.push(path.to_string_lossy().to_string());
+        Ok(())
+    }
+
+    /// Write any remaining rows and return the chunk paths in order.
+    fn finish(mut self) -> Result<Vec<String>> {
+        if !self.pending.is_empty() {
+            self.flush()?;
+        }
+        Ok(self.paths)
     }
 }
 
@@ -131,6 +459,10 @@ impl Node for CsvStringifyNode {
         "Serialize JSON data to CSV text"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let source_key = config
             .get("source_key")
@@ -152,6 +484,32 @@ impl Node for CsvStringifyNode {
             .get("quote_all")
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
+        let sort_headers = config
+            .get("sort_headers")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let columns = match config.get("columns") {
+            None => None,
+            Some(serde_json::Value::Array(columns)) => Some(
+                columns
+                    .iter()
+                    .map(|column| {
+                        column.as_str().map(String::from).ok_or_else(|| {
+                            anyhow::anyhow!("csv_stringify 'columns' must be an array of strings")
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+            Some(_) => anyhow::bail!("csv_stringify 'columns' must be an array of strings"),
+        };
+        let terminator = match config.get("line_terminator").and_then(|v| v.as_str()) {
+            None | Some("\n") | Some("\\n") => Terminator::Any(b'\n'),
+            Some("\r\n") | Some("\\r\\n") => Terminator::CRLF,
+            Some(other) => anyhow::bail!(
+                "csv_stringify 'line_terminator' must be \"\\n\" or \"\\r\\n\", got {:?}",
+                other
+            ),
+        };
 
         let source = ctx
             .get(source_key)
@@ -165,31 +523,34 @@ impl Node for CsvStringifyNode {
             } else {
                 QuoteStyle::Necessary
             })
+            .terminator(terminator)
             .from_writer(Vec::new());
 
         match source {
             serde_json::Value::Array(values) => {
-                let mode = detect_csv_source_mode(values)?;
+                let mode = match detect_csv_source_mode(values)? {
+                    // An empty array with explicit columns still gets its header row.
+                    CsvSourceMode::Scalars if values.is_empty() && columns.is_some() => {
+                        CsvSourceMode::Objects
+                    }
+                    CsvSourceMode::Objects => CsvSourceMode::Objects,
+                    _ if columns.is_some() => anyhow::bail!(
+                        "csv_stringify 'columns' requires an object or an array of objects"
+                    ),
+                    mode => mode,
+                };
                 match mode {
                     CsvSourceMode::Objects => {
-                        let mut headers: Vec<String> = Vec::new();
-                        let mut seen = HashSet::new();
                         let mut rows = Vec::new();
-
                         for value in values {
                             let object = value
                                 .as_object()
                                 .ok_or_else(|| anyhow::anyhow!(
                                     "csv_stringify expects array elements to be objects when source is an array of objects"
                                 ))?;
-                            for field in object.keys() {
-                                if seen.insert(field.clone()) {
-                                    headers.push(field.clone());
-                                }
-                            }
-                            rows.push(object.clone());
+                            rows.push(object);
                         }
-                        headers.sort_unstable();
+                        let headers = object_headers(rows.iter().copied(), columns, sort_headers);
 
                         if include_headers {
                             csv.write_record(&headers)?;
@@ -242,8 +603,7 @@ impl Node for CsvStringifyNode {
                 }
             }
             serde_json::Value::Object(object) => {
-                let mut headers: Vec<String> = object.keys().cloned().collect();
-                headers.sort_unstable();
+                let headers = object_headers([object], columns, sort_headers);
                 if include_headers {
                     csv.write_record(&headers)?;
                 }
@@ -272,6 +632,31 @@ impl Node for CsvStringifyNode {
     }
 }
 
+/// Header row for object rows: `columns` when given, otherwise every key in
+/// the order first seen across rows (sorted with `sort_headers`).
+fn object_headers<'a>(
+    rows: impl IntoIterator<Item = &'a serde_json::Map<String, serde_json::Value>>,
+    columns: Option<Vec<String>>,
+    sort_headers: bool,
+) -> Vec<String> {
+    if let Some(columns) = columns {
+        return columns;
+    }
+    let mut headers: Vec<String> = Vec::new();
+    let mut seen = HashSet::new();
+    for row in rows {
+        for field in row.keys() {
+            if seen.insert(field.as_str()) {
+                headers.push(field.clone());
+            }
+        }
+    }
+    if sort_headers {
+        headers.sort_unstable();
+    }
+    headers
+}
+
 #[derive(Debug)]
 enum CsvSourceMode {
     Objects,
@@ -332,13 +717,90 @@ pub(super) fn parse_csv_single_byte(
     Ok(bytes[0])
 }
 
-fn csv_value_from_str(value: &str, infer_types: bool) -> serde_json::Value {
+/// Make header names unique so later columns don't overwrite earlier ones in
+/// the row object: a repeated `name` becomes `name_2`, `name_3`, ...
+fn dedupe_headers<'a>(headers: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut unique = Vec::new();
+
+    for header in headers {
+        let mut candidate = header.to_string();
+        let mut suffix = 2;
+        while seen.contains(&candidate) {
+            candidate = format!("{}_{}", header, suffix);
+            suffix += 1;
+        }
+        seen.insert(candidate.clone());
+        unique.push(candidate);
+    }
+    unique
+}
+
+/// A type from `csv_parse`'s `column_types`.
+#[derive(Clone, Copy)]
+enum ColumnType {
+    String,
+    Int,
+    Float,
+    Bool,
+}
+
+impl ColumnType {
+    fn parse(name: &str) -> Result<Self> {
+        Ok(match name {
+            "string" => Self::String,
+            "int" | "integer" => Self::Int,
+            "float" | "number" => Self::Float,
+            "bool" | "boolean" => Self::Bool,
+            other => anyhow::bail!(
+                "csv_parse: unsupported column type '{}'. Use string, int, float or bool",
+                other
+            ),
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Int => "int",
+            Self::Float => "float",
+            Self::Bool => "bool",
+        }
+    }
+
+    /// Convert `field`, or `None` when it is not a valid value of this type.
+    /// Empty fields in non-string columns become null.
+    fn convert(self, field: &str) -> Option<serde_json::Value> {
+        let trimmed = field.trim();
+        match self {
+            Self::String => Some(serde_json::Value::String(field.to_string())),
+            _ if trimmed.is_empty() => Some(serde_json::Value::Null),
+            Self::Int => trimmed.parse::<i64>().ok().map(serde_json::Value::from),
+            Self::Float => trimmed
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(serde_json::Value::Number),
+            Self::Bool => match trimmed.to_ascii_lowercase().as_str() {
+                "true" | "1" => Some(serde_json::Value::Bool(true)),
+                "false" | "0" => Some(serde_json::Value::Bool(false)),
+                _ => None,
+            },
+        }
+    }
+}
+
+fn csv_value_from_str(value: &str, infer_types: bool, null_values: &[String]) -> serde_json::Value {
     if !infer_types {
         return serde_json::Value::String(value.to_string());
     }
 
     let trimmed = value.trim();
 
+    if null_values.iter().any(|token| token == trimmed) {
+        return serde_json::Value::Null;
+    }
+
     if trimmed.eq_ignore_ascii_case("true") {
         return serde_json::Value::Bool(true);
     }
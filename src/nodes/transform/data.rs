@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::collections::HashSet;
 
 use anyhow::Result;
@@ -6,6 +7,8 @@ use async_trait::async_trait;
 use crate::engine::types::{Context, NodeOutput};
 use crate::nodes::Node;
 
+use super::json::resolve_json_path;
+
 pub struct SelectFieldsNode;
 
 #[async_trait]
@@ -18,6 +21,10 @@ impl Node for SelectFieldsNode {
         "Select specific fields from a context object"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let source_key = config
             .get("source_key")
@@ -69,6 +76,10 @@ impl Node for RenameFieldsNode {
         "Rename fields in a context object"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let source_key = config
             .get("source_key")
@@ -125,6 +136,10 @@ impl Node for DataFilterNode {
         "Filter array items by a condition"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let source_key = config
             .get("source_key")
@@ -136,18 +151,25 @@ impl Node for DataFilterNode {
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("data_filter requires 'output_key'"))?;
 
-        let field = config
-            .get("field")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("data_filter requires 'field'"))?;
-
-        let op = config.get("op").and_then(|v| v.as_str()).ok_or_else(|| {
-            anyhow::anyhow!(
-                "data_filter requires 'op' (eq, neq, gt, lt, gte, lte, contains, exists)"
-            )
-        })?;
-
-        let compare_value = config.get("value");
+        let conditions = match config.get("conditions") {
+            Some(_) if config.get("field").is_some() => {
+                anyhow::bail!("data_filter accepts either 'field'/'op' or 'conditions', not both")
+            }
+            Some(serde_json::Value::Array(conditions)) if !conditions.is_empty() => conditions
+                .iter()
+                .map(FilterCondition::from_config)
+                .collect::<Result<Vec<_>>>()?,
+            Some(_) => anyhow::bail!("data_filter 'conditions' must be a non-empty array"),
+            None => vec![FilterCondition::from_config(config)?],
+        };
+        let match_any = match config.get("match").and_then(|v| v.as_str()) {
+            None | Some("all") => false,
+            Some("any") => true,
+            Some(other) => anyhow::bail!(
+                "data_filter 'match' must be 'all' or 'any', got '{}'",
+                other
+            ),
+        };
 
         let source = ctx
             .get(source_key)
@@ -159,7 +181,16 @@ impl Node for DataFilterNode {
 
         let filtered: Vec<serde_json::Value> = items
             .iter()
-            .filter(|item| filter_match(item, field, op, compare_value))
+            .filter(|item| {
+                let mut results = conditions
+                    .iter()
+                    .map(|condition| filter_match(item, condition));
+                if match_any {
+                    results.any(|matched| matched)
+                } else {
+                    results.all(|matched| matched)
+                }
+            })
             .cloned()
             .collect();
 
@@ -171,24 +202,69 @@ impl Node for DataFilterNode {
     }
 }
 
+/// One `{field, op, value}` condition of a `data_filter` step.
+struct FilterCondition<'a> {
+    field: &'a str,
+    op: &'a str,
+    value: Option<&'a serde_json::Value>,
+    /// Compiled pattern for the `regex` operator, built once per step.
+    regex: Option<regex::Regex>,
+}
+
+impl<'a> FilterCondition<'a> {
+    fn from_config(config: &'a serde_json::Value) -> Result<Self> {
+        let field = config
+            .get("field")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("data_filter requires 'field'"))?;
+        let op = config.get("op").and_then(|v| v.as_str()).ok_or_else(|| {
+            anyhow::anyhow!(
+                "data_filter requires 'op' (eq, neq, gt, lt, gte, lte, contains, starts_with, ends_with, regex, in, not_in, exists, not_exists)"
+            )
+        })?;
+        let value = config.get("value");
+
+        let regex = match op {
+            "regex" => {
+                let pattern = value.and_then(|v| v.as_str()).ok_or_else(|| {
+                    anyhow::anyhow!("data_filter 'regex' requires a string 'value'")
+                })?;
+                Some(regex::Regex::new(pattern).map_err(|e| {
+                    anyhow::anyhow!("data_filter: invalid regex '{}': {}", pattern, e)
+                })?)
+            }
+            "in" | "not_in" if !value.is_some_and(|v| v.is_array()) => {
+                anyhow::bail!("data_filter '{}' requires an array 'value'", op)
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            field,
+            op,
+            value,
+            regex,
+        })
+    }
+}
+
 /// Evaluate a filter condition on a single item.
-fn filter_match(
-    item: &serde_json::Value,
-    field: &str,
-    op: &str,
-    compare_value: Option<&serde_json::Value>,
-) -> bool {
-    let field_val = item.get(field);
+fn filter_match(item: &serde_json::Value, condition: &FilterCondition) -> bool {
+    let field_val = item.get(condition.field);
 
-    match op {
+    match condition.op {
         "exists" => field_val.is_some() && !field_val.unwrap().is_null(),
         "not_exists" => field_val.is_none() || field_val.unwrap().is_null(),
-        _ => {
+        "regex" => match (field_val.and_then(|v| v.as_str()), &condition.regex) {
+            (Some(text), Some(re)) => re.is_match(text),
+            _ => false,
+        },
+        op => {
             let field_val = match field_val {
                 Some(v) => v,
                 None => return false,
             };
-            let cmp = match compare_value {
+            let cmp = match condition.value {
                 Some(v) => v,
                 None => return false,
             };
@@ -219,6 +295,20 @@ fn filter_match(
                         false
                     }
                 }
+                "starts_with" => field_val
+                    .as_str()
+                    .zip(cmp.as_str())
+                    .is_some_and(|(s, prefix)| s.starts_with(prefix)),
+                "ends_with" => field_val
+                    .as_str()
+                    .zip(cmp.as_str())
+                    .is_some_and(|(s, suffix)| s.ends_with(suffix)),
+                "in" => cmp
+                    .as_array()
+                    .is_some_and(|candidates| candidates.contains(field_val)),
+                "not_in" => cmp
+                    .as_array()
+                    .is_some_and(|candidates| !candidates.contains(field_val)),
                 _ => false,
             }
         }
@@ -237,6 +327,10 @@ impl Node for DataTransformNode {
         "Transform data by mapping and renaming fields"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let source_key = config
             .get("source_key")
@@ -309,6 +403,10 @@ impl Node for BatchNode {
         "Split an array into chunks of a specified size"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let source_key = config
             .get("source_key")
@@ -366,6 +464,10 @@ impl Node for DeduplicateNode {
         "Remove duplicate items from an array"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let source_key = config
             .get("source_key")
@@ -418,3 +520,472 @@ impl Node for DeduplicateNode {
         Ok(output)
     }
 }
+
+pub struct ZipNode;
+
+#[async_trait]
+impl Node for ZipNode {
+    fn node_type(&self) -> &str {
+        "zip"
+    }
+
+    fn description(&self) -> &str {
+        "Combine parallel arrays element-wise into objects or tuples"
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("zip requires 'output_key'"))?;
+
+        // `sources` is either a list of context keys (field names default to
+        // the keys) or a table of field name → context key.
+        let named_sources: Vec<(String, String)> = match config.get("sources") {
+            Some(serde_json::Value::Array(keys)) => keys
+                .iter()
+                .map(|k| {
+                    k.as_str()
+                        .map(|s| (s.to_string(), s.to_string()))
+                        .ok_or_else(|| anyhow::anyhow!("zip 'sources' entries must be strings"))
+                })
+                .collect::<Result<_>>()?,
+            Some(serde_json::Value::Object(map)) => {
+                let mut pairs = map
+                    .iter()
+                    .map(|(name, k)| {
+                        k.as_str()
+                            .map(|s| (name.clone(), s.to_string()))
+                            .ok_or_else(|| {
+                                anyhow::anyhow!("zip 'sources' values must be context keys")
+                            })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                pairs.sort();
+                pairs
+            }
+            _ => anyhow::bail!(
+                "zip requires 'sources' (a list of context keys or a table of field = key)"
+            ),
+        };
+        if named_sources.is_empty() {
+            anyhow::bail!("zip 'sources' must not be empty");
+        }
+
+        let names: Vec<String> = match config.get("names").and_then(|v| v.as_array()) {
+            Some(names) => {
+                if names.len() != named_sources.len() {
+                    anyhow::bail!(
+                        "zip 'names' has {} entries but 'sources' has {}",
+                        names.len(),
+                        named_sources.len()
+                    );
+                }
+                names
+                    .iter()
+                    .map(|n| {
+                        n.as_str()
+                            .map(str::to_string)
+                            .ok_or_else(|| anyhow::anyhow!("zip 'names' entries must be strings"))
+                    })
+                    .collect::<Result<_>>()?
+            }
+            None => named_sources.iter().map(|(name, _)| name.clone()).collect(),
+        };
+
+        let arrays: Vec<&Vec<serde_json::Value>> = named_sources
+            .iter()
+            .map(|(_, key)| {
+                ctx.get(key)
+                    .ok_or_else(|| anyhow::anyhow!("Key '{}' not found in context", key))?
+                    .as_array()
+                    .ok_or_else(|| anyhow::anyhow!("Value at '{}' is not an array", key))
+            })
+            .collect::<Result<_>>()?;
+
+        let shortest = arrays.iter().map(|a| a.len()).min().unwrap_or(0);
+        let longest = arrays.iter().map(|a| a.len()).max().unwrap_or(0);
+        let mismatch = config
+            .get("mismatch")
+            .and_then(|v| v.as_str())
+            .unwrap_or("truncate");
+        let len = match mismatch {
+            "truncate" => shortest,
+            "pad" => longest,
+            "error" => {
+                if shortest != longest {
+                    let lengths: Vec<String> = named_sources
+                        .iter()
+                        .zip(&arrays)
+                        .map(|((_, key), a)| format!("{}={}", key, a.len()))
+                        .collect();
+                    anyhow::bail!("zip sources differ in length: {}", lengths.join(", "));
+                }
+                shortest
+            }
+            other => anyhow::bail!(
+                "zip 'mismatch' must be 'truncate', 'pad' or 'error', got '{}'",
+                other
+            ),
+        };
+        let pad_value = config
+            .get("pad_value")
+            .cloned()
+            .unwrap_or(serde_json::Value::Null);
+
+        let mode = config
+            .get("mode")
+            .and_then(|v| v.as_str())
+            .unwrap_or("object");
+        if !matches!(mode, "object" | "merge" | "tuple") {
+            anyhow::bail!(
+                "zip 'mode' must be 'object', 'merge' or 'tuple', got '{}'",
+                mode
+            );
+        }
+
+        let mut zipped = Vec::with_capacity(len);
+        for i in 0..len {
+            let row = arrays
+                .iter()
+                .map(|a| a.get(i).cloned().unwrap_or_else(|| pad_value.clone()));
+            let item = match mode {
+                "tuple" => serde_json::Value::Array(row.collect()),
+                "merge" => {
+                    let mut merged = serde_json::Map::new();
+                    for (value, (_, key)) in row.zip(&named_sources) {
+                        match value {
+                            serde_json::Value::Object(fields) => merged.extend(fields),
+                            serde_json::Value::Null => {}
+                            _ => anyhow::bail!(
+                                "zip mode 'merge' needs objects, but '{}[{}]' is not one",
+                                key,
+                                i
+                            ),
+                        }
+                    }
+                    serde_json::Value::Object(merged)
+                }
+                _ => serde_json::Value::Object(names.iter().cloned().zip(row).collect()),
+            };
+            zipped.push(item);
+        }
+
+        let count = zipped.len();
+        let mut output = NodeOutput::new();
+        output.insert(output_key.to_string(), serde_json::Value::Array(zipped));
+        output.insert(format!("{}_count", output_key), serde_json::json!(count));
+        Ok(output)
+    }
+
+    fn consumed_keys(&self, config: &serde_json::Value) -> Vec<String> {
+        match config.get("sources") {
+            Some(serde_json::Value::Array(keys)) => keys
+                .iter()
+                .filter_map(|k| k.as_str().map(str::to_string))
+                .collect(),
+            Some(serde_json::Value::Object(map)) => map
+                .values()
+                .filter_map(|k| k.as_str().map(str::to_string))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+pub struct JsonMergeNode;
+
+#[async_trait]
+impl Node for JsonMergeNode {
+    fn node_type(&self) -> &str {
+        "json_merge"
+    }
+
+    fn description(&self) -> &str {
+        "Merge several objects left-to-right, shallow or deep"
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("json_merge requires 'output_key'"))?;
+
+        let deep = match config
+            .get("strategy")
+            .and_then(|v| v.as_str())
+            .unwrap_or("deep")
+        {
+            "deep" => true,
+            "shallow" => false,
+            other => anyhow::bail!(
+                "json_merge 'strategy' must be 'shallow' or 'deep', got '{}'",
+                other
+            ),
+        };
+        let concat_arrays = match config
+            .get("array_strategy")
+            .and_then(|v| v.as_str())
+            .unwrap_or("replace")
+        {
+            "replace" => false,
+            "concat" => true,
+            other => anyhow::bail!(
+                "json_merge 'array_strategy' must be 'replace' or 'concat', got '{}'",
+                other
+            ),
+        };
+
+        // Context keys come first, then inline `sources`, each in list order.
+        let mut objects: Vec<(String, &serde_json::Value)> = Vec::new();
+        if let Some(keys) = config.get("source_keys") {
+            let keys = keys
+                .as_array()
+                .ok_or_else(|| anyhow::anyhow!("json_merge 'source_keys' must be a list"))?;
+            for key in keys {
+                let key = key.as_str().ok_or_else(|| {
+                    anyhow::anyhow!("json_merge 'source_keys' entries must be strings")
+                })?;
+                let value = ctx
+                    .get(key)
+                    .ok_or_else(|| anyhow::anyhow!("Key '{}' not found in context", key))?;
+                objects.push((format!("'{}'", key), value));
+            }
+        }
+        if let Some(sources) = config.get("sources") {
+            let sources = sources
+                .as_array()
+                .ok_or_else(|| anyhow::anyhow!("json_merge 'sources' must be a list"))?;
+            for (i, value) in sources.iter().enumerate() {
+                objects.push((format!("sources[{}]", i + 1), value));
+            }
+        }
+        if objects.is_empty() {
+            anyhow::bail!("json_merge requires 'source_keys' or 'sources'");
+        }
+
+        let mut merged = serde_json::Map::new();
+        for (label, value) in objects {
+            let fields = value
+                .as_object()
+                .ok_or_else(|| anyhow::anyhow!("json_merge: {} is not an object", label))?;
+            if deep {
+                deep_merge_into(&mut merged, fields, concat_arrays);
+            } else {
+                merged.extend(fields.iter().map(|(k, v)| (k.clone(), v.clone())));
+            }
+        }
+
+        let mut output = NodeOutput::new();
+        output.insert(output_key.to_string(), serde_json::Value::Object(merged));
+        Ok(output)
+    }
+
+    fn consumed_keys(&self, config: &serde_json::Value) -> Vec<String> {
+        config
+            .get("source_keys")
+            .and_then(|v| v.as_array())
+            .map(|keys| {
+                keys.iter()
+                    .filter_map(|k| k.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Merge `src` into `dst`: nested objects recurse, arrays are replaced or
+/// appended, and any other value from `src` overwrites.
+fn deep_merge_into(
+    dst: &mut serde_json::Map<String, serde_json::Value>,
+    src: &serde_json::Map<String, serde_json::Value>,
+    concat_arrays: bool,
+) {
+    for (key, value) in src {
+        match (dst.get_mut(key), value) {
+            (Some(serde_json::Value::Object(existing)), serde_json::Value::Object(incoming)) => {
+                deep_merge_into(existing, incoming, concat_arrays);
+            }
+            (Some(serde_json::Value::Array(existing)), serde_json::Value::Array(incoming))
+                if concat_arrays =>
+            {
+                existing.extend(incoming.iter().cloned());
+            }
+            _ => {
+                dst.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+pub struct SortNode;
+
+#[async_trait]
+impl Node for SortNode {
+    fn node_type(&self) -> &str {
+        "sort"
+    }
+
+    fn description(&self) -> &str {
+        "Sort an array by value or by a field path"
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let source_key = config
+            .get("source_key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("sort requires 'source_key'"))?;
+
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("sort requires 'output_key'"))?;
+
+        let key_path = config.get("key").and_then(|v| v.as_str());
+        let descending = match config.get("order").and_then(|v| v.as_str()) {
+            None | Some("asc") => false,
+            Some("desc") => true,
+            Some(other) => anyhow::bail!("sort 'order' must be 'asc' or 'desc', got '{}'", other),
+        };
+
+        let source = ctx
+            .get(source_key)
+            .ok_or_else(|| anyhow::anyhow!("Key '{}' not found in context", source_key))?;
+
+        let items = source
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("Value at '{}' is not an array", source_key))?;
+
+        let sort_value = |item: &serde_json::Value| -> Option<serde_json::Value> {
+            let value = match key_path {
+                Some(path) => resolve_json_path(item, path)?,
+                None => item,
+            };
+            (!value.is_null()).then(|| value.clone())
+        };
+        let mut keyed: Vec<(Option<serde_json::Value>, &serde_json::Value)> =
+            items.iter().map(|item| (sort_value(item), item)).collect();
+
+        // `sort_by` is stable, so items with equal keys keep their order.
+        keyed.sort_by(|(a, _), (b, _)| match (a, b) {
+            (Some(a), Some(b)) => {
+                let ordering = compare_sort_values(a, b);
+                if descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            }
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+
+        let sorted: Vec<serde_json::Value> =
+            keyed.into_iter().map(|(_, item)| item.clone()).collect();
+        let mut output = NodeOutput::new();
+        output.insert(output_key.to_string(), serde_json::Value::Array(sorted));
+        Ok(output)
+    }
+}
+
+/// Order non-null sort keys. Values of different types group as booleans,
+/// then numbers, then strings, then anything else (compared as JSON text).
+fn compare_sort_values(a: &serde_json::Value, b: &serde_json::Value) -> Ordering {
+    fn rank(v: &serde_json::Value) -> u8 {
+        match v {
+            serde_json::Value::Bool(_) => 0,
+            serde_json::Value::Number(_) => 1,
+            serde_json::Value::String(_) => 2,
+            _ => 3,
+        }
+    }
+
+    match (a, b) {
+        (serde_json::Value::Bool(a), serde_json::Value::Bool(b)) => a.cmp(b),
+        (serde_json::Value::Number(a), serde_json::Value::Number(b)) => {
+            let (a, b) = (a.as_f64().unwrap_or(0.0), b.as_f64().unwrap_or(0.0));
+            a.total_cmp(&b)
+        }
+        (serde_json::Value::String(a), serde_json::Value::String(b)) => a.cmp(b),
+        _ => rank(a)
+            .cmp(&rank(b))
+            .then_with(|| a.to_string().cmp(&b.to_string())),
+    }
+}
+
+pub struct FlattenNode;
+
+#[async_trait]
+impl Node for FlattenNode {
+    fn node_type(&self) -> &str {
+        "flatten"
+    }
+
+    fn description(&self) -> &str {
+        "Flatten nested arrays to a given depth"
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let source_key = config
+            .get("source_key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("flatten requires 'source_key'"))?;
+
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("flatten requires 'output_key'"))?;
+
+        let depth = match config.get("depth") {
+            None => 1,
+            Some(serde_json::Value::String(s)) if s == "all" => usize::MAX,
+            Some(v) => v.as_u64().map(|d| d as usize).ok_or_else(|| {
+                anyhow::anyhow!("flatten 'depth' must be a non-negative integer or \"all\"")
+            })?,
+        };
+
+        let source = ctx
+            .get(source_key)
+            .ok_or_else(|| anyhow::anyhow!("Key '{}' not found in context", source_key))?;
+
+        let items = source
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("Value at '{}' is not an array", source_key))?;
+
+        let mut flat = Vec::with_capacity(items.len());
+        flatten_into(&mut flat, items, depth);
+
+        let count = flat.len();
+        let mut output = NodeOutput::new();
+        output.insert(output_key.to_string(), serde_json::Value::Array(flat));
+        output.insert(format!("{}_count", output_key), serde_json::json!(count));
+        Ok(output)
+    }
+}
+
+/// Append `items` to `out`, splicing nested arrays up to `depth` levels deep.
+fn flatten_into(out: &mut Vec<serde_json::Value>, items: &[serde_json::Value], depth: usize) {
+    for item in items {
+        match item {
+            serde_json::Value::Array(inner) if depth > 0 => flatten_into(out, inner, depth - 1),
+            other => out.push(other.clone()),
+        }
+    }
+}
@@ -4,6 +4,8 @@ use async_trait::async_trait;
 use crate::engine::types::{Context, NodeOutput};
 use crate::nodes::Node;
 
+use super::jsonata::evaluate;
+
 pub struct JsonParseNode;
 
 #[async_trait]
@@ -16,6 +18,10 @@ impl Node for JsonParseNode {
         "Parse a JSON string from context into a value"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let source_key = config
             .get("source_key")
@@ -55,6 +61,10 @@ impl Node for JsonStringifyNode {
         "Serialize a context value to a JSON string"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let source_key = config
             .get("source_key")
@@ -90,6 +100,10 @@ impl Node for JsonExtractPathNode {
         "Extract a value from JSON data using a dotted path with optional array indexes"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let source_key = config
             .get("source_key")
@@ -156,7 +170,104 @@ impl Node for JsonExtractPathNode {
     }
 }
 
-pub(super) fn resolve_json_path<'a>(
+pub struct TransformJsonNode;
+
+#[async_trait]
+impl Node for TransformJsonNode {
+    fn node_type(&self) -> &str {
+        "transform_json"
+    }
+
+    fn description(&self) -> &str {
+        "Reshape JSON with a JSONata-style expression (paths, filters, $map, object construction)"
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let expression = config
+            .get("expression")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("transform_json requires 'expression'"))?;
+
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("transform_json requires 'output_key'"))?;
+
+        // Without a source_key the expression sees the whole context.
+        let input = match config.get("source_key").and_then(|v| v.as_str()) {
+            Some(source_key) => ctx
+                .get(source_key)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("Key '{}' not found in context", source_key))?,
+            None => serde_json::to_value(ctx)?,
+        };
+
+        let result =
+            evaluate(expression, &input).map_err(|e| anyhow::anyhow!("transform_json: {}", e))?;
+
+        let mut output = NodeOutput::new();
+        output.insert(
+            output_key.to_string(),
+            result.unwrap_or(serde_json::Value::Null),
+        );
+        Ok(output)
+    }
+}
+
+pub struct JsonQueryNode;
+
+#[async_trait]
+impl Node for JsonQueryNode {
+    fn node_type(&self) -> &str {
+        "json_query"
+    }
+
+    fn description(&self) -> &str {
+        "Query JSON with a JMESPath expression (projections, filters, functions)"
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let expression = config
+            .get("expression")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("json_query requires 'expression'"))?;
+
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("json_query requires 'output_key'"))?;
+
+        // Without a source_key the expression sees the whole context.
+        let input = match config.get("source_key").and_then(|v| v.as_str()) {
+            Some(source_key) => ctx
+                .get(source_key)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("Key '{}' not found in context", source_key))?,
+            None => serde_json::to_value(ctx)?,
+        };
+
+        let compiled = jmespath::compile(expression)
+            .map_err(|e| anyhow::anyhow!("json_query: invalid expression: {}", e))?;
+        let result = compiled
+            .search(input)
+            .map_err(|e| anyhow::anyhow!("json_query: {}", e))?;
+
+        let mut output = NodeOutput::new();
+        output.insert(output_key.to_string(), serde_json::to_value(&*result)?);
+        Ok(output)
+    }
+}
+
+/// Follow a path like `user.addresses[0].city` into `value`.
+pub(crate) fn resolve_json_path<'a>(
     value: &'a serde_json::Value,
     path: &str,
 ) -> Option<&'a serde_json::Value> {
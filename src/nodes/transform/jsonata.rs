@@ -0,0 +1,886 @@
+//! A curated subset of JSONata, evaluated by the `transform_json` node.
+//!
+//! Supported: path navigation (`a.b.c`, `` `odd name` ``), filters
+//! (`items[price > 10]`), literal indexes (`items[0]`, `items[-1]`), object
+//! and array construction, arithmetic, comparison, `and`/`or`, `&` string
+//! concatenation, `cond ? a : b`, the variables `$` (current item), `$$`
+//! (input) and lambda parameters, and the functions listed in [`FUNCTIONS`].
+//! `$map` and `$filter` take a `function($v, $i) { ... }` lambda.
+//!
+//! One deliberate difference from JSONata: results keep their shape. A path
+//! over an array or a filter always yields an array, even with zero or one
+//! match, where JSONata would return nothing or the single item.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde_json::{Map, Value};
+
+/// Built-in functions, listed in error messages for unknown names.
+const FUNCTIONS: &[&str] = &[
+    "map",
+    "filter",
+    "sum",
+    "count",
+    "max",
+    "min",
+    "average",
+    "string",
+    "number",
+    "boolean",
+    "not",
+    "exists",
+    "uppercase",
+    "lowercase",
+    "trim",
+    "length",
+    "join",
+    "keys",
+    "round",
+];
+
+/// Evaluate `expression` against `input`. `None` means the expression
+/// produced no value (JSONata's `undefined`), e.g. a missing field.
+pub(crate) fn evaluate(expression: &str, input: &Value) -> Result<Option<Value>> {
+    let tokens = tokenize(expression)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.expression()?;
+    if parser.peek() != &Token::End {
+        anyhow::bail!(
+            "unexpected {} at position {}",
+            parser.peek(),
+            parser.position()
+        );
+    }
+    let env = Env {
+        root: input,
+        vars: HashMap::new(),
+    };
+    eval(&expr, input, &env)
+}
+
+// ---------------------------------------------------------------------------
+// Lexer
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Str(String),
+    /// A bare field name or keyword; `quoted` for `` `backtick` `` names,
+    /// which are never keywords.
+    Name {
+        name: String,
+        quoted: bool,
+    },
+    /// `$`, `$$` or `$name` (stored without the leading `$`).
+    Var(String),
+    Op(&'static str),
+    End,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Num(n) => write!(f, "number {}", n),
+            Token::Str(s) => write!(f, "string {:?}", s),
+            Token::Name { name, .. } => write!(f, "'{}'", name),
+            Token::Var(v) => write!(f, "'${}'", v),
+            Token::Op(op) => write!(f, "'{}'", op),
+            Token::End => write!(f, "end of expression"),
+        }
+    }
+}
+
+const OPERATORS: &[&str] = &[
+    "!=", "<=", ">=", ".", "[", "]", "{", "}", "(", ")", ",", ":", "?", "&", "+", "-", "*", "/",
+    "%", "=", "<", ">",
+];
+
+/// Split an expression into tokens, each paired with its byte offset.
+fn tokenize(src: &str) -> Result<Vec<(Token, usize)>> {
+    let mut tokens = Vec::new();
+    let chars: Vec<(usize, char)> = src.char_indices().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let (at, c) = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if src[at..].starts_with("/*") {
+            let end = src[at + 2..]
+                .find("*/")
+                .ok_or_else(|| anyhow::anyhow!("unterminated comment at position {}", at))?;
+            let resume = at + 2 + end + 2;
+            while i < chars.len() && chars[i].0 < resume {
+                i += 1;
+            }
+            continue;
+        }
+        if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].1.is_ascii_digit()
+                    || chars[i].1 == '.'
+                        && chars.get(i + 1).is_some_and(|(_, n)| n.is_ascii_digit()))
+            {
+                i += 1;
+            }
+            if i < chars.len() && matches!(chars[i].1, 'e' | 'E') {
+                i += 1;
+                if i < chars.len() && matches!(chars[i].1, '+' | '-') {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i].1.is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            let end = chars.get(i).map_or(src.len(), |(pos, _)| *pos);
+            let text = &src[chars[start].0..end];
+            let n = text
+                .parse::<f64>()
+                .map_err(|_| anyhow::anyhow!("invalid number '{}' at position {}", text, at))?;
+            tokens.push((Token::Num(n), at));
+            continue;
+        }
+        if c == '"' || c == '\'' {
+            let mut s = String::new();
+            i += 1;
+            loop {
+                let Some(&(_, ch)) = chars.get(i) else {
+                    anyhow::bail!("unterminated string at position {}", at);
+                };
+                i += 1;
+                match ch {
+                    ch if ch == c => break,
+                    '\\' => {
+                        let Some(&(_, esc)) = chars.get(i) else {
+                            anyhow::bail!("unterminated string at position {}", at);
+                        };
+                        i += 1;
+                        s.push(match esc {
+                            'n' => '\n',
+                            't' => '\t',
+                            'r' => '\r',
+                            other => other,
+                        });
+                    }
+                    other => s.push(other),
+                }
+            }
+            tokens.push((Token::Str(s), at));
+            continue;
+        }
+        if c == '`' {
+            let end = src[at + 1..]
+                .find('`')
+                .ok_or_else(|| anyhow::anyhow!("unterminated `name` at position {}", at))?;
+            let name = src[at + 1..at + 1 + end].to_string();
+            let resume = at + 1 + end + 1;
+            while i < chars.len() && chars[i].0 < resume {
+                i += 1;
+            }
+            tokens.push((Token::Name { name, quoted: true }, at));
+            continue;
+        }
+        if c == '$' {
+            i += 1;
+            let start = i;
+            if chars.get(i).is_some_and(|(_, n)| *n == '$') {
+                i += 1;
+            } else {
+                while i < chars.len() && is_name_char(chars[i].1) {
+                    i += 1;
+                }
+            }
+            let name: String = chars[start..i].iter().map(|(_, ch)| ch).collect();
+            tokens.push((Token::Var(name), at));
+            continue;
+        }
+        if is_name_start(c) {
+            let start = i;
+            while i < chars.len() && is_name_char(chars[i].1) {
+                i += 1;
+            }
+            let name: String = chars[start..i].iter().map(|(_, ch)| ch).collect();
+            tokens.push((
+                Token::Name {
+                    name,
+                    quoted: false,
+                },
+                at,
+            ));
+            continue;
+        }
+        let op = OPERATORS
+            .iter()
+            .find(|op| src[at..].starts_with(**op))
+            .ok_or_else(|| anyhow::anyhow!("unexpected character '{}' at position {}", c, at))?;
+        i += op.chars().count();
+        tokens.push((Token::Op(op), at));
+    }
+    tokens.push((Token::End, src.len()));
+    Ok(tokens)
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// ---------------------------------------------------------------------------
+// Parser
+// ---------------------------------------------------------------------------
+
+#[derive(Debug)]
+enum Expr {
+    Literal(Value),
+    /// Field of the current item.
+    Field(String),
+    Var(String),
+    /// `lhs.rhs`: evaluate `rhs` once per item of `lhs`.
+    Path(Box<Expr>, Box<Expr>),
+    Filter(Box<Expr>, Box<Expr>),
+    Index(Box<Expr>, i64),
+    Object(Vec<(Expr, Expr)>),
+    Array(Vec<Expr>),
+    Negate(Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Condition(Box<Expr>, Box<Expr>, Option<Box<Expr>>),
+    Call(String, Vec<Expr>),
+    Lambda(Vec<String>, Box<Expr>),
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn position(&self) -> usize {
+        self.tokens[self.pos].1
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].0.clone();
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn is_op(&self, op: &str) -> bool {
+        matches!(self.peek(), Token::Op(o) if *o == op)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Token::Name { name, quoted: false } if name == keyword)
+    }
+
+    fn expect(&mut self, op: &str) -> Result<()> {
+        if !self.is_op(op) {
+            anyhow::bail!(
+                "expected '{}' but found {} at position {}",
+                op,
+                self.peek(),
+                self.position()
+            );
+        }
+        self.next();
+        Ok(())
+    }
+
+    fn expression(&mut self) -> Result<Expr> {
+        let condition = self.or()?;
+        if !self.is_op("?") {
+            return Ok(condition);
+        }
+        self.next();
+        let then = self.expression()?;
+        let otherwise = if self.is_op(":") {
+            self.next();
+            Some(Box::new(self.expression()?))
+        } else {
+            None
+        };
+        Ok(Expr::Condition(
+            Box::new(condition),
+            Box::new(then),
+            otherwise,
+        ))
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut lhs = self.and()?;
+        while self.is_keyword("or") {
+            self.next();
+            lhs = Expr::Binary("or", Box::new(lhs), Box::new(self.and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut lhs = self.comparison()?;
+        while self.is_keyword("and") {
+            self.next();
+            lhs = Expr::Binary("and", Box::new(lhs), Box::new(self.comparison()?));
+        }
+        Ok(lhs)
+    }
+
+    fn comparison(&mut self) -> Result<Expr> {
+        let lhs = self.concat()?;
+        let op = match self.peek() {
+            Token::Op(op @ ("=" | "!=" | "<" | "<=" | ">" | ">=")) => *op,
+            Token::Name {
+                name,
+                quoted: false,
+            } if name == "in" => "in",
+            _ => return Ok(lhs),
+        };
+        self.next();
+        Ok(Expr::Binary(op, Box::new(lhs), Box::new(self.concat()?)))
+    }
+
+    fn concat(&mut self) -> Result<Expr> {
+        let mut lhs = self.additive()?;
+        while self.is_op("&") {
+            self.next();
+            lhs = Expr::Binary("&", Box::new(lhs), Box::new(self.additive()?));
+        }
+        Ok(lhs)
+    }
+
+    fn additive(&mut self) -> Result<Expr> {
+        let mut lhs = self.multiplicative()?;
+        while let Token::Op(op @ ("+" | "-")) = self.peek() {
+            let op = *op;
+            self.next();
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.multiplicative()?));
+        }
+        Ok(lhs)
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr> {
+        let mut lhs = self.unary()?;
+        while let Token::Op(op @ ("*" | "/" | "%")) = self.peek() {
+            let op = *op;
+            self.next();
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.is_op("-") {
+            self.next();
+            return Ok(Expr::Negate(Box::new(self.unary()?)));
+        }
+        self.path()
+    }
+
+    fn path(&mut self) -> Result<Expr> {
+        let mut lhs = self.postfix()?;
+        while self.is_op(".") {
+            self.next();
+            lhs = Expr::Path(Box::new(lhs), Box::new(self.postfix()?));
+        }
+        Ok(lhs)
+    }
+
+    fn postfix(&mut self) -> Result<Expr> {
+        let mut expr = self.primary()?;
+        while self.is_op("[") {
+            self.next();
+            expr = match self.literal_index() {
+                Some(index) => Expr::Index(Box::new(expr), index),
+                None => Expr::Filter(Box::new(expr), Box::new(self.expression()?)),
+            };
+            self.expect("]")?;
+        }
+        Ok(expr)
+    }
+
+    /// Consume `n]` or `-n]` (without the `]`) when the brackets hold only an
+    /// integer literal.
+    fn literal_index(&mut self) -> Option<i64> {
+        let (negative, at) = match self.peek() {
+            Token::Op("-") => (true, self.pos + 1),
+            _ => (false, self.pos),
+        };
+        let Token::Num(n) = self.tokens.get(at)?.0 else {
+            return None;
+        };
+        if n.fract() != 0.0 || !matches!(self.tokens.get(at + 1)?.0, Token::Op("]")) {
+            return None;
+        }
+        self.pos = at + 1;
+        Some(if negative { -(n as i64) } else { n as i64 })
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        let at = self.position();
+        match self.next() {
+            Token::Num(n) => Ok(Expr::Literal(number(n)?)),
+            Token::Str(s) => Ok(Expr::Literal(Value::String(s))),
+            Token::Name {
+                name,
+                quoted: false,
+            } => match name.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                "function" => self.lambda(),
+                _ => Ok(Expr::Field(name)),
+            },
+            Token::Name { name, quoted: true } => Ok(Expr::Field(name)),
+            Token::Var(name) => {
+                if !self.is_op("(") {
+                    return Ok(Expr::Var(name));
+                }
+                self.next();
+                let mut args = Vec::new();
+                while !self.is_op(")") {
+                    args.push(self.expression()?);
+                    if !self.is_op(")") {
+                        self.expect(",")?;
+                    }
+                }
+                self.next();
+                Ok(Expr::Call(name, args))
+            }
+            Token::Op("(") => {
+                let expr = self.expression()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Op("{") => {
+                let mut pairs = Vec::new();
+                while !self.is_op("}") {
+                    let key = self.expression()?;
+                    self.expect(":")?;
+                    pairs.push((key, self.expression()?));
+                    if !self.is_op("}") {
+                        self.expect(",")?;
+                    }
+                }
+                self.next();
+                Ok(Expr::Object(pairs))
+            }
+            Token::Op("[") => {
+                let mut items = Vec::new();
+                while !self.is_op("]") {
+                    items.push(self.expression()?);
+                    if !self.is_op("]") {
+                        self.expect(",")?;
+                    }
+                }
+                self.next();
+                Ok(Expr::Array(items))
+            }
+            other => anyhow::bail!("unexpected {} at position {}", other, at),
+        }
+    }
+
+    /// Parse the rest of `function($a, $b) { body }`.
+    fn lambda(&mut self) -> Result<Expr> {
+        self.expect("(")?;
+        let mut params = Vec::new();
+        while !self.is_op(")") {
+            let at = self.position();
+            match self.next() {
+                Token::Var(name) if !name.is_empty() && name != "$" => params.push(name),
+                other => anyhow::bail!(
+                    "expected a $parameter but found {} at position {}",
+                    other,
+                    at
+                ),
+            }
+            if !self.is_op(")") {
+                self.expect(",")?;
+            }
+        }
+        self.next();
+        self.expect("{")?;
+        let body = self.expression()?;
+        self.expect("}")?;
+        Ok(Expr::Lambda(params, Box::new(body)))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Evaluator
+// ---------------------------------------------------------------------------
+
+struct Env<'a> {
+    root: &'a Value,
+    vars: HashMap<String, Value>,
+}
+
+fn eval(expr: &Expr, item: &Value, env: &Env) -> Result<Option<Value>> {
+    Ok(match expr {
+        Expr::Literal(v) => Some(v.clone()),
+        Expr::Field(name) => field(item, name),
+        Expr::Var(name) => match name.as_str() {
+            "" => Some(item.clone()),
+            "$" => Some(env.root.clone()),
+            other => env.vars.get(other).cloned(),
+        },
+        Expr::Path(lhs, rhs) => match eval(lhs, item, env)? {
+            None => None,
+            Some(Value::Array(items)) => {
+                let mut out = Vec::new();
+                for each in &items {
+                    match eval(rhs, each, env)? {
+                        Some(Value::Array(nested)) if !matches!(**rhs, Expr::Array(_)) => {
+                            out.extend(nested)
+                        }
+                        Some(v) => out.push(v),
+                        None => {}
+                    }
+                }
+                Some(Value::Array(out))
+            }
+            Some(v) => eval(rhs, &v, env)?,
+        },
+        Expr::Filter(lhs, predicate) => match eval(lhs, item, env)? {
+            None => None,
+            Some(v) => {
+                let mut kept = Vec::new();
+                for each in as_items(v) {
+                    if truthy(eval(predicate, &each, env)?.as_ref()) {
+                        kept.push(each);
+                    }
+                }
+                Some(Value::Array(kept))
+            }
+        },
+        Expr::Index(lhs, index) => eval(lhs, item, env)?.and_then(|v| {
+            let items = as_items(v);
+            let at = if *index < 0 {
+                items.len() as i64 + index
+            } else {
+                *index
+            };
+            usize::try_from(at)
+                .ok()
+                .and_then(|at| items.into_iter().nth(at))
+        }),
+        Expr::Object(pairs) => {
+            let mut map = Map::new();
+            for (key, value) in pairs {
+                let key = match eval(key, item, env)? {
+                    Some(Value::String(s)) => s,
+                    Some(other) => anyhow::bail!("object keys must be strings, got {}", other),
+                    None => continue,
+                };
+                if let Some(value) = eval(value, item, env)? {
+                    map.insert(key, value);
+                }
+            }
+            Some(Value::Object(map))
+        }
+        Expr::Array(items) => {
+            let mut out = Vec::new();
+            for each in items {
+                if let Some(v) = eval(each, item, env)? {
+                    out.push(v);
+                }
+            }
+            Some(Value::Array(out))
+        }
+        Expr::Negate(inner) => match eval(inner, item, env)? {
+            None => None,
+            Some(v) => Some(number(-as_number(&v, "-")?)?),
+        },
+        Expr::Binary(op, lhs, rhs) => binary(op, lhs, rhs, item, env)?,
+        Expr::Condition(condition, then, otherwise) => {
+            if truthy(eval(condition, item, env)?.as_ref()) {
+                eval(then, item, env)?
+            } else {
+                match otherwise {
+                    Some(otherwise) => eval(otherwise, item, env)?,
+                    None => None,
+                }
+            }
+        }
+        Expr::Call(name, args) => call(name, args, item, env)?,
+        Expr::Lambda(..) => {
+            anyhow::bail!("functions can only be passed to $map or $filter")
+        }
+    })
+}
+
+/// Look up `name` on an object, or on every object in an array.
+fn field(item: &Value, name: &str) -> Option<Value> {
+    match item {
+        Value::Object(map) => map.get(name).cloned(),
+        Value::Array(items) => {
+            let mut out = Vec::new();
+            for each in items {
+                match field(each, name) {
+                    Some(Value::Array(nested)) => out.extend(nested),
+                    Some(v) => out.push(v),
+                    None => {}
+                }
+            }
+            Some(Value::Array(out))
+        }
+        _ => None,
+    }
+}
+
+fn binary(op: &str, lhs: &Expr, rhs: &Expr, item: &Value, env: &Env) -> Result<Option<Value>> {
+    if op == "and" || op == "or" {
+        let left = truthy(eval(lhs, item, env)?.as_ref());
+        let result = match op {
+            "and" => left && truthy(eval(rhs, item, env)?.as_ref()),
+            _ => left || truthy(eval(rhs, item, env)?.as_ref()),
+        };
+        return Ok(Some(Value::Bool(result)));
+    }
+
+    let left = eval(lhs, item, env)?;
+    let right = eval(rhs, item, env)?;
+    if op == "&" {
+        let text = format!(
+            "{}{}",
+            left.as_ref().map(to_text).unwrap_or_default(),
+            right.as_ref().map(to_text).unwrap_or_default()
+        );
+        return Ok(Some(Value::String(text)));
+    }
+
+    let (Some(left), Some(right)) = (left, right) else {
+        // Comparisons with a missing value are false; arithmetic is undefined.
+        return Ok(
+            matches!(op, "=" | "!=" | "<" | "<=" | ">" | ">=" | "in").then_some(Value::Bool(false))
+        );
+    };
+
+    let result = match op {
+        "=" => Value::Bool(equal(&left, &right)),
+        "!=" => Value::Bool(!equal(&left, &right)),
+        "in" => Value::Bool(as_items(right).iter().any(|v| equal(&left, v))),
+        "<" | "<=" | ">" | ">=" => {
+            let ordering = match (&left, &right) {
+                (Value::Number(a), Value::Number(b)) => a.as_f64().partial_cmp(&b.as_f64()),
+                (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+                _ => None,
+            }
+            .ok_or_else(|| anyhow::anyhow!("cannot compare {} and {}", left, right))?;
+            Value::Bool(match op {
+                "<" => ordering.is_lt(),
+                "<=" => ordering.is_le(),
+                ">" => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            })
+        }
+        _ => {
+            let (a, b) = (as_number(&left, op)?, as_number(&right, op)?);
+            if matches!(op, "/" | "%") && b == 0.0 {
+                anyhow::bail!("division by zero");
+            }
+            number(match op {
+                "+" => a + b,
+                "-" => a - b,
+                "*" => a * b,
+                "/" => a / b,
+                _ => a % b,
+            })?
+        }
+    };
+    Ok(Some(result))
+}
+
+fn call(name: &str, args: &[Expr], item: &Value, env: &Env) -> Result<Option<Value>> {
+    if !FUNCTIONS.contains(&name) {
+        anyhow::bail!(
+            "unknown function ${} (supported: ${})",
+            name,
+            FUNCTIONS.join(", $")
+        );
+    }
+
+    if name == "map" || name == "filter" {
+        let (Some(source), Some(Expr::Lambda(params, body))) = (args.first(), args.get(1)) else {
+            anyhow::bail!(
+                "${} expects an array and a function($v, $i) {{ ... }}",
+                name
+            );
+        };
+        let Some(source) = eval(source, item, env)? else {
+            return Ok(None);
+        };
+        let items = as_items(source);
+        let mut out = Vec::new();
+        for (idx, each) in items.iter().enumerate() {
+            let mut vars = env.vars.clone();
+            let bound = [each.clone(), Value::from(idx), Value::Array(items.clone())];
+            for (param, value) in params.iter().zip(bound) {
+                vars.insert(param.clone(), value);
+            }
+            let scope = Env {
+                root: env.root,
+                vars,
+            };
+            let result = eval(body, item, &scope)?;
+            if name == "filter" {
+                if truthy(result.as_ref()) {
+                    out.push(each.clone());
+                }
+            } else if let Some(v) = result {
+                out.push(v);
+            }
+        }
+        return Ok(Some(Value::Array(out)));
+    }
+
+    let mut values = Vec::with_capacity(args.len());
+    for arg in args {
+        values.push(eval(arg, item, env)?);
+    }
+    let first = values.first().cloned().flatten();
+
+    Ok(match name {
+        "exists" => Some(Value::Bool(first.is_some())),
+        "not" => Some(Value::Bool(!truthy(first.as_ref()))),
+        "boolean" => first.map(|v| Value::Bool(truthy(Some(&v)))),
+        "count" => Some(Value::from(first.map_or(0, |v| as_items(v).len()))),
+        "sum" | "max" | "min" | "average" => {
+            let Some(v) = first else { return Ok(None) };
+            let numbers = as_items(v)
+                .iter()
+                .map(|n| as_number(n, &format!("${}", name)))
+                .collect::<Result<Vec<_>>>()?;
+            let result = match name {
+                "sum" => Some(numbers.iter().sum()),
+                "max" => numbers.iter().copied().reduce(f64::max),
+                "min" => numbers.iter().copied().reduce(f64::min),
+                _ => (!numbers.is_empty())
+                    .then(|| numbers.iter().sum::<f64>() / numbers.len() as f64),
+            };
+            result.map(number).transpose()?
+        }
+        "string" => first.map(|v| Value::String(to_text(&v))),
+        "number" => {
+            match first {
+                None => None,
+                Some(Value::Number(n)) => Some(Value::Number(n)),
+                Some(Value::Bool(b)) => Some(Value::from(b as i64)),
+                Some(Value::String(s)) => Some(number(s.trim().parse::<f64>().map_err(|_| {
+                    anyhow::anyhow!("$number: cannot convert {:?} to a number", s)
+                })?)?),
+                Some(other) => anyhow::bail!("$number: cannot convert {} to a number", other),
+            }
+        }
+        "uppercase" | "lowercase" | "trim" | "length" => match first {
+            None => None,
+            Some(Value::String(s)) => Some(match name {
+                "uppercase" => Value::String(s.to_uppercase()),
+                "lowercase" => Value::String(s.to_lowercase()),
+                "trim" => Value::String(s.split_whitespace().collect::<Vec<_>>().join(" ")),
+                _ => Value::from(s.chars().count()),
+            }),
+            Some(other) => anyhow::bail!("${} expects a string, got {}", name, other),
+        },
+        "join" => {
+            let Some(v) = first else { return Ok(None) };
+            let separator = match values.get(1).cloned().flatten() {
+                Some(Value::String(s)) => s,
+                None => String::new(),
+                Some(other) => anyhow::bail!("$join separator must be a string, got {}", other),
+            };
+            let parts = as_items(v)
+                .into_iter()
+                .map(|part| match part {
+                    Value::String(s) => Ok(s),
+                    other => Err(anyhow::anyhow!("$join expects strings, got {}", other)),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Some(Value::String(parts.join(&separator)))
+        }
+        "keys" => match first {
+            Some(Value::Object(map)) => Some(Value::Array(
+                map.keys().cloned().map(Value::String).collect(),
+            )),
+            _ => None,
+        },
+        "round" => {
+            let Some(v) = first else { return Ok(None) };
+            let digits = match values.get(1).cloned().flatten() {
+                Some(d) => as_number(&d, "$round")? as i32,
+                None => 0,
+            };
+            let scale = 10f64.powi(digits);
+            Some(number(
+                (as_number(&v, "$round")? * scale).round_ties_even() / scale,
+            )?)
+        }
+        _ => unreachable!("every name in FUNCTIONS is handled"),
+    })
+}
+
+/// Treat a non-array value as a one-item array.
+fn as_items(value: Value) -> Vec<Value> {
+    match value {
+        Value::Array(items) => items,
+        other => vec![other],
+    }
+}
+
+/// JSONata truthiness: `false`, `null`, `0`, `""`, empty containers and
+/// missing values are false; an array is true if any item is.
+fn truthy(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => false,
+        Some(Value::Bool(b)) => *b,
+        Some(Value::Number(n)) => n.as_f64().is_some_and(|n| n != 0.0),
+        Some(Value::String(s)) => !s.is_empty(),
+        Some(Value::Array(items)) => items.iter().any(|v| truthy(Some(v))),
+        Some(Value::Object(map)) => !map.is_empty(),
+    }
+}
+
+/// JSON equality that treats `1` and `1.0` as equal.
+fn equal(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(x), Some(y)) => x == y,
+        _ => a == b,
+    }
+}
+
+fn as_number(value: &Value, op: &str) -> Result<f64> {
+    value
+        .as_f64()
+        .ok_or_else(|| anyhow::anyhow!("'{}' expects numbers, got {}", op, value))
+}
+
+/// Convert to a JSON number, keeping whole numbers as integers.
+fn number(n: f64) -> Result<Value> {
+    if !n.is_finite() {
+        anyhow::bail!("result {} is not a valid JSON number", n);
+    }
+    if n.fract() == 0.0 && n.abs() < 9_007_199_254_740_992.0 {
+        Ok(Value::from(n as i64))
+    } else {
+        Ok(Value::from(n))
+    }
+}
+
+/// String form used by `&` and `$string`: strings as-is, other values as
+/// compact JSON.
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
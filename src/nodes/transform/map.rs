@@ -0,0 +1,85 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use mlua::prelude::*;
+
+use crate::engine::types::{Context, NodeOutput};
+use crate::lua::sandbox;
+use crate::nodes::Node;
+use crate::nodes::utility::code::{json_value_to_lua_table, lua_value_to_json};
+use crate::util::limits::{LuaExecutionLimits, apply_lua_limits, collect_lua_garbage};
+
+pub struct MapNode;
+
+#[async_trait]
+impl Node for MapNode {
+    fn node_type(&self) -> &str {
+        "map"
+    }
+
+    fn description(&self) -> &str {
+        "Map each array item through a Lua expression"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let source_key = config
+            .get("source_key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("map requires 'source_key'"))?;
+
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("map requires 'output_key'"))?;
+
+        // An expression is the body of an implicit `return`; a source chunk
+        // returns its value explicitly.
+        let chunk = match (
+            config.get("expression").and_then(|v| v.as_str()),
+            config.get("source").and_then(|v| v.as_str()),
+        ) {
+            (Some(_), Some(_)) => {
+                anyhow::bail!("map accepts either 'expression' or 'source', not both")
+            }
+            (Some(expression), None) => format!("return ({})", expression),
+            (None, Some(source)) => source.to_string(),
+            (None, None) => anyhow::bail!("map requires 'expression' or 'source'"),
+        };
+
+        let items = ctx
+            .get(source_key)
+            .ok_or_else(|| anyhow::anyhow!("Key '{}' not found in context", source_key))?
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("Value at '{}' is not an array", source_key))?;
+
+        let lua = Lua::new();
+        let limits = LuaExecutionLimits::from_env();
+        apply_lua_limits(&lua, limits)?;
+        sandbox::setup_sandbox(&lua, ctx)?;
+        let func: LuaFunction = lua
+            .load(chunk)
+            .set_name("<map>")
+            .into_function()
+            .map_err(|e| anyhow::anyhow!("map: failed to compile Lua: {}", e))?;
+
+        let globals = lua.globals();
+        let mut results = Vec::with_capacity(items.len());
+        for (i, item) in items.iter().enumerate() {
+            globals.set("item", json_value_to_lua_table(&lua, item)?)?;
+            globals.set("index", (i + 1) as i64)?;
+            let value = func.call::<LuaValue>(()).map_err(|e| {
+                anyhow::anyhow!("map failed on item {} (index {}): {}", i, i + 1, e)
+            })?;
+            results.push(lua_value_to_json(&value)?);
+        }
+
+        collect_lua_garbage(&lua, limits)?;
+
+        let mut output = NodeOutput::new();
+        output.insert(
+            format!("{}_count", output_key),
+            serde_json::json!(results.len()),
+        );
+        output.insert(output_key.to_string(), serde_json::Value::Array(results));
+        Ok(output)
+    }
+}
@@ -1,15 +1,21 @@
 mod csv;
 mod data;
 mod json;
+mod jsonata;
+mod map;
 mod xml;
 mod yaml;
 
 pub use csv::{CsvParseNode, CsvStringifyNode};
 pub use data::{
-    BatchNode, DataFilterNode, DataTransformNode, DeduplicateNode, RenameFieldsNode,
-    SelectFieldsNode,
+    BatchNode, DataFilterNode, DataTransformNode, DeduplicateNode, FlattenNode, JsonMergeNode,
+    RenameFieldsNode, SelectFieldsNode, SortNode, ZipNode,
 };
-pub use json::{JsonExtractPathNode, JsonParseNode, JsonStringifyNode};
+pub(crate) use json::resolve_json_path;
+pub use json::{
+    JsonExtractPathNode, JsonParseNode, JsonQueryNode, JsonStringifyNode, TransformJsonNode,
+};
+pub use map::MapNode;
 pub use xml::{XmlParseNode, XmlStringifyNode};
 pub use yaml::{YamlParseNode, YamlStringifyNode};
 
@@ -20,14 +26,21 @@ pub fn register_all(registry: &mut NodeRegistry) {
     registry.register(Arc::new(JsonParseNode));
     registry.register(Arc::new(JsonStringifyNode));
     registry.register(Arc::new(JsonExtractPathNode));
+    registry.register(Arc::new(TransformJsonNode));
+    registry.register(Arc::new(JsonQueryNode));
     registry.register(Arc::new(CsvParseNode));
     registry.register(Arc::new(CsvStringifyNode));
     registry.register(Arc::new(SelectFieldsNode));
     registry.register(Arc::new(RenameFieldsNode));
     registry.register(Arc::new(DataFilterNode));
     registry.register(Arc::new(DataTransformNode));
+    registry.register(Arc::new(MapNode));
     registry.register(Arc::new(BatchNode));
     registry.register(Arc::new(DeduplicateNode));
+    registry.register(Arc::new(ZipNode));
+    registry.register(Arc::new(JsonMergeNode));
+    registry.register(Arc::new(SortNode));
+    registry.register(Arc::new(FlattenNode));
     registry.register(Arc::new(XmlParseNode));
     registry.register(Arc::new(XmlStringifyNode));
     registry.register(Arc::new(YamlParseNode));
@@ -2,7 +2,8 @@ use anyhow::Result;
 use async_trait::async_trait;
 use quick_xml::Reader;
 use quick_xml::XmlVersion;
-use quick_xml::events::Event;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
 
 use crate::engine::types::{Context, NodeOutput};
 use crate::lua::interpolate::interpolate_ctx;
@@ -20,15 +21,24 @@ impl Node for XmlParseNode {
         "Parse XML string into a JSON object"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let output_key = config
             .get("output_key")
             .and_then(|v| v.as_str())
             .unwrap_or("xml_data");
 
+        let strip_namespaces = config
+            .get("strip_namespaces")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
         let input = get_input(config, ctx, "xml_parse")?;
 
-        let parsed = parse_xml_to_json(&input)?;
+        let parsed = parse_xml_to_json(&input, strip_namespaces)?;
 
         let mut output = NodeOutput::new();
         output.insert(output_key.to_string(), parsed);
@@ -48,6 +58,10 @@ impl Node for XmlStringifyNode {
         "Convert a JSON value to an XML string"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let source_key = config
             .get("source_key")
@@ -64,6 +78,11 @@ impl Node for XmlStringifyNode {
             .and_then(|v| v.as_str())
             .unwrap_or("root");
 
+        let unwrap_root = config
+            .get("unwrap_root")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
         let pretty = config
             .get("pretty")
             .and_then(|v| v.as_bool())
@@ -73,6 +92,23 @@ impl Node for XmlStringifyNode {
             .get(source_key)
             .ok_or_else(|| anyhow::anyhow!("Key '{}' not found in context", source_key))?;
 
+        // `unwrap_root` takes the root element from a single-key object,
+        // which is the shape `xml_parse` produces.
+        let (root_tag, source) = if unwrap_root {
+            match source.as_object() {
+                Some(map) if map.len() == 1 => {
+                    let (tag, value) = map.iter().next().expect("map has one entry");
+                    (tag.as_str(), value)
+                }
+                _ => anyhow::bail!(
+                    "xml_stringify 'unwrap_root' needs an object with exactly one key at '{}'",
+                    source_key
+                ),
+            }
+        } else {
+            (root_tag, source)
+        };
+
         let xml = json_to_xml(source, root_tag, pretty)?;
 
         let mut output = NodeOutput::new();
@@ -114,9 +150,11 @@ fn get_input(config: &serde_json::Value, ctx: &Context, node_name: &str) -> Resu
 
 /// Parse an XML string into a serde_json::Value tree.
 /// Elements become objects, attributes are prefixed with `@`, text content uses `#text`.
-fn parse_xml_to_json(xml: &str) -> Result<serde_json::Value> {
+/// Qualified names (`soap:Body`, `@xml:lang`) are kept as written unless
+/// `strip_namespaces` is set, which keeps local names and drops `xmlns`
+/// declarations.
+fn parse_xml_to_json(xml: &str, strip_namespaces: bool) -> Result<serde_json::Value> {
     let mut reader = Reader::from_str(xml);
-    reader.config_mut().trim_text(true);
 
     let mut stack: Vec<(String, serde_json::Map<String, serde_json::Value>)> = Vec::new();
     let mut root: Option<(String, serde_json::Value)> = None;
@@ -124,27 +162,11 @@ fn parse_xml_to_json(xml: &str) -> Result<serde_json::Value> {
     loop {
         match reader.read_event() {
             Ok(Event::Start(ref e)) => {
-                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
-                let mut map = serde_json::Map::new();
-
-                // Add attributes with @ prefix
-                for attr in e.attributes().flatten() {
-                    let attr_name = format!("@{}", String::from_utf8_lossy(attr.key.as_ref()));
-                    let attr_value = String::from_utf8_lossy(&attr.value).to_string();
-                    map.insert(attr_name, serde_json::Value::String(attr_value));
-                }
-
+                let (name, map) = element_parts(e, strip_namespaces);
                 stack.push((name, map));
             }
             Ok(Event::Empty(ref e)) => {
-                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
-                let mut map = serde_json::Map::new();
-
-                for attr in e.attributes().flatten() {
-                    let attr_name = format!("@{}", String::from_utf8_lossy(attr.key.as_ref()));
-                    let attr_value = String::from_utf8_lossy(&attr.value).to_string();
-                    map.insert(attr_name, serde_json::Value::String(attr_value));
-                }
+                let (name, map) = element_parts(e, strip_namespaces);
 
                 let child_value = if map.is_empty() {
                     serde_json::Value::Null
@@ -161,16 +183,43 @@ fn parse_xml_to_json(xml: &str) -> Result<serde_json::Value> {
             Ok(Event::Text(ref e)) => {
                 let text = e
                     .xml_content(XmlVersion::Implicit1_0)
-                    .map_err(|err| anyhow::anyhow!("XML text decode error: {}", err))?
-                    .to_string();
-                if !text.is_empty()
-                    && let Some((_, map)) = stack.last_mut()
+                    .map_err(|err| anyhow::anyhow!("XML text decode error: {}", err))?;
+                append_text(&mut stack, &text);
+            }
+            Ok(Event::CData(ref e)) => {
+                append_text(&mut stack, &String::from_utf8_lossy(e));
+            }
+            // Entity and character references arrive between text events.
+            Ok(Event::GeneralRef(ref e)) => {
+                let text = match e
+                    .resolve_char_ref()
+                    .map_err(|err| anyhow::anyhow!("XML character reference error: {}", err))?
                 {
-                    map.insert("#text".to_string(), serde_json::Value::String(text));
-                }
+                    Some(ch) => ch.to_string(),
+                    None => {
+                        let name = e
+                            .decode()
+                            .map_err(|err| anyhow::anyhow!("XML text decode error: {}", err))?;
+                        resolve_predefined_entity(&name)
+                            .map(str::to_string)
+                            .unwrap_or_else(|| format!("&{};", name))
+                    }
+                };
+                append_text(&mut stack, &text);
             }
             Ok(Event::End(_)) => {
-                if let Some((name, map)) = stack.pop() {
+                if let Some((name, mut map)) = stack.pop() {
+                    // Text is collected untrimmed so spaces around entities
+                    // survive; trim once the element is complete.
+                    if let Some(serde_json::Value::String(text)) = map.get("#text") {
+                        let trimmed = text.trim();
+                        if trimmed.is_empty() {
+                            map.remove("#text");
+                        } else if trimmed.len() != text.len() {
+                            let trimmed = trimmed.to_string();
+                            map.insert("#text".to_string(), serde_json::Value::String(trimmed));
+                        }
+                    }
                     // If the map has only #text, simplify to just the string value
                     let value = simplify_element(map);
 
@@ -197,6 +246,54 @@ fn parse_xml_to_json(xml: &str) -> Result<serde_json::Value> {
     }
 }
 
+/// Append character data to the `#text` of the innermost open element.
+fn append_text(stack: &mut [(String, serde_json::Map<String, serde_json::Value>)], text: &str) {
+    let Some((_, map)) = stack.last_mut() else {
+        return;
+    };
+    match map.get_mut("#text") {
+        Some(serde_json::Value::String(existing)) => existing.push_str(text),
+        _ => {
+            map.insert(
+                "#text".to_string(),
+                serde_json::Value::String(text.to_string()),
+            );
+        }
+    }
+}
+
+/// Tag name and `@`-prefixed attributes of a start or empty element.
+fn element_parts(
+    e: &BytesStart,
+    strip_namespaces: bool,
+) -> (String, serde_json::Map<String, serde_json::Value>) {
+    let name = if strip_namespaces {
+        String::from_utf8_lossy(e.local_name().as_ref()).to_string()
+    } else {
+        String::from_utf8_lossy(e.name().as_ref()).to_string()
+    };
+
+    let mut map = serde_json::Map::new();
+    for attr in e.attributes().flatten() {
+        let key = if strip_namespaces {
+            if attr.key.as_namespace_binding().is_some() {
+                continue;
+            }
+            attr.key.local_name().as_ref().to_vec()
+        } else {
+            attr.key.as_ref().to_vec()
+        };
+        let attr_name = format!("@{}", String::from_utf8_lossy(&key));
+        // Unescape entities so `xml_stringify` does not escape them twice.
+        let attr_value = attr
+            .normalized_value(XmlVersion::Implicit1_0)
+            .map(|v| v.into_owned())
+            .unwrap_or_else(|_| String::from_utf8_lossy(&attr.value).to_string());
+        map.insert(attr_name, serde_json::Value::String(attr_value));
+    }
+    (name, map)
+}
+
 /// If an element map contains only `#text`, return just the string value.
 fn simplify_element(map: serde_json::Map<String, serde_json::Value>) -> serde_json::Value {
     if map.len() == 1
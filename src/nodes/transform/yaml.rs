@@ -18,6 +18,10 @@ impl Node for YamlParseNode {
         "Parse a YAML string into a JSON value"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let has_input = config.get("input").and_then(|v| v.as_str()).is_some();
         let has_source_key = config.get("source_key").and_then(|v| v.as_str()).is_some();
@@ -66,6 +70,10 @@ impl Node for YamlStringifyNode {
         "Convert a JSON value from context to a YAML string"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let source_key = config
             .get("source_key")
@@ -58,6 +58,10 @@ fn cache_dir_from_config(config: &serde_json::Value) -> String {
         .unwrap_or_else(|| DEFAULT_CACHE_DIR.to_string())
 }
 
+#[cfg(not(feature = "redis"))]
+const REDIS_DISABLED: &str = "Redis cache backend requested but the 'redis' feature is not enabled. \
+     Rebuild with: cargo build --features redis";
+
 // ── cache_set ───────────────────────────────────────────────
 
 pub struct CacheSetNode;
@@ -69,7 +73,7 @@ impl Node for CacheSetNode {
     }
 
     fn description(&self) -> &str {
-        "Store a value in the cache (memory or file-based) with optional TTL"
+        "Store a value in the cache (memory, file or Redis) with optional TTL"
     }
 
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
@@ -96,27 +100,9 @@ impl Node for CacheSetNode {
             .and_then(|v| v.as_str())
             .unwrap_or("memory");
 
-        match backend {
-            "memory" => {
-                MEMORY_CACHE.insert(key.clone(), value, ttl_secs);
-            }
-            "file" => {
-                let expires_at = ttl_secs.map(|ttl| {
-                    std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap_or_default()
-                        .as_secs()
-                        + ttl
-                });
-                let entry = CacheEntry { value, expires_at };
-                let cache_dir = cache_dir_from_config(config);
-                write_file_entry(&cache_dir, &key, &entry)?;
-            }
-            other => anyhow::bail!(
-                "cache_set: unsupported backend '{}'. Must be 'memory' or 'file'.",
-                other
-            ),
-        }
+        store(backend, config, &key, value, ttl_secs)
+            .await
+            .map_err(|e| anyhow::anyhow!("cache_set: {:#}", e))?;
 
         let mut output = NodeOutput::new();
         output.insert("cache_key".to_string(), serde_json::json!(key));
@@ -142,7 +128,7 @@ impl Node for CacheGetNode {
     }
 
     fn description(&self) -> &str {
-        "Retrieve a value from the cache (memory or file-based)"
+        "Retrieve a value from the cache (memory, file or Redis)"
     }
 
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
@@ -164,17 +150,9 @@ impl Node for CacheGetNode {
             .and_then(|v| v.as_str())
             .unwrap_or("memory");
 
-        let value = match backend {
-            "memory" => MEMORY_CACHE.get(&key),
-            "file" => {
-                let cache_dir = cache_dir_from_config(config);
-                read_file_entry(&cache_dir, &key)?.map(|e| e.value)
-            }
-            other => anyhow::bail!(
-                "cache_get: unsupported backend '{}'. Must be 'memory' or 'file'.",
-                other
-            ),
-        };
+        let value = load(backend, config, &key)
+            .await
+            .map_err(|e| anyhow::anyhow!("cache_get: {:#}", e))?;
 
         let mut output = NodeOutput::new();
         match value {
@@ -191,6 +169,75 @@ impl Node for CacheGetNode {
     }
 }
 
+// ── Backends ────────────────────────────────────────────────
+
+/// Write `value` under `key` in `backend` (`memory`, `file` or `redis`).
+/// `config` supplies the backend's location (`cache_dir`, `redis_url`).
+/// Shared with other nodes that cache through the same backends.
+pub(crate) async fn store(
+    backend: &str,
+    config: &serde_json::Value,
+    key: &str,
+    value: serde_json::Value,
+    ttl_secs: Option<u64>,
+) -> Result<()> {
+    match backend {
+        "memory" => {
+            MEMORY_CACHE.insert(key.to_string(), value, ttl_secs);
+        }
+        "file" => {
+            let expires_at = ttl_secs.map(|ttl| {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs()
+                    + ttl
+            });
+            let entry = CacheEntry { value, expires_at };
+            let cache_dir = cache_dir_from_config(config);
+            write_file_entry(&cache_dir, key, &entry)?;
+        }
+        #[cfg(feature = "redis")]
+        "redis" => {
+            let url = super::cache_redis::redis_url_from_config(config);
+            super::cache_redis::set(&url, key, &value, ttl_secs).await?;
+        }
+        #[cfg(not(feature = "redis"))]
+        "redis" => anyhow::bail!(REDIS_DISABLED),
+        other => anyhow::bail!(
+            "unsupported backend '{}'. Must be 'memory', 'file' or 'redis'.",
+            other
+        ),
+    }
+    Ok(())
+}
+
+/// Read `key` from `backend`; `None` when missing or expired.
+pub(crate) async fn load(
+    backend: &str,
+    config: &serde_json::Value,
+    key: &str,
+) -> Result<Option<serde_json::Value>> {
+    Ok(match backend {
+        "memory" => MEMORY_CACHE.get(&key.to_string()),
+        "file" => {
+            let cache_dir = cache_dir_from_config(config);
+            read_file_entry(&cache_dir, key)?.map(|e| e.value)
+        }
+        #[cfg(feature = "redis")]
+        "redis" => {
+            let url = super::cache_redis::redis_url_from_config(config);
+            super::cache_redis::get(&url, key).await?
+        }
+        #[cfg(not(feature = "redis"))]
+        "redis" => anyhow::bail!(REDIS_DISABLED),
+        other => anyhow::bail!(
+            "unsupported backend '{}'. Must be 'memory', 'file' or 'redis'.",
+            other
+        ),
+    })
+}
+
 // ── File backend helpers ────────────────────────────────────
 
 fn cache_file_path(cache_dir: &str, key: &str) -> std::path::PathBuf {
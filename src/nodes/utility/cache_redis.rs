@@ -0,0 +1,80 @@
+//! Redis backend for `cache_set` / `cache_get`, so several workers can share
+//! one cache.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use anyhow::{Context as _, Result};
+use redis::AsyncCommands;
+use redis::aio::ConnectionManager;
+use tokio::sync::Mutex;
+
+const DEFAULT_REDIS_URL: &str = "redis://127.0.0.1:6379";
+
+/// One auto-reconnecting connection per Redis URL, shared by every node call.
+static CONNECTIONS: LazyLock<Mutex<HashMap<String, ConnectionManager>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// `redis_url` from the node config, else `REDIS_URL`, else localhost.
+pub(super) fn redis_url_from_config(config: &serde_json::Value) -> String {
+    config
+        .get("redis_url")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .or_else(|| std::env::var("REDIS_URL").ok())
+        .unwrap_or_else(|| DEFAULT_REDIS_URL.to_string())
+}
+
+/// Cache entries live under `<REDIS_PREFIX>cache:<key>`, next to the run
+/// state and events of a Redis-backed deployment.
+fn redis_key(key: &str) -> String {
+    let prefix = std::env::var("REDIS_PREFIX").unwrap_or_else(|_| "ironflow:".to_string());
+    format!("{}cache:{}", prefix, key)
+}
+
+async fn connection(url: &str) -> Result<ConnectionManager> {
+    let mut connections = CONNECTIONS.lock().await;
+    if let Some(conn) = connections.get(url) {
+        return Ok(conn.clone());
+    }
+    let client = redis::Client::open(url).with_context(|| format!("Invalid Redis URL: {}", url))?;
+    let conn = ConnectionManager::new(client)
+        .await
+        .with_context(|| format!("Failed to connect to Redis at {}", url))?;
+    connections.insert(url.to_string(), conn.clone());
+    Ok(conn)
+}
+
+/// Store `value` as JSON, expiring after `ttl_secs` when given. A TTL of
+/// `0` expires the entry at once, as with the memory backend.
+pub(super) async fn set(
+    url: &str,
+    key: &str,
+    value: &serde_json::Value,
+    ttl_secs: Option<u64>,
+) -> Result<()> {
+    let mut conn = connection(url).await?;
+    let redis_key = redis_key(key);
+    let json = serde_json::to_string(value)?;
+    let _: () = match ttl_secs {
+        Some(0) => conn.del(&redis_key).await,
+        Some(ttl) => conn.set_ex(&redis_key, json, ttl).await,
+        None => conn.set(&redis_key, json).await,
+    }
+    .with_context(|| format!("Redis SET failed for cache key '{}'", key))?;
+    Ok(())
+}
+
+/// The cached value, or `None` when the key is missing or expired.
+pub(super) async fn get(url: &str, key: &str) -> Result<Option<serde_json::Value>> {
+    let mut conn = connection(url).await?;
+    let raw: Option<String> = conn
+        .get(redis_key(key))
+        .await
+        .with_context(|| format!("Redis GET failed for cache key '{}'", key))?;
+    raw.map(|raw| {
+        serde_json::from_str(&raw)
+            .with_context(|| format!("Corrupt Redis cache entry for key '{}'", key))
+    })
+    .transpose()
+}
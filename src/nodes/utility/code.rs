@@ -3,13 +3,41 @@ use async_trait::async_trait;
 use base64::Engine;
 use mlua::prelude::*;
 
+use crate::engine::run_meta;
 use crate::engine::types::{Context, NodeOutput};
 use crate::lua::sandbox;
 use crate::nodes::Node;
-use crate::util::limits::{LuaExecutionLimits, apply_lua_limits, collect_lua_garbage};
+use crate::util::limits::{
+    LuaExecutionLimits, apply_lua_limits, collect_lua_garbage, describe_lua_error,
+};
 
 pub struct CodeNode;
 
+/// Await a script, failing with the same error as the instruction hook once
+/// the step timeout passes. The hook alone never fires while the script is
+/// parked on an async helper such as `http_get`.
+async fn within_step_timeout<T>(
+    script: impl std::future::Future<Output = LuaResult<T>>,
+    limits: LuaExecutionLimits,
+    what: &str,
+) -> Result<T> {
+    let result = match limits.step_timeout_ms {
+        Some(ms) => tokio::time::timeout(std::time::Duration::from_millis(ms), script)
+            .await
+            .map_err(|_| {
+                anyhow::anyhow!("{} execution failed: lua script exceeded {}ms", what, ms)
+            })?,
+        None => script.await,
+    };
+    result.map_err(|e| {
+        anyhow::anyhow!(
+            "{} execution failed: {}",
+            what,
+            describe_lua_error(&e, limits)
+        )
+    })
+}
+
 #[async_trait]
 impl Node for CodeNode {
     fn node_type(&self) -> &str {
@@ -22,9 +50,16 @@ impl Node for CodeNode {
 
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let lua = Lua::new();
-        let limits = LuaExecutionLimits::from_env();
+        let limits = LuaExecutionLimits::from_env().with_step_limits(
+            positive_u64(config, "timeout_ms")?,
+            positive_u64(config, "memory_limit_bytes")?,
+        );
         apply_lua_limits(&lua, limits)?;
         let ctx_table = sandbox::setup_sandbox(&lua, ctx)?;
+        set_run_globals(&lua)?;
+        if allows_http(config) {
+            sandbox::setup_http(&lua, ctx)?;
+        }
 
         // Execute either bytecode (function handler) or source string
         let result: LuaValue =
@@ -37,8 +72,7 @@ impl Node for CodeNode {
                     .load(&bytecode)
                     .into_function()
                     .map_err(|e| anyhow::anyhow!("Failed to load function: {}", e))?;
-                func.call(ctx_table)
-                    .map_err(|e| anyhow::anyhow!("Function execution failed: {}", e))?
+                within_step_timeout(func.call_async(ctx_table), limits, "Function").await?
             } else {
                 let source = config
                     .get("source")
@@ -46,10 +80,12 @@ impl Node for CodeNode {
                     .ok_or_else(|| {
                         anyhow::anyhow!("code node requires 'source' or a function handler")
                     })?;
-                lua.load(source)
-                    .set_name("<code>")
-                    .eval()
-                    .map_err(|e| anyhow::anyhow!("Code execution failed: {}", e))?
+                within_step_timeout(
+                    lua.load(source).set_name("<code>").eval_async(),
+                    limits,
+                    "Code",
+                )
+                .await?
             };
 
         // Convert the returned value to NodeOutput
@@ -77,6 +113,66 @@ impl Node for CodeNode {
     }
 }
 
+/// Optional positive integer setting such as `timeout_ms`.
+fn positive_u64(config: &serde_json::Value, key: &str) -> Result<Option<u64>> {
+    match config.get(key) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(value) => match value.as_u64() {
+            Some(n) if n > 0 => Ok(Some(n)),
+            _ => anyhow::bail!("code '{}' must be a positive integer, got {}", key, value),
+        },
+    }
+}
+
+/// Whether the step opted in to `http_get`/`http_request` with `allow_http = true`.
+fn allows_http(config: &serde_json::Value) -> bool {
+    config
+        .get("allow_http")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// Expose `run_id`, `flow_name`, `step_name` and `steps` (outputs of the
+/// steps finished so far) when running inside the engine. Assigning into
+/// `steps` raises an error; the step tables themselves are fresh copies.
+fn set_run_globals(lua: &Lua) -> Result<()> {
+    let Some(scope) = run_meta::current() else {
+        return Ok(());
+    };
+    let globals = lua.globals();
+    globals.set("run_id", scope.run.run_id.as_str())?;
+    globals.set("flow_name", scope.run.flow_name.as_str())?;
+    globals.set("step_name", scope.step_name.as_str())?;
+
+    let outputs = lua.create_table()?;
+    for (name, output) in scope.run.step_outputs().iter() {
+        outputs.set(name.as_str(), json_value_to_lua_table(lua, output)?)?;
+    }
+    let meta = lua.create_table()?;
+    meta.set("__index", outputs.clone())?;
+    meta.set(
+        "__newindex",
+        lua.create_function(|_, (_, key): (LuaValue, LuaValue)| -> LuaResult<()> {
+            Err(LuaError::runtime(format!(
+                "steps is read-only (attempted to set '{}')",
+                key.to_string()?
+            )))
+        })?,
+    )?;
+    meta.set(
+        "__pairs",
+        lua.create_function(move |lua, _: LuaValue| {
+            let next: LuaFunction = lua.globals().get("next")?;
+            Ok((next, outputs.clone(), LuaValue::Nil))
+        })?,
+    )?;
+    meta.set("__metatable", false)?;
+    let steps = lua.create_table()?;
+    steps.set_metatable(Some(meta))?;
+    globals.set("steps", steps)?;
+    Ok(())
+}
+
 /// Convert a serde_json::Value into a Lua value.
 pub(crate) fn json_value_to_lua_table(lua: &Lua, value: &serde_json::Value) -> Result<LuaValue> {
     match value {
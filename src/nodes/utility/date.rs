@@ -68,6 +68,19 @@ impl Node for DateFormatNode {
         "Parse and format dates/timestamps"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    /// `now` reads the clock, and a placeholder or context value may
+    /// resolve to it, so only a literal date input is pure.
+    fn is_pure_with(&self, config: &serde_json::Value) -> bool {
+        config
+            .get("input")
+            .and_then(|v| v.as_str())
+            .is_some_and(|input| !input.contains("${") && !input.eq_ignore_ascii_case("now"))
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let output_format = config
             .get("output_format")
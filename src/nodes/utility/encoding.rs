@@ -1,12 +1,44 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use base64::Engine;
-use base64::engine::general_purpose::{STANDARD, URL_SAFE};
+use base64::alphabet;
+use base64::engine::DecodePaddingMode;
+use base64::engine::general_purpose::{
+    GeneralPurpose, GeneralPurposeConfig, STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD,
+};
 
 use crate::engine::types::{Context, NodeOutput};
 use crate::lua::interpolate::interpolate_ctx;
 use crate::nodes::Node;
 
+/// The encoder for the `url_safe` / `no_pad` flags.
+fn encoder(url_safe: bool, no_pad: bool) -> &'static GeneralPurpose {
+    match (url_safe, no_pad) {
+        (false, false) => &STANDARD,
+        (false, true) => &STANDARD_NO_PAD,
+        (true, false) => &URL_SAFE,
+        (true, true) => &URL_SAFE_NO_PAD,
+    }
+}
+
+/// A decoder for the chosen alphabet that accepts input with or without
+/// trailing `=` padding.
+fn decoder(url_safe: bool) -> GeneralPurpose {
+    let alphabet = if url_safe {
+        &alphabet::URL_SAFE
+    } else {
+        &alphabet::STANDARD
+    };
+    GeneralPurpose::new(
+        alphabet,
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    )
+}
+
+fn flag(config: &serde_json::Value, name: &str) -> bool {
+    config.get(name).and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
 pub struct Base64EncodeNode;
 
 #[async_trait]
@@ -19,16 +51,23 @@ impl Node for Base64EncodeNode {
         "Encode a string or file contents to base64"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    /// Encoding a `file` reads it from disk.
+    fn is_pure_with(&self, config: &serde_json::Value) -> bool {
+        config.get("file").is_none()
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let output_key = config
             .get("output_key")
             .and_then(|v| v.as_str())
             .unwrap_or("base64_encoded");
 
-        let url_safe = config
-            .get("url_safe")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
+        let url_safe = flag(config, "url_safe");
+        let no_pad = flag(config, "no_pad");
 
         let has_input = config.get("input").is_some();
         let has_source_key = config.get("source_key").is_some();
@@ -57,11 +96,7 @@ impl Node for Base64EncodeNode {
             anyhow::bail!("base64_encode requires one of 'input', 'source_key', or 'file'");
         };
 
-        let encoded = if url_safe {
-            URL_SAFE.encode(&bytes)
-        } else {
-            STANDARD.encode(&bytes)
-        };
+        let encoded = encoder(url_safe, no_pad).encode(&bytes);
 
         let mut output = NodeOutput::new();
         output.insert(output_key.to_string(), serde_json::Value::String(encoded));
@@ -81,16 +116,27 @@ impl Node for Base64DecodeNode {
         "Decode a base64 string to text or file"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    /// Decoding to an `output_file` writes it to disk.
+    fn is_pure_with(&self, config: &serde_json::Value) -> bool {
+        config.get("output_file").is_none()
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let output_key = config
             .get("output_key")
             .and_then(|v| v.as_str())
             .unwrap_or("base64_decoded");
 
-        let url_safe = config
-            .get("url_safe")
+        let url_safe = flag(config, "url_safe");
+        let no_pad = flag(config, "no_pad");
+        let as_text = config
+            .get("as_text")
             .and_then(|v| v.as_bool())
-            .unwrap_or(false);
+            .unwrap_or(true);
 
         let output_file = config.get("output_file").and_then(|v| v.as_str());
 
@@ -116,12 +162,15 @@ impl Node for Base64DecodeNode {
             anyhow::bail!("base64_decode requires either 'input' or 'source_key'");
         };
 
-        let decoded_bytes = if url_safe {
-            URL_SAFE.decode(&encoded)
-        } else {
-            STANDARD.decode(&encoded)
-        }
-        .map_err(|e| anyhow::anyhow!("Failed to decode base64: {}", e))?;
+        // Line-wrapped input (MIME, PEM) decodes once whitespace is removed.
+        let compact: String = encoded
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect();
+        let decoded_bytes = decoder(url_safe).decode(&compact).map_err(|e| {
+            let alphabet = if url_safe { "URL-safe" } else { "standard" };
+            anyhow::anyhow!("Failed to decode base64 ({} alphabet): {}", alphabet, e)
+        })?;
 
         let mut output = NodeOutput::new();
 
@@ -134,9 +183,19 @@ impl Node for Base64DecodeNode {
                 format!("{}_path", output_key),
                 serde_json::Value::String(path),
             );
+        } else if !as_text {
+            output.insert(
+                output_key.to_string(),
+                serde_json::Value::String(encoder(url_safe, no_pad).encode(&decoded_bytes)),
+            );
         } else {
             let decoded_str = String::from_utf8(decoded_bytes)
-                .map_err(|e| anyhow::anyhow!("Decoded bytes are not valid UTF-8: {}", e))?;
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Decoded bytes are not valid UTF-8 (set as_text = false or output_file for binary data): {}",
+                        e
+                    )
+                })?;
             output.insert(
                 output_key.to_string(),
                 serde_json::Value::String(decoded_str),
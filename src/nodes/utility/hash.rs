@@ -1,6 +1,10 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use hmac::{Hmac, KeyInit, Mac};
 use md5::Md5;
+use sha1::Sha1;
 use sha2::{Digest, Sha256, Sha384, Sha512};
 
 use crate::engine::types::{Context, NodeOutput};
@@ -19,6 +23,10 @@ impl Node for HashNode {
         "Compute a hash (SHA-256, SHA-384, SHA-512, MD5) of a string or context value"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let algorithm = config
             .get("algorithm")
@@ -30,20 +38,7 @@ impl Node for HashNode {
             .and_then(|v| v.as_str())
             .unwrap_or("hash");
 
-        // Get the input: either from "input" directly or from "source_key" in context
-        let input = if let Some(input_str) = config.get("input").and_then(|v| v.as_str()) {
-            interpolate_ctx(input_str, ctx)
-        } else if let Some(source_key) = config.get("source_key").and_then(|v| v.as_str()) {
-            let val = ctx
-                .get(source_key)
-                .ok_or_else(|| anyhow::anyhow!("Key '{}' not found in context", source_key))?;
-            match val {
-                serde_json::Value::String(s) => s.clone(),
-                other => serde_json::to_string(other)?,
-            }
-        } else {
-            anyhow::bail!("hash requires either 'input' string or 'source_key'");
-        };
+        let input = read_input(config, ctx, "hash")?;
 
         let hash_hex = match algorithm.to_lowercase().as_str() {
             "sha256" | "sha-256" => {
@@ -81,3 +76,116 @@ impl Node for HashNode {
         Ok(output)
     }
 }
+
+pub struct HmacNode;
+
+#[async_trait]
+impl Node for HmacNode {
+    fn node_type(&self) -> &str {
+        "hmac"
+    }
+
+    fn description(&self) -> &str {
+        "Compute or verify a keyed HMAC (SHA-256, SHA-1, SHA-512) signature"
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let algorithm = config
+            .get("algorithm")
+            .and_then(|v| v.as_str())
+            .unwrap_or("sha256");
+
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .unwrap_or("hmac");
+
+        let secret = config
+            .get("secret")
+            .and_then(|v| v.as_str())
+            .map(|s| interpolate_ctx(s, ctx))
+            .ok_or_else(|| anyhow::anyhow!("hmac requires 'secret'"))?;
+
+        let input = read_input(config, ctx, "hmac")?;
+
+        let key = secret.as_bytes();
+        let data = input.as_bytes();
+        let mac = match algorithm.to_lowercase().as_str() {
+            "sha256" | "sha-256" => compute_hmac::<Hmac<Sha256>>(key, data),
+            "sha1" | "sha-1" => compute_hmac::<Hmac<Sha1>>(key, data),
+            "sha512" | "sha-512" => compute_hmac::<Hmac<Sha512>>(key, data),
+            _ => anyhow::bail!(
+                "Unsupported HMAC algorithm '{}'. Use: sha256, sha1, sha512",
+                algorithm
+            ),
+        };
+
+        let signature = match config
+            .get("encoding")
+            .and_then(|v| v.as_str())
+            .unwrap_or("hex")
+        {
+            "hex" => hex::encode(&mac),
+            "base64" => STANDARD.encode(&mac),
+            other => anyhow::bail!("hmac 'encoding' must be 'hex' or 'base64', got '{}'", other),
+        };
+
+        let mut output = NodeOutput::new();
+        // Webhook signatures often arrive as `sha256=<digest>`; compare the
+        // digest part in constant time.
+        if let Some(expected) = config.get("expected").and_then(|v| v.as_str()) {
+            let expected = interpolate_ctx(expected, ctx);
+            let expected = expected
+                .split_once('=')
+                .filter(|(prefix, _)| prefix.eq_ignore_ascii_case(algorithm))
+                .map_or(expected.as_str(), |(_, digest)| digest);
+            let valid = expected.len() == signature.len()
+                && expected
+                    .bytes()
+                    .zip(signature.bytes())
+                    .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+                    == 0;
+            output.insert(
+                format!("{}_valid", output_key),
+                serde_json::Value::Bool(valid),
+            );
+        }
+        output.insert(output_key.to_string(), serde_json::Value::String(signature));
+        output.insert(
+            format!("{}_algorithm", output_key),
+            serde_json::Value::String(algorithm.to_string()),
+        );
+        Ok(output)
+    }
+}
+
+fn compute_hmac<M: Mac + KeyInit>(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = <M as KeyInit>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// The text to digest: `input` (interpolated) or the value at `source_key`,
+/// JSON-serialized unless it is a string.
+fn read_input(config: &serde_json::Value, ctx: &Context, node_name: &str) -> Result<String> {
+    if let Some(input_str) = config.get("input").and_then(|v| v.as_str()) {
+        Ok(interpolate_ctx(input_str, ctx))
+    } else if let Some(source_key) = config.get("source_key").and_then(|v| v.as_str()) {
+        let val = ctx
+            .get(source_key)
+            .ok_or_else(|| anyhow::anyhow!("Key '{}' not found in context", source_key))?;
+        Ok(match val {
+            serde_json::Value::String(s) => s.clone(),
+            other => serde_json::to_string(other)?,
+        })
+    } else {
+        anyhow::bail!(
+            "{} requires either 'input' string or 'source_key'",
+            node_name
+        );
+    }
+}
@@ -21,6 +21,10 @@ impl Node for HtmlSanitizeNode {
         "Sanitize HTML by removing dangerous tags, attributes, and scripts"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let output_key = config
             .get("output_key")
@@ -19,6 +19,10 @@ impl Node for MarkdownToHtmlNode {
         "Convert Markdown text to HTML"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let output_key = config
             .get("output_key")
@@ -65,6 +69,10 @@ impl Node for HtmlToMarkdownNode {
         "Convert HTML to Markdown (best-effort, lossy on complex HTML)"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let output_key = config
             .get("output_key")
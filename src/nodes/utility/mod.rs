@@ -1,4 +1,6 @@
-mod cache;
+pub(crate) mod cache;
+#[cfg(feature = "redis")]
+mod cache_redis;
 pub(crate) mod code;
 mod date;
 mod delay;
@@ -9,7 +11,11 @@ mod log;
 mod markdown;
 mod shell;
 mod template;
+mod text;
 mod validate;
+mod wait_until;
+#[cfg(feature = "wasm")]
+mod wasm;
 
 use crate::nodes::NodeRegistry;
 use std::sync::Arc;
@@ -18,10 +24,14 @@ use std::sync::Arc;
 pub fn register_all(registry: &mut NodeRegistry) {
     registry.register(Arc::new(log::LogNode));
     registry.register(Arc::new(delay::DelayNode));
+    registry.register(Arc::new(wait_until::WaitUntilNode));
+    registry.register(Arc::new(wait_until::SleepUntilNode));
     registry.register(Arc::new(shell::ShellCommandNode));
     registry.register(Arc::new(hash::HashNode));
+    registry.register(Arc::new(hash::HmacNode));
     registry.register(Arc::new(date::DateFormatNode));
     registry.register(Arc::new(template::TemplateRenderNode));
+    registry.register(Arc::new(text::RegexNode));
     registry.register(Arc::new(markdown::MarkdownToHtmlNode));
     registry.register(Arc::new(markdown::HtmlToMarkdownNode));
     registry.register(Arc::new(html_sanitize::HtmlSanitizeNode));
@@ -32,4 +42,6 @@ pub fn register_all(registry: &mut NodeRegistry) {
     registry.register(Arc::new(cache::CacheSetNode));
     registry.register(Arc::new(cache::CacheGetNode));
     registry.register(Arc::new(code::CodeNode));
+    #[cfg(feature = "wasm")]
+    registry.register(Arc::new(wasm::WasmNode));
 }
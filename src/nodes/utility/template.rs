@@ -17,6 +17,10 @@ impl Node for TemplateRenderNode {
         "Render a string template with context variable interpolation"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let template = config
             .get("template")
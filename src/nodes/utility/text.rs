@@ -0,0 +1,159 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use regex::{Captures, Regex, RegexBuilder};
+
+use crate::engine::types::{Context, NodeError, NodeOutput};
+use crate::nodes::Node;
+
+pub struct RegexNode;
+
+#[async_trait]
+impl Node for RegexNode {
+    fn node_type(&self) -> &str {
+        "regex"
+    }
+
+    fn description(&self) -> &str {
+        "Match, replace or split a string with a regular expression"
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let source_key = config
+            .get("source_key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("regex requires 'source_key'"))?;
+
+        let output_key = config
+            .get("output_key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("regex requires 'output_key'"))?;
+
+        let pattern = config
+            .get("pattern")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("regex requires 'pattern'"))?;
+
+        let case_insensitive = config
+            .get("case_insensitive")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let re = RegexBuilder::new(pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+            .map_err(|e| {
+                NodeError::validation(format!("regex: invalid pattern '{}': {}", pattern, e))
+            })?;
+
+        let text = ctx
+            .get(source_key)
+            .ok_or_else(|| anyhow::anyhow!("Key '{}' not found in context", source_key))?
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Value at '{}' is not a string", source_key))?;
+
+        let mode = config
+            .get("mode")
+            .and_then(|v| v.as_str())
+            .unwrap_or("match");
+        // `all` defaults to every match when replacing, the first otherwise.
+        let all = config
+            .get("all")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(mode == "replace");
+
+        let mut output = NodeOutput::new();
+        match mode {
+            "match" => {
+                if all {
+                    let matches: Vec<_> = re
+                        .captures_iter(text)
+                        .map(|caps| match_to_json(&re, &caps))
+                        .collect();
+                    output.insert(
+                        format!("{}_count", output_key),
+                        serde_json::json!(matches.len()),
+                    );
+                    output.insert(output_key.to_string(), serde_json::Value::Array(matches));
+                } else {
+                    let first = re.captures(text).map(|caps| match_to_json(&re, &caps));
+                    output.insert(
+                        format!("{}_matched", output_key),
+                        serde_json::Value::Bool(first.is_some()),
+                    );
+                    output.insert(
+                        output_key.to_string(),
+                        first.unwrap_or(serde_json::Value::Null),
+                    );
+                }
+            }
+            "replace" => {
+                let replacement = config
+                    .get("replacement")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("regex mode 'replace' requires 'replacement'")
+                    })?;
+                let (limit, count) = if all {
+                    (0, re.find_iter(text).count())
+                } else {
+                    (1, usize::from(re.is_match(text)))
+                };
+                let replaced = re.replacen(text, limit, replacement);
+                output.insert(
+                    output_key.to_string(),
+                    serde_json::Value::String(replaced.into_owned()),
+                );
+                output.insert(format!("{}_count", output_key), serde_json::json!(count));
+            }
+            "split" => {
+                let parts: Vec<serde_json::Value> =
+                    match config.get("limit").and_then(|v| v.as_u64()) {
+                        Some(limit) => re
+                            .splitn(text, limit as usize)
+                            .map(|s| serde_json::Value::String(s.to_string()))
+                            .collect(),
+                        None => re
+                            .split(text)
+                            .map(|s| serde_json::Value::String(s.to_string()))
+                            .collect(),
+                    };
+                output.insert(
+                    format!("{}_count", output_key),
+                    serde_json::json!(parts.len()),
+                );
+                output.insert(output_key.to_string(), serde_json::Value::Array(parts));
+            }
+            other => anyhow::bail!(
+                "regex 'mode' must be 'match', 'replace' or 'split', got '{}'",
+                other
+            ),
+        }
+        Ok(output)
+    }
+}
+
+/// `{ match, start, end, groups, named }` for one match. `groups` lists the
+/// numbered capture groups from 1; groups that did not participate are `null`.
+fn match_to_json(re: &Regex, caps: &Captures) -> serde_json::Value {
+    let whole = caps.get(0).expect("group 0 is always present");
+    let as_json = |m: Option<regex::Match>| {
+        m.map(|m| serde_json::Value::String(m.as_str().to_string()))
+            .unwrap_or(serde_json::Value::Null)
+    };
+    let groups: Vec<_> = (1..caps.len()).map(|i| as_json(caps.get(i))).collect();
+    let named: serde_json::Map<_, _> = re
+        .capture_names()
+        .flatten()
+        .map(|name| (name.to_string(), as_json(caps.name(name))))
+        .collect();
+    serde_json::json!({
+        "match": whole.as_str(),
+        "start": whole.start(),
+        "end": whole.end(),
+        "groups": groups,
+        "named": named,
+    })
+}
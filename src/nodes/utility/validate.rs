@@ -1,7 +1,7 @@
 use anyhow::Result;
 use async_trait::async_trait;
 
-use crate::engine::types::{Context, NodeOutput};
+use crate::engine::types::{Context, NodeError, NodeOutput};
 use crate::nodes::Node;
 
 fn validate_against_schema(
@@ -70,6 +70,10 @@ impl Node for ValidateSchemaNode {
         "Validate context data against a JSON Schema"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let source_key = config
             .get("source_key")
@@ -99,7 +103,9 @@ impl Node for ValidateSchemaNode {
                 .filter(|details| !details.is_empty())
                 .unwrap_or_else(|| "Schema validation failed".to_string());
 
-            anyhow::bail!("Schema validation failed: {}", details);
+            return Err(
+                NodeError::validation(format!("Schema validation failed: {}", details)).into(),
+            );
         }
 
         Ok(output)
@@ -118,6 +124,10 @@ impl Node for JsonValidateNode {
         "Validate JSON text against a JSON Schema"
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
         let source_key = config
             .get("source_key")
@@ -154,7 +164,9 @@ impl Node for JsonValidateNode {
                 .filter(|details| !details.is_empty())
                 .unwrap_or_else(|| "Schema validation failed".to_string());
 
-            anyhow::bail!("Schema validation failed: {}", details);
+            return Err(
+                NodeError::validation(format!("Schema validation failed: {}", details)).into(),
+            );
         }
 
         Ok(output)
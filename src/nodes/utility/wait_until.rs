@@ -0,0 +1,130 @@
+use std::str::FromStr;
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveTime, Utc};
+
+use crate::engine::types::{Context, NodeOutput};
+use crate::lua::interpolate::interpolate_ctx;
+use crate::nodes::Node;
+
+/// Default upper bound on how long a single `wait_until` may sleep for an
+/// `at` timestamp (1 hour).
+const DEFAULT_MAX_WAIT_S: f64 = 3600.0;
+
+/// Default bound for `time` and `cron` targets (24 hours), so a daily
+/// schedule works without setting `max_wait`.
+const DEFAULT_SCHEDULE_MAX_WAIT_S: f64 = 86_400.0;
+
+pub struct WaitUntilNode;
+
+#[async_trait]
+impl Node for WaitUntilNode {
+    fn node_type(&self) -> &str {
+        "wait_until"
+    }
+
+    fn description(&self) -> &str {
+        "Pause execution until a wall-clock time (RFC3339 timestamp, time of day or next cron match)"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let now = Utc::now();
+        let field = |key: &str| {
+            config
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(|s| interpolate_ctx(s, ctx))
+        };
+
+        let (target, default_max_wait_s) = if let Some(at) = field("at") {
+            let target = DateTime::parse_from_rfc3339(&at)
+                .map_err(|e| anyhow::anyhow!("wait_until: invalid RFC3339 'at' '{}': {}", at, e))?
+                .with_timezone(&Utc);
+            (target, DEFAULT_MAX_WAIT_S)
+        } else if let Some(time) = field("time") {
+            (next_time_of_day(&time, now)?, DEFAULT_SCHEDULE_MAX_WAIT_S)
+        } else if let Some(expr) = field("cron") {
+            let schedule = cron::Schedule::from_str(&expr)
+                .map_err(|e| anyhow::anyhow!("wait_until: invalid cron '{}': {}", expr, e))?;
+            let target = schedule.after(&now).next().ok_or_else(|| {
+                anyhow::anyhow!("wait_until: cron '{}' has no upcoming time", expr)
+            })?;
+            (target, DEFAULT_SCHEDULE_MAX_WAIT_S)
+        } else {
+            bail!("wait_until requires 'at', 'time' or 'cron'");
+        };
+
+        let max_wait_s = config
+            .get("max_wait")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(default_max_wait_s);
+
+        // A target already in the past is not an error: the step returns at
+        // once and says so, e.g. when a delayed run starts after the deadline.
+        let skipped = target <= now;
+        let remaining = (target - now).to_std().unwrap_or_default();
+        if remaining.as_secs_f64() > max_wait_s {
+            bail!(
+                "wait_until: {} is {:.0}s away, exceeding max_wait of {}s; set a larger 'max_wait' to wait longer",
+                target.to_rfc3339(),
+                remaining.as_secs_f64(),
+                max_wait_s
+            );
+        }
+
+        // `sleep` is cancel-safe: a step timeout or an aborted run drops this
+        // future and the wait ends immediately instead of blocking the run.
+        tokio::time::sleep(remaining).await;
+
+        let mut output = NodeOutput::new();
+        output.insert(
+            "wait_until".to_string(),
+            serde_json::Value::String(target.to_rfc3339()),
+        );
+        output.insert(
+            "waited_seconds".to_string(),
+            serde_json::json!(remaining.as_secs_f64()),
+        );
+        output.insert("wait_skipped".to_string(), serde_json::Value::Bool(skipped));
+        Ok(output)
+    }
+}
+
+/// `sleep_until`: the same node under the name used by schedulers and
+/// shell tools, for flows that read better as "sleep until 09:00".
+pub struct SleepUntilNode;
+
+#[async_trait]
+impl Node for SleepUntilNode {
+    fn node_type(&self) -> &str {
+        "sleep_until"
+    }
+
+    fn description(&self) -> &str {
+        "Alias of wait_until: sleep until an RFC3339 timestamp, time of day or next cron match"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        WaitUntilNode.execute(config, ctx).await
+    }
+}
+
+/// The next occurrence of `HH:MM` or `HH:MM:SS` (UTC) after `now`: today if
+/// it is still ahead, tomorrow otherwise.
+fn next_time_of_day(time: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let time = NaiveTime::parse_from_str(time, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "wait_until: invalid 'time' '{}', expected HH:MM or HH:MM:SS",
+                time
+            )
+        })?;
+    let today = now.date_naive().and_time(time).and_utc();
+    Ok(if today > now {
+        today
+    } else {
+        today + chrono::Duration::days(1)
+    })
+}
@@ -0,0 +1,183 @@
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use base64::Engine as _;
+use wasmtime::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+use wasmtime_wasi::p1::{self, WasiP1Ctx};
+use wasmtime_wasi::p2::pipe::{MemoryInputPipe, MemoryOutputPipe};
+use wasmtime_wasi::{I32Exit, WasiCtxBuilder};
+
+use crate::engine::types::{Context, NodeOutput};
+use crate::lua::interpolate::interpolate_ctx;
+use crate::nodes::Node;
+use crate::util::limits::{max_wasm_fuel, max_wasm_memory_bytes, max_wasm_output_bytes};
+
+/// Per-instance store data: the WASI context plus the resource limiter.
+struct WasmState {
+    wasi: WasiP1Ctx,
+    limits: StoreLimits,
+}
+
+pub struct WasmNode;
+
+/// The `timeout` in seconds (30 when unset), which must be a positive,
+/// finite number.
+fn timeout(config: &serde_json::Value) -> Result<Duration> {
+    let Some(value) = config.get("timeout").filter(|v| !v.is_null()) else {
+        return Ok(Duration::from_secs(30));
+    };
+    match value.as_f64().filter(|s| *s > 0.0) {
+        Some(secs) => Duration::try_from_secs_f64(secs)
+            .map_err(|_| anyhow::anyhow!("wasm 'timeout' is too large: {}", value)),
+        None => bail!("wasm 'timeout' must be a positive number, got {}", value),
+    }
+}
+
+#[async_trait]
+impl Node for WasmNode {
+    fn node_type(&self) -> &str {
+        "wasm"
+    }
+
+    fn description(&self) -> &str {
+        "Run a sandboxed WASI module with the context as JSON on stdin"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let module_bytes = if let Some(b64) = config.get("module_b64").and_then(|v| v.as_str()) {
+            base64::engine::general_purpose::STANDARD
+                .decode(b64)
+                .map_err(|e| anyhow::anyhow!("Failed to decode WASM module: {}", e))?
+        } else if let Some(path) = config.get("path").and_then(|v| v.as_str()) {
+            let path = interpolate_ctx(path, ctx);
+            tokio::fs::read(&path)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to read WASM module '{}': {}", path, e))?
+        } else {
+            bail!("wasm node requires 'path' or 'module_b64'");
+        };
+
+        // Either an explicit `input` value or the whole workflow context.
+        let input = match config.get("input") {
+            Some(value) => value.clone(),
+            None => serde_json::to_value(ctx)?,
+        };
+        let stdin = serde_json::to_vec(&input)?;
+
+        let args: Vec<String> = config
+            .get("args")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let fuel = config
+            .get("fuel")
+            .and_then(|v| v.as_u64())
+            .or_else(max_wasm_fuel);
+        let max_memory_bytes = config
+            .get("max_memory_bytes")
+            .and_then(|v| v.as_u64())
+            .unwrap_or_else(max_wasm_memory_bytes);
+        let timeout = timeout(config)?;
+
+        let mut engine_config = Config::new();
+        engine_config.consume_fuel(fuel.is_some());
+        engine_config.epoch_interruption(true);
+        let engine = Engine::new(&engine_config)?;
+
+        let module = Module::new(&engine, &module_bytes)
+            .map_err(|e| anyhow::anyhow!("Failed to compile WASM module: {}", e))?;
+
+        let stdout = MemoryOutputPipe::new(max_wasm_output_bytes() as usize);
+        let stderr = MemoryOutputPipe::new(max_wasm_output_bytes() as usize);
+
+        let run_engine = engine.clone();
+        let run_stdout = stdout.clone();
+        let run_stderr = stderr.clone();
+        let task = tokio::task::spawn_blocking(move || -> Result<Option<i32>> {
+            let mut wasi = WasiCtxBuilder::new();
+            wasi.stdin(MemoryInputPipe::new(stdin))
+                .stdout(run_stdout)
+                .stderr(run_stderr)
+                .arg("module.wasm")
+                .args(&args);
+
+            let state = WasmState {
+                wasi: wasi.build_p1(),
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(max_memory_bytes.min(usize::MAX as u64) as usize)
+                    .build(),
+            };
+            let mut store = Store::new(&run_engine, state);
+            store.limiter(|state| &mut state.limits);
+            store.set_epoch_deadline(1);
+            if let Some(fuel) = fuel {
+                store.set_fuel(fuel)?;
+            }
+
+            let mut linker: Linker<WasmState> = Linker::new(&run_engine);
+            p1::add_to_linker_sync(&mut linker, |state| &mut state.wasi)?;
+            let instance = linker.instantiate(&mut store, &module)?;
+            let start = instance.get_typed_func::<(), ()>(&mut store, "_start")?;
+
+            match start.call(&mut store, ()) {
+                Ok(()) => Ok(None),
+                Err(e) => match e.downcast_ref::<I32Exit>() {
+                    Some(exit) => Ok(Some(exit.0)),
+                    None => Err(e.into()),
+                },
+            }
+        });
+        tokio::pin!(task);
+
+        // The guest runs on a blocking thread; bumping the engine epoch past
+        // the store's deadline makes it trap at the next safepoint.
+        let result = tokio::select! {
+            res = &mut task => res,
+            _ = tokio::time::sleep(timeout) => {
+                engine.increment_epoch();
+                let _ = task.await;
+                bail!("wasm module timed out after {}s", timeout.as_secs_f64());
+            }
+        };
+
+        let stderr_text = String::from_utf8_lossy(&stderr.contents())
+            .trim()
+            .to_string();
+        match result? {
+            Ok(None) | Ok(Some(0)) => {}
+            Ok(Some(code)) => {
+                bail!("wasm module exited with code {}: {}", code, stderr_text);
+            }
+            Err(e) => bail!("wasm module execution failed: {:#}", e),
+        }
+
+        let stdout_bytes = stdout.contents();
+        let mut output = NodeOutput::new();
+        if stdout_bytes.iter().all(|b| b.is_ascii_whitespace()) {
+            return Ok(output);
+        }
+
+        let value: serde_json::Value = serde_json::from_slice(&stdout_bytes).map_err(|e| {
+            anyhow::anyhow!("wasm module did not write valid JSON to stdout: {}", e)
+        })?;
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, val) in map {
+                    output.insert(key, val);
+                }
+            }
+            serde_json::Value::Null => {}
+            other => {
+                output.insert("result".to_string(), other);
+            }
+        }
+
+        Ok(output)
+    }
+}
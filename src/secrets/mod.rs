@@ -0,0 +1,139 @@
+//! Run-time secret lookup for `${secret:<provider>:<path>}` placeholders.
+//!
+//! Before a step runs, the engine collects the secret placeholders in its
+//! config, fetches each one from the named [`SecretProvider`] and substitutes
+//! the values, so flows reference credentials by path instead of embedding
+//! them. The stored `resolved_config` in task history keeps the placeholders,
+//! never the values.
+//!
+//! Providers are pluggable: [`SecretResolver::with_builtins`] registers AWS
+//! SSM Parameter Store as `ssm`; embedders add their own (Vault, a test
+//! double, ...) with [`SecretResolver::register`].
+
+mod ssm;
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+
+use crate::lua::interpolate::{secret_refs, substitute_secrets};
+use crate::util::bounded_cache::BoundedCache;
+
+pub use ssm::SsmProvider;
+
+const DEFAULT_CACHE_TTL_SECS: u64 = 300;
+const MAX_CACHED_SECRETS: usize = 1024;
+
+/// A backend that can look up a secret by path.
+#[async_trait]
+pub trait SecretProvider: Send + Sync {
+    /// Name used in placeholders, e.g. `ssm` for `${secret:ssm:/path}`.
+    fn name(&self) -> &str;
+
+    /// Fetch the secret stored at `path`.
+    async fn fetch(&self, path: &str) -> Result<String>;
+}
+
+/// Registry of secret providers plus a short-lived cache of fetched values.
+pub struct SecretResolver {
+    providers: HashMap<String, Arc<dyn SecretProvider>>,
+    cache: BoundedCache<String, String>,
+    cache_ttl_secs: Option<u64>,
+}
+
+impl Default for SecretResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SecretResolver {
+    /// An empty resolver. Values are cached for
+    /// `IRONFLOW_SECRET_CACHE_TTL_SECS` (default 300; `0` disables caching).
+    pub fn new() -> Self {
+        let cache_ttl_secs = std::env::var("IRONFLOW_SECRET_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_CACHE_TTL_SECS);
+        Self {
+            providers: HashMap::new(),
+            cache: BoundedCache::new(MAX_CACHED_SECRETS),
+            cache_ttl_secs: (cache_ttl_secs > 0).then_some(cache_ttl_secs),
+        }
+    }
+
+    /// A resolver with the built-in providers (`ssm`).
+    pub fn with_builtins() -> Self {
+        let mut resolver = Self::new();
+        resolver.register(Arc::new(SsmProvider::new()));
+        resolver
+    }
+
+    /// Process-wide built-in resolver, shared by engines that were not given
+    /// one so parent and subworkflow runs reuse the same cache.
+    pub fn shared() -> Arc<Self> {
+        static SHARED: OnceLock<Arc<SecretResolver>> = OnceLock::new();
+        SHARED
+            .get_or_init(|| Arc::new(Self::with_builtins()))
+            .clone()
+    }
+
+    /// Register a provider, replacing any with the same name.
+    pub fn register(&mut self, provider: Arc<dyn SecretProvider>) {
+        self.providers.insert(provider.name().to_string(), provider);
+    }
+
+    /// Resolve one `<provider>:<path>` reference.
+    pub async fn resolve(&self, reference: &str) -> Result<String> {
+        if self.cache_ttl_secs.is_some()
+            && let Some(value) = self.cache.get(&reference.to_string())
+        {
+            return Ok(value);
+        }
+
+        let (provider_name, path) = reference.split_once(':').ok_or_else(|| {
+            anyhow::anyhow!(
+                "Invalid secret reference '{}': expected '${{secret:<provider>:<path>}}'",
+                reference
+            )
+        })?;
+        let provider = self.providers.get(provider_name).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown secret provider '{}' in '${{secret:{}}}'",
+                provider_name,
+                reference
+            )
+        })?;
+        let value = provider
+            .fetch(path)
+            .await
+            .with_context(|| format!("Failed to resolve secret '{}'", reference))?;
+
+        if self.cache_ttl_secs.is_some() {
+            self.cache
+                .insert(reference.to_string(), value.clone(), self.cache_ttl_secs);
+        }
+        Ok(value)
+    }
+
+    /// Substitute every `${secret:...}` placeholder in a step config.
+    /// Configs without placeholders are returned as-is.
+    pub async fn resolve_config<'a>(
+        &self,
+        config: &'a serde_json::Value,
+    ) -> Result<Cow<'a, serde_json::Value>> {
+        let refs = secret_refs(config);
+        if refs.is_empty() {
+            return Ok(Cow::Borrowed(config));
+        }
+        let mut values = HashMap::with_capacity(refs.len());
+        for reference in refs {
+            let value = self.resolve(&reference).await?;
+            values.insert(reference, value);
+        }
+        Ok(Cow::Owned(substitute_secrets(config, &values)))
+    }
+}
@@ -0,0 +1,138 @@
+//! AWS Systems Manager Parameter Store provider (`${secret:ssm:/path}`).
+//!
+//! Calls `GetParameter` with decryption over the SSM JSON protocol, signed
+//! with SigV4 using the standard AWS credential chain and region.
+
+use std::time::SystemTime;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use aws_credential_types::provider::ProvideCredentials;
+use aws_sigv4::http_request::{SignableBody, SignableRequest, SigningSettings, sign};
+use aws_sigv4::sign::v4;
+use tokio::sync::OnceCell;
+
+use super::SecretProvider;
+
+pub struct SsmProvider {
+    endpoint_url: Option<String>,
+    sdk_config: OnceCell<aws_config::SdkConfig>,
+    client: reqwest::Client,
+}
+
+impl Default for SsmProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SsmProvider {
+    /// Endpoint from `AWS_ENDPOINT_URL_SSM` or `AWS_ENDPOINT_URL`, else the
+    /// regional AWS endpoint.
+    pub fn new() -> Self {
+        let endpoint_url = std::env::var("AWS_ENDPOINT_URL_SSM")
+            .or_else(|_| std::env::var("AWS_ENDPOINT_URL"))
+            .ok();
+        Self::with_endpoint_opt(endpoint_url)
+    }
+
+    /// Talk to a specific endpoint (LocalStack, a mock server).
+    pub fn with_endpoint(endpoint_url: impl Into<String>) -> Self {
+        Self::with_endpoint_opt(Some(endpoint_url.into()))
+    }
+
+    fn with_endpoint_opt(endpoint_url: Option<String>) -> Self {
+        Self {
+            endpoint_url,
+            sdk_config: OnceCell::new(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn sdk_config(&self) -> &aws_config::SdkConfig {
+        self.sdk_config
+            .get_or_init(|| aws_config::defaults(aws_config::BehaviorVersion::latest()).load())
+            .await
+    }
+}
+
+#[async_trait]
+impl SecretProvider for SsmProvider {
+    fn name(&self) -> &str {
+        "ssm"
+    }
+
+    async fn fetch(&self, path: &str) -> Result<String> {
+        let sdk_config = self.sdk_config().await;
+        let region = sdk_config
+            .region()
+            .map(ToString::to_string)
+            .ok_or_else(|| anyhow::anyhow!("SSM secrets need a region (set AWS_REGION)"))?;
+        let endpoint = self
+            .endpoint_url
+            .clone()
+            .unwrap_or_else(|| format!("https://ssm.{}.amazonaws.com", region));
+        let url = format!("{}/", endpoint.trim_end_matches('/'));
+
+        let credentials = sdk_config
+            .credentials_provider()
+            .ok_or_else(|| anyhow::anyhow!("No AWS credentials available for SSM"))?
+            .provide_credentials()
+            .await?;
+        let identity = credentials.into();
+
+        let body = serde_json::json!({ "Name": path, "WithDecryption": true }).to_string();
+        let headers = [
+            ("content-type", "application/x-amz-json-1.1"),
+            ("x-amz-target", "AmazonSSM.GetParameter"),
+        ];
+
+        let signing_params = v4::SigningParams::builder()
+            .identity(&identity)
+            .region(&region)
+            .name("ssm")
+            .time(SystemTime::now())
+            .settings(SigningSettings::default())
+            .build()?
+            .into();
+        let signable = SignableRequest::new(
+            "POST",
+            url.as_str(),
+            headers.iter().copied(),
+            SignableBody::Bytes(body.as_bytes()),
+        )?;
+        let (instructions, _) = sign(signable, &signing_params)?.into_parts();
+
+        let mut request = self.client.post(&url).body(body);
+        for (name, value) in headers.iter().copied().chain(instructions.headers()) {
+            request = request.header(name, value);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        let payload: serde_json::Value = response.json().await.unwrap_or_default();
+
+        if !status.is_success() {
+            let kind = payload
+                .get("__type")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Error");
+            let message = payload
+                .get("message")
+                .or_else(|| payload.get("Message"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            anyhow::bail!(
+                "SSM GetParameter returned {} ({}) {}",
+                status,
+                kind,
+                message
+            );
+        }
+
+        payload
+            .pointer("/Parameter/Value")
+            .and_then(|v| v.as_str())
+            .map(ToString::to_string)
+            .ok_or_else(|| anyhow::anyhow!("SSM GetParameter response has no Parameter.Value"))
+    }
+}
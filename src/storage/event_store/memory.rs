@@ -2,19 +2,25 @@ use std::collections::HashMap;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, broadcast};
 
 use crate::engine::events::RunEvent;
 use crate::storage::event_store::EventStore;
 
+/// Subscribers that fall further behind than this miss the wake-up, not the
+/// event: streams re-read from `list_since`.
+const LIVE_CHANNEL_CAPACITY: usize = 1024;
+
 pub struct MemoryEventStore {
     events: RwLock<HashMap<String, Vec<RunEvent>>>,
+    live: broadcast::Sender<RunEvent>,
 }
 
 impl MemoryEventStore {
     pub fn new() -> Self {
         Self {
             events: RwLock::new(HashMap::new()),
+            live: broadcast::channel(LIVE_CHANNEL_CAPACITY).0,
         }
     }
 }
@@ -33,7 +39,9 @@ impl EventStore for MemoryEventStore {
             .await
             .entry(event.run_id.clone())
             .or_default()
-            .push(event);
+            .push(event.clone());
+        // No receivers just means nobody is streaming right now.
+        let _ = self.live.send(event);
         Ok(())
     }
 
@@ -55,4 +63,8 @@ impl EventStore for MemoryEventStore {
 
         Ok(run_events.iter().skip(start).take(limit).cloned().collect())
     }
+
+    fn subscribe(&self) -> Option<broadcast::Receiver<RunEvent>> {
+        Some(self.live.subscribe())
+    }
 }
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use tokio::sync::broadcast;
 
 use crate::engine::events::RunEvent;
 
@@ -23,4 +24,11 @@ pub trait EventStore: Send + Sync {
         after: Option<&str>,
         limit: usize,
     ) -> Result<Vec<RunEvent>>;
+
+    /// Live feed of events published through this store instance, used to
+    /// wake SSE streams without waiting for the next poll. Stores without
+    /// one are polled.
+    fn subscribe(&self) -> Option<broadcast::Receiver<RunEvent>> {
+        None
+    }
 }
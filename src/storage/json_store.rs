@@ -6,6 +6,7 @@ use async_trait::async_trait;
 use chrono::Utc;
 use tokio::sync::RwLock;
 
+use crate::engine::profile::RunProfile;
 use crate::engine::types::*;
 use crate::storage::StateStore;
 
@@ -38,6 +39,11 @@ impl JsonStateStore {
         self.base_dir.join(format!("{}.summary.json", run_id))
     }
 
+    /// Dedupe index entry for a content hash.
+    fn dedupe_path(&self, key: &str) -> PathBuf {
+        self.base_dir.join("dedupe").join(format!("{}.json", key))
+    }
+
     async fn read_run(&self, run_id: &str) -> Result<RunInfo> {
         let path = self.run_path(run_id);
         let data = tokio::fs::read_to_string(&path)
@@ -87,6 +93,7 @@ impl StateStore for JsonStateStore {
             finished: None,
             ctx: ctx.clone(),
             tasks: HashMap::new(),
+            profile: None,
         };
 
         self.write_run(run_id, &info).await
@@ -107,6 +114,7 @@ impl StateStore for JsonStateStore {
         let _lock = self.lock.write().await;
         let mut info = self.read_run(run_id).await?;
         info.tasks.insert(task.name.clone(), task.clone());
+        info.profile = RunProfile::from_tasks(&info.tasks);
         self.write_run(run_id, &info).await
     }
 
@@ -174,6 +182,50 @@ impl StateStore for JsonStateStore {
         Ok(())
     }
 
+    async fn claim_dedupe_key(
+        &self,
+        key: &str,
+        run_id: &str,
+        ttl: std::time::Duration,
+    ) -> Result<Option<String>> {
+        let _lock = self.lock.write().await;
+        let path = self.dedupe_path(key);
+        let now = Utc::now();
+
+        if let Ok(data) = tokio::fs::read_to_string(&path).await
+            && let Ok(entry) = serde_json::from_str::<DedupeEntry>(&data)
+            && entry.expires > now
+        {
+            return Ok(Some(entry.run_id));
+        }
+
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let entry = DedupeEntry {
+            run_id: run_id.to_string(),
+            expires: now + chrono::Duration::from_std(ttl)?,
+        };
+        let tmp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, serde_json::to_string(&entry)?).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+        Ok(None)
+    }
+
+    /// Confirms the store directory exists (creating it if needed) and is
+    /// writable by round-tripping a small probe file.
+    async fn ping(&self) -> Result<()> {
+        tokio::fs::create_dir_all(&self.base_dir)
+            .await
+            .with_context(|| format!("Store directory unavailable: {}", self.base_dir.display()))?;
+        let probe = self.base_dir.join(".ironflow-health");
+        tokio::fs::write(&probe, b"ok").await.with_context(|| {
+            format!("Store directory not writable: {}", self.base_dir.display())
+        })?;
+        let _ = tokio::fs::remove_file(&probe).await;
+        Ok(())
+    }
+
     /// Native summary listing — reads only `*.summary.json` sidecar files.
     /// Falls back to parsing the main record for any run missing a sidecar
     /// (covers data written before the sidecar was introduced).
@@ -238,3 +290,9 @@ impl StateStore for JsonStateStore {
         Ok(summaries)
     }
 }
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct DedupeEntry {
+    run_id: String,
+    expires: chrono::DateTime<Utc>,
+}
@@ -49,9 +49,49 @@ pub trait StateStore: Send + Sync {
         Ok(runs.iter().map(RunSummary::from).collect())
     }
 
+    /// Per-day run statistics for `flow_name`, covering runs started at or
+    /// after `since`. Default implementation aggregates
+    /// `list_run_summaries`; stores with a query engine SHOULD override it
+    /// to read only the flow's runs inside the window.
+    async fn flow_daily_stats(
+        &self,
+        flow_name: &str,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<FlowDayStats>> {
+        let runs: Vec<RunSummary> = self
+            .list_run_summaries(None)
+            .await?
+            .into_iter()
+            .filter(|r| r.flow_name == flow_name)
+            .collect();
+        Ok(FlowDayStats::aggregate(&runs, since, chrono::Utc::now()))
+    }
+
     /// Delete a run record.
     async fn delete_run(&self, run_id: &str) -> Result<()>;
 
+    /// Atomically point the dedupe hash `key` at `run_id` for `ttl`, unless
+    /// an unexpired entry already exists. Returns the run ID of that entry
+    /// when there is one (the caller should not start a new run), `None`
+    /// when `run_id` claimed the key. Stores that cannot keep the index
+    /// return an error.
+    async fn claim_dedupe_key(
+        &self,
+        key: &str,
+        run_id: &str,
+        ttl: std::time::Duration,
+    ) -> Result<Option<String>> {
+        let _ = (key, run_id, ttl);
+        anyhow::bail!("This state store does not support run deduplication")
+    }
+
+    /// Readiness check used by the API health probe. Stores backed by
+    /// external resources SHOULD override this to confirm the backend is
+    /// reachable and writable; the default assumes an in-process store.
+    async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+
     /// Delete runs older than the given cutoff (UTC). Returns the number
     /// removed. Default implementation scans via `list_runs`; stores that
     /// track metadata separately MAY override with an index-only path.
@@ -4,6 +4,7 @@ use std::sync::Mutex;
 use anyhow::Result;
 use async_trait::async_trait;
 
+use crate::engine::profile::RunProfile;
 use crate::engine::types::*;
 use crate::storage::StateStore;
 
@@ -11,6 +12,8 @@ use crate::storage::StateStore;
 /// Holds run state only for the lifetime of the store instance.
 pub struct NullStateStore {
     runs: Mutex<HashMap<String, RunInfo>>,
+    /// Dedupe hash → (run ID, expiry).
+    dedupe: Mutex<HashMap<String, (String, chrono::DateTime<chrono::Utc>)>>,
 }
 
 impl NullStateStore {
@@ -18,6 +21,7 @@ impl NullStateStore {
     pub fn new() -> Self {
         Self {
             runs: Mutex::new(HashMap::new()),
+            dedupe: Mutex::new(HashMap::new()),
         }
     }
 }
@@ -40,6 +44,7 @@ impl StateStore for NullStateStore {
             finished: None,
             ctx: ctx.clone(),
             tasks: HashMap::new(),
+            profile: None,
         };
         self.runs
             .lock()
@@ -62,6 +67,7 @@ impl StateStore for NullStateStore {
     async fn upsert_task(&self, run_id: &str, task: &TaskState) -> Result<()> {
         if let Some(run) = self.runs.lock().unwrap().get_mut(run_id) {
             run.tasks.insert(task.name.clone(), task.clone());
+            run.profile = RunProfile::from_tasks(&run.tasks);
         }
         Ok(())
     }
@@ -97,4 +103,22 @@ impl StateStore for NullStateStore {
         self.runs.lock().unwrap().remove(run_id);
         Ok(())
     }
+
+    async fn claim_dedupe_key(
+        &self,
+        key: &str,
+        run_id: &str,
+        ttl: std::time::Duration,
+    ) -> Result<Option<String>> {
+        let now = chrono::Utc::now();
+        let mut dedupe = self.dedupe.lock().unwrap();
+        if let Some((existing, expires)) = dedupe.get(key)
+            && *expires > now
+        {
+            return Ok(Some(existing.clone()));
+        }
+        let expires = now + chrono::Duration::from_std(ttl)?;
+        dedupe.insert(key.to_string(), (run_id.to_string(), expires));
+        Ok(None)
+    }
 }
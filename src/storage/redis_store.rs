@@ -4,6 +4,7 @@ use chrono::Utc;
 use redis::AsyncCommands;
 use std::collections::HashMap;
 
+use crate::engine::profile::RunProfile;
 use crate::engine::types::*;
 use crate::storage::StateStore;
 
@@ -41,6 +42,11 @@ impl RedisStateStore {
         format!("{}runs:{}", self.prefix, run_id)
     }
 
+    /// Key for a dedupe hash: `{prefix}dedupe:{hash}`
+    fn dedupe_key(&self, hash: &str) -> String {
+        format!("{}dedupe:{}", self.prefix, hash)
+    }
+
     /// Key for the index set: `{prefix}runs:index`
     fn index_key(&self) -> String {
         format!("{}runs:index", self.prefix)
@@ -105,6 +111,7 @@ impl StateStore for RedisStateStore {
             finished: None,
             ctx: ctx.clone(),
             tasks: HashMap::new(),
+            profile: None,
         };
 
         self.write_run(run_id, &info).await?;
@@ -131,6 +138,7 @@ impl StateStore for RedisStateStore {
     async fn upsert_task(&self, run_id: &str, task: &TaskState) -> Result<()> {
         let mut info = self.read_run(run_id).await?;
         info.tasks.insert(task.name.clone(), task.clone());
+        info.profile = RunProfile::from_tasks(&info.tasks);
         self.write_run(run_id, &info).await
     }
 
@@ -233,6 +241,15 @@ impl StateStore for RedisStateStore {
         Ok(summaries)
     }
 
+    async fn ping(&self) -> Result<()> {
+        let mut conn = self.conn.clone();
+        let _: String = redis::cmd("PING")
+            .query_async(&mut conn)
+            .await
+            .context("Redis PING failed")?;
+        Ok(())
+    }
+
     async fn delete_run(&self, run_id: &str) -> Result<()> {
         let mut conn = self.conn.clone();
         let key = self.run_key(run_id);
@@ -249,4 +266,40 @@ impl StateStore for RedisStateStore {
 
         Ok(())
     }
+
+    async fn claim_dedupe_key(
+        &self,
+        key: &str,
+        run_id: &str,
+        ttl: std::time::Duration,
+    ) -> Result<Option<String>> {
+        let mut conn = self.conn.clone();
+        let redis_key = self.dedupe_key(key);
+        let ttl_ms = ttl.as_millis().clamp(1, u64::MAX as u128) as u64;
+
+        // SET NX is the atomic claim; Redis expires the entry on its own.
+        // Retry once if the winner's entry expires between SET and GET.
+        for _ in 0..2 {
+            let claimed: Option<String> = redis::cmd("SET")
+                .arg(&redis_key)
+                .arg(run_id)
+                .arg("NX")
+                .arg("PX")
+                .arg(ttl_ms)
+                .query_async(&mut conn)
+                .await
+                .with_context(|| format!("Redis SET NX failed for dedupe key {}", key))?;
+            if claimed.is_some() {
+                return Ok(None);
+            }
+            let existing: Option<String> = conn
+                .get(&redis_key)
+                .await
+                .with_context(|| format!("Redis GET failed for dedupe key {}", key))?;
+            if existing.is_some() {
+                return Ok(existing);
+            }
+        }
+        anyhow::bail!("Could not claim dedupe key {}", key)
+    }
 }
@@ -33,6 +33,7 @@ pub struct SqlStateTableNames {
     pub tasks: String,
     pub runs_status_started_idx: String,
     pub tasks_run_id_idx: String,
+    pub dedupe: String,
 }
 
 #[derive(Debug, Clone)]
@@ -49,11 +50,13 @@ impl SqlStateTableNames {
             tasks: format!("{prefix}tasks"),
             runs_status_started_idx: format!("{prefix}runs_status_started_idx"),
             tasks_run_id_idx: format!("{prefix}tasks_run_id_idx"),
+            dedupe: format!("{prefix}dedupe"),
         };
         validate_identifier(&names.runs)?;
         validate_identifier(&names.tasks)?;
         validate_identifier(&names.runs_status_started_idx)?;
         validate_identifier(&names.tasks_run_id_idx)?;
+        validate_identifier(&names.dedupe)?;
         Ok(names)
     }
 }
@@ -6,6 +6,7 @@ use chrono::{DateTime, Utc};
 use sqlx::any::AnyPoolOptions;
 use sqlx::{AnyPool, Row};
 
+use crate::engine::profile::RunProfile;
 use crate::engine::types::*;
 use crate::storage::StateStore;
 use crate::storage::sql_names::{SqlDialect, SqlStateTableNames};
@@ -73,7 +74,7 @@ impl SqlStateStore {
 
     async fn read_tasks(&self, run_id: &str) -> Result<HashMap<String, TaskState>> {
         let sql = format!(
-            "SELECT name, node_type, status, attempt, input, output, error, started, finished \
+            "SELECT name, node_type, status, attempt, input, output, error, error_detail, timings, resolved_config, started, finished \
              FROM {} WHERE run_id = {}",
             self.tables.tasks,
             self.placeholder(1)
@@ -94,8 +95,17 @@ impl SqlStateStore {
                 input: parse_optional_json(row.try_get("input")?)?,
                 output: parse_optional_json(row.try_get("output")?)?,
                 error: row.try_get("error")?,
+                error_detail: row
+                    .try_get::<Option<String>, _>("error_detail")?
+                    .map(|raw| serde_json::from_str(&raw))
+                    .transpose()?,
                 started: parse_optional_datetime(row.try_get("started")?)?,
                 finished: parse_optional_datetime(row.try_get("finished")?)?,
+                timings: row
+                    .try_get::<Option<String>, _>("timings")?
+                    .map(|raw| serde_json::from_str(&raw))
+                    .transpose()?,
+                resolved_config: parse_optional_json(row.try_get("resolved_config")?)?,
             };
             tasks.insert(name, task);
         }
@@ -114,6 +124,7 @@ impl SqlStateStore {
             started: parse_optional_datetime(row.try_get("started")?)?,
             finished: parse_optional_datetime(row.try_get("finished")?)?,
             ctx: serde_json::from_str(&ctx_raw)?,
+            profile: RunProfile::from_tasks(&tasks),
             tasks,
         })
     }
@@ -145,6 +156,7 @@ impl StateStore for SqlStateStore {
             finished: None,
             ctx: ctx.clone(),
             tasks: HashMap::new(),
+            profile: None,
         };
         self.upsert_run(&info).await
     }
@@ -189,10 +201,11 @@ impl StateStore for SqlStateStore {
 
     async fn upsert_task(&self, run_id: &str, task: &TaskState) -> Result<()> {
         let sql = format!(
-            "INSERT INTO {} (run_id, name, node_type, status, attempt, input, output, error, started, finished) \
-             VALUES ({}, {}, {}, {}, {}, {}, {}, {}, {}, {}) \
+            "INSERT INTO {} (run_id, name, node_type, status, attempt, input, output, error, error_detail, timings, resolved_config, started, finished) \
+             VALUES ({}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}) \
              ON CONFLICT(run_id, name) DO UPDATE SET node_type = excluded.node_type, status = excluded.status, \
              attempt = excluded.attempt, input = excluded.input, output = excluded.output, error = excluded.error, \
+             error_detail = excluded.error_detail, timings = excluded.timings, resolved_config = excluded.resolved_config, \
              started = excluded.started, finished = excluded.finished",
             self.tables.tasks,
             self.placeholder(1),
@@ -205,6 +218,9 @@ impl StateStore for SqlStateStore {
             self.placeholder(8),
             self.placeholder(9),
             self.placeholder(10),
+            self.placeholder(11),
+            self.placeholder(12),
+            self.placeholder(13),
         );
 
         sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
@@ -216,6 +232,19 @@ impl StateStore for SqlStateStore {
             .bind(optional_json_to_string(&task.input)?)
             .bind(optional_json_to_string(&task.output)?)
             .bind(&task.error)
+            .bind(
+                task.error_detail
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?,
+            )
+            .bind(
+                task.timings
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?,
+            )
+            .bind(optional_json_to_string(&task.resolved_config)?)
             .bind(datetime_to_string(task.started))
             .bind(datetime_to_string(task.finished))
             .execute(&self.pool)
@@ -340,12 +369,101 @@ impl StateStore for SqlStateStore {
         Ok(())
     }
 
+    async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .context("SQL state store is unreachable")?;
+        Ok(())
+    }
+
+    async fn claim_dedupe_key(
+        &self,
+        key: &str,
+        run_id: &str,
+        ttl: std::time::Duration,
+    ) -> Result<Option<String>> {
+        // Fixed-width UTC timestamps so expiry compares as a string.
+        let now = Utc::now();
+        let expires = now + chrono::Duration::from_std(ttl)?;
+        let fmt = |t: DateTime<Utc>| t.to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+
+        let sql = format!(
+            "DELETE FROM {} WHERE hash = {} AND expires_at <= {}",
+            self.tables.dedupe,
+            self.placeholder(1),
+            self.placeholder(2)
+        );
+        sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
+            .bind(key)
+            .bind(fmt(now))
+            .execute(&self.pool)
+            .await?;
+
+        // The primary key makes the insert the atomic claim.
+        let sql = format!(
+            "INSERT INTO {} (hash, run_id, expires_at) VALUES ({}, {}, {}) \
+             ON CONFLICT(hash) DO NOTHING",
+            self.tables.dedupe,
+            self.placeholder(1),
+            self.placeholder(2),
+            self.placeholder(3)
+        );
+        let inserted = sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
+            .bind(key)
+            .bind(run_id)
+            .bind(fmt(expires))
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        if inserted > 0 {
+            return Ok(None);
+        }
+
+        let sql = format!(
+            "SELECT run_id FROM {} WHERE hash = {}",
+            self.tables.dedupe,
+            self.placeholder(1)
+        );
+        let row = sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
+            .bind(key)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(Some(row.try_get("run_id")?))
+    }
+
+    async fn flow_daily_stats(
+        &self,
+        flow_name: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<FlowDayStats>> {
+        // Timestamps are stored as RFC 3339 UTC strings, which sort in time
+        // order, so the window filter can run in the database.
+        let sql = format!(
+            "SELECT id, flow_name, status, started, finished, 0 AS task_count \
+             FROM {} WHERE flow_name = {} AND started >= {}",
+            self.tables.runs,
+            self.placeholder(1),
+            self.placeholder(2)
+        );
+        let rows = sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
+            .bind(flow_name)
+            .bind(since.to_rfc3339())
+            .fetch_all(&self.pool)
+            .await?;
+        let runs = rows
+            .iter()
+            .map(Self::row_to_summary)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(FlowDayStats::aggregate(&runs, since, Utc::now()))
+    }
+
     async fn prune_before(&self, cutoff: DateTime<Utc>) -> Result<usize> {
         let sql = format!(
             "SELECT r.id, r.flow_name, r.status, r.started, r.finished, COUNT(t.name) AS task_count \
              FROM {} r \
              LEFT JOIN {} t ON t.run_id = r.id \
-             WHERE r.started < {} AND r.status IN ('success', 'failed', 'stalled') \
+             WHERE r.started < {} AND r.status IN ('success', 'failed', 'stalled', 'cancelled') \
              GROUP BY r.id, r.flow_name, r.status, r.started, r.finished",
             self.tables.runs,
             self.tables.tasks,
@@ -401,6 +519,7 @@ fn parse_run_status(value: &str) -> Result<RunStatus> {
         "success" => Ok(RunStatus::Success),
         "failed" => Ok(RunStatus::Failed),
         "stalled" => Ok(RunStatus::Stalled),
+        "cancelled" => Ok(RunStatus::Cancelled),
         _ => anyhow::bail!("Invalid run status '{}'", value),
     }
 }
@@ -412,6 +531,7 @@ fn parse_task_status(value: &str) -> Result<TaskStatus> {
         "success" => Ok(TaskStatus::Success),
         "failed" => Ok(TaskStatus::Failed),
         "skipped" => Ok(TaskStatus::Skipped),
+        "timed_out" => Ok(TaskStatus::TimedOut),
         _ => anyhow::bail!("Invalid task status '{}'", value),
     }
 }
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 use super::SqlStateStore;
 
@@ -31,6 +31,9 @@ impl SqlStateStore {
                 input TEXT,
                 output TEXT,
                 error TEXT,
+                error_detail TEXT,
+                timings TEXT,
+                resolved_config TEXT,
                 started TEXT,
                 finished TEXT,
                 PRIMARY KEY (run_id, name)
@@ -41,6 +44,39 @@ impl SqlStateStore {
         .execute(&self.pool)
         .await?;
 
+        // Tables created before these newer columns existed need them
+        // added. SQLite has no `ADD COLUMN IF NOT EXISTS`, so a
+        // duplicate-column failure here just means the schema is already
+        // current; any other failure is a real problem.
+        for column in ["error_detail", "timings", "resolved_config"] {
+            let added = sqlx::query(sqlx::AssertSqlSafe(format!(
+                "ALTER TABLE {} ADD COLUMN {} TEXT",
+                self.tables.tasks, column
+            )))
+            .execute(&self.pool)
+            .await;
+            if let Err(e) = added
+                && !is_duplicate_column(&e)
+            {
+                return Err(e).with_context(|| {
+                    format!("Failed to add column '{}' to {}", column, self.tables.tasks)
+                });
+            }
+        }
+
+        sqlx::query(sqlx::AssertSqlSafe(format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {} (
+                hash TEXT PRIMARY KEY,
+                run_id TEXT NOT NULL,
+                expires_at TEXT NOT NULL
+            )
+            "#,
+            self.tables.dedupe
+        )))
+        .execute(&self.pool)
+        .await?;
+
         sqlx::query(sqlx::AssertSqlSafe(format!(
             "CREATE INDEX IF NOT EXISTS {} ON {}(status, started)",
             self.tables.runs_status_started_idx, self.tables.runs
@@ -58,3 +94,15 @@ impl SqlStateStore {
         Ok(())
     }
 }
+
+/// Whether `err` is the "column already exists" failure of `ALTER TABLE ...
+/// ADD COLUMN`: SQLSTATE 42701 on Postgres, a "duplicate column name"
+/// message on SQLite (which has no dedicated error code for it).
+fn is_duplicate_column(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::Database(db) => {
+            db.code().as_deref() == Some("42701") || db.message().contains("duplicate column name")
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,92 @@
+//! Transparent decompression for nodes that read files or fetch objects.
+//!
+//! A `path` ending in `.gz` is decompressed on read; an explicit
+//! `compressed: true|false` in the node config overrides the extension check.
+//! [`Codec`] covers the `Content-Encoding` values remote stores report.
+
+use std::io::{self, Read};
+
+use flate2::read::MultiGzDecoder;
+
+use super::limits::max_file_bytes;
+
+/// Whether input at `path` should be gunzipped, honouring a `compressed` flag.
+pub fn wants_gunzip(path: &str, config: &serde_json::Value) -> bool {
+    config
+        .get("compressed")
+        .and_then(|v| v.as_bool())
+        .unwrap_or_else(|| path.ends_with(".gz"))
+}
+
+/// Decompress gzip data (including concatenated members), capped at
+/// `IRONFLOW_MAX_FILE_BYTES` so a small archive cannot expand without bound.
+pub fn gunzip(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    read_capped(MultiGzDecoder::new(bytes))
+}
+
+/// A compression format, named as in HTTP `Content-Encoding`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Codec {
+    /// Parse a codec name (`gzip`, `x-gzip`, `zstd`, `bzip2`, `x-bzip2`),
+    /// ignoring case. Returns `None` for `identity` and unknown names.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(Codec::Gzip),
+            "zstd" => Some(Codec::Zstd),
+            "bzip2" | "x-bzip2" => Some(Codec::Bzip2),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Codec::Gzip => "gzip",
+            Codec::Zstd => "zstd",
+            Codec::Bzip2 => "bzip2",
+        }
+    }
+
+    /// Decompress `bytes`, with the same size cap as [`gunzip`].
+    pub fn decode(self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Codec::Gzip => gunzip(bytes),
+            Codec::Zstd => read_capped(zstd::stream::read::Decoder::new(bytes)?),
+            Codec::Bzip2 => read_capped(bzip2::read::MultiBzDecoder::new(bytes)),
+        }
+    }
+}
+
+fn read_capped(reader: impl Read) -> io::Result<Vec<u8>> {
+    let limit = max_file_bytes();
+    let mut out = Vec::new();
+    reader.take(limit.saturating_add(1)).read_to_end(&mut out)?;
+    if out.len() as u64 > limit {
+        return Err(io::Error::other(format!(
+            "decompressed data exceeds limit {} bytes (set IRONFLOW_MAX_FILE_BYTES to raise)",
+            limit
+        )));
+    }
+    Ok(out)
+}
+
+/// Read a file, decompressing it when [`wants_gunzip`] says so.
+pub fn read_file_bytes(path: &str, config: &serde_json::Value) -> io::Result<Vec<u8>> {
+    let bytes = std::fs::read(path)?;
+    if wants_gunzip(path, config) {
+        gunzip(&bytes)
+    } else {
+        Ok(bytes)
+    }
+}
+
+/// [`read_file_bytes`] decoded as UTF-8.
+pub fn read_file_text(path: &str, config: &serde_json::Value) -> io::Result<String> {
+    String::from_utf8(read_file_bytes(path, config)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
@@ -29,6 +29,9 @@ const DEFAULT_DB_MAX_ROWS: u64 = 1_000;
 /// Default cap for serialized `db_query` JSON rows (10 MB).
 const DEFAULT_DB_MAX_RESULT_BYTES: u64 = 10 * 1024 * 1024;
 
+/// Default cap for connections per database connection pool.
+const DEFAULT_DB_POOL_MAX: u64 = 10;
+
 /// Default cap for directory listings and ZIP entry enumeration.
 const DEFAULT_MAX_DIRECTORY_ENTRIES: u64 = 10_000;
 
@@ -65,6 +68,21 @@ const DEFAULT_LUA_MAX_MEMORY_BYTES: u64 = 128 * 1024 * 1024;
 /// How often the Lua debug hook checks budgets.
 const DEFAULT_LUA_HOOK_INTERVAL: u64 = 10_000;
 
+/// Default cap for the serialized workflow context (disabled).
+const DEFAULT_MAX_CONTEXT_BYTES: u64 = 0;
+
+/// Default cap for a single serialized context value (disabled).
+const DEFAULT_MAX_CONTEXT_VALUE_BYTES: u64 = 0;
+
+/// Default WASM fuel budget per module run.
+const DEFAULT_WASM_MAX_FUEL: u64 = 1_000_000_000;
+
+/// Default WASM linear memory cap (128 MB).
+const DEFAULT_WASM_MAX_MEMORY_BYTES: u64 = 128 * 1024 * 1024;
+
+/// Default cap for captured WASM `stdout`/`stderr` (10 MB each).
+const DEFAULT_WASM_OUTPUT_BYTES: u64 = 10 * 1024 * 1024;
+
 fn env_u64(var: &str, default: u64) -> u64 {
     std::env::var(var)
         .ok()
@@ -119,6 +137,10 @@ pub fn max_db_result_bytes() -> Option<u64> {
     env_optional_u64("IRONFLOW_DB_MAX_RESULT_BYTES", DEFAULT_DB_MAX_RESULT_BYTES)
 }
 
+pub fn db_pool_max() -> u64 {
+    env_u64("IRONFLOW_DB_POOL_MAX", DEFAULT_DB_POOL_MAX)
+}
+
 pub fn max_directory_entries() -> u64 {
     env_u64(
         "IRONFLOW_MAX_DIRECTORY_ENTRIES",
@@ -189,6 +211,32 @@ pub fn lua_gc_after_execution() -> bool {
     env_bool("IRONFLOW_LUA_GC_AFTER_EXECUTION", true)
 }
 
+pub fn max_context_bytes() -> Option<u64> {
+    env_optional_u64("IRONFLOW_MAX_CONTEXT_BYTES", DEFAULT_MAX_CONTEXT_BYTES)
+}
+
+pub fn max_context_value_bytes() -> Option<u64> {
+    env_optional_u64(
+        "IRONFLOW_MAX_CONTEXT_VALUE_BYTES",
+        DEFAULT_MAX_CONTEXT_VALUE_BYTES,
+    )
+}
+
+pub fn max_wasm_fuel() -> Option<u64> {
+    env_optional_u64("IRONFLOW_WASM_MAX_FUEL", DEFAULT_WASM_MAX_FUEL)
+}
+
+pub fn max_wasm_memory_bytes() -> u64 {
+    env_u64(
+        "IRONFLOW_WASM_MAX_MEMORY_BYTES",
+        DEFAULT_WASM_MAX_MEMORY_BYTES,
+    )
+}
+
+pub fn max_wasm_output_bytes() -> u64 {
+    env_u64("IRONFLOW_WASM_MAX_OUTPUT_BYTES", DEFAULT_WASM_OUTPUT_BYTES)
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct LuaExecutionLimits {
     pub max_instructions: Option<u64>,
@@ -196,6 +244,8 @@ pub struct LuaExecutionLimits {
     pub max_memory_bytes: Option<u64>,
     pub hook_interval: u32,
     pub gc_after_execution: bool,
+    /// Per-step wall-clock budget from a `code` step's `timeout_ms`.
+    pub step_timeout_ms: Option<u64>,
 }
 
 impl LuaExecutionLimits {
@@ -206,8 +256,28 @@ impl LuaExecutionLimits {
             max_memory_bytes: max_lua_memory_bytes(),
             hook_interval: lua_hook_interval().min(u32::MAX as u64) as u32,
             gc_after_execution: lua_gc_after_execution(),
+            step_timeout_ms: None,
         }
     }
+
+    /// Narrow the time and memory budgets for one step. The process-wide
+    /// budgets stay in force, so a step can lower them but never raise them.
+    pub fn with_step_limits(
+        mut self,
+        timeout_ms: Option<u64>,
+        memory_limit_bytes: Option<u64>,
+    ) -> Self {
+        if let Some(timeout_ms) = timeout_ms {
+            let env_ms = self.max_seconds.map(|s| s.saturating_mul(1000));
+            if env_ms.is_none_or(|env_ms| timeout_ms < env_ms) {
+                self.step_timeout_ms = Some(timeout_ms);
+            }
+        }
+        if let Some(bytes) = memory_limit_bytes {
+            self.max_memory_bytes = Some(self.max_memory_bytes.map_or(bytes, |m| m.min(bytes)));
+        }
+        self
+    }
 }
 
 pub fn apply_lua_limits(lua: &Lua, limits: LuaExecutionLimits) -> Result<()> {
@@ -219,7 +289,10 @@ pub fn apply_lua_limits(lua: &Lua, limits: LuaExecutionLimits) -> Result<()> {
     }
 
     let hook_interval = limits.hook_interval.max(1);
-    if limits.max_instructions.is_none() && limits.max_seconds.is_none() {
+    if limits.max_instructions.is_none()
+        && limits.max_seconds.is_none()
+        && limits.step_timeout_ms.is_none()
+    {
         return Ok(());
     }
 
@@ -227,9 +300,12 @@ pub fn apply_lua_limits(lua: &Lua, limits: LuaExecutionLimits) -> Result<()> {
         .max_instructions
         .map(|max| Arc::new(AtomicI64::new(max.min(i64::MAX as u64) as i64)));
     let max_duration = limits.max_seconds.map(Duration::from_secs);
+    let step_timeout = limits.step_timeout_ms.map(Duration::from_millis);
     let started = Instant::now();
 
-    lua.set_hook(
+    // A global hook is inherited by every coroutine, including the threads
+    // `call_async` runs chunks on; `set_hook` would cover the main thread only.
+    lua.set_global_hook(
         LuaHookTriggers::new().every_nth_instruction(hook_interval),
         move |_lua, _debug| {
             if let Some(ref remaining) = remaining
@@ -242,6 +318,15 @@ pub fn apply_lua_limits(lua: &Lua, limits: LuaExecutionLimits) -> Result<()> {
                 )));
             }
 
+            if let Some(step_timeout) = step_timeout
+                && started.elapsed() >= step_timeout
+            {
+                return Err(LuaError::runtime(format!(
+                    "lua script exceeded {}ms",
+                    step_timeout.as_millis()
+                )));
+            }
+
             if let Some(max_duration) = max_duration
                 && started.elapsed() >= max_duration
             {
@@ -258,6 +343,21 @@ pub fn apply_lua_limits(lua: &Lua, limits: LuaExecutionLimits) -> Result<()> {
     Ok(())
 }
 
+/// Render a Lua error, naming the memory budget when an allocation failed
+/// against it.
+pub fn describe_lua_error(err: &LuaError, limits: LuaExecutionLimits) -> String {
+    let mut cause = err;
+    while let LuaError::CallbackError { cause: inner, .. } = cause {
+        cause = inner;
+    }
+    match (cause, limits.max_memory_bytes) {
+        (LuaError::MemoryError(_), Some(bytes)) => {
+            format!("lua script exceeded memory limit of {} bytes", bytes)
+        }
+        _ => err.to_string(),
+    }
+}
+
 pub fn collect_lua_garbage(lua: &Lua, limits: LuaExecutionLimits) -> Result<()> {
     if limits.gc_after_execution {
         lua.gc_collect()?;
@@ -1,2 +1,5 @@
 pub mod bounded_cache;
+pub mod compression;
 pub mod limits;
+pub mod redact;
+pub mod text;
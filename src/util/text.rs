@@ -0,0 +1,45 @@
+//! Text normalization shared by nodes that read user-supplied text.
+//!
+//! Files saved by spreadsheet tools and Windows editors often start with a
+//! UTF-8 byte-order mark and use CRLF line endings. `csv_parse`, `read_file`
+//! and the `extract_html`, `extract_vtt` and `extract_srt` nodes strip the
+//! BOM. Line endings are normalized to `\n` by the extract nodes, and by
+//! `read_file` only when `normalize_newlines` is set; `csv_parse` leaves them
+//! to the CSV reader, which accepts CRLF as is.
+
+use std::borrow::Cow;
+
+/// Remove a leading UTF-8 byte-order mark, if present.
+pub fn strip_bom(text: &str) -> &str {
+    text.strip_prefix('\u{feff}').unwrap_or(text)
+}
+
+/// Convert CRLF and lone CR line endings to LF.
+pub fn normalize_newlines(text: &str) -> Cow<'_, str> {
+    if !text.contains('\r') {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(text.replace("\r\n", "\n").replace('\r', "\n"))
+}
+
+/// [`strip_bom`] followed by [`normalize_newlines`].
+pub fn clean_text(text: &str) -> String {
+    normalize_newlines(strip_bom(text)).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_only_leading_bom() {
+        assert_eq!(strip_bom("\u{feff}a,b"), "a,b");
+        assert_eq!(strip_bom("a\u{feff}b"), "a\u{feff}b");
+    }
+
+    #[test]
+    fn normalizes_crlf_and_lone_cr() {
+        assert_eq!(normalize_newlines("a\r\nb\rc\n"), "a\nb\nc\n");
+        assert!(matches!(normalize_newlines("a\nb"), Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,50 @@
+//! One-node plugin used by the plugin loader tests.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use ironflow::engine::types::{Context, NodeOutput};
+use ironflow::nodes::{Node, NodeRegistry};
+
+/// Reverses `ctx[input_key]` into `reversed`.
+struct ReverseNode;
+
+#[async_trait]
+impl Node for ReverseNode {
+    fn node_type(&self) -> &str {
+        "fixture_reverse"
+    }
+
+    fn description(&self) -> &str {
+        "Reverse a context string (plugin fixture)"
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let key = config
+            .get("input_key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("fixture_reverse requires 'input_key'"))?;
+        let value = ctx
+            .get(key)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Key '{}' not found in context", key))?;
+
+        let mut output = NodeOutput::new();
+        output.insert(
+            "reversed".to_string(),
+            serde_json::Value::String(value.chars().rev().collect()),
+        );
+        Ok(output)
+    }
+}
+
+fn register(registry: &mut NodeRegistry) {
+    registry.register(Arc::new(ReverseNode));
+}
+
+ironflow::declare_plugin!(register);
@@ -83,13 +83,74 @@ async fn api_flow_validate_unknown_node() {
             retry: RetryConfig::default(),
             timeout_s: None,
             route: None,
+            route_from: None,
             on_error: None,
         }],
+        vars: Default::default(),
+        environments: Default::default(),
+        dedupe: None,
+        timeout_s: None,
+        hooks: Default::default(),
     };
 
     assert!(registry.get("nonexistent_node").is_none());
 }
 
+#[tokio::test]
+async fn api_flow_validate_warns_on_dangling_source_key() {
+    let registry = Arc::new(NodeRegistry::with_builtins());
+
+    let source = r#"
+        local flow = Flow.new("dangling")
+        flow:step("parse", nodes.json_parse({ source_key = "raw", output_key = "parsed" }))
+        flow:step("pick", nodes.select_fields({
+            source_key = "parsd", output_key = "picked", fields = { "id" }
+        })):depends_on("parse")
+        flow:step("late", nodes.json_stringify({ source_key = "summary", output_key = "out" }))
+            :depends_on("pick")
+        flow:step("summarize", nodes.select_fields({
+            source_key = "picked", output_key = "summary", fields = { "id" }
+        })):depends_on("pick")
+        return flow
+    "#;
+
+    let flow = LuaRuntime::load_flow_from_string(source, &registry).unwrap();
+    assert!(flow.validate_dag().is_empty());
+    let warnings = flow.context_key_warnings(&registry);
+    assert_eq!(
+        warnings,
+        vec![
+            "Step 'parse' reads 'raw', which is not produced by any dependency".to_string(),
+            "Step 'pick' reads 'parsd', which is not produced by any dependency".to_string(),
+            "Step 'late' reads 'summary', which is produced by 'summarize' but that step is not one of its dependencies".to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn api_flow_validate_context_keys_skip_unknown_outputs() {
+    let registry = Arc::new(NodeRegistry::with_builtins());
+
+    // `code` steps can write any key, `_`-prefixed keys are injected by the
+    // runtime, env keys come from flow:env, and `<key>_count` companions are
+    // covered by `<key>`.
+    let source = r#"
+        local flow = Flow.new("clean")
+        flow:env("default", { seed_text = "[1,2]" })
+        flow:step("load", nodes.code({ source = function() return { body = "{}" } end }))
+        flow:step("parse", nodes.json_parse({ source_key = "body", output_key = "doc" }))
+            :depends_on("load")
+        flow:step("seed", nodes.json_parse({ source_key = "seed_text", output_key = "nums" }))
+        flow:step("count", nodes.json_stringify({ source_key = "nums_count", output_key = "n" }))
+            :depends_on("seed")
+        flow:step("headers", nodes.json_stringify({ source_key = "_headers", output_key = "h" }))
+        return flow
+    "#;
+
+    let flow = LuaRuntime::load_flow_from_string(source, &registry).unwrap();
+    assert!(flow.context_key_warnings(&registry).is_empty());
+}
+
 #[tokio::test]
 async fn api_list_runs_with_filter() {
     let dir = tempfile::tempdir().unwrap();
@@ -202,7 +263,10 @@ fn build_state_with_flows_dir(flows_dir: std::path::PathBuf) -> ironflow::api::A
         event_store: Arc::new(MemoryEventStore::new()),
         flows_dir: Some(flows_dir),
         max_concurrent_tasks: None,
+        concurrency_limits: Default::default(),
         webhooks: std::collections::HashMap::new(),
+        started_at: std::time::Instant::now(),
+        active_runs: ironflow::api::shutdown::ActiveRuns::new(),
     }
 }
 
@@ -282,7 +346,7 @@ async fn api_nodes_list() {
     assert!(nodes.len() >= 44);
 
     // Verify some key nodes exist
-    let names: Vec<&str> = nodes.iter().map(|(n, _)| *n).collect();
+    let names: Vec<&str> = nodes.iter().map(|(n, _, _)| *n).collect();
     assert!(names.contains(&"log"));
     assert!(names.contains(&"http_get"));
     assert!(names.contains(&"code"));
@@ -302,7 +366,10 @@ async fn api_run_events_streams_first_sse_event() {
         event_store: event_store.clone(),
         flows_dir: None,
         max_concurrent_tasks: None,
+        concurrency_limits: Default::default(),
         webhooks: std::collections::HashMap::new(),
+        started_at: std::time::Instant::now(),
+        active_runs: ironflow::api::shutdown::ActiveRuns::new(),
     });
 
     store
@@ -358,6 +425,602 @@ async fn api_run_events_streams_first_sse_event() {
     assert!(!text.contains("\"output\""));
 }
 
+#[tokio::test]
+async fn api_run_events_streams_live_task_updates_and_closes_when_run_finishes() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(JsonStateStore::new(dir.path()));
+    let event_store = Arc::new(MemoryEventStore::new());
+    let registry = Arc::new(NodeRegistry::with_builtins());
+    let state = Arc::new(ironflow::api::AppState {
+        registry: registry.clone(),
+        store: store.clone(),
+        event_store: event_store.clone(),
+        flows_dir: None,
+        max_concurrent_tasks: None,
+        concurrency_limits: Default::default(),
+        webhooks: std::collections::HashMap::new(),
+        started_at: std::time::Instant::now(),
+        active_runs: ironflow::api::shutdown::ActiveRuns::new(),
+    });
+
+    let flow = LuaRuntime::load_flow_from_string(
+        r#"
+        local flow = Flow.new("sse_live")
+        flow:step("wait", nodes.delay({ seconds = 0.3 }))
+        flow:step("done", nodes.log({ message = "done" })):depends_on("wait")
+        return flow
+    "#,
+        &registry,
+    )
+    .unwrap();
+    let engine = WorkflowEngine::new_with_events(registry, store.clone(), event_store, None);
+    let run_id = "run-sse-live".to_string();
+    let run = tokio::spawn({
+        let run_id = run_id.clone();
+        async move {
+            engine
+                .execute_with_id(&flow, Context::new(), run_id)
+                .await
+                .unwrap()
+        }
+    });
+    // The handler 404s until the run exists.
+    for _ in 0..100 {
+        if store.get_run_info(&run_id).await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    }
+
+    let app = Router::new()
+        .route(
+            "/runs/{id}/events",
+            get(ironflow::api::handlers::run_events),
+        )
+        .with_state(state);
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/runs/{}/events", run_id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Collecting the whole body only completes because the stream closes.
+    let body = tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        response.into_body().collect(),
+    )
+    .await
+    .expect("SSE stream should close after the run finishes")
+    .unwrap()
+    .to_bytes();
+    run.await.unwrap();
+    let text = std::str::from_utf8(&body).unwrap();
+
+    let event_names: Vec<&str> = text
+        .lines()
+        .filter_map(|line| line.strip_prefix("event: "))
+        .collect();
+    assert_eq!(event_names.first(), Some(&"run_started"));
+    assert_eq!(event_names.last(), Some(&"run_finished"));
+    assert_eq!(
+        event_names
+            .iter()
+            .filter(|name| **name == "task_succeeded")
+            .count(),
+        2
+    );
+    assert!(text.contains("\"step\":\"wait\",\"node_type\":\"delay\",\"task_status\":\"running\""));
+}
+
+// --- Health / liveness probes ---
+
+fn health_test_app(store: Arc<dyn StateStore>) -> Router {
+    let state = Arc::new(ironflow::api::AppState {
+        registry: Arc::new(NodeRegistry::with_builtins()),
+        store,
+        event_store: Arc::new(MemoryEventStore::new()),
+        flows_dir: None,
+        max_concurrent_tasks: None,
+        concurrency_limits: Default::default(),
+        webhooks: std::collections::HashMap::new(),
+        started_at: std::time::Instant::now(),
+        active_runs: ironflow::api::shutdown::ActiveRuns::new(),
+    });
+
+    Router::new()
+        .route("/health", get(ironflow::api::handlers::health))
+        .route("/livez", get(ironflow::api::handlers::livez))
+        .with_state(state)
+}
+
+async fn get_json(app: Router, uri: &str) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn health_reports_ready_store() {
+    let dir = tempfile::tempdir().unwrap();
+    let app = health_test_app(Arc::new(JsonStateStore::new(dir.path().join("runs"))));
+
+    let (status, body) = get_json(app, "/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+    assert_eq!(body["store"], "ok");
+    assert_eq!(body["active_runs"], 0);
+    assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    assert!(body["uptime_s"].is_u64());
+}
+
+#[tokio::test]
+async fn health_returns_503_for_broken_store_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    // A regular file where the store directory should be cannot be written to.
+    let blocker = dir.path().join("not-a-dir");
+    std::fs::write(&blocker, "").unwrap();
+    let app = health_test_app(Arc::new(JsonStateStore::new(blocker)));
+
+    let (status, body) = get_json(app, "/health").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "unavailable");
+    assert!(body["store_error"].as_str().is_some());
+}
+
+#[tokio::test]
+async fn livez_ignores_store_state() {
+    let dir = tempfile::tempdir().unwrap();
+    let blocker = dir.path().join("not-a-dir");
+    std::fs::write(&blocker, "").unwrap();
+    let app = health_test_app(Arc::new(JsonStateStore::new(blocker)));
+
+    let (status, body) = get_json(app, "/livez").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+}
+
+// --- Graceful shutdown ---
+
+async fn start_shutdown_test_server(
+    shutdown_timeout: std::time::Duration,
+) -> (
+    Arc<ironflow::api::AppState>,
+    std::net::SocketAddr,
+    tokio::sync::oneshot::Sender<()>,
+    tokio::task::JoinHandle<anyhow::Result<()>>,
+    tempfile::TempDir,
+) {
+    let dir = tempfile::tempdir().unwrap();
+    let state = Arc::new(ironflow::api::AppState {
+        registry: Arc::new(NodeRegistry::with_builtins()),
+        store: Arc::new(JsonStateStore::new(dir.path())),
+        event_store: Arc::new(MemoryEventStore::new()),
+        flows_dir: None,
+        max_concurrent_tasks: None,
+        concurrency_limits: Default::default(),
+        webhooks: std::collections::HashMap::new(),
+        started_at: std::time::Instant::now(),
+        active_runs: ironflow::api::shutdown::ActiveRuns::new(),
+    });
+    let app = Router::new()
+        .route(
+            "/flows/run",
+            axum::routing::post(ironflow::api::handlers::run_flow),
+        )
+        .route(
+            "/runs/{id}/cancel",
+            axum::routing::post(ironflow::api::handlers::cancel_run),
+        )
+        .with_state(state.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    let server = tokio::spawn(ironflow::api::shutdown::serve_until(
+        listener,
+        app,
+        state.clone(),
+        shutdown_timeout,
+        async move {
+            let _ = rx.await;
+        },
+    ));
+    (state, addr, tx, server, dir)
+}
+
+fn post_delay_flow(
+    addr: std::net::SocketAddr,
+    seconds: f64,
+) -> tokio::task::JoinHandle<reqwest::Result<serde_json::Value>> {
+    let source = format!(
+        r#"
+        local flow = Flow.new("shutdown_test")
+        flow:step("wait", nodes.delay({{ seconds = {} }}))
+        return flow
+    "#,
+        seconds
+    );
+    tokio::spawn(async move {
+        reqwest::Client::new()
+            .post(format!("http://{}/flows/run", addr))
+            .json(&serde_json::json!({ "source": source }))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await
+    })
+}
+
+async fn wait_for_active_run(state: &ironflow::api::AppState) -> String {
+    for _ in 0..200 {
+        if let Some(id) = state.active_runs.ids().into_iter().next() {
+            return id;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    panic!("run never became active");
+}
+
+#[tokio::test]
+async fn shutdown_waits_for_in_flight_run() {
+    let (state, addr, shutdown, server, _dir) =
+        start_shutdown_test_server(std::time::Duration::from_secs(10)).await;
+
+    let request = post_delay_flow(addr, 0.5);
+    let run_id = wait_for_active_run(&state).await;
+    shutdown.send(()).unwrap();
+
+    server.await.unwrap().unwrap();
+    let body = request.await.unwrap().unwrap();
+    assert_eq!(body["run_id"], run_id.as_str());
+    assert_eq!(body["status"], "success");
+    assert!(state.active_runs.is_empty());
+
+    let info = state.store.get_run_info(&run_id).await.unwrap();
+    assert_eq!(info.status, RunStatus::Success);
+}
+
+#[tokio::test]
+async fn shutdown_timeout_marks_unfinished_runs_stalled() {
+    let (state, addr, shutdown, server, _dir) =
+        start_shutdown_test_server(std::time::Duration::from_millis(100)).await;
+
+    let _request = post_delay_flow(addr, 30.0);
+    let run_id = wait_for_active_run(&state).await;
+    shutdown.send(()).unwrap();
+
+    server.await.unwrap().unwrap();
+    let info = state.store.get_run_info(&run_id).await.unwrap();
+    assert_eq!(info.status, RunStatus::Stalled);
+}
+
+#[tokio::test]
+async fn cancel_endpoint_stops_active_run() {
+    let (state, addr, _shutdown, _server, _dir) =
+        start_shutdown_test_server(std::time::Duration::from_secs(10)).await;
+
+    let request = post_delay_flow(addr, 30.0);
+    let run_id = wait_for_active_run(&state).await;
+    // Cancel once the step is mid-flight; a step that never started is
+    // skipped rather than failed.
+    for _ in 0..200 {
+        let started = match state.store.get_run_info(&run_id).await {
+            Ok(info) => info
+                .tasks
+                .get("wait")
+                .is_some_and(|task| task.status == TaskStatus::Running),
+            Err(_) => false,
+        };
+        if started {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(format!("http://{}/runs/{}/cancel", addr, run_id))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::ACCEPTED);
+    let accepted: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(accepted["run_id"], run_id.as_str());
+    // The run may not have reached the store yet when the cancel lands.
+    assert!(matches!(
+        accepted["status"].as_str(),
+        Some("running" | "pending")
+    ));
+
+    let body = tokio::time::timeout(std::time::Duration::from_secs(5), request)
+        .await
+        .expect("cancelled run should return promptly")
+        .unwrap()
+        .unwrap();
+    assert_eq!(body["status"], "cancelled");
+
+    let info = state.store.get_run_info(&run_id).await.unwrap();
+    assert_eq!(info.status, RunStatus::Cancelled);
+    assert_eq!(
+        info.tasks["wait"].error.as_deref(),
+        Some("cancelled by request")
+    );
+
+    // The run is no longer active, so a second cancel finds nothing.
+    let response = client
+        .post(format!("http://{}/runs/{}/cancel", addr, run_id))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+}
+
+// --- Run callbacks ---
+
+type CallbackRx = tokio::sync::mpsc::UnboundedReceiver<(axum::http::HeaderMap, String)>;
+
+/// A local callback receiver: its `/hook` URL and the channel its
+/// deliveries arrive on.
+async fn callback_receiver() -> (String, CallbackRx) {
+    use axum::http::HeaderMap;
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<(HeaderMap, String)>();
+    let receiver = Router::new().route(
+        "/hook",
+        axum::routing::post(move |headers: HeaderMap, body: String| {
+            let tx = tx.clone();
+            async move {
+                tx.send((headers, body)).unwrap();
+                StatusCode::NO_CONTENT
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let receiver_addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, receiver).await.unwrap();
+    });
+    (format!("http://{}/hook", receiver_addr), rx)
+}
+
+/// A `/flows/run` app plus a local receiver whose deliveries arrive on the
+/// returned channel; the string is the receiver's `/hook` URL.
+async fn callback_test_app() -> (Router, String, CallbackRx, tempfile::TempDir) {
+    let (hook_url, rx) = callback_receiver().await;
+    let dir = tempfile::tempdir().unwrap();
+    let state = Arc::new(ironflow::api::AppState {
+        registry: Arc::new(NodeRegistry::with_builtins()),
+        store: Arc::new(JsonStateStore::new(dir.path())),
+        event_store: Arc::new(MemoryEventStore::new()),
+        flows_dir: None,
+        max_concurrent_tasks: None,
+        concurrency_limits: Default::default(),
+        webhooks: std::collections::HashMap::new(),
+        started_at: std::time::Instant::now(),
+        active_runs: ironflow::api::shutdown::ActiveRuns::new(),
+    });
+    let app = Router::new()
+        .route(
+            "/flows/run",
+            axum::routing::post(ironflow::api::handlers::run_flow),
+        )
+        .with_state(state);
+    (app, hook_url, rx, dir)
+}
+
+async fn post_run(app: Router, request: serde_json::Value) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/flows/run")
+                .header("content-type", "application/json")
+                .body(Body::from(request.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn run_with_callback_url_posts_signed_outputs() {
+    unsafe {
+        std::env::set_var("IRONFLOW_CALLBACK_SECRET", "cb-secret");
+    }
+
+    let (app, hook_url, mut rx, _dir) = callback_test_app().await;
+    let request = serde_json::json!({
+        "source": r#"
+            local flow = Flow.new("callback_test")
+            flow:step("greet", nodes.log({ message = "hi ${ctx.name}" }))
+            return flow
+        "#,
+        "context": { "name": "Ada" },
+        "callback_url": hook_url,
+    });
+    let (status, run) = post_run(app, request).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+
+    let (headers, body) = tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+        .await
+        .expect("callback was not delivered")
+        .unwrap();
+    assert_eq!(
+        headers[ironflow::api::callbacks::SIGNATURE_HEADER],
+        ironflow::api::callbacks::sign("cb-secret", body.as_bytes()).as_str()
+    );
+    let payload: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(payload["run_id"], run["run_id"]);
+    assert_eq!(payload["flow_name"], "callback_test");
+    assert_eq!(payload["status"], "success");
+    assert_eq!(payload["outputs"]["log_message"], "hi Ada");
+    assert!(payload["outputs"].get("_flow_dir").is_none());
+    assert!(payload.get("error").is_none());
+}
+
+#[tokio::test]
+async fn run_with_callback_url_answers_before_the_flow_finishes() {
+    let (app, hook_url, mut rx, _dir) = callback_test_app().await;
+    let request = serde_json::json!({
+        "source": r#"
+            local flow = Flow.new("slow_callback")
+            flow:step("wait", nodes.delay({ seconds = 2 }))
+            return flow
+        "#,
+        "callback_url": hook_url,
+    });
+
+    let started = std::time::Instant::now();
+    let (status, run) = post_run(app, request).await;
+    assert!(
+        started.elapsed() < std::time::Duration::from_secs(1),
+        "the response waited for the run"
+    );
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(run["status"], "pending");
+    assert_eq!(run["flow_name"], "slow_callback");
+    assert!(rx.try_recv().is_err());
+
+    let (_, body) = tokio::time::timeout(std::time::Duration::from_secs(10), rx.recv())
+        .await
+        .expect("callback was not delivered")
+        .unwrap();
+    let payload: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(payload["run_id"], run["run_id"]);
+    assert_eq!(payload["status"], "success");
+}
+
+#[tokio::test]
+async fn shutdown_waits_for_callback_runs_and_their_delivery() {
+    let (state, addr, shutdown, server, _dir) =
+        start_shutdown_test_server(std::time::Duration::from_secs(10)).await;
+    let (hook_url, mut rx) = callback_receiver().await;
+
+    let response = reqwest::Client::new()
+        .post(format!("http://{}/flows/run", addr))
+        .json(&serde_json::json!({
+            "source": r#"
+                local flow = Flow.new("callback_shutdown")
+                flow:step("wait", nodes.delay({ seconds = 0.5 }))
+                return flow
+            "#,
+            "callback_url": hook_url,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::ACCEPTED);
+    let run: serde_json::Value = response.json().await.unwrap();
+    shutdown.send(()).unwrap();
+
+    server.await.unwrap().unwrap();
+    let (_, body) = rx
+        .try_recv()
+        .expect("shutdown did not wait for the callback");
+    let payload: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(payload["run_id"], run["run_id"]);
+    assert_eq!(payload["status"], "success");
+    let run_id = run["run_id"].as_str().unwrap();
+    let info = state.store.get_run_info(run_id).await.unwrap();
+    assert_eq!(info.status, RunStatus::Success);
+    assert!(state.active_runs.is_empty());
+}
+
+#[tokio::test]
+async fn deduplicated_callback_run_reports_the_earlier_run() {
+    let (app, hook_url, mut rx, _dir) = callback_test_app().await;
+    let request = serde_json::json!({
+        "source": r#"
+            local flow = Flow.new("dedupe_callback")
+            flow:dedupe({ keys = { "order" }, ttl_s = 60 })
+            flow:step("greet", nodes.log({ message = "order ${ctx.order}" }))
+            return flow
+        "#,
+        "context": { "order": 7 },
+        "callback_url": hook_url,
+    });
+
+    let (status, first) = post_run(app.clone(), request.clone()).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let (_, body) = tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+        .await
+        .expect("callback was not delivered")
+        .unwrap();
+    let payload: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(payload["run_id"], first["run_id"]);
+
+    // The duplicate names the run that handled the order and starts nothing.
+    let (status, second) = post_run(app, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(second["run_id"], first["run_id"]);
+    assert_eq!(second["status"], "success");
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    assert!(rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn run_flow_applies_the_default_environment_overlay() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(JsonStateStore::new(dir.path()));
+    let state = Arc::new(ironflow::api::AppState {
+        registry: Arc::new(NodeRegistry::with_builtins()),
+        store: store.clone(),
+        event_store: Arc::new(MemoryEventStore::new()),
+        flows_dir: None,
+        max_concurrent_tasks: None,
+        concurrency_limits: Default::default(),
+        webhooks: std::collections::HashMap::new(),
+        started_at: std::time::Instant::now(),
+        active_runs: ironflow::api::shutdown::ActiveRuns::new(),
+    });
+    let app = Router::new()
+        .route(
+            "/flows/run",
+            axum::routing::post(ironflow::api::handlers::run_flow),
+        )
+        .with_state(state);
+
+    let request = serde_json::json!({
+        "source": r#"
+            local flow = Flow.new("env_overlay")
+            flow:env("default", { greeting = "hello", name = "world" })
+            flow:step("greet", nodes.log({ message = "${ctx.greeting} ${ctx.name}" }))
+            return flow
+        "#,
+        "context": { "name": "Ada" },
+    });
+    let (status, run) = post_run(app, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(run["status"], "success");
+
+    let info = store
+        .get_run_info(run["run_id"].as_str().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(info.ctx["log_message"], "hello Ada");
+}
+
+#[test]
+fn callback_signature_is_hmac_sha256_hex() {
+    // RFC 4231 test case 2.
+    assert_eq!(
+        ironflow::api::callbacks::sign("Jefe", b"what do ya want for nothing?"),
+        "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+}
+
 // --- CORS policy ---
 
 async fn cors_response(origin: &str, origins: Option<Vec<String>>) -> axum::response::Response {
@@ -495,8 +1158,149 @@ async fn api_auth_rejects_missing_token() {
     assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
 }
 
+#[tokio::test]
+async fn api_auth_rejects_wrong_token() {
+    let response = auth_test_app()
+        .oneshot(
+            Request::builder()
+                .uri("/")
+                .header(AUTHORIZATION, "Bearer secret-tokem")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+fn app_state(store: Arc<dyn StateStore>) -> Arc<ironflow::api::AppState> {
+    Arc::new(ironflow::api::AppState {
+        registry: Arc::new(NodeRegistry::with_builtins()),
+        store,
+        event_store: Arc::new(MemoryEventStore::new()),
+        flows_dir: None,
+        max_concurrent_tasks: None,
+        concurrency_limits: Default::default(),
+        webhooks: std::collections::HashMap::new(),
+        started_at: std::time::Instant::now(),
+        active_runs: ironflow::api::shutdown::ActiveRuns::new(),
+    })
+}
+
+fn authed_router() -> Router {
+    let store = Arc::new(JsonStateStore::new(tempfile::tempdir().unwrap().keep()));
+    ironflow::api::router(
+        app_state(store),
+        Some(ironflow::api::ApiAuth::new("secret-token")),
+    )
+}
+
+#[tokio::test]
+async fn api_router_leaves_health_open() {
+    let response = authed_router()
+        .oneshot(
+            Request::builder()
+                .uri("/health")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn api_router_requires_key_on_other_routes() {
+    let response = authed_router()
+        .oneshot(Request::builder().uri("/runs").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = authed_router()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/flows/run")
+                .header("content-type", "application/json")
+                .body(Body::from("{}"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = authed_router()
+        .oneshot(
+            Request::builder()
+                .uri("/runs")
+                .header(AUTHORIZATION, "Bearer secret-token")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
 // --- Pagination edge cases ---
 
+async fn get_runs_page(app: Router, query: &str) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/runs?{query}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or_default())
+}
+
+fn page_ids(json: &serde_json::Value) -> Vec<&str> {
+    json["runs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|run| run["id"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn list_runs_endpoint_sorts_and_pages() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(JsonStateStore::new(dir.path()));
+    for i in 0..5 {
+        store
+            .init_run(&format!("r{i}"), "flow", &std::collections::HashMap::new())
+            .await
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    }
+    let app = ironflow::api::router(app_state(store), None);
+
+    let (status, json) = get_runs_page(app.clone(), "").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page_ids(&json), ["r4", "r3", "r2", "r1", "r0"]);
+    assert_eq!(json["sort"], "started_desc");
+
+    let (_, json) = get_runs_page(app.clone(), "sort=started_asc&limit=2&offset=1").await;
+    assert_eq!(page_ids(&json), ["r1", "r2"]);
+    assert_eq!(json["total"], 5);
+    assert_eq!(json["returned"], 2);
+
+    let (_, json) = get_runs_page(app.clone(), "limit=2&offset=3").await;
+    assert_eq!(page_ids(&json), ["r1", "r0"]);
+
+    let (status, _) = get_runs_page(app, "sort=name").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
 #[tokio::test]
 async fn list_run_summaries_offset_beyond_returns_empty() {
     use ironflow::storage::json_store::JsonStateStore;
@@ -521,3 +1325,70 @@ async fn list_run_summaries_offset_beyond_returns_empty() {
         "offset beyond result set must yield an empty page, not an error"
     );
 }
+
+fn stats_test_app(store: Arc<dyn StateStore>) -> Router {
+    let state = Arc::new(ironflow::api::AppState {
+        registry: Arc::new(NodeRegistry::with_builtins()),
+        store,
+        event_store: Arc::new(MemoryEventStore::new()),
+        flows_dir: None,
+        max_concurrent_tasks: None,
+        concurrency_limits: Default::default(),
+        webhooks: std::collections::HashMap::new(),
+        started_at: std::time::Instant::now(),
+        active_runs: ironflow::api::shutdown::ActiveRuns::new(),
+    });
+
+    Router::new()
+        .route(
+            "/flows/{name}/stats",
+            get(ironflow::api::handlers::flow_stats),
+        )
+        .with_state(state)
+}
+
+#[tokio::test]
+async fn flow_stats_aggregates_runs_by_day() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(JsonStateStore::new(dir.path().join("runs")));
+    let ctx = Context::new();
+    for (id, status) in [
+        ("s1", RunStatus::Success),
+        ("s2", RunStatus::Success),
+        ("s3", RunStatus::Success),
+        ("f1", RunStatus::Failed),
+    ] {
+        store.init_run(id, "nightly", &ctx).await.unwrap();
+        store.set_run_status(id, status).await.unwrap();
+    }
+
+    let (status, body) = get_json(stats_test_app(store.clone()), "/flows/nightly/stats").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["flow_name"], "nightly");
+    assert_eq!(body["window"], "7d");
+    assert_eq!(body["days"].as_array().unwrap().len(), 8);
+    assert_eq!(body["totals"]["runs"], 4);
+    assert_eq!(body["totals"]["success"], 3);
+    assert_eq!(body["totals"]["failed"], 1);
+    assert_eq!(body["totals"]["success_rate"], 0.75);
+    let today = body["days"].as_array().unwrap().last().unwrap();
+    assert_eq!(today["success_rate"], 0.75);
+    assert!(today["p95_duration_ms"].is_u64());
+
+    let (status, body) = get_json(
+        stats_test_app(store.clone()),
+        "/flows/nightly/stats?window=12h",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["totals"]["runs"], 4);
+
+    let (status, _) = get_json(
+        stats_test_app(store.clone()),
+        "/flows/nightly/stats?window=1y",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get_json(stats_test_app(store), "/flows/nightly/stats?window=9999d").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
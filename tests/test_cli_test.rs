@@ -0,0 +1,63 @@
+use std::path::Path;
+use std::process::Command;
+
+fn ironflow_test(path: &Path) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_ironflow"))
+        .arg("test")
+        .arg(path)
+        .env("RUST_LOG", "error")
+        .output()
+        .expect("failed to run ironflow")
+}
+
+#[test]
+fn example_fixtures_pass() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/21-testing");
+    let output = ironflow_test(&dir);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stdout: {}", stdout);
+    assert!(stdout.contains("2 passed, 0 failed"), "stdout: {}", stdout);
+}
+
+#[test]
+fn failing_fixture_sets_nonzero_exit_code() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("greet.lua"),
+        r#"
+local flow = Flow.new("greet")
+flow:step("greet", function(ctx)
+    return { greeting = "Hello, " .. ctx.name }
+end)
+return flow
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("ok.test.lua"),
+        r#"return { flow = "greet.lua", context = { name = "Ada" }, expect = { ctx = { greeting = "Hello, Ada" } } }"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("wrong.test.lua"),
+        r#"return { name = "wrong greeting", flow = "greet.lua", context = { name = "Ada" }, expect = { ctx = { greeting = "Hi, Ada" } } }"#,
+    )
+    .unwrap();
+
+    let output = ironflow_test(dir.path());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "stdout: {}", stdout);
+    assert!(stdout.contains("✗ wrong greeting"), "stdout: {}", stdout);
+    assert!(
+        stdout.contains(r#"ctx.greeting: expected "Hi, Ada", got "Hello, Ada""#),
+        "stdout: {}",
+        stdout
+    );
+    assert!(stdout.contains("1 passed, 1 failed"), "stdout: {}", stdout);
+    assert!(
+        stderr.contains("1 of 2 flow tests failed"),
+        "stderr: {}",
+        stderr
+    );
+}
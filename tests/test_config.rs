@@ -1,5 +1,6 @@
 use std::io::Write;
 
+use ironflow::api::webhooks::{RouteSelector, WebhookTarget};
 use ironflow::cli::IronFlowConfig;
 use tempfile::NamedTempFile;
 
@@ -17,6 +18,8 @@ sql_table_prefix: "tenant_a_"
 flows_dir: "my_flows"
 max_body: 2097152
 max_concurrent_tasks: 8
+concurrency_limits:
+  llm: 2
 api_key: "from-config"
 allow_unauthenticated_api: true
 cors_origins:
@@ -43,6 +46,10 @@ cors_origins:
     assert_eq!(cfg.flows_dir.as_deref(), Some("my_flows"));
     assert_eq!(cfg.max_body, Some(2097152));
     assert_eq!(cfg.max_concurrent_tasks, Some(8));
+    assert_eq!(
+        cfg.concurrency_limits,
+        Some(std::collections::HashMap::from([("llm".to_string(), 2)]))
+    );
     assert_eq!(cfg.api_key.as_deref(), Some("from-config"));
     assert_eq!(cfg.allow_unauthenticated_api, Some(true));
     assert_eq!(
@@ -153,8 +160,55 @@ webhooks:
 
     let webhooks = cfg.webhooks.unwrap();
     assert_eq!(webhooks.len(), 2);
-    assert_eq!(webhooks.get("hello").unwrap(), "hello_world.lua");
-    assert_eq!(webhooks.get("process-order").unwrap(), "orders/process.lua");
+    assert_eq!(webhooks["hello"], WebhookTarget::from("hello_world.lua"));
+    assert_eq!(
+        webhooks["process-order"],
+        WebhookTarget::from("orders/process.lua")
+    );
+}
+
+#[test]
+fn routed_webhooks_parsed_from_yaml() {
+    let yaml = r#"
+webhooks:
+  github:
+    route_by:
+      header: X-GitHub-Event
+    routes:
+      push: github/push.lua
+      pull_request: github/pr.lua
+    default: github/other.lua
+  orders:
+    route_by:
+      json_path: $.order.type
+    routes:
+      refund: refunds.lua
+"#;
+
+    let mut f = NamedTempFile::new().unwrap();
+    f.write_all(yaml.as_bytes()).unwrap();
+
+    let cfg = IronFlowConfig::load(Some(f.path())).unwrap();
+    let webhooks = cfg.webhooks.unwrap();
+
+    let WebhookTarget::Routed(github) = &webhooks["github"] else {
+        panic!("expected routed webhook");
+    };
+    assert_eq!(
+        github.route_by,
+        RouteSelector::Header("X-GitHub-Event".to_string())
+    );
+    assert_eq!(github.routes["pull_request"], "github/pr.lua");
+    assert_eq!(github.default.as_deref(), Some("github/other.lua"));
+
+    let WebhookTarget::Routed(orders) = &webhooks["orders"] else {
+        panic!("expected routed webhook");
+    };
+    assert_eq!(
+        orders.route_by,
+        RouteSelector::JsonPath("$.order.type".to_string())
+    );
+    assert_eq!(orders.default, None);
 }
 
 #[test]
@@ -184,3 +238,40 @@ another_random_key: "hello"
     let cfg = IronFlowConfig::load(Some(f.path())).unwrap();
     assert_eq!(cfg.port, Some(4000));
 }
+
+#[test]
+fn signed_webhooks_parsed_from_yaml() {
+    let yaml = r#"
+webhooks:
+  ci:
+    flow: ci.lua
+    signature:
+      secret_env: CI_SECRET
+  github:
+    route_by:
+      header: X-GitHub-Event
+    routes:
+      push: push.lua
+    signature:
+      secret: abc
+      encoding: base64
+"#;
+
+    let mut f = NamedTempFile::new().unwrap();
+    f.write_all(yaml.as_bytes()).unwrap();
+
+    let cfg = IronFlowConfig::load(Some(f.path())).unwrap();
+    let webhooks = cfg.webhooks.unwrap();
+
+    let WebhookTarget::Signed(ci) = &webhooks["ci"] else {
+        panic!("expected signed webhook");
+    };
+    assert_eq!(ci.flow, "ci.lua");
+    assert_eq!(ci.signature.secret_env.as_deref(), Some("CI_SECRET"));
+    assert_eq!(ci.signature.header, "X-Hub-Signature-256");
+    assert_eq!(ci.signature.algorithm, "sha256");
+
+    let signature = webhooks["github"].signature().unwrap();
+    assert_eq!(signature.secret.as_deref(), Some("abc"));
+    assert_eq!(signature.encoding, "base64");
+}
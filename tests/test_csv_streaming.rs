@@ -0,0 +1,100 @@
+//! Streaming `csv_parse` from a file must keep memory bounded by one chunk.
+//!
+//! This file holds a single test so the counting allocator below only sees
+//! its allocations.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::{BufWriter, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ironflow::engine::types::Context;
+use ironflow::nodes::NodeRegistry;
+
+struct PeakAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let now = CURRENT.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(now, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOC: PeakAlloc = PeakAlloc;
+
+const ROWS: usize = 300_000;
+const BATCH_SIZE: usize = 5_000;
+const MEMORY_BUDGET: usize = 8 * 1024 * 1024;
+
+#[tokio::test]
+async fn csv_parse_streams_large_file_into_chunks_within_memory_budget() {
+    let dir = tempfile::tempdir().unwrap();
+    let csv_path = dir.path().join("large.csv");
+    {
+        let mut out = BufWriter::new(std::fs::File::create(&csv_path).unwrap());
+        writeln!(out, "id,name,amount,active").unwrap();
+        for i in 0..ROWS {
+            writeln!(out, "{},customer-{},{}.25,{}", i, i, i % 1000, i % 2 == 0).unwrap();
+        }
+    }
+    let file_size = std::fs::metadata(&csv_path).unwrap().len() as usize;
+    assert!(
+        file_size > MEMORY_BUDGET,
+        "test file ({} bytes) should exceed the budget",
+        file_size
+    );
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("csv_parse").unwrap();
+    let config = serde_json::json!({
+        "path": csv_path.to_str().unwrap(),
+        "output_key": "rows",
+        "infer_types": true,
+        "batch_size": BATCH_SIZE,
+        "output_dir": dir.path().join("chunks").to_str().unwrap()
+    });
+    let ctx = Context::new();
+
+    let baseline = CURRENT.load(Ordering::SeqCst);
+    PEAK.store(baseline, Ordering::SeqCst);
+    let result = node.execute(&config, &ctx).await.unwrap();
+    let peak = PEAK.load(Ordering::SeqCst) - baseline;
+
+    assert!(
+        peak < MEMORY_BUDGET,
+        "parsing used {} bytes at peak, budget is {}",
+        peak,
+        MEMORY_BUDGET
+    );
+    assert_eq!(result["rows_count"], ROWS);
+    assert!(!result.contains_key("rows"));
+
+    let chunks = result["rows_chunks"].as_array().unwrap();
+    assert_eq!(chunks.len(), ROWS / BATCH_SIZE);
+    let last: Vec<serde_json::Value> = serde_json::from_str(
+        &std::fs::read_to_string(chunks.last().unwrap().as_str().unwrap()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(last.len(), BATCH_SIZE);
+    assert_eq!(
+        last[BATCH_SIZE - 1],
+        serde_json::json!({
+            "id": ROWS - 1,
+            "name": format!("customer-{}", ROWS - 1),
+            "amount": 999.25,
+            "active": false
+        })
+    );
+}
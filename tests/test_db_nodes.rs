@@ -177,3 +177,336 @@ async fn db_query_respects_max_result_bytes() {
         "expected max_result_bytes error, got: {err}"
     );
 }
+
+#[tokio::test]
+async fn db_query_reports_profile_timings() {
+    let reg = NodeRegistry::with_builtins();
+    let db_query = reg.get("db_query").unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let config = serde_json::json!({
+        "connection": sqlite_url(&dir.path().join("profile.db")),
+        "query": "SELECT 1 AS one"
+    });
+
+    let ctx = empty_ctx();
+    let (result, timings) =
+        ironflow::engine::profile::collect(true, db_query.execute(&config, &ctx)).await;
+    result.unwrap();
+    let timings = timings.unwrap();
+    assert!(timings.contains_key("db.connect_ms"));
+    assert!(timings.contains_key("db.query_ms"));
+}
+
+#[tokio::test]
+async fn db_nodes_reuse_pooled_connections() {
+    let reg = NodeRegistry::with_builtins();
+    let db_exec = reg.get("db_exec").unwrap();
+    let db_query = reg.get("db_query").unwrap();
+    // Every SQLite in-memory connection is its own database, so the table
+    // is only visible to later steps if they get the same connection back.
+    let connection = "sqlite::memory:";
+
+    db_exec
+        .execute(
+            &serde_json::json!({
+                "connection": connection,
+                "pool_max": 1,
+                "query": "CREATE TABLE events (n INTEGER)"
+            }),
+            &empty_ctx(),
+        )
+        .await
+        .unwrap();
+    for n in 0..50 {
+        db_exec
+            .execute(
+                &serde_json::json!({
+                    "connection": connection,
+                    "query": "INSERT INTO events(n) VALUES(?)",
+                    "params": [n]
+                }),
+                &empty_ctx(),
+            )
+            .await
+            .unwrap();
+    }
+    let output = db_query
+        .execute(
+            &serde_json::json!({
+                "connection": connection,
+                "query": "SELECT n FROM events",
+                "max_rows": 100
+            }),
+            &empty_ctx(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(output["rows_count"], 50);
+    assert_eq!(ironflow::nodes::database::pool_size(connection), Some(1));
+}
+
+async fn create_accounts_table(connection: &str) {
+    let reg = NodeRegistry::with_builtins();
+    reg.get("db_exec")
+        .unwrap()
+        .execute(
+            &serde_json::json!({
+                "connection": connection,
+                "query": "CREATE TABLE accounts (name TEXT PRIMARY KEY, balance INTEGER)"
+            }),
+            &empty_ctx(),
+        )
+        .await
+        .unwrap();
+}
+
+async fn account_names(connection: &str) -> serde_json::Value {
+    let reg = NodeRegistry::with_builtins();
+    let output = reg
+        .get("db_query")
+        .unwrap()
+        .execute(
+            &serde_json::json!({
+                "connection": connection,
+                "query": "SELECT name FROM accounts ORDER BY name"
+            }),
+            &empty_ctx(),
+        )
+        .await
+        .unwrap();
+    output["rows"].clone()
+}
+
+/// Open a transaction, insert two accounts inside it, then finish it with
+/// `finish_node` (`db_commit` or `db_rollback`).
+async fn run_transaction(connection: &str, finish_node: &str) {
+    let reg = NodeRegistry::with_builtins();
+    let mut ctx = empty_ctx();
+    let opened = reg
+        .get("db_transaction")
+        .unwrap()
+        .execute(
+            &serde_json::json!({ "connection": connection, "output_key": "tx" }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    ctx.extend(opened);
+
+    for name in ["alice", "bob"] {
+        let inserted = reg
+            .get("db_exec")
+            .unwrap()
+            .execute(
+                &serde_json::json!({
+                    "transaction_key": "tx",
+                    "query": "INSERT INTO accounts(name, balance) VALUES(?, ?)",
+                    "params": [name, 100]
+                }),
+                &ctx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(inserted["rows_affected"], 1);
+    }
+
+    // Reads inside the transaction see its uncommitted writes.
+    let inside = reg
+        .get("db_query")
+        .unwrap()
+        .execute(
+            &serde_json::json!({
+                "transaction_key": "tx",
+                "query": "SELECT name FROM accounts"
+            }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(inside["rows_count"], 2);
+
+    reg.get(finish_node)
+        .unwrap()
+        .execute(&serde_json::json!({ "transaction_key": "tx" }), &ctx)
+        .await
+        .unwrap();
+
+    // The handle is gone once the transaction has ended.
+    let err = reg
+        .get("db_exec")
+        .unwrap()
+        .execute(
+            &serde_json::json!({ "transaction_key": "tx", "query": "SELECT 1" }),
+            &ctx,
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("is not open"), "{err}");
+}
+
+#[tokio::test]
+async fn db_transaction_commit_persists_all_statements() {
+    let dir = tempfile::tempdir().unwrap();
+    let connection = sqlite_url(&dir.path().join("commit.db"));
+    create_accounts_table(&connection).await;
+
+    run_transaction(&connection, "db_commit").await;
+
+    assert_eq!(
+        account_names(&connection).await,
+        serde_json::json!([{ "name": "alice" }, { "name": "bob" }])
+    );
+}
+
+#[tokio::test]
+async fn db_transaction_rollback_discards_all_statements() {
+    let dir = tempfile::tempdir().unwrap();
+    let connection = sqlite_url(&dir.path().join("rollback.db"));
+    create_accounts_table(&connection).await;
+
+    run_transaction(&connection, "db_rollback").await;
+
+    assert_eq!(account_names(&connection).await, serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn db_transaction_is_rolled_back_when_the_run_fails() {
+    use ironflow::engine::WorkflowEngine;
+    use ironflow::engine::types::RunStatus;
+    use ironflow::lua::LuaRuntime;
+    use ironflow::storage::StateStore;
+    use ironflow::storage::null_store::NullStateStore;
+    use std::sync::Arc;
+
+    let dir = tempfile::tempdir().unwrap();
+    let connection = sqlite_url(&dir.path().join("failed_run.db"));
+    create_accounts_table(&connection).await;
+
+    let reg = Arc::new(NodeRegistry::with_builtins());
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(reg.clone(), store.clone(), None);
+    let flow = LuaRuntime::load_flow_from_string(
+        &format!(
+            r#"
+            local flow = Flow.new("tx_failure")
+            flow:step("begin", nodes.db_transaction({{ connection = "{connection}", output_key = "tx" }}))
+            flow:step("insert", nodes.db_exec({{
+                transaction_key = "tx",
+                query = "INSERT INTO accounts(name, balance) VALUES('carol', 5)"
+            }})):depends_on("begin")
+            flow:step("boom", nodes.db_exec({{
+                transaction_key = "tx",
+                query = "INSERT INTO missing_table VALUES(1)"
+            }})):depends_on("insert")
+            flow:step("commit", nodes.db_commit({{ transaction_key = "tx" }})):depends_on("boom")
+            return flow
+        "#
+        ),
+        &reg,
+    )
+    .unwrap();
+
+    let run_id = engine.execute(&flow, empty_ctx()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+    assert_eq!(info.status, RunStatus::Failed);
+
+    // The open transaction was dropped with the run, so its insert is gone
+    // and the database is not left locked for the next writer.
+    assert_eq!(account_names(&connection).await, serde_json::json!([]));
+    let inserted = reg
+        .get("db_exec")
+        .unwrap()
+        .execute(
+            &serde_json::json!({
+                "connection": connection,
+                "query": "INSERT INTO accounts(name, balance) VALUES('dave', 1)"
+            }),
+            &empty_ctx(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(inserted["rows_affected"], 1);
+}
+
+#[cfg(feature = "postgres")]
+fn postgres_database_url() -> Option<String> {
+    dotenvy::dotenv().ok();
+    std::env::var("DATABASE_URL")
+        .ok()
+        .filter(|url| url.starts_with("postgres://") || url.starts_with("postgresql://"))
+}
+
+#[cfg(feature = "postgres")]
+#[tokio::test]
+async fn pg_query_insert_returning_and_parameterized_select() {
+    let Some(url) = postgres_database_url() else {
+        eprintln!("Skipping test: DATABASE_URL is not configured for Postgres");
+        return;
+    };
+    let reg = NodeRegistry::with_builtins();
+    let pg_query = reg.get("pg_query").unwrap();
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let table = format!("pg_query_test_{}", &id[..8]);
+
+    pg_query
+        .execute(
+            &serde_json::json!({
+                "connection": url,
+                "query": format!(
+                    "CREATE TABLE {table} (id SERIAL PRIMARY KEY, name TEXT NOT NULL, score INT8, meta JSONB)"
+                )
+            }),
+            &empty_ctx(),
+        )
+        .await
+        .unwrap();
+
+    let mut ctx = empty_ctx();
+    ctx.insert("name".to_string(), serde_json::json!("Ada"));
+    let inserted = pg_query
+        .execute(
+            &serde_json::json!({
+                "connection": url,
+                "query": format!(
+                    "INSERT INTO {table} (name, score, meta) VALUES ($1, $2, $3) RETURNING id, name, meta"
+                ),
+                "params": ["${ctx.name}", 97, { "team": "engines" }],
+                "output_key": "created"
+            }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(inserted["created_count"], 1);
+    assert_eq!(inserted["created"][0]["name"], "Ada");
+    assert_eq!(
+        inserted["created"][0]["meta"],
+        serde_json::json!({ "team": "engines" })
+    );
+    assert!(inserted["created"][0]["id"].is_i64());
+
+    let selected = pg_query
+        .execute(
+            &serde_json::json!({
+                "connection": url,
+                "query": format!("SELECT name, score FROM {table} WHERE score > $1 AND name = $2"),
+                "params": [90, "Ada"]
+            }),
+            &empty_ctx(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        selected["rows"],
+        serde_json::json!([{ "name": "Ada", "score": 97 }])
+    );
+
+    pg_query
+        .execute(
+            &serde_json::json!({ "connection": url, "query": format!("DROP TABLE {table}") }),
+            &empty_ctx(),
+        )
+        .await
+        .unwrap();
+}
@@ -191,6 +191,43 @@ async fn base64_decode_url_safe() {
     );
 }
 
+#[tokio::test]
+async fn base64_no_pad_round_trip_and_normalized_decode() {
+    let reg = NodeRegistry::with_builtins();
+    let encode_node = reg.get("base64_encode").unwrap();
+    let decode_node = reg.get("base64_decode").unwrap();
+
+    let encoded = encode_node
+        .execute(
+            &serde_json::json!({ "input": "hi?>", "url_safe": true, "no_pad": true }),
+            &empty_ctx(),
+        )
+        .await
+        .unwrap();
+    let encoded = encoded["base64_encoded"].as_str().unwrap().to_string();
+    assert_eq!(encoded, "aGk_Pg");
+
+    // Unpadded input decodes without asking for `no_pad`.
+    let decoded = decode_node
+        .execute(
+            &serde_json::json!({ "input": encoded, "url_safe": true }),
+            &empty_ctx(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(decoded["base64_decoded"], "hi?>");
+
+    // Binary data stays base64, re-encoded in canonical padded form.
+    let normalized = decode_node
+        .execute(
+            &serde_json::json!({ "input": "//79\n/A", "as_text": false }),
+            &empty_ctx(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(normalized["base64_decoded"], "//79/A==");
+}
+
 #[tokio::test]
 async fn base64_decode_invalid_error() {
     let reg = NodeRegistry::with_builtins();
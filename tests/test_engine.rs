@@ -4,7 +4,9 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use ironflow::engine::RunEventType;
-use ironflow::engine::executor::WorkflowEngine;
+use ironflow::engine::executor::{
+    ConcurrencyLimits, ContextLimitAction, ContextLimits, WorkflowEngine,
+};
 use ironflow::engine::types::*;
 use ironflow::lua::runtime::LuaRuntime;
 use ironflow::nodes::NodeRegistry;
@@ -138,6 +140,30 @@ async fn initial_context_available() {
     );
 }
 
+#[tokio::test]
+async fn flow_vars_seed_context_unless_overridden() {
+    let (engine, store) = engine();
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("vars_test")
+        flow.vars = { base_url = "https://api.example.com", bucket = "dev-assets" }
+        flow:step("check", nodes.code({ source = "return { url = ctx.base_url .. '/' .. ctx.bucket }" }))
+        return flow
+    "#,
+    );
+
+    let mut ctx = HashMap::new();
+    ctx.insert("bucket".to_string(), serde_json::json!("prod-assets"));
+
+    let run_id = engine.execute(&flow, ctx).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(info.ctx["base_url"], "https://api.example.com");
+    assert_eq!(info.ctx["bucket"], "prod-assets");
+    assert_eq!(info.ctx["url"], "https://api.example.com/prod-assets");
+}
+
 // --- Conditional routing ---
 
 #[tokio::test]
@@ -168,6 +194,94 @@ async fn conditional_routing_true_branch() {
     assert_eq!(info.tasks["low_val"].status, TaskStatus::Skipped);
 }
 
+#[tokio::test]
+async fn on_route_only_follows_named_dependency() {
+    let (engine, store) = engine();
+    // Both guards publish a route; each branch listens to exactly one of them,
+    // so "fast" on the tier guard must not enable the shipping branch.
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("labelled_routes")
+        flow:step("tier", nodes.if_node({
+            condition = "ctx.amount > 100",
+            true_route = "high",
+            false_route = "low"
+        }))
+        flow:step("shipping", nodes.if_node({
+            condition = "ctx.express",
+            true_route = "fast",
+            false_route = "high"
+        }))
+        flow:step("premium", nodes.code({ source = "return { premium = true }" }))
+            :depends_on("shipping"):on_route("tier", "high")
+        flow:step("standard", nodes.code({ source = "return { standard = true }" }))
+            :on_route("tier", "low")
+        flow:step("express", nodes.code({ source = "return { express_shipping = true }" }))
+            :depends_on("tier"):on_route("shipping", "high")
+        return flow
+    "#,
+    );
+
+    let mut ctx = HashMap::new();
+    ctx.insert("amount".to_string(), serde_json::json!(200));
+    ctx.insert("express".to_string(), serde_json::json!(true));
+
+    let run_id = engine.execute(&flow, ctx).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(info.tasks["premium"].status, TaskStatus::Success);
+    assert_eq!(info.tasks["standard"].status, TaskStatus::Skipped);
+    // Without on_route, tier's "high" would have enabled this step.
+    assert_eq!(info.tasks["express"].status, TaskStatus::Skipped);
+    assert_eq!(info.ctx["premium"], true);
+    assert!(!info.ctx.contains_key("standard"));
+}
+
+#[tokio::test]
+async fn skipped_branch_cascades_to_its_dependents() {
+    let (engine, store) = engine();
+    // Diamond: check -> {high, low} -> join. The low branch also has a tail
+    // of its own that must not run once the branch is routed off.
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("diamond")
+        flow:step("check", nodes.if_node({
+            condition = "ctx.amount > 100",
+            true_route = "high",
+            false_route = "low"
+        }))
+        flow:step("high", nodes.code({ source = "return { branch = 'high' }" }))
+            :depends_on("check"):route("high")
+        flow:step("low", nodes.code({ source = "return { branch = 'low' }" }))
+            :depends_on("check"):route("low")
+        flow:step("low_notify", nodes.code({ source = "return { notified = true }" }))
+            :depends_on("low")
+        flow:step("low_audit", nodes.code({ source = "return { audited = true }" }))
+            :depends_on("low_notify")
+        flow:step("join", nodes.code({ source = "return { joined = true }" }))
+            :depends_on("high", "low")
+        return flow
+    "#,
+    );
+
+    let mut ctx = HashMap::new();
+    ctx.insert("amount".to_string(), serde_json::json!(200));
+
+    let run_id = engine.execute(&flow, ctx).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(info.tasks["high"].status, TaskStatus::Success);
+    assert_eq!(info.tasks["low"].status, TaskStatus::Skipped);
+    assert_eq!(info.tasks["low_notify"].status, TaskStatus::Skipped);
+    assert_eq!(info.tasks["low_audit"].status, TaskStatus::Skipped);
+    assert_eq!(info.tasks["join"].status, TaskStatus::Success);
+    assert!(!info.ctx.contains_key("notified"));
+    assert!(!info.ctx.contains_key("audited"));
+    assert_eq!(info.ctx["joined"], true);
+}
+
 // --- Error handling ---
 
 #[tokio::test]
@@ -255,15 +369,20 @@ async fn on_error_injects_error_context() {
     );
 }
 
-// --- Timeout ---
+// --- Flow hooks ---
 
 #[tokio::test]
-async fn step_timeout() {
+async fn flow_on_error_hook_runs_after_failure() {
     let (engine, store) = engine();
     let flow = load_flow(
         r#"
-        local flow = Flow.new("timeout")
-        flow:step("slow", nodes.delay({ seconds = 10 })):timeout(0.1)
+        local flow = Flow.new("hooks_fail")
+        flow:step("risky", nodes.read_file({ path = "/nonexistent_abc123" }))
+        flow:step("after", nodes.log({ message = "unreachable" })):depends_on("risky")
+        flow:on_error("alert", function(ctx)
+            return { alerted = ctx._error_step, failed = ctx._failed_steps, kind = ctx._error_kind }
+        end)
+        flow:on_success("celebrate", nodes.code({ source = "return { celebrated = true }" }))
         return flow
     "#,
     );
@@ -272,148 +391,1265 @@ async fn step_timeout() {
     let info = store.get_run_info(&run_id).await.unwrap();
 
     assert_eq!(info.status, RunStatus::Failed);
-    assert_eq!(info.tasks["slow"].status, TaskStatus::Failed);
+    assert_eq!(info.ctx["alerted"], "risky");
+    assert_eq!(info.ctx["failed"], serde_json::json!(["risky"]));
+    assert!(info.ctx["kind"].is_string());
+    assert_eq!(info.tasks["alert"].status, TaskStatus::Success);
+    assert!(!info.ctx.contains_key("celebrated"));
+    assert!(!info.tasks.contains_key("celebrate"));
 }
 
-// --- DAG cycle detection ---
-
 #[tokio::test]
-async fn cycle_detection_prevents_execution() {
-    let flow = FlowDefinition {
-        name: "cycle".to_string(),
-        steps: vec![
-            StepDefinition {
-                name: "a".to_string(),
-                node_type: "log".to_string(),
-                config: serde_json::json!({"message": "a"}),
-                dependencies: vec!["b".to_string()],
-                retry: RetryConfig::default(),
-                timeout_s: None,
-                route: None,
-                on_error: None,
-            },
-            StepDefinition {
-                name: "b".to_string(),
-                node_type: "log".to_string(),
-                config: serde_json::json!({"message": "b"}),
-                dependencies: vec!["a".to_string()],
-                retry: RetryConfig::default(),
-                timeout_s: None,
-                route: None,
-                on_error: None,
-            },
-        ],
-    };
+async fn flow_on_success_hook_runs_only_on_full_success() {
+    let (engine, store) = engine();
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("hooks_ok")
+        flow:step("work", nodes.code({ source = "return { done = true }" }))
+        flow:on_success("celebrate", function(ctx) return { celebrated = ctx.done } end)
+        flow:on_error("alert", nodes.code({ source = "return { alerted = true }" }))
+        return flow
+    "#,
+    );
 
-    let (engine, _store) = engine();
-    let result = engine.execute(&flow, HashMap::new()).await;
-    assert!(result.is_err());
-    assert!(result.unwrap_err().to_string().contains("Cycle"));
-}
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
 
-// --- File I/O round-trip ---
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(info.ctx["celebrated"], true);
+    assert!(!info.ctx.contains_key("alerted"));
+    assert!(!info.ctx.contains_key("_error_step"));
+}
 
 #[tokio::test]
-async fn file_write_and_read() {
+async fn failing_flow_hook_does_not_mask_original_error() {
     let (engine, store) = engine();
-    let dir = tempfile::tempdir().unwrap();
-    let file_path = dir.path().join("test.txt").to_string_lossy().to_string();
-
-    let source = format!(
+    let flow = load_flow(
         r#"
-        local flow = Flow.new("file_io")
-        flow:step("write", nodes.write_file({{
-            path = "{}",
-            content = "hello world"
-        }}))
-        flow:step("read", nodes.read_file({{
-            path = "{}",
-            output_key = "content"
-        }})):depends_on("write")
+        local flow = Flow.new("hooks_mask")
+        flow:step("risky", nodes.read_file({ path = "/nonexistent_abc123" }))
+        flow:on_error("broken_alert", function(ctx) error("alert service down") end)
+        flow:on_error("cleanup", nodes.code({ source = "return { cleaned = true }" }))
         return flow
     "#,
-        file_path, file_path
     );
 
-    let flow = load_flow(&source);
     let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
     let info = store.get_run_info(&run_id).await.unwrap();
 
-    assert_eq!(info.status, RunStatus::Success);
-    assert_eq!(
-        info.ctx.get("content_content").unwrap(),
-        &serde_json::json!("hello world")
+    assert_eq!(info.status, RunStatus::Failed);
+    assert_eq!(info.ctx["_error_step"], "risky");
+    assert!(
+        !info.ctx["_error_message"]
+            .as_str()
+            .unwrap()
+            .contains("alert service down")
     );
+    let hook = &info.tasks["broken_alert"];
+    assert_eq!(hook.status, TaskStatus::Failed);
+    assert!(
+        hook.error
+            .as_deref()
+            .unwrap()
+            .contains("alert service down")
+    );
+    assert_eq!(info.ctx["cleaned"], true);
 }
 
-// --- step_if ---
+#[tokio::test]
+async fn failing_on_success_hook_fails_the_run() {
+    let (engine, store) = engine();
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("hooks_success_fails")
+        flow:step("work", nodes.log({ message = "ok" }))
+        flow:on_success("notify", function(ctx) error("webhook rejected") end)
+        return flow
+    "#,
+    );
+
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Failed);
+    assert_eq!(info.tasks["work"].status, TaskStatus::Success);
+    assert_eq!(info.tasks["notify"].status, TaskStatus::Failed);
+}
 
 #[tokio::test]
-async fn step_if_runs_when_true() {
+async fn finally_runs_after_failure_and_hooks() {
     let (engine, store) = engine();
     let flow = load_flow(
         r#"
-        local flow = Flow.new("step_if_true")
-        flow:step_if("ctx.score > 50", "bonus", nodes.code({ source = "return { got_bonus = true }" }))
+        local flow = Flow.new("finally_fail")
+        flow:step("risky", nodes.read_file({ path = "/nonexistent_abc123" }))
+        flow:finally("cleanup", function(ctx)
+            return { cleaned = true, saw_alert = ctx.alerted == true }
+        end)
+        flow:on_error("alert", nodes.code({ source = "return { alerted = true }" }))
         return flow
     "#,
     );
 
-    let mut ctx = HashMap::new();
-    ctx.insert("score".to_string(), serde_json::json!(80));
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
 
-    let run_id = engine.execute(&flow, ctx).await.unwrap();
+    assert_eq!(info.status, RunStatus::Failed);
+    assert_eq!(info.tasks["cleanup"].status, TaskStatus::Success);
+    assert_eq!(info.ctx["cleaned"], true);
+    // finally runs after the on_error hooks, even when declared first.
+    assert_eq!(info.ctx["saw_alert"], true);
+}
+
+#[tokio::test]
+async fn finally_runs_when_run_succeeds() {
+    let (engine, store) = engine();
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("finally_ok")
+        flow:step("work", nodes.code({ source = "return { done = true }" }))
+        flow:finally("cleanup", nodes.code({ source = "return { cleaned = true }" }))
+        return flow
+    "#,
+    );
+
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
     let info = store.get_run_info(&run_id).await.unwrap();
 
     assert_eq!(info.status, RunStatus::Success);
-    assert_eq!(info.ctx.get("got_bonus").unwrap(), &serde_json::json!(true));
+    assert_eq!(info.ctx["cleaned"], true);
 }
 
 #[tokio::test]
-async fn step_if_skips_when_false() {
+async fn finally_runs_when_run_times_out() {
     let (engine, store) = engine();
     let flow = load_flow(
         r#"
-        local flow = Flow.new("step_if_false")
-        flow:step_if("ctx.score > 50", "bonus", nodes.code({ source = "return { got_bonus = true }" }))
+        local flow = Flow.new("finally_timeout")
+        flow:timeout(0.3)
+        flow:step("slow", nodes.delay({ seconds = 30 }))
+        flow:on_error("alert", nodes.code({ source = "return { alerted = true }" }))
+        flow:finally("cleanup", nodes.code({ source = "return { cleaned = true }" }))
         return flow
     "#,
     );
 
-    let mut ctx = HashMap::new();
-    ctx.insert("score".to_string(), serde_json::json!(30));
-
-    let run_id = engine.execute(&flow, ctx).await.unwrap();
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
     let info = store.get_run_info(&run_id).await.unwrap();
 
-    assert_eq!(info.status, RunStatus::Success);
-    assert!(!info.ctx.contains_key("got_bonus"));
-    assert_eq!(info.tasks["bonus"].status, TaskStatus::Skipped);
+    assert_eq!(info.status, RunStatus::Stalled);
+    assert_eq!(info.tasks["slow"].status, TaskStatus::Failed);
+    assert_eq!(info.tasks["cleanup"].status, TaskStatus::Success);
+    assert_eq!(info.ctx["cleaned"], true);
+    assert!(!info.ctx.contains_key("alerted"));
 }
 
 #[tokio::test]
-async fn step_if_with_function_handler() {
+async fn finally_runs_when_run_is_cancelled() {
     let (engine, store) = engine();
     let flow = load_flow(
         r#"
-        local flow = Flow.new("step_if_fn")
-        flow:step_if("ctx.active", "greet", function(ctx)
-            return { greeting = "Hello " .. ctx.name }
-        end)
+        local flow = Flow.new("finally_cancel")
+        flow:step("slow", nodes.delay({ seconds = 30 }))
+        flow:finally("cleanup", nodes.code({ source = "return { cleaned = true }" }))
         return flow
     "#,
     );
 
-    let mut ctx = HashMap::new();
-    ctx.insert("active".to_string(), serde_json::json!(true));
-    ctx.insert("name".to_string(), serde_json::json!("Alice"));
+    let cancel = tokio_util::sync::CancellationToken::new();
+    let trigger = cancel.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        trigger.cancel();
+    });
+
+    let run_id = engine
+        .execute_with_cancel(&flow, HashMap::new(), cancel)
+        .await
+        .unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
 
-    let run_id = engine.execute(&flow, ctx).await.unwrap();
+    assert_eq!(info.status, RunStatus::Cancelled);
+    assert_eq!(info.tasks["cleanup"].status, TaskStatus::Success);
+    assert_eq!(info.ctx["cleaned"], true);
+}
+
+#[tokio::test(start_paused = true)]
+async fn finally_of_a_stopped_run_is_time_limited() {
+    let (engine, store) = engine();
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("finally_hangs")
+        flow:timeout(1)
+        flow:step("slow", nodes.delay({ seconds = 30 }))
+        flow:finally("hang", nodes.delay({ seconds = 3600 }))
+        return flow
+    "#,
+    );
+
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
     let info = store.get_run_info(&run_id).await.unwrap();
 
-    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(info.status, RunStatus::Stalled);
+    assert_eq!(info.tasks["hang"].status, TaskStatus::Failed);
+}
+
+/// Panics when executed, to stand in for a crashed step task.
+struct PanicNode;
+
+#[async_trait::async_trait]
+impl ironflow::nodes::Node for PanicNode {
+    fn node_type(&self) -> &str {
+        "panic_node"
+    }
+
+    fn description(&self) -> &str {
+        "Panic (test node)"
+    }
+
+    async fn execute(
+        &self,
+        _config: &serde_json::Value,
+        _ctx: &Context,
+    ) -> anyhow::Result<NodeOutput> {
+        panic!("step crashed");
+    }
+}
+
+#[tokio::test]
+async fn finally_runs_when_a_step_task_panics() {
+    let reg = Arc::new(NodeRegistry::with_builtins_and(|registry| {
+        registry.register(Arc::new(PanicNode));
+    }));
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(reg.clone(), store.clone(), None);
+    let flow = LuaRuntime::load_flow_from_string(
+        r#"
+        local flow = Flow.new("finally_panic")
+        flow:step("crash", nodes.panic_node({}))
+        flow:finally("cleanup", nodes.code({ source = "return { cleaned = true }" }))
+        return flow
+    "#,
+        &reg,
+    )
+    .unwrap();
+
+    let run_id = "finally-panic-run".to_string();
+    assert!(
+        engine
+            .execute_with_id(&flow, HashMap::new(), run_id.clone())
+            .await
+            .is_err()
+    );
+    let info = store.get_run_info(&run_id).await.unwrap();
+    assert_eq!(info.status, RunStatus::Failed);
+    assert_eq!(info.tasks["cleanup"].status, TaskStatus::Success);
+    assert_eq!(info.ctx["cleaned"], true);
+}
+
+// --- Timeout ---
+
+#[tokio::test]
+async fn step_timeout() {
+    let (engine, store) = engine();
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("timeout")
+        flow:step("slow", nodes.delay({ seconds = 10 })):timeout(0.1)
+        return flow
+    "#,
+    );
+
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Failed);
+    assert_eq!(info.tasks["slow"].status, TaskStatus::TimedOut);
+    assert!(info.tasks["slow"].status.is_failed());
     assert_eq!(
-        info.ctx.get("greeting").unwrap(),
-        &serde_json::json!("Hello Alice")
+        info.tasks["slow"].error.as_deref(),
+        Some("node timed out after 0.1s")
+    );
+}
+
+#[tokio::test]
+async fn step_timeout_retries_and_releases_its_concurrency_slot() {
+    let reg = Arc::new(NodeRegistry::with_builtins());
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(reg, store.clone(), Some(1));
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("timeout_slot")
+        flow:step("slow", nodes.delay({ seconds = 10 })):timeout(0.05):retries(1, 0.01)
+        flow:step("fast", nodes.log({ message = "ran" }))
+        flow:step("after", nodes.log({ message = "after" })):depends_on("fast")
+        return flow
+    "#,
+    );
+
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.tasks["slow"].status, TaskStatus::TimedOut);
+    assert_eq!(info.tasks["slow"].attempt, 2);
+    assert_eq!(info.tasks["fast"].status, TaskStatus::Success);
+    assert_eq!(info.tasks["after"].status, TaskStatus::Success);
+}
+
+#[tokio::test]
+async fn retry_on_only_retries_matching_errors() {
+    let (engine, store) = engine();
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("retry_on")
+        flow:step("slow", nodes.delay({ seconds = 10 })):timeout(0.05)
+            :retries(2, 0.01, { retry_on = { "timeout", "5xx" } })
+        flow:step("broken", nodes.code({ source = "error('bad input')" }))
+            :retries(3, 0.01, { retry_on = { "timeout", "5xx" } })
+        return flow
+    "#,
+    );
+
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.tasks["slow"].status, TaskStatus::TimedOut);
+    assert_eq!(info.tasks["slow"].attempt, 3);
+    assert_eq!(info.tasks["broken"].status, TaskStatus::Failed);
+    assert_eq!(info.tasks["broken"].attempt, 1);
+}
+
+#[tokio::test]
+async fn step_timeout_is_classified_for_error_handler() {
+    let (engine, store) = engine();
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("timeout_kind")
+        flow:step("slow", nodes.delay({ seconds = 10 })):timeout(0.1):on_error("recover")
+        flow:step("recover", nodes.code({ source = "return { seen_kind = ctx._error_kind }" }))
+        return flow
+    "#,
+    );
+
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    let detail = info.tasks["slow"].error_detail.as_ref().unwrap();
+    assert_eq!(detail.kind, NodeErrorKind::Timeout);
+    assert!(detail.retriable);
+    assert_eq!(info.ctx["seen_kind"], "timeout");
+    assert_eq!(info.ctx["_error_detail"]["kind"], "timeout");
+}
+
+// --- Resolved config ---
+
+#[tokio::test]
+async fn task_records_resolved_config_with_secrets_redacted() {
+    let (engine, store) = engine();
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("resolved")
+        flow:step("call", nodes.log({
+            message = "${ctx.base}/x",
+            api_key = "${ctx.key}"
+        }))
+        return flow
+    "#,
+    );
+
+    let mut ctx = HashMap::new();
+    ctx.insert(
+        "base".to_string(),
+        serde_json::json!("https://api.example.com"),
+    );
+    ctx.insert("key".to_string(), serde_json::json!("sk-live-123"));
+
+    let run_id = engine.execute(&flow, ctx).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    let resolved = info.tasks["call"].resolved_config.as_ref().unwrap();
+    assert_eq!(resolved["message"], "https://api.example.com/x");
+    assert_eq!(resolved["api_key"], "[REDACTED]");
+    assert!(!resolved.to_string().contains("sk-live-123"));
+}
+
+// --- DAG cycle detection ---
+
+#[tokio::test]
+async fn cycle_detection_prevents_execution() {
+    let flow = FlowDefinition {
+        name: "cycle".to_string(),
+        steps: vec![
+            StepDefinition {
+                name: "a".to_string(),
+                node_type: "log".to_string(),
+                config: serde_json::json!({"message": "a"}),
+                dependencies: vec!["b".to_string()],
+                retry: RetryConfig::default(),
+                timeout_s: None,
+                route: None,
+                route_from: None,
+                on_error: None,
+            },
+            StepDefinition {
+                name: "b".to_string(),
+                node_type: "log".to_string(),
+                config: serde_json::json!({"message": "b"}),
+                dependencies: vec!["a".to_string()],
+                retry: RetryConfig::default(),
+                timeout_s: None,
+                route: None,
+                route_from: None,
+                on_error: None,
+            },
+        ],
+        vars: Default::default(),
+        environments: Default::default(),
+        dedupe: None,
+        timeout_s: None,
+        hooks: Default::default(),
+    };
+
+    let (engine, _store) = engine();
+    let result = engine.execute(&flow, HashMap::new()).await;
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("Cycle"));
+}
+
+// --- File I/O round-trip ---
+
+#[tokio::test]
+async fn file_write_and_read() {
+    let (engine, store) = engine();
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("test.txt").to_string_lossy().to_string();
+
+    let source = format!(
+        r#"
+        local flow = Flow.new("file_io")
+        flow:step("write", nodes.write_file({{
+            path = "{}",
+            content = "hello world"
+        }}))
+        flow:step("read", nodes.read_file({{
+            path = "{}",
+            output_key = "content"
+        }})):depends_on("write")
+        return flow
+    "#,
+        file_path, file_path
+    );
+
+    let flow = load_flow(&source);
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(
+        info.ctx.get("content_content").unwrap(),
+        &serde_json::json!("hello world")
+    );
+}
+
+// --- step_if ---
+
+#[tokio::test]
+async fn step_if_runs_when_true() {
+    let (engine, store) = engine();
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("step_if_true")
+        flow:step_if("ctx.score > 50", "bonus", nodes.code({ source = "return { got_bonus = true }" }))
+        return flow
+    "#,
+    );
+
+    let mut ctx = HashMap::new();
+    ctx.insert("score".to_string(), serde_json::json!(80));
+
+    let run_id = engine.execute(&flow, ctx).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(info.ctx.get("got_bonus").unwrap(), &serde_json::json!(true));
+}
+
+#[tokio::test]
+async fn step_if_skips_when_false() {
+    let (engine, store) = engine();
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("step_if_false")
+        flow:step_if("ctx.score > 50", "bonus", nodes.code({ source = "return { got_bonus = true }" }))
+        return flow
+    "#,
+    );
+
+    let mut ctx = HashMap::new();
+    ctx.insert("score".to_string(), serde_json::json!(30));
+
+    let run_id = engine.execute(&flow, ctx).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Success);
+    assert!(!info.ctx.contains_key("got_bonus"));
+    assert_eq!(info.tasks["bonus"].status, TaskStatus::Skipped);
+}
+
+#[tokio::test]
+async fn step_if_with_function_handler() {
+    let (engine, store) = engine();
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("step_if_fn")
+        flow:step_if("ctx.active", "greet", function(ctx)
+            return { greeting = "Hello " .. ctx.name }
+        end)
+        return flow
+    "#,
+    );
+
+    let mut ctx = HashMap::new();
+    ctx.insert("active".to_string(), serde_json::json!(true));
+    ctx.insert("name".to_string(), serde_json::json!("Alice"));
+
+    let run_id = engine.execute(&flow, ctx).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(
+        info.ctx.get("greeting").unwrap(),
+        &serde_json::json!("Hello Alice")
+    );
+}
+
+// --- Seeded deterministic mode ---
+
+const SEEDED_FLOW: &str = r#"
+    local flow = Flow.new("seeded")
+    flow:step("a", nodes.code({
+        source = "return { id_a = uuid4(), at = now_rfc3339(), roll = math.random(1, 1000000) }"
+    }))
+    flow:step("b", nodes.code({
+        source = "return { id_b = uuid4(), ms = now_unix_ms() }"
+    }))
+    return flow
+"#;
+
+async fn seeded_run(seed: u64) -> RunInfo {
+    let reg = Arc::new(NodeRegistry::with_builtins());
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(reg, store.clone(), None).with_seed(seed);
+    let run_id = engine
+        .execute(&load_flow(SEEDED_FLOW), HashMap::new())
+        .await
+        .unwrap();
+    store.get_run_info(&run_id).await.unwrap()
+}
+
+#[tokio::test]
+async fn seeded_runs_are_identical() {
+    let first = seeded_run(42).await;
+    let second = seeded_run(42).await;
+
+    assert_eq!(first.status, RunStatus::Success);
+    assert_eq!(first.id, second.id);
+    assert_eq!(first.ctx, second.ctx);
+    for (name, task) in &first.tasks {
+        assert_eq!(task.output, second.tasks[name].output);
+    }
+    assert_eq!(first.ctx["at"], "2000-01-01T00:00:00+00:00");
+    assert_ne!(first.ctx["id_a"], first.ctx["id_b"]);
+}
+
+#[tokio::test]
+async fn different_seeds_diverge() {
+    let first = seeded_run(1).await;
+    let second = seeded_run(2).await;
+
+    assert_ne!(first.id, second.id);
+    assert_ne!(first.ctx["id_a"], second.ctx["id_a"]);
+}
+
+// --- Namespaced step outputs ---
+
+#[tokio::test]
+async fn namespaced_step_outputs_keep_colliding_keys_apart() {
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("namespaced")
+        flow:step("first", nodes.code({ source = "return { result = 'one' }" }))
+        flow:step("second", nodes.code({ source = "return { result = 'two' }" }))
+            :depends_on("first")
+        flow:step("combine", nodes.template_render({
+            template = "${steps.first.result}-${steps.second.result}",
+            output_key = "combined"
+        })):depends_on("second")
+        flow:step("read", nodes.code({
+            source = "return { via_code = ctx.steps.first.result }"
+        })):depends_on("second")
+        return flow
+    "#,
+    );
+
+    let reg = Arc::new(NodeRegistry::with_builtins());
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(reg, store.clone(), None).with_namespaced_step_outputs(true);
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(info.ctx["combined"], "one-two");
+    assert_eq!(info.ctx["via_code"], "one");
+    // The flat merge is unchanged: the last writer wins.
+    assert_eq!(info.ctx["result"], "two");
+}
+
+// --- Run metadata in code nodes ---
+
+#[tokio::test]
+async fn code_node_reads_run_metadata_and_step_outputs() {
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("metadata")
+        flow:step("fetch", nodes.code({ source = "return { result = 'one' }" }))
+        flow:step("inspect", nodes.code({
+            source = [[
+                local ok, err = pcall(function() steps.fetch = {} end)
+                return {
+                    seen_run_id = run_id,
+                    seen_flow = flow_name,
+                    seen_step = step_name,
+                    fetched = steps.fetch.result,
+                    write_blocked = not ok and string.find(tostring(err), "read-only", 1, true) ~= nil
+                }
+            ]]
+        })):depends_on("fetch")
+        return flow
+    "#,
+    );
+
+    let (engine, store) = engine();
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(info.ctx["seen_run_id"], run_id.as_str());
+    assert_eq!(info.ctx["seen_flow"], "metadata");
+    assert_eq!(info.ctx["seen_step"], "inspect");
+    assert_eq!(info.ctx["fetched"], "one");
+    assert_eq!(info.ctx["write_blocked"], true);
+}
+
+// --- Context limits ---
+
+fn limited_engine(limits: ContextLimits) -> (WorkflowEngine, Arc<dyn StateStore>) {
+    let reg = Arc::new(NodeRegistry::with_builtins());
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(reg, store.clone(), None).with_context_limits(limits);
+    (engine, store)
+}
+
+fn bloating_flow() -> FlowDefinition {
+    load_flow(
+        r#"
+        local flow = Flow.new("bloat")
+        flow:step("small", nodes.code({ source = "return { note = 'ok' }" }))
+        flow:step("big", nodes.code({
+            source = "return { blob = string.rep('x', 4096), tag = 'big' }"
+        })):depends_on("small")
+        flow:step("after", nodes.log({ message = "done" })):depends_on("big")
+        return flow
+    "#,
+    )
+}
+
+#[tokio::test]
+async fn context_over_limit_fails_run_with_clear_message() {
+    let (engine, store) = limited_engine(ContextLimits {
+        max_context_bytes: Some(1024),
+        ..Default::default()
+    });
+    let run_id = engine
+        .execute(&bloating_flow(), HashMap::new())
+        .await
+        .unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Failed);
+    assert_eq!(info.tasks["small"].status, TaskStatus::Success);
+    let error = info.tasks["big"].error.as_deref().unwrap();
+    assert!(error.contains("after step 'big'"), "{error}");
+    assert!(error.contains("limit of 1024 bytes"), "{error}");
+    assert_eq!(info.tasks["after"].status, TaskStatus::Skipped);
+    assert!(
+        !info.ctx.contains_key("blob"),
+        "oversized output must not be merged"
+    );
+}
+
+#[tokio::test]
+async fn context_value_over_limit_fails_step() {
+    let (engine, store) = limited_engine(ContextLimits {
+        max_value_bytes: Some(100),
+        ..Default::default()
+    });
+    let run_id = engine
+        .execute(&bloating_flow(), HashMap::new())
+        .await
+        .unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Failed);
+    let error = info.tasks["big"].error.as_deref().unwrap();
+    assert!(error.contains("to ctx.blob"), "{error}");
+    assert!(error.contains("per-value limit of 100 bytes"), "{error}");
+}
+
+#[tokio::test]
+async fn context_limit_counts_parallel_outputs_and_is_not_retried() {
+    let (engine, store) = limited_engine(ContextLimits {
+        max_context_bytes: Some(1024),
+        ..Default::default()
+    });
+    // Each output fits alone; whichever step merges second pushes the
+    // context over the limit.
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("parallel_bloat")
+        flow:step("left", nodes.code({ source = "return { left = string.rep('l', 600) }" }))
+            :retries(3, 0.01)
+        flow:step("right", nodes.code({ source = "return { right = string.rep('r', 600) }" }))
+            :retries(3, 0.01)
+        return flow
+    "#,
+    );
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Failed);
+    let failed: Vec<&TaskState> = info
+        .tasks
+        .values()
+        .filter(|t| t.status == TaskStatus::Failed)
+        .collect();
+    assert_eq!(failed.len(), 1, "exactly one merge must be rejected");
+    assert_eq!(failed[0].attempt, 1, "limit violations are not retried");
+    let detail = failed[0].error_detail.as_ref().unwrap();
+    assert_eq!(detail.kind, NodeErrorKind::ContextLimit);
+    assert!(!detail.retriable);
+    assert!(info.ctx.contains_key("left") != info.ctx.contains_key("right"));
+}
+
+#[tokio::test]
+async fn context_limit_counts_namespaced_step_outputs() {
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("namespaced_bloat")
+        flow:step("fill", nodes.code({ source = "return { fill = string.rep('f', 600) }" }))
+        return flow
+    "#,
+    );
+    let limits = ContextLimits {
+        max_context_bytes: Some(1024),
+        ..Default::default()
+    };
+
+    // The flat merge alone fits; the copy under ctx.steps.fill does not.
+    let (engine, store) = limited_engine(limits);
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+    assert_eq!(info.status, RunStatus::Success);
+
+    let (engine, store) = limited_engine(limits);
+    let engine = engine.with_namespaced_step_outputs(true);
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+    assert_eq!(info.status, RunStatus::Failed);
+    let error = info.tasks["fill"].error.as_deref().unwrap();
+    assert!(error.contains("after step 'fill'"), "{error}");
+    assert!(!info.ctx.contains_key("steps"));
+}
+
+#[tokio::test]
+async fn context_limit_truncate_mode_replaces_large_values() {
+    let (engine, store) = limited_engine(ContextLimits {
+        max_context_bytes: Some(1024),
+        max_value_bytes: None,
+        action: ContextLimitAction::Truncate,
+    });
+    let run_id = engine
+        .execute(&bloating_flow(), HashMap::new())
+        .await
+        .unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(info.ctx["blob"]["_truncated"], true);
+    assert_eq!(info.ctx["blob"]["_original_bytes"], 4098);
+    assert_eq!(info.ctx["tag"], "big");
+    assert_eq!(info.ctx["note"], "ok");
+}
+
+// --- Profiling ---
+
+#[tokio::test]
+async fn profile_lists_steps_slowest_first() {
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("profiled")
+        flow:step("quick", nodes.delay({ seconds = 0.01 }))
+        flow:step("slow", nodes.delay({ seconds = 0.15 }))
+        flow:step("medium", nodes.delay({ seconds = 0.07 }))
+        return flow
+    "#,
+    );
+
+    let reg = Arc::new(NodeRegistry::with_builtins());
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(reg, store.clone(), None).with_profiling(true);
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    let profile = info.profile.expect("profiled run should carry a profile");
+    let names: Vec<&str> = profile.steps.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["slow", "medium", "quick"]);
+    assert!(
+        profile
+            .steps
+            .windows(2)
+            .all(|w| w[0].duration_ms >= w[1].duration_ms)
+    );
+}
+
+#[tokio::test]
+async fn unprofiled_run_has_no_profile() {
+    let reg = Arc::new(NodeRegistry::with_builtins());
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(reg, store.clone(), None);
+    let run_id = engine
+        .execute(&load_flow(SEEDED_FLOW), HashMap::new())
+        .await
+        .unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+    assert!(info.profile.is_none());
+}
+
+// --- Concurrency limits ---
+
+#[tokio::test]
+async fn node_type_concurrency_limit_caps_parallel_steps() {
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("throttled")
+        for i = 1, 5 do
+            flow:step("wait_" .. i, nodes.delay({ seconds = 0.2 }))
+        end
+        flow:step("note", nodes.log({ message = "unthrottled" }))
+        return flow
+    "#,
+    );
+
+    let reg = Arc::new(NodeRegistry::with_builtins());
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(reg, store.clone(), Some(8))
+        .with_concurrency_limits(HashMap::from([("delay".to_string(), 2)]));
+
+    let started = std::time::Instant::now();
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let elapsed = started.elapsed();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Success);
+    // Five 0.2s delays two at a time need three rounds.
+    assert!(
+        elapsed >= std::time::Duration::from_millis(600),
+        "finished too fast for a limit of 2: {elapsed:?}"
+    );
+}
+
+/// Tracks how many executions overlap, standing in for a slow I/O node.
+struct GaugeNode {
+    running: Arc<std::sync::atomic::AtomicUsize>,
+    peak: Arc<std::sync::atomic::AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl ironflow::nodes::Node for GaugeNode {
+    fn node_type(&self) -> &str {
+        "gauge"
+    }
+
+    fn description(&self) -> &str {
+        "Records peak concurrent executions"
+    }
+
+    async fn execute(
+        &self,
+        config: &serde_json::Value,
+        _ctx: &Context,
+    ) -> anyhow::Result<NodeOutput> {
+        use std::sync::atomic::Ordering;
+
+        /// Decrements on drop, so a timed-out (dropped) execution counts too.
+        struct Running(Arc<std::sync::atomic::AtomicUsize>);
+        impl Drop for Running {
+            fn drop(&mut self) {
+                self.0.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+
+        let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        let _running = Running(self.running.clone());
+        self.peak.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        if config.get("fail").and_then(|v| v.as_bool()) == Some(true) {
+            anyhow::bail!("gauge failure");
+        }
+        Ok(NodeOutput::new())
+    }
+}
+
+#[tokio::test]
+async fn max_concurrent_tasks_bounds_simultaneous_steps() {
+    let running = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let peak = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let mut reg = NodeRegistry::with_builtins();
+    reg.register(Arc::new(GaugeNode {
+        running: running.clone(),
+        peak: peak.clone(),
+    }));
+    let reg = Arc::new(reg);
+
+    let flow = LuaRuntime::load_flow_from_string(
+        r#"
+        local flow = Flow.new("bounded")
+        for i = 1, 12 do
+            -- Failing and timing-out steps must give their permit back too.
+            if i % 4 == 0 then
+                flow:step("g" .. i, nodes.gauge({ fail = true }))
+            elseif i % 4 == 1 then
+                flow:step("g" .. i, nodes.gauge({})):timeout(0.01)
+            else
+                flow:step("g" .. i, nodes.gauge({}))
+            end
+        end
+        return flow
+    "#,
+        &reg,
+    )
+    .unwrap();
+
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::with_concurrency(reg, store.clone(), 3);
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.tasks.len(), 12);
+    assert!(info.tasks.values().all(|t| t.status != TaskStatus::Pending));
+    let peak = peak.load(std::sync::atomic::Ordering::SeqCst);
+    assert!(peak <= 3, "{peak} steps ran at once with a limit of 3");
+    assert!(peak >= 2, "steps never overlapped");
+}
+
+#[tokio::test]
+async fn node_type_concurrency_limit_is_shared_across_runs() {
+    let running = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let peak = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let mut reg = NodeRegistry::with_builtins();
+    reg.register(Arc::new(GaugeNode {
+        running: running.clone(),
+        peak: peak.clone(),
+    }));
+    let reg = Arc::new(reg);
+
+    let flow = LuaRuntime::load_flow_from_string(
+        r#"
+        local flow = Flow.new("shared_limit")
+        for i = 1, 3 do
+            flow:step("g" .. i, nodes.gauge({}))
+        end
+        return flow
+    "#,
+        &reg,
+    )
+    .unwrap();
+
+    // Two runs on one engine plus a run on a second engine handed the same
+    // limits, as the API server does for each request.
+    let limits = ConcurrencyLimits::new(&HashMap::from([("gauge".to_string(), 2)]));
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(reg.clone(), store.clone(), Some(8))
+        .with_concurrency_limits(limits.clone());
+    let other = WorkflowEngine::new(reg, store, Some(8)).with_concurrency_limits(limits);
+    let (a, b, c) = tokio::join!(
+        engine.execute(&flow, HashMap::new()),
+        engine.execute(&flow, HashMap::new()),
+        other.execute(&flow, HashMap::new()),
+    );
+    a.unwrap();
+    b.unwrap();
+    c.unwrap();
+
+    let peak = peak.load(std::sync::atomic::Ordering::SeqCst);
+    assert_eq!(
+        peak, 2,
+        "{peak} gauge steps ran at once with a shared limit of 2"
+    );
+}
+
+// --- Retrying failed runs ---
+
+/// Counts executions and fails while `healthy` is false, standing in for a
+/// step with a transient external dependency.
+struct FlakyNode {
+    healthy: Arc<std::sync::atomic::AtomicBool>,
+    calls: Arc<std::sync::atomic::AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl ironflow::nodes::Node for FlakyNode {
+    fn node_type(&self) -> &str {
+        "flaky"
+    }
+
+    fn description(&self) -> &str {
+        "Fails until marked healthy"
+    }
+
+    async fn execute(
+        &self,
+        config: &serde_json::Value,
+        ctx: &Context,
+    ) -> anyhow::Result<NodeOutput> {
+        use std::sync::atomic::Ordering;
+
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if config.get("check").and_then(|v| v.as_bool()) == Some(true)
+            && !self.healthy.load(Ordering::SeqCst)
+        {
+            anyhow::bail!("upstream unavailable");
+        }
+        // Output whether the first step's result is visible.
+        let key = config["output_key"].as_str().unwrap().to_string();
+        Ok(NodeOutput::from([(
+            key,
+            serde_json::Value::Bool(ctx.contains_key("prepared")),
+        )]))
+    }
+}
+
+#[tokio::test]
+async fn retry_reruns_only_failed_and_downstream_steps() {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    let healthy = Arc::new(AtomicBool::new(false));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let reg = Arc::new(NodeRegistry::with_builtins_and(|reg| {
+        reg.register(Arc::new(FlakyNode {
+            healthy: healthy.clone(),
+            calls: calls.clone(),
+        }))
+    }));
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(reg.clone(), store.clone(), None);
+
+    let flow = LuaRuntime::load_flow_from_string(
+        r#"
+        local flow = Flow.new("retryable")
+        flow:step("prepare", nodes.flaky({ output_key = "prepared" }))
+        flow:step("call", nodes.flaky({ check = true, output_key = "called" }))
+            :depends_on("prepare")
+        flow:step("report", nodes.log({ message = "called=${ctx.called}" }))
+            :depends_on("call")
+        return flow
+    "#,
+        &reg,
+    )
+    .unwrap();
+
+    let first_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let first = store.get_run_info(&first_id).await.unwrap();
+    assert_eq!(first.status, RunStatus::Failed);
+    assert_eq!(first.tasks["call"].status, TaskStatus::Failed);
+    assert_eq!(first.tasks["report"].status, TaskStatus::Skipped);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // Fix the transient cause, then retry the failed run.
+    healthy.store(true, Ordering::SeqCst);
+    let retry_id = engine.retry(&flow, &first).await.unwrap();
+    assert_ne!(retry_id, first_id);
+
+    let retried = store.get_run_info(&retry_id).await.unwrap();
+    assert_eq!(retried.status, RunStatus::Success);
+    assert_eq!(retried.ctx["_retried_from"], first_id);
+    assert!(!retried.ctx.contains_key("_error"));
+    // `prepare` kept its recorded output and was not executed again.
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert_eq!(retried.tasks["prepare"].status, TaskStatus::Success);
+    assert_eq!(
+        retried.tasks["prepare"].started,
+        first.tasks["prepare"].started
+    );
+    assert_eq!(retried.tasks["call"].attempt, 1);
+    assert_eq!(retried.ctx["log_message"], "called=true");
+
+    // A successful run has nothing to retry.
+    let error = engine.retry(&flow, &retried).await.unwrap_err();
+    assert!(error.to_string().contains("no failed steps"));
+}
+
+// --- Cancellation ---
+
+#[tokio::test]
+async fn cancel_token_aborts_in_flight_step_and_skips_the_rest() {
+    let (engine, store) = engine();
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("cancel_test")
+        flow:step("quick", nodes.log({ message = "started" }))
+        flow:step("slow", nodes.delay({ seconds = 30 })):depends_on("quick")
+        flow:step("after", nodes.log({ message = "never" })):depends_on("slow")
+        return flow
+    "#,
+    );
+
+    let cancel = tokio_util::sync::CancellationToken::new();
+    let trigger = cancel.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        trigger.cancel();
+    });
+
+    let started = std::time::Instant::now();
+    let run_id = engine
+        .execute_with_cancel(&flow, HashMap::new(), cancel)
+        .await
+        .unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+
+    let info = store.get_run_info(&run_id).await.unwrap();
+    assert_eq!(info.status, RunStatus::Cancelled);
+    assert!(info.finished.is_some());
+    assert_eq!(info.tasks["quick"].status, TaskStatus::Success);
+    assert_eq!(info.tasks["slow"].status, TaskStatus::Failed);
+    assert_eq!(
+        info.tasks["slow"].error.as_deref(),
+        Some("cancelled by request")
+    );
+    assert_eq!(info.tasks["after"].status, TaskStatus::Skipped);
+}
+
+// --- Run timeout ---
+
+#[tokio::test]
+async fn flow_timeout_stalls_run_and_fails_in_flight_step() {
+    let (engine, store) = engine();
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("timeout_test")
+        flow:timeout(0.3)
+        flow:step("quick", nodes.log({ message = "started" }))
+        flow:step("slow", nodes.delay({ seconds = 30 })):depends_on("quick")
+        flow:step("after", nodes.log({ message = "never" })):depends_on("slow")
+        return flow
+    "#,
+    );
+    assert_eq!(flow.timeout_s, Some(0.3));
+
+    let started = std::time::Instant::now();
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+
+    let info = store.get_run_info(&run_id).await.unwrap();
+    assert_eq!(info.status, RunStatus::Stalled);
+    assert_eq!(info.tasks["quick"].status, TaskStatus::Success);
+    assert_eq!(info.tasks["slow"].status, TaskStatus::Failed);
+    assert_eq!(
+        info.tasks["slow"].error.as_deref(),
+        Some("run timed out after 0.3s")
+    );
+    assert_eq!(info.tasks["after"].status, TaskStatus::Skipped);
+    assert_eq!(info.ctx["_error_message"], "run timed out after 0.3s");
+}
+
+#[tokio::test]
+async fn flow_timeout_does_not_affect_runs_that_finish_in_time() {
+    let (engine, store) = engine();
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new("timeout_ok")
+        flow:timeout(30)
+        flow:step("quick", nodes.log({ message = "done" }))
+        return flow
+    "#,
+    );
+
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+    assert_eq!(info.status, RunStatus::Success);
+    assert!(!info.ctx.contains_key("_error_message"));
+}
+
+#[test]
+fn flow_timeout_must_be_positive() {
+    let registry = NodeRegistry::with_builtins();
+    let err = LuaRuntime::load_flow_from_string(
+        r#"
+        local flow = Flow.new("bad")
+        flow:timeout(0)
+        flow:step("a", nodes.log({ message = "x" }))
+        return flow
+    "#,
+        &registry,
+    )
+    .unwrap_err();
+    assert!(format!("{:#}", err).contains("flow:timeout"));
+}
+
+// --- Dry run ---
+
+#[tokio::test]
+async fn plan_resolves_configs_without_executing_anything() {
+    let (engine, store) = engine();
+    let dir = tempfile::tempdir().unwrap();
+    let marker = dir.path().join("ran.txt");
+    let flow = load_flow(&format!(
+        r#"
+        local flow = Flow.new("dry")
+        flow:step("touch", nodes.shell_command({{
+            cmd = "touch",
+            args = {{ "{}" }}
+        }}))
+        flow:step("greet", nodes.template_render({{
+            template = "hello ${{ctx.name}}",
+            output_key = "greeting"
+        }})):depends_on("touch")
+        flow:step("call", nodes.http_post({{
+            url = "https://example.com/${{ctx.name}}",
+            headers = {{ Authorization = "Bearer abc" }}
+        }})):depends_on("touch")
+        return flow
+    "#,
+        marker.display()
+    ));
+    let ctx = HashMap::from([("name".to_string(), serde_json::json!("ada"))]);
+
+    let plan = engine.plan(&flow, &ctx).unwrap();
+
+    assert!(!marker.exists(), "dry run must not execute shell_command");
+    assert!(store.list_runs(None).await.unwrap().is_empty());
+
+    let names: Vec<(&str, usize)> = plan.iter().map(|s| (s.name.as_str(), s.phase)).collect();
+    assert_eq!(names, [("touch", 1), ("greet", 2), ("call", 2)]);
+    assert!(!plan[0].pure);
+    assert!(plan[1].pure);
+    assert!(!plan[2].pure);
+    assert_eq!(plan[1].resolved_config["template"], "hello ada");
+    assert_eq!(plan[2].resolved_config["url"], "https://example.com/ada");
+    assert_ne!(
+        plan[2].resolved_config["headers"]["Authorization"],
+        "Bearer abc"
     );
+    assert!(plan[0].resolved_config.get("_node_type").is_none());
 }
@@ -365,6 +365,32 @@ async fn extract_vtt_markdown() {
     assert!(transcript.contains("Great to see you"));
 }
 
+#[tokio::test]
+async fn extract_srt_with_bom_and_crlf() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("windows.srt");
+    fs::write(
+        &path,
+        "\u{feff}1\r\n00:00:00,000 --> 00:00:02,000\r\nFirst line\r\n\r\n2\r\n00:00:02,000 --> 00:00:04,000\r\nSecond line\r\n",
+    )
+    .unwrap();
+    let node = NodeRegistry::with_builtins().get("extract_srt").unwrap();
+
+    let out = node
+        .execute(
+            &serde_json::json!({ "path": path.to_string_lossy(), "format": "text" }),
+            &Context::new(),
+        )
+        .await
+        .unwrap();
+
+    let cues = out.get("cues").and_then(|v| v.as_array()).unwrap();
+    assert_eq!(cues.len(), 2);
+    assert_eq!(cues[0]["text"], "First line");
+    let transcript = out.get("transcript").unwrap().as_str().unwrap();
+    assert!(!transcript.contains('\r') && !transcript.contains('\u{feff}'));
+}
+
 #[tokio::test]
 async fn extract_srt_text_and_metadata() {
     let path = sample_srt_path();
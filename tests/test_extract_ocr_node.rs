@@ -0,0 +1,72 @@
+#![cfg(feature = "ocr")]
+
+//! Tests for the extract_ocr node. They need Tesseract with the `eng`
+//! traineddata installed.
+
+use std::collections::HashMap;
+
+use ironflow::nodes::NodeRegistry;
+use serde_json::json;
+
+/// Write a white PNG with `text` drawn in black by the watermark node.
+async fn create_text_image(registry: &NodeRegistry, path: &std::path::Path, text: &str) {
+    let blank = path.with_file_name("blank.png");
+    image::RgbImage::from_pixel(640, 160, image::Rgb([255, 255, 255]))
+        .save(&blank)
+        .unwrap();
+    let config = json!({
+        "path": blank.to_str().unwrap(),
+        "output_path": path.to_str().unwrap(),
+        "text": text,
+        "color": "#000000",
+        "opacity": 1.0,
+        "font_size": 48,
+    });
+    registry
+        .get("watermark")
+        .unwrap()
+        .execute(&config, &HashMap::new())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn extract_ocr_reads_text_from_image() {
+    let registry = NodeRegistry::with_builtins();
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("text.png");
+    create_text_image(&registry, &input, "INVOICE 42").await;
+
+    let node = registry
+        .get("extract_ocr")
+        .expect("extract_ocr not registered");
+    let config = json!({
+        "path": input.to_str().unwrap(),
+        "lang": "eng",
+        "output_key": "ocr",
+    });
+    let result = node.execute(&config, &HashMap::new()).await.unwrap();
+
+    let text = result.get("ocr").unwrap().as_str().unwrap();
+    assert!(text.contains("INVOICE"), "unexpected OCR text: {:?}", text);
+    let pages = result.get("ocr_pages").unwrap().as_array().unwrap();
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0]["page"], json!(1));
+    assert!(pages[0]["confidence"].as_i64().unwrap() > 0);
+}
+
+#[tokio::test]
+async fn extract_ocr_rejects_unknown_language() {
+    let registry = NodeRegistry::with_builtins();
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("text.png");
+    create_text_image(&registry, &input, "HELLO").await;
+
+    let node = registry.get("extract_ocr").unwrap();
+    let config = json!({
+        "path": input.to_str().unwrap(),
+        "lang": "no_such_language",
+    });
+    let err = node.execute(&config, &HashMap::new()).await.unwrap_err();
+    assert!(err.to_string().contains("failed to initialize Tesseract"));
+}
@@ -15,6 +15,68 @@ fn ctx_with(pairs: Vec<(&str, serde_json::Value)>) -> Context {
     pairs.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
 }
 
+// --- read_file ---
+
+#[tokio::test]
+async fn read_file_strips_bom_and_optionally_normalizes_newlines() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("windows.txt");
+    std::fs::write(&path, "\u{feff}line one\r\nline two\r\n").unwrap();
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("read_file").unwrap();
+
+    let raw = node
+        .execute(
+            &serde_json::json!({ "path": path.to_str().unwrap() }),
+            &empty_ctx(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(raw["file_content"], "line one\r\nline two\r\n");
+
+    let normalized = node
+        .execute(
+            &serde_json::json!({ "path": path.to_str().unwrap(), "normalize_newlines": true }),
+            &empty_ctx(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(normalized["file_content"], "line one\nline two\n");
+}
+
+#[tokio::test]
+async fn read_file_decompresses_gzip_transparently() {
+    let dir = tempfile::tempdir().unwrap();
+    let plain = dir.path().join("app.log");
+    let gzipped = dir.path().join("app.log.gz");
+    let text = "2026-01-01 INFO started\n2026-01-01 WARN slow request\n";
+    std::fs::write(&plain, text).unwrap();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(text.as_bytes()).unwrap();
+    std::fs::write(&gzipped, encoder.finish().unwrap()).unwrap();
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("read_file").unwrap();
+
+    let plain_out = node
+        .execute(
+            &serde_json::json!({ "path": plain.to_str().unwrap() }),
+            &empty_ctx(),
+        )
+        .await
+        .unwrap();
+    let gz_out = node
+        .execute(
+            &serde_json::json!({ "path": gzipped.to_str().unwrap() }),
+            &empty_ctx(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(gz_out["file_content"], plain_out["file_content"]);
+    assert_eq!(gz_out["file_content"], text);
+}
+
 // --- copy_file ---
 
 #[tokio::test]
@@ -0,0 +1,73 @@
+//! Tests for DOT / Mermaid export of a flow's step graph.
+
+use ironflow::engine::types::FlowDefinition;
+use ironflow::lua::runtime::LuaRuntime;
+use ironflow::nodes::NodeRegistry;
+
+fn load_flow(source: &str) -> FlowDefinition {
+    let registry = NodeRegistry::with_builtins();
+    LuaRuntime::load_flow_from_string(source, &registry).unwrap()
+}
+
+fn routed_flow() -> FlowDefinition {
+    load_flow(
+        r#"
+        local flow = Flow.new("orders")
+        flow:step("fetch", nodes.http_get({ url = "https://example.com/orders" }))
+        flow:step("check", nodes.if_node({
+            condition = "ctx.http_status == 200",
+            true_route = "ok",
+            false_route = "bad"
+        })):depends_on("fetch")
+        flow:step("store", nodes.log({ message = "stored" })):depends_on("check"):route("ok")
+        flow:step("alert", nodes.log({ message = "alert" })):depends_on("check"):route("bad")
+        flow:step("report", nodes.log({ message = "done" })):depends_on("store", "alert")
+            :on_error("cleanup")
+        flow:step("cleanup", nodes.log({ message = "cleanup" }))
+        return flow
+    "#,
+    )
+}
+
+#[test]
+fn dot_export_has_nodes_edges_and_route_labels() {
+    let dot = routed_flow().to_dot();
+
+    assert!(dot.starts_with("digraph \"orders\" {"));
+    assert!(dot.contains("\"fetch\" [label=\"fetch\\n[http_get]\"];"));
+    assert!(dot.contains("\"check\" [label=\"check\\n[if_node]\"];"));
+    assert!(dot.contains("\"fetch\" -> \"check\";"));
+    assert!(dot.contains("\"check\" -> \"store\" [label=\"ok\"];"));
+    assert!(dot.contains("\"check\" -> \"alert\" [label=\"bad\"];"));
+    assert!(dot.contains("\"store\" -> \"report\";"));
+    assert!(dot.contains("\"alert\" -> \"report\";"));
+    assert!(dot.contains("\"report\" -> \"cleanup\" [label=\"on_error\", style=dashed];"));
+    assert_eq!(dot.matches("->").count(), 6);
+    assert!(dot.trim_end().ends_with('}'));
+}
+
+#[test]
+fn mermaid_export_uses_step_ids_and_labels() {
+    let mermaid = routed_flow().to_mermaid();
+
+    assert!(mermaid.starts_with("flowchart TD\n"));
+    assert!(mermaid.contains("s0[\"fetch<br/>[http_get]\"]"));
+    assert!(mermaid.contains("s0 --> s1"));
+    assert!(mermaid.contains("s1 -->|\"ok\"| s2"));
+    assert!(mermaid.contains("s1 -->|\"bad\"| s3"));
+    assert!(mermaid.contains("s4 -.->|\"on_error\"| s5"));
+}
+
+#[test]
+fn dot_export_escapes_quotes_in_names() {
+    let flow = load_flow(
+        r#"
+        local flow = Flow.new('say "hi"')
+        flow:step('greet "world"', nodes.log({ message = "hi" }))
+        return flow
+    "#,
+    );
+    let dot = flow.to_dot();
+    assert!(dot.contains("digraph \"say \\\"hi\\\"\""));
+    assert!(dot.contains("\"greet \\\"world\\\"\" [label="));
+}
@@ -117,3 +117,177 @@ async fn foreach_filter_nulls_false_preserves_nil_results() {
     assert_eq!(arr[1], serde_json::Value::Null);
     assert_eq!(out.get("mapped_count").unwrap(), 4);
 }
+
+fn failing_foreach_config(continue_on_error: bool, max_concurrency: u32) -> serde_json::Value {
+    foreach_config(&format!(
+        r#"
+        local flow = Flow.new("foreach_errors")
+        flow:step("x", nodes.foreach({{
+            source_key = "items",
+            output_key = "mapped",
+            filter_nulls = false,
+            max_concurrency = {max_concurrency},
+            continue_on_error = {continue_on_error},
+            transform = function(item)
+                if item == 3 then
+                    error("bad item")
+                end
+                -- Earlier items take longer, so they finish out of order
+                local spin = 0
+                for _ = 1, (6 - item) * 200000 do spin = spin + 1 end
+                return item * 10
+            end
+        }}))
+        return flow
+    "#
+    ))
+}
+
+#[tokio::test]
+async fn foreach_concurrent_keeps_input_order_and_collects_errors() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("foreach").unwrap();
+    let ctx = ctx_with(vec![("items", serde_json::json!([1, 2, 3, 4, 5]))]);
+
+    for max_concurrency in [1, 3] {
+        let config = failing_foreach_config(true, max_concurrency);
+        let out = node.execute(&config, &ctx).await.unwrap();
+        assert_eq!(
+            out["mapped"],
+            serde_json::json!([10, 20, null, 40, 50]),
+            "max_concurrency = {max_concurrency}"
+        );
+        let errors = out["mapped_errors"].as_array().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0]["index"], 3);
+        assert_eq!(errors[0]["item"], 3);
+        assert!(errors[0]["error"].as_str().unwrap().contains("bad item"));
+    }
+}
+
+#[tokio::test]
+async fn foreach_concurrent_aborts_on_error_by_default() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("foreach").unwrap();
+    let ctx = ctx_with(vec![("items", serde_json::json!([1, 2, 3, 4, 5]))]);
+
+    for max_concurrency in [1, 3] {
+        let config = failing_foreach_config(false, max_concurrency);
+        let err = node.execute(&config, &ctx).await.unwrap_err();
+        let message = format!("{:#}", err);
+        assert!(message.contains("failed on item 2 (index 3)"), "{message}");
+        assert!(message.contains("bad item"), "{message}");
+    }
+}
+
+#[tokio::test]
+async fn foreach_concurrent_results_match_sequential() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("foreach").unwrap();
+    let items: Vec<u32> = (1..=40).collect();
+    let ctx = ctx_with(vec![("items", serde_json::json!(items))]);
+
+    let config = foreach_config(
+        r#"
+        local flow = Flow.new("foreach_concurrent")
+        flow:step("x", nodes.foreach({
+            source_key = "items",
+            output_key = "squares",
+            max_concurrency = 8,
+            transform = function(item, idx)
+                return { item = item * item, idx = idx, scale = ctx.scale }
+            end
+        }))
+        return flow
+    "#,
+    );
+    let mut ctx = ctx;
+    ctx.insert("scale".to_string(), serde_json::json!(2));
+
+    let out = node.execute(&config, &ctx).await.unwrap();
+    let squares = out["squares"].as_array().unwrap();
+    assert_eq!(squares.len(), 40);
+    for (i, entry) in squares.iter().enumerate() {
+        let n = (i + 1) as u64;
+        assert_eq!(entry["item"], n * n);
+        assert_eq!(entry["idx"], n);
+        assert_eq!(entry["scale"], 2);
+    }
+    assert!(!out.contains_key("squares_errors"));
+}
+
+#[tokio::test]
+async fn foreach_body_templates_output_paths_with_foreach_index() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("foreach").unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let out_dir = dir.path().to_string_lossy().replace('\\', "/");
+
+    let mut sources = Vec::new();
+    for (i, width) in [40u32, 60].into_iter().enumerate() {
+        let path = dir.path().join(format!("source_{}.png", i));
+        image::RgbaImage::from_pixel(width, 20, image::Rgba([200, 10, 10, 255]))
+            .save(&path)
+            .unwrap();
+        sources.push(serde_json::json!({ "path": path.to_string_lossy() }));
+    }
+
+    let config = foreach_config(&format!(
+        r#"
+        local flow = Flow.new("foreach_body")
+        flow:step("x", nodes.foreach({{
+            source_key = "images",
+            output_key = "thumbs",
+            body = nodes.image_resize({{
+                path = "${{ctx._foreach_item.path}}",
+                output_path = "{out_dir}/thumb_${{ctx._foreach_index}}.png",
+                width = 10,
+                output_key = "thumb"
+            }})
+        }}))
+        return flow
+    "#
+    ));
+    assert!(!node.is_pure_with(&config), "image bodies write files");
+
+    let ctx = ctx_with(vec![("images", serde_json::Value::Array(sources))]);
+    let out = node.execute(&config, &ctx).await.unwrap();
+
+    let thumbs = out["thumbs"].as_array().unwrap();
+    assert_eq!(out["thumbs_count"], 2);
+    assert_eq!(thumbs[0]["thumb"], format!("{}/thumb_1.png", out_dir));
+    assert_eq!(thumbs[1]["thumb"], format!("{}/thumb_2.png", out_dir));
+    assert_eq!(thumbs[0]["thumb_height"], 5);
+    assert_eq!(thumbs[1]["thumb_height"], 3);
+    assert!(!out.contains_key("_foreach_index"));
+    assert!(!out.contains_key("_foreach_item"));
+    for i in 1..=2 {
+        assert!(dir.path().join(format!("thumb_{}.png", i)).exists());
+    }
+}
+
+#[tokio::test]
+async fn foreach_rejects_transform_and_body_together() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("foreach").unwrap();
+
+    let config = foreach_config(
+        r#"
+        local flow = Flow.new("foreach_both")
+        flow:step("x", nodes.foreach({
+            source_key = "items",
+            transform = function(item) return item end,
+            body = nodes.log({ message = "${ctx._foreach_index}" })
+        }))
+        return flow
+    "#,
+    );
+
+    let ctx = ctx_with(vec![("items", serde_json::json!([1]))]);
+    let err = node.execute(&config, &ctx).await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("accepts either 'transform' or 'body', not both"),
+        "{err}"
+    );
+}
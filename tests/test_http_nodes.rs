@@ -1,11 +1,16 @@
-//! Tests for HTTP node implementations (http_get, http_post, http_put, http_delete, http_request).
+//! Tests for HTTP node implementations (http_get, http_post, http_put, http_delete, http_patch, http_head, http_request, graphql, websocket).
 
 use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::net::TcpListener;
+use std::sync::Arc;
 
-use ironflow::engine::types::Context;
+use ironflow::engine::WorkflowEngine;
+use ironflow::engine::types::{Context, NodeError, NodeErrorKind, TaskStatus};
+use ironflow::lua::LuaRuntime;
 use ironflow::nodes::NodeRegistry;
+use ironflow::storage::StateStore;
+use ironflow::storage::null_store::NullStateStore;
 
 type MockResponse = (
     u16,
@@ -128,9 +133,7 @@ fn spawn_capturing_mock_server(
     let (tx, rx) = std::sync::mpsc::channel();
     let handle = std::thread::spawn(move || {
         for mut stream in listener.incoming().take(1).flatten() {
-            let mut buf = [0u8; 4096];
-            let n = stream.read(&mut buf).unwrap_or(0);
-            let captured = String::from_utf8_lossy(&buf[..n]).to_string();
+            let captured = read_request(&mut stream);
             let _ = tx.send(captured);
             let _ = stream.write_all(response.as_bytes());
             let _ = stream.flush();
@@ -139,6 +142,35 @@ fn spawn_capturing_mock_server(
     (url, handle, rx)
 }
 
+/// Read one request: headers plus `Content-Length` bytes of body, which may
+/// arrive over several reads.
+fn read_request(stream: &mut std::net::TcpStream) -> String {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let n = stream.read(&mut buf).unwrap_or(0);
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&data);
+        if let Some(header_end) = text.find("\r\n\r\n") {
+            let content_length = text[..header_end]
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or(0);
+            if data.len() >= header_end + 4 + content_length {
+                break;
+            }
+        }
+    }
+    String::from_utf8_lossy(&data).to_string()
+}
+
 // ==================== http_get ====================
 
 #[tokio::test]
@@ -205,6 +237,125 @@ async fn http_get_fails_on_non_success_status_by_default() {
     handle.join().unwrap();
 }
 
+#[tokio::test]
+async fn step_retry_waits_for_retry_after() {
+    let (url, handle) = spawn_sequence_mock_server(vec![
+        (429, "Too Many Requests", vec![("Retry-After", "2")], "{}"),
+        (200, "OK", vec![], r#"{"ok":true}"#),
+    ]);
+
+    let reg = Arc::new(NodeRegistry::with_builtins());
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(reg.clone(), store.clone(), None);
+    let flow = LuaRuntime::load_flow_from_string(
+        &format!(
+            r#"
+            local flow = Flow.new("retry_after")
+            flow:step("fetch", nodes.http_get({{ url = "{url}" }})):retries(1, 0.01)
+            return flow
+        "#
+        ),
+        &reg,
+    )
+    .unwrap();
+
+    let started = std::time::Instant::now();
+    let run_id = engine.execute(&flow, empty_ctx()).await.unwrap();
+    let elapsed = started.elapsed();
+    handle.join().unwrap();
+
+    let info = store.get_run_info(&run_id).await.unwrap();
+    assert_eq!(info.tasks["fetch"].status, TaskStatus::Success);
+    assert_eq!(info.tasks["fetch"].attempt, 2);
+    assert!(
+        elapsed >= std::time::Duration::from_millis(1900),
+        "retried after {:?}, not the 2s Retry-After",
+        elapsed
+    );
+}
+
+#[tokio::test]
+async fn retry_after_http_date_is_surfaced_in_error() {
+    let (url, handle) = spawn_status_mock_server(
+        503,
+        "Service Unavailable",
+        &[("Retry-After", "Wed, 21 Oct 2015 07:28:00 GMT")],
+        "{}",
+    );
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_get").unwrap();
+    let err = node
+        .execute(&serde_json::json!({ "url": url }), &empty_ctx())
+        .await
+        .unwrap_err();
+    handle.join().unwrap();
+
+    // A date in the past means "retry now".
+    let detail = NodeError::classify(&err);
+    assert_eq!(detail.retry_after_ms, Some(0));
+    assert!(
+        detail.message.contains("Retry-After: 0s"),
+        "{}",
+        detail.message
+    );
+}
+
+#[tokio::test]
+async fn huge_retry_after_is_capped_by_max_retry_after() {
+    let (url, handle) = spawn_status_mock_server(
+        503,
+        "Service Unavailable",
+        &[("Retry-After", "1e300")],
+        "{}",
+    );
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_get").unwrap();
+    let err = node
+        .execute(
+            &serde_json::json!({ "url": url, "max_retry_after": 5 }),
+            &empty_ctx(),
+        )
+        .await
+        .unwrap_err();
+    handle.join().unwrap();
+
+    let detail = NodeError::classify(&err);
+    assert_eq!(detail.retry_after_ms, Some(5_000));
+}
+
+#[tokio::test]
+async fn http_404_surfaces_structured_error_kind() {
+    let (url, handle) = spawn_status_mock_server(404, "Not Found", &[], r#"{"error":"missing"}"#);
+
+    let reg = Arc::new(NodeRegistry::with_builtins());
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(reg.clone(), store.clone(), None);
+    let flow = LuaRuntime::load_flow_from_string(
+        &format!(
+            r#"
+            local flow = Flow.new("http_404")
+            flow:step("fetch", nodes.http_get({{ url = "{url}", fail_on_status = true }}))
+            return flow
+        "#
+        ),
+        &reg,
+    )
+    .unwrap();
+
+    let run_id = engine.execute(&flow, empty_ctx()).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+    let task = &info.tasks["fetch"];
+
+    assert_eq!(task.status, TaskStatus::Failed);
+    let detail = task.error_detail.as_ref().unwrap();
+    assert_eq!(detail.kind, NodeErrorKind::Http { status: 404 });
+    assert!(!detail.retriable);
+    assert!(task.error.as_ref().unwrap().contains("404"));
+    handle.join().unwrap();
+}
+
 #[tokio::test]
 async fn http_get_can_return_non_success_status_output() {
     let body = r#"{"error":{"message":"rate limited"}}"#;
@@ -529,6 +680,85 @@ async fn http_delete_happy_path() {
     handle.join().unwrap();
 }
 
+// ==================== http_patch ====================
+
+#[tokio::test]
+async fn http_patch_sends_body() {
+    let body = r#"{"patched":true}"#;
+    let (url, handle, rx) = spawn_capturing_mock_server(body);
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_patch").unwrap();
+    let config = serde_json::json!({
+        "url": url,
+        "body": { "status": "archived" }
+    });
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+
+    assert_eq!(output.get("http_status"), Some(&serde_json::json!(200)));
+    assert_eq!(
+        output.get("http_data"),
+        Some(&serde_json::json!({"patched": true}))
+    );
+
+    let captured = rx.recv().unwrap();
+    assert!(
+        captured.starts_with("PATCH "),
+        "Expected PATCH request, got: {}",
+        &captured[..20]
+    );
+    assert!(
+        captured.contains(r#"{"status":"archived"}"#),
+        "PATCH body missing: {}",
+        captured
+    );
+
+    handle.join().unwrap();
+}
+
+// ==================== http_head ====================
+
+#[tokio::test]
+async fn http_head_returns_headers_without_body() {
+    let (url, handle, rx) = spawn_capturing_mock_server(r#"{"ignored":true}"#);
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_head").unwrap();
+    let config = serde_json::json!({ "url": url, "output_key": "probe" });
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+
+    assert_eq!(output.get("probe_status"), Some(&serde_json::json!(200)));
+    assert_eq!(output.get("probe_success"), Some(&serde_json::json!(true)));
+    assert_eq!(
+        output["probe_headers"]["content-type"],
+        serde_json::json!("application/json")
+    );
+    assert!(!output.contains_key("probe_data"));
+
+    let captured = rx.recv().unwrap();
+    assert!(
+        captured.starts_with("HEAD "),
+        "Expected HEAD request, got: {}",
+        &captured[..20]
+    );
+    assert!(
+        captured.ends_with("\r\n\r\n"),
+        "HEAD request should have no body: {:?}",
+        captured
+    );
+
+    handle.join().unwrap();
+}
+
+#[tokio::test]
+async fn http_head_rejects_body() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_head").unwrap();
+    let config = serde_json::json!({ "url": "http://127.0.0.1:9", "body": { "a": 1 } });
+    let err = node.execute(&config, &empty_ctx()).await.unwrap_err();
+    assert!(err.to_string().contains("cannot have a 'body'"), "{}", err);
+}
+
 // ==================== http_request (generic) ====================
 
 #[tokio::test]
@@ -626,6 +856,76 @@ async fn http_request_post_form_body() {
     handle.join().unwrap();
 }
 
+#[tokio::test]
+async fn http_request_post_multipart_body() {
+    let dir = tempfile::tempdir().unwrap();
+    let report = dir.path().join("report.pdf");
+    std::fs::write(&report, b"%PDF-1.4 fake").unwrap();
+
+    let (url, handle, rx) = spawn_capturing_mock_server(r#"{"ok":true}"#);
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_request").unwrap();
+    let config = serde_json::json!({
+        "url": url,
+        "method": "POST",
+        "body_type": "multipart",
+        "headers": { "Content-Type": "application/json" },
+        "body": {
+            "title": "Q${ctx.quarter} report",
+            "document": { "file": "${ctx.report_path}", "content_type": "application/pdf" },
+            "renamed": { "file": "${ctx.report_path}", "filename": "summary.pdf" }
+        }
+    });
+    let ctx = HashMap::from([
+        ("quarter".to_string(), serde_json::json!(3)),
+        (
+            "report_path".to_string(),
+            serde_json::json!(report.to_string_lossy()),
+        ),
+    ]);
+    let output = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(output["http_status"], 200);
+
+    let captured = rx.recv().unwrap();
+    handle.join().unwrap();
+    let boundary = captured
+        .lines()
+        .find_map(|l| {
+            l.to_ascii_lowercase()
+                .starts_with("content-type: multipart/form-data; boundary=")
+                .then(|| l.split("boundary=").nth(1).unwrap().trim().to_string())
+        })
+        .expect("multipart content type with boundary");
+    assert!(!captured.contains("application/json"));
+    assert!(captured.contains(&format!("--{}\r\n", boundary)));
+    assert!(captured.contains(&format!("--{}--", boundary)));
+    assert!(captured.contains("Content-Disposition: form-data; name=\"title\"\r\n\r\nQ3 report"));
+    assert!(captured.contains(
+        "Content-Disposition: form-data; name=\"document\"; filename=\"report.pdf\"\r\nContent-Type: application/pdf\r\n\r\n%PDF-1.4 fake"
+    ));
+    assert!(captured.contains(
+        "name=\"renamed\"; filename=\"summary.pdf\"\r\nContent-Type: application/octet-stream"
+    ));
+}
+
+#[tokio::test]
+async fn http_request_multipart_missing_file_fails() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_request").unwrap();
+    let config = serde_json::json!({
+        "url": "http://127.0.0.1:9/",
+        "method": "POST",
+        "body_type": "multipart",
+        "body": { "upload": { "file": "/nonexistent/ironflow-upload.bin" } }
+    });
+    let err = node.execute(&config, &empty_ctx()).await.unwrap_err();
+    assert!(
+        err.to_string().contains("multipart field 'upload'"),
+        "{}",
+        err
+    );
+}
+
 #[tokio::test]
 async fn http_request_invalid_body_type() {
     let reg = NodeRegistry::with_builtins();
@@ -679,6 +979,377 @@ async fn http_request_connection_refused() {
     assert!(result.is_err(), "Connection to closed port should fail");
 }
 
+// ==================== response caching ====================
+
+#[tokio::test]
+async fn http_get_cache_ttl_serves_repeat_from_cache() {
+    // The mock answers a single connection; a second network call would fail.
+    let (url, handle) = spawn_mock_server(r#"{"rate":1.25}"#);
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_get").unwrap();
+    let config = serde_json::json!({
+        "url": format!("{}/rates?cache_test=hit", url),
+        "output_key": "rates",
+        "cache_ttl": 60
+    });
+
+    let first = node.execute(&config, &empty_ctx()).await.unwrap();
+    handle.join().unwrap();
+    assert_eq!(first["rates_cached"], false);
+    assert_eq!(first["rates_data"]["rate"], 1.25);
+
+    let second = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(second["rates_cached"], true);
+    assert_eq!(second["rates_status"], 200);
+    assert_eq!(second["rates_data"]["rate"], 1.25);
+    assert_eq!(second["rates_attempts"], 0);
+}
+
+#[tokio::test]
+async fn http_get_cache_respects_no_store() {
+    let (url, handle) = spawn_sequence_mock_server(vec![
+        (
+            200,
+            "OK",
+            vec![("Cache-Control", "private, no-store")],
+            r#"{"n":1}"#,
+        ),
+        (200, "OK", vec![], r#"{"n":2}"#),
+    ]);
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_get").unwrap();
+    let config = serde_json::json!({ "url": format!("{}/no-store", url), "cache_ttl": 60 });
+
+    let first = node.execute(&config, &empty_ctx()).await.unwrap();
+    let second = node.execute(&config, &empty_ctx()).await.unwrap();
+    handle.join().unwrap();
+
+    assert_eq!(first["http_data"]["n"], 1);
+    assert_eq!(second["http_data"]["n"], 2);
+    assert_eq!(second["http_cached"], false);
+}
+
+#[tokio::test]
+async fn http_get_without_cache_ttl_has_no_cached_flag() {
+    let (url, handle) = spawn_mock_server(r#"{"ok":true}"#);
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_get").unwrap();
+    let config = serde_json::json!({ "url": url });
+
+    let result = node.execute(&config, &empty_ctx()).await.unwrap();
+    handle.join().unwrap();
+    assert!(!result.contains_key("http_cached"));
+}
+
+// ==================== rate limiting ====================
+
+#[tokio::test]
+async fn http_rate_limit_spaces_requests_to_one_host() {
+    let (url, handle) = spawn_sequence_mock_server(vec![
+        (200, "OK", vec![], "{}"),
+        (200, "OK", vec![], "{}"),
+        (200, "OK", vec![], "{}"),
+        (200, "OK", vec![], "{}"),
+    ]);
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_request").unwrap();
+    let config = serde_json::json!({
+        "url": url,
+        "method": "GET",
+        "rate_limit": { "requests_per_second": 2 }
+    });
+
+    // Four concurrent calls at 2/s with a burst of 1: the last one leaves
+    // 1.5s after the first.
+    let ctx = empty_ctx();
+    let started = std::time::Instant::now();
+    let results = futures_util::future::join_all((0..4).map(|_| node.execute(&config, &ctx))).await;
+    let elapsed = started.elapsed();
+    handle.join().unwrap();
+
+    assert!(results.iter().all(|r| r.is_ok()), "{:?}", results);
+    assert!(
+        elapsed >= std::time::Duration::from_millis(1450),
+        "took {:?}",
+        elapsed
+    );
+}
+
+#[tokio::test]
+async fn http_rate_limit_burst_lets_first_requests_through() {
+    let (url, handle) =
+        spawn_sequence_mock_server(vec![(200, "OK", vec![], "{}"), (200, "OK", vec![], "{}")]);
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_get").unwrap();
+    let config = serde_json::json!({
+        "url": url,
+        "rate_limit": { "requests_per_second": 0.5, "burst": 2 }
+    });
+
+    let started = std::time::Instant::now();
+    node.execute(&config, &empty_ctx()).await.unwrap();
+    node.execute(&config, &empty_ctx()).await.unwrap();
+    handle.join().unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn http_rate_limit_rejects_invalid_config() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_get").unwrap();
+    let config = serde_json::json!({
+        "url": "http://127.0.0.1:9/",
+        "rate_limit": { "requests_per_second": 0 }
+    });
+    let err = node.execute(&config, &empty_ctx()).await.unwrap_err();
+    assert!(err.to_string().contains("requests_per_second"));
+}
+
+// ==================== http_poll ====================
+
+#[tokio::test]
+async fn http_poll_waits_until_success_expr_matches() {
+    let (url, handle) = spawn_sequence_mock_server(vec![
+        (200, "OK", vec![], r#"{"state":"pending"}"#),
+        (200, "OK", vec![], r#"{"state":"pending"}"#),
+        (200, "OK", vec![], r#"{"state":"done","result":42}"#),
+    ]);
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_poll").unwrap();
+    let config = serde_json::json!({
+        "status_url": url,
+        "success_expr": "body.state == 'done'",
+        "failure_expr": "body.state == 'failed'",
+        "interval_s": 0.01,
+        "timeout_s": 10
+    });
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+
+    assert_eq!(output["poll_polls"], serde_json::json!(3));
+    assert_eq!(output["poll_status"], serde_json::json!(200));
+    assert_eq!(
+        output["poll_data"],
+        serde_json::json!({"state": "done", "result": 42})
+    );
+
+    handle.join().unwrap();
+}
+
+#[tokio::test]
+async fn http_poll_start_request_and_failure_expr() {
+    let (url, handle) = spawn_sequence_mock_server(vec![
+        (202, "Accepted", vec![], r#"{"id":"job-7"}"#),
+        (200, "OK", vec![], r#"{"state":"running"}"#),
+        (200, "OK", vec![], r#"{"state":"failed"}"#),
+    ]);
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_poll").unwrap();
+    let config = serde_json::json!({
+        "start": { "url": format!("{url}/jobs"), "body": { "input": "x" } },
+        "status_url": format!("{url}/jobs/${{ctx.job_start_data.id}}"),
+        "success_expr": "body.state == 'done'",
+        "failure_expr": "body.state == 'failed'",
+        "interval_s": 0,
+        "output_key": "job"
+    });
+    let err = node.execute(&config, &empty_ctx()).await.unwrap_err();
+
+    let message = err.to_string();
+    assert!(message.contains("after 2 polls"), "unexpected: {message}");
+    assert!(message.contains("failed"), "unexpected: {message}");
+
+    handle.join().unwrap();
+}
+
+#[tokio::test]
+async fn http_poll_times_out_with_timeout_kind() {
+    let (url, handle) =
+        spawn_sequence_mock_server(vec![(200, "OK", vec![], r#"{"state":"pending"}"#)]);
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_poll").unwrap();
+    let config = serde_json::json!({
+        "status_url": url,
+        "success_expr": "body.state == 'done'",
+        "interval_s": 5,
+        "timeout_s": 1
+    });
+    let err = node.execute(&config, &empty_ctx()).await.unwrap_err();
+
+    let detail = ironflow::engine::types::NodeError::classify(&err);
+    assert_eq!(detail.kind, NodeErrorKind::Timeout);
+
+    handle.join().unwrap();
+}
+
+// ==================== graphql ====================
+
+#[tokio::test]
+async fn graphql_posts_query_and_splits_data() {
+    let (url, handle, rx) =
+        spawn_capturing_mock_server(r#"{"data":{"user":{"id":"7","name":"Ada"}}}"#);
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("graphql").unwrap();
+    let mut ctx = empty_ctx();
+    ctx.insert("user_id".to_string(), serde_json::json!("7"));
+    let config = serde_json::json!({
+        "url": url,
+        "query": "query User($id: ID!) { user(id: $id) { id name } }",
+        "variables": { "id": "${ctx.user_id}" },
+        "operation_name": "User",
+        "auth": { "type": "bearer", "token": "gql-token" }
+    });
+    let output = node.execute(&config, &ctx).await.unwrap();
+
+    assert_eq!(
+        output["graphql_data"],
+        serde_json::json!({"user": {"id": "7", "name": "Ada"}})
+    );
+    assert_eq!(output["graphql_errors"], serde_json::json!([]));
+    assert_eq!(output["graphql_success"], serde_json::json!(true));
+
+    let captured = rx.recv().unwrap();
+    assert!(captured.starts_with("POST "));
+    assert!(
+        captured
+            .to_lowercase()
+            .contains("authorization: bearer gql-token")
+    );
+    let body = captured.split("\r\n\r\n").nth(1).unwrap();
+    let payload: serde_json::Value = serde_json::from_str(body).unwrap();
+    assert_eq!(payload["variables"], serde_json::json!({"id": "7"}));
+    assert_eq!(payload["operationName"], "User");
+    assert!(payload["query"].as_str().unwrap().starts_with("query User"));
+
+    handle.join().unwrap();
+}
+
+#[tokio::test]
+async fn graphql_fails_on_errors_unless_ignored() {
+    let response = r#"{"data":null,"errors":[{"message":"Cannot query field \"nope\""}]}"#;
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("graphql").unwrap();
+
+    let (url, handle) = spawn_mock_server(response);
+    let config = serde_json::json!({ "url": url, "query": "{ nope }" });
+    let err = node.execute(&config, &empty_ctx()).await.unwrap_err();
+    assert!(err.to_string().contains("Cannot query field \"nope\""));
+    handle.join().unwrap();
+
+    let (url, handle) = spawn_mock_server(response);
+    let config = serde_json::json!({
+        "url": url,
+        "query": "{ nope }",
+        "ignore_errors": true,
+        "output_key": "gql"
+    });
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(output["gql_data"], serde_json::Value::Null);
+    assert_eq!(
+        output["gql_errors"][0]["message"],
+        "Cannot query field \"nope\""
+    );
+    assert_eq!(output["gql_success"], serde_json::json!(false));
+    handle.join().unwrap();
+}
+
+// ==================== websocket ====================
+
+/// Spawn a WebSocket server that echoes every text and binary frame back on
+/// one connection, capturing the `Authorization` header of the handshake.
+// The handshake callback's error type is tungstenite's, not ours to shrink.
+#[allow(clippy::result_large_err)]
+async fn spawn_echo_websocket_server() -> (
+    String,
+    tokio::task::JoinHandle<()>,
+    tokio::sync::oneshot::Receiver<Option<String>>,
+) {
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let handle = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let capture = |request: &Request, response: Response| {
+            let auth = request
+                .headers()
+                .get("authorization")
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string);
+            let _ = tx.send(auth);
+            Ok(response)
+        };
+        let mut socket = tokio_tungstenite::accept_hdr_async(stream, capture)
+            .await
+            .unwrap();
+        while let Some(Ok(message)) = socket.next().await {
+            if message.is_text() || message.is_binary() {
+                if socket.send(message).await.is_err() {
+                    break;
+                }
+            } else if message.is_close() {
+                break;
+            }
+        }
+    });
+    (url, handle, rx)
+}
+
+#[tokio::test]
+async fn websocket_captures_echoed_message() {
+    let (url, handle, auth_rx) = spawn_echo_websocket_server().await;
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("websocket").unwrap();
+    let mut ctx = empty_ctx();
+    ctx.insert("greeting".to_string(), serde_json::json!("hello"));
+    let config = serde_json::json!({
+        "url": url,
+        "message": "${ctx.greeting} socket",
+        "headers": { "Authorization": "Bearer ws-token" },
+        "timeout_s": 5
+    });
+    let output = node.execute(&config, &ctx).await.unwrap();
+
+    assert_eq!(
+        output["websocket"],
+        serde_json::json!([{ "type": "text", "data": "hello socket" }])
+    );
+    assert_eq!(output["websocket_timed_out"], serde_json::json!(false));
+    assert_eq!(auth_rx.await.unwrap().as_deref(), Some("Bearer ws-token"));
+
+    handle.await.unwrap();
+}
+
+#[tokio::test]
+async fn websocket_stops_at_timeout_with_collected_frames() {
+    let (url, handle, _auth_rx) = spawn_echo_websocket_server().await;
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("websocket").unwrap();
+    let config = serde_json::json!({
+        "url": url,
+        "message": { "op": "ping" },
+        "max_messages": 3,
+        "timeout_s": 0.5,
+        "output_key": "ws"
+    });
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+
+    assert_eq!(
+        output["ws"],
+        serde_json::json!([{ "type": "text", "data": "{\"op\":\"ping\"}" }])
+    );
+    assert_eq!(output["ws_timed_out"], serde_json::json!(true));
+
+    handle.await.unwrap();
+}
+
 // ==================== custom output_key ====================
 
 #[tokio::test]
@@ -710,6 +1381,230 @@ async fn http_get_custom_output_key() {
     handle.join().unwrap();
 }
 
+// --- Lua http helpers (code node) ---
+
+#[tokio::test]
+async fn code_http_get_fetches_and_parses_json() {
+    let (url, handle, rx) = spawn_capturing_mock_server(r#"{"items":[{"id":7,"name":"widget"}]}"#);
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("code").unwrap();
+    let config = serde_json::json!({
+        "allow_http": true,
+        "source": format!(r#"
+            local resp = http_get("{}/items", {{ ["X-Trace"] = "abc" }})
+            local data = json_parse(resp.body)
+            return {{
+                status = resp.status,
+                success = resp.success,
+                content_type = resp.headers["content-type"],
+                first_name = data.items[1].name,
+            }}
+        "#, url),
+    });
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+
+    assert_eq!(output["status"], serde_json::json!(200));
+    assert_eq!(output["success"], serde_json::json!(true));
+    assert_eq!(
+        output["content_type"],
+        serde_json::json!("application/json")
+    );
+    assert_eq!(output["first_name"], serde_json::json!("widget"));
+    let request = rx.recv().unwrap();
+    assert!(request.starts_with("GET /items "), "{}", request);
+    assert!(
+        request.to_lowercase().contains("x-trace: abc"),
+        "{}",
+        request
+    );
+    handle.join().unwrap();
+}
+
+#[tokio::test]
+async fn code_http_request_posts_body_and_returns_error_status() {
+    let (url, handle, rx) = spawn_capturing_mock_server(r#"{"ok":true}"#);
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("code").unwrap();
+    let config = serde_json::json!({
+        "allow_http": true,
+        "source": format!(r#"
+            local resp = http_request({{
+                method = "POST",
+                url = "{}/orders",
+                body = {{ sku = "A-1", qty = 2 }},
+            }})
+            return {{ status = resp.status, ok = json_parse(resp.body).ok }}
+        "#, url),
+    });
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(output["status"], serde_json::json!(200));
+    assert_eq!(output["ok"], serde_json::json!(true));
+    let request = rx.recv().unwrap();
+    assert!(request.starts_with("POST /orders "), "{}", request);
+    assert!(request.contains(r#""sku":"A-1""#), "{}", request);
+    handle.join().unwrap();
+
+    let (url, handle) = spawn_status_mock_server(404, "Not Found", &[], r#"{"error":"missing"}"#);
+    let config = serde_json::json!({
+        "allow_http": true,
+        "source": format!(r#"return {{ status = http_get("{}").status }}"#, url),
+    });
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(output["status"], serde_json::json!(404));
+    handle.join().unwrap();
+}
+
+#[tokio::test]
+async fn code_http_helpers_require_allow_http() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("code").unwrap();
+    let config = serde_json::json!({
+        "source": "return { get = type(http_get), request = type(http_request) }",
+    });
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(output["get"], serde_json::json!("nil"));
+    assert_eq!(output["request"], serde_json::json!("nil"));
+
+    assert!(!node.is_pure_with(&serde_json::json!({ "source": "return 1", "allow_http": true })));
+}
+
+#[tokio::test]
+async fn code_timeout_ms_stops_script_waiting_on_http() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        if let Ok((mut stream, _)) = listener.accept() {
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf);
+            std::thread::sleep(std::time::Duration::from_secs(5));
+            let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}");
+        }
+    });
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("code").unwrap();
+    let config = serde_json::json!({
+        "allow_http": true,
+        "timeout_ms": 200,
+        "source": format!(r#"return {{ status = http_get("{}").status }}"#, url),
+    });
+    let started = std::time::Instant::now();
+    let err = node.execute(&config, &empty_ctx()).await.unwrap_err();
+    assert!(
+        err.to_string().contains("lua script exceeded 200ms"),
+        "{}",
+        err
+    );
+    assert!(started.elapsed() < std::time::Duration::from_secs(3));
+}
+
+// --- Response decoding ---
+
+/// Spawn a mock server returning `body` as raw bytes with the given headers.
+fn spawn_bytes_mock_server(
+    headers: &[(&str, &str)],
+    body: Vec<u8>,
+) -> (String, std::thread::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let url = format!("http://{}", addr);
+    let header_text = headers
+        .iter()
+        .map(|(name, value)| format!("{name}: {value}\r\n"))
+        .collect::<String>();
+    let mut response = format!(
+        "HTTP/1.1 200 OK\r\n{header_text}Content-Length: {}\r\n\r\n",
+        body.len()
+    )
+    .into_bytes();
+    response.extend_from_slice(&body);
+    let handle = std::thread::spawn(move || {
+        for mut stream in listener.incoming().take(1).flatten() {
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf);
+            let _ = stream.write_all(&response);
+            let _ = stream.flush();
+        }
+    });
+    (url, handle)
+}
+
+#[tokio::test]
+async fn http_get_decompresses_gzip_json() {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder
+        .write_all(br#"{"message":"compressed","count":3}"#)
+        .unwrap();
+    let gzipped = encoder.finish().unwrap();
+    let (url, handle) = spawn_bytes_mock_server(
+        &[
+            ("Content-Type", "application/json"),
+            ("Content-Encoding", "gzip"),
+        ],
+        gzipped,
+    );
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_get").unwrap();
+    let config = serde_json::json!({ "url": url });
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(output["http_data"]["message"], "compressed");
+    assert_eq!(output["http_data"]["count"], 3);
+    handle.join().unwrap();
+}
+
+#[tokio::test]
+async fn http_get_decodes_latin1_charset() {
+    // "café" in ISO-8859-1: 'é' is the single byte 0xE9.
+    let (url, handle) = spawn_bytes_mock_server(
+        &[("Content-Type", "text/plain; charset=ISO-8859-1")],
+        b"caf\xe9".to_vec(),
+    );
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_get").unwrap();
+    let config = serde_json::json!({ "url": url });
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(output["http_data"], "café");
+    handle.join().unwrap();
+}
+
+#[tokio::test]
+async fn http_get_response_encoding_base64_and_bytes() {
+    let payload = vec![0x89, b'P', b'N', b'G', 0x00, 0xff];
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_get").unwrap();
+
+    let (url, handle) = spawn_bytes_mock_server(&[("Content-Type", "image/png")], payload.clone());
+    let config = serde_json::json!({ "url": url, "response_encoding": "base64" });
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(output["http_data"], "iVBORwD/");
+    handle.join().unwrap();
+
+    let (url, handle) = spawn_bytes_mock_server(&[("Content-Type", "image/png")], payload);
+    let config = serde_json::json!({ "url": url, "response_encoding": "bytes" });
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(
+        output["http_data"],
+        serde_json::json!([0x89, 80, 78, 71, 0, 255])
+    );
+    handle.join().unwrap();
+}
+
+#[tokio::test]
+async fn http_get_rejects_unknown_response_encoding() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("http_get").unwrap();
+    let config = serde_json::json!({ "url": "http://127.0.0.1:9/", "response_encoding": "hex" });
+    let err = node.execute(&config, &empty_ctx()).await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Unsupported response_encoding: hex")
+    );
+}
+
 // --- Response size limit regression tests ---
 
 fn spawn_oversized_honest_server(response_body: Vec<u8>) -> (String, std::thread::JoinHandle<()>) {
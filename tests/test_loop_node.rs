@@ -0,0 +1,100 @@
+//! Tests for the `loop` node.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ironflow::engine::executor::WorkflowEngine;
+use ironflow::engine::types::*;
+use ironflow::lua::runtime::LuaRuntime;
+use ironflow::nodes::NodeRegistry;
+use ironflow::storage::StateStore;
+use ironflow::storage::null_store::NullStateStore;
+
+async fn run_flow(source: &str, ctx: Context) -> RunInfo {
+    let reg = Arc::new(NodeRegistry::with_builtins());
+    let flow = LuaRuntime::load_flow_from_string(source, &reg).unwrap();
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(reg, store.clone(), None);
+    let run_id = engine.execute(&flow, ctx).await.unwrap();
+    store.get_run_info(&run_id).await.unwrap()
+}
+
+fn counter_flow(max_iterations: u32) -> String {
+    format!(
+        r#"
+        local flow = Flow.new("count_up")
+        flow:step("count", nodes.loop({{
+            condition = "ctx.counter < 5",
+            max_iterations = {max_iterations},
+            body = nodes.code({{ source = "return {{ counter = ctx.counter + 1, seen = ctx.counter }}" }})
+        }}))
+        return flow
+    "#
+    )
+}
+
+#[tokio::test]
+async fn loop_increments_counter_until_threshold() {
+    let ctx = HashMap::from([("counter".to_string(), serde_json::json!(0))]);
+    let info = run_flow(&counter_flow(10), ctx).await;
+
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(info.ctx["counter"], 5);
+    // Each iteration sees the previous iteration's output
+    assert_eq!(info.ctx["seen"], 4);
+    assert_eq!(info.ctx["loop_iterations"], 5);
+}
+
+#[tokio::test]
+async fn loop_runs_zero_times_when_condition_starts_false() {
+    let ctx = HashMap::from([("counter".to_string(), serde_json::json!(7))]);
+    let info = run_flow(&counter_flow(10), ctx).await;
+
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(info.ctx["counter"], 7);
+    assert_eq!(info.ctx["loop_iterations"], 0);
+    assert!(!info.ctx.contains_key("seen"));
+}
+
+#[tokio::test]
+async fn loop_fails_when_max_iterations_exceeded() {
+    let ctx = HashMap::from([("counter".to_string(), serde_json::json!(0))]);
+    let info = run_flow(&counter_flow(3), ctx).await;
+
+    assert_eq!(info.status, RunStatus::Failed);
+    let error = info.tasks["count"].error.as_deref().unwrap();
+    assert!(error.contains("max_iterations (3)"), "{error}");
+}
+
+#[tokio::test]
+async fn loop_requires_max_iterations() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("loop").unwrap();
+    let config = serde_json::json!({
+        "condition": "ctx.counter < 5",
+        "body": { "node": "log", "config": { "message": "tick" } }
+    });
+
+    let err = node.execute(&config, &Context::new()).await.unwrap_err();
+    assert!(err.to_string().contains("max_iterations"));
+}
+
+#[tokio::test]
+async fn loop_accepts_node_and_config_body_with_output_key() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("loop").unwrap();
+    let config = serde_json::json!({
+        "condition": "ctx.attempt < 3",
+        "max_iterations": 5,
+        "output_key": "poll",
+        "body": {
+            "node": "code",
+            "config": { "source": "return { attempt = (ctx.attempt or 0) + 1 }" }
+        }
+    });
+    let ctx = Context::from([("attempt".to_string(), serde_json::json!(0))]);
+
+    let out = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(out["attempt"], 3);
+    assert_eq!(out["poll_iterations"], 3);
+}
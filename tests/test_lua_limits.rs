@@ -38,6 +38,7 @@ fn lua_instruction_hook_stops_infinite_loop() {
             max_memory_bytes: None,
             hook_interval: 10,
             gc_after_execution: true,
+            step_timeout_ms: None,
         },
     )
     .unwrap();
@@ -92,3 +93,147 @@ async fn code_node_stops_infinite_lua_source() {
 
     clear_lua_limit_env();
 }
+
+#[tokio::test]
+async fn code_node_stops_infinite_lua_inside_coroutine() {
+    let _guard = set_lua_limit_env().await;
+    let registry = NodeRegistry::with_builtins();
+    let node = registry.get("code").unwrap();
+
+    let err = node
+        .execute(
+            &serde_json::json!({
+                "source": "coroutine.wrap(function() while true do end end)()",
+            }),
+            &Default::default(),
+        )
+        .await
+        .expect_err("code node must interrupt infinite Lua in a coroutine");
+
+    assert!(
+        err.to_string().contains("instruction budget"),
+        "expected instruction budget error, got: {err}"
+    );
+
+    clear_lua_limit_env();
+}
+
+/// Run with only the per-step limits in play: no instruction, time or
+/// memory budget from the environment.
+async fn clear_process_budgets() -> tokio::sync::MutexGuard<'static, ()> {
+    let guard = ENV_LOCK.lock().await;
+    unsafe {
+        std::env::set_var("IRONFLOW_LUA_MAX_INSTRUCTIONS", "0");
+        std::env::set_var("IRONFLOW_LUA_MAX_SECONDS", "0");
+        std::env::set_var("IRONFLOW_LUA_MAX_MEMORY_BYTES", "0");
+    }
+    guard
+}
+
+#[tokio::test]
+async fn code_node_timeout_ms_stops_infinite_loop() {
+    let _guard = clear_process_budgets().await;
+    let registry = NodeRegistry::with_builtins();
+    let node = registry.get("code").unwrap();
+
+    let started = std::time::Instant::now();
+    let err = node
+        .execute(
+            &serde_json::json!({
+                "source": "while true do end",
+                "timeout_ms": 200,
+            }),
+            &Default::default(),
+        )
+        .await
+        .expect_err("timeout_ms must interrupt infinite Lua");
+    clear_lua_limit_env();
+
+    assert!(
+        err.to_string().contains("lua script exceeded 200ms"),
+        "expected step timeout error, got: {err}"
+    );
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn code_node_memory_limit_bytes_stops_large_allocation() {
+    let _guard = clear_process_budgets().await;
+    let registry = NodeRegistry::with_builtins();
+    let node = registry.get("code").unwrap();
+
+    let err = node
+        .execute(
+            &serde_json::json!({
+                "source": r#"
+                    local parts = {}
+                    for i = 1, 100000 do
+                        parts[i] = string.rep("x", 1024) .. i
+                    end
+                    return { count = #parts }
+                "#,
+                "memory_limit_bytes": 8 * 1024 * 1024,
+            }),
+            &Default::default(),
+        )
+        .await
+        .expect_err("memory_limit_bytes must stop a 100 MB allocation");
+
+    let small = node
+        .execute(
+            &serde_json::json!({
+                "source": "return { total = #string.rep('x', 1024) }",
+                "memory_limit_bytes": 8 * 1024 * 1024,
+            }),
+            &Default::default(),
+        )
+        .await;
+    clear_lua_limit_env();
+
+    assert!(
+        err.to_string()
+            .contains("lua script exceeded memory limit of 8388608 bytes"),
+        "expected memory limit error, got: {err}"
+    );
+    assert_eq!(small.unwrap()["total"], serde_json::json!(1024));
+}
+
+#[tokio::test]
+async fn code_node_rejects_invalid_step_limits() {
+    let registry = NodeRegistry::with_builtins();
+    let node = registry.get("code").unwrap();
+
+    for config in [
+        serde_json::json!({ "source": "return 1", "timeout_ms": 0 }),
+        serde_json::json!({ "source": "return 1", "memory_limit_bytes": "lots" }),
+    ] {
+        let err = node
+            .execute(&config, &Default::default())
+            .await
+            .expect_err("invalid limits must be rejected");
+        assert!(
+            err.to_string().contains("must be a positive integer"),
+            "got: {err}"
+        );
+    }
+}
+
+#[test]
+fn step_limits_only_tighten_process_budgets() {
+    let env = LuaExecutionLimits {
+        max_instructions: None,
+        max_seconds: Some(10),
+        max_memory_bytes: Some(1024),
+        hook_interval: 10,
+        gc_after_execution: true,
+        step_timeout_ms: None,
+    };
+
+    let tighter = env.with_step_limits(Some(500), Some(512));
+    assert_eq!(tighter.step_timeout_ms, Some(500));
+    assert_eq!(tighter.max_memory_bytes, Some(512));
+
+    let looser = env.with_step_limits(Some(60_000), Some(4096));
+    assert_eq!(looser.step_timeout_ms, None);
+    assert_eq!(looser.max_memory_bytes, Some(1024));
+}
@@ -3,6 +3,7 @@
 use std::io::Write;
 use std::sync::Arc;
 
+use ironflow::engine::types::BackoffStrategy;
 use ironflow::lua::runtime::LuaRuntime;
 use ironflow::nodes::NodeRegistry;
 
@@ -57,6 +58,98 @@ fn load_flow_with_retries() {
     assert!((flow.steps[0].retry.backoff_s - 2.0).abs() < f64::EPSILON);
 }
 
+#[test]
+fn load_flow_with_backoff_strategy() {
+    let reg = registry();
+    let source = r#"
+        local flow = Flow.new("backoff_test")
+        flow.defaults = { retry = { max_retries = 2, backoff = "fixed" } }
+        flow:step("plain", nodes.log({ message = "a" }))
+        flow:step("api_call", nodes.log({ message = "b" }))
+            :retries(5, 0.5, { backoff = "exponential_jitter", max_backoff_s = 8 })
+        return flow
+    "#;
+
+    let flow = LuaRuntime::load_flow_from_string(source, &reg).unwrap();
+    assert_eq!(flow.steps[0].retry.backoff, BackoffStrategy::Fixed);
+    assert_eq!(flow.steps[0].retry.max_backoff_s, None);
+    let retry = &flow.steps[1].retry;
+    assert_eq!(retry.max_retries, 5);
+    assert_eq!(retry.backoff, BackoffStrategy::ExponentialJitter);
+    assert_eq!(retry.max_backoff_s, Some(8.0));
+}
+
+#[test]
+fn load_flow_with_retry_on() {
+    let reg = registry();
+    let source = r#"
+        local flow = Flow.new("retry_on_test")
+        flow.defaults = { retry = { max_retries = 2, retry_on = { "timeout" } } }
+        flow:step("plain", nodes.log({ message = "a" }))
+        flow:step("api_call", nodes.log({ message = "b" }))
+            :retries(3, 0.5, { retry_on = { "timeout", "5xx" } })
+        return flow
+    "#;
+
+    let flow = LuaRuntime::load_flow_from_string(source, &reg).unwrap();
+    assert_eq!(flow.steps[0].retry.retry_on, vec!["timeout"]);
+    assert_eq!(flow.steps[1].retry.retry_on, vec!["timeout", "5xx"]);
+}
+
+#[test]
+fn load_flow_rejects_unknown_backoff_strategy() {
+    let reg = registry();
+    let source = r#"
+        local flow = Flow.new("bad_backoff")
+        flow:step("s", nodes.log({ message = "a" })):retries(1, 1.0, { backoff = "linear" })
+        return flow
+    "#;
+
+    let err = LuaRuntime::load_flow_from_string(source, &reg).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("unknown backoff 'linear'"),
+        "{err:#}"
+    );
+}
+
+#[test]
+fn load_flow_rejects_negative_delays_and_timeouts() {
+    let reg = registry();
+    for (setup, expected) in [
+        (
+            r#"flow:step("s", nodes.log({ message = "a" })):retries(1, 1.0, { max_backoff_s = -5 })"#,
+            "Step 's': max_backoff_s must be a non-negative number",
+        ),
+        (
+            r#"flow:step("s", nodes.log({ message = "a" })):retries(1, 0/0)"#,
+            "Step 's': backoff_s must be a non-negative number",
+        ),
+        (
+            r#"flow.defaults = { retry = { max_backoff_s = 1/0 } }"#,
+            "flow.defaults: max_backoff_s must be a non-negative number",
+        ),
+        (
+            r#"flow.defaults = { timeout_s = -1 }"#,
+            "flow.defaults: timeout_s must be a positive number",
+        ),
+        (
+            r#"flow:step("s", nodes.log({ message = "a" })):timeout(0)"#,
+            "Step 's': timeout_s must be a positive number",
+        ),
+    ] {
+        let source = format!(
+            r#"
+            local flow = Flow.new("bad_delays")
+            {setup}
+            flow:step("last", nodes.log({{ message = "b" }}))
+            return flow
+        "#
+        );
+        let err = LuaRuntime::load_flow_from_string(&source, &reg).unwrap_err();
+        assert!(format!("{err:#}").contains(expected), "{err:#}");
+    }
+}
+
 #[test]
 fn load_flow_with_timeout() {
     let reg = registry();
@@ -70,6 +163,29 @@ fn load_flow_with_timeout() {
     assert_eq!(flow.steps[0].timeout_s, Some(30.0));
 }
 
+#[test]
+fn load_flow_steps_inherit_flow_defaults() {
+    let reg = registry();
+    let source = r#"
+        local flow = Flow.new("defaults_test")
+        flow.defaults = { retry = { max_retries = 3, backoff_s = 0.5 }, timeout_s = 30 }
+        flow:step("plain", nodes.log({ message = "a" }))
+        flow:step("custom", nodes.log({ message = "b" })):retries(1, 2.0):timeout(5)
+        return flow
+    "#;
+
+    let flow = LuaRuntime::load_flow_from_string(source, &reg).unwrap();
+    let plain = &flow.steps[0];
+    assert_eq!(plain.retry.max_retries, 3);
+    assert!((plain.retry.backoff_s - 0.5).abs() < f64::EPSILON);
+    assert_eq!(plain.timeout_s, Some(30.0));
+
+    let custom = &flow.steps[1];
+    assert_eq!(custom.retry.max_retries, 1);
+    assert!((custom.retry.backoff_s - 2.0).abs() < f64::EPSILON);
+    assert_eq!(custom.timeout_s, Some(5.0));
+}
+
 #[test]
 fn load_flow_with_route() {
     let reg = registry();
@@ -155,8 +271,122 @@ fn load_flow_chained_builder() {
     assert_eq!(s.route.as_deref(), Some("yes"));
 }
 
+#[test]
+fn load_flow_on_route_adds_dependency_once() {
+    let reg = registry();
+    let source = r#"
+        local flow = Flow.new("labelled")
+        flow:step("check", nodes.if_node({ condition = "true" }))
+        flow:step("a", nodes.log({ message = "a" })):on_route("check", "true")
+        flow:step("b", nodes.log({ message = "b" })):depends_on("check"):on_route("check", "false")
+        return flow
+    "#;
+
+    let flow = LuaRuntime::load_flow_from_string(source, &reg).unwrap();
+    for (step, route) in flow.steps[1..].iter().zip(["true", "false"]) {
+        assert_eq!(step.dependencies, vec!["check"]);
+        assert_eq!(step.route.as_deref(), Some(route));
+        assert_eq!(step.route_from.as_deref(), Some("check"));
+    }
+}
+
+// --- Environment overlays ---
+
+#[test]
+fn flow_env_overlays_override_defaults() {
+    let reg = registry();
+    let source = r#"
+        local flow = Flow.new("deploy")
+        flow:env("default", { api_url = "http://localhost:8080", bucket = "dev-assets", retries = 1 })
+        flow:env("prod", { api_url = "https://api.example.com", bucket = "prod-assets" })
+        flow:step("s", nodes.log({ message = "${ctx.api_url}" }))
+        return flow
+    "#;
+    let flow = LuaRuntime::load_flow_from_string(source, &reg).unwrap();
+
+    let prod = flow.environment_context(Some("prod")).unwrap();
+    assert_eq!(prod["api_url"], "https://api.example.com");
+    assert_eq!(prod["bucket"], "prod-assets");
+    assert_eq!(prod["retries"], 1);
+
+    let defaults = flow.environment_context(None).unwrap();
+    assert_eq!(defaults["api_url"], "http://localhost:8080");
+
+    let err = flow.environment_context(Some("staging")).unwrap_err();
+    assert!(
+        err.to_string().contains("declared: default, prod"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn flow_vars_sit_beneath_env_overlays() {
+    let reg = registry();
+    let source = r#"
+        local flow = Flow.new("deploy")
+        flow.vars = { api_url = "http://localhost:8080", region = "eu-west-1" }
+        flow:env("prod", { api_url = "https://api.example.com" })
+        flow:step("s", nodes.log({ message = "${ctx.api_url}" }))
+        return flow
+    "#;
+    let flow = LuaRuntime::load_flow_from_string(source, &reg).unwrap();
+    assert_eq!(flow.vars["region"], "eu-west-1");
+
+    let prod = flow.environment_context(Some("prod")).unwrap();
+    assert_eq!(prod["api_url"], "https://api.example.com");
+    assert_eq!(prod["region"], "eu-west-1");
+
+    let defaults = flow.environment_context(None).unwrap();
+    assert_eq!(defaults["api_url"], "http://localhost:8080");
+}
+
+#[test]
+fn flow_vars_reject_internal_keys() {
+    let reg = registry();
+    let source = r#"
+        local flow = Flow.new("deploy")
+        flow.vars = { _flow_dir = "/tmp" }
+        flow:step("s", nodes.log({ message = "hi" }))
+        return flow
+    "#;
+    let err = LuaRuntime::load_flow_from_string(source, &reg).unwrap_err();
+    assert!(err.to_string().contains("'_flow_dir'"), "{}", err);
+}
+
 // --- Duplicate step name detection ---
 
+#[test]
+fn flow_hooks_share_step_names() {
+    let reg = registry();
+    let source = r#"
+        local flow = Flow.new("hooks")
+        flow:step("notify", nodes.log({ message = "step" }))
+        flow:on_success("notify", nodes.log({ message = "hook" }))
+        return flow
+    "#;
+    let err = LuaRuntime::load_flow_from_string(source, &reg).unwrap_err();
+    assert!(
+        err.to_string().contains("Duplicate step name 'notify'"),
+        "{}",
+        err
+    );
+
+    let source = r#"
+        local flow = Flow.new("hooks")
+        flow:step("work", nodes.log({ message = "step" }))
+        flow:on_error("alert", nodes.log({ message = "failed" })):retries(2):timeout(5)
+        flow:on_success("done", function(ctx) return { ok = true } end)
+        return flow
+    "#;
+    let flow = LuaRuntime::load_flow_from_string(source, &reg).unwrap();
+    assert_eq!(flow.steps.len(), 1);
+    assert_eq!(flow.hooks.on_error[0].name, "alert");
+    assert_eq!(flow.hooks.on_error[0].retry.max_retries, 2);
+    assert_eq!(flow.hooks.on_error[0].timeout_s, Some(5.0));
+    assert_eq!(flow.hooks.on_success[0].node_type, "code");
+}
+
 #[test]
 fn duplicate_step_name_errors() {
     let reg = registry();
@@ -265,6 +495,236 @@ fn load_flow_from_file() {
     assert_eq!(flow.steps.len(), 1);
 }
 
+#[test]
+fn load_flow_includes_steps_from_other_files() {
+    let reg = registry();
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("lib")).unwrap();
+    std::fs::write(
+        dir.path().join("main.lua"),
+        r#"
+        local flow = Flow.new("composed")
+        flow:step("start", nodes.log({ message = "start" }))
+        include("lib/fetch.lua", flow)
+        local notify = include("lib/notify.lua")
+        notify(flow, "fetch")
+        return flow
+    "#,
+    )
+    .unwrap();
+    // Nested includes resolve relative to the including file.
+    std::fs::write(
+        dir.path().join("lib/fetch.lua"),
+        r#"
+        local flow = ...
+        flow:step("fetch", nodes.log({ message = "fetch" })):depends_on("start")
+        include("parse.lua", flow)
+    "#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("lib/parse.lua"),
+        r#"
+        local flow = ...
+        flow:step("parse", nodes.json_parse({ source_key = "raw", output_key = "data" }))
+            :depends_on("fetch")
+    "#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("lib/notify.lua"),
+        r#"
+        return function(flow, after)
+            flow:step("notify", nodes.log({ message = "done" })):depends_on(after)
+        end
+    "#,
+    )
+    .unwrap();
+
+    let flow = LuaRuntime::load_flow(&dir.path().join("main.lua").to_string_lossy(), &reg).unwrap();
+    let names: Vec<&str> = flow.steps.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["start", "fetch", "parse", "notify"]);
+    assert_eq!(flow.steps[2].node_type, "json_parse");
+    assert_eq!(flow.steps[3].dependencies, ["fetch"]);
+}
+
+#[test]
+fn load_flow_rejects_include_cycles() {
+    let reg = registry();
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("main.lua"),
+        r#"
+        local flow = Flow.new("cyclic")
+        include("a.lua", flow)
+        return flow
+    "#,
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("a.lua"), "include('b.lua', ...)").unwrap();
+    std::fs::write(dir.path().join("b.lua"), "include('a.lua', ...)").unwrap();
+
+    let err = LuaRuntime::load_flow(&dir.path().join("main.lua").to_string_lossy(), &reg)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("include cycle"), "{err}");
+    assert!(err.contains("a.lua -> "), "{err}");
+}
+
+#[test]
+fn load_flow_imports_steps_and_vars_from_flow_files() {
+    let reg = registry();
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("lib")).unwrap();
+    std::fs::write(
+        dir.path().join("main.lua"),
+        r#"
+        local flow = Flow.new("orders")
+        flow.vars = { region = "eu-west-1" }
+        flow:import("lib/fetch.lua")
+        flow:step("report", nodes.log({ message = "${ctx.orders_count}" })):depends_on("fetch")
+        return flow
+    "#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("lib/fetch.lua"),
+        r#"
+        local lib = Flow.new("fetch_library")
+        lib.vars = { base_url = "https://api.example.com", region = "us-east-1" }
+        lib:step("fetch", nodes.http_get({ url = "${ctx.base_url}/orders", output_key = "orders" }))
+        return lib
+    "#,
+    )
+    .unwrap();
+
+    let flow = LuaRuntime::load_flow(&dir.path().join("main.lua").to_string_lossy(), &reg).unwrap();
+    assert_eq!(flow.name, "orders");
+    let names: Vec<&str> = flow.steps.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["fetch", "report"]);
+    assert_eq!(flow.vars["base_url"], "https://api.example.com");
+    // The importing flow's own vars win.
+    assert_eq!(flow.vars["region"], "eu-west-1");
+}
+
+#[test]
+fn nested_imports_keep_vars_from_every_level() {
+    let reg = registry();
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("main.lua"),
+        r#"
+        local flow = Flow.new("main")
+        flow.vars = { tier = "main" }
+        flow:import("b.lua")
+        return flow
+    "#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("b.lua"),
+        r#"
+        local b = Flow.new("b")
+        b.vars = { from_b = true, shared = "b" }
+        b:import("c.lua")
+        b:step("b_step", nodes.log({ message = "b" })):depends_on("c_step")
+        return b
+    "#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("c.lua"),
+        r#"
+        local c = Flow.new("c")
+        c.vars = { from_c = true, shared = "c", tier = "c" }
+        c:step("c_step", nodes.log({ message = "c" }))
+        return c
+    "#,
+    )
+    .unwrap();
+
+    let flow = LuaRuntime::load_flow(&dir.path().join("main.lua").to_string_lossy(), &reg).unwrap();
+    let names: Vec<&str> = flow.steps.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["c_step", "b_step"]);
+    assert_eq!(flow.vars["from_c"], true);
+    assert_eq!(flow.vars["from_b"], true);
+    // Nearer flows win: main over b over c.
+    assert_eq!(flow.vars["shared"], "b");
+    assert_eq!(flow.vars["tier"], "main");
+}
+
+#[test]
+fn load_flow_rejects_import_cycles() {
+    let reg = registry();
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("main.lua"),
+        r#"
+        local flow = Flow.new("cyclic")
+        flow:import("a.lua")
+        return flow
+    "#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("a.lua"),
+        "local a = Flow.new('a'); a:import('b.lua'); return a",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("b.lua"),
+        "local b = Flow.new('b'); b:import('a.lua'); return b",
+    )
+    .unwrap();
+
+    let err = LuaRuntime::load_flow(&dir.path().join("main.lua").to_string_lossy(), &reg)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("include cycle"), "{err}");
+    assert!(err.contains("a.lua -> "), "{err}");
+    assert!(err.contains("b.lua -> "), "{err}");
+}
+
+#[test]
+fn import_requires_a_flow() {
+    let reg = registry();
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("main.lua"),
+        r#"
+        local flow = Flow.new("main")
+        flow:import("helpers.lua")
+        return flow
+    "#,
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("helpers.lua"), "return { answer = 42 }").unwrap();
+
+    let err = LuaRuntime::load_flow(&dir.path().join("main.lua").to_string_lossy(), &reg)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("must return a Flow"), "{err}");
+}
+
+#[test]
+fn inline_flow_cannot_include() {
+    let reg = registry();
+    let err = LuaRuntime::load_flow_from_string(
+        r#"
+        local flow = Flow.new("inline")
+        include("other.lua", flow)
+        return flow
+    "#,
+        &reg,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(
+        err.contains("only available in flows loaded from a file"),
+        "{err}"
+    );
+}
+
 #[test]
 fn load_flow_missing_file() {
     let reg = registry();
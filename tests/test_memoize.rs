@@ -0,0 +1,202 @@
+//! Tests for per-run memoization of pure node outputs.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use ironflow::engine::executor::WorkflowEngine;
+use ironflow::engine::types::{Context, NodeOutput, RunStatus, TaskStatus};
+use ironflow::lua::runtime::LuaRuntime;
+use ironflow::nodes::{Node, NodeRegistry};
+use ironflow::storage::StateStore;
+use ironflow::storage::null_store::NullStateStore;
+
+/// Doubles `ctx[source_key]` into `output_key`, counting its executions.
+struct CountingNode {
+    pure: bool,
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Node for CountingNode {
+    fn node_type(&self) -> &str {
+        if self.pure { "double" } else { "double_impure" }
+    }
+
+    fn description(&self) -> &str {
+        "Double a context number (test node)"
+    }
+
+    fn is_pure(&self) -> bool {
+        self.pure
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let source_key = config["source_key"].as_str().unwrap();
+        let output_key = config["output_key"].as_str().unwrap();
+        let value = ctx.get(source_key).and_then(|v| v.as_i64()).unwrap_or(0);
+
+        let mut output = NodeOutput::new();
+        output.insert(output_key.to_string(), serde_json::json!(value * 2));
+        Ok(output)
+    }
+}
+
+struct Counters {
+    pure: Arc<AtomicUsize>,
+    impure: Arc<AtomicUsize>,
+}
+
+fn counting_registry() -> (Arc<NodeRegistry>, Counters) {
+    let counters = Counters {
+        pure: Arc::new(AtomicUsize::new(0)),
+        impure: Arc::new(AtomicUsize::new(0)),
+    };
+    let (pure, impure) = (counters.pure.clone(), counters.impure.clone());
+    let registry = NodeRegistry::with_builtins_and(move |registry| {
+        registry.register(Arc::new(CountingNode {
+            pure: true,
+            calls: pure,
+        }));
+        registry.register(Arc::new(CountingNode {
+            pure: false,
+            calls: impure,
+        }));
+    });
+    (Arc::new(registry), counters)
+}
+
+/// `first` and `second` run the same step config one after the other;
+/// `third` reads a different key. Steps run in sequence so no other output
+/// lands in the context between two identical calls.
+const FLOW: &str = r#"
+    local flow = Flow.new("memo")
+    flow:step("first", nodes.double({ source_key = "n", output_key = "doubled" }))
+    flow:step("second", nodes.double({ source_key = "n", output_key = "doubled" }))
+        :depends_on("first")
+    flow:step("third", nodes.double({ source_key = "doubled", output_key = "quadrupled" }))
+        :depends_on("second")
+    flow:step("impure_a", nodes.double_impure({ source_key = "n", output_key = "other" }))
+        :depends_on("third")
+    flow:step("impure_b", nodes.double_impure({ source_key = "n", output_key = "other" }))
+        :depends_on("impure_a")
+    return flow
+"#;
+
+async fn run(memo_entries: usize) -> (Counters, Context) {
+    let (registry, counters) = counting_registry();
+    let flow = LuaRuntime::load_flow_from_string(FLOW, &registry).unwrap();
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine =
+        WorkflowEngine::new(registry, store.clone(), Some(4)).with_memoization(memo_entries);
+
+    let ctx = HashMap::from([("n".to_string(), serde_json::json!(21))]);
+    let run_id = engine.execute(&flow, ctx).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+    assert_eq!(info.status, RunStatus::Success);
+    (counters, info.ctx)
+}
+
+#[tokio::test]
+async fn memoized_pure_node_executes_once_for_identical_inputs() {
+    let (counters, ctx) = run(16).await;
+
+    // `first` and `second` share one execution; `third` has new inputs.
+    assert_eq!(counters.pure.load(Ordering::SeqCst), 2);
+    assert_eq!(ctx["doubled"], 42);
+    assert_eq!(ctx["quadrupled"], 84);
+}
+
+#[tokio::test]
+async fn memoization_never_skips_impure_nodes() {
+    let (counters, _) = run(16).await;
+    assert_eq!(counters.impure.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn memoization_is_off_by_default() {
+    let (counters, ctx) = run(0).await;
+    assert_eq!(counters.pure.load(Ordering::SeqCst), 3);
+    assert_eq!(ctx["doubled"], 42);
+}
+
+#[tokio::test]
+async fn route_mode_asserts_with_the_same_config_each_write_their_route() {
+    let registry = Arc::new(NodeRegistry::with_builtins());
+    let flow = LuaRuntime::load_flow_from_string(
+        r#"
+        local flow = Flow.new("memo_routes")
+        local check = { conditions = { "ctx.n > 10" }, on_fail = "route", output_key = "check" }
+        flow:step("check_a", nodes.assert(check))
+        flow:step("check_b", nodes.assert(check)):depends_on("check_a")
+        flow:step("after_b", nodes.log({ message = "passed" })):on_route("check_b", "pass")
+        return flow
+    "#,
+        &registry,
+    )
+    .unwrap();
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(registry.clone(), store.clone(), Some(4)).with_memoization(16);
+
+    let ctx = HashMap::from([("n".to_string(), serde_json::json!(21))]);
+    let run_id = engine.execute(&flow, ctx).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(info.ctx["_route_check_a"], "pass");
+    assert_eq!(info.ctx["_route_check_b"], "pass");
+    assert_eq!(info.tasks["after_b"].status, TaskStatus::Success);
+
+    // The route key is named after the step, so the memo key must keep the
+    // step name: route-mode asserts cannot list their outputs.
+    let assert_node = registry.get("assert").unwrap();
+    let config = serde_json::json!({ "conditions": ["ctx.n > 10"], "on_fail": "route", "output_key": "check" });
+    assert!(assert_node.produced_keys(&config).is_none());
+    for node_type in [
+        "if_node",
+        "switch_node",
+        "if_http_status",
+        "if_body_contains",
+    ] {
+        let node = registry.get(node_type).unwrap();
+        let config = serde_json::json!({ "output_key": "ignored" });
+        assert!(node.produced_keys(&config).is_none(), "{}", node_type);
+    }
+}
+
+#[tokio::test]
+async fn steps_that_read_and_write_the_same_key_are_not_reused() {
+    let registry = Arc::new(NodeRegistry::with_builtins());
+    let flow = LuaRuntime::load_flow_from_string(
+        r#"
+        local flow = Flow.new("memo_in_place")
+        local encode = { source_key = "data", output_key = "data" }
+        flow:step("enc_1", nodes.base64_encode(encode))
+        flow:step("enc_2", nodes.base64_encode(encode)):depends_on("enc_1")
+        local templated = { input = "${ctx.text}", output_key = "text" }
+        flow:step("tpl_1", nodes.base64_encode(templated)):depends_on("enc_2")
+        flow:step("tpl_2", nodes.base64_encode(templated)):depends_on("tpl_1")
+        return flow
+    "#,
+        &registry,
+    )
+    .unwrap();
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(registry, store.clone(), Some(4)).with_memoization(16);
+
+    let ctx = HashMap::from([
+        ("data".to_string(), serde_json::json!("raw")),
+        ("text".to_string(), serde_json::json!("raw")),
+    ]);
+    let run_id = engine.execute(&flow, ctx).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+
+    assert_eq!(info.status, RunStatus::Success);
+    // enc(enc("raw")), not enc("raw") reused from the first step.
+    assert_eq!(info.ctx["data"], "Y21GMw==");
+    assert_eq!(info.ctx["text"], "Y21GMw==");
+}
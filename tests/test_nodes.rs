@@ -2,7 +2,7 @@
 
 use std::collections::HashMap;
 
-use ironflow::engine::types::Context;
+use ironflow::engine::types::{Context, NodeOutput};
 use ironflow::nodes::NodeRegistry;
 
 // --- Helper ---
@@ -135,12 +135,96 @@ fn registry_snapshot_shares_nodes() {
 fn registry_list_is_sorted() {
     let reg = NodeRegistry::with_builtins();
     let list = reg.list();
-    let names: Vec<&str> = list.iter().map(|(n, _)| *n).collect();
+    let names: Vec<&str> = list.iter().map(|(n, _, _)| *n).collect();
     let mut sorted = names.clone();
     sorted.sort();
     assert_eq!(names, sorted);
 }
 
+#[test]
+fn registry_list_reports_purity() {
+    let reg = NodeRegistry::with_builtins();
+    let pure = |name: &str| {
+        reg.list()
+            .into_iter()
+            .find(|(n, _, _)| *n == name)
+            .map(|(_, _, pure)| pure)
+            .unwrap()
+    };
+    assert!(pure("json_parse"));
+    assert!(!pure("shell_command"));
+    assert!(!reg.get("shell_command").unwrap().is_pure());
+    assert!(reg.get("json_parse").unwrap().is_pure());
+}
+
+#[test]
+fn purity_accounts_for_clock_randomness_and_files() {
+    let reg = NodeRegistry::with_builtins();
+    let pure_with =
+        |name: &str, config: serde_json::Value| reg.get(name).unwrap().is_pure_with(&config);
+
+    // The Lua sandbox exposes uuid4, now_rfc3339, math.random and run globals.
+    assert!(!pure_with(
+        "code",
+        serde_json::json!({ "source": "return 1" })
+    ));
+    assert!(!pure_with(
+        "map",
+        serde_json::json!({ "expression": "item" })
+    ));
+    assert!(!pure_with(
+        "foreach",
+        serde_json::json!({ "transform_bytecode_b64": "" })
+    ));
+
+    assert!(pure_with(
+        "date_format",
+        serde_json::json!({ "input": "2024-01-15" })
+    ));
+    assert!(!pure_with(
+        "date_format",
+        serde_json::json!({ "input": "now" })
+    ));
+    assert!(!pure_with(
+        "date_format",
+        serde_json::json!({ "input": "${ctx.when}" })
+    ));
+    assert!(!pure_with(
+        "date_format",
+        serde_json::json!({ "source_key": "when" })
+    ));
+
+    assert!(pure_with(
+        "csv_parse",
+        serde_json::json!({ "source_key": "raw" })
+    ));
+    assert!(!pure_with(
+        "csv_parse",
+        serde_json::json!({ "path": "in.csv" })
+    ));
+    assert!(!pure_with(
+        "csv_parse",
+        serde_json::json!({ "source_key": "raw", "batch_size": 10, "output_dir": "out" })
+    ));
+
+    assert!(pure_with(
+        "base64_encode",
+        serde_json::json!({ "input": "x" })
+    ));
+    assert!(!pure_with(
+        "base64_encode",
+        serde_json::json!({ "file": "in.bin" })
+    ));
+    assert!(!pure_with(
+        "base64_decode",
+        serde_json::json!({ "output_file": "out.bin" })
+    ));
+    assert!(!pure_with(
+        "render_email",
+        serde_json::json!({ "template_dir": "emails" })
+    ));
+}
+
 // --- LogNode ---
 
 #[tokio::test]
@@ -272,6 +356,281 @@ async fn csv_parse_without_header() {
     assert_eq!(row[1], "30");
 }
 
+#[tokio::test]
+async fn csv_parse_deduplicates_headers() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("csv_parse").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "raw_csv",
+        "output_key": "rows"
+    });
+    let ctx = ctx_with(vec![(
+        "raw_csv",
+        serde_json::json!("name,name,age,name\nAlice,Smith,30,Jr"),
+    )]);
+
+    let result = node.execute(&config, &ctx).await.unwrap();
+    let rows = result.get("rows").unwrap().as_array().unwrap();
+    assert_eq!(
+        rows[0],
+        serde_json::json!({"name": "Alice", "name_2": "Smith", "age": "30", "name_3": "Jr"})
+    );
+}
+
+#[tokio::test]
+async fn csv_parse_strips_bom_and_handles_crlf() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("csv_parse").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "raw_csv",
+        "output_key": "rows"
+    });
+    let ctx = ctx_with(vec![(
+        "raw_csv",
+        serde_json::json!("\u{feff}id,name\r\n1,Alice\r\n2,Bob\r\n"),
+    )]);
+
+    let result = node.execute(&config, &ctx).await.unwrap();
+    let rows = result.get("rows").unwrap().as_array().unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0], serde_json::json!({"id": "1", "name": "Alice"}));
+    assert_eq!(rows[1], serde_json::json!({"id": "2", "name": "Bob"}));
+}
+
+#[tokio::test]
+async fn csv_parse_gzipped_source_matches_plain() {
+    use base64::Engine;
+    use std::io::Write;
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("csv_parse").unwrap();
+
+    let csv_text = "name,age\nAlice,30\nBob,25\n";
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(csv_text.as_bytes()).unwrap();
+    let gz_b64 = base64::engine::general_purpose::STANDARD.encode(encoder.finish().unwrap());
+
+    let plain = node
+        .execute(
+            &serde_json::json!({ "source_key": "raw", "output_key": "rows" }),
+            &ctx_with(vec![("raw", serde_json::json!(csv_text))]),
+        )
+        .await
+        .unwrap();
+    let gzipped = node
+        .execute(
+            &serde_json::json!({ "source_key": "raw", "output_key": "rows", "compressed": true }),
+            &ctx_with(vec![("raw", serde_json::json!(gz_b64))]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(gzipped["rows"], plain["rows"]);
+    assert_eq!(gzipped["rows"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn csv_parse_null_values_and_max_rows() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("csv_parse").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "raw_csv",
+        "output_key": "rows",
+        "infer_types": true,
+        "null_values": ["NA", ""],
+        "max_rows": 2
+    });
+    let ctx = ctx_with(vec![(
+        "raw_csv",
+        serde_json::json!("name,age\nAlice,NA\nBob,\nCarol,41"),
+    )]);
+
+    let result = node.execute(&config, &ctx).await.unwrap();
+    let rows = result.get("rows").unwrap().as_array().unwrap();
+    assert_eq!(rows.len(), 2);
+    assert!(rows[0]["age"].is_null());
+    assert!(rows[1]["age"].is_null());
+    assert_eq!(rows[1]["name"], "Bob");
+}
+
+#[tokio::test]
+async fn csv_parse_column_types_override_inference() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("csv_parse").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "raw_csv",
+        "output_key": "users",
+        "infer_types": true,
+        "column_types": { "zip": "string", "age": "int", "active": "bool" }
+    });
+    let ctx = ctx_with(vec![(
+        "raw_csv",
+        serde_json::json!(
+            "name,zip,age,active,score\nAlice,02134,34,TRUE,9.5\nBob,10001,n/a,0,7\nCarol,94105,,yes,8"
+        ),
+    )]);
+
+    let result = node.execute(&config, &ctx).await.unwrap();
+    let rows = result.get("users").unwrap().as_array().unwrap();
+    assert_eq!(rows[0]["zip"], "02134");
+    assert_eq!(rows[0]["age"], 34);
+    assert_eq!(rows[0]["active"], true);
+    // Columns without an explicit type are still inferred.
+    assert_eq!(rows[0]["score"], 9.5);
+    assert_eq!(rows[1]["active"], false);
+    assert!(rows[2]["age"].is_null());
+
+    // Unparseable fields stay strings and are reported.
+    assert_eq!(rows[1]["age"], "n/a");
+    assert_eq!(rows[2]["active"], "yes");
+    assert_eq!(
+        result.get("users_errors").unwrap(),
+        &serde_json::json!([
+            { "row": 2, "column": "age", "value": "n/a", "type": "int" },
+            { "row": 3, "column": "active", "value": "yes", "type": "bool" }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn csv_parse_column_types_stop_at_row_cap() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("csv_parse").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "raw_csv",
+        "output_key": "rows",
+        "has_header": false,
+        "column_types": { "column_2": "float" },
+        "max_rows": 2
+    });
+    let ctx = ctx_with(vec![("raw_csv", serde_json::json!("a,1.5\nb,2\nc,oops"))]);
+
+    let result = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(
+        result.get("rows").unwrap(),
+        &serde_json::json!([["a", 1.5], ["b", 2.0]])
+    );
+    // The third row is never read, so its bad value is not reported.
+    assert_eq!(result.get("rows_errors").unwrap(), &serde_json::json!([]));
+
+    let err = node
+        .execute(
+            &serde_json::json!({
+                "source_key": "raw_csv",
+                "output_key": "rows",
+                "column_types": { "a": "date" }
+            }),
+            &ctx,
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("unsupported column type 'date'"));
+}
+
+#[tokio::test]
+async fn csv_parse_path_streams_gzipped_file_with_bom() {
+    use std::io::Write;
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("csv_parse").unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("people.csv.gz");
+    let mut encoder = flate2::write::GzEncoder::new(
+        std::fs::File::create(&path).unwrap(),
+        flate2::Compression::default(),
+    );
+    encoder
+        .write_all("\u{feff}name,age\r\nAlice,30\r\nBob,25\r\nCarol,41\r\n".as_bytes())
+        .unwrap();
+    encoder.finish().unwrap();
+
+    let config = serde_json::json!({
+        "path": "${ctx.dir}/people.csv.gz",
+        "output_key": "rows",
+        "infer_types": true,
+        "batch_size": 2,
+        "output_dir": "${ctx.dir}/chunks"
+    });
+    let ctx = ctx_with(vec![(
+        "dir",
+        serde_json::json!(dir.path().to_str().unwrap()),
+    )]);
+    let result = node.execute(&config, &ctx).await.unwrap();
+
+    assert_eq!(result["rows_count"], 3);
+    let chunks: Vec<serde_json::Value> = result["rows_chunks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| {
+            serde_json::from_str(&std::fs::read_to_string(p.as_str().unwrap()).unwrap()).unwrap()
+        })
+        .collect();
+    assert_eq!(
+        chunks,
+        vec![
+            serde_json::json!([{ "name": "Alice", "age": 30 }, { "name": "Bob", "age": 25 }]),
+            serde_json::json!([{ "name": "Carol", "age": 41 }]),
+        ]
+    );
+
+    // Without batch_size the streamed rows land in context as usual.
+    let config = serde_json::json!({ "path": path.to_str().unwrap(), "output_key": "rows" });
+    let result = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(result["rows"][0]["name"], "Alice");
+    assert_eq!(result["rows"].as_array().unwrap().len(), 3);
+
+    let both = serde_json::json!({ "path": "x.csv", "source_key": "raw", "output_key": "rows" });
+    assert!(node.execute(&both, &empty_ctx()).await.is_err());
+}
+
+#[tokio::test]
+async fn csv_parse_caps_reported_errors_and_chunks_them() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("csv_parse").unwrap();
+
+    let csv = (0..1500).map(|i| format!("bad{i}\n")).collect::<String>();
+    let ctx = ctx_with(vec![("raw_csv", serde_json::json!(csv))]);
+    let config = serde_json::json!({
+        "source_key": "raw_csv",
+        "output_key": "rows",
+        "has_header": false,
+        "column_types": { "column_1": "int" }
+    });
+    let result = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(result["rows_error_count"], 1500);
+    let errors = result["rows_errors"].as_array().unwrap();
+    assert_eq!(errors.len(), 1000);
+    assert_eq!(errors[999]["value"], "bad999");
+
+    // With chunking, every error is also written next to the row chunks.
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = config;
+    config["batch_size"] = serde_json::json!(1000);
+    config["output_dir"] = serde_json::json!(dir.path().to_str().unwrap());
+    let result = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(result["rows_error_count"], 1500);
+    assert_eq!(result["rows_errors"].as_array().unwrap().len(), 1000);
+    let error_chunks = result["rows_error_chunks"].as_array().unwrap();
+    assert_eq!(error_chunks.len(), 2);
+    assert!(
+        error_chunks[0]
+            .as_str()
+            .unwrap()
+            .ends_with("rows_errors_00001.json")
+    );
+    let last: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(error_chunks[1].as_str().unwrap()).unwrap())
+            .unwrap();
+    assert_eq!(last.as_array().unwrap().len(), 500);
+    assert_eq!(last[499]["value"], "bad1499");
+}
+
 #[tokio::test]
 async fn csv_stringify_node_objects() {
     let reg = NodeRegistry::with_builtins();
@@ -298,6 +657,74 @@ async fn csv_stringify_node_objects() {
     assert_eq!(lines[2], "25,Bob");
 }
 
+#[tokio::test]
+async fn csv_stringify_headers_keep_first_seen_order() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("csv_stringify").unwrap();
+    let ctx = ctx_with(vec![(
+        "rows",
+        serde_json::json!([
+            {"name": "Alice"},
+            {"age": 25, "name": "Bob"}
+        ]),
+    )]);
+
+    let config = serde_json::json!({ "source_key": "rows", "output_key": "csv" });
+    let result = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(result["csv"], "name,age\nAlice,\nBob,25\n");
+
+    let config = serde_json::json!({
+        "source_key": "rows",
+        "output_key": "csv",
+        "sort_headers": true
+    });
+    let result = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(result["csv"], "age,name\n,Alice\n25,Bob\n");
+}
+
+#[tokio::test]
+async fn csv_stringify_explicit_columns_and_crlf() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("csv_stringify").unwrap();
+    let ctx = ctx_with(vec![
+        (
+            "rows",
+            serde_json::json!([
+                {"name": "Alice", "email": "alice@example.com", "age": 30},
+                {"name": "Bob", "age": 25}
+            ]),
+        ),
+        ("empty", serde_json::json!([])),
+    ]);
+
+    let config = serde_json::json!({
+        "source_key": "rows",
+        "output_key": "csv",
+        "columns": ["name", "email"],
+        "line_terminator": "\r\n"
+    });
+    let result = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(
+        result["csv"],
+        "name,email\r\nAlice,alice@example.com\r\nBob,\r\n"
+    );
+
+    let config = serde_json::json!({
+        "source_key": "empty",
+        "output_key": "csv",
+        "columns": ["name", "email"]
+    });
+    let result = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(result["csv"], "name,email\n");
+
+    let config = serde_json::json!({
+        "source_key": "rows",
+        "output_key": "csv",
+        "line_terminator": ";"
+    });
+    assert!(node.execute(&config, &ctx).await.is_err());
+}
+
 #[tokio::test]
 async fn csv_stringify_node_arrays() {
     let reg = NodeRegistry::with_builtins();
@@ -445,6 +872,225 @@ async fn data_filter_exists() {
     assert_eq!(filtered.len(), 1); // only "a" has non-null email
 }
 
+fn product_ctx() -> Context {
+    ctx_with(vec![(
+        "items",
+        serde_json::json!([
+            {"name": "a", "status": "active", "price": 5, "sku": "A-1"},
+            {"name": "b", "status": "active", "price": 15},
+            {"name": "c", "status": "inactive", "price": 25, "sku": "C-3"},
+            {"name": "d", "status": "inactive", "price": 8}
+        ]),
+    )])
+}
+
+fn filtered_names(result: &NodeOutput, key: &str) -> Vec<String> {
+    result[key]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn data_filter_conditions_all() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("data_filter").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "items",
+        "output_key": "filtered",
+        "conditions": [
+            {"field": "status", "op": "eq", "value": "active"},
+            {"field": "price", "op": "gt", "value": 10}
+        ]
+    });
+
+    let result = node.execute(&config, &product_ctx()).await.unwrap();
+    assert_eq!(filtered_names(&result, "filtered"), ["b"]);
+    assert_eq!(result.get("filtered_count").unwrap(), 1);
+}
+
+#[tokio::test]
+async fn data_filter_conditions_any() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("data_filter").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "items",
+        "output_key": "filtered",
+        "match": "any",
+        "conditions": [
+            {"field": "status", "op": "eq", "value": "active"},
+            {"field": "price", "op": "gte", "value": 25}
+        ]
+    });
+
+    let result = node.execute(&config, &product_ctx()).await.unwrap();
+    assert_eq!(filtered_names(&result, "filtered"), ["a", "b", "c"]);
+}
+
+#[tokio::test]
+async fn data_filter_conditions_mix_existence_and_comparison() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("data_filter").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "items",
+        "output_key": "with_sku",
+        "conditions": [
+            {"field": "sku", "op": "exists"},
+            {"field": "price", "op": "lt", "value": 20}
+        ]
+    });
+    let result = node.execute(&config, &product_ctx()).await.unwrap();
+    assert_eq!(filtered_names(&result, "with_sku"), ["a"]);
+
+    let config = serde_json::json!({
+        "source_key": "items",
+        "output_key": "unlisted_or_cheap",
+        "match": "any",
+        "conditions": [
+            {"field": "sku", "op": "not_exists"},
+            {"field": "price", "op": "lte", "value": 5}
+        ]
+    });
+    let result = node.execute(&config, &product_ctx()).await.unwrap();
+    assert_eq!(
+        filtered_names(&result, "unlisted_or_cheap"),
+        ["a", "b", "d"]
+    );
+}
+
+#[tokio::test]
+async fn data_filter_conditions_reject_invalid_config() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("data_filter").unwrap();
+
+    for (config, message) in [
+        (
+            serde_json::json!({
+                "source_key": "items",
+                "output_key": "filtered",
+                "field": "status",
+                "op": "eq",
+                "value": "active",
+                "conditions": [{"field": "price", "op": "gt", "value": 10}]
+            }),
+            "not both",
+        ),
+        (
+            serde_json::json!({
+                "source_key": "items",
+                "output_key": "filtered",
+                "conditions": []
+            }),
+            "non-empty array",
+        ),
+        (
+            serde_json::json!({
+                "source_key": "items",
+                "output_key": "filtered",
+                "match": "none",
+                "conditions": [{"field": "price", "op": "gt", "value": 10}]
+            }),
+            "'all' or 'any'",
+        ),
+    ] {
+        let err = node.execute(&config, &product_ctx()).await.unwrap_err();
+        assert!(err.to_string().contains(message), "{}", err);
+    }
+}
+
+#[tokio::test]
+async fn data_filter_in_and_not_in() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("data_filter").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "items",
+        "output_key": "picked",
+        "field": "name",
+        "op": "in",
+        "value": ["a", "c", "z"]
+    });
+    let result = node.execute(&config, &product_ctx()).await.unwrap();
+    assert_eq!(filtered_names(&result, "picked"), ["a", "c"]);
+
+    let config = serde_json::json!({
+        "source_key": "items",
+        "output_key": "rest",
+        "field": "price",
+        "op": "not_in",
+        "value": [5, 25]
+    });
+    let result = node.execute(&config, &product_ctx()).await.unwrap();
+    assert_eq!(filtered_names(&result, "rest"), ["b", "d"]);
+
+    let config = serde_json::json!({
+        "source_key": "items",
+        "output_key": "picked",
+        "field": "name",
+        "op": "in",
+        "value": "a"
+    });
+    let err = node.execute(&config, &product_ctx()).await.unwrap_err();
+    assert!(err.to_string().contains("requires an array"), "{}", err);
+}
+
+#[tokio::test]
+async fn data_filter_starts_with_and_ends_with() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("data_filter").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "items",
+        "output_key": "prefixed",
+        "field": "status",
+        "op": "starts_with",
+        "value": "in"
+    });
+    let result = node.execute(&config, &product_ctx()).await.unwrap();
+    assert_eq!(filtered_names(&result, "prefixed"), ["c", "d"]);
+
+    let config = serde_json::json!({
+        "source_key": "items",
+        "output_key": "suffixed",
+        "field": "sku",
+        "op": "ends_with",
+        "value": "-3"
+    });
+    let result = node.execute(&config, &product_ctx()).await.unwrap();
+    assert_eq!(filtered_names(&result, "suffixed"), ["c"]);
+}
+
+#[tokio::test]
+async fn data_filter_regex() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("data_filter").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "items",
+        "output_key": "matched",
+        "field": "sku",
+        "op": "regex",
+        "value": "^[A-Z]-\\d$"
+    });
+    let result = node.execute(&config, &product_ctx()).await.unwrap();
+    assert_eq!(filtered_names(&result, "matched"), ["a", "c"]);
+
+    let config = serde_json::json!({
+        "source_key": "items",
+        "output_key": "matched",
+        "field": "sku",
+        "op": "regex",
+        "value": "("
+    });
+    let err = node.execute(&config, &product_ctx()).await.unwrap_err();
+    assert!(err.to_string().contains("invalid regex"), "{}", err);
+}
+
 // --- BatchNode ---
 
 #[tokio::test]
@@ -526,6 +1172,301 @@ async fn deduplicate_node_by_field() {
     assert_eq!(unique.len(), 2);
 }
 
+#[tokio::test]
+async fn zip_node_combines_arrays_into_objects() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("zip").unwrap();
+
+    let config = serde_json::json!({
+        "sources": { "id": "ids", "info": "enrichments" },
+        "output_key": "pairs"
+    });
+    let ctx = ctx_with(vec![
+        ("ids", serde_json::json!([1, 2, 3])),
+        (
+            "enrichments",
+            serde_json::json!([{"name": "a"}, {"name": "b"}]),
+        ),
+    ]);
+
+    let result = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(
+        result["pairs"],
+        serde_json::json!([
+            {"id": 1, "info": {"name": "a"}},
+            {"id": 2, "info": {"name": "b"}}
+        ])
+    );
+    assert_eq!(result["pairs_count"], 2);
+}
+
+#[tokio::test]
+async fn zip_node_merge_pad_and_tuple_modes() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("zip").unwrap();
+    let ctx = ctx_with(vec![
+        ("users", serde_json::json!([{"id": 1}, {"id": 2}])),
+        ("scores", serde_json::json!([{"score": 9}])),
+    ]);
+
+    let merged = node
+        .execute(
+            &serde_json::json!({
+                "sources": ["users", "scores"],
+                "mode": "merge",
+                "mismatch": "pad",
+                "output_key": "rows"
+            }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        merged["rows"],
+        serde_json::json!([{"id": 1, "score": 9}, {"id": 2}])
+    );
+
+    let tuples = node
+        .execute(
+            &serde_json::json!({
+                "sources": ["users", "scores"],
+                "mode": "tuple",
+                "mismatch": "pad",
+                "pad_value": 0,
+                "output_key": "rows"
+            }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        tuples["rows"],
+        serde_json::json!([[{"id": 1}, {"score": 9}], [{"id": 2}, 0]])
+    );
+
+    let err = node
+        .execute(
+            &serde_json::json!({
+                "sources": ["users", "scores"],
+                "mismatch": "error",
+                "output_key": "rows"
+            }),
+            &ctx,
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("users=2, scores=1"), "{err}");
+}
+
+// --- JsonMergeNode ---
+
+#[tokio::test]
+async fn json_merge_deep_merges_nested_objects_left_to_right() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("json_merge").unwrap();
+    let ctx = ctx_with(vec![
+        (
+            "defaults",
+            serde_json::json!({"retries": 1, "http": {"timeout": 10, "headers": {"a": "1"}}, "tags": ["x"]}),
+        ),
+        (
+            "overrides",
+            serde_json::json!({"retries": 3, "http": {"headers": {"b": "2"}}, "tags": ["y"]}),
+        ),
+    ]);
+
+    let config = serde_json::json!({
+        "source_keys": ["defaults", "overrides"],
+        "sources": [{"http": {"timeout": 30}}],
+        "output_key": "settings"
+    });
+    let result = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(
+        result["settings"],
+        serde_json::json!({
+            "retries": 3,
+            "http": {"timeout": 30, "headers": {"a": "1", "b": "2"}},
+            "tags": ["y"]
+        })
+    );
+
+    let concat = node
+        .execute(
+            &serde_json::json!({
+                "source_keys": ["defaults", "overrides"],
+                "array_strategy": "concat",
+                "output_key": "settings"
+            }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(concat["settings"]["tags"], serde_json::json!(["x", "y"]));
+}
+
+#[tokio::test]
+async fn json_merge_shallow_replaces_overlapping_keys() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("json_merge").unwrap();
+    let ctx = ctx_with(vec![(
+        "base",
+        serde_json::json!({"name": "a", "meta": {"x": 1, "y": 2}}),
+    )]);
+
+    let config = serde_json::json!({
+        "source_keys": ["base"],
+        "sources": [{"meta": {"z": 3}, "extra": true}],
+        "strategy": "shallow",
+        "output_key": "out"
+    });
+    let result = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(
+        result["out"],
+        serde_json::json!({"name": "a", "meta": {"z": 3}, "extra": true})
+    );
+
+    let err = node
+        .execute(
+            &serde_json::json!({ "sources": [{"a": 1}, [1, 2]], "output_key": "out" }),
+            &ctx,
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("sources[2] is not an object"),
+        "{err}"
+    );
+}
+
+// --- SortNode ---
+
+#[tokio::test]
+async fn sort_orders_objects_by_nested_number_descending_with_nulls_last() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("sort").unwrap();
+    let ctx = ctx_with(vec![(
+        "products",
+        serde_json::json!([
+            {"sku": "a", "stats": {"sales": 5}},
+            {"sku": "b", "stats": {}},
+            {"sku": "c", "stats": {"sales": 12.5}},
+            {"sku": "d", "stats": {"sales": null}},
+            {"sku": "e", "stats": {"sales": 7}}
+        ]),
+    )]);
+
+    let config = serde_json::json!({
+        "source_key": "products",
+        "key": "stats.sales",
+        "order": "desc",
+        "output_key": "ranked"
+    });
+    let result = node.execute(&config, &ctx).await.unwrap();
+    let skus: Vec<&str> = result["ranked"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["sku"].as_str().unwrap())
+        .collect();
+    assert_eq!(skus, vec!["c", "e", "a", "b", "d"]);
+}
+
+#[tokio::test]
+async fn sort_is_stable_and_sorts_scalars_without_key() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("sort").unwrap();
+    let ctx = ctx_with(vec![
+        (
+            "rows",
+            serde_json::json!([
+                {"id": 1, "group": "b"},
+                {"id": 2, "group": "a"},
+                {"id": 3, "group": "b"},
+                {"id": 4, "group": "a"}
+            ]),
+        ),
+        (
+            "mixed",
+            serde_json::json!(["pear", 3, null, true, "apple", 1]),
+        ),
+    ]);
+
+    let result = node
+        .execute(
+            &serde_json::json!({ "source_key": "rows", "key": "group", "output_key": "out" }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    let ids: Vec<u64> = result["out"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![2, 4, 1, 3]);
+
+    let result = node
+        .execute(
+            &serde_json::json!({ "source_key": "mixed", "output_key": "out" }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        result["out"],
+        serde_json::json!([true, 1, 3, "apple", "pear", null])
+    );
+}
+
+// --- FlattenNode ---
+
+#[tokio::test]
+async fn flatten_one_level_and_all_levels() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("flatten").unwrap();
+    let ctx = ctx_with(vec![("nested", serde_json::json!([[1, 2], [3, [4]], 5]))]);
+
+    let one = node
+        .execute(
+            &serde_json::json!({ "source_key": "nested", "output_key": "flat" }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(one["flat"], serde_json::json!([1, 2, 3, [4], 5]));
+    assert_eq!(one["flat_count"], 5);
+
+    let all = node
+        .execute(
+            &serde_json::json!({ "source_key": "nested", "depth": "all", "output_key": "flat" }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(all["flat"], serde_json::json!([1, 2, 3, 4, 5]));
+    assert_eq!(all["flat_count"], 5);
+}
+
+#[tokio::test]
+async fn flatten_rejects_non_array_source() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("flatten").unwrap();
+    let ctx = ctx_with(vec![("obj", serde_json::json!({"a": [1]}))]);
+
+    let err = node
+        .execute(
+            &serde_json::json!({ "source_key": "obj", "output_key": "flat" }),
+            &ctx,
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("Value at 'obj' is not an array"),
+        "{err}"
+    );
+}
+
 // --- IfNode ---
 
 #[tokio::test]
@@ -780,24 +1721,95 @@ async fn if_body_contains_node_no_match() {
         &serde_json::json!("notfound")
     );
     assert_eq!(
-        result.get("_contains_payload").unwrap(),
-        &serde_json::json!(false)
+        result.get("_contains_payload").unwrap(),
+        &serde_json::json!(false)
+    );
+}
+
+#[tokio::test]
+async fn if_body_contains_node_required_key_missing() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("if_body_contains").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "missing",
+        "pattern": "x",
+        "required": true
+    });
+
+    let result = node.execute(&config, &empty_ctx()).await;
+    assert!(result.is_err());
+}
+
+// --- AssertNode ---
+
+fn order_ctx() -> Context {
+    ctx_with(vec![
+        ("order_id", serde_json::json!("ORD-0042")),
+        ("total", serde_json::json!(99.5)),
+        ("items", serde_json::json!([{ "sku": "A" }, { "sku": "B" }])),
+        ("status", serde_json::json!("paid")),
+    ])
+}
+
+#[tokio::test]
+async fn assert_passes_when_all_conditions_hold() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("assert").unwrap();
+
+    let config = serde_json::json!({
+        "conditions": [
+            "ctx.status == 'paid'",
+            { "path": "ctx.order_id", "matches": "^ORD-\\d{4}$" },
+            { "path": "total", "min": 0, "max": 100 },
+            { "path": "items.0.sku", "equals": "A" },
+            { "path": "refund", "exists": false }
+        ]
+    });
+    let result = node.execute(&config, &order_ctx()).await.unwrap();
+
+    assert_eq!(result["assert_passed"], serde_json::json!(true));
+    assert_eq!(result["assert_failures"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn assert_fails_naming_first_failed_assertion() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("assert").unwrap();
+
+    let config = serde_json::json!({
+        "conditions": [
+            { "path": "ctx.status", "equals": "paid" },
+            { "path": "ctx.total", "max": 50, "message": "order too large" },
+            { "path": "ctx.customer", "exists": true }
+        ]
+    });
+    let err = node.execute(&config, &order_ctx()).await.unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "assert: assertion 2 failed: order too large (ctx.total expected <= 50, got 99.5) (and 1 more)"
     );
 }
 
 #[tokio::test]
-async fn if_body_contains_node_required_key_missing() {
+async fn assert_routes_instead_of_failing() {
     let reg = NodeRegistry::with_builtins();
-    let node = reg.get("if_body_contains").unwrap();
+    let node = reg.get("assert").unwrap();
 
     let config = serde_json::json!({
-        "source_key": "missing",
-        "pattern": "x",
-        "required": true
+        "conditions": ["ctx.status == 'refunded'"],
+        "on_fail": "route",
+        "_step_name": "verify"
     });
+    let result = node.execute(&config, &order_ctx()).await.unwrap();
 
-    let result = node.execute(&config, &empty_ctx()).await;
-    assert!(result.is_err());
+    assert_eq!(result["_route_verify"], serde_json::json!("fail"));
+    assert_eq!(result["assert_passed"], serde_json::json!(false));
+    assert_eq!(
+        result["assert_failures"][0],
+        serde_json::json!("assertion 1 failed: `ctx.status == 'refunded'` is false")
+    );
 }
 
 // --- SwitchNode ---
@@ -914,6 +1926,162 @@ async fn json_extract_path_node_required_missing_fails() {
     assert!(result.is_err());
 }
 
+// --- TransformJsonNode ---
+
+fn purchase_ctx() -> Context {
+    ctx_with(vec![(
+        "order",
+        serde_json::json!({
+            "id": "A-100",
+            "customer": { "first": "Ada", "last": "Lovelace" },
+            "items": [
+                { "sku": "pen", "price": 2.5, "qty": 4 },
+                { "sku": "book", "price": 30, "qty": 1 },
+                { "sku": "lamp", "price": 45, "qty": 2 }
+            ]
+        }),
+    )])
+}
+
+#[tokio::test]
+async fn transform_json_projection_with_computed_field_and_filter() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("transform_json").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "order",
+        "output_key": "summary",
+        "expression": r#"{
+            "id": id,
+            "customer": customer.first & " " & customer.last,
+            "total": $sum(items.(price * qty)),
+            "large": items[price >= 30].sku,
+            "first_sku": items[0].sku,
+            "last_sku": items[-1].sku,
+            "missing": customer.email
+        }"#
+    });
+
+    let result = node.execute(&config, &purchase_ctx()).await.unwrap();
+    assert_eq!(
+        result["summary"],
+        serde_json::json!({
+            "id": "A-100",
+            "customer": "Ada Lovelace",
+            "total": 130,
+            "large": ["book", "lamp"],
+            "first_sku": "pen",
+            "last_sku": "lamp"
+        })
+    );
+}
+
+#[tokio::test]
+async fn transform_json_map_and_filter_lambdas() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("transform_json").unwrap();
+
+    // No source_key: the expression sees the whole context.
+    let config = serde_json::json!({
+        "output_key": "lines",
+        "expression": r#"$map(
+            $filter(order.items, function($i) { $i.qty > 1 }),
+            function($i, $n) {
+                { "line": $n + 1, "sku": $uppercase($i.sku), "subtotal": $i.price * $i.qty,
+                  "bulk": $i.qty >= 4 ? true : false }
+            }
+        )"#
+    });
+
+    let result = node.execute(&config, &purchase_ctx()).await.unwrap();
+    assert_eq!(
+        result["lines"],
+        serde_json::json!([
+            { "line": 1, "sku": "PEN", "subtotal": 10, "bulk": true },
+            { "line": 2, "sku": "LAMP", "subtotal": 90, "bulk": false }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn transform_json_reports_parse_errors_and_unknown_functions() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("transform_json").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "order",
+        "output_key": "out",
+        "expression": "items[price > 10"
+    });
+    let err = node.execute(&config, &purchase_ctx()).await.unwrap_err();
+    assert!(err.to_string().contains("expected ']'"), "{}", err);
+
+    let config = serde_json::json!({
+        "source_key": "order",
+        "output_key": "out",
+        "expression": "$reverse(items)"
+    });
+    let err = node.execute(&config, &purchase_ctx()).await.unwrap_err();
+    assert!(
+        err.to_string().contains("unknown function $reverse"),
+        "{}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn json_query_projection() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("json_query").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "order",
+        "output_key": "lines",
+        "expression": "{ id: id, skus: items[*].sku, count: length(items) }"
+    });
+
+    let result = node.execute(&config, &purchase_ctx()).await.unwrap();
+    assert_eq!(
+        result["lines"],
+        serde_json::json!({
+            "id": "A-100",
+            "skus": ["pen", "book", "lamp"],
+            "count": 3
+        })
+    );
+}
+
+#[tokio::test]
+async fn json_query_filter_and_whole_context() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("json_query").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "order",
+        "output_key": "expensive",
+        "expression": "items[?price > `10`].sku"
+    });
+    let result = node.execute(&config, &purchase_ctx()).await.unwrap();
+    assert_eq!(result["expensive"], serde_json::json!(["book", "lamp"]));
+
+    // No source_key: the expression sees the whole context, and a missing
+    // field yields null.
+    let config = serde_json::json!({
+        "output_key": "email",
+        "expression": "order.customer.email"
+    });
+    let result = node.execute(&config, &purchase_ctx()).await.unwrap();
+    assert!(result["email"].is_null());
+
+    let config = serde_json::json!({
+        "source_key": "order",
+        "output_key": "bad",
+        "expression": "items[?price >"
+    });
+    let err = node.execute(&config, &purchase_ctx()).await.unwrap_err();
+    assert!(err.to_string().contains("json_query: invalid expression"));
+}
+
 // --- HashNode ---
 
 #[tokio::test]
@@ -931,6 +2099,95 @@ async fn hash_node_sha256() {
     assert_eq!(hash.len(), 64); // SHA-256 hex is 64 chars
 }
 
+// --- HmacNode ---
+
+#[tokio::test]
+async fn hmac_node_matches_rfc_test_vectors() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("hmac").unwrap();
+    // RFC 4231 / RFC 2202 test case 2.
+    let ctx = ctx_with(vec![(
+        "body",
+        serde_json::json!("what do ya want for nothing?"),
+    )]);
+
+    for (algorithm, expected) in [
+        (
+            "sha256",
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+        ),
+        ("sha1", "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"),
+        (
+            "sha512",
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+             9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+        ),
+    ] {
+        let config = serde_json::json!({
+            "source_key": "body",
+            "secret": "Jefe",
+            "algorithm": algorithm
+        });
+        let result = node.execute(&config, &ctx).await.unwrap();
+        assert_eq!(result["hmac"], expected, "{algorithm}");
+    }
+
+    let base64 = node
+        .execute(
+            &serde_json::json!({ "source_key": "body", "secret": "Jefe", "encoding": "base64" }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        base64["hmac"],
+        "W9zBRr9gdU5qBCQmCJV1x1oAPwidJzmDnexYuWTsOEM="
+    );
+}
+
+#[tokio::test]
+async fn hmac_node_verifies_expected_signature() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("hmac").unwrap();
+    let ctx = ctx_with(vec![
+        ("body", serde_json::json!("what do ya want for nothing?")),
+        (
+            "signature",
+            serde_json::json!(
+                "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+            ),
+        ),
+    ]);
+
+    let good = node
+        .execute(
+            &serde_json::json!({
+                "source_key": "body",
+                "secret": "Jefe",
+                "expected": "${ctx.signature}",
+                "output_key": "sig"
+            }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(good["sig_valid"], true);
+
+    let bad = node
+        .execute(
+            &serde_json::json!({
+                "source_key": "body",
+                "secret": "wrong",
+                "expected": "${ctx.signature}",
+                "output_key": "sig"
+            }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(bad["sig_valid"], false);
+}
+
 // --- DelayNode ---
 
 #[tokio::test]
@@ -947,6 +2204,146 @@ async fn delay_node() {
     assert!(result.contains_key("delay_seconds"));
 }
 
+// --- WaitUntilNode ---
+
+#[tokio::test(start_paused = true)]
+async fn wait_until_sleeps_until_near_future_timestamp() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("wait_until").unwrap();
+
+    let target = chrono::Utc::now() + chrono::Duration::milliseconds(300);
+    let config = serde_json::json!({ "at": target.to_rfc3339() });
+    let start = tokio::time::Instant::now();
+    let result = node.execute(&config, &empty_ctx()).await.unwrap();
+    let elapsed = start.elapsed();
+    assert_eq!(result["wait_skipped"], false);
+
+    let waited = result["waited_seconds"].as_f64().unwrap();
+    assert!(waited > 0.2 && waited <= 0.3, "waited {waited}s");
+    assert!(elapsed.as_millis() >= 200);
+}
+
+#[tokio::test(start_paused = true)]
+async fn wait_until_interpolates_its_target() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("wait_until").unwrap();
+
+    let deadline = (chrono::Utc::now() + chrono::Duration::minutes(10)).to_rfc3339();
+    let ctx = ctx_with(vec![("deadline", serde_json::json!(deadline))]);
+    let start = tokio::time::Instant::now();
+    let result = node
+        .execute(&serde_json::json!({ "at": "${ctx.deadline}" }), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(result["wait_skipped"], false);
+    assert!(start.elapsed().as_secs() >= 590);
+    assert!(result["waited_seconds"].as_f64().unwrap() > 590.0);
+
+    let ctx = ctx_with(vec![("every", serde_json::json!("* * * * * *"))]);
+    let result = node
+        .execute(&serde_json::json!({ "cron": "${ctx.every}" }), &ctx)
+        .await
+        .unwrap();
+    assert!(result["waited_seconds"].as_f64().unwrap() <= 1.0);
+}
+
+#[tokio::test]
+async fn wait_until_past_timestamp_returns_immediately() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("wait_until").unwrap();
+
+    let config = serde_json::json!({ "at": "2000-01-01T00:00:00Z" });
+    let result = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(result["waited_seconds"], 0.0);
+    assert_eq!(result["wait_skipped"], true);
+}
+
+#[tokio::test(start_paused = true)]
+async fn wait_until_time_of_day_waits_for_next_occurrence() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("wait_until").unwrap();
+
+    let target = chrono::Utc::now() + chrono::Duration::seconds(1);
+    let time = target.format("%H:%M:%S").to_string();
+    let config = serde_json::json!({ "time": time });
+    let result = node.execute(&config, &empty_ctx()).await.unwrap();
+
+    assert!(result["waited_seconds"].as_f64().unwrap() <= 1.0);
+    assert_eq!(result["wait_skipped"], false);
+    assert!(result["wait_until"].as_str().unwrap().contains(&time));
+}
+
+#[tokio::test]
+async fn wait_until_time_of_day_in_the_past_rolls_to_tomorrow() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("wait_until").unwrap();
+
+    // An hour ago is ~23h away; max_wait rejects it instead of sleeping.
+    let earlier = chrono::Utc::now() - chrono::Duration::hours(1);
+    let config = serde_json::json!({ "time": earlier.format("%H:%M").to_string(), "max_wait": 60 });
+    let err = node.execute(&config, &empty_ctx()).await.unwrap_err();
+    assert!(err.to_string().contains("max_wait"));
+
+    let config = serde_json::json!({ "time": "25:00" });
+    let err = node.execute(&config, &empty_ctx()).await.unwrap_err();
+    assert!(err.to_string().contains("HH:MM"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn wait_until_time_of_day_allows_a_day_by_default() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("wait_until").unwrap();
+
+    let earlier = chrono::Utc::now() - chrono::Duration::hours(1);
+    let config = serde_json::json!({ "time": earlier.format("%H:%M").to_string() });
+    let result = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert!(result["waited_seconds"].as_f64().unwrap() > 3600.0);
+
+    // `at` keeps the one-hour default and says how to raise it.
+    let target = chrono::Utc::now() + chrono::Duration::hours(2);
+    let config = serde_json::json!({ "at": target.to_rfc3339() });
+    let err = node.execute(&config, &empty_ctx()).await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("max_wait of 3600s; set a larger 'max_wait'"),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn wait_until_rejects_target_beyond_max_wait() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("wait_until").unwrap();
+
+    let target = chrono::Utc::now() + chrono::Duration::hours(2);
+    let config = serde_json::json!({ "at": target.to_rfc3339(), "max_wait": 60 });
+    let err = node.execute(&config, &empty_ctx()).await.unwrap_err();
+    assert!(err.to_string().contains("max_wait"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn wait_until_cron_waits_for_next_match() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("wait_until").unwrap();
+
+    // Every second: the next match is at most one second away.
+    let config = serde_json::json!({ "cron": "* * * * * *" });
+    let result = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert!(result["waited_seconds"].as_f64().unwrap() <= 1.0);
+}
+
+#[tokio::test]
+async fn sleep_until_is_an_alias_of_wait_until() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("sleep_until").unwrap();
+    assert_eq!(node.node_type(), "sleep_until");
+
+    let config = serde_json::json!({ "at": "2000-01-01T00:00:00Z" });
+    let result = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(result["wait_skipped"], true);
+    assert_eq!(result["wait_until"], "2000-01-01T00:00:00+00:00");
+}
+
 // --- TemplateRenderNode ---
 
 #[tokio::test]
@@ -967,6 +2364,101 @@ async fn template_render_node() {
     );
 }
 
+// --- RegexNode ---
+
+#[tokio::test]
+async fn regex_match_returns_named_and_indexed_groups() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("regex").unwrap();
+    let ctx = ctx_with(vec![(
+        "log",
+        serde_json::json!("ORDER-2024-17 shipped; order-2025-3 pending"),
+    )]);
+
+    let first = node
+        .execute(
+            &serde_json::json!({
+                "source_key": "log",
+                "pattern": r"order-(?P<year>\d{4})-(\d+)",
+                "case_insensitive": true,
+                "output_key": "order"
+            }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(first["order_matched"], true);
+    assert_eq!(first["order"]["match"], "ORDER-2024-17");
+    assert_eq!(first["order"]["groups"], serde_json::json!(["2024", "17"]));
+    assert_eq!(first["order"]["named"], serde_json::json!({"year": "2024"}));
+
+    let all = node
+        .execute(
+            &serde_json::json!({
+                "source_key": "log",
+                "pattern": r"order-(?P<year>\d{4})-(\d+)",
+                "case_insensitive": true,
+                "all": true,
+                "output_key": "orders"
+            }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(all["orders_count"], 2);
+    assert_eq!(all["orders"][1]["named"]["year"], "2025");
+    assert_eq!(all["orders"][1]["start"], 23);
+}
+
+#[tokio::test]
+async fn regex_replace_is_global_and_split_splits() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("regex").unwrap();
+    let ctx = ctx_with(vec![("dates", serde_json::json!("2024-01-31, 2025-12-01"))]);
+
+    let replaced = node
+        .execute(
+            &serde_json::json!({
+                "source_key": "dates",
+                "mode": "replace",
+                "pattern": r"(?P<y>\d{4})-(?P<m>\d{2})-(?P<d>\d{2})",
+                "replacement": "${d}/${m}/${y}",
+                "output_key": "eu"
+            }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(replaced["eu"], "31/01/2024, 01/12/2025");
+    assert_eq!(replaced["eu_count"], 2);
+
+    let parts = node
+        .execute(
+            &serde_json::json!({
+                "source_key": "dates",
+                "mode": "split",
+                "pattern": r",\s*",
+                "output_key": "parts"
+            }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        parts["parts"],
+        serde_json::json!(["2024-01-31", "2025-12-01"])
+    );
+
+    let err = node
+        .execute(
+            &serde_json::json!({ "source_key": "dates", "pattern": "(", "output_key": "x" }),
+            &ctx,
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("invalid pattern '('"), "{err}");
+}
+
 // --- ValidateSchemaNode ---
 
 #[tokio::test]
@@ -1292,3 +2784,78 @@ async fn data_transform_array() {
     assert_eq!(arr[0].get("n").unwrap(), "Alice");
     assert_eq!(arr[1].get("n").unwrap(), "Bob");
 }
+
+// --- MapNode ---
+
+#[tokio::test]
+async fn map_numbers_to_squares() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("map").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "numbers",
+        "output_key": "squares",
+        "expression": "item * item"
+    });
+    let ctx = ctx_with(vec![("numbers", serde_json::json!([1, 2, 3, 4]))]);
+
+    let result = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(result["squares"], serde_json::json!([1, 4, 9, 16]));
+    assert_eq!(result["squares_count"], 4);
+}
+
+#[tokio::test]
+async fn map_objects_to_derived_field() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("map").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "users",
+        "output_key": "labels",
+        "source": "return { label = index .. '. ' .. item.first .. ' ' .. item.last, prefix = ctx.prefix }"
+    });
+    let ctx = ctx_with(vec![
+        (
+            "users",
+            serde_json::json!([
+                {"first": "Ada", "last": "Lovelace"},
+                {"first": "Alan", "last": "Turing"}
+            ]),
+        ),
+        ("prefix", serde_json::json!("user")),
+    ]);
+
+    let result = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(
+        result["labels"],
+        serde_json::json!([
+            {"label": "1. Ada Lovelace", "prefix": "user"},
+            {"label": "2. Alan Turing", "prefix": "user"}
+        ])
+    );
+}
+
+#[tokio::test]
+async fn map_reports_failing_index() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("map").unwrap();
+
+    let config = serde_json::json!({
+        "source_key": "values",
+        "output_key": "doubled",
+        "expression": "item * 2"
+    });
+    let ctx = ctx_with(vec![("values", serde_json::json!([1, {"nested": true}]))]);
+
+    let err = node.execute(&config, &ctx).await.unwrap_err();
+    assert!(err.to_string().contains("index 2"), "{}", err);
+
+    let config = serde_json::json!({
+        "source_key": "values",
+        "output_key": "doubled",
+        "expression": "item * 2",
+        "source": "return item"
+    });
+    let err = node.execute(&config, &ctx).await.unwrap_err();
+    assert!(err.to_string().contains("not both"), "{}", err);
+}
@@ -0,0 +1,217 @@
+//! Tests for the inline `parallel` node.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ironflow::engine::executor::WorkflowEngine;
+use ironflow::engine::types::*;
+use ironflow::lua::runtime::LuaRuntime;
+use ironflow::nodes::NodeRegistry;
+use ironflow::storage::StateStore;
+use ironflow::storage::null_store::NullStateStore;
+
+async fn run_flow(source: &str) -> RunInfo {
+    let reg = Arc::new(NodeRegistry::with_builtins());
+    let flow = LuaRuntime::load_flow_from_string(source, &reg).unwrap();
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(reg, store.clone(), None);
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+    store.get_run_info(&run_id).await.unwrap()
+}
+
+#[tokio::test]
+async fn parallel_branches_merge_outputs() {
+    let info = run_flow(
+        r#"
+        local flow = Flow.new("fan_out")
+        flow:step("both", nodes.parallel({
+            branches = {
+                nodes.code({ source = "return { users = 3 }" }),
+                nodes.code({ source = "return { orders = 7 }" }),
+            }
+        }))
+        flow:step("after", nodes.template_render({
+            template = "${ctx.users}/${ctx.orders}",
+            output_key = "summary"
+        })):depends_on("both")
+        return flow
+    "#,
+    )
+    .await;
+
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(info.ctx["users"], 3);
+    assert_eq!(info.ctx["orders"], 7);
+    assert_eq!(info.ctx["summary"], "3/7");
+    assert_eq!(info.ctx["parallel_succeeded"], 2);
+    assert_eq!(info.ctx["parallel_failed"], 0);
+}
+
+#[tokio::test]
+async fn parallel_all_mode_fails_when_one_branch_fails() {
+    let info = run_flow(
+        r#"
+        local flow = Flow.new("fan_out_fail")
+        flow:step("both", nodes.parallel({
+            branches = {
+                nodes.code({ source = "return { ok = true }" }),
+                nodes.read_file({ path = "/nonexistent_parallel_branch" }),
+            }
+        }))
+        return flow
+    "#,
+    )
+    .await;
+
+    assert_eq!(info.status, RunStatus::Failed);
+    let error = info.tasks["both"].error.as_ref().unwrap();
+    assert!(
+        error.contains("branch 2 (read_file)"),
+        "unexpected: {error}"
+    );
+}
+
+#[tokio::test]
+async fn parallel_any_mode_records_branch_errors() {
+    let info = run_flow(
+        r#"
+        local flow = Flow.new("fan_out_any")
+        flow:step("both", nodes.parallel({
+            mode = "any",
+            output_key = "fan",
+            branches = {
+                nodes.read_file({ path = "/nonexistent_parallel_branch" }),
+                nodes.code({ source = "return { ok = true }" }),
+            }
+        }))
+        return flow
+    "#,
+    )
+    .await;
+
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(info.ctx["ok"], true);
+    assert_eq!(info.ctx["fan_succeeded"], 1);
+    assert_eq!(info.ctx["fan_failed"], 1);
+    assert_eq!(info.ctx["fan_errors"][0]["branch"], 1);
+    assert_eq!(info.ctx["fan_errors"][0]["node_type"], "read_file");
+}
+
+#[tokio::test]
+async fn parallel_conflict_policies() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("parallel").unwrap();
+    let branches = serde_json::json!([
+        { "_node_type": "code", "source": "return { value = 'first' }" },
+        { "_node_type": "code", "source": "return { value = 'second' }" }
+    ]);
+
+    let last = node
+        .execute(
+            &serde_json::json!({ "branches": branches }),
+            &Context::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(last["value"], "second");
+
+    let first = node
+        .execute(
+            &serde_json::json!({ "branches": branches, "on_conflict": "first" }),
+            &Context::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(first["value"], "first");
+
+    let err = node
+        .execute(
+            &serde_json::json!({ "branches": branches, "on_conflict": "error" }),
+            &Context::new(),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("'value'"), "unexpected: {err}");
+}
+
+#[tokio::test]
+async fn parallel_node_and_config_branches_collect_indexed_results() {
+    let info = run_flow(
+        r#"
+        local flow = Flow.new("fan_out_results")
+        flow:step("fan", nodes.parallel({
+            max_concurrency = 1,
+            output_key = "fan",
+            branches = {
+                { node = "code", config = { source = "return { a = 1 }" } },
+                nodes.code({ source = "return { b = 2 }" }),
+                { node = "code", config = { source = "return { c = 3 }" } },
+            }
+        }))
+        return flow
+    "#,
+    )
+    .await;
+
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(
+        info.ctx["fan_results"],
+        serde_json::json!([{ "a": 1 }, { "b": 2 }, { "c": 3 }])
+    );
+    assert_eq!(info.ctx["a"], 1);
+    assert_eq!(info.ctx["c"], 3);
+}
+
+#[tokio::test]
+async fn parallel_fail_fast_aborts_remaining_branches() {
+    let started = std::time::Instant::now();
+    let info = run_flow(
+        r#"
+        local flow = Flow.new("fan_out_fail_fast")
+        flow:step("fan", nodes.parallel({
+            branches = {
+                nodes.delay({ seconds = 30 }),
+                nodes.read_file({ path = "/nonexistent_parallel_branch" }),
+            }
+        }))
+        return flow
+    "#,
+    )
+    .await;
+
+    assert_eq!(info.status, RunStatus::Failed);
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+    let error = info.tasks["fan"].error.as_ref().unwrap();
+    assert!(
+        error.contains("branch 2 (read_file)"),
+        "unexpected: {error}"
+    );
+}
+
+#[tokio::test]
+async fn parallel_without_fail_fast_reports_every_failed_branch() {
+    let info = run_flow(
+        r#"
+        local flow = Flow.new("fan_out_collect")
+        flow:step("fan", nodes.parallel({
+            fail_fast = false,
+            branches = {
+                nodes.read_file({ path = "/nonexistent_parallel_a" }),
+                nodes.code({ source = "return { ok = true }" }),
+                nodes.read_file({ path = "/nonexistent_parallel_b" }),
+            }
+        }))
+        return flow
+    "#,
+    )
+    .await;
+
+    assert_eq!(info.status, RunStatus::Failed);
+    let error = info.tasks["fan"].error.as_ref().unwrap();
+    assert!(
+        error.contains("2 of 3 branches failed"),
+        "unexpected: {error}"
+    );
+    assert!(error.contains("\"branch\":1"), "unexpected: {error}");
+    assert!(error.contains("\"branch\":3"), "unexpected: {error}");
+}
@@ -306,6 +306,68 @@ async fn pdf_metadata_extracts_key_fields() {
     assert!(meta.get("pages").unwrap().as_u64().unwrap() > 0);
 }
 
+#[tokio::test]
+async fn markdown_to_pdf_renders_heading_and_paragraph() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("markdown_to_pdf").unwrap();
+    let dir = tempdir().unwrap();
+    let output_path = dir.path().join("docs/guide.pdf");
+
+    let config = serde_json::json!({
+        "source_key": "doc",
+        "output_path": output_path.to_string_lossy(),
+        "css": "h1 { color: #336699; font-size: 28px }"
+    });
+    let ctx = ctx_with(vec![(
+        "doc",
+        serde_json::json!("# Release Notes\n\nThis release adds **markdown** to PDF rendering."),
+    )]);
+
+    let result = node.execute(&config, &ctx).await.unwrap();
+    let page_count = result["markdown_to_pdf_page_count"].as_u64().unwrap();
+    assert!(page_count > 0);
+    assert_eq!(result["markdown_to_pdf_success"], true);
+
+    let path = result["markdown_to_pdf_path"].as_str().unwrap();
+    let pdf = lopdf::Document::load(path).unwrap();
+    assert_eq!(pdf.get_pages().len() as u64, page_count);
+    let text = pdf.extract_text(&[1]).unwrap();
+    assert!(text.contains("Release Notes"), "{}", text);
+    assert!(text.contains("markdown"), "{}", text);
+}
+
+#[tokio::test]
+async fn markdown_to_pdf_flows_onto_new_pages() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("markdown_to_pdf").unwrap();
+    let dir = tempdir().unwrap();
+    let output_path = dir.path().join("long.pdf");
+
+    let paragraph = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. ".repeat(20);
+    let markdown = (1..=12)
+        .map(|i| {
+            format!(
+                "## Section {}\n\n{}\n\n```\nlet x = {};\n```\n",
+                i, paragraph, i
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let config = serde_json::json!({
+        "content": markdown,
+        "output_path": output_path.to_string_lossy(),
+        "output_key": "doc",
+        "page_size": "Letter",
+        "margin": { "top": 72, "bottom": 72 }
+    });
+
+    let result = node.execute(&config, &empty_ctx()).await.unwrap();
+    let page_count = result["doc_page_count"].as_u64().unwrap();
+    assert!(page_count > 1, "expected several pages, got {}", page_count);
+    let pdf = lopdf::Document::load(&output_path).unwrap();
+    assert_eq!(pdf.get_pages().len() as u64, page_count);
+}
+
 #[tokio::test]
 async fn image_rotate_generates_rotated_file() {
     let reg = NodeRegistry::with_builtins();
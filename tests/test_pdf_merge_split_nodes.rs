@@ -150,6 +150,112 @@ async fn pdf_merge_missing_file_error() {
     assert!(err.contains("failed to load"), "Error: {}", err);
 }
 
+#[tokio::test]
+async fn pdf_merge_sources_rebuilds_a_single_page_tree() {
+    use ironflow::nodes::NodeRegistry;
+
+    let registry = NodeRegistry::with_builtins();
+    let node = registry.get("pdf_merge").expect("pdf_merge not registered");
+    let metadata = registry.get("pdf_metadata").unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let pdf1 = dir.path().join("a.pdf");
+    let pdf2 = dir.path().join("b.pdf");
+    let output = dir.path().join("out/merged.pdf");
+    create_test_pdf(&pdf1);
+    create_multi_page_pdf(&pdf2, 3);
+
+    let mut ctx = HashMap::new();
+    ctx.insert("dir".to_string(), json!(dir.path().to_str().unwrap()));
+    let config = json!({
+        "sources": ["${ctx.dir}/a.pdf", "${ctx.dir}/b.pdf"],
+        "output_path": output.to_str().unwrap(),
+    });
+    let result = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(result.get("pdf_merge_page_count"), Some(&json!(4)));
+
+    let meta = metadata
+        .execute(&json!({ "path": output.to_str().unwrap() }), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(meta["metadata"]["pages"], json!(4));
+
+    // Only the merged page tree is written; the sources' trees are not
+    // dragged along through the pages' Parent links.
+    let merged = Document::load(&output).unwrap();
+    let pages_nodes = merged
+        .objects
+        .values()
+        .filter(|obj| {
+            obj.as_dict()
+                .is_ok_and(|dict| dict.get(b"Type").ok() == Some(&Object::Name(b"Pages".to_vec())))
+        })
+        .count();
+    assert_eq!(pages_nodes, 1);
+}
+
+#[tokio::test]
+async fn pdf_merge_reads_sources_from_context() {
+    use ironflow::nodes::NodeRegistry;
+
+    let registry = NodeRegistry::with_builtins();
+    let node = registry.get("pdf_merge").expect("pdf_merge not registered");
+
+    let dir = tempfile::tempdir().unwrap();
+    let pdf1 = dir.path().join("a.pdf");
+    let pdf2 = dir.path().join("b.pdf");
+    let output = dir.path().join("merged.pdf");
+    create_test_pdf(&pdf1);
+    create_test_pdf(&pdf2);
+
+    let mut ctx = HashMap::new();
+    ctx.insert(
+        "parts".to_string(),
+        json!([pdf1.to_str().unwrap(), pdf2.to_str().unwrap()]),
+    );
+    let config = json!({
+        "source_key": "parts",
+        "output_path": output.to_str().unwrap(),
+        "output_key": "report",
+    });
+    let result = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(result.get("report_page_count"), Some(&json!(2)));
+    assert_eq!(Document::load(&output).unwrap().get_pages().len(), 2);
+}
+
+#[tokio::test]
+async fn pdf_merge_rejects_empty_and_invalid_sources() {
+    use ironflow::nodes::NodeRegistry;
+
+    let registry = NodeRegistry::with_builtins();
+    let node = registry.get("pdf_merge").expect("pdf_merge not registered");
+
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("merged.pdf");
+    let not_pdf = dir.path().join("notes.pdf");
+    std::fs::write(&not_pdf, "plain text").unwrap();
+    let ctx = HashMap::new();
+
+    let err = node
+        .execute(
+            &json!({ "sources": [], "output_path": output.to_str().unwrap() }),
+            &ctx,
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("must not be empty"), "{err}");
+
+    let err = node
+        .execute(
+            &json!({ "sources": [not_pdf.to_str().unwrap()], "output_path": output.to_str().unwrap() }),
+            &ctx,
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("failed to load"), "{err}");
+    assert!(!output.exists());
+}
+
 #[tokio::test]
 async fn pdf_split_single_page() {
     use ironflow::nodes::NodeRegistry;
@@ -232,3 +338,117 @@ async fn pdf_split_missing_file_error() {
     let err = result.unwrap_err().to_string();
     assert!(err.contains("failed to load"), "Error: {}", err);
 }
+
+#[tokio::test]
+async fn pdf_split_extracts_pages_into_one_file_with_metadata() {
+    use ironflow::nodes::NodeRegistry;
+
+    let registry = NodeRegistry::with_builtins();
+    let node = registry.get("pdf_split").expect("pdf_split not registered");
+
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("report.pdf");
+    let output = dir.path().join("excerpt.pdf");
+    create_multi_page_pdf(&source, 3);
+
+    let mut doc = Document::load(&source).unwrap();
+    let info_id = doc.add_object(dictionary! {
+        "Title" => Object::string_literal("Quarterly report"),
+        "Author" => Object::string_literal("Finance"),
+    });
+    doc.trailer.set("Info", info_id);
+    doc.save(&source).unwrap();
+
+    let config = json!({
+        "path": source.to_str().unwrap(),
+        "pages": "1,3",
+        "output_path": output.to_str().unwrap(),
+    });
+    let result = node.execute(&config, &HashMap::new()).await.unwrap();
+
+    assert_eq!(result.get("pdf_split_page_count"), Some(&json!(2)));
+    assert_eq!(
+        result.get("pdf_split_path"),
+        Some(&json!(output.to_str().unwrap()))
+    );
+    assert_eq!(
+        result.get("pdf_split_files"),
+        Some(&json!([output.to_str().unwrap()]))
+    );
+
+    let extracted = Document::load(&output).unwrap();
+    assert_eq!(extracted.get_pages().len(), 2);
+    let contents: Vec<String> = extracted
+        .get_pages()
+        .into_values()
+        .map(|page_id| {
+            String::from_utf8_lossy(&extracted.get_page_content(page_id).unwrap()).into_owned()
+        })
+        .collect();
+    assert!(contents[0].contains("(Page 1)"), "{contents:?}");
+    assert!(contents[1].contains("(Page 3)"), "{contents:?}");
+
+    let info = extracted
+        .trailer
+        .get(b"Info")
+        .and_then(|info| extracted.dereference(info))
+        .and_then(|(_, info)| info.as_dict())
+        .unwrap();
+    assert_eq!(
+        info.get(b"Title").unwrap().as_str().unwrap(),
+        b"Quarterly report"
+    );
+}
+
+#[tokio::test]
+async fn pdf_split_each_writes_one_file_per_page() {
+    use ironflow::nodes::NodeRegistry;
+
+    let registry = NodeRegistry::with_builtins();
+    let node = registry.get("pdf_split").expect("pdf_split not registered");
+
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("report.pdf");
+    let output_dir = dir.path().join("pages");
+    create_multi_page_pdf(&source, 3);
+
+    let config = json!({
+        "path": source.to_str().unwrap(),
+        "pages": "1,3",
+        "split_each": true,
+        "output_dir": output_dir.to_str().unwrap(),
+    });
+    let result = node.execute(&config, &HashMap::new()).await.unwrap();
+
+    let files = result.get("pdf_split_files").unwrap().as_array().unwrap();
+    assert_eq!(files.len(), 2);
+    assert!(files[1].as_str().unwrap().ends_with("report_3.pdf"));
+    for f in files {
+        let page = Document::load(f.as_str().unwrap()).unwrap();
+        assert_eq!(page.get_pages().len(), 1);
+    }
+}
+
+#[tokio::test]
+async fn pdf_split_requires_output_path_without_split_each() {
+    use ironflow::nodes::NodeRegistry;
+
+    let registry = NodeRegistry::with_builtins();
+    let node = registry.get("pdf_split").expect("pdf_split not registered");
+
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("report.pdf");
+    create_multi_page_pdf(&source, 2);
+
+    let config = json!({
+        "path": source.to_str().unwrap(),
+        "split_each": false,
+        "output_dir": dir.path().to_str().unwrap(),
+    });
+    let err = node.execute(&config, &HashMap::new()).await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("requires 'output_path' parameter when split_each is false"),
+        "{err}"
+    );
+}
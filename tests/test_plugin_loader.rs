@@ -0,0 +1,124 @@
+//! Tests for loading custom nodes from a shared library. The fixture plugin
+//! in tests/fixtures/plugin_fixture is built on first use into its own
+//! target directory. That nested cargo build is slow, so the tests that need
+//! it are ignored by default; run them with `cargo test -- --ignored`.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+use ironflow::nodes::NodeRegistry;
+use ironflow::nodes::plugin::{load_plugin, plugin_paths};
+
+fn fixture_library() -> &'static Path {
+    static LIBRARY: OnceLock<PathBuf> = OnceLock::new();
+    LIBRARY.get_or_init(|| {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let fixture = root.join("tests/fixtures/plugin_fixture");
+        let target_dir = root.join("target/plugin-fixture");
+
+        // Build from a copy of the manifest under the target directory so the
+        // lockfile cargo writes never lands in the source tree.
+        let staging = target_dir.join("crate");
+        std::fs::create_dir_all(&staging).unwrap();
+        let manifest = std::fs::read_to_string(fixture.join("Cargo.toml"))
+            .unwrap()
+            .replace(
+                "path = \"../../..\"",
+                &format!("path = {:?}", root.display().to_string()),
+            )
+            .replace(
+                "[lib]",
+                &format!(
+                    "[lib]\npath = {:?}",
+                    fixture.join("src/lib.rs").display().to_string()
+                ),
+            );
+        std::fs::write(staging.join("Cargo.toml"), manifest).unwrap();
+        // Resolve the same dependency versions as the host build.
+        if root.join("Cargo.lock").exists() {
+            std::fs::copy(root.join("Cargo.lock"), staging.join("Cargo.lock")).unwrap();
+        }
+
+        let status = Command::new(env!("CARGO"))
+            .arg("build")
+            .arg("--manifest-path")
+            .arg(staging.join("Cargo.toml"))
+            .arg("--target-dir")
+            .arg(&target_dir)
+            .status()
+            .expect("failed to run cargo");
+        assert!(status.success(), "building the plugin fixture failed");
+
+        target_dir.join("debug").join(format!(
+            "{}ironflow_plugin_fixture.{}",
+            std::env::consts::DLL_PREFIX,
+            std::env::consts::DLL_EXTENSION
+        ))
+    })
+}
+
+#[tokio::test]
+#[ignore = "builds the plugin fixture with a nested cargo invocation"]
+async fn plugin_node_is_listed_and_runs() {
+    let registry = NodeRegistry::with_builtins_and(|registry| {
+        let added = load_plugin(registry, fixture_library()).unwrap();
+        assert_eq!(added, ["fixture_reverse"]);
+    });
+
+    let names: Vec<&str> = registry.list().iter().map(|(n, _, _)| *n).collect();
+    assert!(names.contains(&"fixture_reverse"));
+    assert!(names.contains(&"log"));
+
+    let node = registry.get("fixture_reverse").unwrap();
+    let ctx = [("word".to_string(), serde_json::json!("plugin"))].into();
+    let output = node
+        .execute(&serde_json::json!({ "input_key": "word" }), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(output["reversed"], serde_json::json!("nigulp"));
+}
+
+#[test]
+#[ignore = "builds the plugin fixture with a nested cargo invocation"]
+fn plugin_cannot_replace_registered_node() {
+    let mut registry = NodeRegistry::new();
+    load_plugin(&mut registry, fixture_library()).unwrap();
+
+    let err = load_plugin(&mut registry, fixture_library()).unwrap_err();
+    assert!(
+        err.to_string().contains("already registered"),
+        "unexpected error: {err}"
+    );
+}
+
+#[test]
+fn plugin_paths_lists_libraries_in_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let ext = std::env::consts::DLL_EXTENSION;
+    for name in [
+        format!("b.{ext}"),
+        format!("a.{ext}"),
+        "notes.txt".to_string(),
+    ] {
+        std::fs::write(dir.path().join(name), b"").unwrap();
+    }
+    let explicit = PathBuf::from("/opt/plugins/first.so");
+
+    let paths = plugin_paths(std::slice::from_ref(&explicit), Some(dir.path())).unwrap();
+    assert_eq!(
+        paths,
+        [
+            explicit,
+            dir.path().join(format!("a.{ext}")),
+            dir.path().join(format!("b.{ext}")),
+        ]
+    );
+}
+
+#[test]
+fn load_plugin_reports_missing_library() {
+    let mut registry = NodeRegistry::new();
+    let err = load_plugin(&mut registry, Path::new("/nonexistent/plugin.so")).unwrap_err();
+    assert!(err.to_string().contains("Failed to load plugin"), "{err}");
+}
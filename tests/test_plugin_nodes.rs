@@ -0,0 +1,112 @@
+//! Tests for registering embedder-provided nodes alongside the built-ins.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use ironflow::engine::executor::WorkflowEngine;
+use ironflow::engine::types::{Context, NodeOutput, RunStatus};
+use ironflow::lua::runtime::LuaRuntime;
+use ironflow::nodes::{Node, NodeRegistry};
+use ironflow::storage::StateStore;
+use ironflow::storage::null_store::NullStateStore;
+
+/// Minimal custom node: upper-cases `ctx[input_key]` into `shout`.
+struct ShoutNode;
+
+#[async_trait]
+impl Node for ShoutNode {
+    fn node_type(&self) -> &str {
+        "shout"
+    }
+
+    fn description(&self) -> &str {
+        "Upper-case a context string (test plugin)"
+    }
+
+    async fn execute(&self, config: &serde_json::Value, ctx: &Context) -> Result<NodeOutput> {
+        let key = config
+            .get("input_key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("shout requires 'input_key'"))?;
+        let value = ctx
+            .get(key)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Key '{}' not found in context", key))?;
+
+        let mut output = NodeOutput::new();
+        output.insert(
+            "shout".to_string(),
+            serde_json::Value::String(value.to_uppercase()),
+        );
+        Ok(output)
+    }
+}
+
+fn plugin_registry() -> Arc<NodeRegistry> {
+    Arc::new(NodeRegistry::with_builtins_and(|registry| {
+        registry.register(Arc::new(ShoutNode));
+    }))
+}
+
+#[test]
+fn custom_node_is_listed_with_builtins() {
+    let registry = plugin_registry();
+    let names: Vec<&str> = registry.list().iter().map(|(n, _, _)| *n).collect();
+    assert!(names.contains(&"shout"));
+    assert!(names.contains(&"log"));
+}
+
+#[tokio::test]
+async fn flow_runs_custom_node() {
+    let registry = plugin_registry();
+    let flow = LuaRuntime::load_flow_from_string(
+        r#"
+        local flow = Flow.new("plugin_flow")
+        flow:step("loud", nodes.shout({ input_key = "greeting" }))
+        return flow
+    "#,
+        &registry,
+    )
+    .unwrap();
+
+    let store: Arc<dyn StateStore> = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(registry, store.clone(), None);
+    let mut ctx = Context::new();
+    ctx.insert("greeting".to_string(), serde_json::json!("hello"));
+
+    let run_id = engine.execute(&flow, ctx).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(info.ctx["shout"], "HELLO");
+}
+
+#[tokio::test]
+async fn custom_node_is_available_inside_subworkflow() {
+    let registry = plugin_registry();
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("child.lua"),
+        r#"
+        local flow = Flow.new("child")
+        flow:step("loud", nodes.shout({ input_key = "greeting" }))
+        return flow
+    "#,
+    )
+    .unwrap();
+
+    let mut ctx = Context::new();
+    ctx.insert("greeting".to_string(), serde_json::json!("nested"));
+    ctx.insert(
+        "_flow_dir".to_string(),
+        serde_json::Value::String(dir.path().to_string_lossy().to_string()),
+    );
+
+    let node = registry.get("subworkflow").unwrap();
+    let out = node
+        .execute(&serde_json::json!({ "flow": "child.lua" }), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(out["shout"], "NESTED");
+}
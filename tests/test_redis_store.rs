@@ -105,6 +105,9 @@ async fn redis_upsert_task() {
         input: None,
         output: None,
         error: None,
+        error_detail: None,
+        timings: None,
+        resolved_config: None,
     };
 
     store.upsert_task("run-t1", &task).await.unwrap();
@@ -0,0 +1,90 @@
+//! Tests for the `render_email` node.
+
+use ironflow::engine::types::Context;
+use ironflow::nodes::NodeRegistry;
+
+#[tokio::test]
+async fn render_email_loops_line_items_into_html_body() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("render_email").unwrap();
+
+    let mut ctx = Context::new();
+    ctx.insert(
+        "order".to_string(),
+        serde_json::json!({
+            "id": "A-17",
+            "customer": "Ada",
+            "items": [
+                { "name": "Widget", "qty": 2, "price": "4.50" },
+                { "name": "Gadget <XL>", "qty": 1, "price": "12.00" }
+            ]
+        }),
+    );
+
+    let config = serde_json::json!({
+        "data_key": "order",
+        "subject": "Order {{ id }} confirmed\n",
+        "text": "Hi {{ customer }},\n{% for item in items %}- {{ item.qty }} x {{ item.name }}\n{% endfor %}",
+        "html": "<ul>{% for item in items %}<li>{{ item.qty }} &times; {{ item.name | escape }} (${{ item.price }})</li>{% endfor %}</ul>"
+    });
+    let output = node.execute(&config, &ctx).await.unwrap();
+
+    assert_eq!(output["email_subject"], "Order A-17 confirmed");
+    assert_eq!(
+        output["email_text_body"],
+        "Hi Ada,\n- 2 x Widget\n- 1 x Gadget <XL>\n"
+    );
+    assert_eq!(
+        output["email_html_body"],
+        "<ul><li>2 &times; Widget ($4.50)</li><li>1 &times; Gadget &lt;XL&gt; ($12.00)</li></ul>"
+    );
+}
+
+#[tokio::test]
+async fn render_email_reads_template_dir_with_inline_override() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("subject.liquid"), "Welcome, {{ name }}").unwrap();
+    std::fs::write(
+        dir.path().join("html.liquid"),
+        "<p>Hello {{ name | upcase }}</p>",
+    )
+    .unwrap();
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("render_email").unwrap();
+
+    let mut ctx = Context::new();
+    ctx.insert("user_name".to_string(), serde_json::json!("grace"));
+    let config = serde_json::json!({
+        "template_dir": dir.path().to_str().unwrap(),
+        "text": "Hello {{ name }}",
+        "data": { "name": "${ctx.user_name}" },
+        "output_key": "welcome"
+    });
+    let output = node.execute(&config, &ctx).await.unwrap();
+
+    assert_eq!(output["welcome_subject"], "Welcome, grace");
+    assert_eq!(output["welcome_text_body"], "Hello grace");
+    assert_eq!(output["welcome_html_body"], "<p>Hello GRACE</p>");
+}
+
+#[tokio::test]
+async fn render_email_requires_subject_and_body() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("render_email").unwrap();
+
+    let err = node
+        .execute(&serde_json::json!({ "text": "hi" }), &Context::new())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("'subject'"), "unexpected: {err}");
+
+    let err = node
+        .execute(&serde_json::json!({ "subject": "hi" }), &Context::new())
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("'text' or 'html'"),
+        "unexpected: {err}"
+    );
+}
@@ -10,6 +10,7 @@ fn s3_nodes_are_registered() {
     let reg = NodeRegistry::with_builtins();
     assert!(reg.get("s3_presign_url").is_some());
     assert!(reg.get("s3_get_object").is_some());
+    assert!(reg.get("s3_head_object").is_some());
     assert!(reg.get("s3_put_object").is_some());
     assert!(reg.get("s3_delete_object").is_some());
     assert!(reg.get("s3_copy_object").is_some());
@@ -174,3 +175,150 @@ async fn s3_copy_object_requires_source_bucket() {
     let error = result.unwrap_err().to_string();
     assert!(error.contains("s3_copy_object requires 'source_bucket'"));
 }
+
+/// Serve a single object at `/{bucket}/report.txt` (path-style), optionally
+/// with a `Content-Encoding` header, and return the endpoint URL. Other keys
+/// answer 404.
+async fn start_mock_s3(body: Vec<u8>, content_encoding: Option<&'static str>) -> String {
+    use axum::extract::Path;
+    use axum::http::StatusCode;
+    use axum::http::header::{CONTENT_ENCODING, CONTENT_TYPE};
+    use axum::response::IntoResponse;
+
+    // The SDK needs some credentials to sign with; the mock ignores them.
+    unsafe {
+        std::env::set_var("AWS_ACCESS_KEY_ID", "test");
+        std::env::set_var("AWS_SECRET_ACCESS_KEY", "test");
+        std::env::set_var("AWS_EC2_METADATA_DISABLED", "true");
+    }
+
+    // Axum answers HEAD from GET routes with the headers and no body.
+    let app = axum::Router::new().route(
+        "/{bucket}/{key}",
+        axum::routing::get(move |Path((_, key)): Path<(String, String)>| {
+            let body = body.clone();
+            async move {
+                if key != "report.txt" {
+                    return StatusCode::NOT_FOUND.into_response();
+                }
+                let mut response = (
+                    [
+                        (CONTENT_TYPE, "text/plain"),
+                        ("etag".parse().unwrap(), "\"abc123\""),
+                        ("x-amz-meta-owner".parse().unwrap(), "reports-team"),
+                    ],
+                    body,
+                )
+                    .into_response();
+                if let Some(encoding) = content_encoding {
+                    response
+                        .headers_mut()
+                        .insert(CONTENT_ENCODING, encoding.parse().unwrap());
+                }
+                response
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+fn get_object_config(endpoint: &str, extra: serde_json::Value) -> serde_json::Value {
+    let mut config = serde_json::json!({
+        "bucket": "test-bucket",
+        "key": "report.txt",
+        "endpoint_url": endpoint,
+        "region": "us-east-1",
+        "force_path_style": true,
+        "output_key": "obj"
+    });
+    config
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    config
+}
+
+#[tokio::test]
+async fn s3_get_object_auto_decompresses_gzip_content_encoding() {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(b"hello from a gzipped object").unwrap();
+    let endpoint = start_mock_s3(encoder.finish().unwrap(), Some("gzip")).await;
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("s3_get_object").unwrap();
+
+    let config = get_object_config(&endpoint, serde_json::json!({ "auto_decompress": true }));
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(output["obj_content"], "hello from a gzipped object");
+    assert_eq!(output["obj_content_encoding"], "gzip");
+    assert_eq!(output["obj_decompressed"], true);
+    assert_eq!(output["obj_size"], 27);
+
+    // Raw bytes stay the default.
+    let config = get_object_config(&endpoint, serde_json::json!({ "encoding": "base64" }));
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(output["obj_decompressed"], false);
+    assert_ne!(output["obj_size"], 27);
+}
+
+#[tokio::test]
+async fn s3_get_object_decompress_forces_codec() {
+    let compressed = zstd::encode_all(&b"zstd payload"[..], 3).unwrap();
+    let endpoint = start_mock_s3(compressed, None).await;
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("s3_get_object").unwrap();
+
+    let config = get_object_config(&endpoint, serde_json::json!({ "decompress": "zstd" }));
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(output["obj_content"], "zstd payload");
+    assert_eq!(output["obj_decompressed"], true);
+
+    let config = get_object_config(&endpoint, serde_json::json!({ "decompress": "lz4" }));
+    let error = node.execute(&config, &empty_ctx()).await.unwrap_err();
+    assert!(error.to_string().contains("'decompress' must be"));
+}
+
+#[tokio::test]
+async fn s3_head_object_reports_existing_object_metadata() {
+    let endpoint = start_mock_s3(b"quarterly numbers".to_vec(), None).await;
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("s3_head_object").unwrap();
+
+    let config = get_object_config(&endpoint, serde_json::json!({}));
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(output["obj_exists"], true);
+    assert_eq!(output["obj_content_length"], 17);
+    assert_eq!(output["obj_content_type"], "text/plain");
+    assert_eq!(output["obj_etag"], "\"abc123\"");
+    assert_eq!(output["obj_metadata"]["owner"], "reports-team");
+    assert!(!output.contains_key("obj_content"));
+}
+
+#[tokio::test]
+async fn s3_head_object_missing_key_is_not_an_error_unless_required() {
+    let endpoint = start_mock_s3(Vec::new(), None).await;
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("s3_head_object").unwrap();
+
+    let config = get_object_config(&endpoint, serde_json::json!({ "key": "missing.txt" }));
+    let output = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(output["obj_exists"], false);
+    assert_eq!(output["obj_success"], true);
+
+    let config = get_object_config(
+        &endpoint,
+        serde_json::json!({ "key": "missing.txt", "required": true }),
+    );
+    let error = node.execute(&config, &empty_ctx()).await.unwrap_err();
+    assert!(error.to_string().contains("does not exist"));
+}
@@ -0,0 +1,165 @@
+//! Tests for `${secret:<provider>:<path>}` resolution.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use ironflow::engine::executor::WorkflowEngine;
+use ironflow::engine::types::RunStatus;
+use ironflow::lua::runtime::LuaRuntime;
+use ironflow::nodes::NodeRegistry;
+use ironflow::secrets::{SecretProvider, SecretResolver, SsmProvider};
+use ironflow::storage::StateStore;
+use ironflow::storage::json_store::JsonStateStore;
+
+/// In-memory provider standing in for a real secrets manager.
+struct MockProvider {
+    secrets: HashMap<String, String>,
+    fetches: AtomicUsize,
+}
+
+#[async_trait]
+impl SecretProvider for MockProvider {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    async fn fetch(&self, path: &str) -> Result<String> {
+        self.fetches.fetch_add(1, Ordering::SeqCst);
+        self.secrets
+            .get(path)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("no secret at {}", path))
+    }
+}
+
+fn mock_resolver() -> (Arc<SecretResolver>, Arc<MockProvider>) {
+    let provider = Arc::new(MockProvider {
+        secrets: HashMap::from([("/app/api_token".to_string(), "tok-123".to_string())]),
+        fetches: AtomicUsize::new(0),
+    });
+    let mut resolver = SecretResolver::new();
+    resolver.register(provider.clone());
+    (Arc::new(resolver), provider)
+}
+
+#[tokio::test]
+async fn secret_placeholder_is_resolved_at_run_time_and_not_stored() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(JsonStateStore::new(dir.path()));
+    let registry = Arc::new(NodeRegistry::with_builtins());
+    let (resolver, provider) = mock_resolver();
+
+    let flow = LuaRuntime::load_flow_from_string(
+        r#"
+        local flow = Flow.new("secret_flow")
+        flow:step("first", nodes.log({ message = "token=${secret:mock:/app/api_token}" }))
+        flow:step("second", nodes.log({ message = "again=${secret:mock:/app/api_token}" }))
+            :depends_on("first")
+        return flow
+    "#,
+        &registry,
+    )
+    .unwrap();
+
+    let engine = WorkflowEngine::new(registry, store.clone(), None).with_secrets(resolver);
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+
+    let info = store.get_run_info(&run_id).await.unwrap();
+    assert_eq!(info.status, RunStatus::Success);
+    assert_eq!(
+        info.tasks["first"].output.as_ref().unwrap()["log_message"],
+        "token=tok-123"
+    );
+    assert_eq!(info.ctx["log_message"], "again=tok-123");
+
+    // Task history keeps the placeholder, not the value.
+    let resolved = info.tasks["first"].resolved_config.as_ref().unwrap();
+    assert_eq!(resolved["message"], "token=${secret:mock:/app/api_token}");
+
+    // The second step was served from the cache.
+    assert_eq!(provider.fetches.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn unknown_secret_provider_fails_the_step() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(JsonStateStore::new(dir.path()));
+    let registry = Arc::new(NodeRegistry::with_builtins());
+    let (resolver, _) = mock_resolver();
+
+    let flow = LuaRuntime::load_flow_from_string(
+        r#"
+        local flow = Flow.new("bad_secret")
+        flow:step("s1", nodes.log({ message = "${secret:vault:kv/db}" }))
+        return flow
+    "#,
+        &registry,
+    )
+    .unwrap();
+
+    let engine = WorkflowEngine::new(registry, store.clone(), None).with_secrets(resolver);
+    let run_id = engine.execute(&flow, HashMap::new()).await.unwrap();
+
+    let info = store.get_run_info(&run_id).await.unwrap();
+    assert_eq!(info.status, RunStatus::Failed);
+    let error = info.tasks["s1"].error.as_ref().unwrap();
+    assert!(error.contains("Unknown secret provider 'vault'"), "{error}");
+}
+
+#[tokio::test]
+async fn ssm_provider_fetches_parameter_with_signed_request() {
+    use axum::Json;
+    use axum::http::HeaderMap;
+    use std::sync::Mutex;
+
+    // The SDK credential chain and region come from the environment.
+    unsafe {
+        std::env::set_var("AWS_ACCESS_KEY_ID", "test");
+        std::env::set_var("AWS_SECRET_ACCESS_KEY", "test");
+        std::env::set_var("AWS_REGION", "eu-west-1");
+        std::env::set_var("AWS_EC2_METADATA_DISABLED", "true");
+    }
+
+    let seen: Arc<Mutex<Option<(HeaderMap, serde_json::Value)>>> = Arc::new(Mutex::new(None));
+    let seen_handler = seen.clone();
+    let app = axum::Router::new().route(
+        "/",
+        axum::routing::post(move |headers: HeaderMap, body: String| {
+            let seen = seen_handler.clone();
+            async move {
+                let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+                let name = body["Name"].as_str().unwrap().to_string();
+                *seen.lock().unwrap() = Some((headers, body));
+                Json(serde_json::json!({
+                    "Parameter": { "Name": name, "Type": "SecureString", "Value": "hunter2" }
+                }))
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let mut resolver = SecretResolver::new();
+    resolver.register(Arc::new(SsmProvider::with_endpoint(format!(
+        "http://{}",
+        addr
+    ))));
+
+    let config = serde_json::json!({ "password": "${secret:ssm:/prod/db/password}" });
+    let resolved = resolver.resolve_config(&config).await.unwrap();
+    assert_eq!(resolved["password"], "hunter2");
+
+    let (headers, body) = seen.lock().unwrap().take().unwrap();
+    assert_eq!(body["Name"], "/prod/db/password");
+    assert_eq!(body["WithDecryption"], true);
+    assert_eq!(headers["x-amz-target"], "AmazonSSM.GetParameter");
+    let authorization = headers["authorization"].to_str().unwrap();
+    assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=test/"));
+    assert!(authorization.contains("/eu-west-1/ssm/aws4_request"));
+}
@@ -335,3 +335,157 @@ async fn send_email_accepts_array_recipients() {
     assert!(received.contains("a@example.com"));
     assert!(received.contains("b@example.com"));
 }
+
+/// Spawn a minimal SMTP server for one session. Returns its port and a
+/// handle yielding the envelope commands and the raw DATA payload.
+fn spawn_smtp_server() -> (u16, std::thread::JoinHandle<(Vec<String>, String)>) {
+    use std::io::BufRead;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handle = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut writer = stream.try_clone().unwrap();
+        let mut reader = std::io::BufReader::new(stream);
+        let mut commands = Vec::new();
+        let mut data = String::new();
+        writer.write_all(b"220 mock ESMTP\r\n").unwrap();
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap_or(0) > 0 {
+            let command = line.trim_end().to_string();
+            line.clear();
+            let upper = command.to_uppercase();
+            if upper.starts_with("EHLO") {
+                writer.write_all(b"250 mock\r\n").unwrap();
+            } else if upper == "DATA" {
+                writer.write_all(b"354 go ahead\r\n").unwrap();
+                while reader.read_line(&mut line).unwrap_or(0) > 0 {
+                    if line == ".\r\n" {
+                        break;
+                    }
+                    data.push_str(&line);
+                    line.clear();
+                }
+                writer.write_all(b"250 queued\r\n").unwrap();
+                // The transport pools the connection rather than sending
+                // QUIT, so stop once the message is in.
+                break;
+            } else {
+                commands.push(command);
+                writer.write_all(b"250 ok\r\n").unwrap();
+            }
+        }
+        (commands, data)
+    });
+    (port, handle)
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn send_email_smtp_builds_message_with_attachments() {
+    let (port, handle) = spawn_smtp_server();
+    let dir = tempfile::tempdir().unwrap();
+    let report = dir.path().join("report.csv");
+    std::fs::write(&report, "id,total\n1,42\n").unwrap();
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("send_email").unwrap();
+    let mut ctx = empty_ctx();
+    ctx.insert("order_id".to_string(), serde_json::json!("A-17"));
+    ctx.insert(
+        "report_path".to_string(),
+        serde_json::json!(report.to_string_lossy()),
+    );
+
+    let config = serde_json::json!({
+        "provider": "smtp",
+        "smtp_server": "127.0.0.1",
+        "smtp_port": port,
+        "smtp_tls": "none",
+        "from": "orders@example.com",
+        "to": "buyer@example.com",
+        "cc": ["audit@example.com"],
+        "subject": "Order ${ctx.order_id} shipped",
+        "text": "Your order ${ctx.order_id} is on its way.",
+        "html": "<p>Order <b>${ctx.order_id}</b> shipped.</p>",
+        "attachments": [
+            { "path": "${ctx.report_path}", "content_type": "text/csv" },
+            { "content": "aGVsbG8gd29ybGQ=", "filename": "note.txt", "content_type": "text/plain" }
+        ],
+        "timeout": 5
+    });
+
+    let out = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(out["email_success"], true);
+    assert_eq!(out["email_status"], "250");
+
+    let (commands, data) = handle.join().unwrap();
+    assert!(commands.contains(&"MAIL FROM:<orders@example.com>".to_string()));
+    assert!(commands.contains(&"RCPT TO:<buyer@example.com>".to_string()));
+    assert!(commands.contains(&"RCPT TO:<audit@example.com>".to_string()));
+
+    assert!(data.contains("Subject: Order A-17 shipped"), "{data}");
+    assert!(data.contains("To: buyer@example.com"), "{data}");
+    assert!(data.contains("Cc: audit@example.com"), "{data}");
+    assert!(data.contains("multipart/mixed"), "{data}");
+    assert!(data.contains("multipart/alternative"), "{data}");
+    assert!(data.contains("Your order A-17 is on its way."), "{data}");
+    assert!(
+        data.contains("Content-Disposition: attachment; filename=\"report.csv\""),
+        "{data}"
+    );
+    assert!(data.contains("Content-Type: text/csv"), "{data}");
+    assert!(
+        data.contains("Content-Disposition: attachment; filename=\"note.txt\""),
+        "{data}"
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn send_email_resend_sends_base64_attachments() {
+    let (url, handle) = spawn_mock_server(r#"{"id":"email_att"}"#, 200);
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("send_email").unwrap();
+    let config = serde_json::json!({
+        "api_key": "re_test_key",
+        "api_url": url,
+        "to": "a@example.com",
+        "from": "noreply@example.com",
+        "subject": "Invoice",
+        "text": "Attached.",
+        "attachments": [{ "content": "JVBERi0=", "filename": "invoice.pdf", "content_type": "application/pdf" }],
+        "timeout": 5
+    });
+
+    node.execute(&config, &empty_ctx()).await.unwrap();
+    let received = handle.join().unwrap();
+    let body = received.split("\r\n\r\n").nth(1).unwrap();
+    let payload: serde_json::Value = serde_json::from_str(body).unwrap();
+    assert_eq!(
+        payload["attachments"],
+        serde_json::json!([{
+            "filename": "invoice.pdf",
+            "content": "JVBERi0=",
+            "content_type": "application/pdf"
+        }])
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn send_email_rejects_attachment_without_source() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("send_email").unwrap();
+    let config = serde_json::json!({
+        "api_key": "re_test_key",
+        "to": "a@example.com",
+        "subject": "Bad",
+        "attachments": [{ "filename": "x.txt" }]
+    });
+
+    let err = node.execute(&config, &empty_ctx()).await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("needs exactly one of 'path' or 'content'"),
+        "{err}"
+    );
+}
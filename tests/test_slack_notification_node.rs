@@ -251,3 +251,120 @@ async fn slack_notification_custom_output_key() {
     assert!(out.contains_key("notif_success"));
     assert!(!out.contains_key("slack_status"));
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn slack_notify_posts_blocks_with_mentions_to_webhook() {
+    let (url, handle) = spawn_mock_server("ok", 200);
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("slack_notify").unwrap();
+    let mut ctx = Context::new();
+    ctx.insert("job".to_string(), serde_json::json!("nightly-etl"));
+
+    let config = serde_json::json!({
+        "webhook_url": url,
+        "text": "${ctx.job} failed",
+        "mention": ["@here", "U024BE7LH"],
+        "blocks": [{
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": "*${ctx.job}* failed" }
+        }],
+        "timeout": 5
+    });
+
+    let out = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(out["slack_success"], true);
+
+    let received = handle.join().unwrap();
+    let body = received.split("\r\n\r\n").nth(1).unwrap();
+    let payload: serde_json::Value = serde_json::from_str(body).unwrap();
+    assert_eq!(
+        payload,
+        serde_json::json!({
+            "text": "<!here> <@U024BE7LH> nightly-etl failed",
+            "blocks": [{
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": "*nightly-etl* failed" }
+            }]
+        })
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn slack_notify_posts_to_channel_with_bot_token() {
+    let (url, handle) = spawn_mock_server(
+        r#"{"ok":true,"channel":"C123","ts":"1700000000.000100"}"#,
+        200,
+    );
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("slack_notify").unwrap();
+    let config = serde_json::json!({
+        "api_url": url,
+        "token": "xoxb-test",
+        "channel": "#deploys",
+        "text": "Deploy finished",
+        "output_key": "notice",
+        "timeout": 5
+    });
+
+    let out = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(out["notice_ts"], "1700000000.000100");
+    assert_eq!(out["notice_success"], true);
+
+    let received = handle.join().unwrap();
+    assert!(
+        received.starts_with("POST /chat.postMessage "),
+        "{received}"
+    );
+    assert!(
+        received
+            .to_lowercase()
+            .contains("authorization: bearer xoxb-test"),
+        "{received}"
+    );
+    let body = received.split("\r\n\r\n").nth(1).unwrap();
+    let payload: serde_json::Value = serde_json::from_str(body).unwrap();
+    assert_eq!(
+        payload,
+        serde_json::json!({ "channel": "#deploys", "text": "Deploy finished" })
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn slack_notify_surfaces_api_errors() {
+    let (url, _handle) = spawn_mock_server(r#"{"ok":false,"error":"channel_not_found"}"#, 200);
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("slack_notify").unwrap();
+    let config = serde_json::json!({
+        "api_url": url,
+        "token": "xoxb-test",
+        "channel": "#missing",
+        "text": "hello",
+        "timeout": 5
+    });
+
+    let err = node.execute(&config, &empty_ctx()).await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("chat.postMessage to '#missing' failed: channel_not_found"),
+        "{err}"
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn slack_notify_requires_text_or_blocks() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("slack_notify").unwrap();
+    let config = serde_json::json!({
+        "webhook_url": "https://example.invalid/slack_webhook"
+    });
+
+    let err = node.execute(&config, &empty_ctx()).await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("requires 'text', 'message', or 'blocks'"),
+        "{err}"
+    );
+}
@@ -274,6 +274,12 @@ async fn sql_store_init_update_and_get() {
     task.status = TaskStatus::Success;
     task.attempt = 2;
     task.output = Some(serde_json::json!({"ok": true}));
+    task.error_detail = Some(NodeError::http(503, "upstream down"));
+    task.timings = Some(std::collections::BTreeMap::from([(
+        "http.first_byte_ms".to_string(),
+        12.5,
+    )]));
+    task.resolved_config = Some(serde_json::json!({"url": "https://example.com/x"}));
     store.upsert_task("r1", &task).await.unwrap();
 
     let mut ctx_update = HashMap::new();
@@ -290,6 +296,18 @@ async fn sql_store_init_update_and_get() {
         info.tasks["step1"].output.as_ref().unwrap(),
         &serde_json::json!({"ok": true})
     );
+    let detail = info.tasks["step1"].error_detail.as_ref().unwrap();
+    assert_eq!(detail.kind, NodeErrorKind::Http { status: 503 });
+    assert!(detail.retriable);
+    assert_eq!(
+        info.tasks["step1"].timings.as_ref().unwrap()["http.first_byte_ms"],
+        12.5
+    );
+    assert_eq!(
+        info.tasks["step1"].resolved_config.as_ref().unwrap()["url"],
+        "https://example.com/x"
+    );
+    assert!(info.profile.is_some());
 }
 
 #[tokio::test]
@@ -354,6 +372,58 @@ async fn sql_store_uses_custom_table_prefix() {
     assert_eq!(row.get::<i64, _>("count"), 0);
 }
 
+#[tokio::test]
+async fn sql_store_adds_new_columns_to_an_existing_tasks_table() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = sqlite_store_url(dir.path());
+    sqlx::any::install_default_drivers();
+    let pool = sqlx::AnyPool::connect(&url).await.unwrap();
+    sqlx::query(
+        "CREATE TABLE ironflow_tasks (run_id TEXT NOT NULL, name TEXT NOT NULL, \
+         node_type TEXT NOT NULL, status TEXT NOT NULL, attempt INTEGER NOT NULL, \
+         input TEXT, output TEXT, error TEXT, started TEXT, finished TEXT, \
+         PRIMARY KEY (run_id, name))",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let store = SqlStateStore::new(&url).await.unwrap();
+    store
+        .init_run("r1", "old_db", &HashMap::new())
+        .await
+        .unwrap();
+    let mut task = TaskState::new("step1", "log");
+    task.resolved_config = Some(serde_json::json!({"message": "hi"}));
+    store.upsert_task("r1", &task).await.unwrap();
+    let info = store.get_run_info("r1").await.unwrap();
+    assert_eq!(
+        info.tasks["step1"].resolved_config.as_ref().unwrap()["message"],
+        "hi"
+    );
+
+    // Opening the now-current schema again must not trip over the columns.
+    SqlStateStore::new(&url).await.unwrap();
+}
+
+#[tokio::test]
+async fn sql_store_reports_schema_migration_failures() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = sqlite_store_url(dir.path());
+    sqlx::any::install_default_drivers();
+    let pool = sqlx::AnyPool::connect(&url).await.unwrap();
+    // A view shadowing the tasks table cannot take new columns.
+    sqlx::query("CREATE VIEW ironflow_tasks AS SELECT 1 AS run_id")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let err = SqlStateStore::new(&url).await.err().unwrap();
+    let message = format!("{err:#}");
+    assert!(message.contains("Failed to add column"), "{message}");
+    assert!(message.contains("view"), "{message}");
+}
+
 #[cfg(feature = "postgres")]
 #[tokio::test]
 async fn postgres_sql_store_works_with_custom_table_prefix() {
@@ -521,3 +591,172 @@ async fn json_store_status_filter_in_summary_listing() {
     assert_eq!(pending.len(), 1);
     assert_eq!(pending[0].id, "r1");
 }
+
+// ===== Flow stats =====
+
+async fn seed_mixed_runs(store: &dyn StateStore) {
+    let statuses = [
+        RunStatus::Success,
+        RunStatus::Success,
+        RunStatus::Success,
+        RunStatus::Failed,
+        RunStatus::Stalled,
+        RunStatus::Running,
+    ];
+    for (i, status) in statuses.into_iter().enumerate() {
+        let id = format!("r{i}");
+        store
+            .init_run(&id, "stats_flow", &test_ctx())
+            .await
+            .unwrap();
+        store.set_run_status(&id, status).await.unwrap();
+    }
+    store
+        .init_run("other", "other_flow", &test_ctx())
+        .await
+        .unwrap();
+    store
+        .set_run_status("other", RunStatus::Failed)
+        .await
+        .unwrap();
+}
+
+fn assert_mixed_run_stats(days: &[FlowDayStats]) {
+    assert_eq!(days.len(), 8, "7-day window spans 8 calendar days");
+    let today = days.last().unwrap();
+    assert_eq!(today.date, chrono::Utc::now().date_naive());
+    assert_eq!(today.runs, 6);
+    assert_eq!(today.success, 3);
+    assert_eq!(today.failed, 2);
+    assert_eq!(today.success_rate, Some(0.6));
+    assert!(today.p50_duration_ms.is_some());
+    assert!(today.p95_duration_ms >= today.p50_duration_ms);
+    for day in &days[..7] {
+        assert_eq!(day.runs, 0);
+        assert_eq!(day.success_rate, None);
+    }
+}
+
+#[tokio::test]
+async fn json_store_flow_daily_stats_mixed_statuses() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = JsonStateStore::new(dir.path());
+    seed_mixed_runs(&store).await;
+
+    let since = chrono::Utc::now() - chrono::Duration::days(7);
+    let days = store.flow_daily_stats("stats_flow", since).await.unwrap();
+    assert_mixed_run_stats(&days);
+}
+
+#[tokio::test]
+async fn sql_store_flow_daily_stats_mixed_statuses() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = SqlStateStore::new(&sqlite_store_url(dir.path()))
+        .await
+        .unwrap();
+    seed_mixed_runs(&store).await;
+
+    let since = chrono::Utc::now() - chrono::Duration::days(7);
+    let days = store.flow_daily_stats("stats_flow", since).await.unwrap();
+    assert_mixed_run_stats(&days);
+
+    let future = chrono::Utc::now() + chrono::Duration::hours(1);
+    let empty = store.flow_daily_stats("stats_flow", future).await.unwrap();
+    assert!(empty.iter().all(|d| d.runs == 0));
+}
+
+#[test]
+fn flow_day_stats_aggregate_rates_and_percentiles() {
+    use chrono::TimeZone;
+
+    let day1 = chrono::Utc.with_ymd_and_hms(2025, 3, 1, 10, 0, 0).unwrap();
+    let day2 = chrono::Utc.with_ymd_and_hms(2025, 3, 2, 10, 0, 0).unwrap();
+    let run = |id: &str, status: RunStatus, started, duration_ms: i64| RunSummary {
+        id: id.to_string(),
+        flow_name: "f".to_string(),
+        finished: status
+            .is_terminal()
+            .then(|| started + chrono::Duration::milliseconds(duration_ms)),
+        status,
+        started: Some(started),
+        task_count: 0,
+    };
+    let runs = vec![
+        run("a", RunStatus::Success, day1, 100),
+        run("b", RunStatus::Success, day1, 200),
+        run("c", RunStatus::Failed, day1, 300),
+        run("d", RunStatus::Success, day1, 400),
+        run("e", RunStatus::Running, day1, 0),
+        run("f", RunStatus::Stalled, day2, 5000),
+    ];
+
+    let since = chrono::Utc.with_ymd_and_hms(2025, 2, 28, 12, 0, 0).unwrap();
+    let until = chrono::Utc.with_ymd_and_hms(2025, 3, 2, 23, 0, 0).unwrap();
+    let days = FlowDayStats::aggregate(&runs, since, until);
+
+    assert_eq!(days.len(), 3);
+    assert_eq!(days[0].runs, 0);
+    assert_eq!(days[0].success_rate, None);
+    assert_eq!(days[0].p50_duration_ms, None);
+
+    assert_eq!(days[1].runs, 5);
+    assert_eq!(days[1].success, 3);
+    assert_eq!(days[1].failed, 1);
+    assert_eq!(days[1].success_rate, Some(0.75));
+    assert_eq!(days[1].p50_duration_ms, Some(200));
+    assert_eq!(days[1].p95_duration_ms, Some(400));
+
+    assert_eq!(days[2].runs, 1);
+    assert_eq!(days[2].success_rate, Some(0.0));
+    assert_eq!(days[2].p95_duration_ms, Some(5000));
+}
+
+// ===== Dedupe index =====
+
+async fn assert_dedupe_claims(store: &dyn StateStore) {
+    let ttl = std::time::Duration::from_secs(60);
+    assert_eq!(store.claim_dedupe_key("h1", "r1", ttl).await.unwrap(), None);
+    assert_eq!(
+        store.claim_dedupe_key("h1", "r2", ttl).await.unwrap(),
+        Some("r1".to_string())
+    );
+    assert_eq!(store.claim_dedupe_key("h2", "r3", ttl).await.unwrap(), None);
+
+    // An expired entry can be claimed again.
+    let short = std::time::Duration::from_millis(20);
+    assert_eq!(
+        store.claim_dedupe_key("h3", "r4", short).await.unwrap(),
+        None
+    );
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert_eq!(store.claim_dedupe_key("h3", "r5", ttl).await.unwrap(), None);
+    assert_eq!(
+        store.claim_dedupe_key("h3", "r6", ttl).await.unwrap(),
+        Some("r5".to_string())
+    );
+}
+
+#[tokio::test]
+async fn null_store_dedupe_claims() {
+    assert_dedupe_claims(&NullStateStore::new()).await;
+}
+
+#[tokio::test]
+async fn json_store_dedupe_claims() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = JsonStateStore::new(dir.path());
+    assert_dedupe_claims(&store).await;
+    assert!(
+        store.list_runs(None).await.unwrap().is_empty(),
+        "dedupe entries must not show up as runs"
+    );
+}
+
+#[tokio::test]
+async fn sql_store_dedupe_claims() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = SqlStateStore::new(&sqlite_store_url(dir.path()))
+        .await
+        .unwrap();
+    assert_dedupe_claims(&store).await;
+}
@@ -144,3 +144,47 @@ async fn subworkflow_failure_without_output_key_propagates_error() {
     let err = node.execute(&config, &ctx).await.unwrap_err();
     assert!(err.to_string().contains("Subworkflow"));
 }
+
+#[tokio::test]
+async fn subworkflow_runs_in_the_parent_runs_environment() {
+    use std::sync::Arc;
+
+    use ironflow::engine::executor::WorkflowEngine;
+    use ironflow::lua::runtime::LuaRuntime;
+    use ironflow::storage::StateStore;
+    use ironflow::storage::null_store::NullStateStore;
+
+    let dir = tempfile::tempdir().unwrap();
+    write_flow(
+        &dir.path().join("child.lua"),
+        r#"
+        flow:env("default", { api_url = "http://localhost" })
+        flow:env("prod", { api_url = "https://api.example.com" })
+        flow:step("s", nodes.log({ message = "${ctx.api_url}" }))
+        "#,
+    );
+
+    let registry = Arc::new(NodeRegistry::with_builtins());
+    let parent = LuaRuntime::load_flow_from_string(
+        r#"
+        local flow = Flow.new("parent")
+        flow:env("prod", { region = "eu" })
+        flow:step("call", nodes.subworkflow({ flow = "child.lua", output_key = "child" }))
+        return flow
+        "#,
+        &registry,
+    )
+    .unwrap();
+    let store = Arc::new(NullStateStore::new());
+    let engine = WorkflowEngine::new(registry, store.clone(), None)
+        .with_environment(Some("prod".to_string()));
+    let ctx = ctx_with(vec![(
+        "_flow_dir",
+        serde_json::json!(dir.path().to_string_lossy()),
+    )]);
+
+    let run_id = engine.execute(&parent, ctx).await.unwrap();
+    let info = store.get_run_info(&run_id).await.unwrap();
+    assert_eq!(info.ctx["region"], "eu");
+    assert_eq!(info.ctx["child"]["log_message"], "https://api.example.com");
+}
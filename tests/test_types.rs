@@ -11,6 +11,109 @@ fn retry_config_default() {
     assert!((rc.backoff_s - 1.0).abs() < f64::EPSILON);
 }
 
+fn delays(retry: &RetryConfig, attempts: u32, jitter: f64) -> Vec<f64> {
+    (1..=attempts)
+        .map(|a| retry.delay_s(a, || jitter))
+        .collect()
+}
+
+#[test]
+fn retry_delay_fixed() {
+    let retry = RetryConfig {
+        max_retries: 4,
+        backoff_s: 1.5,
+        backoff: BackoffStrategy::Fixed,
+        max_backoff_s: None,
+        retry_on: Vec::new(),
+    };
+    assert_eq!(delays(&retry, 4, 0.5), vec![1.5, 1.5, 1.5, 1.5]);
+}
+
+#[test]
+fn retry_delay_exponential_is_the_default_and_capped() {
+    let mut retry = RetryConfig {
+        max_retries: 5,
+        backoff_s: 1.0,
+        ..RetryConfig::default()
+    };
+    assert_eq!(retry.backoff, BackoffStrategy::Exponential);
+    assert_eq!(delays(&retry, 5, 0.5), vec![1.0, 2.0, 4.0, 8.0, 16.0]);
+
+    retry.max_backoff_s = Some(5.0);
+    assert_eq!(delays(&retry, 5, 0.5), vec![1.0, 2.0, 4.0, 5.0, 5.0]);
+}
+
+#[test]
+fn retry_delay_exponential_jitter_scales_the_capped_delay() {
+    let retry = RetryConfig {
+        max_retries: 4,
+        backoff_s: 2.0,
+        backoff: BackoffStrategy::ExponentialJitter,
+        max_backoff_s: Some(10.0),
+        retry_on: Vec::new(),
+    };
+    assert_eq!(delays(&retry, 4, 0.5), vec![1.0, 2.0, 4.0, 5.0]);
+    assert_eq!(delays(&retry, 4, 0.0), vec![0.0; 4]);
+
+    // Real samples stay within [0, capped delay].
+    for attempt in 1..=4 {
+        let d = retry.delay_s(attempt, rand::random::<f64>);
+        assert!((0.0..=10.0).contains(&d), "{d}");
+    }
+}
+
+#[test]
+fn retry_after_overrides_backoff_up_to_max() {
+    let mut retry = RetryConfig {
+        max_retries: 3,
+        backoff_s: 1.0,
+        backoff: BackoffStrategy::Exponential,
+        max_backoff_s: None,
+        retry_on: Vec::new(),
+    };
+    let plain = NodeError::http(503, "unavailable");
+    let throttled =
+        NodeError::http(429, "slow down").with_retry_after(std::time::Duration::from_millis(2500));
+
+    assert_eq!(retry.delay_after_s(3, &plain, || 0.0), 4.0);
+    assert_eq!(retry.delay_after_s(3, &throttled, || 0.0), 2.5);
+
+    retry.max_backoff_s = Some(2.0);
+    assert_eq!(retry.delay_after_s(1, &throttled, || 0.0), 2.0);
+
+    // Without `max_backoff_s` an upstream still cannot park the step for days.
+    retry.max_backoff_s = None;
+    let parked =
+        NodeError::http(503, "later").with_retry_after(std::time::Duration::from_secs(3 * 86_400));
+    assert_eq!(
+        retry.delay_after_s(1, &parked, || 0.0),
+        DEFAULT_MAX_RETRY_AFTER_S
+    );
+}
+
+#[test]
+fn retry_on_matches_error_classes() {
+    let not_found = NodeError::http(404, "HTTP GET /x returned status 404");
+    let unavailable = NodeError::http(503, "HTTP GET /x returned status 503");
+    let timeout = NodeError::new(NodeErrorKind::Timeout, "node timed out after 2s");
+    let refused = NodeError::new(NodeErrorKind::Io, "Connection refused");
+
+    assert!(not_found.matches_class("4xx") && not_found.matches_class("404"));
+    assert!(!not_found.matches_class("5xx") && !not_found.matches_class("timeout"));
+    assert!(unavailable.matches_class("5XX") && unavailable.matches_class("http"));
+    assert!(timeout.matches_class("timeout") && timeout.matches_class("retriable"));
+    assert!(refused.matches_class("connection") && refused.matches_class("io"));
+    // Unknown classes match against the message.
+    assert!(refused.matches_class("refused"));
+    assert!(!unavailable.matches_class("refused"));
+
+    let mut retry = RetryConfig::default();
+    assert!(retry.should_retry(&not_found));
+    retry.retry_on = vec!["timeout".into(), "5xx".into()];
+    assert!(retry.should_retry(&unavailable) && retry.should_retry(&timeout));
+    assert!(!retry.should_retry(&not_found) && !retry.should_retry(&refused));
+}
+
 // --- TaskState ---
 
 #[test]
@@ -45,6 +148,11 @@ fn task_status_display() {
     assert_eq!(TaskStatus::Success.to_string(), "success");
     assert_eq!(TaskStatus::Failed.to_string(), "failed");
     assert_eq!(TaskStatus::Skipped.to_string(), "skipped");
+    assert_eq!(TaskStatus::TimedOut.to_string(), "timed_out");
+    assert_eq!(
+        serde_json::to_string(&TaskStatus::TimedOut).unwrap(),
+        r#""timed_out""#
+    );
 }
 
 // --- RunStatus serialization ---
@@ -68,6 +176,7 @@ fn make_step(name: &str, deps: Vec<&str>) -> StepDefinition {
         retry: RetryConfig::default(),
         timeout_s: None,
         route: None,
+        route_from: None,
         on_error: None,
     }
 }
@@ -77,6 +186,11 @@ fn validate_dag_empty_flow() {
     let flow = FlowDefinition {
         name: "empty".to_string(),
         steps: vec![],
+        vars: Default::default(),
+        environments: Default::default(),
+        dedupe: None,
+        timeout_s: None,
+        hooks: Default::default(),
     };
     assert!(flow.validate_dag().is_empty());
 }
@@ -86,6 +200,11 @@ fn validate_dag_no_deps() {
     let flow = FlowDefinition {
         name: "parallel".to_string(),
         steps: vec![make_step("a", vec![]), make_step("b", vec![])],
+        vars: Default::default(),
+        environments: Default::default(),
+        dedupe: None,
+        timeout_s: None,
+        hooks: Default::default(),
     };
     assert!(flow.validate_dag().is_empty());
 }
@@ -99,6 +218,11 @@ fn validate_dag_linear_chain() {
             make_step("b", vec!["a"]),
             make_step("c", vec!["b"]),
         ],
+        vars: Default::default(),
+        environments: Default::default(),
+        dedupe: None,
+        timeout_s: None,
+        hooks: Default::default(),
     };
     assert!(flow.validate_dag().is_empty());
 }
@@ -108,6 +232,11 @@ fn validate_dag_missing_dependency() {
     let flow = FlowDefinition {
         name: "broken".to_string(),
         steps: vec![make_step("a", vec!["nonexistent"])],
+        vars: Default::default(),
+        environments: Default::default(),
+        dedupe: None,
+        timeout_s: None,
+        hooks: Default::default(),
     };
     let errors = flow.validate_dag();
     assert_eq!(errors.len(), 1);
@@ -120,6 +249,11 @@ fn validate_dag_simple_cycle() {
     let flow = FlowDefinition {
         name: "cycle".to_string(),
         steps: vec![make_step("a", vec!["b"]), make_step("b", vec!["a"])],
+        vars: Default::default(),
+        environments: Default::default(),
+        dedupe: None,
+        timeout_s: None,
+        hooks: Default::default(),
     };
     let errors = flow.validate_dag();
     assert_eq!(errors.len(), 1);
@@ -135,6 +269,11 @@ fn validate_dag_three_step_cycle() {
             make_step("b", vec!["a"]),
             make_step("c", vec!["b"]),
         ],
+        vars: Default::default(),
+        environments: Default::default(),
+        dedupe: None,
+        timeout_s: None,
+        hooks: Default::default(),
     };
     let errors = flow.validate_dag();
     assert!(!errors.is_empty());
@@ -152,6 +291,43 @@ fn validate_dag_diamond() {
             make_step("c", vec!["a"]),
             make_step("d", vec!["b", "c"]),
         ],
+        vars: Default::default(),
+        environments: Default::default(),
+        dedupe: None,
+        timeout_s: None,
+        hooks: Default::default(),
     };
     assert!(flow.validate_dag().is_empty());
 }
+
+#[test]
+fn dedupe_hash_ignores_key_order_and_injected_keys() {
+    let whole = DedupeConfig {
+        keys: vec![],
+        ttl_s: DedupeConfig::DEFAULT_TTL_S,
+    };
+    let a: Context = serde_json::from_str(
+        r#"{"order": {"id": 1, "sku": "x"}, "qty": 2, "_headers": {"x-delivery": "d1"}}"#,
+    )
+    .unwrap();
+    let b: Context = serde_json::from_str(
+        r#"{"qty": 2, "order": {"sku": "x", "id": 1}, "_headers": {"x-delivery": "d2"}}"#,
+    )
+    .unwrap();
+    assert_eq!(whole.hash("f", &a), whole.hash("f", &b));
+    assert_ne!(whole.hash("f", &a), whole.hash("g", &a));
+
+    let by_delivery = DedupeConfig {
+        keys: vec!["_headers.x-delivery".to_string()],
+        ttl_s: DedupeConfig::DEFAULT_TTL_S,
+    };
+    assert_ne!(by_delivery.hash("f", &a), by_delivery.hash("f", &b));
+
+    let by_order = DedupeConfig {
+        keys: vec!["order.id".to_string()],
+        ttl_s: DedupeConfig::DEFAULT_TTL_S,
+    };
+    let mut c = b.clone();
+    c.insert("qty".to_string(), serde_json::json!(5));
+    assert_eq!(by_order.hash("f", &a), by_order.hash("f", &c));
+}
@@ -0,0 +1,155 @@
+#![cfg(feature = "wasm")]
+
+//! Tests for the wasm node.
+
+use std::collections::HashMap;
+
+use base64::Engine;
+use ironflow::engine::types::Context;
+use ironflow::nodes::NodeRegistry;
+
+/// WASI command that reads all of stdin and writes `{"echo":<stdin>}` back.
+const ECHO_WAT: &str = r#"
+(module
+  (import "wasi_snapshot_preview1" "fd_read"
+    (func $fd_read (param i32 i32 i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "fd_write"
+    (func $fd_write (param i32 i32 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "{\"echo\":")
+  (data (i32.const 8) "}")
+
+  (func $write (param $ptr i32) (param $len i32)
+    (i32.store (i32.const 16) (local.get $ptr))
+    (i32.store (i32.const 20) (local.get $len))
+    (drop (call $fd_write (i32.const 1) (i32.const 16) (i32.const 1) (i32.const 24))))
+
+  (func (export "_start")
+    (local $total i32)
+    (local $n i32)
+    (block $done
+      (loop $read
+        (i32.store (i32.const 16) (i32.add (i32.const 1024) (local.get $total)))
+        (i32.store (i32.const 20) (i32.sub (i32.const 60000) (local.get $total)))
+        (drop (call $fd_read (i32.const 0) (i32.const 16) (i32.const 1) (i32.const 24)))
+        (local.set $n (i32.load (i32.const 24)))
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $total (i32.add (local.get $total) (local.get $n)))
+        (br $read)))
+    (call $write (i32.const 0) (i32.const 8))
+    (call $write (i32.const 1024) (local.get $total))
+    (call $write (i32.const 8) (i32.const 1))))
+"#;
+
+/// WASI command that never returns.
+const SPIN_WAT: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (func (export "_start")
+    (loop $forever (br $forever))))
+"#;
+
+fn b64(source: &str) -> String {
+    base64::engine::general_purpose::STANDARD.encode(source)
+}
+
+#[tokio::test]
+async fn wasm_echoes_input_as_json() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("wasm").unwrap();
+
+    let config = serde_json::json!({
+        "module_b64": b64(ECHO_WAT),
+        "input": { "name": "ironflow", "n": 3 }
+    });
+
+    let result = node.execute(&config, &HashMap::new()).await.unwrap();
+    assert_eq!(
+        result.get("echo").unwrap(),
+        &serde_json::json!({ "name": "ironflow", "n": 3 })
+    );
+}
+
+#[tokio::test]
+async fn wasm_defaults_to_whole_context_from_path() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("echo.wat"), ECHO_WAT).unwrap();
+
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("wasm").unwrap();
+
+    let ctx: Context = HashMap::from([
+        ("user".to_string(), serde_json::json!("alice")),
+        (
+            "module_dir".to_string(),
+            serde_json::json!(dir.path().to_str().unwrap()),
+        ),
+    ]);
+    let config = serde_json::json!({ "path": "${ctx.module_dir}/echo.wat" });
+
+    let result = node.execute(&config, &ctx).await.unwrap();
+    assert_eq!(result["echo"]["user"], "alice");
+}
+
+#[tokio::test]
+async fn wasm_fuel_exhaustion_returns_error() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("wasm").unwrap();
+
+    let config = serde_json::json!({
+        "module_b64": b64(SPIN_WAT),
+        "fuel": 10_000
+    });
+
+    let err = node
+        .execute(&config, &HashMap::new())
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("execution failed"), "unexpected error: {err}");
+}
+
+#[tokio::test]
+async fn wasm_timeout_interrupts_guest() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("wasm").unwrap();
+
+    let config = serde_json::json!({
+        "module_b64": b64(SPIN_WAT),
+        "fuel": u64::MAX,
+        "timeout": 0.2
+    });
+
+    let err = node
+        .execute(&config, &HashMap::new())
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("timed out"), "unexpected error: {err}");
+}
+
+#[tokio::test]
+async fn wasm_rejects_non_positive_timeout() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("wasm").unwrap();
+
+    for timeout in [
+        serde_json::json!(-1),
+        serde_json::json!(0),
+        serde_json::json!("5"),
+    ] {
+        let config = serde_json::json!({
+            "module_b64": b64(SPIN_WAT),
+            "timeout": timeout
+        });
+        let err = node
+            .execute(&config, &HashMap::new())
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("'timeout' must be a positive number"),
+            "unexpected error: {err}"
+        );
+    }
+}
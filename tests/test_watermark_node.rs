@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use ironflow::nodes::NodeRegistry;
+use lopdf::{Document, Object, Stream, dictionary};
+use serde_json::json;
+
+fn create_test_image(path: &std::path::Path, width: u32, height: u32) {
+    let img = image::RgbImage::from_fn(width, height, |x, y| {
+        image::Rgb([(x % 256) as u8, (y % 256) as u8, 128])
+    });
+    img.save(path).unwrap();
+}
+
+fn create_multi_page_pdf(path: &std::path::Path, num_pages: u32) {
+    let mut doc = Document::new();
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+    });
+    let resources_id = doc.add_object(dictionary! {
+        "Font" => dictionary! {
+            "F1" => font_id,
+        },
+    });
+
+    let mut kids = Vec::new();
+    for i in 0..num_pages {
+        let content = format!("BT /F1 12 Tf 100 700 Td (Page {}) Tj ET", i + 1);
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content.into_bytes()));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+        });
+        kids.push(Object::Reference(page_id));
+    }
+
+    // Resources and MediaBox are inherited from the page tree.
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => num_pages,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.save(path).unwrap();
+}
+
+#[tokio::test]
+async fn watermark_text_on_image_keeps_dimensions() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("input.png");
+    let output = dir.path().join("stamped.png");
+    create_test_image(&input, 240, 160);
+
+    let registry = NodeRegistry::with_builtins();
+    let node = registry.get("watermark").expect("watermark not registered");
+    let config = json!({
+        "path": input.to_str().unwrap(),
+        "output_path": output.to_str().unwrap(),
+        "text": "DRAFT",
+        "opacity": 0.8,
+        "rotation": 30,
+        "color": "#ff0000",
+    });
+
+    let result = node.execute(&config, &HashMap::new()).await.unwrap();
+    assert_eq!(result.get("watermark_success"), Some(&json!(true)));
+
+    let before = image::open(&input).unwrap().to_rgb8();
+    let after = image::open(&output).unwrap().to_rgb8();
+    assert_eq!(after.dimensions(), before.dimensions());
+    assert_ne!(after.as_raw(), before.as_raw());
+    // Corners stay untouched by a centered stamp.
+    assert_eq!(after.get_pixel(0, 0), before.get_pixel(0, 0));
+}
+
+#[tokio::test]
+async fn watermark_image_overlay_at_corner() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("input.png");
+    let logo = dir.path().join("logo.png");
+    let output = dir.path().join("stamped.png");
+    create_test_image(&input, 200, 200);
+    image::RgbaImage::from_pixel(10, 10, image::Rgba([255, 255, 255, 255]))
+        .save(&logo)
+        .unwrap();
+
+    let registry = NodeRegistry::with_builtins();
+    let node = registry.get("watermark").unwrap();
+    let config = json!({
+        "path": input.to_str().unwrap(),
+        "output_path": output.to_str().unwrap(),
+        "image_path": logo.to_str().unwrap(),
+        "width": 40,
+        "position": "bottom-right",
+        "opacity": 1.0,
+    });
+    node.execute(&config, &HashMap::new()).await.unwrap();
+
+    let after = image::open(&output).unwrap().to_rgb8();
+    assert_eq!(after.dimensions(), (200, 200));
+    assert_eq!(after.get_pixel(180, 180), &image::Rgb([255, 255, 255]));
+    assert_ne!(after.get_pixel(20, 20), &image::Rgb([255, 255, 255]));
+}
+
+#[tokio::test]
+async fn watermark_stamps_every_pdf_page() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("input.pdf");
+    let output = dir.path().join("stamped.pdf");
+    create_multi_page_pdf(&input, 3);
+
+    let registry = NodeRegistry::with_builtins();
+    let node = registry.get("watermark").unwrap();
+    let config = json!({
+        "path": input.to_str().unwrap(),
+        "output_path": output.to_str().unwrap(),
+        "text": "CONFIDENTIAL",
+        "rotation": 45,
+        "font_size": 48,
+        "output_key": "stamp",
+    });
+
+    let result = node.execute(&config, &HashMap::new()).await.unwrap();
+    assert_eq!(result.get("stamp_pages"), Some(&json!(3)));
+
+    let doc = Document::load(&output).unwrap();
+    let pages = doc.get_pages();
+    assert_eq!(pages.len(), 3);
+    for page_id in pages.values() {
+        let page = doc.get_dictionary(*page_id).unwrap();
+        let resources = page.get(b"Resources").unwrap().as_dict().unwrap();
+        let xobjects = resources.get(b"XObject").unwrap().as_dict().unwrap();
+        assert!(xobjects.has(b"IronflowWatermark"));
+        // The original font resources are kept alongside the stamp.
+        assert!(
+            resources
+                .get(b"Font")
+                .unwrap()
+                .as_dict()
+                .unwrap()
+                .has(b"F1")
+        );
+
+        let content = String::from_utf8(doc.get_page_content(*page_id).unwrap()).unwrap();
+        assert!(content.contains("(Page "));
+        assert!(content.contains("/IronflowWatermark Do"));
+    }
+}
+
+#[tokio::test]
+async fn watermark_requires_text_or_image() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("input.png");
+    create_test_image(&input, 20, 20);
+
+    let registry = NodeRegistry::with_builtins();
+    let node = registry.get("watermark").unwrap();
+    let config = json!({
+        "path": input.to_str().unwrap(),
+        "output_path": dir.path().join("out.png").to_str().unwrap(),
+    });
+
+    let err = node.execute(&config, &HashMap::new()).await.unwrap_err();
+    assert!(err.to_string().contains("'text' or 'image_path'"));
+}
@@ -11,12 +11,18 @@ use axum::routing::post;
 use http_body_util::BodyExt;
 use tower::ServiceExt;
 
+use ironflow::api::webhooks::{
+    RouteSelector, SignedFlow, WebhookRouting, WebhookSignature, WebhookTarget,
+};
 use ironflow::nodes::NodeRegistry;
 use ironflow::storage::event_store::MemoryEventStore;
 use ironflow::storage::json_store::JsonStateStore;
 
 /// Build a test router with the webhook route wired up, mirroring src/api/mod.rs.
-fn build_test_app(flows_dir: std::path::PathBuf, webhooks: HashMap<String, String>) -> Router {
+fn build_test_app<T: Into<WebhookTarget>>(
+    flows_dir: std::path::PathBuf,
+    webhooks: HashMap<String, T>,
+) -> Router {
     let registry = Arc::new(NodeRegistry::with_builtins());
     let store = Arc::new(JsonStateStore::new(tempfile::tempdir().unwrap().keep()));
 
@@ -26,7 +32,10 @@ fn build_test_app(flows_dir: std::path::PathBuf, webhooks: HashMap<String, Strin
         event_store: Arc::new(MemoryEventStore::new()),
         flows_dir: Some(flows_dir),
         max_concurrent_tasks: None,
-        webhooks,
+        concurrency_limits: Default::default(),
+        webhooks: webhooks.into_iter().map(|(k, v)| (k, v.into())).collect(),
+        started_at: std::time::Instant::now(),
+        active_runs: ironflow::api::shutdown::ActiveRuns::new(),
     });
 
     Router::new()
@@ -85,7 +94,7 @@ async fn webhook_executes_flow_and_returns_run_id() {
 async fn webhook_unknown_name_returns_404() {
     let (_dir, dir_path) = setup_flow_dir();
 
-    let app = build_test_app(dir_path, HashMap::new());
+    let app = build_test_app(dir_path, HashMap::<String, String>::new());
 
     let req = Request::builder()
         .method("POST")
@@ -154,6 +163,36 @@ async fn webhook_works_with_no_body() {
     assert_eq!(resp.status(), StatusCode::OK);
 }
 
+#[tokio::test]
+async fn webhook_rejects_bodies_that_are_not_json() {
+    let (_dir, dir_path) = setup_flow_dir();
+    let webhooks = HashMap::from([("hello".to_string(), "hello_world.lua".to_string())]);
+
+    let post = |content_type: &'static str, body: &'static str| {
+        let app = build_test_app(dir_path.clone(), webhooks.clone());
+        async move {
+            let req = Request::builder()
+                .method("POST")
+                .uri("/webhooks/hello")
+                .header("content-type", content_type)
+                .body(Body::from(body))
+                .unwrap();
+            app.oneshot(req).await.unwrap().status()
+        }
+    };
+
+    assert_eq!(
+        post("text/plain", "name=ada").await,
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    );
+    assert_eq!(
+        post("application/json", "{not json").await,
+        StatusCode::BAD_REQUEST
+    );
+    // An empty body still runs the flow with an empty context.
+    assert_eq!(post("text/plain", "").await, StatusCode::OK);
+}
+
 #[tokio::test]
 async fn webhook_injects_headers_into_context() {
     let dir = tempfile::tempdir().unwrap();
@@ -221,3 +260,306 @@ async fn webhook_injects_webhook_name_into_context() {
     let resp = app.oneshot(req).await.unwrap();
     assert_eq!(resp.status(), StatusCode::OK);
 }
+
+fn write_flow(dir: &std::path::Path, file: &str, flow_name: &str) {
+    std::fs::write(
+        dir.join(file),
+        format!(
+            r#"
+        local flow = Flow.new("{flow_name}")
+        flow:step("noop", nodes.log({{ message = "{flow_name}" }}))
+        return flow
+    "#
+        ),
+    )
+    .unwrap();
+}
+
+async fn post_webhook(
+    app: Router,
+    uri: &str,
+    event: Option<&str>,
+    body: &str,
+) -> (StatusCode, serde_json::Value) {
+    let mut req = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/json");
+    if let Some(event) = event {
+        req = req.header("X-GitHub-Event", event);
+    }
+    let resp = app
+        .oneshot(req.body(Body::from(body.to_string())).unwrap())
+        .await
+        .unwrap();
+    let status = resp.status();
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+fn routed_webhooks(
+    route_by: RouteSelector,
+    default: Option<&str>,
+) -> HashMap<String, WebhookTarget> {
+    let routing = WebhookRouting {
+        route_by,
+        routes: HashMap::from([
+            ("push".to_string(), "push.lua".to_string()),
+            ("pull_request".to_string(), "pr.lua".to_string()),
+        ]),
+        default: default.map(str::to_string),
+        signature: None,
+    };
+    HashMap::from([("github".to_string(), WebhookTarget::Routed(routing))])
+}
+
+#[tokio::test]
+async fn routed_webhook_dispatches_on_event_header() {
+    let dir = tempfile::tempdir().unwrap();
+    write_flow(dir.path(), "push.lua", "on_push");
+    write_flow(dir.path(), "pr.lua", "on_pull_request");
+    let webhooks = routed_webhooks(RouteSelector::Header("X-GitHub-Event".to_string()), None);
+
+    let (status, json) = post_webhook(
+        build_test_app(dir.path().to_path_buf(), webhooks.clone()),
+        "/webhooks/github",
+        Some("push"),
+        "{}",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["flow_name"], "on_push");
+
+    let (status, json) = post_webhook(
+        build_test_app(dir.path().to_path_buf(), webhooks.clone()),
+        "/webhooks/github",
+        Some("pull_request"),
+        "{}",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["flow_name"], "on_pull_request");
+
+    let (status, json) = post_webhook(
+        build_test_app(dir.path().to_path_buf(), webhooks),
+        "/webhooks/github",
+        Some("issues"),
+        "{}",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        json["error"]
+            .as_str()
+            .unwrap()
+            .contains("No route for header"),
+        "{json}"
+    );
+}
+
+#[tokio::test]
+async fn routed_webhook_uses_json_path_and_default() {
+    let dir = tempfile::tempdir().unwrap();
+    write_flow(dir.path(), "push.lua", "on_push");
+    write_flow(dir.path(), "pr.lua", "on_pull_request");
+    write_flow(dir.path(), "other.lua", "on_other");
+    let webhooks = routed_webhooks(
+        RouteSelector::JsonPath("$.event.kind".to_string()),
+        Some("other.lua"),
+    );
+
+    let (status, json) = post_webhook(
+        build_test_app(dir.path().to_path_buf(), webhooks.clone()),
+        "/webhooks/github",
+        None,
+        r#"{"event": {"kind": "pull_request"}}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["flow_name"], "on_pull_request");
+
+    let (status, json) = post_webhook(
+        build_test_app(dir.path().to_path_buf(), webhooks),
+        "/webhooks/github",
+        None,
+        r#"{"event": {"kind": "release"}}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["flow_name"], "on_other");
+}
+
+#[tokio::test]
+async fn dedupe_flow_runs_identical_payloads_once() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("dedupe.lua"),
+        r#"
+        local flow = Flow.new("dedupe_hook")
+        flow:dedupe({ keys = { "order.id" }, ttl_s = 60 })
+        flow:step("noop", nodes.log({ message = "order ${ctx.order.id}" }))
+        return flow
+    "#,
+    )
+    .unwrap();
+    let app = build_test_app(
+        dir.path().to_path_buf(),
+        HashMap::from([("orders".to_string(), "dedupe.lua".to_string())]),
+    );
+
+    let (status, first) = post_webhook(
+        app.clone(),
+        "/webhooks/orders",
+        None,
+        r#"{"order": {"id": 7, "note": "a"}}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(first["status"], "success");
+
+    // Same order id, different unrelated field and delivery header.
+    let (status, second) = post_webhook(
+        app.clone(),
+        "/webhooks/orders",
+        Some("redelivery"),
+        r#"{"order": {"id": 7, "note": "b"}}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(second["run_id"], first["run_id"]);
+    assert_eq!(second["status"], "success");
+
+    let (status, third) =
+        post_webhook(app, "/webhooks/orders", None, r#"{"order": {"id": 8}}"#).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(third["run_id"], first["run_id"]);
+}
+
+fn signed_webhooks(secret: &str) -> HashMap<String, WebhookTarget> {
+    let signature: WebhookSignature =
+        serde_json::from_value(serde_json::json!({ "secret": secret })).unwrap();
+    let signed = SignedFlow {
+        flow: "signed.lua".to_string(),
+        signature,
+    };
+    HashMap::from([("signed".to_string(), WebhookTarget::Signed(signed))])
+}
+
+fn sign(secret: &str, body: &str) -> String {
+    use hmac::{Hmac, KeyInit, Mac};
+    let mut mac = <Hmac<sha2::Sha256> as KeyInit>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+async fn post_signed(
+    app: Router,
+    body: &str,
+    signature: Option<&str>,
+) -> (StatusCode, serde_json::Value) {
+    let mut req = Request::builder()
+        .method("POST")
+        .uri("/webhooks/signed")
+        .header("content-type", "application/json");
+    if let Some(signature) = signature {
+        req = req.header("X-Hub-Signature-256", signature);
+    }
+    let resp = app
+        .oneshot(req.body(Body::from(body.to_string())).unwrap())
+        .await
+        .unwrap();
+    let status = resp.status();
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn routed_webhook_verifies_signature() {
+    let dir = tempfile::tempdir().unwrap();
+    write_flow(dir.path(), "push.lua", "on_push");
+    let mut webhooks = routed_webhooks(RouteSelector::Header("X-GitHub-Event".to_string()), None);
+    if let Some(WebhookTarget::Routed(routing)) = webhooks.get_mut("github") {
+        routing.signature =
+            Some(serde_json::from_value(serde_json::json!({ "secret": "s3cret" })).unwrap());
+    }
+    let body = r#"{"ref":"main"}"#;
+
+    let send = |signature: String| {
+        let app = build_test_app(dir.path().to_path_buf(), webhooks.clone());
+        async move {
+            let req = Request::builder()
+                .method("POST")
+                .uri("/webhooks/github")
+                .header("content-type", "application/json")
+                .header("X-GitHub-Event", "push")
+                .header("X-Hub-Signature-256", signature)
+                .body(Body::from(body))
+                .unwrap();
+            app.oneshot(req).await.unwrap().status()
+        }
+    };
+
+    assert_eq!(send(sign("s3cret", body)).await, StatusCode::OK);
+    assert_eq!(send(sign("wrong", body)).await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn signed_webhook_accepts_valid_signature() {
+    let dir = tempfile::tempdir().unwrap();
+    write_flow(dir.path(), "signed.lua", "on_signed");
+    let body = r#"{"action":"opened"}"#;
+
+    let (status, json) = post_signed(
+        build_test_app(dir.path().to_path_buf(), signed_webhooks("s3cret")),
+        body,
+        Some(&sign("s3cret", body)),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["flow_name"], "on_signed");
+}
+
+#[tokio::test]
+async fn signed_webhook_rejects_bad_or_missing_signature() {
+    let dir = tempfile::tempdir().unwrap();
+    write_flow(dir.path(), "signed.lua", "on_signed");
+    let body = r#"{"action":"opened"}"#;
+    let webhooks = signed_webhooks("s3cret");
+
+    let (status, json) = post_signed(
+        build_test_app(dir.path().to_path_buf(), webhooks.clone()),
+        body,
+        Some(&sign("wrong", body)),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(
+        json["error"]
+            .as_str()
+            .unwrap()
+            .contains("signature mismatch")
+    );
+
+    // Signature over a different body than the one sent
+    let (status, _) = post_signed(
+        build_test_app(dir.path().to_path_buf(), webhooks.clone()),
+        body,
+        Some(&sign("s3cret", "{}")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, json) = post_signed(
+        build_test_app(dir.path().to_path_buf(), webhooks),
+        body,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(
+        json["error"]
+            .as_str()
+            .unwrap()
+            .contains("missing X-Hub-Signature-256 header")
+    );
+}
@@ -141,3 +141,61 @@ async fn xml_stringify_custom_root_tag() {
     assert!(xml.contains("<catalog>"));
     assert!(xml.contains("</catalog>"));
 }
+
+#[tokio::test]
+async fn xml_parse_and_stringify_round_trip() {
+    let reg = NodeRegistry::with_builtins();
+    let parse = reg.get("xml_parse").unwrap();
+    let stringify = reg.get("xml_stringify").unwrap();
+    let doc = r#"<catalog xmlns:dc="http://purl.org/dc/elements/1.1/"><book id="1"><dc:title>Rust &amp; You</dc:title><tag>a</tag><tag>b</tag></book><book id="2"><dc:title>Tokio</dc:title></book></catalog>"#;
+
+    let parsed = parse
+        .execute(&serde_json::json!({ "input": doc }), &empty_ctx())
+        .await
+        .unwrap();
+    let data = &parsed["xml_data"];
+    assert_eq!(
+        data["catalog"]["@xmlns:dc"],
+        "http://purl.org/dc/elements/1.1/"
+    );
+    assert_eq!(data["catalog"]["book"][0]["dc:title"], "Rust & You");
+    assert_eq!(
+        data["catalog"]["book"][0]["tag"],
+        serde_json::json!(["a", "b"])
+    );
+    assert_eq!(data["catalog"]["book"][1]["@id"], "2");
+
+    let ctx = ctx_with(vec![("doc", data.clone())]);
+    let xml = stringify
+        .execute(
+            &serde_json::json!({ "source_key": "doc", "unwrap_root": true }),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    let xml = xml["xml"].as_str().unwrap();
+    assert!(xml.contains("<dc:title>Rust &amp; You</dc:title>"), "{xml}");
+
+    let reparsed = parse
+        .execute(&serde_json::json!({ "input": xml }), &empty_ctx())
+        .await
+        .unwrap();
+    assert_eq!(&reparsed["xml_data"], data);
+}
+
+#[tokio::test]
+async fn xml_parse_strip_namespaces() {
+    let reg = NodeRegistry::with_builtins();
+    let node = reg.get("xml_parse").unwrap();
+    let config = serde_json::json!({
+        "input": r#"<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/"><soap:Body><m:Price xmlns:m="urn:prices" m:currency="EUR">9.5</m:Price></soap:Body></soap:Envelope>"#,
+        "strip_namespaces": true
+    });
+    let result = node.execute(&config, &empty_ctx()).await.unwrap();
+    assert_eq!(
+        result["xml_data"],
+        serde_json::json!({
+            "Envelope": { "Body": { "Price": { "@currency": "EUR", "#text": "9.5" } } }
+        })
+    );
+}